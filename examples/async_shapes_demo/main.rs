@@ -18,8 +18,10 @@ use rustdds::{
   with_key::Sample, DomainParticipantBuilder, Keyed, QosPolicyBuilder, StatusEvented,
   TopicDescription, TopicKind,
 };
-use rustdds::policy::{Deadline, Durability, History, Reliability}; /* import all QoS
-                                                                     * policies directly */
+// import all QoS policies directly
+use rustdds::policy::{
+  Deadline, Durability, History, Ownership, Partition, Reliability, TimeBasedFilter,
+};
 use serde::{Deserialize, Serialize};
 use clap::{Arg, ArgMatches, Command}; // command line argument processing
 use rand::prelude::*;
@@ -118,22 +120,26 @@ fn main() {
     qos_b = qos_b.deadline(dl);
   }
 
-  assert!(
-    !matches.contains_id("partition"),
-    "QoS policy Partition is not yet implemented."
-  );
-
-  assert!(
-    !matches.contains_id("interval"),
-    "QoS policy Time Based Filter is not yet implemented."
-  );
+  if let Some(interval) = matches.get_one::<f64>("interval") {
+    qos_b = qos_b.time_based_filter(TimeBasedFilter {
+      minimum_separation: rustdds::Duration::from_frac_seconds(*interval),
+    });
+  }
 
-  assert!(
-    !matches.contains_id("ownership_strength"),
-    "QoS policy Ownership Strength is not yet implemented."
-  );
+  if let Some(strength) = matches.get_one::<i32>("ownership_strength") {
+    qos_b = qos_b.ownership(if *strength < 0 {
+      Ownership::Shared
+    } else {
+      Ownership::Exclusive {
+        strength: *strength,
+      }
+    });
+  }
 
-  let qos = qos_b.build();
+  let mut qos = qos_b.build();
+  if let Some(partition) = matches.get_one::<String>("partition") {
+    qos = qos.with_partition(Partition::new([partition]));
+  }
 
   let write_interval: Duration = match deadline_policy {
     None => Duration::from_millis(200), // This is the default rate
@@ -432,6 +438,8 @@ fn get_matches() -> ArgMatches {
         .help("Set ownership strength [-1: SHARED]")
         .short('s')
         .value_parser(clap::value_parser!(i32))
+        .allow_negative_numbers(true)
+        .value_parser(clap::value_parser!(i32))
         .value_name("strength"),
     )
     .arg(
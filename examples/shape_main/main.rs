@@ -28,12 +28,14 @@ use rustdds::{
     statusevents::{DataReaderStatus, DataWriterStatus},
   },
   policy::{
-    DataRepresentation, Deadline, Durability, History, Lifespan, Ownership, Reliability,
-    TimeBasedFilter, XCDR2_DATA_REPRESENTATION, XCDR_DATA_REPRESENTATION,
+    DataRepresentation, Deadline, Durability, History, Lifespan, Ownership, Partition,
+    Presentation, PresentationAccessScope, Reliability, TimeBasedFilter, XCDR2_DATA_REPRESENTATION,
+    XCDR_DATA_REPRESENTATION,
   },
-  with_key::Sample,
-  DomainParticipantBuilder, Duration, Keyed, QosPolicyBuilder, StatusEvented, TopicDescription,
-  TopicKind,
+  serialization::xcdr2,
+  with_key::{DataWriter, Sample, SerializerAdapter},
+  CDR2SerializerAdapter, CDRDeserializerAdapter, DomainParticipantBuilder, Duration, Keyed,
+  QosPolicyBuilder, StatusEvented, TopicDescription, TopicKind,
 };
 use serde::{Deserialize, Serialize};
 use smol::Timer;
@@ -123,7 +125,7 @@ struct Args {
   #[arg(short = 'c')]
   color: Option<String>,
 
-  /// partition (unsupported by RustDDS)
+  /// partition
   #[arg(short = 'p')]
   partition: Option<String>,
 
@@ -135,7 +137,7 @@ struct Args {
   #[arg(long = "no-same-host-loopback", default_value_t = false)]
   no_same_host_loopback: bool,
 
-  /// data representation (1: XCDR, 2: XCDR2). RustDDS only supports XCDR1.
+  /// data representation (1: XCDR, 2: XCDR2)
   #[arg(short = 'x')]
   data_representation: Option<String>,
 
@@ -187,19 +189,19 @@ struct Args {
   #[arg(short = 'M', long = "final-instance-state")]
   final_instance_state: Option<char>,
 
-  /// presentation access scope (unsupported)
+  /// presentation access scope (i: INSTANCE, t: TOPIC, g: GROUP)
   #[arg(short = 'C', long = "access-scope")]
   access_scope: Option<char>,
 
-  /// coherent access (unsupported)
+  /// coherent access
   #[arg(short = 'T', long = "coherent", default_value_t = false)]
   coherent: bool,
 
-  /// ordered access (unsupported)
+  /// ordered access
   #[arg(short = 'O', long = "ordered", default_value_t = false)]
   ordered: bool,
 
-  /// coherent set sample count (unsupported)
+  /// number of samples (per instance) in each coherent set
   #[arg(short = 'H', long = "coherent-sample-count")]
   coherent_sample_count: Option<i32>,
 
@@ -211,7 +213,7 @@ struct Args {
   #[arg(short = 'K', long = "take-read", default_value_t = false)]
   take_read: bool,
 
-  /// content filter expression (unsupported by RustDDS)
+  /// content filter expression, e.g. `x < 100 and color = 'BLUE'`
   #[arg(short = 'F', long = "cft")]
   cft: Option<String>,
 
@@ -228,6 +230,68 @@ struct Args {
   datafrag_size: Option<u64>,
 }
 
+/// The DataWriter of the test, typed by the data representation it writes.
+enum ShapeWriter {
+  Xcdr1(DataWriter<ShapeType>),
+  Xcdr2(DataWriter<ShapeType, CDR2SerializerAdapter<ShapeType, xcdr2::Appendable>>),
+}
+
+/// A content filter expression of the form `<field> <op> <value>`, possibly
+/// several joined by `AND`, as used by the harness (e.g. `x < 100` or
+/// `color = 'BLUE'`). The fields are `color`, `x`, `y` and `shapesize`.
+struct ContentFilter {
+  terms: Vec<(String, String, String)>,
+}
+
+impl ContentFilter {
+  const OPERATORS: [&'static str; 7] = ["<=", ">=", "<>", "!=", "<", ">", "="];
+
+  fn parse(expression: &str) -> Option<Self> {
+    let mut terms = Vec::new();
+    for term in expression
+      .split(" and ")
+      .flat_map(|t| t.split(" AND "))
+      .map(str::trim)
+    {
+      let (field, op, value) = Self::OPERATORS.iter().find_map(|op| {
+        term
+          .split_once(op)
+          .map(|(field, value)| (field.trim(), *op, value.trim()))
+      })?;
+      match field {
+        "color" => {
+          value.strip_prefix('\'')?.strip_suffix('\'')?;
+        }
+        "x" | "y" | "shapesize" => {
+          value.parse::<i32>().ok()?;
+        }
+        _ => return None,
+      }
+      terms.push((field.to_owned(), op.to_owned(), value.to_owned()));
+    }
+    Some(Self { terms })
+  }
+
+  fn matches(&self, shape: &ShapeType) -> bool {
+    self.terms.iter().all(|(field, op, value)| {
+      let ordering = match field.as_str() {
+        "color" => shape.color.as_str().cmp(value.trim_matches('\'')),
+        "x" => shape.x.cmp(&value.parse().unwrap_or_default()),
+        "y" => shape.y.cmp(&value.parse().unwrap_or_default()),
+        _ => shape.shapesize.cmp(&value.parse().unwrap_or_default()),
+      };
+      match op.as_str() {
+        "<=" => ordering.is_le(),
+        ">=" => ordering.is_ge(),
+        "<" => ordering.is_lt(),
+        ">" => ordering.is_gt(),
+        "=" => ordering.is_eq(),
+        _ => ordering.is_ne(),
+      }
+    })
+  }
+}
+
 /// Print a message containing "not supported" (which the harness recognizes as
 /// an unsupported feature) and exit cleanly.
 fn unsupported(feature: &str) -> ! {
@@ -248,26 +312,12 @@ fn main() {
     std::process::exit(1);
   }
 
-  // Features RustDDS 0.12 does not provide. Report them as unsupported instead
-  // of failing in a way the harness cannot classify.
-  if args.partition.is_some() {
-    unsupported("PARTITION QoS");
-  }
-  if matches!(args.data_representation.as_deref(), Some("2")) {
-    unsupported("XCDR2 data representation");
-  }
-  if args.cft.is_some() {
-    unsupported("ContentFilteredTopic");
-  }
-  if args.coherent || args.ordered || args.access_scope.is_some() {
-    unsupported("PRESENTATION coherent/ordered access");
-  }
-  // RustDDS 0.12 accepts the TIME_BASED_FILTER QoS but its reader does not
-  // enforce `minimum_separation` (every sample is delivered), so report it as
-  // unsupported rather than silently delivering unfiltered data.
-  if args.time_filter_ms.is_some() {
-    unsupported("TIME_BASED_FILTER QoS");
-  }
+  // Parse the filter before creating any entities, so that an expression we
+  // cannot evaluate is reported as unsupported instead of failing later.
+  let content_filter = args.cft.as_deref().map(|expression| {
+    ContentFilter::parse(expression)
+      .unwrap_or_else(|| unsupported(&format!("content filter expression {expression:?}")))
+  });
 
   let topic_name = args.topic.clone();
   let color = args.color.clone().unwrap_or_else(|| "BLUE".to_owned());
@@ -319,9 +369,23 @@ fn main() {
 
   let writer_opt = if args.publish {
     let publisher = domain_participant.create_publisher(&qos).unwrap();
-    let writer = publisher
-      .create_datawriter_cdr::<ShapeType>(&topic, None)
-      .unwrap();
+    // The DataWriter encodes in the first representation of the QoS list, so
+    // `-x 2` needs the XCDR2 serializer. ShapeType is @appendable.
+    let writer = if matches!(args.data_representation.as_deref(), Some("2")) {
+      ShapeWriter::Xcdr2(
+        publisher
+          .create_datawriter::<ShapeType, CDR2SerializerAdapter<ShapeType, xcdr2::Appendable>>(
+            &topic, None,
+          )
+          .unwrap(),
+      )
+    } else {
+      ShapeWriter::Xcdr1(
+        publisher
+          .create_datawriter_cdr::<ShapeType>(&topic, None)
+          .unwrap(),
+      )
+    };
     println!("Create writer for topic: {topic_name} color: {color}");
     Some(writer)
   } else {
@@ -330,17 +394,25 @@ fn main() {
 
   let reader_opt = if args.subscribe {
     let subscriber = domain_participant.create_subscriber(&qos).unwrap();
-    let reader = subscriber
-      .create_datareader_cdr::<ShapeType>(&topic, Some(qos.clone()))
-      .unwrap();
+    // CDRDeserializerAdapter decodes both XCDR1 and XCDR2.
+    let reader = match content_filter {
+      Some(filter) => subscriber
+        .create_content_filtered_datareader::<ShapeType, CDRDeserializerAdapter<ShapeType>, _>(
+          &topic,
+          Some(qos.clone()),
+          move |shape| filter.matches(shape),
+        )
+        .unwrap(),
+      None => subscriber
+        .create_datareader_cdr::<ShapeType>(&topic, Some(qos.clone()))
+        .unwrap(),
+    };
     println!("Create reader for topic: {topic_name}");
     Some(reader)
   } else {
     None
   };
 
-  let write_interval = write_interval(&args);
-
   let read_loop = async {
     let Some(datareader) = reader_opt else {
       return;
@@ -366,114 +438,156 @@ fn main() {
   };
 
   let write_loop = async {
-    let Some(datawriter) = writer_opt else {
-      return;
-    };
-    let mut run = true;
-    let stop = stop_receiver.recv().fuse();
-    pin_mut!(stop);
-    let mut status_stream = datawriter.as_async_status_stream();
-    let mut ticks = StreamExt::fuse(Timer::interval(write_interval));
+    match writer_opt {
+      Some(ShapeWriter::Xcdr1(datawriter)) => {
+        write_shapes(datawriter, &args, &topic_name, &color, &stop_receiver).await;
+      }
+      Some(ShapeWriter::Xcdr2(datawriter)) => {
+        write_shapes(datawriter, &args, &topic_name, &color, &stop_receiver).await;
+      }
+      None => {}
+    }
+  };
 
-    let num_instances = args.num_instances.unwrap_or(1).max(1);
-    let payload = match args.additional_payload_size {
-      Some(n) if n > 0 => vec![0xffu8; n as usize],
-      _ => Vec::new(),
-    };
-    let mut shapes: Vec<ShapeType> = (0..num_instances)
-      .map(|i| ShapeType {
-        color: instance_color(&color, i),
-        x: rand::random_range(0..DA_WIDTH),
-        y: rand::random_range(0..DA_HEIGHT),
-        shapesize: if args.shapesize == 0 {
-          1
-        } else {
-          args.shapesize
-        },
-        additional_payload_size: payload.clone(),
-      })
-      .collect();
-    let mut velocities: Vec<(i32, i32)> = (0..num_instances).map(|_| random_velocity()).collect();
-    let mut iterations = 0i32;
-
-    // RustDDS 0.12 does not raise the writer-side OfferedDeadlineMissed status,
-    // so we compute it here: the writer controls its own write cadence and
-    // knows the requested deadline. If a deadline elapses without a write, we
-    // emit `on_offered_deadline_missed()` (once per missed period), matching
-    // what the test harness expects from the publisher.
-    let deadline = args
-      .deadline_ms
-      .map(|ms| StdDuration::from_millis(ms.max(0) as u64));
-    let mut deadline_check = StreamExt::fuse(Timer::interval(
-      deadline.unwrap_or(StdDuration::from_secs(3600)),
-    ));
-    let mut last_write = std::time::Instant::now();
-    let mut deadline_reported = false;
+  debug!("Starting RustDDS shape_main: {args:?}");
+  smol::block_on(async { futures::join!(read_loop, write_loop) });
+  println!("Done.");
+}
 
-    while run {
-      select! {
-        _ = stop => run = false,
-        _ = ticks.select_next_some() => {
-          for (shape, vel) in shapes.iter_mut().zip(velocities.iter_mut()) {
-            // Send the current sample first, then advance position/size for the
-            // next one. This makes the very first sample the initial value
-            // (e.g. shapesize == 1 with `-z 0`), which the TRANSIENT_LOCAL
-            // durability test relies on.
-            //
-            // Under reliable back-pressure the write can return WouldBlock when
-            // the send window is full (a slow/lagging reader). Retry with a small
-            // delay so we never drop a sample; this gives the lossless behavior
-            // the reliability/history tests expect.
-            let mut pending = Some(shape.clone());
-            while let Some(s) = pending.take() {
-              match datawriter.async_write(s, None).await {
-                Ok(()) => {}
-                Err(WriteError::WouldBlock { data }) => {
-                  pending = Some(data);
-                  Timer::after(StdDuration::from_millis(2)).await;
-                }
-                Err(e) => error!("DataWriter write failed: {e:?}"),
+/// Publish the shapes until stopped or `--num-iterations` is reached. With
+/// `--coherent`, every `--coherent-sample-count` rounds of writes form one
+/// coherent set of the Publisher.
+async fn write_shapes<SA>(
+  datawriter: DataWriter<ShapeType, SA>,
+  args: &Args,
+  topic_name: &str,
+  color: &str,
+  stop_receiver: &smol::channel::Receiver<()>,
+) where
+  SA: SerializerAdapter<ShapeType>,
+{
+  let mut run = true;
+  let stop = stop_receiver.recv().fuse();
+  pin_mut!(stop);
+  let mut status_stream = datawriter.as_async_status_stream();
+  let mut ticks = StreamExt::fuse(Timer::interval(write_interval(args)));
+
+  let num_instances = args.num_instances.unwrap_or(1).max(1);
+  let payload = match args.additional_payload_size {
+    Some(n) if n > 0 => vec![0xffu8; n as usize],
+    _ => Vec::new(),
+  };
+  let mut shapes: Vec<ShapeType> = (0..num_instances)
+    .map(|i| ShapeType {
+      color: instance_color(color, i),
+      x: rand::random_range(0..DA_WIDTH),
+      y: rand::random_range(0..DA_HEIGHT),
+      shapesize: if args.shapesize == 0 {
+        1
+      } else {
+        args.shapesize
+      },
+      additional_payload_size: payload.clone(),
+    })
+    .collect();
+  let mut velocities: Vec<(i32, i32)> = (0..num_instances).map(|_| random_velocity()).collect();
+  let mut iterations = 0i32;
+  let publisher = datawriter.publisher();
+  let coherent_set_size = args.coherent_sample_count.unwrap_or(1).max(1);
+  let mut in_coherent_set = 0i32;
+
+  // RustDDS 0.12 does not raise the writer-side OfferedDeadlineMissed status,
+  // so we compute it here: the writer controls its own write cadence and
+  // knows the requested deadline. If a deadline elapses without a write, we
+  // emit `on_offered_deadline_missed()` (once per missed period), matching
+  // what the test harness expects from the publisher.
+  let deadline = args
+    .deadline_ms
+    .map(|ms| StdDuration::from_millis(ms.max(0) as u64));
+  let mut deadline_check = StreamExt::fuse(Timer::interval(
+    deadline.unwrap_or(StdDuration::from_secs(3600)),
+  ));
+  let mut last_write = std::time::Instant::now();
+  let mut deadline_reported = false;
+
+  while run {
+    select! {
+      _ = stop => run = false,
+      _ = ticks.select_next_some() => {
+        if args.coherent && in_coherent_set == 0 {
+          publisher.begin_coherent_changes();
+        }
+        for (shape, vel) in shapes.iter_mut().zip(velocities.iter_mut()) {
+          // Send the current sample first, then advance position/size for the
+          // next one. This makes the very first sample the initial value
+          // (e.g. shapesize == 1 with `-z 0`), which the TRANSIENT_LOCAL
+          // durability test relies on.
+          //
+          // Under reliable back-pressure the write can return WouldBlock when
+          // the send window is full (a slow/lagging reader). Retry with a small
+          // delay so we never drop a sample; this gives the lossless behavior
+          // the reliability/history tests expect.
+          let mut pending = Some(shape.clone());
+          while let Some(s) = pending.take() {
+            match datawriter.async_write(s, None).await {
+              Ok(()) => {}
+              Err(WriteError::WouldBlock { data }) => {
+                pending = Some(data);
+                Timer::after(StdDuration::from_millis(2)).await;
               }
+              Err(e) => error!("DataWriter write failed: {e:?}"),
             }
-            if args.print_writer_samples {
-              print_sample(&topic_name, shape);
-            }
-            step_shape(shape, vel, &args);
           }
-          last_write = std::time::Instant::now();
-          deadline_reported = false;
-          iterations += 1;
-          if let Some(n) = args.num_iterations {
-            if n > 0 && iterations >= n {
-              run = false;
-            }
+          if args.print_writer_samples {
+            print_sample(topic_name, shape);
           }
-        },
-        _ = deadline_check.select_next_some() => {
-          if let Some(dl) = deadline {
-            if !deadline_reported && last_write.elapsed() >= dl {
-              println!("on_offered_deadline_missed()");
-              deadline_reported = true;
-            }
+          step_shape(shape, vel, args);
+        }
+        if args.coherent {
+          in_coherent_set += 1;
+          if in_coherent_set == coherent_set_size {
+            publisher.end_coherent_changes();
+            in_coherent_set = 0;
           }
-        },
-        e = status_stream.select_next_some() => report_writer_status(&e),
-      }
+        }
+        last_write = std::time::Instant::now();
+        deadline_reported = false;
+        iterations += 1;
+        if let Some(n) = args.num_iterations {
+          if n > 0 && iterations >= n {
+            run = false;
+          }
+        }
+      },
+      _ = deadline_check.select_next_some() => {
+        if let Some(dl) = deadline {
+          if !deadline_reported && last_write.elapsed() >= dl {
+            println!("on_offered_deadline_missed()");
+            deadline_reported = true;
+          }
+        }
+      },
+      e = status_stream.select_next_some() => report_writer_status(&e),
     }
+  }
+  if in_coherent_set > 0 {
+    publisher.end_coherent_changes();
+  }
 
-    // Final instance action requested by --final-instance-state.
-    if matches!(args.final_instance_state, Some('d')) {
+  // Final instance action requested by --final-instance-state.
+  match args.final_instance_state {
+    Some('d') => {
       for shape in &shapes {
         let _ = datawriter.dispose(&shape.key(), None);
       }
     }
-    // 'u' (unregister) has no direct RustDDS API; dropping the writer lets the
-    // reader observe the loss of the writer.
-  };
-
-  debug!("Starting RustDDS shape_main: {args:?}");
-  smol::block_on(async { futures::join!(read_loop, write_loop) });
-  println!("Done.");
+    Some('u') => {
+      for shape in &shapes {
+        let _ = datawriter.unregister_instance(&shape.key(), None);
+      }
+    }
+    _ => {}
+  }
 }
 
 /// Translate the command-line options into a `QosPolicies` value using only the
@@ -526,6 +640,18 @@ fn build_qos(args: &Args) -> rustdds::QosPolicies {
     });
   }
 
+  if args.coherent || args.ordered || args.access_scope.is_some() {
+    b = b.presentation(Presentation {
+      access_scope: match args.access_scope {
+        Some('t') => PresentationAccessScope::Topic,
+        Some('g') => PresentationAccessScope::Group,
+        _ => PresentationAccessScope::Instance,
+      },
+      coherent_access: args.coherent,
+      ordered_access: args.ordered,
+    });
+  }
+
   let data_representation = match args.data_representation.as_deref() {
     Some("2") => DataRepresentation {
       value: vec![XCDR2_DATA_REPRESENTATION],
@@ -536,7 +662,11 @@ fn build_qos(args: &Args) -> rustdds::QosPolicies {
     Some(other) => panic!("unsupported data representation {other} (use 1 or 2)"),
   };
 
-  b.build().with_data_representation(data_representation)
+  let qos = b.build().with_data_representation(data_representation);
+  match &args.partition {
+    Some(partition) => qos.with_partition(Partition::new([partition])),
+    None => qos,
+  }
 }
 
 /// Determine the write period: an explicit `--write-period` wins; otherwise
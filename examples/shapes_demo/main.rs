@@ -19,8 +19,8 @@ use log4rs::{
 use rustdds::{
   dds::statusevents,
   policy::{
-    DataRepresentation, Deadline, Durability, History, Reliability, XCDR2_DATA_REPRESENTATION,
-    XCDR_DATA_REPRESENTATION,
+    DataRepresentation, Deadline, Durability, History, Ownership, Partition, Reliability,
+    TimeBasedFilter, XCDR2_DATA_REPRESENTATION, XCDR_DATA_REPRESENTATION,
   },
   with_key::Sample,
   DomainParticipantBuilder, Keyed, QosPolicyBuilder, StatusEvented, TopicDescription, TopicKind,
@@ -146,20 +146,21 @@ fn main() {
     qos_b = qos_b.deadline(dl);
   }
 
-  assert!(
-    !matches.contains_id("partition"),
-    "QoS policy Partition is not yet implemented."
-  );
-
-  assert!(
-    !matches.contains_id("interval"),
-    "QoS policy Time Based Filter is not yet implemented."
-  );
+  if let Some(interval) = matches.get_one::<f64>("interval") {
+    qos_b = qos_b.time_based_filter(TimeBasedFilter {
+      minimum_separation: rustdds::Duration::from_frac_seconds(*interval),
+    });
+  }
 
-  assert!(
-    !matches.contains_id("ownership_strength"),
-    "QoS policy Ownership Strength is not yet implemented."
-  );
+  if let Some(strength) = matches.get_one::<i32>("ownership_strength") {
+    qos_b = qos_b.ownership(if *strength < 0 {
+      Ownership::Shared
+    } else {
+      Ownership::Exclusive {
+        strength: *strength,
+      }
+    });
+  }
 
   // Match the OMG shape_main default (XCDR1) so cross-vendor interop advertises
   // PID_DATA_REPRESENTATION in discovery. Honor -x for XCDR2 incompatibility
@@ -177,7 +178,10 @@ fn main() {
     Some(other) => panic!("unsupported data representation {other} (use 1 or 2)"),
   };
 
-  let qos = qos_b.build().with_data_representation(data_representation);
+  let mut qos = qos_b.build().with_data_representation(data_representation);
+  if let Some(partition) = matches.get_one::<String>("partition") {
+    qos = qos.with_partition(Partition::new([partition]));
+  }
 
   let loop_delay: Duration = match deadline_policy {
     None => Duration::from_millis(200), // This is the default rate
@@ -539,12 +543,15 @@ fn get_matches() -> ArgMatches {
       Arg::new("interval")
         .help("Apply 'time based filter' with interval (seconds)")
         .short('i')
+        .value_parser(clap::value_parser!(f64))
         .value_name("interval"),
     )
     .arg(
       Arg::new("ownership_strength")
         .help("Set ownership strength [-1: SHARED]")
         .short('s')
+        .value_parser(clap::value_parser!(i32))
+        .allow_negative_numbers(true)
         .value_name("strength"),
     )
     .arg(
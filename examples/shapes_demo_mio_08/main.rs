@@ -25,8 +25,10 @@ use rustdds::{
   with_key::Sample, DomainParticipantBuilder, Keyed, QosPolicyBuilder, StatusEvented,
   TopicDescription, TopicKind,
 };
-use rustdds::policy::{Deadline, Durability, History, Reliability}; /* import all QoS
-                                                                     * policies directly */
+// import all QoS policies directly
+use rustdds::policy::{
+  Deadline, Durability, History, Ownership, Partition, Reliability, TimeBasedFilter,
+};
 use serde::{Deserialize, Serialize};
 use clap::{Arg, ArgMatches, Command}; // command line argument processing
 use mio_08::{Events, Interest, Poll, Token}; // non-blocking i/o polling
@@ -132,22 +134,26 @@ fn main() {
     qos_b = qos_b.deadline(dl);
   }
 
-  assert!(
-    !matches.contains_id("partition"),
-    "QoS policy Partition is not yet implemented."
-  );
-
-  assert!(
-    !matches.contains_id("interval"),
-    "QoS policy Time Based Filter is not yet implemented."
-  );
+  if let Some(interval) = matches.get_one::<f64>("interval") {
+    qos_b = qos_b.time_based_filter(TimeBasedFilter {
+      minimum_separation: rustdds::Duration::from_frac_seconds(*interval),
+    });
+  }
 
-  assert!(
-    !matches.contains_id("ownership_strength"),
-    "QoS policy Ownership Strength is not yet implemented."
-  );
+  if let Some(strength) = matches.get_one::<i32>("ownership_strength") {
+    qos_b = qos_b.ownership(if *strength < 0 {
+      Ownership::Shared
+    } else {
+      Ownership::Exclusive {
+        strength: *strength,
+      }
+    });
+  }
 
-  let qos = qos_b.build();
+  let mut qos = qos_b.build();
+  if let Some(partition) = matches.get_one::<String>("partition") {
+    qos = qos.with_partition(Partition::new([partition]));
+  }
 
   let loop_delay: Duration = match deadline_policy {
     None => Duration::from_millis(200), // This is the default rate
@@ -447,12 +453,15 @@ fn get_matches() -> ArgMatches {
       Arg::new("interval")
         .help("Apply 'time based filter' with interval (seconds)")
         .short('i')
+        .value_parser(clap::value_parser!(f64))
         .value_name("interval"),
     )
     .arg(
       Arg::new("ownership_strength")
         .help("Set ownership strength [-1: SHARED]")
         .short('s')
+        .value_parser(clap::value_parser!(i32))
+        .allow_negative_numbers(true)
         .value_name("strength"),
     )
     .arg(
@@ -33,6 +33,12 @@ pub mod sampleinfo;
 /// Defines instance Keys that are needed to access WITH_KEY topics.
 pub mod key;
 
+/// Synchronous waiting for data and status changes.
+pub mod waitset;
+
+/// Timeouts for the async sample streams.
+pub mod sample_stream;
+
 /// Participating to NoKey topics.
 pub mod no_key;
 /// Participating to WithKey topics.
@@ -40,3 +46,6 @@ pub mod with_key;
 
 /// Serializer/deserializer adapters to connect serialization to RTPS.
 pub mod adapters;
+
+/// Keeping the samples of Transient DataWriters for late-joining DataReaders.
+pub mod durability_store;
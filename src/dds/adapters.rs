@@ -154,6 +154,28 @@ pub mod no_key {
     {
       Self::from_bytes_with(input_bytes, encoding, Self::DECODER)
     }
+
+    /// Deserialize data from bytes into an existing object using the given
+    /// decoder, so that the allocations of `target` can be reused.
+    ///
+    /// The provided implementation decodes a new value and assigns it to
+    /// `target`, which does not avoid allocation. Adapters whose
+    /// [`Self::Decoded`] is `D` can forward to [`Decode::decode_bytes_into`]
+    /// instead.
+    ///
+    /// On error, `target` may be left partially updated.
+    fn from_bytes_into_with<'de, S>(
+      input_bytes: &'de [u8],
+      encoding: RepresentationIdentifier,
+      decoder: S,
+      target: &mut D,
+    ) -> Result<(), S::Error>
+    where
+      S: Decode<'de, Self::Decoded>,
+    {
+      *target = Self::from_bytes_with(input_bytes, encoding, decoder)?;
+      Ok(())
+    }
   }
 
   /// The `DeserializerAdapter` can be used without a decoder object as there is
@@ -192,6 +214,24 @@ pub mod no_key {
       input_bytes: &'de [u8],
       encoding: RepresentationIdentifier,
     ) -> Result<Decoded, Self::Error>;
+
+    /// Decodes like [`Self::decode_bytes`], but into an existing value, so
+    /// that its allocations can be reused.
+    ///
+    /// The provided implementation decodes a new value and assigns it.
+    /// On error, `target` may be left partially updated.
+    fn decode_bytes_into(
+      self,
+      input_bytes: &'de [u8],
+      encoding: RepresentationIdentifier,
+      target: &mut Decoded,
+    ) -> Result<(), Self::Error>
+    where
+      Self: Sized,
+    {
+      *target = self.decode_bytes(input_bytes, encoding)?;
+      Ok(())
+    }
   }
 
   /// trait for connecting a Serializer implementation and DataWriter
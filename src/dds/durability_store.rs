@@ -0,0 +1,348 @@
+use std::{
+  collections::{BTreeMap, VecDeque},
+  fmt,
+  sync::Mutex,
+};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::{
+  dds::{
+    key::KeyHash,
+    qos::policy::{DurabilityService, History},
+  },
+  structure::{
+    cache_change::{CacheChange, ChangeKind},
+    guid::GUID,
+    sequence_number::SequenceNumber,
+    time::Timestamp,
+  },
+};
+
+/// A sample written by a Transient or Persistent DataWriter, as kept by a
+/// [`DurabilityStore`].
+///
+/// The sample keeps the GUID, SequenceNumber and source timestamp of the
+/// original write, so a DataReader that receives it from the store sees the
+/// same sample as it would have seen from the DataWriter, and does not take it
+/// twice if the DataWriter is still there.
+#[derive(Debug, Clone)]
+pub struct StoredSample {
+  change: CacheChange,
+}
+
+impl StoredSample {
+  pub(crate) fn new(change: CacheChange) -> Self {
+    Self { change }
+  }
+
+  pub(crate) fn into_cache_change(self) -> CacheChange {
+    self.change
+  }
+
+  pub fn writer_guid(&self) -> GUID {
+    self.change.writer_guid
+  }
+
+  pub fn sequence_number(&self) -> SequenceNumber {
+    self.change.sequence_number
+  }
+
+  pub fn source_timestamp(&self) -> Option<Timestamp> {
+    self.change.write_options.source_timestamp()
+  }
+
+  /// Key hash of the instance of the sample, if the DataWriter sent one.
+  /// Samples of NO_KEY topics have none.
+  pub fn instance(&self) -> Option<KeyHash> {
+    self.change.write_options.key_hash()
+  }
+
+  /// Is this a live sample, as opposed to a dispose or unregister?
+  pub fn is_alive(&self) -> bool {
+    self.change.data_value.change_kind() == ChangeKind::Alive
+  }
+
+  /// Does this sample dispose its instance?
+  pub fn is_disposed(&self) -> bool {
+    matches!(
+      self.change.data_value.change_kind(),
+      ChangeKind::NotAliveDisposed | ChangeKind::NotAliveDisposedUnregistered
+    )
+  }
+
+  /// Size of the serialized data or key.
+  pub fn payload_size(&self) -> usize {
+    self.change.data_value.payload_size()
+  }
+}
+
+/// Keeps the samples of Transient and Persistent DataWriters, so that they
+/// outlive the DataWriter and can be replayed to DataReaders that join later.
+///
+/// A DataWriter with Transient or Persistent Durability hands every sample it
+/// writes to the store of its DomainParticipant. A DataReader with Transient
+/// or Persistent Durability receives the samples of its Topic from the store
+/// when it is created, in addition to the history that matched DataWriters
+/// send to it.
+///
+/// The store of a DomainParticipant is set with
+/// [`DomainParticipantBuilder::durability_store`](crate::DomainParticipantBuilder::durability_store).
+/// The default is a [`InMemoryDurabilityStore`] of its own. Sharing one store
+/// between DomainParticipants lets samples outlive the DomainParticipant that
+/// wrote them, too. An implementation that writes to disk makes the samples
+/// Persistent.
+pub trait DurabilityStore: fmt::Debug + Send + Sync {
+  /// Keep `sample` of Topic `topic_name`, within the limits of the
+  /// DURABILITY_SERVICE QoS policy of the DataWriter. The samples of each
+  /// DataWriter are stored in SequenceNumber order.
+  fn store(&self, topic_name: &str, service: &DurabilityService, sample: StoredSample);
+
+  /// The samples kept of Topic `topic_name`, in the order they were stored.
+  fn samples(&self, topic_name: &str) -> Vec<StoredSample>;
+}
+
+/// A [`DurabilityStore`] that keeps the samples in memory, by Topic and
+/// instance.
+///
+/// The DURABILITY_SERVICE QoS policy of the DataWriter bounds what is kept:
+/// the history depth and `max_samples_per_instance` the samples of each
+/// instance, `max_instances` the instances of the Topic, and `max_samples`
+/// all samples of the Topic. The oldest samples and the least recently
+/// written instances are dropped first. A disposed instance is removed when it
+/// has stayed disposed for `service_cleanup_delay`.
+#[derive(Debug, Default)]
+pub struct InMemoryDurabilityStore {
+  topics: Mutex<BTreeMap<String, TopicStore>>,
+}
+
+impl InMemoryDurabilityStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+#[derive(Debug, Default)]
+struct TopicStore {
+  // Counts stored samples, to return them in the order they were stored.
+  next_index: u64,
+  // Samples without a key hash are all in the `None` instance.
+  instances: BTreeMap<Option<KeyHash>, InstanceStore>,
+}
+
+#[derive(Debug, Default)]
+struct InstanceStore {
+  samples: VecDeque<(u64, StoredSample)>,
+  // Set when the instance is disposed: service_cleanup_delay later.
+  remove_at: Option<Timestamp>,
+}
+
+impl InstanceStore {
+  fn newest_index(&self) -> u64 {
+    self.samples.back().map_or(0, |(index, _)| *index)
+  }
+}
+
+impl TopicStore {
+  fn insert(&mut self, service: &DurabilityService, sample: StoredSample, now: Timestamp) {
+    let index = self.next_index;
+    self.next_index += 1;
+
+    let instance = self.instances.entry(sample.instance()).or_default();
+    instance.remove_at = sample
+      .is_disposed()
+      .then(|| now + service.service_cleanup_delay);
+    instance.samples.push_back((index, sample));
+
+    let depth = match service.history {
+      History::KeepLast { depth } => Some(depth.max(1) as usize),
+      History::KeepAll => None,
+    };
+    let per_instance = limit(service.resource_limits.max_samples_per_instance);
+    if let Some(keep) = min_limit(depth, per_instance) {
+      while instance.samples.len() > keep {
+        instance.samples.pop_front();
+      }
+    }
+
+    if let Some(max_instances) = limit(service.resource_limits.max_instances) {
+      while self.instances.len() > max_instances {
+        let least_recent = self
+          .instances
+          .iter()
+          .min_by_key(|(_, instance)| instance.newest_index())
+          .map(|(key, _)| *key);
+        if let Some(key) = least_recent {
+          self.instances.remove(&key);
+        }
+      }
+    }
+
+    if let Some(max_samples) = limit(service.resource_limits.max_samples) {
+      while self.len() > max_samples {
+        let oldest = self
+          .instances
+          .iter()
+          .filter_map(|(key, instance)| instance.samples.front().map(|(index, _)| (*index, *key)))
+          .min();
+        if let Some((_, key)) = oldest {
+          if let Some(instance) = self.instances.get_mut(&key) {
+            instance.samples.pop_front();
+          }
+        }
+      }
+    }
+    self
+      .instances
+      .retain(|_, instance| !instance.samples.is_empty());
+  }
+
+  fn remove_disposed(&mut self, now: Timestamp) {
+    self
+      .instances
+      .retain(|_, instance| instance.remove_at.is_none_or(|remove_at| now < remove_at));
+  }
+
+  fn len(&self) -> usize {
+    self.instances.values().map(|i| i.samples.len()).sum()
+  }
+
+  fn samples(&self) -> Vec<StoredSample> {
+    let mut samples: Vec<&(u64, StoredSample)> = self
+      .instances
+      .values()
+      .flat_map(|instance| instance.samples.iter())
+      .collect();
+    samples.sort_by_key(|(index, _)| *index);
+    samples.into_iter().map(|(_, s)| s.clone()).collect()
+  }
+}
+
+// A ResourceLimits value, where non-positive means unlimited.
+fn limit(value: i32) -> Option<usize> {
+  (value > 0).then_some(value as usize)
+}
+
+fn min_limit(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+  match (a, b) {
+    (Some(a), Some(b)) => Some(a.min(b)),
+    (a, b) => a.or(b),
+  }
+}
+
+impl DurabilityStore for InMemoryDurabilityStore {
+  fn store(&self, topic_name: &str, service: &DurabilityService, sample: StoredSample) {
+    let now = Timestamp::now();
+    let mut topics = self.topics.lock().unwrap();
+    let topic = topics.entry(topic_name.to_string()).or_default();
+    topic.insert(service, sample, now);
+    topic.remove_disposed(now);
+  }
+
+  fn samples(&self, topic_name: &str) -> Vec<StoredSample> {
+    let mut topics = self.topics.lock().unwrap();
+    match topics.get_mut(topic_name) {
+      Some(topic) => {
+        topic.remove_disposed(Timestamp::now());
+        topic.samples()
+      }
+      None => Vec::new(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    dds::{ddsdata::DDSData, qos::policy::ResourceLimits},
+    messages::submessages::elements::serialized_payload::SerializedPayload,
+    structure::guid::EntityKind,
+    Duration, RepresentationIdentifier, WriteOptionsBuilder,
+  };
+
+  fn sample(writer: GUID, sn: i64, key: u8, change_kind: ChangeKind) -> StoredSample {
+    let key_hash = KeyHash::from_pl_cdr_bytes(vec![key; 16]).unwrap();
+    let write_options = WriteOptionsBuilder::new()
+      .source_timestamp(Timestamp::now())
+      .key_hash(key_hash)
+      .build();
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![key, 0, 0, 0]);
+    let data = match change_kind {
+      ChangeKind::Alive => DDSData::new(payload),
+      kind => DDSData::new_disposed_by_key(kind, payload),
+    };
+    StoredSample::new(CacheChange::new(
+      writer,
+      SequenceNumber::new(sn),
+      write_options,
+      data,
+    ))
+  }
+
+  fn writer() -> GUID {
+    GUID::dummy_test_guid(EntityKind::WRITER_WITH_KEY_USER_DEFINED)
+  }
+
+  fn sns(samples: &[StoredSample]) -> Vec<i64> {
+    samples
+      .iter()
+      .map(|s| i64::from(s.sequence_number()))
+      .collect()
+  }
+
+  #[test]
+  fn keeps_history_depth_per_instance_in_write_order() {
+    let store = InMemoryDurabilityStore::new();
+    let service = DurabilityService {
+      history: History::KeepLast { depth: 2 },
+      ..DurabilityService::default()
+    };
+    let w = writer();
+    for (sn, key) in [(1, 1), (2, 2), (3, 1), (4, 1), (5, 2)] {
+      store.store("T", &service, sample(w, sn, key, ChangeKind::Alive));
+    }
+    assert_eq!(sns(&store.samples("T")), vec![2, 3, 4, 5]);
+    assert!(store.samples("other").is_empty());
+  }
+
+  #[test]
+  fn limits_instances_and_samples() {
+    let store = InMemoryDurabilityStore::new();
+    let service = DurabilityService {
+      history: History::KeepAll,
+      resource_limits: ResourceLimits {
+        max_samples: 3,
+        max_instances: 2,
+        max_samples_per_instance: -1,
+      },
+      ..DurabilityService::default()
+    };
+    let w = writer();
+    for (sn, key) in [(1, 1), (2, 2), (3, 3), (4, 3), (5, 3)] {
+      store.store("T", &service, sample(w, sn, key, ChangeKind::Alive));
+    }
+    // Instance 1 is dropped for max_instances, sample 2 for max_samples.
+    assert_eq!(sns(&store.samples("T")), vec![3, 4, 5]);
+  }
+
+  #[test]
+  fn removes_disposed_instance_after_cleanup_delay() {
+    let store = InMemoryDurabilityStore::new();
+    let w = writer();
+    let keep = DurabilityService {
+      service_cleanup_delay: Duration::from_secs(3600),
+      ..DurabilityService::default()
+    };
+    store.store("T", &keep, sample(w, 1, 1, ChangeKind::Alive));
+    store.store("T", &keep, sample(w, 2, 1, ChangeKind::NotAliveDisposed));
+    let stored = store.samples("T");
+    assert_eq!(sns(&stored), vec![2]);
+    assert!(stored[0].is_disposed());
+
+    let remove = DurabilityService::default();
+    store.store("T", &remove, sample(w, 3, 2, ChangeKind::NotAliveDisposed));
+    assert_eq!(sns(&store.samples("T")), vec![2]);
+  }
+}
@@ -68,6 +68,13 @@ impl KeyHash {
   }
 }
 
+/// Identifies an instance of a WITH_KEY topic, as returned by
+/// `DataReader::lookup_instance`.
+///
+/// This is the RTPS KeyHash of the instance key, so it identifies the same
+/// instance on all participants.
+pub type InstanceHandle = KeyHash;
+
 /// Trait for instance lookup key in a WITH_KEY topic.
 ///
 /// The corresponding data sample type must implement [`Keyed`].
@@ -139,8 +146,10 @@ pub trait Key:
 
     // The specification calls for "sequential CDR representation of all the key
     // fields" and "CDR Big- Endian representation of all the Key fields in
-    // sequence". We take this to mean the CDR encoding of the Key.
-    // (Does it include CDR-specified alignment padding too?)
+    // sequence". We take this to mean the CDR encoding of the Key, including
+    // alignment padding. It is always big-endian, whatever the encoding of the
+    // payload is. DataWriters send this in the inline QoS, and DataReaders
+    // compute it the same way to resolve disposals by key hash.
     //
 
     /*
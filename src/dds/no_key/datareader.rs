@@ -2,6 +2,7 @@ use std::{
   io,
   pin::Pin,
   task::{Context, Poll},
+  time::{Duration as StdDuration, Instant},
 };
 
 use futures::stream::{FusedStream, Stream};
@@ -12,18 +13,20 @@ use crate::{
     no_key::datasample::DataSample,
     qos::{HasQoSPolicy, QosPolicies},
     readcondition::ReadCondition,
-    result::ReadResult,
-    statusevents::DataReaderStatus,
+    result::{CreateResult, ReadResult},
+    sampleinfo::SampleInfo,
+    statusevents::{CacheOccupancy, CountWithChange, DataReaderStatus, LivelinessChangedStatus},
+    waitset::{DataAvailableCondition, StatusCondition},
     with_key::{
       datareader as datareader_with_key,
       datasample::{DataSample as WithKeyDataSample, Sample},
       BareDataReaderStream as WithKeyBareDataReaderStream,
-      DataReaderEventStream as WithKeyDataReaderEventStream,
-      DataReaderStream as WithKeyDataReaderStream,
+      DataReaderEventStream as WithKeyDataReaderEventStream, AmbiguousStatusInfo,
+      DataReaderStream as WithKeyDataReaderStream, DeliveryOrder, DuplicateAcceptance,
     },
   },
   serialization::CDRDeserializerAdapter,
-  structure::entity::RTPSEntity,
+  structure::{duration::Duration, entity::RTPSEntity, time::Timestamp},
   StatusEvented, GUID,
 };
 use super::wrappers::{DAWrapper, NoKeyWrapper};
@@ -73,6 +76,12 @@ where
       keyed_datareader: keyed,
     }
   }
+
+  pub(crate) fn set_content_filter(&mut self, filter: impl Fn(&D) -> bool + Send + Sync + 'static) {
+    self
+      .keyed_datareader
+      .set_content_filter(Box::new(move |sample: &NoKeyWrapper<D>| filter(&sample.d)));
+  }
 }
 
 impl<D: 'static, DA> DataReader<D, DA>
@@ -168,6 +177,38 @@ where
     Ok(result)
   }
 
+  /// Like [`read`](Self::read), but returns each value paired with its
+  /// [`SampleInfo`].
+  pub fn read_with_info(
+    &mut self,
+    max_samples: usize,
+    read_condition: ReadCondition,
+  ) -> ReadResult<Vec<(&D, SampleInfo)>> {
+    Ok(
+      self
+        .read(max_samples, read_condition)?
+        .into_iter()
+        .map(DataSample::into_parts)
+        .collect(),
+    )
+  }
+
+  /// Like [`take`](Self::take), but returns each value paired with its
+  /// [`SampleInfo`].
+  pub fn take_with_info(
+    &mut self,
+    max_samples: usize,
+    read_condition: ReadCondition,
+  ) -> ReadResult<Vec<(D, SampleInfo)>> {
+    Ok(
+      self
+        .take(max_samples, read_condition)?
+        .into_iter()
+        .map(DataSample::into_parts)
+        .collect(),
+    )
+  }
+
   /// Reads next unread sample
   ///
   /// # Examples
@@ -228,6 +269,27 @@ where
     Ok(ds.pop())
   }
 
+  /// See
+  /// [`with_key::DataReader::take_next_sample_timeout`](crate::with_key::DataReader::take_next_sample_timeout).
+  pub fn take_next_sample_timeout(
+    &mut self,
+    timeout: StdDuration,
+  ) -> ReadResult<Option<DataSample<D>>> {
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+      let remaining = deadline.map_or(timeout, |d| d.saturating_duration_since(Instant::now()));
+      match self.keyed_datareader.take_next_sample_timeout(remaining)? {
+        // A disposal is not a sample of a NO_KEY topic. Keep waiting.
+        Some(ks) => {
+          if let Some(s) = DataSample::<D>::from_with_key(ks) {
+            return Ok(Some(s));
+          }
+        }
+        None => return Ok(None),
+      }
+    }
+  }
+
   // Iterator interface
 
   /// Produces an iterator over the currently available NOT_READ samples.
@@ -389,40 +451,101 @@ where
         .map(|ds| ds.value),
     )
   }
-  /*
-  /// Gets latest RequestedDeadlineMissed status
-  ///
-  /// # Examples
-  ///
-  /// ```
-  /// # use serde::{Serialize, Deserialize};
-  /// # use rustdds::*;
-  /// # use rustdds::no_key::DataReader;
-  /// # use rustdds::serialization::CDRDeserializerAdapter;
-  /// #
-  /// # let domain_participant = DomainParticipant::new(0).unwrap();
-  /// # let qos = QosPolicyBuilder::new().build();
-  /// # let subscriber = domain_participant.create_subscriber(&qos).unwrap();
-  /// #
-  /// # // NoKey is important
-  /// # let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::NoKey).unwrap();
-  /// #
-  /// # #[derive(Serialize, Deserialize)]
-  /// # struct SomeType {}
-  /// #
-  /// let mut data_reader = subscriber.create_datareader_no_key::<SomeType, CDRDeserializerAdapter<_>>(topic, None).unwrap();
-  /// if let Ok(Some(status)) = data_reader.get_requested_deadline_missed_status() {
-  ///   // Do something
-  /// }
-  /// ```
 
+  /// Current liveliness of the matched DataWriters. See
+  /// [`with_key::DataReader::liveliness_changed_status`](crate::with_key::DataReader::liveliness_changed_status).
+  pub fn liveliness_changed_status(&self) -> LivelinessChangedStatus {
+    self.keyed_datareader.liveliness_changed_status()
+  }
 
-  pub fn get_requested_deadline_missed_status(
-    &mut self,
-  ) -> ReadResult<Option<RequestedDeadlineMissedStatus>> {
-    self.keyed_datareader.get_requested_deadline_missed_status()
+  /// The REQUESTED_DEADLINE_MISSED status. See
+  /// [`with_key::DataReader::requested_deadline_missed_status`](crate::with_key::DataReader::requested_deadline_missed_status).
+  pub fn requested_deadline_missed_status(&self) -> CountWithChange {
+    self.keyed_datareader.requested_deadline_missed_status()
+  }
+
+  /// See [`with_key::DataReader::data_available_condition`](crate::with_key::DataReader::data_available_condition).
+  pub fn data_available_condition(&self) -> DataAvailableCondition {
+    self.keyed_datareader.data_available_condition()
+  }
+
+  /// See [`with_key::DataReader::status_condition`](crate::with_key::DataReader::status_condition).
+  pub fn status_condition(&self) -> StatusCondition {
+    self.keyed_datareader.status_condition()
+  }
+
+  /// Create another DataReader that shares the RTPS Reader of this one. See
+  /// [`with_key::DataReader::share`](crate::with_key::DataReader::share).
+  pub fn share(&self) -> CreateResult<Self> {
+    Ok(Self {
+      keyed_datareader: self.keyed_datareader.share()?,
+    })
+  }
+
+  /// Detect samples whose serialized payload is identical to that of the
+  /// previous sample, and flag them with
+  /// [`SampleInfo::is_content_duplicate`](crate::SampleInfo::is_content_duplicate).
+  /// Off by default, because every received payload is hashed. See
+  /// [`with_key::DataReader::set_detect_content_duplicates`](crate::with_key::DataReader::set_detect_content_duplicates).
+  pub fn set_detect_content_duplicates(&mut self, enabled: bool) {
+    self.keyed_datareader.set_detect_content_duplicates(enabled);
+  }
+
+  /// See
+  /// [`with_key::DataReader::set_ambiguous_status_info`](crate::with_key::DataReader::set_ambiguous_status_info).
+  pub fn set_ambiguous_status_info(&mut self, handling: AmbiguousStatusInfo) {
+    self.keyed_datareader.set_ambiguous_status_info(handling);
+  }
+
+  /// See
+  /// [`with_key::DataReader::set_heartbeat_suppression`](crate::with_key::DataReader::set_heartbeat_suppression).
+  pub fn set_heartbeat_suppression(&mut self, duration: Duration) {
+    self.keyed_datareader.set_heartbeat_suppression(duration);
+  }
+
+  /// See
+  /// [`with_key::DataReader::set_duplicate_acceptance`](crate::with_key::DataReader::set_duplicate_acceptance).
+  pub fn set_duplicate_acceptance(&mut self, acceptance: DuplicateAcceptance) {
+    self.keyed_datareader.set_duplicate_acceptance(acceptance);
+  }
+
+  /// Stop taking in samples. See
+  /// [`with_key::DataReader::pause`](crate::with_key::DataReader::pause).
+  pub fn pause(&self) {
+    self.keyed_datareader.pause();
+  }
+
+  /// Start taking in samples again after [`pause`](Self::pause).
+  pub fn resume(&self) {
+    self.keyed_datareader.resume();
+  }
+
+  /// Whether this DataReader is paused. See [`pause`](Self::pause).
+  pub fn is_paused(&self) -> bool {
+    self.keyed_datareader.is_paused()
+  }
+
+  /// Samples in the topic cache that this DataReader reads from. See
+  /// [`with_key::DataReader::cache_occupancy`](crate::with_key::DataReader::cache_occupancy).
+  pub fn cache_occupancy(&self) -> CacheOccupancy {
+    self.keyed_datareader.cache_occupancy()
+  }
+
+  /// Remove samples received before `timestamp` from the topic cache. See
+  /// [`with_key::DataReader::purge_before`](crate::with_key::DataReader::purge_before).
+  pub fn purge_before(&self, timestamp: Timestamp) -> usize {
+    self.keyed_datareader.purge_before(timestamp)
+  }
+
+  pub fn delivery_order(&self) -> DeliveryOrder {
+    self.keyed_datareader.delivery_order()
+  }
+
+  /// Choose when received samples are handed over to the application. See
+  /// [`with_key::DataReader::set_delivery_order`](crate::with_key::DataReader::set_delivery_order).
+  pub fn set_delivery_order(&mut self, delivery_order: DeliveryOrder) {
+    self.keyed_datareader.set_delivery_order(delivery_order);
   }
-  */
 
   /// An async stream for reading the (bare) data samples
   pub fn async_bare_sample_stream(self) -> BareDataReaderStream<D, DA> {
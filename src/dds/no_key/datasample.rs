@@ -60,6 +60,11 @@ impl<D> DataSample<D> {
     self.value
   }
 
+  /// Splits this into the value and its metadata.
+  pub fn into_parts(self) -> (D, SampleInfo) {
+    (self.value, self.sample_info)
+  }
+
   pub fn sample_info(&self) -> &SampleInfo {
     &self.sample_info
   }
@@ -8,7 +8,9 @@ use crate::{
     pubsub::Publisher,
     qos::{HasQoSPolicy, QosPolicies},
     result::{unwrap_no_key_write_error, WriteResult},
-    statusevents::{DataWriterStatus, StatusReceiverStream},
+    statusevents::{
+      CoalescingStatistics, DataWriterStatus, FanOutStatistics, StatusReceiverStream,
+    },
     topic::Topic,
     with_key::datawriter as datawriter_with_key,
   },
@@ -90,6 +92,25 @@ where
       .map_err(unwrap_no_key_write_error)
   }
 
+  /// Writes `data` only if it could reach a DataReader. Returns `Ok(false)` if
+  /// the sample was dropped. See
+  /// [`with_key::DataWriter::write_if_matched`](crate::with_key::DataWriter::write_if_matched)
+  /// for when samples are dropped.
+  pub fn write_if_matched(
+    &self,
+    data: D,
+    source_timestamp: Option<Timestamp>,
+  ) -> WriteResult<bool, D> {
+    self
+      .keyed_datawriter
+      .write_if_matched(NoKeyWrapper::<D> { d: data }, source_timestamp)
+      .map_err(unwrap_no_key_write_error)
+  }
+
+  /// Like [`write`](Self::write), but with
+  /// [`WriteOptions`](crate::WriteOptions). Returns the identity of the
+  /// written sample, see
+  /// [`with_key::DataWriter::write_with_options`](crate::with_key::DataWriter::write_with_options).
   pub fn write_with_options(
     &self,
     data: D,
@@ -128,6 +149,54 @@ where
   pub fn wait_for_acknowledgments(&self, max_wait: Duration) -> WriteResult<bool, ()> {
     self.keyed_datawriter.wait_for_acknowledgments(max_wait)
   }
+
+  /// Waits until the history cache of this DataWriter is empty. See
+  /// [`with_key::DataWriter::wait_for_history_drain`](crate::with_key::DataWriter::wait_for_history_drain).
+  pub fn wait_for_history_drain(&self, max_wait: Duration) -> WriteResult<bool, ()> {
+    self.keyed_datawriter.wait_for_history_drain(max_wait)
+  }
+
+  /// Statistics of coalescing samples into datagrams. See
+  /// [`with_key::DataWriter::coalescing_statistics`](crate::with_key::DataWriter::coalescing_statistics).
+  pub fn coalescing_statistics(&self) -> CoalescingStatistics {
+    self.keyed_datawriter.coalescing_statistics()
+  }
+
+  /// Coalescing delay of this DataWriter. See
+  /// [`with_key::DataWriter::set_max_coalesce_delay`](crate::with_key::DataWriter::set_max_coalesce_delay).
+  pub fn set_max_coalesce_delay(&self, max_delay: Duration) {
+    self.keyed_datawriter.set_max_coalesce_delay(max_delay);
+  }
+
+  /// Send held samples now. See
+  /// [`with_key::DataWriter::flush`](crate::with_key::DataWriter::flush).
+  pub fn flush(&self) {
+    self.keyed_datawriter.flush();
+  }
+
+  /// Matched readers and their repair load. See
+  /// [`with_key::DataWriter::fan_out_statistics`](crate::with_key::DataWriter::fan_out_statistics).
+  pub fn fan_out_statistics(&self) -> FanOutStatistics {
+    self.keyed_datawriter.fan_out_statistics()
+  }
+
+  /// Path MTU to each matched reader. See
+  /// [`with_key::DataWriter::matched_reader_path_mtus`](crate::with_key::DataWriter::matched_reader_path_mtus).
+  pub fn matched_reader_path_mtus(&self) -> Vec<(GUID, u32)> {
+    self.keyed_datawriter.matched_reader_path_mtus()
+  }
+
+  /// Samples not yet acknowledged by `reader`. See
+  /// [`with_key::DataWriter::unacked_sample_count`](crate::with_key::DataWriter::unacked_sample_count).
+  pub fn unacked_sample_count(&self, reader: GUID) -> usize {
+    self.keyed_datawriter.unacked_sample_count(reader)
+  }
+
+  /// Samples not yet acknowledged by the slowest reader. See
+  /// [`with_key::DataWriter::max_unacked_across_readers`](crate::with_key::DataWriter::max_unacked_across_readers).
+  pub fn max_unacked_across_readers(&self) -> usize {
+    self.keyed_datawriter.max_unacked_across_readers()
+  }
   /*
   // status queries
   /// Unimplemented. <b>Do not use</b>.
@@ -331,17 +400,21 @@ where
     self.keyed_datawriter.assert_liveliness()
   }
 
-  /// Placeholder only — not implemented. **Will panic if called.**
-  ///
-  /// # Panics
-  ///
-  /// Always panics. This method is a placeholder and is not implemented.
-  #[deprecated(note = "placeholder only; will panic if called")]
+  /// The DataReaders currently matched with this DataWriter. See
+  /// [`with_key::DataWriter::get_matched_subscriptions`](crate::with_key::DataWriter::get_matched_subscriptions).
   pub fn get_matched_subscriptions(&self) -> Vec<SubscriptionBuiltinTopicData> {
-    #[allow(deprecated)]
     self.keyed_datawriter.get_matched_subscriptions()
   }
 
+  /// See
+  /// [`with_key::DataWriter::get_matched_subscription_data`](crate::with_key::DataWriter::get_matched_subscription_data).
+  pub fn get_matched_subscription_data(
+    &self,
+    reader: GUID,
+  ) -> Option<SubscriptionBuiltinTopicData> {
+    self.keyed_datawriter.get_matched_subscription_data(reader)
+  }
+
   /*
   /// Unimplemented. <b>Do not use</b>.
   ///
@@ -453,6 +526,8 @@ where
       .map_err(unwrap_no_key_write_error)
   }
 
+  /// See
+  /// [`with_key::DataWriter::async_wait_for_acknowledgments`](crate::with_key::DataWriter::async_wait_for_acknowledgments).
   pub async fn async_wait_for_acknowledgments(&self) -> WriteResult<bool, ()> {
     self.keyed_datawriter.async_wait_for_acknowledgments().await
   } // fn
@@ -83,6 +83,18 @@ where
     self.keyed_simpledatareader.qos()
   }
 
+  pub fn delivery_order(&self) -> with_key::DeliveryOrder {
+    self.keyed_simpledatareader.delivery_order()
+  }
+
+  /// Choose when received samples are delivered. See
+  /// [`with_key::SimpleDataReader::set_delivery_order`].
+  pub fn set_delivery_order(&mut self, delivery_order: with_key::DeliveryOrder) {
+    self
+      .keyed_simpledatareader
+      .set_delivery_order(delivery_order);
+  }
+
   pub fn guid(&self) -> GUID {
     self.keyed_simpledatareader.guid()
   }
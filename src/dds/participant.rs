@@ -1,9 +1,9 @@
 // use mio::Token;
 use std::{
-  collections::HashMap,
+  collections::{BTreeSet, HashMap},
   io,
   io::ErrorKind,
-  net::{IpAddr, Ipv4Addr},
+  net::{IpAddr, SocketAddr},
   pin::Pin,
   sync::{atomic, Arc, Mutex, RwLock, Weak},
   task::{Context, Poll},
@@ -21,32 +21,53 @@ use futures::stream::{FusedStream, Stream};
 use log::{debug, error, info, trace, warn};
 
 use crate::{
-  create_error_out_of_resources, create_error_poisoned,
+  create_error_bad_parameter, create_error_out_of_resources, create_error_poisoned,
   dds::{
+    durability_store::{DurabilityStore, InMemoryDurabilityStore},
     pubsub::*,
     qos::*,
     result::*,
     statusevents::{
-      sync_status_channel, DomainParticipantStatusEvent, StatusChannelReceiver, StatusChannelSender,
+      configured_status_channel, DomainParticipantStatusEvent, ParticipantStatistics,
+      StatusChannelConfig, StatusChannelReceiver, StatusChannelSender, StatusOverflowPolicy,
     },
     topic::*,
     typedesc::TypeDesc,
   },
   discovery::{
     discovery::{Discovery, DiscoveryCommand},
-    discovery_db::DiscoveryDB,
+    discovery_db::{discovery_db_read, DiscoveryDB},
+    discovery_dump::DiscoveryDump,
     sedp_messages::DiscoveredTopicData,
   },
-  network::{constant::*, udp_listener::UDPListener},
+  network::{
+    constant::*,
+    interface_filter::InterfaceFilter,
+    multicast::{DiscoveryMulticast, IpVersion},
+    path_mtu::PathMtu,
+    transport::{self, Listeners},
+    udp_listener::UDPListener,
+    util::InterfaceConfig,
+  },
   rtps::{
     constant::*,
     dp_event_loop::{DPEventLoop, DomainInfo, EventLoopCommand},
     reader::*,
+    statistics::StatisticsCounters,
+    vendor_quirks::VendorQuirks,
     writer::WriterIngredients,
   },
-  structure::{dds_cache::DDSCache, entity::RTPSEntity, guid::*, locator::Locator},
+  structure::{
+    dds_cache::DDSCache,
+    entity::RTPSEntity,
+    guid::*,
+    locator::Locator,
+    time::{Clock, SystemClock},
+  },
   StatusEvented,
 };
+#[cfg(feature = "tcp")]
+use crate::network::{tcp_listener::TCPListener, tcp_transport::TcpTransport};
 #[cfg(feature = "security")]
 use crate::{
   create_error_internal, create_error_not_allowed_by_security,
@@ -60,6 +81,155 @@ use crate::{
 #[cfg(not(feature = "security"))]
 use crate::no_security::SecurityPluginsHandle;
 
+/// Backoff policy for the periodic HEARTBEATs that a reliable DataWriter sends
+/// to a matched DataReader that has unacknowledged samples.
+///
+/// The first HEARTBEAT is sent `min_interval` after the previous one. While the
+/// DataReader does not respond with ACKNACK, each following interval is
+/// `factor` times the previous one, but at most `max_interval`. Any ACKNACK
+/// from the DataReader resets the interval back to `min_interval`.
+///
+/// This reduces useless traffic to DataReaders that are slow or gone, and still
+/// recovers when they come back. See
+/// [`DomainParticipantBuilder::heartbeat_backoff`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeartbeatBackoff {
+  pub min_interval: Duration,
+  pub max_interval: Duration,
+  pub factor: f64,
+}
+
+impl Default for HeartbeatBackoff {
+  fn default() -> Self {
+    Self {
+      min_interval: Duration::from(HEARTBEAT_PERIOD_FAST),
+      max_interval: Duration::from_secs(5),
+      factor: 2.0,
+    }
+  }
+}
+
+/// How a reliable DataWriter sends its periodic HEARTBEATs to the matched
+/// DataReaders. See [`DomainParticipantBuilder::heartbeat_delivery`].
+///
+/// A HEARTBEAT sent to a multicast group reaches every DataReader listening to
+/// the group with a single datagram. All DataReaders get the same HEARTBEAT
+/// with the same count, so a DataReader that receives it both by multicast and
+/// by unicast processes it only once. DataReaders always respond with unicast
+/// ACKNACKs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeartbeatDelivery {
+  /// Send one HEARTBEAT to each multicast group that at least `min_readers`
+  /// of the DataReaders are reached at, and unicast to the other DataReaders.
+  Multicast { min_readers: usize },
+  /// Send the HEARTBEAT by unicast to each DataReader.
+  Unicast,
+}
+
+impl Default for HeartbeatDelivery {
+  fn default() -> Self {
+    Self::Multicast { min_readers: 1 }
+  }
+}
+
+/// How a reliable DataWriter with many matched DataReaders repairs lost
+/// samples. See [`DomainParticipantBuilder::large_fan_out`].
+///
+/// Without this, a DataWriter repairs each DataReader separately by unicast,
+/// so the repair load grows with the number of DataReaders that lose a sample.
+/// See [`DataWriter::fan_out_statistics`](crate::with_key::DataWriter::fan_out_statistics)
+/// for the current load.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LargeFanOut {
+  /// The policy applies to DataWriters with at least this many matched
+  /// DataReaders.
+  pub min_readers: usize,
+  /// Send a repaired sample once to the multicast group of the requesting
+  /// DataReader. Other DataReaders of the group that are missing the same
+  /// sample then get it from the same datagram. Fragmented samples and samples
+  /// written to a single DataReader are always repaired by unicast.
+  pub multicast_repair: bool,
+  /// Repair at most this many samples by unicast to one DataReader between two
+  /// periodic HEARTBEATs. The rest is repaired when the DataReader asks again.
+  /// `None` = no limit.
+  pub max_unicast_repairs_per_reader: Option<usize>,
+}
+
+impl Default for LargeFanOut {
+  fn default() -> Self {
+    Self {
+      min_readers: 32,
+      multicast_repair: true,
+      max_unicast_repairs_per_reader: Some(64),
+    }
+  }
+}
+
+/// Delivery of samples from BestEffort DataWriters in sequence, despite
+/// reordering in the network. See
+/// [`DomainParticipantBuilder::best_effort_reordering`].
+///
+/// A sample that arrives after a gap in the sequence is held until the
+/// missing samples arrive, so that the DataReader gets the samples of each
+/// DataWriter in the order they were written. A gap is given up as lost when
+/// a held sample has waited for `max_delay`, or when `max_samples` samples
+/// are held. A missing sample that arrives after its gap was given up is
+/// dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BestEffortReordering {
+  /// Longest time a sample is held waiting for earlier ones
+  pub max_delay: Duration,
+  /// Largest number of samples held for one DataWriter
+  pub max_samples: usize,
+}
+
+impl Default for BestEffortReordering {
+  fn default() -> Self {
+    Self {
+      max_delay: Duration::from_millis(20),
+      max_samples: 64,
+    }
+  }
+}
+
+/// Garbage collection of partially received fragmented samples. See
+/// [`DomainParticipantBuilder::fragment_assembly`].
+///
+/// A sample whose fragments stop arriving is dropped when no fragment of it
+/// has been received for `timeout`. Partially received samples are checked at
+/// most once per `gc_interval`, so a sample may be dropped up to
+/// `timeout + gc_interval` after its last fragment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FragmentAssembly {
+  /// How long a partially received sample waits for its next fragment
+  pub timeout: Duration,
+  /// Shortest interval between checks for timed out samples
+  pub gc_interval: Duration,
+}
+
+impl Default for FragmentAssembly {
+  fn default() -> Self {
+    Self {
+      timeout: Duration::from_secs(10),
+      gc_interval: Duration::from_secs(2),
+    }
+  }
+}
+
+/// Which timestamp of a received sample a DataReader measures its requested
+/// DEADLINE against. See [`DomainParticipantBuilder::deadline_timestamp`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeadlineTimestamp {
+  /// The time the sample was received. Network latency counts towards the
+  /// deadline.
+  #[default]
+  Reception,
+  /// The source timestamp set by the DataWriter, or the reception time if the
+  /// sample has none. Meaningful only if the clocks of the participants are
+  /// synchronized.
+  Source,
+}
+
 /// Builder object to create a [`DomainParticipant`] with non-default
 /// configuration.
 ///
@@ -71,8 +241,48 @@ pub struct DomainParticipantBuilder {
 
   only_networks: Option<Vec<IpAddr>>, /* optional IP address filter for discovery advertisements
                                        * and multicast setup */
+  interface_filter: InterfaceFilter, // network interfaces to use
+  advertised_addresses: Option<Vec<IpAddr>>, // use these instead of enumerating interfaces
+  fallback_addresses: Option<Vec<IpAddr>>, // use these if interface enumeration fails
+  discovery_multicast: DiscoveryMulticast, // SPDP multicast group, scope and TTL
+  path_mtu: PathMtu,                 // path MTU to remote destinations
+  #[cfg(feature = "tcp")]
+  tcp_transport: Option<TcpTransport>, // RTPS over TCP instead of UDP
+  initial_peers: Vec<SocketAddr>,    // unicast SPDP destinations
+  unicast_discovery_only: bool,      // no discovery multicast
 
   same_host_loopback: bool, // prefer loopback for same-host peers + localhost SPDP discovery peers
+  accept_own_spdp: bool,    // process SPDP messages sent by ourselves
+
+  participant_id: Option<u16>,     // selects our well-known unicast ports
+  tenant_id: Option<u16>,          // embedded in our GUID prefix
+  discover_only_same_tenant: bool, // ignore participants with a different tenant_id
+  in_process_discovery: bool,      /* exchange SPDP directly with participants of this
+                                    * process */
+  lightweight_discovery: bool, // discover participants only, not their endpoints
+  read_only: bool,             // subscribe only, never publish
+  topic_type_registry: TopicTypeRegistry, // expected type names of topics
+  durability_store: Arc<dyn DurabilityStore>, // samples of Transient DataWriters
+
+  heartbeat_backoff: Option<HeartbeatBackoff>, // for reliable DataWriters
+  heartbeat_period: Option<Duration>,          // for reliable DataWriters
+  heartbeat_response_delay: Duration,          // for reliable DataReaders
+  heartbeat_delivery: HeartbeatDelivery,       // multicast or unicast HEARTBEATs
+  large_fan_out: Option<LargeFanOut>,          // repair policy for many matched readers
+  max_coalesce_delay: Option<Duration>,        // hold samples to coalesce datagrams
+  vendor_quirks: VendorQuirks,                 // workarounds for other implementations
+  record_reception_context: bool,              // store ReceptionContext with received samples
+  max_fragmented_sample_size: usize,           // largest sample accepted in DATA_FRAGs
+  best_effort_reordering: Option<BestEffortReordering>, // reorder BestEffort samples
+  proactive_nackfrag_delay: Option<Duration>,  // request fragment gaps without HEARTBEAT
+  fragment_assembly: FragmentAssembly,         // expiry of partially received samples
+  deadline_timestamp: DeadlineTimestamp,       // timestamp for requested deadline checks
+  clock: Arc<dyn Clock>,                       // for reception timestamps and deadlines
+  shutdown_grace_period: Duration,             // to send disposals when dropped
+
+  participant_status_channel: StatusChannelConfig,
+  datawriter_status_channel: StatusChannelConfig,
+  datareader_status_channel: StatusChannelConfig,
 
   socket_receive_buffer_size: usize,
   socket_send_buffer_size: usize,
@@ -88,7 +298,43 @@ impl DomainParticipantBuilder {
     DomainParticipantBuilder {
       domain_id,
       only_networks: None,
+      interface_filter: InterfaceFilter::default(),
+      advertised_addresses: None,
+      fallback_addresses: None,
+      discovery_multicast: DiscoveryMulticast::default(),
+      path_mtu: PathMtu::default(),
+      #[cfg(feature = "tcp")]
+      tcp_transport: None,
+      initial_peers: Vec::new(),
+      unicast_discovery_only: false,
       same_host_loopback: true,
+      accept_own_spdp: false,
+      participant_id: None,
+      tenant_id: None,
+      discover_only_same_tenant: false,
+      in_process_discovery: false,
+      lightweight_discovery: false,
+      read_only: false,
+      topic_type_registry: TopicTypeRegistry::default(),
+      durability_store: Arc::new(InMemoryDurabilityStore::new()),
+      heartbeat_backoff: None,
+      heartbeat_period: None,
+      heartbeat_response_delay: Duration::ZERO,
+      heartbeat_delivery: HeartbeatDelivery::default(),
+      large_fan_out: None,
+      max_coalesce_delay: None,
+      vendor_quirks: VendorQuirks::default(),
+      record_reception_context: false,
+      max_fragmented_sample_size: DEFAULT_MAX_FRAGMENTED_SAMPLE_SIZE,
+      best_effort_reordering: None,
+      proactive_nackfrag_delay: None,
+      fragment_assembly: FragmentAssembly::default(),
+      deadline_timestamp: DeadlineTimestamp::default(),
+      clock: Arc::new(SystemClock),
+      shutdown_grace_period: Self::DEFAULT_SHUTDOWN_GRACE_PERIOD,
+      participant_status_channel: StatusChannelConfig::new(2048, StatusOverflowPolicy::DropNewest),
+      datawriter_status_channel: StatusChannelConfig::default(),
+      datareader_status_channel: StatusChannelConfig::default(),
       socket_receive_buffer_size: Self::DEFAULT_SOCKET_RECEIVE_BUFFER_SIZE,
       socket_send_buffer_size: Self::DEFAULT_SOCKET_SEND_BUFFER_SIZE,
       #[cfg(feature = "security")]
@@ -111,6 +357,120 @@ impl DomainParticipantBuilder {
     self
   }
 
+  /// Select the network interfaces used for multicast and advertised in
+  /// discovery by name or address (default: all interfaces).
+  ///
+  /// Use this on multi-homed hosts where only some interfaces carry DDS
+  /// traffic. See [`InterfaceFilter`]. [`build`](Self::build) fails if an
+  /// interface name is empty or an address is unspecified, or if no interface
+  /// is selected.
+  pub fn network_interfaces(mut self, interface_filter: InterfaceFilter) -> Self {
+    self.interface_filter = interface_filter;
+    self
+  }
+
+  /// Use exactly these local IP addresses for multicast and discovery
+  /// advertisements, bypassing network interface enumeration entirely.
+  ///
+  /// This is intended for systems where interface enumeration is unreliable or
+  /// reports addresses that are not reachable by peers (e.g. some containers).
+  /// The addresses are advertised as given; RustDDS cannot verify that they
+  /// are actually assigned to this host. The
+  /// [`with_only_networks`](Self::with_only_networks) filter, if also set, is
+  /// still applied to them.
+  pub fn advertised_addresses(
+    mut self,
+    addrs: impl IntoIterator<Item = impl Into<IpAddr>>,
+  ) -> Self {
+    self.advertised_addresses = Some(addrs.into_iter().map(Into::into).collect());
+    self
+  }
+
+  /// Local IP addresses to fall back to if network interface enumeration fails
+  /// or finds no interfaces.
+  ///
+  /// Without a fallback, participant construction fails in that case, because
+  /// a participant that cannot advertise any locator cannot communicate.
+  pub fn fallback_addresses(mut self, addrs: impl IntoIterator<Item = impl Into<IpAddr>>) -> Self {
+    self.fallback_addresses = Some(addrs.into_iter().map(Into::into).collect());
+    self
+  }
+
+  /// Set the multicast group, scope and TTL (IPv4) or hop limit (IPv6) of
+  /// discovery (default: IPv4 group 239.255.0.1, link-local scope, TTL 1).
+  ///
+  /// Use a wider scope to discover participants across routers, if the
+  /// network routes multicast. See [`DiscoveryMulticast`] for the default
+  /// group of each scope. [`build`](Self::build) fails if the group is not a
+  /// multicast address of the configured IP version and scope, or the TTL is
+  /// not in range 1..=255.
+  pub fn discovery_multicast(mut self, discovery_multicast: DiscoveryMulticast) -> Self {
+    self.discovery_multicast = discovery_multicast;
+    self
+  }
+
+  /// Set the path MTU to remote destinations (default: the MTU of the local
+  /// interface for peers in a local subnet, else 1500 bytes).
+  ///
+  /// DataWriters fragment samples and pack datagrams to fit the path MTU to
+  /// each matched DataReader. Configure a smaller MTU for peers behind VPNs or
+  /// tunnels to avoid IP fragmentation. See [`PathMtu`].
+  /// [`build`](Self::build) fails if an MTU is not in range 576..=65535 or a
+  /// prefix length is longer than its address.
+  pub fn path_mtu(mut self, path_mtu: PathMtu) -> Self {
+    self.path_mtu = path_mtu;
+    self
+  }
+
+  /// Send and receive all RTPS messages over TCP instead of UDP (default:
+  /// UDP).
+  ///
+  /// Use this where UDP or multicast does not get through, e.g. across
+  /// firewalls or NAT. TCP cannot multicast, so participants are discovered
+  /// only through the initial peers of `tcp_transport`. The participant does
+  /// not listen on UDP at all, so it cannot communicate with UDP participants.
+  ///
+  /// The connections follow the DDS Interoperability Wire Protocol TCP/IP
+  /// PSM. Requires the feature `tcp`. See [`TcpTransport`].
+  #[cfg(feature = "tcp")]
+  pub fn tcp_transport(mut self, tcp_transport: TcpTransport) -> Self {
+    self.tcp_transport = Some(tcp_transport);
+    self
+  }
+
+  /// Announce this participant by unicast to these SPDP peers, in addition to
+  /// multicast (default: none).
+  ///
+  /// Use this where multicast does not get through, e.g. in many cloud and
+  /// container networks. A peer is the address and well-known SPDP unicast
+  /// port of a remote participant, which depends on its domain id and
+  /// [`participant_id`](Self::participant_id). Announcements go to the peers
+  /// on the usual SPDP period whether or not they are up. A participant that
+  /// receives the announcement replies by unicast, so it is enough that one
+  /// side lists the other, and both sides listing each other works as well.
+  /// Combine with [`unicast_discovery_only`](Self::unicast_discovery_only) to
+  /// stop multicast discovery.
+  ///
+  /// [`build`](Self::build) fails if a peer has port 0 or a multicast or
+  /// unspecified address, or if `tcp_transport` is also set. The
+  /// `TcpTransport` has initial peers of its own.
+  pub fn initial_peers(mut self, peers: impl IntoIterator<Item = SocketAddr>) -> Self {
+    self.initial_peers = peers.into_iter().collect();
+    self
+  }
+
+  /// Do not use multicast for discovery (default: disabled).
+  ///
+  /// The participant neither listens to the discovery multicast group nor
+  /// announces itself to it. It then discovers and is discovered only through the
+  /// [`initial_peers`](Self::initial_peers), the localhost peers of
+  /// [`same_host_loopback`](Self::same_host_loopback), and peers that list
+  /// this participant. User traffic is not affected.
+  pub fn unicast_discovery_only(mut self, enabled: bool) -> Self {
+    self.unicast_discovery_only = enabled;
+    self
+  }
+
   /// Enable/disable same-host communication over loopback (default: enabled).
   ///
   /// When enabled, the participant (a) additionally announces SPDP to the
@@ -125,8 +485,417 @@ impl DomainParticipantBuilder {
     self
   }
 
+  /// Process SPDP participant announcements that this participant receives
+  /// from itself (default: disabled).
+  ///
+  /// A participant usually receives its own announcements back, e.g. via
+  /// multicast loopback. By default these are dropped, because the participant
+  /// already knows itself. Enabling this is only useful for testing discovery
+  /// with a single participant.
+  ///
+  /// This does not affect user data: a DataReader always receives samples from
+  /// DataWriters of the same participant.
+  pub fn accept_own_spdp(mut self, enabled: bool) -> Self {
+    self.accept_own_spdp = enabled;
+    self
+  }
+
+  /// Use exactly this participant id (default: the smallest free one).
+  ///
+  /// The participant id selects the well-known unicast ports of this
+  /// participant (RTPS spec v2.5 Section 9.6.2.3). By default RustDDS tries
+  /// participant ids from 0 upwards until it finds one whose discovery port is
+  /// free. With an explicit participant id, [`build`](Self::build) fails with
+  /// [`CreateError::Bind`] instead, if the port cannot be bound, e.g. because
+  /// another participant on this host already uses the same id. The
+  /// application can then retry with another id.
+  ///
+  /// The participant id must be less than 120.
+  pub fn participant_id(mut self, participant_id: u16) -> Self {
+    self.participant_id = Some(participant_id);
+    self
+  }
+
+  /// Embed a tenant (application) identifier in the GUID prefix of this
+  /// participant.
+  ///
+  /// This allows running several logically isolated RustDDS systems on the
+  /// same network and domain id, as a lightweight alternative to a domain tag.
+  /// Combine with
+  /// [`discover_only_same_tenant`](Self::discover_only_same_tenant) to make
+  /// discovery ignore participants of other tenants.
+  ///
+  /// RustDDS GUID prefixes consist of our 2-byte vendor id followed by random
+  /// bytes. No host id or process id is encoded. The tenant id occupies the two
+  /// bytes after the vendor id, so 8 random bytes remain to keep prefixes
+  /// unique. Other DDS implementations do not use this layout.
+  ///
+  /// With the `security` feature, the GUID prefix of an authenticated
+  /// participant is derived from its identity certificate, so it cannot carry
+  /// a tenant id. [`build`](Self::build) fails if a tenant id is combined with
+  /// security.
+  pub fn tenant_id(mut self, tenant_id: u16) -> Self {
+    self.tenant_id = Some(tenant_id);
+    self
+  }
+
+  /// Only discover remote participants that have the same tenant id as this
+  /// participant (default: disabled).
+  ///
+  /// The filter is applied to SPDP and SEDP data before anything is added to
+  /// the discovery database, so participants of other tenants are never
+  /// matched. Participants of other DDS vendors never carry a tenant id, so
+  /// they are ignored, too. This has no effect unless
+  /// [`tenant_id`](Self::tenant_id) is set.
+  pub fn discover_only_same_tenant(mut self, enabled: bool) -> Self {
+    self.discover_only_same_tenant = enabled;
+    self
+  }
+
+  /// Discover other participants of the same process directly, without
+  /// waiting for SPDP over the network (default: disabled).
+  ///
+  /// Participants that enable this and run in the same process and domain
+  /// deliver their SPDP announcements to each other in memory. A new
+  /// participant is therefore discovered as soon as it has started, even if
+  /// multicast does not work on the host. This is mostly useful for tests and
+  /// for local development where all participants run in one process.
+  ///
+  /// Only participant discovery is done in memory. Endpoint discovery and user
+  /// data still use the network, normally loopback. Participants in other
+  /// processes, or in this process but without this setting, are discovered
+  /// over the network as usual.
+  ///
+  /// In-process announcements are not authenticated, so
+  /// [`build`](Self::build) fails if this is combined with security.
+  pub fn in_process_discovery(mut self, enabled: bool) -> Self {
+    self.in_process_discovery = enabled;
+    self
+  }
+
+  /// Discover only participants, not their Readers and Writers (default:
+  /// disabled).
+  ///
+  /// Normally every pair of participants runs reliable endpoint discovery
+  /// (SEDP) and liveliness messaging with each other, so each participant
+  /// keeps reliable reader and writer state per remote participant for
+  /// several built-in topics. In a large domain this is a lot of state and
+  /// traffic for a node that only wants to know which participants exist,
+  /// e.g. a monitoring tool.
+  ///
+  /// With lightweight discovery, this participant only announces itself and
+  /// listens to the announcements of others (SPDP), which is best-effort and
+  /// needs no per-participant reliability state. Remote participants are
+  /// still discovered, reported as
+  /// [`ParticipantDiscovered`](crate::DomainParticipantStatusEvent::ParticipantDiscovered)
+  /// and lost when their lease expires, and listed by
+  /// [`discovered_participants`](crate::DomainParticipant::discovered_participants).
+  ///
+  /// What is dropped:
+  /// * Endpoint discovery with remote participants. Their Readers, Writers and
+  ///   Topics are not discovered, and they do not discover ours. Local
+  ///   DataReaders and DataWriters therefore match only with each other, never
+  ///   with remote ones.
+  /// * Participant liveliness messages, so remote DataWriters with `Automatic`
+  ///   or `ManualByParticipant` liveliness cannot be followed.
+  ///
+  /// This participant advertises only its SPDP endpoints, so that remote
+  /// participants do not try to run endpoint discovery with it either.
+  ///
+  /// Security needs endpoint discovery for authentication, so
+  /// [`build`](Self::build) fails if this is combined with security.
+  pub fn lightweight_discovery(mut self, enabled: bool) -> Self {
+    self.lightweight_discovery = enabled;
+    self
+  }
+
+  /// Make this a read-only participant, which subscribes but never publishes,
+  /// e.g. a monitoring node. This reduces the discovery traffic caused by
+  /// this participant.
+  ///
+  /// DataWriters cannot be created in a read-only participant: creating one
+  /// fails with [`BadParameter`](crate::dds::CreateError::BadParameter).
+  /// Publishers can still be created, but they stay empty.
+  ///
+  /// Reliable subscription needs only these built-in endpoints, which remain
+  /// in use:
+  /// * the SPDP writer and reader, to announce this participant with its
+  ///   locators, and to discover remote participants,
+  /// * the SEDP subscriptions writer, to announce our DataReaders, so that
+  ///   remote DataWriters match them,
+  /// * the SEDP publications and subscriptions readers, to discover remote
+  ///   DataWriters and DataReaders, and the SEDP topics reader, and
+  /// * the participant message reader, to follow the liveliness of remote
+  ///   DataWriters.
+  ///
+  /// The other built-in writers, i.e. the SEDP publications and topics writers
+  /// and the participant message writer, are not advertised to remote
+  /// participants, nor matched with their readers, so they send nothing. Remote
+  /// participants thus see this participant as one that only subscribes.
+  ///
+  /// Unlike [`lightweight_discovery`](Self::lightweight_discovery), this keeps
+  /// endpoint discovery running, so local DataReaders match remote
+  /// DataWriters.
+  ///
+  /// The secure built-in endpoints are not reduced, so [`build`](Self::build)
+  /// fails if this is combined with security.
+  pub fn read_only(mut self, enabled: bool) -> Self {
+    self.read_only = enabled;
+    self
+  }
+
+  /// How long dropping the DomainParticipant may wait for its last messages
+  /// to be sent (default: 100 ms).
+  ///
+  /// On drop, the participant disposes itself and its DataWriters and
+  /// DataReaders over discovery, so that remote participants forget them at
+  /// once instead of waiting for the lease to expire. These messages are
+  /// normally sent without delay. The grace period only matters when the send
+  /// sockets are congested. After it, the remaining messages are dropped. A
+  /// zero grace period still sends what fits in the socket buffers.
+  pub fn shutdown_grace_period(mut self, grace_period: Duration) -> Self {
+    self.shutdown_grace_period = grace_period;
+    self
+  }
+
+  /// Check the type names of topics against `registry`, see
+  /// [`TopicTypeRegistry`]. By default no topic is checked.
+  pub fn topic_type_registry(mut self, registry: TopicTypeRegistry) -> Self {
+    self.topic_type_registry = registry;
+    self
+  }
+
+  /// Back off the HEARTBEAT rate of reliable DataWriters towards DataReaders
+  /// that do not acknowledge (default: disabled).
+  ///
+  /// When disabled, a DataWriter with unacknowledged samples sends HEARTBEATs
+  /// to all of its matched DataReaders at a fixed, fast period. See
+  /// [`HeartbeatBackoff`] for how the interval grows when enabled.
+  /// [`build`](Self::build) fails if `factor` is less than 1, or
+  /// `max_interval` is less than `min_interval`, or `min_interval` is zero.
+  pub fn heartbeat_backoff(mut self, backoff: HeartbeatBackoff) -> Self {
+    self.heartbeat_backoff = Some(backoff);
+    self
+  }
+
+  /// Period of the HEARTBEATs of reliable DataWriters when all matched
+  /// DataReaders have acknowledged everything (default: 1 s).
+  ///
+  /// While some DataReader is behind, HEARTBEATs are sent more often, every
+  /// 100 ms or `period` if that is shorter. A longer period reduces idle
+  /// traffic, e.g. with many matched DataReaders, at the cost of noticing lost
+  /// samples later. DataWriters with MANUAL_BY_TOPIC liveliness send
+  /// HEARTBEATs according to their lease duration instead.
+  /// [`build`](Self::build) fails if `period` is zero.
+  pub fn heartbeat_period(mut self, period: Duration) -> Self {
+    self.heartbeat_period = Some(period);
+    self
+  }
+
+  /// Let reliable DataReaders delay their response to a HEARTBEAT by up to
+  /// `delay` (default: zero, i.e. respond at once).
+  ///
+  /// Each response is delayed randomly between `delay / 2` and `delay`, so
+  /// that the DataReaders matched with one DataWriter do not all respond at
+  /// the same moment, and HEARTBEATs received meanwhile are answered with a
+  /// single ACKNACK. The response reports what has been received by the time
+  /// it is sent. Keep `delay` short for low-latency repair of lost samples.
+  pub fn heartbeat_response_delay(mut self, delay: Duration) -> Self {
+    self.heartbeat_response_delay = delay;
+    self
+  }
+
+  /// How reliable DataWriters send their periodic HEARTBEATs (default:
+  /// multicast to every group that a DataReader is reached at).
+  ///
+  /// With [`HeartbeatDelivery::Multicast`], DataReaders reached at the same
+  /// multicast group share one HEARTBEAT datagram, so the HEARTBEAT traffic of
+  /// a DataWriter does not grow with the number of DataReaders. Raise
+  /// `min_readers` to avoid multicasting HEARTBEATs, which all participants on
+  /// the group receive, for just one or a few DataReaders.
+  /// [`build`](Self::build) fails if `min_readers` is zero.
+  pub fn heartbeat_delivery(mut self, delivery: HeartbeatDelivery) -> Self {
+    self.heartbeat_delivery = delivery;
+    self
+  }
+
+  /// Repair lost samples of reliable DataWriters with many matched DataReaders
+  /// according to `policy` (default: disabled).
+  ///
+  /// This is meant for telemetry-style topics with a large number of
+  /// subscribers, where repairing each DataReader separately becomes a
+  /// bottleneck. See [`LargeFanOut`]. [`build`](Self::build) fails if
+  /// `min_readers` or `max_unicast_repairs_per_reader` is zero.
+  pub fn large_fan_out(mut self, policy: LargeFanOut) -> Self {
+    self.large_fan_out = Some(policy);
+    self
+  }
+
+  /// Let DataWriters hold a sample back for at most `max_delay` to send it
+  /// together with later samples in one datagram (default: disabled).
+  ///
+  /// DataWriters always coalesce small samples that are already waiting to be
+  /// sent. With this setting they also wait for more samples, until either the
+  /// datagram is full or the oldest waiting sample has been held for
+  /// `max_delay`. This bounds the latency added by batching, and makes it
+  /// usable on latency-sensitive topics. Built-in (discovery) DataWriters never
+  /// hold samples, and a DataWriter can choose its own delay with
+  /// [`DataWriter::set_max_coalesce_delay`](crate::with_key::DataWriter::set_max_coalesce_delay). The added latency is reported by
+  /// [`DataWriter::coalescing_statistics`](crate::with_key::DataWriter::coalescing_statistics).
+  pub fn max_coalesce_delay(mut self, max_delay: Duration) -> Self {
+    self.max_coalesce_delay = Some(max_delay);
+    self
+  }
+
+  /// Configure workarounds for known deviations of other RTPS
+  /// implementations. By default each workaround is applied only to peers
+  /// detected to need it. See [`VendorQuirks`].
+  pub fn vendor_quirks(mut self, vendor_quirks: VendorQuirks) -> Self {
+    self.vendor_quirks = vendor_quirks;
+    self
+  }
+
+  /// Store the RTPS routing context (source and destination GuidPrefix, source
+  /// timestamp, reply locators) with every received sample, available from
+  /// [`SampleInfo::reception_context`](crate::SampleInfo::reception_context)
+  /// (default: disabled).
+  ///
+  /// This is meant for relays and monitoring tools. It costs an allocation per
+  /// received sample, so leave it disabled otherwise.
+  pub fn record_reception_context(mut self, enabled: bool) -> Self {
+    self.record_reception_context = enabled;
+    self
+  }
+
+  /// Largest sample, in bytes, that DataReaders accept in fragments
+  /// (DATA_FRAG submessages). The default is 256 MiB.
+  ///
+  /// A fragmented sample is reassembled into a buffer of the size the writer
+  /// announces in its first received fragment. Samples announced to be larger
+  /// than this are dropped before anything is allocated, so that a faulty or
+  /// malicious writer cannot exhaust memory. A reliable DataReader treats a
+  /// dropped sample as lost, and does not request it again.
+  pub fn max_fragmented_sample_size(mut self, size: usize) -> Self {
+    self.max_fragmented_sample_size = size;
+    self
+  }
+
+  /// Deliver the samples of each BestEffort DataWriter to DataReaders in
+  /// sequence, holding samples that arrive out of order according to `policy`
+  /// (default: disabled, samples are delivered as they arrive).
+  ///
+  /// This is meant for streams, e.g. media or telemetry, that tolerate
+  /// occasional loss but not reordering, which may happen when samples arrive
+  /// both by multicast and unicast, or over several paths. It applies to
+  /// BestEffort DataReaders only; Reliable ones always deliver in sequence. See
+  /// [`BestEffortReordering`]. [`build`](Self::build) fails if `max_delay` or
+  /// `max_samples` is zero.
+  pub fn best_effort_reordering(mut self, policy: BestEffortReordering) -> Self {
+    self.best_effort_reordering = Some(policy);
+    self
+  }
+
+  /// Let reliable DataReaders request the missing fragments of a partially
+  /// received sample when they have seen a gap in its fragments for `delay`
+  /// (default: disabled).
+  ///
+  /// Normally missing fragments are requested only in response to a HEARTBEAT
+  /// or HEARTBEAT_FRAG from the DataWriter. A gap is a missing fragment below
+  /// the highest fragment received, so fragments that have not been sent yet
+  /// are not requested. This speeds up the repair of large samples on lossy
+  /// links. Set `delay` longer than the reordering on the network, so that
+  /// fragments that are only late are not requested. A sample is requested at
+  /// most once per `delay`, also counting requests made in response to
+  /// HEARTBEATs. [`build`](Self::build) fails if `delay` is zero.
+  pub fn proactive_nackfrag_delay(mut self, delay: Duration) -> Self {
+    self.proactive_nackfrag_delay = Some(delay);
+    self
+  }
+
+  /// Set how long DataReaders keep partially received fragmented samples
+  /// (default: [`FragmentAssembly::default`], 10 s timeout checked every 2 s).
+  ///
+  /// A dropped sample is reported to its DataReader as
+  /// [`DataReaderStatus::SampleReassemblyFailed`](crate::dds::statusevents::DataReaderStatus::SampleReassemblyFailed).
+  /// Large samples on slow or lossy links may need a longer timeout, whereas
+  /// a shorter one releases the memory of abandoned samples sooner.
+  /// [`build`](Self::build) fails if `timeout` or `gc_interval` is zero.
+  pub fn fragment_assembly(mut self, timing: FragmentAssembly) -> Self {
+    self.fragment_assembly = timing;
+    self
+  }
+
+  /// Measure the requested DEADLINE of DataReaders from the timestamp
+  /// `timestamp` of the latest received sample (default:
+  /// [`DeadlineTimestamp::Reception`]).
+  ///
+  /// With reception timestamps, network latency and its variation count
+  /// towards the deadline, so a deadline close to the writing period may be
+  /// reported missed although the DataWriter kept it. With
+  /// [`DeadlineTimestamp::Source`], the deadline follows the writing times
+  /// instead, which requires that the clocks of the participants are
+  /// synchronized. Either way, the current time is read from the participant
+  /// [`clock`](Self::clock).
+  pub fn deadline_timestamp(mut self, timestamp: DeadlineTimestamp) -> Self {
+    self.deadline_timestamp = timestamp;
+    self
+  }
+
+  /// Read the current time from `clock` instead of the system clock
+  /// ([`SystemClock`]), e.g. for deterministic replay or hardware-in-the-loop
+  /// simulation with controlled time. See [`Clock`] for what follows the
+  /// clock.
+  ///
+  /// The clock should not run backwards. Reception timestamps are kept
+  /// strictly increasing regardless.
+  pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+    self.clock = clock;
+    self
+  }
+
+  /// Keep the samples of Transient and Persistent DataWriters in `store`, and
+  /// replay them from there to Transient and Persistent DataReaders. The
+  /// default is an [`InMemoryDurabilityStore`] of this participant only. Give
+  /// several participants the same store to let their DataReaders receive
+  /// samples written by participants that are gone.
+  pub fn durability_store(mut self, store: Arc<dyn DurabilityStore>) -> Self {
+    self.durability_store = store;
+    self
+  }
+
+  /// Depth and overflow policy of the status event channel of the
+  /// DomainParticipant, see
+  /// [`DomainParticipant::status_listener`](crate::DomainParticipant::status_listener).
+  /// The default depth is 2048 events, and new events are dropped when the
+  /// channel is full.
+  pub fn participant_status_channel(mut self, config: StatusChannelConfig) -> Self {
+    self.participant_status_channel = config;
+    self
+  }
+
+  /// Depth and overflow policy of the status event channel of each DataWriter
+  /// of this participant. The default is [`StatusChannelConfig::default`]:
+  /// a few events, and new events are dropped when the channel is full.
+  ///
+  /// An application that must see every event, e.g. every
+  /// `PublicationMatched`, should make the channel deeper or use
+  /// [`StatusOverflowPolicy::Block`].
+  pub fn datawriter_status_channel(mut self, config: StatusChannelConfig) -> Self {
+    self.datawriter_status_channel = config;
+    self
+  }
+
+  /// Depth and overflow policy of the status event channel of each DataReader
+  /// of this participant. The default is [`StatusChannelConfig::default`], as
+  /// for [`datawriter_status_channel`](Self::datawriter_status_channel).
+  pub fn datareader_status_channel(mut self, config: StatusChannelConfig) -> Self {
+    self.datareader_status_channel = config;
+    self
+  }
+
   pub const DEFAULT_SOCKET_RECEIVE_BUFFER_SIZE: usize = 8 * 1024 * 1024;
   pub const DEFAULT_SOCKET_SEND_BUFFER_SIZE: usize = 8 * 1024 * 1024;
+  pub const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_millis(100);
 
   /// Requested `SO_RCVBUF` (kernel receive buffer) for every UDP listener
   /// socket, in bytes. A large receive buffer absorbs traffic bursts before the
@@ -185,6 +954,79 @@ impl DomainParticipantBuilder {
   }
 
   pub fn build(#[allow(unused_mut)] mut self) -> CreateResult<DomainParticipant> {
+    if let Some(backoff) = self.heartbeat_backoff {
+      if backoff.min_interval.is_zero()
+        || backoff.max_interval < backoff.min_interval
+        || !(backoff.factor >= 1.0 && backoff.factor.is_finite())
+      {
+        return create_error_bad_parameter!("Invalid heartbeat backoff policy: {backoff:?}");
+      }
+    }
+    if self.heartbeat_delivery == (HeartbeatDelivery::Multicast { min_readers: 0 }) {
+      return create_error_bad_parameter!("HeartbeatDelivery::Multicast min_readers must be >= 1");
+    }
+    if let Some(policy) = self.large_fan_out {
+      if policy.min_readers == 0 || policy.max_unicast_repairs_per_reader == Some(0) {
+        return create_error_bad_parameter!("Invalid large fan-out policy: {policy:?}");
+      }
+    }
+    if let Some(policy) = self.best_effort_reordering {
+      if policy.max_delay.is_zero() || policy.max_samples == 0 {
+        return create_error_bad_parameter!("Invalid best-effort reordering policy: {policy:?}");
+      }
+    }
+    if self.heartbeat_period == Some(Duration::ZERO) {
+      return create_error_bad_parameter!("Heartbeat period must be non-zero");
+    }
+    if self.proactive_nackfrag_delay == Some(Duration::ZERO) {
+      return create_error_bad_parameter!("Proactive NACK_FRAG delay must be non-zero");
+    }
+    if self.fragment_assembly.timeout.is_zero() || self.fragment_assembly.gc_interval.is_zero() {
+      return create_error_bad_parameter!(
+        "Invalid fragment assembly timing: {:?}",
+        self.fragment_assembly
+      );
+    }
+    for config in [
+      self.participant_status_channel,
+      self.datawriter_status_channel,
+      self.datareader_status_channel,
+    ] {
+      if config.depth == 0 {
+        return create_error_bad_parameter!("Status channel depth must be >= 1: {config:?}");
+      }
+    }
+    if let Err(e) = self.discovery_multicast.validate() {
+      return create_error_bad_parameter!("Invalid discovery multicast configuration: {e}");
+    }
+    if let Err(e) = self.path_mtu.validate() {
+      return create_error_bad_parameter!("Invalid path MTU configuration: {e}");
+    }
+    if let Err(e) = self.interface_filter.validate() {
+      return create_error_bad_parameter!("Invalid network interface filter: {e}");
+    }
+    if let Some(peer) = self
+      .initial_peers
+      .iter()
+      .find(|p| p.port() == 0 || p.ip().is_multicast() || p.ip().is_unspecified())
+    {
+      return create_error_bad_parameter!("Invalid initial peer: {peer}");
+    }
+    #[cfg(feature = "tcp")]
+    if self.tcp_transport.is_some() && !self.initial_peers.is_empty() {
+      return create_error_bad_parameter!(
+        "Initial peers cannot be combined with tcp_transport. Use the initial peers of \
+         TcpTransport instead."
+      );
+    }
+    if let Some(participant_id) = self.participant_id {
+      if participant_id >= MAX_PARTICIPANT_ID {
+        return create_error_bad_parameter!(
+          "Participant id must be less than {MAX_PARTICIPANT_ID}: {participant_id}"
+        );
+      }
+    }
+
     // QosPolicies with possible security properties, otherwise default
     let participant_qos = QosPolicies {
       #[cfg(feature = "security")]
@@ -192,13 +1034,38 @@ impl DomainParticipantBuilder {
       ..Default::default()
     };
 
-    let candidate_participant_guid = GUID::new_participant_guid();
+    let candidate_participant_guid = match self.tenant_id {
+      Some(tenant_id) => GUID::new_participant_guid_for_tenant(tenant_id),
+      None => GUID::new_participant_guid(),
+    };
+    let tenant_filter = self.tenant_id.filter(|_| self.discover_only_same_tenant);
+    let in_process_discovery = self.in_process_discovery;
+    let lightweight_discovery = self.lightweight_discovery;
+    let read_only = self.read_only;
     #[cfg(not(feature = "security"))]
     let participant_guid = candidate_participant_guid;
     // If security plugins are present, security is enabled
     #[cfg(feature = "security")]
     let participant_guid = if let Some(ref mut security_plugins) = self.security_plugins.as_mut() {
       trace!("DomainParticipant security construction start");
+      if self.tenant_id.is_some() {
+        return create_error_bad_parameter!(
+          "A tenant id cannot be embedded into the GUID prefix of a secure participant."
+        );
+      }
+      if self.in_process_discovery {
+        return create_error_bad_parameter!(
+          "In-process discovery cannot be used with a secure participant."
+        );
+      }
+      if self.lightweight_discovery {
+        return create_error_bad_parameter!(
+          "Lightweight discovery cannot be used with a secure participant."
+        );
+      }
+      if self.read_only {
+        return create_error_bad_parameter!("A secure participant cannot be read-only.");
+      }
       // Do the security checks according to DDS Security spec v1.1
       // Section "8.8.1 Authentication and AccessControl behavior with local
       // DomainParticipant". The other steps related to Discovery
@@ -301,7 +1168,8 @@ impl DomainParticipantBuilder {
     // participants are discovered at once. A single participant can expose
     // many endpoints (e.g. ~16 in a typical ROS 2 node), so 2048 handles
     // large deployments without silent event loss.
-    let (status_sender, status_receiver) = sync_status_channel(2048)?;
+    let (status_sender, status_receiver) =
+      configured_status_channel(self.participant_status_channel)?;
 
     #[cfg(not(feature = "security"))]
     let security_plugins_handle = None;
@@ -311,6 +1179,7 @@ impl DomainParticipantBuilder {
     // intermediate DP wrapper
     let dp = DomainParticipantDisc::new(
       self.domain_id,
+      self.participant_id,
       participant_guid,
       participant_qos,
       djh_receiver,
@@ -322,8 +1191,40 @@ impl DomainParticipantBuilder {
       security_plugins_handle.clone(),
       self.socket_receive_buffer_size,
       self.socket_send_buffer_size,
-      self.only_networks,
+      InterfaceConfig {
+        only_networks: self.only_networks.map(Into::into),
+        interface_filter: self.interface_filter,
+        advertised_addresses: self.advertised_addresses.map(Into::into),
+        fallback_addresses: self.fallback_addresses.map(Into::into),
+        discovery_multicast: self.discovery_multicast,
+        path_mtu: Arc::new(self.path_mtu),
+        #[cfg(feature = "tcp")]
+        tcp_transport: self.tcp_transport,
+        initial_peers: self.initial_peers.into(),
+        unicast_discovery_only: self.unicast_discovery_only,
+      },
       self.same_host_loopback,
+      self.accept_own_spdp,
+      self.heartbeat_backoff,
+      self.heartbeat_period,
+      self.heartbeat_response_delay,
+      self.heartbeat_delivery,
+      self.large_fan_out,
+      self.max_coalesce_delay,
+      self.vendor_quirks,
+      self.record_reception_context,
+      self.max_fragmented_sample_size,
+      self.best_effort_reordering,
+      self.proactive_nackfrag_delay,
+      self.fragment_assembly,
+      self.deadline_timestamp,
+      self.clock,
+      self.datawriter_status_channel,
+      self.datareader_status_channel,
+      Arc::new(self.topic_type_registry),
+      self.durability_store,
+      read_only,
+      self.shutdown_grace_period,
     )?;
 
     // outer DP wrapper
@@ -348,6 +1249,10 @@ impl DomainParticipantBuilder {
           spdp_liveness_receiver,
           status_sender,
           security_plugins_handle,
+          tenant_filter,
+          in_process_discovery,
+          lightweight_discovery,
+          read_only,
         ) {
           discovery.discovery_event_loop(); // run the event loop
         }
@@ -513,8 +1418,44 @@ impl DomainParticipant {
     self.dpi.lock().unwrap().participant_id()
   }
 
-  pub(crate) fn only_networks(&self) -> Option<Arc<[IpAddr]>> {
-    self.dpi.lock().ok().and_then(|g| g.only_networks())
+  pub(crate) fn datawriter_status_channel(&self) -> StatusChannelConfig {
+    self
+      .dpi
+      .lock()
+      .map(|g| g.datawriter_status_channel())
+      .unwrap_or_default()
+  }
+
+  pub(crate) fn datareader_status_channel(&self) -> StatusChannelConfig {
+    self
+      .dpi
+      .lock()
+      .map(|g| g.datareader_status_channel())
+      .unwrap_or_default()
+  }
+
+  pub(crate) fn topic_type_registry(&self) -> Arc<TopicTypeRegistry> {
+    self
+      .dpi
+      .lock()
+      .map(|g| g.topic_type_registry())
+      .unwrap_or_default()
+  }
+
+  pub(crate) fn durability_store(&self) -> Option<Arc<dyn DurabilityStore>> {
+    self.dpi.lock().ok().map(|g| g.durability_store())
+  }
+
+  /// Is this a read-only participant, see
+  /// [`DomainParticipantBuilder::read_only`]?
+  pub fn is_read_only(&self) -> bool {
+    self.dpi.lock().is_ok_and(|g| g.is_read_only())
+  }
+
+  /// Traffic counters of this participant, e.g. messages sent and received.
+  /// Useful for monitoring. See [`ParticipantStatistics`].
+  pub fn statistics(&self) -> ParticipantStatistics {
+    self.dpi.lock().map(|g| g.statistics()).unwrap_or_default()
   }
 
   /// Gets all DiscoveredTopics from DDS network
@@ -534,6 +1475,39 @@ impl DomainParticipant {
     self.dpi.lock().unwrap().discovered_topics()
   }
 
+  /// QoS of the topic `topic_name`, as defined by this participant, or else as
+  /// published by a remote participant to the DCPSTopic built-in topic.
+  ///
+  /// If the topic is known only from remote DataReaders or DataWriters, this is
+  /// the QoS of one of them, which may differ from the QoS of the topic.
+  /// `None` if the topic is not known.
+  ///
+  /// Use this to align the QoS of local DataReaders and DataWriters with the
+  /// QoS declared for the topic.
+  pub fn topic_qos(&self, topic_name: &str) -> Option<QosPolicies> {
+    self.dpi.lock().unwrap().topic_qos(topic_name)
+  }
+
+  /// Snapshot of the whole discovery database: known participants, local and
+  /// remote readers and writers, and whether each local endpoint matches the
+  /// remote endpoints on its topic. This is meant for debugging discovery,
+  /// e.g. finding out why two endpoints do not match.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use rustdds::DomainParticipant;
+  ///
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let dump = domain_participant.discovery_dump();
+  /// for m in dump.matches.iter() {
+  ///   println!("{} {:?} -> {:?}: {:?}", m.topic_name, m.writer, m.reader, m.status);
+  /// }
+  /// ```
+  pub fn discovery_dump(&self) -> DiscoveryDump {
+    self.dpi.lock().unwrap().discovery_dump()
+  }
+
   /// Manually asserts liveliness, affecting all writers with
   /// LIVELINESS QoS of MANUAL_BY_PARTICIPANT created by
   /// this particular participant.
@@ -550,6 +1524,42 @@ impl DomainParticipant {
     self.dpi.lock()?.assert_liveliness()
   }
 
+  /// Re-reads the local network interfaces and announces the resulting
+  /// locators to the domain.
+  ///
+  /// Call this after the network configuration of the host has changed, e.g.
+  /// an interface went up or down, or an IP address changed, so that remote
+  /// participants learn how to reach this participant again without
+  /// restarting it. The new locators are sent out immediately in SPDP and
+  /// SEDP announcements. Multicast group memberships are also joined on any
+  /// new interfaces.
+  ///
+  /// Multicast sending keeps using the interfaces that were present when the
+  /// participant was created.
+  ///
+  /// Returns `true` if the set of advertised locators changed. If no usable
+  /// network interface is found, an error is returned and the previous
+  /// locators are kept.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rustdds::DomainParticipant;
+  ///
+  /// let domain_participant = DomainParticipant::new(0).expect("Failed to create participant");
+  /// let changed = domain_participant.refresh_locators().unwrap();
+  /// assert!(!changed);
+  /// ```
+  pub fn refresh_locators(&self) -> CreateResult<bool> {
+    // Do not hold the lock while the event loop re-reads the interfaces, so
+    // that Discovery and other calls can proceed meanwhile.
+    let reply_receiver = self.dpi.lock()?.request_interface_refresh()?;
+    let self_locators = reply_receiver
+      .recv_timeout(Duration::from_secs(10))
+      .or_else(|e| create_error_poisoned!("dp_event_loop did not refresh interfaces: {e:?}"))?;
+    self.dpi.lock()?.update_self_locators(self_locators)
+  }
+
   /// Get a `DomainDomainParticipantStatusListener` that can be used
   /// to get `DomainParticipantStatusEvent`s for this DomainParticipant.
   pub fn status_listener(&self) -> DomainParticipantStatusListener {
@@ -756,7 +1766,6 @@ impl PartialEq for DomainParticipant {
 pub struct DomainParticipantWeak {
   dpi: Weak<Mutex<DomainParticipantDisc>>,
   // This struct caches some items to avoid construction deadlocks
-  #[cfg(feature = "security")] // just to avoid warning
   domain_id: u16,
   guid: GUID,
   #[cfg(feature = "security")] // just to avoid warning
@@ -767,7 +1776,6 @@ impl DomainParticipantWeak {
   pub fn new(dp: &DomainParticipant) -> Self {
     Self {
       dpi: Arc::downgrade(&dp.dpi),
-      #[cfg(feature="security")] // just to avoid warning
       domain_id: dp.domain_id(),
       guid: dp.guid(),
       #[cfg(feature="security")] // just to avoid warning
@@ -795,7 +1803,6 @@ impl DomainParticipantWeak {
       .and_then(|dpi| dpi.lock()?.create_subscriber(self, qos))
   }
 
-  #[cfg(feature = "security")] // just to avoid warning
   pub fn domain_id(&self) -> u16 {
     self.domain_id
   }
@@ -851,6 +1858,7 @@ impl DomainParticipantDisc {
   #[allow(clippy::too_many_arguments)]
   pub fn new(
     domain_id: u16,
+    requested_participant_id: Option<u16>,
     participant_guid: GUID,
     qos_policies: QosPolicies,
     discovery_join_handle: mio_channel::Receiver<JoinHandle<()>>,
@@ -862,11 +1870,33 @@ impl DomainParticipantDisc {
     security_plugins_handle: Option<SecurityPluginsHandle>,
     socket_receive_buffer_size: usize,
     socket_send_buffer_size: usize,
-    only_networks: Option<Vec<IpAddr>>,
+    interfaces: InterfaceConfig,
     same_host_loopback: bool,
+    accept_own_spdp: bool,
+    heartbeat_backoff: Option<HeartbeatBackoff>,
+    heartbeat_period: Option<Duration>,
+    heartbeat_response_delay: Duration,
+    heartbeat_delivery: HeartbeatDelivery,
+    large_fan_out: Option<LargeFanOut>,
+    max_coalesce_delay: Option<Duration>,
+    vendor_quirks: VendorQuirks,
+    record_reception_context: bool,
+    max_fragmented_sample_size: usize,
+    best_effort_reordering: Option<BestEffortReordering>,
+    proactive_nackfrag_delay: Option<Duration>,
+    fragment_assembly: FragmentAssembly,
+    deadline_timestamp: DeadlineTimestamp,
+    clock: Arc<dyn Clock>,
+    datawriter_status_channel: StatusChannelConfig,
+    datareader_status_channel: StatusChannelConfig,
+    topic_type_registry: Arc<TopicTypeRegistry>,
+    durability_store: Arc<dyn DurabilityStore>,
+    read_only: bool,
+    shutdown_grace_period: Duration,
   ) -> CreateResult<Self> {
     let dpi = DomainParticipantInner::new(
       domain_id,
+      requested_participant_id,
       participant_guid,
       qos_policies,
       discovery_update_notification_receiver,
@@ -877,8 +1907,29 @@ impl DomainParticipantDisc {
       security_plugins_handle,
       socket_receive_buffer_size,
       socket_send_buffer_size,
-      only_networks,
+      interfaces,
       same_host_loopback,
+      accept_own_spdp,
+      heartbeat_backoff,
+      heartbeat_period,
+      heartbeat_response_delay,
+      heartbeat_delivery,
+      large_fan_out,
+      max_coalesce_delay,
+      vendor_quirks,
+      record_reception_context,
+      max_fragmented_sample_size,
+      best_effort_reordering,
+      proactive_nackfrag_delay,
+      fragment_assembly,
+      deadline_timestamp,
+      clock,
+      datawriter_status_channel,
+      datareader_status_channel,
+      topic_type_registry,
+      durability_store,
+      read_only,
+      shutdown_grace_period,
     )?;
 
     Ok(Self {
@@ -952,12 +2003,40 @@ impl DomainParticipantDisc {
     self.dpi.discovered_topics()
   }
 
+  pub fn topic_qos(&self, topic_name: &str) -> Option<QosPolicies> {
+    self.dpi.topic_qos(topic_name)
+  }
+
+  pub fn discovery_dump(&self) -> DiscoveryDump {
+    self.dpi.discovery_dump()
+  }
+
   pub(crate) fn dds_cache(&self) -> Arc<RwLock<DDSCache>> {
     self.dpi.dds_cache()
   }
 
-  pub(crate) fn only_networks(&self) -> Option<Arc<[IpAddr]>> {
-    self.dpi.only_networks()
+  pub(crate) fn datawriter_status_channel(&self) -> StatusChannelConfig {
+    self.dpi.datawriter_status_channel()
+  }
+
+  pub(crate) fn datareader_status_channel(&self) -> StatusChannelConfig {
+    self.dpi.datareader_status_channel()
+  }
+
+  pub(crate) fn topic_type_registry(&self) -> Arc<TopicTypeRegistry> {
+    self.dpi.topic_type_registry()
+  }
+
+  pub(crate) fn durability_store(&self) -> Arc<dyn DurabilityStore> {
+    self.dpi.durability_store()
+  }
+
+  pub(crate) fn is_read_only(&self) -> bool {
+    self.dpi.is_read_only()
+  }
+
+  pub(crate) fn statistics(&self) -> ParticipantStatistics {
+    self.dpi.statistics()
   }
 
   #[cfg(feature = "security")] // just to avoid warning
@@ -984,6 +2063,24 @@ impl DomainParticipantDisc {
     self.dpi.self_locators.clone()
   }
 
+  pub(crate) fn request_interface_refresh(&self) -> CreateResult<RefreshedLocatorsReceiver> {
+    self.dpi.request_interface_refresh()
+  }
+
+  pub(crate) fn update_self_locators(
+    &mut self,
+    self_locators: HashMap<mio_06::Token, Vec<Locator>>,
+  ) -> CreateResult<bool> {
+    let changed = self.dpi.update_self_locators(self_locators)?;
+    // Announce even if nothing changed, as remote participants may have lost
+    // us while the network was down.
+    self
+      .discovery_command_sender
+      .send(DiscoveryCommand::RefreshLocators)
+      .or_else(|e| create_error_poisoned!("Cannot request Discovery to refresh locators: {e:?}"))?;
+    Ok(changed)
+  }
+
   pub(crate) fn status_channel_receiver(
     &self,
   ) -> &StatusChannelReceiver<DomainParticipantStatusEvent> {
@@ -1022,6 +2119,14 @@ impl Drop for DomainParticipantDisc {
 
     debug!("Waiting for Discovery join.");
     if let Ok(handle) = self.discovery_join_handle.try_recv() {
+      if handle.thread().id() == thread::current().id() {
+        // Discovery itself let go of the last reference to the participant.
+        // It cannot join itself, and it sends the disposals only when it gets
+        // to the stop signal, so the event loop must outlive it.
+        debug!("Dropped by Discovery. Stopping in the background.");
+        self.dpi.discovery_to_outlive = Some(handle);
+        return;
+      }
       handle
         .join()
         .unwrap_or_else(|e| warn!("Failed to join discovery thread: {e:?}"));
@@ -1062,37 +2167,81 @@ pub(crate) struct DomainParticipantInner {
 
   security_plugins_handle: Option<SecurityPluginsHandle>,
 
-  only_networks: Option<Arc<[IpAddr]>>,
+  datawriter_status_channel: StatusChannelConfig,
+  datareader_status_channel: StatusChannelConfig,
+  topic_type_registry: Arc<TopicTypeRegistry>,
+  durability_store: Arc<dyn DurabilityStore>,
+  read_only: bool,
+  shutdown_grace_period: Duration,
+  // Set on drop, if Discovery is still running. See the Drop impl.
+  discovery_to_outlive: Option<JoinHandle<()>>,
+
+  // Updated by the event loop thread
+  statistics: Arc<StatisticsCounters>,
 }
 
 impl Drop for DomainParticipantInner {
   fn drop(&mut self) {
-    // if send has an error simply leave as we have lost control of the
-    // ev_loop_thread anyways
-    if self.stop_poll_sender.send(EventLoopCommand::Stop).is_err() {
-      error!("dp_event_loop not responding to stop discovery_command");
+    let Some(ev_loop_handle) = self.ev_loop_handle.take() else {
+      error!("Someone managed to steal dp_event_loop join handle from DomainParticipantInner.");
       return;
-    }
-
-    debug!("Waiting for dp_event_loop join");
-    match self.ev_loop_handle.take() {
-      Some(join_handle) => {
-        join_handle
-          .join()
-          .unwrap_or_else(|e| warn!("Failed to join dp_event_loop: {e:?}"));
-      }
-      None => {
-        error!("Someone managed to steal dp_event_loop join handle from DomainParticipantInner.");
+    };
+    let stop_poll_sender = self.stop_poll_sender.clone();
+    let grace_period = self.shutdown_grace_period;
+
+    match self.discovery_to_outlive.take() {
+      None => stop_event_loop(&stop_poll_sender, ev_loop_handle, grace_period),
+      Some(discovery_handle) => {
+        // We are in the Discovery thread, which has yet to write the disposals.
+        // Stop the event loop once they are written, without blocking here.
+        let spawned = thread::Builder::new()
+          .name("RustDDS shutdown".to_string())
+          .spawn(move || {
+            discovery_handle
+              .join()
+              .unwrap_or_else(|e| warn!("Failed to join discovery thread: {e:?}"));
+            stop_event_loop(&stop_poll_sender, ev_loop_handle, grace_period);
+          });
+        if let Err(e) = spawned {
+          error!("Cannot stop dp_event_loop in the background: {e}");
+        }
       }
     }
-    debug!("Joined dp_event_loop");
   }
 }
 
+// Discovery has written its disposals by now. The event loop sends them before
+// stopping.
+fn stop_event_loop(
+  stop_poll_sender: &mio_channel::Sender<EventLoopCommand>,
+  ev_loop_handle: JoinHandle<()>,
+  grace_period: Duration,
+) {
+  // if send has an error simply leave as we have lost control of the
+  // ev_loop_thread anyways
+  if stop_poll_sender
+    .send(EventLoopCommand::Stop { grace_period })
+    .is_err()
+  {
+    error!("dp_event_loop not responding to stop discovery_command");
+    return;
+  }
+
+  debug!("Waiting for dp_event_loop join");
+  ev_loop_handle
+    .join()
+    .unwrap_or_else(|e| warn!("Failed to join dp_event_loop: {e:?}"));
+  debug!("Joined dp_event_loop");
+}
+
+// Where the event loop replies with the locators after re-reading interfaces.
+type RefreshedLocatorsReceiver = std::sync::mpsc::Receiver<HashMap<mio_06::Token, Vec<Locator>>>;
+
 impl DomainParticipantInner {
   #[allow(clippy::too_many_arguments)]
   fn new(
     domain_id: u16,
+    requested_participant_id: Option<u16>,
     participant_guid: GUID,
     _qos_policies: QosPolicies,
     discovery_update_notification_receiver: mio_channel::Receiver<DiscoveryNotificationType>,
@@ -1103,108 +2252,66 @@ impl DomainParticipantInner {
     security_plugins_handle: Option<SecurityPluginsHandle>,
     socket_receive_buffer_size: usize,
     socket_send_buffer_size: usize,
-    only_networks: Option<Vec<IpAddr>>,
+    interfaces: InterfaceConfig,
     same_host_loopback: bool,
+    accept_own_spdp: bool,
+    heartbeat_backoff: Option<HeartbeatBackoff>,
+    heartbeat_period: Option<Duration>,
+    heartbeat_response_delay: Duration,
+    heartbeat_delivery: HeartbeatDelivery,
+    large_fan_out: Option<LargeFanOut>,
+    max_coalesce_delay: Option<Duration>,
+    vendor_quirks: VendorQuirks,
+    record_reception_context: bool,
+    max_fragmented_sample_size: usize,
+    best_effort_reordering: Option<BestEffortReordering>,
+    proactive_nackfrag_delay: Option<Duration>,
+    fragment_assembly: FragmentAssembly,
+    deadline_timestamp: DeadlineTimestamp,
+    clock: Arc<dyn Clock>,
+    datawriter_status_channel: StatusChannelConfig,
+    datareader_status_channel: StatusChannelConfig,
+    topic_type_registry: Arc<TopicTypeRegistry>,
+    durability_store: Arc<dyn DurabilityStore>,
+    read_only: bool,
+    shutdown_grace_period: Duration,
   ) -> CreateResult<Self> {
     #[cfg(not(feature = "security"))]
     let _dummy = _qos_policies; // to make clippy happy
 
-    let only_networks: Option<Arc<[IpAddr]>> = only_networks.map(|v| v.into());
-
-    let mut listeners = HashMap::new();
-
-    match UDPListener::new_multicast_with_buf_size(
-      "0.0.0.0",
-      spdp_well_known_multicast_port(domain_id),
-      Ipv4Addr::new(239, 255, 0, 1),
-      socket_receive_buffer_size,
-      only_networks.as_deref(),
-    ) {
-      Ok(l) => {
-        listeners.insert(DISCOVERY_MUL_LISTENER_TOKEN, l);
-      }
-      Err(e) => warn!("Cannot get multicast discovery listener: {e:?}"),
-    }
-
-    let mut participant_id = 0;
-
-    let mut discovery_listener = None;
-
-    // Magic value 120 below is from RTPS spec 2.5 Section "9.6.2.3 Default Port
-    // Numbers"
-    while discovery_listener.is_none() && participant_id < 120 {
-      discovery_listener = UDPListener::new_unicast_with_buf_size(
-        "0.0.0.0",
-        spdp_well_known_unicast_port(domain_id, participant_id),
+    #[cfg(feature = "tcp")]
+    let (participant_id, listeners) = match &interfaces.tcp_transport {
+      Some(tcp) => tcp_listeners(domain_id, requested_participant_id, tcp, &interfaces)?,
+      None => udp_listeners(
+        domain_id,
+        requested_participant_id,
         socket_receive_buffer_size,
-      )
-      .ok();
-      if discovery_listener.is_none() {
-        participant_id += 1;
-      }
-    }
-
-    info!("ParticipantId {participant_id} selected.");
-
-    // here discovery_listener is redefined (shadowed)
-    let discovery_listener = match discovery_listener {
-      Some(dl) => dl,
-      None => return create_error_out_of_resources!("Could not find free ParticipantId"),
+        &interfaces,
+      )?,
     };
-    listeners.insert(DISCOVERY_LISTENER_TOKEN, discovery_listener);
-
-    // Now the user traffic listeners
-
-    match UDPListener::new_multicast_with_buf_size(
-      "0.0.0.0",
-      user_traffic_multicast_port(domain_id),
-      Ipv4Addr::new(239, 255, 0, 1),
-      socket_receive_buffer_size,
-      only_networks.as_deref(),
-    ) {
-      Ok(l) => {
-        listeners.insert(USER_TRAFFIC_MUL_LISTENER_TOKEN, l);
-      }
-      Err(e) => warn!("Cannot get multicast user traffic listener: {e:?}"),
-    }
-
-    let user_traffic_listener = UDPListener::new_unicast_with_buf_size(
-      "0.0.0.0",
-      user_traffic_unicast_port(domain_id, participant_id),
+    #[cfg(not(feature = "tcp"))]
+    let (participant_id, listeners) = udp_listeners(
+      domain_id,
+      requested_participant_id,
       socket_receive_buffer_size,
-    )
-    .or_else(|e| {
-      if matches!(e.kind(), ErrorKind::AddrInUse) {
-        // If we do not get the preferred listening port,
-        // try again, with "any" port number.
-        UDPListener::new_unicast_with_buf_size("0.0.0.0", 0, socket_receive_buffer_size).or_else(
-          |e| {
-            create_error_out_of_resources!(
-              "Could not open unicast user traffic listener, any port number: {:?}",
-              e
-            )
-          },
-        )
-      } else {
-        create_error_out_of_resources!("Could not open unicast user traffic listener: {e:?}")
-      }
-    })?;
-
-    listeners.insert(USER_TRAFFIC_LISTENER_TOKEN, user_traffic_listener);
+      &interfaces,
+    )?;
 
     // construct our own Locators
-    let self_locators: HashMap<mio_06::Token, Vec<Locator>> = listeners
+    let self_locators = transport::self_locators(&listeners, &interfaces);
+
+    // A participant without any unicast locator cannot be reached by anyone,
+    // so refuse to construct one rather than let it fail silently.
+    let has_unicast_locators = [DISCOVERY_LISTENER_TOKEN, USER_TRAFFIC_LISTENER_TOKEN]
       .iter()
-      .map(
-        |(t, l)| match l.to_locator_address(only_networks.as_deref()) {
-          Ok(locs) => (*t, locs),
-          Err(e) => {
-            error!("No local network address for token {t:?}: {e:?}");
-            (*t, vec![])
-          }
-        },
-      )
-      .collect();
+      .all(|t| self_locators.get(t).is_some_and(|locs| !locs.is_empty()));
+    if !has_unicast_locators {
+      return create_error_out_of_resources!(
+        "No usable network interface found, so this participant would have no locators to \
+         advertise. Check the network configuration, or configure addresses explicitly with \
+         DomainParticipantBuilder::advertised_addresses() or fallback_addresses()."
+      );
+    }
 
     // Adding readers
     let (sender_add_reader, receiver_add_reader) =
@@ -1230,20 +2337,25 @@ impl DomainParticipantInner {
       mio_channel::sync_channel::<()>(1);
 
     // Discovert DB creation
-    let discovery_db = Arc::new(RwLock::new(DiscoveryDB::new(
-      participant_guid,
-      discovery_db_event_sender,
-      status_sender.clone(),
-    )));
+    let discovery_db = Arc::new(RwLock::new(
+      DiscoveryDB::new(
+        participant_guid,
+        discovery_db_event_sender,
+        status_sender.clone(),
+      )
+      .with_topic_type_registry(topic_type_registry.clone()),
+    ));
 
     let (stop_poll_sender, stop_poll_receiver) = mio_channel::channel();
 
     let (ev_ready_tx, ev_ready_rx) = std::sync::mpsc::sync_channel::<CreateResult<()>>(1);
 
+    let statistics = Arc::new(StatisticsCounters::default());
+
     // Launch the background thread for DomainParticipant
     let disc_db_clone = discovery_db.clone();
+    let statistics_clone = Arc::clone(&statistics);
     let security_plugins_clone = security_plugins_handle.clone();
-    let only_networks_for_ev_loop = only_networks.clone();
     let ev_loop_handle = thread::Builder::new()
       .name(format!("RustDDS Participant {participant_id} event loop"))
       .spawn(move || {
@@ -1275,9 +2387,25 @@ impl DomainParticipantInner {
           spdp_liveness_sender,
           status_sender,
           security_plugins_clone,
-          only_networks_for_ev_loop,
+          interfaces,
           socket_send_buffer_size,
           same_host_loopback,
+          accept_own_spdp,
+          heartbeat_backoff,
+          heartbeat_period,
+          heartbeat_response_delay,
+          heartbeat_delivery,
+          large_fan_out,
+          max_coalesce_delay,
+          vendor_quirks,
+          record_reception_context,
+          max_fragmented_sample_size,
+          best_effort_reordering,
+          proactive_nackfrag_delay,
+          fragment_assembly,
+          deadline_timestamp,
+          clock,
+          statistics_clone,
         ) {
           Ok(dp_event_loop) => {
             let _ = ev_ready_tx.send(Ok(()));
@@ -1323,7 +2451,14 @@ impl DomainParticipantInner {
       status_receiver,
       self_locators,
       security_plugins_handle,
-      only_networks,
+      datawriter_status_channel,
+      datareader_status_channel,
+      topic_type_registry,
+      durability_store,
+      read_only,
+      shutdown_grace_period,
+      discovery_to_outlive: None,
+      statistics,
     })
   }
 
@@ -1331,8 +2466,71 @@ impl DomainParticipantInner {
     self.dds_cache.clone()
   }
 
-  pub(crate) fn only_networks(&self) -> Option<Arc<[IpAddr]>> {
-    self.only_networks.clone()
+  pub(crate) fn datawriter_status_channel(&self) -> StatusChannelConfig {
+    self.datawriter_status_channel
+  }
+
+  pub(crate) fn datareader_status_channel(&self) -> StatusChannelConfig {
+    self.datareader_status_channel
+  }
+
+  pub(crate) fn topic_type_registry(&self) -> Arc<TopicTypeRegistry> {
+    self.topic_type_registry.clone()
+  }
+
+  pub(crate) fn durability_store(&self) -> Arc<dyn DurabilityStore> {
+    self.durability_store.clone()
+  }
+
+  pub(crate) fn is_read_only(&self) -> bool {
+    self.read_only
+  }
+
+  pub(crate) fn statistics(&self) -> ParticipantStatistics {
+    self.statistics.snapshot()
+  }
+
+  // Asks the event loop to re-read network interfaces. The resulting locators
+  // arrive through the returned receiver.
+  pub(crate) fn request_interface_refresh(&self) -> CreateResult<RefreshedLocatorsReceiver> {
+    let (reply_sender, reply_receiver) = std::sync::mpsc::sync_channel(1);
+    self
+      .stop_poll_sender
+      .send(EventLoopCommand::RefreshInterfaces {
+        reply: reply_sender,
+      })
+      .or_else(|e| create_error_poisoned!("dp_event_loop not responding: {e:?}"))?;
+    Ok(reply_receiver)
+  }
+
+  // Stores the locators from a refresh. Returns true if they changed.
+  pub(crate) fn update_self_locators(
+    &mut self,
+    self_locators: HashMap<mio_06::Token, Vec<Locator>>,
+  ) -> CreateResult<bool> {
+    let has_unicast_locators = [DISCOVERY_LISTENER_TOKEN, USER_TRAFFIC_LISTENER_TOKEN]
+      .iter()
+      .all(|t| self_locators.get(t).is_some_and(|locs| !locs.is_empty()));
+    if !has_unicast_locators {
+      return create_error_out_of_resources!(
+        "No usable network interface found. Keeping previous locators {:?}",
+        self.self_locators
+      );
+    }
+
+    // Interface enumeration order is not stable, so compare as sets.
+    let as_sets = |locators: &HashMap<mio_06::Token, Vec<Locator>>| {
+      locators
+        .iter()
+        .map(|(t, locs)| (*t, locs.iter().copied().collect::<BTreeSet<_>>()))
+        .collect::<HashMap<_, _>>()
+    };
+    let changed = as_sets(&self_locators) != as_sets(&self.self_locators);
+    if changed {
+      info!("Local locators changed to {self_locators:?}");
+      self.self_locators = self_locators;
+    }
+    Ok(changed)
   }
 
   #[cfg(feature = "security")] // just to avoid warning
@@ -1423,6 +2621,10 @@ impl DomainParticipantInner {
       };
     }
 
+    if let Err(e) = self.topic_type_registry.check(&name, &type_desc) {
+      return create_error_bad_parameter!("Cannot create topic: {e}");
+    }
+
     let topic_type_desc = TypeDesc::new(type_desc);
     let topic = Topic::new(
       domain_participant_weak,
@@ -1553,6 +2755,16 @@ impl DomainParticipantInner {
 
     db.all_user_topics().cloned().collect()
   }
+
+  pub fn topic_qos(&self, topic_name: &str) -> Option<QosPolicies> {
+    discovery_db_read(&self.discovery_db)
+      .get_topic(topic_name)
+      .map(|d| d.topic_data.qos())
+  }
+
+  pub fn discovery_dump(&self) -> DiscoveryDump {
+    discovery_db_read(&self.discovery_db).dump()
+  }
   pub(crate) fn status_channel_receiver(
     &self,
   ) -> &StatusChannelReceiver<DomainParticipantStatusEvent> {
@@ -1565,6 +2777,181 @@ impl DomainParticipantInner {
   }
 } // impl
 
+// The UDP listeners: discovery and user traffic, unicast and multicast.
+fn udp_listeners(
+  domain_id: u16,
+  requested_participant_id: Option<u16>,
+  socket_receive_buffer_size: usize,
+  interfaces: &InterfaceConfig,
+) -> CreateResult<(u16, Listeners)> {
+  let mut listeners: Listeners = HashMap::new();
+
+  let discovery_multicast = interfaces.discovery_multicast;
+  let discovery_multicast_host = match discovery_multicast.ip_version() {
+    IpVersion::V4 => "0.0.0.0",
+    IpVersion::V6 => "::",
+  };
+  // Without the multicast listener, we also advertise no metatraffic multicast
+  // locators, so SPDP is not sent by multicast either.
+  if !interfaces.unicast_discovery_only {
+    match UDPListener::new_multicast_with_buf_size(
+      discovery_multicast_host,
+      spdp_well_known_multicast_port(domain_id),
+      discovery_multicast.group(),
+      socket_receive_buffer_size,
+      interfaces,
+    ) {
+      Ok(l) => {
+        listeners.insert(DISCOVERY_MUL_LISTENER_TOKEN, Box::new(l));
+      }
+      Err(e) => warn!("Cannot get multicast discovery listener: {e:?}"),
+    }
+  }
+
+  let (participant_id, discovery_listener) =
+    bind_participant_port(domain_id, requested_participant_id, |port| {
+      UDPListener::new_unicast_with_buf_size("0.0.0.0", port, socket_receive_buffer_size)
+    })?;
+  info!("ParticipantId {participant_id} selected.");
+  let discovery_port = discovery_listener.port();
+  listeners.insert(DISCOVERY_LISTENER_TOKEN, Box::new(discovery_listener));
+
+  // With IPv6 discovery, listen to IPv6 unicast also, on the same ports as
+  // IPv4. The IPv4 listeners remain, so that dual-stack hosts can be reached
+  // both ways.
+  let listen_ipv6 = discovery_multicast.ip_version() == IpVersion::V6;
+  let new_ipv6_listener = |port| {
+    UDPListener::new_unicast_with_buf_size("::", port, socket_receive_buffer_size)
+      .inspect_err(|e| warn!("Cannot bind IPv6 unicast listener to port {port}: {e}"))
+      .ok()
+  };
+  if listen_ipv6 {
+    if let Some(l) = new_ipv6_listener(discovery_port) {
+      listeners.insert(DISCOVERY_LISTENER_V6_TOKEN, Box::new(l));
+    }
+  }
+
+  // Now the user traffic listeners
+
+  match UDPListener::new_multicast_with_buf_size(
+    "0.0.0.0",
+    user_traffic_multicast_port(domain_id),
+    IpAddr::V4(DiscoveryMulticast::DEFAULT_IPV4_GROUP),
+    socket_receive_buffer_size,
+    interfaces,
+  ) {
+    Ok(l) => {
+      listeners.insert(USER_TRAFFIC_MUL_LISTENER_TOKEN, Box::new(l));
+    }
+    Err(e) => warn!("Cannot get multicast user traffic listener: {e:?}"),
+  }
+
+  let user_traffic_port = user_traffic_unicast_port(domain_id, participant_id);
+  let user_traffic_listener = UDPListener::new_unicast_with_buf_size(
+    "0.0.0.0",
+    user_traffic_port,
+    socket_receive_buffer_size,
+  )
+  .or_else(|e| {
+    if matches!(e.kind(), ErrorKind::AddrInUse) {
+      // If we do not get the preferred listening port,
+      // try again, with "any" port number.
+      UDPListener::new_unicast_with_buf_size("0.0.0.0", 0, socket_receive_buffer_size)
+        .map_err(|source| CreateError::Bind { port: 0, source })
+    } else {
+      Err(CreateError::Bind {
+        port: user_traffic_port,
+        source: e,
+      })
+    }
+  })
+  .inspect_err(|e| error!("Could not open unicast user traffic listener: {e}"))?;
+
+  if listen_ipv6 {
+    if let Some(l) = new_ipv6_listener(user_traffic_listener.port()) {
+      listeners.insert(USER_TRAFFIC_LISTENER_V6_TOKEN, Box::new(l));
+    }
+  }
+  listeners.insert(USER_TRAFFIC_LISTENER_TOKEN, Box::new(user_traffic_listener));
+
+  Ok((participant_id, listeners))
+}
+
+// The TCP listener, which receives both discovery and user traffic.
+#[cfg(feature = "tcp")]
+fn tcp_listeners(
+  domain_id: u16,
+  requested_participant_id: Option<u16>,
+  tcp: &TcpTransport,
+  interfaces: &InterfaceConfig,
+) -> CreateResult<(u16, Listeners)> {
+  let host = match interfaces.discovery_multicast.ip_version() {
+    IpVersion::V4 => "0.0.0.0",
+    IpVersion::V6 => "::",
+  };
+  // Each listener serves the logical port of its participant, and the one of
+  // participant 0, which initial peers are sent to.
+  let initial_peer_port = spdp_well_known_unicast_port(domain_id, 0);
+  let (participant_id, listener) = match tcp.listen_port() {
+    Some(port) => {
+      let participant_id = requested_participant_id.unwrap_or(0);
+      let listener = TCPListener::new(
+        host,
+        port,
+        &[
+          spdp_well_known_unicast_port(domain_id, participant_id),
+          initial_peer_port,
+        ],
+        tcp.keep_alive(),
+        tcp.max_connections(),
+      )
+      .map_err(|source| CreateError::Bind { port, source })?;
+      (participant_id, listener)
+    }
+    // The listen port is the logical port of the participant, too.
+    None => bind_participant_port(domain_id, requested_participant_id, |port| {
+      TCPListener::new(
+        host,
+        port,
+        &[port, initial_peer_port],
+        tcp.keep_alive(),
+        tcp.max_connections(),
+      )
+    })?,
+  };
+  info!("ParticipantId {participant_id} selected.");
+  let mut listeners: Listeners = HashMap::new();
+  listeners.insert(TCP_LISTENER_TOKEN, Box::new(listener));
+  Ok((participant_id, listeners))
+}
+
+// Bind to the discovery port of the requested participant id only, or else of
+// the first participant id whose port is free.
+fn bind_participant_port<L>(
+  domain_id: u16,
+  requested_participant_id: Option<u16>,
+  mut bind: impl FnMut(u16) -> io::Result<L>,
+) -> CreateResult<(u16, L)> {
+  let participant_ids = match requested_participant_id {
+    Some(id) => id..id + 1,
+    None => 0..MAX_PARTICIPANT_ID,
+  };
+  for participant_id in participant_ids {
+    let port = spdp_well_known_unicast_port(domain_id, participant_id);
+    match bind(port) {
+      Ok(listener) => return Ok((participant_id, listener)),
+      Err(e) if requested_participant_id.is_none() && matches!(e.kind(), ErrorKind::AddrInUse) => {
+        debug!("ParticipantId {participant_id} is in use: {e}");
+      }
+      Err(source) => {
+        error!("Cannot bind discovery listener to port {port}: {source}");
+        return Err(CreateError::Bind { port, source });
+      }
+    }
+  }
+  create_error_out_of_resources!("Could not find free ParticipantId")
+}
+
 impl RTPSEntity for DomainParticipant {
   fn guid(&self) -> GUID {
     self.dpi.lock().unwrap().guid()
@@ -1609,9 +2996,9 @@ mod tests {
       header::Header, protocol_id::ProtocolId, protocol_version::ProtocolVersion,
       submessages::submessages::*, vendor_id::VendorId,
     },
-    network::{constant::user_traffic_unicast_port, udp_sender::UDPSender},
+    network::{constant::user_traffic_unicast_port, transport::Transport, udp_sender::UDPSender},
     rtps::{submessage::*, Message},
-    serialization::CDRSerializerAdapter,
+    serialization::{CDRDeserializerAdapter, CDRSerializerAdapter},
     structure::{
       guid::{EntityId, GUID},
       locator::Locator,
@@ -1722,4 +3109,124 @@ mod tests {
     let locators = vec![Locator::UdpV4(socket_address)];
     sender.send_to_locator_list(&_data, &locators);
   }
+
+  #[test]
+  fn topic_type_registry_rejects_mismatching_types() {
+    use crate::dds::{result::CreateError, topic::TopicTypeRegistry};
+
+    let unchecked = DomainParticipant::new(0).unwrap();
+    let checked = super::DomainParticipantBuilder::new(0)
+      .topic_type_registry(TopicTypeRegistry::new().with_topic("odom", "RandomData"))
+      .build()
+      .unwrap();
+    let qos = QosPolicies::qos_none();
+
+    assert!(matches!(
+      checked.create_topic(
+        "odom".to_string(),
+        "Twist".to_string(),
+        &qos,
+        TopicKind::WithKey
+      ),
+      Err(CreateError::BadParameter { .. })
+    ));
+    checked
+      .create_topic(
+        "odom".to_string(),
+        "RandomData".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+
+    // A Topic from elsewhere is checked when creating endpoints.
+    let foreign_topic = unchecked
+      .create_topic(
+        "odom".to_string(),
+        "Twist".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+    let subscriber = checked.create_subscriber(&qos).unwrap();
+    assert!(matches!(
+      subscriber
+        .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(&foreign_topic, None),
+      Err(CreateError::BadParameter { .. })
+    ));
+    let publisher = checked.create_publisher(&qos).unwrap();
+    assert!(matches!(
+      publisher.create_datawriter::<RandomData, CDRSerializerAdapter<RandomData, LittleEndian>>(
+        &foreign_topic,
+        None
+      ),
+      Err(CreateError::BadParameter { .. })
+    ));
+  }
+
+  #[test]
+  fn read_only_participant_does_not_publish() {
+    use crate::dds::result::CreateError;
+
+    let dp = super::DomainParticipantBuilder::new(0)
+      .read_only(true)
+      .build()
+      .unwrap();
+    assert!(dp.is_read_only());
+    assert!(!DomainParticipant::new(0).unwrap().is_read_only());
+
+    let qos = QosPolicies::qos_none();
+    let topic = dp
+      .create_topic(
+        "read_only".to_string(),
+        "RandomData".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+    let publisher = dp.create_publisher(&qos).unwrap();
+    assert!(matches!(
+      publisher.create_datawriter::<RandomData, CDRSerializerAdapter<RandomData, LittleEndian>>(
+        &topic, None
+      ),
+      Err(CreateError::BadParameter { .. })
+    ));
+    dp.create_subscriber(&qos)
+      .unwrap()
+      .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(&topic, None)
+      .unwrap();
+  }
+
+  #[test]
+  fn participant_id_in_use_is_a_bind_error() {
+    use std::io::ErrorKind;
+
+    use crate::{dds::result::CreateError, network::constant::spdp_well_known_unicast_port};
+
+    let builder = || super::DomainParticipantBuilder::new(42).participant_id(7);
+    let first = builder().build().unwrap();
+    assert_eq!(first.participant_id(), 7);
+
+    match builder().build() {
+      Err(CreateError::Bind { port, source }) => {
+        assert_eq!(port, spdp_well_known_unicast_port(42, 7));
+        assert_eq!(source.kind(), ErrorKind::AddrInUse);
+      }
+      other => panic!(
+        "Expected a bind error, got {:?}",
+        other.map(|dp| dp.participant_id())
+      ),
+    }
+
+    // Without an explicit participant id, the next free one is used.
+    let second = super::DomainParticipantBuilder::new(42).build().unwrap();
+    assert_ne!(second.participant_id(), 7);
+
+    assert!(matches!(
+      super::DomainParticipantBuilder::new(42)
+        .participant_id(120)
+        .build(),
+      Err(CreateError::BadParameter { .. })
+    ));
+  }
 }
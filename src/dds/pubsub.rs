@@ -1,6 +1,7 @@
 use std::{
+  collections::BTreeMap,
   fmt::Debug,
-  sync::{Arc, Mutex, MutexGuard, RwLock},
+  sync::{atomic::AtomicBool, Arc, Mutex, MutexGuard, RwLock},
   time::Duration,
 };
 
@@ -11,9 +12,10 @@ use byteorder::LittleEndian;
 use log::{debug, error, info, trace, warn};
 
 use crate::{
-  create_error_dropped, create_error_poisoned,
+  create_error_bad_parameter, create_error_dropped, create_error_poisoned,
   dds::{
     adapters,
+    durability_store::StoredSample,
     key::Keyed,
     no_key,
     no_key::{
@@ -22,11 +24,14 @@ use crate::{
     participant::*,
     qos::*,
     result::{CreateError, CreateResult, WaitResult},
-    statusevents::{sync_status_channel, DataReaderStatus},
+    statusevents::{
+      configured_status_channel, CountWithChange, DataReaderStatus, LivelinessChangedStatus,
+    },
     topic::*,
     with_key,
     with_key::{
       datareader::DataReader as WithKeyDataReader, datawriter::DataWriter as WithKeyDataWriter,
+      DuplicateAcceptance, HistoryBoundaries, ReadStart,
     },
   },
   discovery::{
@@ -55,6 +60,12 @@ use crate::{
 };
 #[cfg(not(feature = "security"))]
 use crate::no_security::{security_plugins::SecurityPluginsHandle, EndpointSecurityInfo};
+#[cfg(feature = "json")]
+use crate::serialization::JsonAdapter;
+
+// Commands from a DataReader to its Reader. They are rare, as they are sent
+// only when the application reads a status that has changed.
+const READER_COMMAND_QUEUE_LEN: usize = 4;
 
 // -------------------------------------------------------------------
 
@@ -190,6 +201,21 @@ impl Publisher {
     self.create_datawriter::<D, CDRSerializerAdapter<D, LittleEndian>>(topic, qos)
   }
 
+  /// Shorthand for create_datawriter with [`JsonAdapter`], a non-standard data
+  /// representation. Requires the feature "json".
+  #[cfg(feature = "json")]
+  pub fn create_datawriter_json<D>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+  ) -> CreateResult<WithKeyDataWriter<D, JsonAdapter<D>>>
+  where
+    D: Keyed + serde::Serialize,
+    <D as Keyed>::K: Serialize,
+  {
+    self.create_datawriter::<D, JsonAdapter<D>>(topic, qos)
+  }
+
   /// Creates DDS [DataWriter](struct.DataWriter.html) for Nokey Topic
   ///
   /// # Arguments
@@ -240,6 +266,20 @@ impl Publisher {
     self.create_datawriter_no_key::<D, CDRSerializerAdapter<D, LittleEndian>>(topic, qos)
   }
 
+  /// Shorthand for create_datawriter_no_key with [`JsonAdapter`], a
+  /// non-standard data representation. Requires the feature "json".
+  #[cfg(feature = "json")]
+  pub fn create_datawriter_no_key_json<D>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+  ) -> CreateResult<NoKeyDataWriter<D, JsonAdapter<D>>>
+  where
+    D: serde::Serialize,
+  {
+    self.create_datawriter_no_key::<D, JsonAdapter<D>>(topic, qos)
+  }
+
   // Versions with callee-specified EntityId. These are for Discovery use only.
 
   pub(crate) fn create_datawriter_with_entity_id_with_key<D, SA>(
@@ -307,18 +347,53 @@ impl Publisher {
     unreachable!("resume_publications is a placeholder only and must not be called")
   }
 
-  // coherent change set
-  // In case such QoS is not supported, these should be no-ops.
-  // TODO: Implement these when coherent change-sets are supported.
-  // Coherent set not implemented and currently does nothing
-  /// **NOT IMPLEMENTED. DO NOT USE**
-  #[deprecated(note = "unimplemented")]
-  pub fn begin_coherent_changes(&self) {}
+  /// Starts grouping the samples written by the DataWriters of this Publisher
+  /// into coherent sets, until
+  /// [`end_coherent_changes`](Self::end_coherent_changes).
+  ///
+  /// The samples written by one DataWriter form one coherent set, as with the
+  /// Presentation QoS access scope TOPIC. A matched DataReader gets all
+  /// samples of the set at once, or none of them if the set cannot be
+  /// completed, e.g. because the DataWriter was lost in the middle of it.
+  ///
+  /// Calls may be nested. The sets end at the outermost `end_coherent_changes`.
+  ///
+  /// See DDS Spec 1.4 Section 2.2.2.4.1.10 begin_coherent_changes.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use rustdds::*;
+  /// use serde::Serialize;
+  /// #
+  /// # let domain_participant = DomainParticipant::new(0).unwrap();
+  /// # let qos = QosPolicyBuilder::new().build();
+  /// # let publisher = domain_participant.create_publisher(&qos).unwrap();
+  /// #
+  /// #[derive(Serialize, Debug)]
+  /// struct Position { x: i32, y: i32 }
+  ///
+  /// let topic = domain_participant.create_topic("position".to_string(), "Position".to_string(), &qos, TopicKind::NoKey).unwrap();
+  /// let data_writer = publisher.create_datawriter_no_key_cdr::<Position>(&topic, None).unwrap();
+  ///
+  /// publisher.begin_coherent_changes();
+  /// data_writer.write(Position { x: 1, y: 0 }, None).unwrap();
+  /// data_writer.write(Position { x: 1, y: 1 }, None).unwrap();
+  /// publisher.end_coherent_changes();
+  /// ```
+  pub fn begin_coherent_changes(&self) {
+    self.inner_lock().begin_coherent_changes();
+  }
 
-  // Coherent set not implemented and currently does nothing
-  /// **NOT IMPLEMENTED. DO NOT USE**
-  #[deprecated(note = "unimplemented")]
-  pub fn end_coherent_changes(&self) {}
+  /// Ends the coherent sets started by
+  /// [`begin_coherent_changes`](Self::begin_coherent_changes), and sends an
+  /// end marker from each DataWriter that wrote in them. A call without a
+  /// matching `begin_coherent_changes` does nothing.
+  ///
+  /// See DDS Spec 1.4 Section 2.2.2.4.1.11 end_coherent_changes.
+  pub fn end_coherent_changes(&self) {
+    self.inner_lock().end_coherent_changes();
+  }
 
   // Wait for all matched reliable DataReaders acknowledge data written so far,
   // or timeout.
@@ -430,6 +505,9 @@ struct InnerPublisher {
   remove_writer_sender: mio_channel::SyncSender<GUID>,
   discovery_command: mio_channel::SyncSender<DiscoveryCommand>,
   security_plugins_handle: Option<SecurityPluginsHandle>,
+  // Send buffers and doorbells of our DataWriters, for coherent sets
+  writers: BTreeMap<GUID, (WriterSendBuffer, mio_06::SetReadiness)>,
+  coherent_changes_depth: u32, // nesting of begin_coherent_changes
 }
 
 // public interface for Publisher
@@ -460,11 +538,13 @@ impl InnerPublisher {
       remove_writer_sender,
       discovery_command,
       security_plugins_handle,
+      writers: BTreeMap::new(),
+      coherent_changes_depth: 0,
     }
   }
 
   pub fn create_datawriter<D, SA>(
-    &self,
+    &mut self,
     outer: &Publisher,
     entity_id_opt: Option<EntityId>,
     topic: &Topic,
@@ -475,9 +555,6 @@ impl InnerPublisher {
     D: Keyed,
     SA: adapters::with_key::SerializerAdapter<D>,
   {
-    // Status reports back from Writer to DataWriter.
-    let (status_sender, status_receiver) = sync_status_channel(4)?;
-
     // DDS Spec 2.2.2.4.1.5 create_datawriter:
     // If no QoS is specified, we should take the Publisher default
     // QoS, modify it to match any QoS settings (that are set) in the
@@ -485,10 +562,14 @@ impl InnerPublisher {
 
     // Use Publisher QoS as basis, modify by Topic settings, and modify by specified
     // QoS.
-    let writer_qos = self
+    let mut writer_qos = self
       .default_datawriter_qos
       .modify_by(&topic.qos())
       .modify_by(&optional_qos.unwrap_or_else(QosPolicies::qos_none));
+    if writer_qos.data_representation.is_none() {
+      writer_qos.data_representation =
+        policy::DataRepresentation::default_offered(SA::output_encoding());
+    }
 
     let entity_id =
       self.unwrap_or_new_entity_id(entity_id_opt, EntityKind::WRITER_WITH_KEY_USER_DEFINED)?;
@@ -497,8 +578,25 @@ impl InnerPublisher {
       .ok_or("upgrade fail")
       .or_else(|e| create_error_dropped!("Where is my DomainParticipant? {}", e))?;
 
+    if dp.is_read_only() && entity_id.kind().is_user_defined() {
+      return create_error_bad_parameter!(
+        "Cannot create DataWriter: the DomainParticipant is read-only."
+      );
+    }
+
+    if let Err(e) = dp
+      .topic_type_registry()
+      .check(&topic.name(), topic.get_type().name())
+    {
+      return create_error_bad_parameter!("Cannot create DataWriter: {e}");
+    }
+
     let guid = GUID::new_with_prefix_and_id(dp.guid().prefix, entity_id);
 
+    // Status reports back from Writer to DataWriter.
+    let (status_sender, status_receiver) =
+      configured_status_channel(dp.datawriter_status_channel())?;
+
     // Shared, flow-controlled send buffer between the DataWriter (producer) and
     // the RTPS Writer (consumer). The reliable send window is derived from the
     // writer's History / ResourceLimits QoS.
@@ -544,6 +642,16 @@ impl InnerPublisher {
       backlog_limit,
       max_retain,
     );
+    // Transient and Persistent writers keep their samples also in the durability
+    // store, so they outlive the writer.
+    if writer_qos.durability() >= Some(policy::Durability::Transient)
+      && guid.entity_id.entity_kind.is_user_defined()
+    {
+      let Some(store) = dp.durability_store() else {
+        return create_error_poisoned!("Cannot lock DomainParticipant.");
+      };
+      send_buffer.set_durability_store(store, writer_qos.durability_service().unwrap_or_default());
+    }
     // mio readiness "doorbell": the DataWriter rings `doorbell` after admitting a
     // sample; the event loop registers `doorbell_registration` under the writer's
     // entity token and wakes to transmit.
@@ -667,6 +775,13 @@ impl InnerPublisher {
     // Instead, it's done by the DP event loop once it has actually created the new
     // writer. This is done to avoid data races.
 
+    if self.coherent_changes_depth > 0 {
+      send_buffer.begin_coherent_set();
+    }
+    self
+      .writers
+      .insert(guid, (send_buffer.clone(), doorbell.clone()));
+
     // Send writer ingredients to DP event loop, where the actual writer will be
     // constructed
     let new_writer = WriterIngredients {
@@ -691,7 +806,7 @@ impl InnerPublisher {
   }
 
   pub fn create_datawriter_no_key<D, SA>(
-    &self,
+    &mut self,
     outer: &Publisher,
     entity_id_opt: Option<EntityId>,
     topic: &Topic,
@@ -737,11 +852,39 @@ impl InnerPublisher {
     Ok(entity_id_opt.unwrap_or_else(|| dp.new_entity_id(entity_kind)))
   }
 
-  pub(crate) fn remove_writer(&self, guid: GUID) {
+  pub(crate) fn remove_writer(&mut self, guid: GUID) {
+    self.writers.remove(&guid);
     try_send_timeout(&self.remove_writer_sender, guid, None)
       .unwrap_or_else(|e| error!("Cannot remove Writer {guid:?} : {e:?}"));
   }
 
+  pub fn begin_coherent_changes(&mut self) {
+    self.coherent_changes_depth += 1;
+    if self.coherent_changes_depth == 1 {
+      for (send_buffer, _doorbell) in self.writers.values() {
+        send_buffer.begin_coherent_set();
+      }
+    }
+  }
+
+  pub fn end_coherent_changes(&mut self) {
+    match self.coherent_changes_depth {
+      0 => warn!("end_coherent_changes called without begin_coherent_changes"),
+      1 => {
+        self.coherent_changes_depth = 0;
+        for (guid, (send_buffer, doorbell)) in &self.writers {
+          // Wake the Writer to send the end marker.
+          if send_buffer.end_coherent_set() {
+            if let Err(e) = doorbell.set_readiness(mio_06::Ready::readable()) {
+              warn!("Failed to ring writer doorbell: {guid:?} {e}");
+            }
+          }
+        }
+      }
+      _ => self.coherent_changes_depth -= 1,
+    }
+  }
+
   pub(crate) fn identity(&self) -> EntityId {
     self.id
   }
@@ -862,7 +1005,9 @@ impl Subscriber {
     D: 'static + Keyed,
     SA: adapters::with_key::DeserializerAdapter<D>,
   {
-    self.inner.create_datareader(self, topic, None, qos, false)
+    self
+      .inner
+      .create_datareader(self, topic, None, qos, false, ReadStart::AllAvailable)
   }
 
   pub fn create_datareader_cdr<D>(
@@ -877,6 +1022,133 @@ impl Subscriber {
     self.create_datareader::<D, CDRDeserializerAdapter<D>>(topic, qos)
   }
 
+  /// Shorthand for create_datareader with [`JsonAdapter`], a non-standard data
+  /// representation. The DataReader accepts only JSON data. Requires the
+  /// feature "json".
+  #[cfg(feature = "json")]
+  pub fn create_datareader_json<D>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+  ) -> CreateResult<WithKeyDataReader<D, JsonAdapter<D>>>
+  where
+    D: 'static + serde::de::DeserializeOwned + Keyed,
+    for<'de> <D as Keyed>::K: Deserialize<'de>,
+  {
+    self.create_datareader::<D, JsonAdapter<D>>(topic, qos)
+  }
+
+  /// Creates DDS DataReader for keyed Topics that delivers only the samples
+  /// for which `filter` returns `true`.
+  ///
+  /// Samples are filtered when the DataReader takes them in, before they are
+  /// stored in its own cache, so filtered out samples are never copied there
+  /// or returned. Disposals are always delivered, even if all samples of the
+  /// instance were filtered out, so that instance states stay correct.
+  ///
+  /// The samples are still received and acknowledged as usual, so the
+  /// filter does not reduce network traffic.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use rustdds::*;
+  /// use serde::Deserialize;
+  /// use rustdds::serialization::CDRDeserializerAdapter;
+  /// #
+  /// # let domain_participant = DomainParticipant::new(0).unwrap();
+  /// # let qos = QosPolicyBuilder::new().build();
+  /// #
+  ///
+  /// let subscriber = domain_participant.create_subscriber(&qos).unwrap();
+  ///
+  /// #[derive(Deserialize)]
+  /// struct Shape { color: String, x: i32 }
+  /// impl Keyed for Shape {
+  ///   type K = String;
+  ///
+  ///   fn key(&self) -> Self::K {
+  ///     self.color.clone()
+  ///   }
+  /// }
+  ///
+  /// let topic = domain_participant.create_topic("Square".to_string(), "ShapeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let data_reader = subscriber
+  ///   .create_content_filtered_datareader::<Shape, CDRDeserializerAdapter<_>, _>(
+  ///     &topic,
+  ///     None,
+  ///     |shape| shape.x > 100,
+  ///   );
+  /// ```
+  pub fn create_content_filtered_datareader<D, SA, F>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+    filter: F,
+  ) -> CreateResult<WithKeyDataReader<D, SA>>
+  where
+    D: 'static + Keyed,
+    SA: adapters::with_key::DeserializerAdapter<D>,
+    F: Fn(&D) -> bool + Send + Sync + 'static,
+  {
+    let mut reader = self.create_datareader::<D, SA>(topic, qos)?;
+    reader.set_content_filter(Box::new(filter));
+    Ok(reader)
+  }
+
+  /// Creates DDS DataReader for keyed Topics that starts from `start`.
+  ///
+  /// With [`ReadStart::FromNow`] the DataReader delivers only samples written
+  /// after it was created, even if matched DataWriters keep history for late
+  /// joining readers. The history is skipped per DataWriter, so no sample
+  /// written after creation is lost. [`create_datareader`](Self::create_datareader)
+  /// is the same as [`ReadStart::AllAvailable`].
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use rustdds::*;
+  /// use serde::Deserialize;
+  /// use rustdds::serialization::CDRDeserializerAdapter;
+  /// #
+  /// # let domain_participant = DomainParticipant::new(0).unwrap();
+  /// # let qos = QosPolicyBuilder::new().build();
+  /// #
+  ///
+  /// let subscriber = domain_participant.create_subscriber(&qos).unwrap();
+  ///
+  /// #[derive(Deserialize)]
+  /// struct Shape { color: String, x: i32 }
+  /// impl Keyed for Shape {
+  ///   type K = String;
+  ///
+  ///   fn key(&self) -> Self::K {
+  ///     self.color.clone()
+  ///   }
+  /// }
+  ///
+  /// let topic = domain_participant.create_topic("Square".to_string(), "ShapeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let data_reader = subscriber.create_datareader_with_start::<Shape, CDRDeserializerAdapter<_>>(
+  ///   &topic,
+  ///   None,
+  ///   ReadStart::FromNow,
+  /// );
+  /// ```
+  pub fn create_datareader_with_start<D, SA>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+    start: ReadStart,
+  ) -> CreateResult<WithKeyDataReader<D, SA>>
+  where
+    D: 'static + Keyed,
+    SA: adapters::with_key::DeserializerAdapter<D>,
+  {
+    self
+      .inner
+      .create_datareader(self, topic, None, qos, false, start)
+  }
+
   /// Create DDS DataReader for non keyed Topics
   ///
   /// # Arguments
@@ -917,7 +1189,7 @@ impl Subscriber {
   {
     self
       .inner
-      .create_datareader_no_key(self, topic, None, qos, false)
+      .create_datareader_no_key(self, topic, None, qos, false, ReadStart::AllAvailable)
   }
 
   pub fn create_simple_datareader_no_key<D, DA>(
@@ -945,6 +1217,57 @@ impl Subscriber {
     self.create_datareader_no_key::<D, CDRDeserializerAdapter<D>>(topic, qos)
   }
 
+  /// Shorthand for create_datareader_no_key with [`JsonAdapter`], a
+  /// non-standard data representation. The DataReader accepts only JSON data.
+  /// Requires the feature "json".
+  #[cfg(feature = "json")]
+  pub fn create_datareader_no_key_json<D>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+  ) -> CreateResult<NoKeyDataReader<D, JsonAdapter<D>>>
+  where
+    D: 'static + serde::de::DeserializeOwned,
+  {
+    self.create_datareader_no_key::<D, JsonAdapter<D>>(topic, qos)
+  }
+
+  /// Creates DDS DataReader for non keyed Topics that delivers only the
+  /// samples for which `filter` returns `true`. See
+  /// [`create_content_filtered_datareader`](Self::create_content_filtered_datareader).
+  pub fn create_content_filtered_datareader_no_key<D, SA, F>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+    filter: F,
+  ) -> CreateResult<NoKeyDataReader<D, SA>>
+  where
+    D: 'static,
+    SA: adapters::no_key::DeserializerAdapter<D>,
+    F: Fn(&D) -> bool + Send + Sync + 'static,
+  {
+    let mut reader = self.create_datareader_no_key::<D, SA>(topic, qos)?;
+    reader.set_content_filter(filter);
+    Ok(reader)
+  }
+
+  /// Creates DDS DataReader for non keyed Topics that starts from `start`. See
+  /// [`create_datareader_with_start`](Self::create_datareader_with_start).
+  pub fn create_datareader_no_key_with_start<D, SA>(
+    &self,
+    topic: &Topic,
+    qos: Option<QosPolicies>,
+    start: ReadStart,
+  ) -> CreateResult<NoKeyDataReader<D, SA>>
+  where
+    D: 'static,
+    SA: adapters::no_key::DeserializerAdapter<D>,
+  {
+    self
+      .inner
+      .create_datareader_no_key(self, topic, None, qos, false, start)
+  }
+
   // versions with callee-specified EntityId. These are for Discovery use only.
 
   pub(crate) fn create_datareader_with_entity_id_with_key<D, SA>(
@@ -958,9 +1281,14 @@ impl Subscriber {
     D: 'static + Keyed,
     SA: adapters::with_key::DeserializerAdapter<D>,
   {
-    self
-      .inner
-      .create_datareader(self, topic, Some(entity_id), qos, reader_like_stateless)
+    self.inner.create_datareader(
+      self,
+      topic,
+      Some(entity_id),
+      qos,
+      reader_like_stateless,
+      ReadStart::AllAvailable,
+    )
   }
 
   #[cfg(feature = "security")] // to avoid "never used" warning
@@ -975,9 +1303,14 @@ impl Subscriber {
     D: 'static,
     SA: adapters::no_key::DeserializerAdapter<D>,
   {
-    self
-      .inner
-      .create_datareader_no_key(self, topic, Some(entity_id), qos, reader_like_stateless)
+    self.inner.create_datareader_no_key(
+      self,
+      topic,
+      Some(entity_id),
+      qos,
+      reader_like_stateless,
+      ReadStart::AllAvailable,
+    )
   }
 
   // Retrieves a previously created DataReader belonging to the Subscriber.
@@ -1016,16 +1349,62 @@ impl Subscriber {
     self.inner.participant()
   }
 
+  /// Returns default DataReader qos.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rustdds::*;
+  ///
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  ///
+  /// let subscriber = domain_participant.create_subscriber(&qos).unwrap();
+  /// assert_eq!(qos, subscriber.get_default_datareader_qos());
+  /// ```
+  pub fn get_default_datareader_qos(&self) -> QosPolicies {
+    self.inner.get_default_datareader_qos()
+  }
+
+  /// Sets default DataReader qos. DataReaders created later are given these
+  /// QoS policies, modified by the Topic QoS and the QoS given at creation.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rustdds::*;
+  ///
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  ///
+  /// let mut subscriber = domain_participant.create_subscriber(&qos).unwrap();
+  /// let qos2 =
+  /// QosPolicyBuilder::new().durability(policy::Durability::Transient).build();
+  /// subscriber.set_default_datareader_qos(&qos2);
+  ///
+  /// assert_ne!(qos, subscriber.get_default_datareader_qos());
+  /// assert_eq!(qos2, subscriber.get_default_datareader_qos());
+  /// ```
+  pub fn set_default_datareader_qos(&mut self, q: &QosPolicies) {
+    self.inner.set_default_datareader_qos(q);
+  }
+
   pub(crate) fn remove_reader(&self, guid: GUID) {
     self.inner.remove_reader(guid);
   }
 }
 
-#[derive(Clone)]
+impl Debug for Subscriber {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.inner.fmt(f)
+  }
+}
+
 pub struct InnerSubscriber {
   domain_participant: DomainParticipantWeak,
   discovery_db: Arc<RwLock<DiscoveryDB>>,
   qos: QosPolicies,
+  default_datareader_qos: Mutex<QosPolicies>, // used when creating a new DataReader
   sender_add_reader: mio_channel::SyncSender<ReaderIngredients>,
   sender_remove_reader: mio_channel::SyncSender<GUID>,
   discovery_command: mio_channel::SyncSender<DiscoveryCommand>,
@@ -1045,6 +1424,7 @@ impl InnerSubscriber {
     Self {
       domain_participant,
       discovery_db,
+      default_datareader_qos: Mutex::new(qos.clone()),
       qos,
       sender_add_reader,
       sender_remove_reader,
@@ -1060,6 +1440,7 @@ impl InnerSubscriber {
     topic: &Topic,
     optional_qos: Option<QosPolicies>,
     reader_like_stateless: bool, // Create a stateless-like RTPS reader? Usually false
+    read_start: ReadStart,
   ) -> CreateResult<WithKeyDataReader<D, SA>>
   where
     D: 'static + Keyed,
@@ -1071,6 +1452,7 @@ impl InnerSubscriber {
       topic,
       optional_qos,
       reader_like_stateless,
+      read_start,
     )?;
     Ok(with_key::DataReader::<D, SA>::from_simple_data_reader(
       simple_dr,
@@ -1084,6 +1466,7 @@ impl InnerSubscriber {
     topic: &Topic,
     optional_qos: Option<QosPolicies>,
     reader_like_stateless: bool, // Create a stateless-like RTPS reader? Usually false
+    read_start: ReadStart,
   ) -> CreateResult<with_key::SimpleDataReader<D, SA>>
   where
     D: 'static + Keyed,
@@ -1091,23 +1474,21 @@ impl InnerSubscriber {
   {
     // incoming data notification channel from Reader to DataReader
     let (send, rec) = mio_channel::sync_channel::<()>(4);
-    // status change channel from Reader to DataReader
-    let (status_sender, status_receiver) = sync_status_channel::<DataReaderStatus>(4)?;
 
     // reader command channel from Datareader to Reader
     let (reader_command_sender, reader_command_receiver) =
-      mio_channel::sync_channel::<ReaderCommand>(0);
-    // The buffer length is zero, i.e. sender and receiver must rendezvous at
-    // send/receive. This is needed to synchronize sending of wakers from
-    // DataReader to Reader. If the capacity is increased, then some data
-    // available for reading notifications may be missed.
-
-    // Use subscriber QoS as basis, modify by Topic settings, and modify by
-    // specified QoS.
-    let qos = self
-      .qos
+      mio_channel::sync_channel::<ReaderCommand>(READER_COMMAND_QUEUE_LEN);
+
+    // Use default DataReader QoS as basis, modify by Topic settings, and modify
+    // by specified QoS.
+    let mut qos = self
+      .get_default_datareader_qos()
       .modify_by(&topic.qos())
       .modify_by(&optional_qos.unwrap_or_else(QosPolicies::qos_none));
+    if qos.data_representation.is_none() {
+      qos.data_representation =
+        policy::DataRepresentation::default_accepted(SA::supported_encodings());
+    }
 
     let entity_id =
       self.unwrap_or_new_entity_id(entity_id_opt, EntityKind::READER_WITH_KEY_USER_DEFINED)?;
@@ -1117,6 +1498,13 @@ impl InnerSubscriber {
       None => return create_error_dropped!("DomainParticipant doesn't exist anymore."),
     };
 
+    if let Err(e) = dp
+      .topic_type_registry()
+      .check(&topic.name(), topic.get_type().name())
+    {
+      return create_error_bad_parameter!("Cannot create DataReader: {e}");
+    }
+
     // Get a handle to the topic cache
     let topic_cache_handle = match dp.dds_cache().read() {
       Ok(dds_cache) => dds_cache.get_existing_topic_cache(&topic.name())?,
@@ -1127,9 +1515,30 @@ impl InnerSubscriber {
       Ok(mut tc) => tc.update_keep_limits(&qos),
       Err(e) => return create_error_poisoned!("Cannot lock topic cache. Error: {}", e),
     };
+    // A Transient or Persistent reader also receives the samples kept in the
+    // durability store, including those of writers that are gone.
+    let stored_changes = if qos.durability() >= Some(policy::Durability::Transient)
+      && entity_id.entity_kind.is_user_defined()
+      && read_start == ReadStart::AllAvailable
+    {
+      let Some(store) = dp.durability_store() else {
+        return create_error_poisoned!("Cannot lock DomainParticipant.");
+      };
+      store
+        .samples(&topic.name())
+        .into_iter()
+        .map(StoredSample::into_cache_change)
+        .collect()
+    } else {
+      Vec::new()
+    };
 
     let reader_guid = GUID::new_with_prefix_and_id(dp.guid_prefix(), entity_id);
 
+    // status change channel from Reader to DataReader
+    let (status_sender, status_receiver) =
+      configured_status_channel::<DataReaderStatus>(dp.datareader_status_channel())?;
+
     #[cfg(feature = "security")]
     if let Some(sec_handle) = self.security_plugins_handle.as_ref() {
       // Security is enabled.
@@ -1181,8 +1590,15 @@ impl InnerSubscriber {
 
     // Construct the ReaderIngredients
     let data_reader_waker = Arc::new(Mutex::new(None));
+    let liveliness_status = Arc::new(Mutex::new(LivelinessChangedStatus::default()));
+    let requested_deadline_missed_status = Arc::new(Mutex::new(CountWithChange::default()));
+    let detect_content_duplicates = Arc::new(AtomicBool::new(false));
+    let ignore_ambiguous_status_info = Arc::new(AtomicBool::new(false));
+    let duplicate_acceptance = Arc::new(Mutex::new(DuplicateAcceptance::default()));
+    let paused = Arc::new(AtomicBool::new(false));
 
     let (poll_event_source, poll_event_sender) = mio_source::make_poll_channel()?;
+    let history_boundaries = (read_start == ReadStart::FromNow).then(HistoryBoundaries::default);
 
     let new_reader = ReaderIngredients {
       guid: reader_guid,
@@ -1195,8 +1611,17 @@ impl InnerSubscriber {
       data_reader_command_receiver: reader_command_receiver,
       data_reader_waker: data_reader_waker.clone(),
       poll_event_sender,
+      liveliness_status: liveliness_status.clone(),
+      requested_deadline_missed_status: requested_deadline_missed_status.clone(),
+      detect_content_duplicates: detect_content_duplicates.clone(),
+      ignore_ambiguous_status_info: ignore_ambiguous_status_info.clone(),
+      duplicate_acceptance: duplicate_acceptance.clone(),
+      paused: paused.clone(),
+      history_boundaries: history_boundaries.clone(),
       security_plugins: self.security_plugins_handle.clone(),
     };
+    // The Reader is constructed in the event loop. Fail here instead.
+    new_reader.check_topic_cache()?;
 
     // Construct security info if needed
     #[cfg(not(feature = "security"))]
@@ -1254,7 +1679,7 @@ impl InnerSubscriber {
     // reader. This is done to avoid data races.
 
     // Construct the data reader
-    let datareader = with_key::SimpleDataReader::<D, SA>::new(
+    let mut datareader = with_key::SimpleDataReader::<D, SA>::new(
       outer.clone(),
       entity_id,
       topic.clone(),
@@ -1265,8 +1690,21 @@ impl InnerSubscriber {
       status_receiver,
       reader_command_sender,
       data_reader_waker,
+      liveliness_status,
+      requested_deadline_missed_status,
+      detect_content_duplicates,
+      ignore_ambiguous_status_info,
+      duplicate_acceptance,
+      paused,
       poll_event_source,
     )?;
+    // Replayed to this DataReader only, not added to the topic cache, so that
+    // the other DataReaders of the Topic are not affected.
+    datareader.set_stored_changes(stored_changes);
+    // Before the Reader exists, so that it cannot add samples in between.
+    if let Some(boundaries) = history_boundaries {
+      datareader.start_from_now(boundaries);
+    }
 
     // Send reader ingredients to DP event loop, where the actual reader will be
     // constructed
@@ -1286,6 +1724,7 @@ impl InnerSubscriber {
     entity_id: Option<EntityId>,
     qos: Option<QosPolicies>,
     reader_like_stateless: bool, // Create a stateless-like RTPS reader? Usually false
+    read_start: ReadStart,
   ) -> CreateResult<WithKeyDataReader<D, SA>>
   where
     D: 'static + Keyed,
@@ -1294,7 +1733,14 @@ impl InnerSubscriber {
     if topic.kind() != TopicKind::WithKey {
       return Err(CreateError::TopicKind(TopicKind::WithKey));
     }
-    self.create_datareader_internal(outer, entity_id, topic, qos, reader_like_stateless)
+    self.create_datareader_internal(
+      outer,
+      entity_id,
+      topic,
+      qos,
+      reader_like_stateless,
+      read_start,
+    )
   }
 
   pub fn create_datareader_no_key<D: 'static, SA>(
@@ -1304,6 +1750,7 @@ impl InnerSubscriber {
     entity_id_opt: Option<EntityId>,
     qos: Option<QosPolicies>,
     reader_like_stateless: bool, // Create a stateless-like RTPS reader? Usually false
+    read_start: ReadStart,
   ) -> CreateResult<NoKeyDataReader<D, SA>>
   where
     SA: adapters::no_key::DeserializerAdapter<D>,
@@ -1321,6 +1768,7 @@ impl InnerSubscriber {
       topic,
       qos,
       reader_like_stateless,
+      read_start,
     )?;
 
     Ok(NoKeyDataReader::<D, SA>::from_keyed(d))
@@ -1349,6 +1797,7 @@ impl InnerSubscriber {
       topic,
       qos,
       false,
+      ReadStart::AllAvailable,
     )?;
 
     Ok(no_key::SimpleDataReader::<D, SA>::from_keyed(d))
@@ -1374,9 +1823,92 @@ impl InnerSubscriber {
       .or_else(|e| create_error_dropped!("Where is my DomainParticipant? {}", e))?;
     Ok(entity_id_opt.unwrap_or_else(|| dp.new_entity_id(entity_kind)))
   }
+
+  pub fn get_default_datareader_qos(&self) -> QosPolicies {
+    self.default_datareader_qos_lock().clone()
+  }
+
+  pub fn set_default_datareader_qos(&self, q: &QosPolicies) {
+    *self.default_datareader_qos_lock() = q.clone();
+  }
+
+  fn default_datareader_qos_lock(&self) -> MutexGuard<'_, QosPolicies> {
+    self.default_datareader_qos.lock().unwrap_or_else(|e| {
+      panic!("RustDDS internal bug: default DataReader QoS lock poisoned: {e:?}")
+    })
+  }
+}
+
+impl Debug for InnerSubscriber {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_fmt(format_args!("{:?}", self.participant()))?;
+    f.write_fmt(format_args!("Subscriber QoS: {:?}", self.qos))?;
+    f.write_fmt(format_args!(
+      "Subscribers default Reader QoS: {:?}",
+      self.get_default_datareader_qos()
+    ))
+  }
 }
 
 // -------------------------------------------------------------------
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+  use super::*;
+  use crate::dds::qos::policy::{Durability, History, Reliability};
+
+  // Endpoints created without QoS get the default endpoint QoS of their
+  // Publisher / Subscriber, modified by the Topic QoS.
+  #[test]
+  fn endpoints_inherit_default_qos() {
+    let dp = DomainParticipant::new(0).unwrap();
+    let topic_qos = QosPolicyBuilder::new()
+      .durability(Durability::TransientLocal)
+      .build();
+    let topic = dp
+      .create_topic(
+        "default_qos_topic".to_string(),
+        "i32".to_string(),
+        &topic_qos,
+        TopicKind::NoKey,
+      )
+      .unwrap();
+
+    let defaults = QosPolicyBuilder::new()
+      .reliability(Reliability::Reliable {
+        max_blocking_time: crate::Duration::from_millis(100),
+      })
+      .history(History::KeepLast { depth: 7 })
+      .durability(Durability::Volatile)
+      .build();
+
+    let mut publisher = dp.create_publisher(&QosPolicies::qos_none()).unwrap();
+    publisher.set_default_datawriter_qos(&defaults);
+    let writer = publisher
+      .create_datawriter_no_key_cdr::<i32>(&topic, None)
+      .unwrap();
+
+    let mut subscriber = dp.create_subscriber(&QosPolicies::qos_none()).unwrap();
+    subscriber.set_default_datareader_qos(&defaults);
+    let reader = subscriber
+      .create_datareader_no_key_cdr::<i32>(&topic, None)
+      .unwrap();
+
+    for qos in [writer.qos(), reader.qos()] {
+      assert_eq!(qos.reliability, defaults.reliability);
+      assert_eq!(qos.history, Some(History::KeepLast { depth: 7 }));
+      // Topic QoS overrides the defaults
+      assert_eq!(qos.durability, Some(Durability::TransientLocal));
+    }
+
+    // QoS given at creation overrides both
+    let reader = subscriber
+      .create_datareader_no_key_cdr::<i32>(
+        &topic,
+        Some(QosPolicyBuilder::new().history(History::KeepAll).build()),
+      )
+      .unwrap();
+    assert_eq!(reader.qos().history, Some(History::KeepAll));
+    assert_eq!(reader.qos().reliability, defaults.reliability);
+  }
+}
@@ -44,11 +44,11 @@ pub enum QosPolicyId {
   // OwnershipStrength, // 7
   Liveliness,
   TimeBasedFilter, // 9
-  // Partition,
+  Partition,
 
-  // Note: If "Partition" is ever implemented, observe also DDS Security spec v1.1
-  // Section "7.3.5 Immutability of Publisher Partition Qos in combination with non-volatile
-  // Durability kind" when implementing.
+  // Note: With security, observe also DDS Security spec v1.1 Section "7.3.5
+  // Immutability of Publisher Partition Qos in combination with non-volatile
+  // Durability kind". RustDDS does not change the QoS of existing entities.
   Reliability, // 11
   DestinationOrder,
   History, // 13
@@ -60,9 +60,9 @@ pub enum QosPolicyId {
   // GroupData,
   // TransportPriority, // 20
   Lifespan,
-  // DurabilityService, // 22
-  Representation, // 23 (DDS-XTypes v1.3 DATA_REPRESENTATION)
-  Property,       // No Id in the security spec (But this is from older DDS/RTPs spec.)
+  DurabilityService, // 22
+  Representation,    // 23 (DDS-XTypes v1.3 DATA_REPRESENTATION)
+  Property,          // No Id in the security spec (But this is from older DDS/RTPs spec.)
 }
 
 /// Utility for building [QosPolicies]
@@ -80,6 +80,7 @@ pub struct QosPolicyBuilder {
   history: Option<policy::History>,
   resource_limits: Option<policy::ResourceLimits>,
   lifespan: Option<policy::Lifespan>,
+  durability_service: Option<policy::DurabilityService>,
   // #[cfg(feature = "security")]
   // property: Option<policy::Property>,
   //
@@ -105,6 +106,7 @@ impl QosPolicyBuilder {
       history: None,
       resource_limits: None,
       lifespan: None,
+      durability_service: None,
     }
   }
 
@@ -192,6 +194,12 @@ impl QosPolicyBuilder {
     self
   }
 
+  #[must_use]
+  pub const fn durability_service(mut self, durability_service: policy::DurabilityService) -> Self {
+    self.durability_service = Some(durability_service);
+    self
+  }
+
   pub const fn build(self) -> QosPolicies {
     QosPolicies {
       durability: self.durability,
@@ -201,14 +209,18 @@ impl QosPolicyBuilder {
       ownership: self.ownership,
       liveliness: self.liveliness,
       time_based_filter: self.time_based_filter,
+      // PARTITION and DATA_REPRESENTATION are not part of the (const) builder:
+      // they hold a `Vec` (drop glue) which is incompatible with `const fn`, and
+      // the built-in QoS policies never need them. Set them via
+      // `QosPolicies::with_partition` and
+      // `QosPolicies::with_data_representation`.
+      partition: None,
       reliability: self.reliability,
       destination_order: self.destination_order,
       history: self.history,
       resource_limits: self.resource_limits,
       lifespan: self.lifespan,
-      // DATA_REPRESENTATION is not part of the (const) builder: it holds a `Vec`
-      // (drop glue) which is incompatible with `const fn`, and the built-in QoS
-      // policies never need it. Set it via `QosPolicies::with_data_representation`.
+      durability_service: self.durability_service,
       data_representation: None,
       #[cfg(feature = "security")]
       property: None,
@@ -229,11 +241,13 @@ pub struct QosPolicies {
   pub(crate) ownership: Option<policy::Ownership>,
   pub(crate) liveliness: Option<policy::Liveliness>,
   pub(crate) time_based_filter: Option<policy::TimeBasedFilter>,
+  pub(crate) partition: Option<policy::Partition>,
   pub(crate) reliability: Option<policy::Reliability>,
   pub(crate) destination_order: Option<policy::DestinationOrder>,
   pub(crate) history: Option<policy::History>,
   pub(crate) resource_limits: Option<policy::ResourceLimits>,
   pub(crate) lifespan: Option<policy::Lifespan>,
+  pub(crate) durability_service: Option<policy::DurabilityService>,
   pub(crate) data_representation: Option<policy::DataRepresentation>,
   #[cfg(feature = "security")]
   pub(crate) property: Option<policy::Property>,
@@ -250,6 +264,86 @@ impl QosPolicies {
     QosPolicyBuilder::new()
   }
 
+  // Reliability max_blocking_time used by the presets. This is the DDS default.
+  const PRESET_MAX_BLOCKING_TIME: Duration = Duration::from_millis(100);
+
+  /// Preset for state that late-joining DataReaders should receive, e.g.
+  /// configuration or the latest status:
+  ///
+  /// * Reliability: Reliable, max_blocking_time 100 ms
+  /// * Durability: TransientLocal
+  /// * History: KeepLast, depth 1
+  ///
+  /// All other policies are unset. Presets are a starting point, which can be
+  /// modified with [`modify_by`](Self::modify_by):
+  ///
+  /// ```
+  /// # use rustdds::*;
+  /// let qos = QosPolicies::reliable_transient_local().modify_by(
+  ///   &QosPolicyBuilder::new()
+  ///     .history(policy::History::KeepLast { depth: 10 })
+  ///     .build(),
+  /// );
+  /// assert_eq!(qos.durability(), Some(policy::Durability::TransientLocal));
+  /// assert_eq!(qos.history(), Some(policy::History::KeepLast { depth: 10 }));
+  /// ```
+  pub const fn reliable_transient_local() -> Self {
+    QosPolicyBuilder::new()
+      .reliable(Self::PRESET_MAX_BLOCKING_TIME)
+      .durability(policy::Durability::TransientLocal)
+      .history(policy::History::KeepLast { depth: 1 })
+      .build()
+  }
+
+  /// Preset for data that is useful only when fresh, and only to DataReaders
+  /// that exist when it is written:
+  ///
+  /// * Reliability: BestEffort
+  /// * Durability: Volatile
+  ///
+  /// All other policies are unset.
+  pub const fn best_effort_volatile() -> Self {
+    QosPolicyBuilder::new()
+      .best_effort()
+      .durability(policy::Durability::Volatile)
+      .build()
+  }
+
+  /// Preset for periodic sensor data, where only the newest sample matters
+  /// and a lost sample is soon replaced by the next one:
+  ///
+  /// * Reliability: BestEffort
+  /// * Durability: Volatile
+  /// * History: KeepLast, depth 1
+  ///
+  /// All other policies are unset.
+  pub const fn sensor_data() -> Self {
+    QosPolicyBuilder::new()
+      .best_effort()
+      .durability(policy::Durability::Volatile)
+      .history(policy::History::KeepLast { depth: 1 })
+      .build()
+  }
+
+  /// Preset for commands, which must all be delivered in order, also to
+  /// DataReaders that join later:
+  ///
+  /// * Reliability: Reliable, max_blocking_time 100 ms
+  /// * Durability: Transient
+  /// * History: KeepAll
+  ///
+  /// All other policies are unset. A Transient DataWriter also keeps its
+  /// samples in the [`DurabilityStore`](crate::DurabilityStore) of the
+  /// DomainParticipant, so they outlive the DataWriter. With KeepAll, the
+  /// history is bounded only by ResourceLimits, so consider setting those, too.
+  pub const fn command() -> Self {
+    QosPolicyBuilder::new()
+      .reliable(Self::PRESET_MAX_BLOCKING_TIME)
+      .durability(policy::Durability::Transient)
+      .history(policy::History::KeepAll)
+      .build()
+  }
+
   pub const fn durability(&self) -> Option<policy::Durability> {
     self.durability
   }
@@ -282,6 +376,20 @@ impl QosPolicies {
     self.time_based_filter
   }
 
+  pub fn partition(&self) -> Option<policy::Partition> {
+    self.partition.clone()
+  }
+
+  /// Set the PARTITION QoS policy (DDS v1.4 Section 2.2.3.13).
+  ///
+  /// This is not part of [`QosPolicyBuilder`] because it holds a `Vec`
+  /// (incompatible with the `const` builder used for built-in QoS).
+  #[must_use]
+  pub fn with_partition(mut self, partition: policy::Partition) -> Self {
+    self.partition = Some(partition);
+    self
+  }
+
   pub const fn reliability(&self) -> Option<policy::Reliability> {
     self.reliability
   }
@@ -314,6 +422,10 @@ impl QosPolicies {
     self.lifespan
   }
 
+  pub const fn durability_service(&self) -> Option<policy::DurabilityService> {
+    self.durability_service
+  }
+
   pub fn data_representation(&self) -> Option<policy::DataRepresentation> {
     self.data_representation.clone()
   }
@@ -353,11 +465,13 @@ impl QosPolicies {
       ownership: other.ownership.or(self.ownership),
       liveliness: other.liveliness.or(self.liveliness),
       time_based_filter: other.time_based_filter.or(self.time_based_filter),
+      partition: other.partition.clone().or(self.partition.clone()),
       reliability: other.reliability.or(self.reliability),
       destination_order: other.destination_order.or(self.destination_order),
       history: other.history.or(self.history),
       resource_limits: other.resource_limits.or(self.resource_limits),
       lifespan: other.lifespan.or(self.lifespan),
+      durability_service: other.durability_service.or(self.durability_service),
       data_representation: other
         .data_representation
         .clone()
@@ -494,6 +608,28 @@ impl QosPolicies {
       }
     }
 
+    // check Time Based Filter: the requested deadline period must be at least
+    // the requested minimum_separation (DDS spec v1.4 Section 2.2.3.12). This
+    // is a consistency rule within the requested QoS, and the offered QoS does
+    // not matter. Default: zero separation, INFINITE deadline.
+    {
+      let req_separation = other
+        .time_based_filter
+        .map_or(Duration::ZERO, |tbf| tbf.minimum_separation);
+      let req_deadline = other
+        .deadline
+        .unwrap_or(policy::Deadline(Duration::INFINITE));
+      if req_deadline.0 < req_separation {
+        return Some(QosPolicyId::TimeBasedFilter);
+      }
+    }
+
+    // check Partition: at least one partition name in common, see
+    // `Partition::matches`. Default: the empty (default) partition.
+    if !policy::Partition::matches(self.partition.as_ref(), other.partition.as_ref()) {
+      return Some(QosPolicyId::Partition);
+    }
+
     // check Reliability
     // offered kind >= requested kind
     // kind ranking: BEST_EFFORT < RELIABLE
@@ -560,11 +696,13 @@ impl QosPolicies {
       ownership,
       liveliness,
       time_based_filter,
+      partition,
       reliability,
       destination_order,
       history,
       resource_limits,
       lifespan,
+      durability_service,
       data_representation,
       #[cfg(feature = "security")]
         property: _, // TODO: properties to parameter list?
@@ -611,6 +749,7 @@ impl QosPolicies {
       time_based_filter,
       policy::TimeBasedFilter
     );
+    emit_option!(PID_PARTITION, partition, policy::Partition);
 
     if let Some(rel) = reliability.as_ref() {
       let reliability_ser = match rel {
@@ -647,6 +786,11 @@ impl QosPolicies {
     }
     emit_option!(PID_RESOURCE_LIMITS, resource_limits, policy::ResourceLimits);
     emit_option!(PID_LIFESPAN, lifespan, policy::Lifespan);
+    emit_option!(
+      PID_DURABILITY_SERVICE,
+      durability_service,
+      policy::DurabilityService
+    );
     emit_option!(
       PID_DATA_REPRESENTATION,
       data_representation,
@@ -674,19 +818,15 @@ impl QosPolicies {
     // Ownership is in 2 parts
     let ownership_kind: Option<OwnershipKind> = get_option!(PID_OWNERSHIP);
     let ownership_strength: Option<i32> = get_option!(PID_OWNERSHIP_STRENGTH);
+    // Some implementations send the strength regardless of the kind, and the
+    // strength defaults to zero (DDS spec v1.4 Section 2.2.3.10), so neither
+    // an extra nor a missing strength loses the policy.
     let ownership = match (ownership_kind, ownership_strength) {
-      (Some(OwnershipKind::Shared), None) => Some(policy::Ownership::Shared),
-      (Some(OwnershipKind::Shared), Some(_strength)) => {
-        warn!("QosPolicies deserializer: Received OwnershipKind::Shared and a strength value.");
-        None
-      }
+      (Some(OwnershipKind::Shared), _) => Some(policy::Ownership::Shared),
       (Some(OwnershipKind::Exclusive), Some(strength)) => {
         Some(policy::Ownership::Exclusive { strength })
       }
-      (Some(OwnershipKind::Exclusive), None) => {
-        warn!("QosPolicies deserializer: Received OwnershipKind::Exclusive but no strength value.");
-        None
-      }
+      (Some(OwnershipKind::Exclusive), None) => Some(policy::Ownership::Exclusive { strength: 0 }),
       (None, Some(_strength)) => {
         warn!(
           "QosPolicies deserializer: Received ownership strength value, but no kind parameter."
@@ -713,9 +853,11 @@ impl QosPolicies {
 
     let liveliness: Option<policy::Liveliness> = get_option!(PID_LIVELINESS);
     let time_based_filter: Option<policy::TimeBasedFilter> = get_option!(PID_TIME_BASED_FILTER);
+    let partition: Option<policy::Partition> = get_option!(PID_PARTITION);
 
     let resource_limits: Option<policy::ResourceLimits> = get_option!(PID_RESOURCE_LIMITS);
     let lifespan: Option<policy::Lifespan> = get_option!(PID_LIFESPAN);
+    let durability_service: Option<policy::DurabilityService> = get_option!(PID_DURABILITY_SERVICE);
     let data_representation: Option<policy::DataRepresentation> =
       get_option!(PID_DATA_REPRESENTATION);
 
@@ -732,11 +874,13 @@ impl QosPolicies {
       ownership,
       liveliness,
       time_based_filter,
+      partition,
       reliability,
       destination_order,
       history,
       resource_limits,
       lifespan,
+      durability_service,
       data_representation,
       #[cfg(feature = "security")]
       property,
@@ -782,16 +926,17 @@ pub const LENGTH_UNLIMITED: i32 = -1;
 pub mod policy {
   use std::cmp::Ordering;
 
-  use speedy::{Readable, Writable};
+  use speedy::{Context, Readable, Reader, Writable, Writer};
   use serde::{Deserialize, Serialize};
   #[allow(unused_imports)]
   use log::{debug, error, info, trace, warn};
   #[cfg(feature = "security")]
-  use speedy::{Context, IsEof, Reader, Writer};
+  use speedy::IsEof;
 
-  use crate::structure::duration::Duration;
-  #[cfg(feature = "security")]
-  use crate::serialization::speedy_pl_cdr_helpers::*;
+  use crate::{
+    serialization::speedy_pl_cdr_helpers::*, structure::duration::Duration,
+    RepresentationIdentifier,
+  };
 
   /*
   pub struct UserData {
@@ -812,6 +957,11 @@ pub mod policy {
   */
 
   /// DDS 2.2.3.16 LIFESPAN
+  ///
+  /// A sample expires `duration` after its source timestamp. Samples written
+  /// without a source timestamp are stamped with the time of writing. A
+  /// DataWriter does not send expired samples, not even to late-joining
+  /// DataReaders.
   #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Readable, Writable, Serialize, Deserialize)]
   pub struct Lifespan {
     pub duration: Duration,
@@ -841,6 +991,8 @@ pub mod policy {
     Serialize,
     Deserialize,
   )]
+  // The variants are in increasing order of durability. The derived ordering
+  // is used in the QoS compatibility check, so do not reorder them.
   pub enum Durability {
     Volatile,
     TransientLocal,
@@ -878,6 +1030,10 @@ pub mod policy {
   }
 
   /// DDS 2.2.3.7 DEADLINE
+  ///
+  /// A DataReader reports a missed deadline when a matched DataWriter has not
+  /// sent a sample within the deadline. A newly matched DataWriter has the
+  /// deadline to send its first sample.
   #[derive(
     Copy,
     Clone,
@@ -900,27 +1056,24 @@ pub mod policy {
     pub duration: Duration,
   }
 
-  /// DDS 2.2.3.9 OWNERSHIP
-  ///
-  /// # Support status / known limitation
+  /// DDS 2.2.3.9 OWNERSHIP and 2.2.3.10 OWNERSHIP_STRENGTH
   ///
-  /// RustDDS uses OWNERSHIP only for **matching**: a `Shared` endpoint and an
-  /// `Exclusive` endpoint are incompatible (`INCOMPATIBLE_QOS`), while the
-  /// `strength` value does *not* affect matching (it is a writer-only policy,
-  /// DDS spec v1.4 §2.2.3.10).
+  /// A `Shared` endpoint and an `Exclusive` endpoint are incompatible
+  /// (`INCOMPATIBLE_QOS`). The `strength` does not affect matching. It is
+  /// used by DataWriters only.
   ///
-  /// RustDDS does **not** implement EXCLUSIVE-ownership *delivery filtering*.
-  /// When several EXCLUSIVE writers publish to the **same instance**, a correct
-  /// implementation would deliver only the samples of the highest-strength
-  /// live writer (with owner hand-off on liveliness loss / unregister). RustDDS
-  /// instead delivers samples from all matched writers. Consequently a reader
-  /// sees data from every EXCLUSIVE writer regardless of `strength`.
+  /// An `Exclusive` DataReader delivers the samples of each instance from one
+  /// DataWriter only, the owner of the instance. The owner is the DataWriter
+  /// with the highest `strength`, of those that write the instance. The owner
+  /// loses the instance, if it is no longer matched, loses its liveliness,
+  /// misses the Deadline for the instance, or unregisters the instance. Then
+  /// the next sample of the instance from any DataWriter makes that writer the
+  /// owner, so the instance fails over to the strongest remaining writer as
+  /// soon as it writes. If the strengths are equal, the DataWriter with the
+  /// smaller GUID wins.
   ///
-  /// This surfaces in the dds-rtps interoperability suite as the single failing
-  /// ownership case `Test_Ownership_3` (two same-instance EXCLUSIVE writers,
-  /// expected `RECEIVING_FROM_ONE`, RustDDS yields `RECEIVING_FROM_BOTH`). All
-  /// other ownership cases pass. Implementing per-instance owner tracking would
-  /// remove this limitation.
+  /// The instance of a sample is known from its key hash. Samples without a
+  /// key hash are not arbitrated.
   #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
   pub enum Ownership {
     Shared,
@@ -934,7 +1087,8 @@ pub mod policy {
   pub const XCDR_DATA_REPRESENTATION: DataRepresentationId = 0;
   /// XML data representation (not supported by RustDDS).
   pub const XML_DATA_REPRESENTATION: DataRepresentationId = 1;
-  /// Extensible CDR encoding version 2 (not supported by RustDDS).
+  /// Extensible CDR encoding version 2. See
+  /// [`CDR2SerializerAdapter`](crate::CDR2SerializerAdapter).
   pub const XCDR2_DATA_REPRESENTATION: DataRepresentationId = 2;
 
   /// DDS-XTypes v1.3 Section 7.6.3.1 DATA_REPRESENTATION QoS policy.
@@ -971,6 +1125,34 @@ pub mod policy {
         _ => vec![XCDR_DATA_REPRESENTATION],
       }
     }
+
+    // The policy a DataReader advertises when the application has not set one.
+    // It lists XCDR2 if the deserializer supports it, so that XCDR2 writers
+    // match. Otherwise the policy is left out, which means XCDR1.
+    pub(crate) fn default_accepted(
+      supported_encodings: &[RepresentationIdentifier],
+    ) -> Option<Self> {
+      let mut value: Vec<DataRepresentationId> = Vec::new();
+      for id in supported_encodings
+        .iter()
+        .filter_map(|e| e.data_representation_id())
+      {
+        if !value.contains(&id) {
+          value.push(id);
+        }
+      }
+      value
+        .contains(&XCDR2_DATA_REPRESENTATION)
+        .then_some(Self { value })
+    }
+
+    // The policy a DataWriter advertises when the application has not set one.
+    // Only XCDR2 needs to be announced, as XCDR1 is the default.
+    pub(crate) fn default_offered(output_encoding: RepresentationIdentifier) -> Option<Self> {
+      (output_encoding.data_representation_id() == Some(XCDR2_DATA_REPRESENTATION)).then(|| Self {
+        value: vec![XCDR2_DATA_REPRESENTATION],
+      })
+    }
   }
 
   /// DDS 2.2.3.11 LIVELINESS
@@ -1017,16 +1199,142 @@ pub mod policy {
   }
 
   /// DDS 2.2.3.12 TIME_BASED_FILTER
+  ///
+  /// A DataReader drops a sample that arrives sooner than `minimum_separation`
+  /// after the last sample of the same instance that it accepted. The samples
+  /// are dropped before they reach the reader history, so they do not take
+  /// space from `History::KeepLast`. Instance state changes (dispose,
+  /// unregister) are never dropped.
+  ///
+  /// The instance of a sample of a WITH_KEY topic is known from its key hash.
+  /// Samples without a key hash are not filtered.
+  ///
+  /// A Deadline period shorter than `minimum_separation` is inconsistent.
+  /// Such a DataReader does not match any DataWriter, and it reports
+  /// [`QosPolicyId::TimeBasedFilter`](super::QosPolicyId::TimeBasedFilter) as
+  /// the incompatible QoS.
   #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Readable, Writable, Serialize, Deserialize)]
   pub struct TimeBasedFilter {
     pub minimum_separation: Duration,
   }
 
-  /*
+  /// DDS 2.2.3.13 PARTITION
+  ///
+  /// A DataWriter and a DataReader match only if they have at least one
+  /// partition name in common. A name may be a pattern with the wildcards `*`
+  /// (any sequence of characters) and `?` (any single character), which is
+  /// matched against the plain names of the other side. Two patterns match
+  /// only if they are the same string.
+  ///
+  /// An empty list, or no Partition policy at all, means the default
+  /// partition, which matches only the default partition.
+  ///
+  /// RustDDS reports a Partition mismatch like an incompatible QoS, with
+  /// [`QosPolicyId::Partition`](super::QosPolicyId::Partition).
+  ///
+  /// On the wire this is `PID_PARTITION`, a CDR `sequence<string>`.
+  #[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
   pub struct Partition {
-    pub name: Vec<Vec<u8>>,
+    pub name: Vec<String>,
+  }
+
+  impl Partition {
+    pub fn new(names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+      Self {
+        name: names.into_iter().map(Into::into).collect(),
+      }
+    }
+
+    /// Do the partitions of a DataWriter and a DataReader have a name in
+    /// common? `None` means the default partition.
+    pub fn matches(a: Option<&Partition>, b: Option<&Partition>) -> bool {
+      let names = |p: Option<&Partition>| match p {
+        Some(p) if !p.name.is_empty() => p.name.clone(),
+        _ => vec![String::new()], // the default partition
+      };
+      let (a, b) = (names(a), names(b));
+      a.iter().any(|x| b.iter().any(|y| names_match(x, y)))
+    }
+  }
+
+  fn is_pattern(name: &str) -> bool {
+    name.contains(['*', '?'])
+  }
+
+  fn names_match(a: &str, b: &str) -> bool {
+    match (is_pattern(a), is_pattern(b)) {
+      // The default partition "" is matched only by itself.
+      (true, false) => !b.is_empty() && wildcard_match(a, b),
+      (false, true) => !a.is_empty() && wildcard_match(b, a),
+      _ => a == b,
+    }
+  }
+
+  // Does `name` match `pattern`, where `*` matches any sequence of characters
+  // and `?` any single character?
+  fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` in pattern, and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+      match pattern.get(p) {
+        Some('*') => {
+          backtrack = Some((p, n));
+          p += 1;
+        }
+        Some(&c) if c == '?' || c == name[n] => {
+          p += 1;
+          n += 1;
+        }
+        _ => match backtrack {
+          // Let the last `*` match one more character.
+          Some((star_p, star_n)) => {
+            backtrack = Some((star_p, star_n + 1));
+            p = star_p + 1;
+            n = star_n + 1;
+          }
+          None => return false,
+        },
+      }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+  }
+
+  impl<'a, C: Context> Readable<'a, C> for Partition {
+    fn minimum_bytes_needed() -> usize {
+      4
+    }
+
+    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
+      let count = reader.read_u32()?;
+      let mut name = Vec::new();
+      let mut prev_len = 0;
+      for _ in 0..count {
+        read_pad(reader, prev_len, 4)?;
+        let s: StringWithNul = reader.read_value()?;
+        prev_len = s.len();
+        name.push(s.into());
+      }
+      Ok(Partition { name })
+    }
+  }
+
+  // Alignment comes before each string length.
+  impl<C: Context> Writable<C> for Partition {
+    fn write_to<T: ?Sized + Writer<C>>(&self, writer: &mut T) -> Result<(), C::Error> {
+      writer.write_u32(self.name.len() as u32)?;
+      let mut prev_len = 0;
+      for n in &self.name {
+        write_pad(writer, prev_len, 4)?;
+        let s = StringWithNul::from(n);
+        writer.write_value(&s)?;
+        prev_len = s.len();
+      }
+      Ok(())
+    }
   }
-  */
 
   /// DDS 2.2.3.14 RELIABILITY
   #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -1098,6 +1406,11 @@ pub mod policy {
   ///
   /// Negative values are needed, because DDS spec defines the special value
   /// const long LENGTH_UNLIMITED = -1;
+  ///
+  /// On the receiving side, the instance limits apply to samples that carry a
+  /// key hash. If samples are dropped to stay within `max_instances` or
+  /// `max_samples_per_instance`, the DataReader reports
+  /// [`SampleRejected`](crate::DataReaderStatus::SampleRejected).
   #[derive(Copy, Clone, Debug, PartialEq, Eq, Writable, Readable, Serialize, Deserialize)]
   pub struct ResourceLimits {
     pub max_samples: i32,
@@ -1105,6 +1418,81 @@ pub mod policy {
     pub max_samples_per_instance: i32,
   }
 
+  /// DDS Spec v1.4 Section 2.2.3.5 DURABILITY_SERVICE
+  ///
+  /// Bounds what the [`DurabilityStore`](crate::DurabilityStore) keeps of the
+  /// samples of a Transient or Persistent DataWriter: `history` and
+  /// `resource_limits` apply like for a DataWriter, and an instance is removed
+  /// `service_cleanup_delay` after it was disposed. The default keeps the
+  /// last sample of each instance, with no resource limits and no cleanup.
+  ///
+  /// On the wire this is `PID_DURABILITY_SERVICE`, which is part of the
+  /// publication and topic discovery data.
+  #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+  pub struct DurabilityService {
+    pub service_cleanup_delay: Duration,
+    pub history: History,
+    pub resource_limits: ResourceLimits,
+  }
+
+  impl Default for DurabilityService {
+    fn default() -> Self {
+      Self {
+        service_cleanup_delay: Duration::ZERO,
+        history: History::KeepLast { depth: 1 },
+        resource_limits: ResourceLimits {
+          max_samples: super::LENGTH_UNLIMITED,
+          max_instances: super::LENGTH_UNLIMITED,
+          max_samples_per_instance: super::LENGTH_UNLIMITED,
+        },
+      }
+    }
+  }
+
+  // DDS Spec v1.4 p.147 "struct DurabilityServiceQosPolicy": the history kind
+  // and depth come between the delay and the resource limits.
+  impl<'a, C: Context> Readable<'a, C> for DurabilityService {
+    fn minimum_bytes_needed() -> usize {
+      28
+    }
+
+    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
+      let service_cleanup_delay: Duration = reader.read_value()?;
+      let history_kind = reader.read_u32()?;
+      let history_depth = reader.read_i32()?;
+      let resource_limits: ResourceLimits = reader.read_value()?;
+      let history = if history_kind == 1 {
+        History::KeepAll
+      } else {
+        History::KeepLast {
+          depth: history_depth,
+        }
+      };
+      Ok(DurabilityService {
+        service_cleanup_delay,
+        history,
+        resource_limits,
+      })
+    }
+  }
+
+  impl<C: Context> Writable<C> for DurabilityService {
+    fn write_to<T: ?Sized + Writer<C>>(&self, writer: &mut T) -> Result<(), C::Error> {
+      writer.write_value(&self.service_cleanup_delay)?;
+      match self.history {
+        History::KeepLast { depth } => {
+          writer.write_u32(0)?;
+          writer.write_i32(depth)?;
+        }
+        History::KeepAll => {
+          writer.write_u32(1)?;
+          writer.write_i32(0)?;
+        }
+      }
+      writer.write_value(&self.resource_limits)
+    }
+  }
+
   #[cfg(feature = "security")]
   use crate::security;
   // DDS Security spec v1.1
@@ -1261,3 +1649,237 @@ pub mod policy {
     }
   }
 } // mod policy
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn durability(durability: Option<policy::Durability>) -> QosPolicies {
+    let builder = QosPolicyBuilder::new();
+    match durability {
+      Some(d) => builder.durability(d),
+      None => builder,
+    }
+    .build()
+  }
+
+  #[test]
+  fn durability_compatibility() {
+    use policy::Durability::*;
+    // DDS spec v1.4 Section 2.2.3.4: offered must be at least as durable as
+    // requested. `None` means the policy is not set, i.e. VOLATILE.
+    let table = [
+      // (offered, requested, compatible)
+      (Volatile, Volatile, true),
+      (Volatile, TransientLocal, false),
+      (Volatile, Transient, false),
+      (Volatile, Persistent, false),
+      (TransientLocal, Volatile, true),
+      (TransientLocal, TransientLocal, true),
+      (TransientLocal, Transient, false),
+      (TransientLocal, Persistent, false),
+      (Transient, Volatile, true),
+      (Transient, TransientLocal, true),
+      (Transient, Transient, true),
+      (Transient, Persistent, false),
+      (Persistent, Volatile, true),
+      (Persistent, TransientLocal, true),
+      (Persistent, Transient, true),
+      (Persistent, Persistent, true),
+    ];
+    for (offered, requested, compatible) in table {
+      let failure = durability(Some(offered)).compliance_failure_wrt(&durability(Some(requested)));
+      let expected = (!compatible).then_some(QosPolicyId::Durability);
+      assert_eq!(
+        failure, expected,
+        "offered {offered:?}, requested {requested:?}"
+      );
+    }
+
+    // An endpoint that does not announce durability is VOLATILE.
+    assert_eq!(
+      durability(None).compliance_failure_wrt(&durability(Some(TransientLocal))),
+      Some(QosPolicyId::Durability)
+    );
+    assert_eq!(
+      durability(Some(TransientLocal)).compliance_failure_wrt(&durability(None)),
+      None
+    );
+    assert_eq!(
+      durability(None).compliance_failure_wrt(&durability(Some(Volatile))),
+      None
+    );
+  }
+
+  fn partition(names: &[&str]) -> QosPolicies {
+    QosPolicies::qos_none().with_partition(policy::Partition::new(names.iter().copied()))
+  }
+
+  #[test]
+  fn default_data_representation() {
+    use policy::{DataRepresentation, XCDR2_DATA_REPRESENTATION, XCDR_DATA_REPRESENTATION};
+
+    use crate::RepresentationIdentifier as R;
+
+    // Readers announce XCDR2 only if they can decode it.
+    assert_eq!(
+      DataRepresentation::default_accepted(&[R::CDR_LE, R::CDR_BE, R::PL_CDR_LE]),
+      None
+    );
+    let accepted =
+      DataRepresentation::default_accepted(&[R::CDR_LE, R::CDR_BE, R::PL_XCDR2_LE, R::XCDR2_BE]);
+    assert_eq!(
+      accepted,
+      Some(DataRepresentation {
+        value: vec![XCDR_DATA_REPRESENTATION, XCDR2_DATA_REPRESENTATION],
+      })
+    );
+    assert_eq!(DataRepresentation::default_offered(R::CDR_LE), None);
+    let offered = DataRepresentation::default_offered(R::D_CDR2_BE);
+
+    // XCDR2 writers match such readers, but not XCDR1-only ones.
+    let reader = QosPolicies {
+      data_representation: accepted,
+      ..QosPolicies::qos_none()
+    };
+    let writer = QosPolicies {
+      data_representation: offered,
+      ..QosPolicies::qos_none()
+    };
+    assert_eq!(writer.compliance_failure_wrt(&reader), None);
+    assert_eq!(
+      QosPolicies::qos_none().compliance_failure_wrt(&reader),
+      None
+    );
+    assert!(writer
+      .compliance_failure_wrt(&QosPolicies::qos_none())
+      .is_some());
+  }
+
+  #[test]
+  fn partition_compatibility() {
+    let table: [(&[&str], &[&str], bool); 11] = [
+      // (offered, requested, compatible)
+      (&[], &[], true),
+      (&[""], &[], true),
+      (&["A"], &[], false),
+      (&["A"], &["A"], true),
+      (&["A"], &["B"], false),
+      (&["A", "B"], &["C", "B"], true),
+      (&["sensor*"], &["sensors/front"], true),
+      (&["sensor?"], &["sensors/front"], false),
+      (&["a?c*"], &["abcdef", "x"], true),
+      // Patterns match only plain names, not other patterns nor the default.
+      (&["A*"], &["A?"], false),
+      (&["*"], &[], false),
+    ];
+    for (offered, requested, compatible) in table {
+      let expected = (!compatible).then_some(QosPolicyId::Partition);
+      assert_eq!(
+        partition(offered).compliance_failure_wrt(&partition(requested)),
+        expected,
+        "offered {offered:?}, requested {requested:?}"
+      );
+      assert_eq!(
+        partition(requested).compliance_failure_wrt(&partition(offered)),
+        expected,
+        "offered {requested:?}, requested {offered:?}"
+      );
+    }
+    // No Partition policy is the default partition.
+    assert_eq!(
+      QosPolicies::qos_none().compliance_failure_wrt(&partition(&[""])),
+      None
+    );
+    assert_eq!(
+      QosPolicies::qos_none().compliance_failure_wrt(&partition(&["A"])),
+      Some(QosPolicyId::Partition)
+    );
+  }
+
+  #[test]
+  fn partition_parameter_round_trip() {
+    let qos = partition(&["A", "sensor*", ""]);
+    for ctx in [
+      speedy::Endianness::LittleEndian,
+      speedy::Endianness::BigEndian,
+    ] {
+      let pl = qos.to_parameter_list(ctx).unwrap();
+      let param = pl
+        .iter()
+        .find(|p| p.parameter_id == ParameterId::PID_PARTITION)
+        .unwrap();
+      // count, then each string as length (with NUL) + bytes + NUL, padded to 4
+      let (count, a_len) = match ctx {
+        speedy::Endianness::LittleEndian => ([3, 0, 0, 0], [2, 0, 0, 0]),
+        speedy::Endianness::BigEndian => ([0, 0, 0, 3], [0, 0, 0, 2]),
+      };
+      assert_eq!(param.value[0..4], count);
+      assert_eq!(param.value[4..8], a_len);
+      assert_eq!(param.value[8..10], *b"A\0");
+
+      let mut pl_map = BTreeMap::new();
+      for p in &pl {
+        pl_map
+          .entry(p.parameter_id)
+          .or_insert_with(Vec::new)
+          .push(p);
+      }
+      assert_eq!(QosPolicies::from_parameter_list(ctx, &pl_map).unwrap(), qos);
+    }
+  }
+
+  #[test]
+  fn durability_service_parameter_round_trip() {
+    let qos = QosPolicyBuilder::new()
+      .durability_service(policy::DurabilityService {
+        service_cleanup_delay: Duration::from_secs(5),
+        history: policy::History::KeepLast { depth: 3 },
+        resource_limits: policy::ResourceLimits {
+          max_samples: 100,
+          max_instances: 10,
+          max_samples_per_instance: LENGTH_UNLIMITED,
+        },
+      })
+      .build();
+    let ctx = speedy::Endianness::LittleEndian;
+    let pl = qos.to_parameter_list(ctx).unwrap();
+    let param = pl
+      .iter()
+      .find(|p| p.parameter_id == ParameterId::PID_DURABILITY_SERVICE)
+      .unwrap();
+    // delay (8), history kind and depth (4 + 4), resource limits (3 * 4)
+    assert_eq!(param.value.len(), 28);
+    assert_eq!(param.value[8..16], [0, 0, 0, 0, 3, 0, 0, 0]);
+
+    let pl_map = pl.iter().map(|p| (p.parameter_id, vec![p])).collect();
+    assert_eq!(QosPolicies::from_parameter_list(ctx, &pl_map).unwrap(), qos);
+  }
+
+  #[test]
+  fn time_based_filter_must_not_exceed_deadline() {
+    let requested = |separation_ms, deadline_ms: Option<i64>| {
+      let b = QosPolicyBuilder::new().time_based_filter(policy::TimeBasedFilter {
+        minimum_separation: Duration::from_millis(separation_ms),
+      });
+      match deadline_ms {
+        Some(ms) => b.deadline(policy::Deadline(Duration::from_millis(ms))),
+        None => b,
+      }
+      .build()
+    };
+    let offered = QosPolicyBuilder::new()
+      .deadline(policy::Deadline(Duration::from_millis(100)))
+      .build();
+
+    assert_eq!(offered.compliance_failure_wrt(&requested(500, None)), None);
+    assert_eq!(
+      offered.compliance_failure_wrt(&requested(500, Some(500))),
+      None
+    );
+    assert_eq!(
+      offered.compliance_failure_wrt(&requested(500, Some(499))),
+      Some(QosPolicyId::TimeBasedFilter)
+    );
+  }
+}
@@ -209,6 +209,17 @@ pub enum CreateError {
   #[error("Resource allocation failed: {reason}")]
   OutOfResources { reason: String },
 
+  /// A network socket could not be bound to `port`. If the port is already in
+  /// use, e.g. by another participant with the same participant id, `source`
+  /// has kind [`AddrInUse`](std::io::ErrorKind::AddrInUse). Port 0 means that
+  /// any port was requested.
+  #[error("Cannot bind UDP port {port}: {source}")]
+  Bind {
+    port: u16,
+    #[source]
+    source: std::io::Error,
+  },
+
   #[cfg(feature = "security")]
   #[error("Not allowed by security: {reason}")]
   NotAllowedBySecurity { reason: String },
@@ -299,6 +310,10 @@ pub type CreateResult<T> = std::result::Result<T, CreateError>;
 pub enum WaitError {
   #[error("Waiting timed out")]
   Timeout,
+
+  /// a [`std::io::Error`] occurred while waiting.
+  #[error("std:io:Error {0}")]
+  Io(#[from] std::io::Error),
 }
 
 pub type WaitResult<T> = std::result::Result<T, WaitError>;
@@ -0,0 +1,219 @@
+use std::{
+  collections::BTreeMap,
+  future::Future,
+  pin::Pin,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Condvar, Mutex, OnceLock,
+  },
+  task::{Context, Poll, Waker},
+  thread,
+  time::{Duration, Instant},
+};
+
+use futures::stream::Stream;
+use log::error;
+
+/// Timeouts for the async sample streams, e.g.
+/// [`DataReaderStream`](crate::with_key::DataReaderStream) and
+/// [`BareDataReaderStream`](crate::with_key::BareDataReaderStream).
+///
+/// This works with any async runtime, as the timeouts are run by a background
+/// thread of RustDDS.
+pub trait SampleStreamExt: Stream + Unpin {
+  /// Waits for the next item of the stream for at most `timeout`.
+  ///
+  /// Resolves to `None` if no item arrived in time. The future is
+  /// cancellation-safe: an item is taken from the stream only when the future
+  /// resolves to it, so dropping the future, or timing out, never loses a
+  /// sample. An item that is available when the timeout expires is returned
+  /// instead of `None`.
+  ///
+  /// ```no_run
+  /// # use std::time::Duration;
+  /// # use rustdds::{with_key::DataReaderStream, Keyed, SampleStreamExt};
+  /// # async fn example<D: Keyed + 'static>(mut stream: DataReaderStream<D>)
+  /// # where D: for<'de> serde::Deserialize<'de>, D::K: for<'de> serde::Deserialize<'de> {
+  /// match stream.next_with_timeout(Duration::from_secs(1)).await {
+  ///   Some(Ok(sample)) => println!("Got sample {:?}", sample.sample_info()),
+  ///   Some(Err(e)) => println!("Read error {e}"),
+  ///   None => println!("Nothing received in a second"),
+  /// }
+  /// # }
+  /// ```
+  fn next_with_timeout(&mut self, timeout: Duration) -> NextWithTimeout<'_, Self> {
+    NextWithTimeout {
+      stream: self,
+      // A timeout too long to represent never expires.
+      deadline: Instant::now().checked_add(timeout),
+      timer_id: None,
+    }
+  }
+}
+
+impl<S: Stream + Unpin + ?Sized> SampleStreamExt for S {}
+
+/// Future returned by [`SampleStreamExt::next_with_timeout`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct NextWithTimeout<'a, S: ?Sized> {
+  stream: &'a mut S,
+  deadline: Option<Instant>,
+  // Registration with the timer thread, once we have had to wait.
+  timer_id: Option<u64>,
+}
+
+impl<S: Stream + Unpin + ?Sized> Future for NextWithTimeout<'_, S> {
+  type Output = Option<S::Item>;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    // Poll the stream before looking at the clock, so that an item arriving
+    // just at the deadline is not discarded.
+    if let Poll::Ready(item) = Pin::new(&mut *self.stream).poll_next(cx) {
+      return Poll::Ready(item);
+    }
+    let Some(deadline) = self.deadline else {
+      return Poll::Pending;
+    };
+    if Instant::now() >= deadline {
+      return Poll::Ready(None);
+    }
+    let timer = Timer::get();
+    let id = *self.timer_id.get_or_insert_with(|| timer.new_id());
+    timer.register(deadline, id, cx.waker());
+    Poll::Pending
+  }
+}
+
+impl<S: ?Sized> Drop for NextWithTimeout<'_, S> {
+  fn drop(&mut self) {
+    if let (Some(deadline), Some(id)) = (self.deadline, self.timer_id) {
+      Timer::get().unregister(deadline, id);
+    }
+  }
+}
+
+// Wakes up the waiting NextWithTimeout futures at their deadlines. There is
+// only one timer thread, started on first use.
+struct Timer {
+  next_id: AtomicU64,
+  wakeups: Mutex<BTreeMap<(Instant, u64), Waker>>,
+  wakeups_changed: Condvar,
+}
+
+impl Timer {
+  fn get() -> &'static Self {
+    static TIMER: OnceLock<&'static Timer> = OnceLock::new();
+    TIMER.get_or_init(|| {
+      let timer: &'static Self = Box::leak(Box::new(Self {
+        next_id: AtomicU64::new(0),
+        wakeups: Mutex::new(BTreeMap::new()),
+        wakeups_changed: Condvar::new(),
+      }));
+      let spawned = thread::Builder::new()
+        .name("RustDDS stream timer".to_owned())
+        .spawn(move || timer.run());
+      if let Err(e) = spawned {
+        // Timeouts then expire only when polled for other reasons.
+        error!("Cannot start stream timer thread: {e}");
+      }
+      timer
+    })
+  }
+
+  fn new_id(&self) -> u64 {
+    self.next_id.fetch_add(1, Ordering::Relaxed)
+  }
+
+  fn register(&self, deadline: Instant, id: u64, waker: &Waker) {
+    let mut wakeups = self.wakeups.lock().unwrap();
+    match wakeups.get_mut(&(deadline, id)) {
+      Some(w) => w.clone_from(waker),
+      None => {
+        wakeups.insert((deadline, id), waker.clone());
+        // The timer thread may be sleeping until a later deadline.
+        if wakeups
+          .first_key_value()
+          .is_some_and(|(key, _)| key.1 == id)
+        {
+          self.wakeups_changed.notify_one();
+        }
+      }
+    }
+  }
+
+  fn unregister(&self, deadline: Instant, id: u64) {
+    self.wakeups.lock().unwrap().remove(&(deadline, id));
+  }
+
+  fn run(&self) {
+    let mut wakeups = self.wakeups.lock().unwrap();
+    loop {
+      let now = Instant::now();
+      let mut expired = Vec::new();
+      while let Some(entry) = wakeups.first_entry() {
+        if entry.key().0 > now {
+          break;
+        }
+        expired.push(entry.remove());
+      }
+      if !expired.is_empty() {
+        // Do not hold the lock while wakers run.
+        drop(wakeups);
+        expired.into_iter().for_each(Waker::wake);
+        wakeups = self.wakeups.lock().unwrap();
+        continue;
+      }
+      wakeups = match wakeups.first_key_value() {
+        Some(((deadline, _), _)) => {
+          let sleep = deadline.saturating_duration_since(now);
+          self.wakeups_changed.wait_timeout(wakeups, sleep).unwrap().0
+        }
+        None => self.wakeups_changed.wait(wakeups).unwrap(),
+      };
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use futures::{channel::mpsc, executor::block_on};
+
+  use super::*;
+
+  #[test]
+  fn next_with_timeout_times_out_on_empty_stream() {
+    let (_sender, mut receiver) = mpsc::unbounded::<u32>();
+    let start = Instant::now();
+    let item = block_on(receiver.next_with_timeout(Duration::from_millis(100)));
+    assert_eq!(item, None);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+  }
+
+  #[test]
+  fn next_with_timeout_returns_item_arriving_in_time() {
+    let (sender, mut receiver) = mpsc::unbounded::<u32>();
+    let sending = thread::spawn(move || {
+      thread::sleep(Duration::from_millis(50));
+      sender.unbounded_send(7).unwrap();
+    });
+    let item = block_on(receiver.next_with_timeout(Duration::from_secs(10)));
+    assert_eq!(item, Some(7));
+    sending.join().unwrap();
+  }
+
+  #[test]
+  fn next_with_timeout_prefers_item_over_expired_timeout() {
+    let (sender, mut receiver) = mpsc::unbounded::<u32>();
+    sender.unbounded_send(1).unwrap();
+    // Already expired, but there is an item available.
+    let item = block_on(receiver.next_with_timeout(Duration::ZERO));
+    assert_eq!(item, Some(1));
+
+    // A dropped timeout does not lose items sent after it.
+    let pending = receiver.next_with_timeout(Duration::from_secs(10));
+    drop(pending);
+    sender.unbounded_send(2).unwrap();
+    let item = block_on(receiver.next_with_timeout(Duration::from_millis(100)));
+    assert_eq!(item, Some(2));
+  }
+}
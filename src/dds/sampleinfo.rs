@@ -2,7 +2,13 @@ use enumflags2::{bitflags, BitFlags};
 
 use crate::{
   dds::with_key::datawriter::WriteOptions,
-  structure::{guid::GUID, rpc::SampleIdentity, sequence_number::SequenceNumber, time::Timestamp},
+  structure::{
+    guid::{GuidPrefix, GUID},
+    locator::Locator,
+    rpc::SampleIdentity,
+    sequence_number::SequenceNumber,
+    time::Timestamp,
+  },
 };
 
 // use std::num::Zero; // unstable
@@ -226,6 +232,10 @@ pub struct SampleInfo {
 
   pub(crate) write_options: WriteOptions,
 
+  /// Time when this DataReader received the sample. Not a standard DDS
+  /// field.
+  pub(crate) reception_timestamp: Timestamp,
+
   /// publication_handle identifies the DataWriter that modified
   /// the instance (i.e. wrote this sample)
   pub(crate) publication_handle: GUID,
@@ -239,6 +249,12 @@ impl SampleInfo {
     self.write_options.source_timestamp()
   }
 
+  /// Time when the sample was received, from the participant clock. Samples
+  /// are ordered by it, so it differs for every sample of a DataReader.
+  pub fn reception_timestamp(&self) -> Timestamp {
+    self.reception_timestamp
+  }
+
   pub fn sample_state(&self) -> SampleState {
     self.sample_state
   }
@@ -293,10 +309,15 @@ impl SampleInfo {
     self.publication_handle
   }
 
+  /// The sample this sample is related to, if the DataWriter set one with
+  /// [`WriteOptionsBuilder::related_sample_identity`](crate::WriteOptionsBuilder::related_sample_identity).
+  /// For a reply, this is the identity of the request.
   pub fn related_sample_identity(&self) -> Option<SampleIdentity> {
     self.write_options.related_sample_identity()
   }
 
+  /// The writer and sequence number of this sample. This equals the
+  /// identity returned by `write_with_options` to the DataWriter.
   pub fn sample_identity(&self) -> SampleIdentity {
     SampleIdentity {
       writer_guid: self.publication_handle,
@@ -304,7 +325,43 @@ impl SampleInfo {
     }
   }
 
+  /// RTPS routing context in which this sample was received. Recorded only if
+  /// enabled with
+  /// [`DomainParticipantBuilder::record_reception_context`](crate::DomainParticipantBuilder::record_reception_context).
+  pub fn reception_context(&self) -> Option<&ReceptionContext> {
+    self.write_options.reception_context()
+  }
+
+  /// True if the serialized payload of this sample is identical to that of
+  /// the previous sample of the same instance, e.g. because the DataWriter
+  /// resent unchanged state. Detected only if enabled with
+  /// [`DataReader::set_detect_content_duplicates`](crate::with_key::DataReader::set_detect_content_duplicates).
+  pub fn is_content_duplicate(&self) -> bool {
+    self.write_options.content_duplicate()
+  }
+
   // pub fn set_publication_handle(&mut self, publication_handle: GUID) {
   //   self.publication_handle = publication_handle
   // }
 }
+
+/// RTPS message receiver state at the time a sample was received, i.e. the
+/// routing context of the sample. See RTPS spec v2.5 Section 8.3.4 "The RTPS
+/// Message Receiver".
+///
+/// This is what e.g. a relay or a monitoring tool needs to reconstruct where
+/// a sample came from and where replies should go.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ReceptionContext {
+  /// GuidPrefix of the sending participant, from the RTPS header or
+  /// INFO_SOURCE.
+  pub source_guid_prefix: GuidPrefix,
+  /// GuidPrefix from INFO_DESTINATION, if the message contained one.
+  pub dest_guid_prefix: Option<GuidPrefix>,
+  /// Timestamp from INFO_TIMESTAMP, if any.
+  pub source_timestamp: Option<Timestamp>,
+  /// Unicast locators from INFO_REPLY. Empty if there was no INFO_REPLY.
+  pub unicast_reply_locators: Vec<Locator>,
+  /// Multicast locators from INFO_REPLY.
+  pub multicast_reply_locators: Vec<Locator>,
+}
@@ -6,15 +6,17 @@
 // Communication statues are detailed in Figure 2.13 and tables in Section
 // 2.2.4.1 in DDS Specification v1.4
 use std::{
+  collections::{BTreeSet, VecDeque},
   io,
   pin::Pin,
-  sync::{Arc, Mutex},
+  sync::{Arc, Mutex, Weak},
   task::{Context, Poll, Waker},
 };
 
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 use futures::stream::{FusedStream, Stream};
+use enumflags2::{bitflags, BitFlags};
 use mio_06::Evented;
 use mio_extras::channel as mio_channel;
 #[cfg(feature = "mio_08")]
@@ -26,7 +28,7 @@ use crate::{
   discovery::SpdpDiscoveredParticipantData,
   messages::{protocol_version::ProtocolVersion, vendor_id::VendorId},
   mio_source::*,
-  structure::guid::GuidPrefix,
+  structure::{guid::GuidPrefix, sequence_number::SequenceNumber, time::Timestamp},
   Duration, QosPolicies, GUID,
 };
 #[cfg(feature = "security")]
@@ -59,22 +61,80 @@ where
 // This is only used so that a mio-0.6 channel can pose as a
 // mio-0.8 event::Source.
 
+/// What a status channel does with a new status event, when the channel is
+/// full, i.e. the application has not taken the earlier events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StatusOverflowPolicy {
+  /// Discard the new event.
+  #[default]
+  DropNewest,
+  /// Discard the oldest event in the channel to make room for the new one.
+  DropOldest,
+  /// Wait until the application takes an event from the channel.
+  ///
+  /// Status events are sent from the event loop thread of the
+  /// DomainParticipant, so a full channel stalls all communication of the
+  /// participant. Use this only if the application takes status events
+  /// promptly, and from a thread that does not wait for the participant.
+  Block,
+}
+
+/// Depth and overflow policy of a status event channel. See e.g.
+/// [`DomainParticipantBuilder::datareader_status_channel`](crate::DomainParticipantBuilder::datareader_status_channel).
+///
+/// The default is the DataReader and DataWriter default: depth 4 and
+/// [`DropNewest`](StatusOverflowPolicy::DropNewest).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatusChannelConfig {
+  /// How many events the channel holds
+  pub depth: usize,
+  pub overflow: StatusOverflowPolicy,
+}
+
+impl StatusChannelConfig {
+  pub fn new(depth: usize, overflow: StatusOverflowPolicy) -> Self {
+    Self { depth, overflow }
+  }
+}
+
+impl Default for StatusChannelConfig {
+  fn default() -> Self {
+    Self::new(4, StatusOverflowPolicy::DropNewest)
+  }
+}
+
+#[cfg(any(test, feature = "test-util"))]
 pub(crate) fn sync_status_channel<T>(
   capacity: usize,
+) -> io::Result<(StatusChannelSender<T>, StatusChannelReceiver<T>)> {
+  configured_status_channel(StatusChannelConfig::new(
+    capacity,
+    StatusOverflowPolicy::DropNewest,
+  ))
+}
+
+pub(crate) fn configured_status_channel<T>(
+  config: StatusChannelConfig,
 ) -> io::Result<(StatusChannelSender<T>, StatusChannelReceiver<T>)> {
   let (signal_receiver, signal_sender) = make_poll_channel()?;
-  let (actual_sender, actual_receiver) = mio_channel::sync_channel(capacity);
+  let (actual_sender, actual_receiver) = mio_channel::sync_channel(config.depth);
+  let actual_receiver = Arc::new(Mutex::new(actual_receiver));
   let waker = Arc::new(Mutex::new(None));
+  let peeked = Arc::new(Mutex::new(VecDeque::new()));
   Ok((
     StatusChannelSender {
       actual_sender,
       signal_sender,
       waker: Arc::clone(&waker),
+      overflow: config.overflow,
+      receiver: Arc::downgrade(&actual_receiver),
     },
     StatusChannelReceiver {
-      actual_receiver: Mutex::new(actual_receiver),
+      actual_receiver,
       signal_receiver,
       waker,
+      peeked,
+      depth: config.depth,
     },
   ))
 }
@@ -85,19 +145,44 @@ pub struct StatusChannelSender<T> {
   actual_sender: mio_channel::SyncSender<T>,
   signal_sender: PollEventSender,
   waker: Arc<Mutex<Option<Waker>>>,
+  overflow: StatusOverflowPolicy,
+  // For discarding the oldest event. This is Weak, so that the channel still
+  // disconnects when the receiver is dropped.
+  receiver: Weak<Mutex<mio_channel::Receiver<T>>>,
 }
 
 pub struct StatusChannelReceiver<T> {
-  actual_receiver: Mutex<mio_channel::Receiver<T>>,
+  actual_receiver: Arc<Mutex<mio_channel::Receiver<T>>>,
   signal_receiver: PollEventSource,
   waker: Arc<Mutex<Option<Waker>>>,
+  // Events that a StatusChannelPeeker has taken out of actual_receiver, but
+  // the application has not received yet. These are received first.
+  peeked: Arc<Mutex<VecDeque<T>>>,
+  depth: usize,
 }
 
 impl<T> StatusChannelSender<T> {
-  /// Best-effort send. If there is no receiver, this will fail silently.
+  /// Send according to the overflow policy. With the default policy this is a
+  /// best-effort send. If there is no receiver, this will fail silently.
   pub fn try_send(&self, t: T) -> Result<(), mio_channel::TrySendError<T>> {
+    if self.overflow == StatusOverflowPolicy::Block {
+      return self.send_blocking(t);
+    }
     let mut w = self.waker.lock().unwrap(); // lock already at the beginning
-    match self.actual_sender.try_send(t) {
+    let result = match self.actual_sender.try_send(t) {
+      Err(mio_channel::TrySendError::Full(t))
+        if self.overflow == StatusOverflowPolicy::DropOldest =>
+      {
+        if let Some(receiver) = self.receiver.upgrade() {
+          debug!("StatusChannelSender: channel is full, discarding the oldest status change.");
+          // Lock order is the same as in StatusReceiverStream: waker first.
+          let _discarded = receiver.lock().unwrap().try_recv();
+        }
+        self.actual_sender.try_send(t)
+      }
+      other => other,
+    };
+    match result {
       Ok(()) => {
         self.signal_sender.send();
         w.as_ref().map(|w| w.wake_by_ref());
@@ -118,6 +203,20 @@ impl<T> StatusChannelSender<T> {
       Err(other_fail) => Err(other_fail),
     }
   }
+
+  fn send_blocking(&self, t: T) -> Result<(), mio_channel::TrySendError<T>> {
+    // The waker must not be locked while blocked, because an async receiver
+    // locks it to take events.
+    self.actual_sender.send(t).map_err(|e| match e {
+      mio_channel::SendError::Io(e) => mio_channel::TrySendError::Io(e),
+      mio_channel::SendError::Disconnected(t) => mio_channel::TrySendError::Disconnected(t),
+    })?;
+    let mut w = self.waker.lock().unwrap();
+    self.signal_sender.send();
+    w.as_ref().map(|w| w.wake_by_ref());
+    *w = None;
+    Ok(())
+  }
 }
 
 impl<T> StatusChannelReceiver<T> {
@@ -125,7 +224,22 @@ impl<T> StatusChannelReceiver<T> {
     // We do not manipulate waker here, because the
     // synchronous and asynchronous receiving are not supposed to be mixed.
     self.signal_receiver.drain();
-    self.actual_receiver.lock().unwrap().try_recv()
+    // Lock order: peeked first, then actual_receiver, as in
+    // StatusChannelPeeker.
+    let mut peeked = self.peeked.lock().unwrap();
+    match peeked.pop_front() {
+      Some(t) => Ok(t),
+      None => self.actual_receiver.lock().unwrap().try_recv(),
+    }
+  }
+
+  /// A handle to inspect the pending events without receiving them.
+  pub(crate) fn peeker(&self) -> StatusChannelPeeker<T> {
+    StatusChannelPeeker {
+      actual_receiver: Arc::clone(&self.actual_receiver),
+      peeked: Arc::clone(&self.peeked),
+      depth: self.depth,
+    }
   }
 
   pub(crate) fn get_waker_update_lock(&self) -> std::sync::MutexGuard<'_, Option<Waker>> {
@@ -211,6 +325,67 @@ impl<T> event::Source for StatusChannelReceiver<T> {
   }
 }
 
+/// Inspects the events pending in a status channel, so that a
+/// [`StatusCondition`](crate::dds::waitset::StatusCondition) can tell if any
+/// of them is of interest, without taking them from the application.
+pub(crate) struct StatusChannelPeeker<T> {
+  actual_receiver: Arc<Mutex<mio_channel::Receiver<T>>>,
+  peeked: Arc<Mutex<VecDeque<T>>>,
+  depth: usize,
+}
+
+impl<T> StatusChannelPeeker<T> {
+  /// Is any pending event accepted by `pred`?
+  pub fn any_pending(&self, pred: impl Fn(&T) -> bool) -> bool {
+    let mut peeked = self.peeked.lock().unwrap();
+    let receiver = self.actual_receiver.lock().unwrap();
+    // The channel cannot be peeked into, so move its contents aside. The
+    // application receives them from there in the same order.
+    while let Ok(t) = receiver.try_recv() {
+      if peeked.len() >= self.depth.max(1) {
+        debug!("StatusChannelPeeker: too many events pending, discarding the oldest.");
+        peeked.pop_front();
+      }
+      peeked.push_back(t);
+    }
+    peeked.iter().any(pred)
+  }
+}
+
+impl<T> Evented for StatusChannelPeeker<T> {
+  fn register(
+    &self,
+    poll: &mio_06::Poll,
+    token: mio_06::Token,
+    interest: mio_06::Ready,
+    opts: mio_06::PollOpt,
+  ) -> io::Result<()> {
+    self
+      .actual_receiver
+      .lock()
+      .unwrap()
+      .register(poll, token, interest, opts)
+  }
+
+  fn reregister(
+    &self,
+    poll: &mio_06::Poll,
+    token: mio_06::Token,
+    interest: mio_06::Ready,
+    opts: mio_06::PollOpt,
+  ) -> io::Result<()> {
+    self
+      .actual_receiver
+      .lock()
+      .unwrap()
+      .reregister(poll, token, interest, opts)
+  }
+
+  fn deregister(&self, poll: &mio_06::Poll) -> io::Result<()> {
+    self.actual_receiver.lock().unwrap().deregister(poll)
+  }
+}
+
 // -------------------------------------------------------------------------------
 // -------------------------------------------------------------------------------
 // -------------------------------------------------------------------------------
@@ -272,10 +447,14 @@ pub enum DomainParticipantStatusEvent {
     discovered_topic_data: Box<TopicData>, // What incoming Discovery data tells us about Topic
     discovery_source: GUID,                // Who sent the Discovery data
   },
-  /// Discovery detects a new topic
+  /// Discovery detects a new topic. This is reported once per topic name,
+  /// from whichever of the topic, publication, or subscription announcements
+  /// arrives first. `qos` is the QoS of that announcement, so for a
+  /// publication or subscription it is that of the endpoint.
   TopicDetected {
     name: String,
     type_name: String,
+    qos: Box<QosPolicies>,
   },
   /// Topics are lost when there are no more known Readers or Writers in them.
   TopicLost {
@@ -454,6 +633,120 @@ pub enum DataReaderStatus {
     writer: GUID,
     // last_publication_key:
   },
+
+  /// A matched DataWriter has restarted with the same GUID, and numbers its
+  /// samples again from 1. This is not a standard DDS status.
+  ///
+  /// Samples of the earlier incarnation that a Reliable DataReader had not
+  /// read yet are skipped. Restarts are detected from the HEARTBEATs of the
+  /// DataWriter, so only Reliable DataReaders report this.
+  WriterRestarted {
+    count: CountWithChange,
+    writer: GUID,
+  },
+
+  /// A Reliable DataReader has received, or learned to be unavailable, every
+  /// sample of a matched DataWriter up to the last one the DataWriter has
+  /// announced, i.e. it has caught up with the DataWriter without gaps. This
+  /// is not a standard DDS status.
+  ///
+  /// Reported again each time the DataReader catches up after having fallen
+  /// behind, e.g. when a repair of lost samples completes. Nothing is reported
+  /// before the first HEARTBEAT from the DataWriter.
+  ReliableStreamSynced { writer: GUID },
+
+  /// A partially received fragmented sample was dropped, because no more of
+  /// its fragments arrived in time. `missing_count` fragments were never
+  /// received. This is not a standard DDS status.
+  ///
+  /// The timeout is set by
+  /// [`DomainParticipantBuilder::fragment_assembly`](crate::DomainParticipantBuilder::fragment_assembly).
+  SampleReassemblyFailed {
+    writer: GUID,
+    seq: SequenceNumber,
+    missing_count: usize,
+  },
+}
+
+/// The kind of a [`DataReaderStatus`], without its contents. Used to select
+/// the statuses that trigger a
+/// [`StatusCondition`](crate::dds::waitset::StatusCondition).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[bitflags]
+#[repr(u32)]
+pub enum DataReaderStatusKind {
+  SampleRejected = 0b0000_0001,
+  LivelinessChanged = 0b0000_0010,
+  RequestedDeadlineMissed = 0b0000_0100,
+  RequestedIncompatibleQos = 0b0000_1000,
+  SampleLost = 0b0001_0000,
+  SubscriptionMatched = 0b0010_0000,
+  WriterRestarted = 0b0100_0000,
+  ReliableStreamSynced = 0b1000_0000,
+  SampleReassemblyFailed = 0b1_0000_0000,
+}
+
+impl DataReaderStatusKind {
+  /// Set that contains all kinds
+  pub fn any() -> BitFlags<Self> {
+    BitFlags::<Self>::all()
+  }
+}
+
+impl DataReaderStatus {
+  pub fn kind(&self) -> DataReaderStatusKind {
+    match self {
+      Self::SampleRejected { .. } => DataReaderStatusKind::SampleRejected,
+      Self::LivelinessChanged { .. } => DataReaderStatusKind::LivelinessChanged,
+      Self::RequestedDeadlineMissed { .. } => DataReaderStatusKind::RequestedDeadlineMissed,
+      Self::RequestedIncompatibleQos { .. } => DataReaderStatusKind::RequestedIncompatibleQos,
+      Self::SampleLost { .. } => DataReaderStatusKind::SampleLost,
+      Self::SubscriptionMatched { .. } => DataReaderStatusKind::SubscriptionMatched,
+      Self::WriterRestarted { .. } => DataReaderStatusKind::WriterRestarted,
+      Self::ReliableStreamSynced { .. } => DataReaderStatusKind::ReliableStreamSynced,
+      Self::SampleReassemblyFailed { .. } => DataReaderStatusKind::SampleReassemblyFailed,
+    }
+  }
+}
+
+/// Current liveliness of the DataWriters matched with a DataReader.
+///
+/// This extends the counts of the standard LIVELINESS_CHANGED status (DDS Spec
+/// v1.4 Section 2.2.4.1) with the GUIDs of the DataWriters, so that an
+/// application can see which publishers are considered alive and which are
+/// not. Returned by `DataReader::liveliness_changed_status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LivelinessChangedStatus {
+  alive_writers: BTreeSet<GUID>,
+  not_alive_writers: BTreeSet<GUID>,
+}
+
+impl LivelinessChangedStatus {
+  pub(crate) fn new(alive_writers: BTreeSet<GUID>, not_alive_writers: BTreeSet<GUID>) -> Self {
+    Self {
+      alive_writers,
+      not_alive_writers,
+    }
+  }
+
+  pub fn alive_count(&self) -> i32 {
+    self.alive_writers.len() as i32
+  }
+
+  pub fn not_alive_count(&self) -> i32 {
+    self.not_alive_writers.len() as i32
+  }
+
+  /// Matched DataWriters that have asserted their liveliness within their
+  /// offered lease duration
+  pub fn alive_writers(&self) -> &BTreeSet<GUID> {
+    &self.alive_writers
+  }
+
+  /// Matched DataWriters whose liveliness lease has expired
+  pub fn not_alive_writers(&self) -> &BTreeSet<GUID> {
+    &self.not_alive_writers
+  }
 }
 
 #[derive(Debug, Clone)]
@@ -481,8 +774,109 @@ pub enum DataWriterStatus {
   },
 }
 
+/// Statistics of a DataWriter sending several small samples coalesced into one
+/// RTPS message. Returned by `DataWriter::coalescing_statistics`.
+///
+/// With
+/// [`DomainParticipantBuilder::max_coalesce_delay`](crate::DomainParticipantBuilder::max_coalesce_delay)
+/// a DataWriter may hold samples back to fill a datagram. The hold delays tell
+/// how much latency this currently adds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoalescingStatistics {
+  /// Number of datagrams sent on the coalescing path
+  pub datagrams: u64,
+  /// Number of samples sent in those datagrams
+  pub samples: u64,
+  /// How long the most recent datagram was held back
+  pub last_hold_delay: std::time::Duration,
+  /// Longest time any datagram was held back
+  pub max_hold_delay: std::time::Duration,
+}
+
+/// Traffic counters of a DomainParticipant since it was created. Returned by
+/// `DomainParticipant::statistics`.
+///
+/// The counters are updated independently of each other, so a snapshot taken
+/// while the participant is busy may be slightly inconsistent, e.g. count a
+/// HEARTBEAT before the message carrying it. Messages are counted on UDP only,
+/// i.e. not when using TCP transport.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParticipantStatistics {
+  /// Bytes in the RTPS messages sent
+  pub bytes_sent: u64,
+  /// RTPS messages (datagrams) sent. A message sent to several destinations
+  /// counts once per destination.
+  pub messages_sent: u64,
+  /// Bytes in the RTPS messages received
+  pub bytes_received: u64,
+  /// RTPS messages (datagrams) received
+  pub messages_received: u64,
+  /// ACKNACK messages sent by Readers
+  pub acknacks_sent: u64,
+  /// NACK_FRAG submessages sent by Readers
+  pub nackfrags_sent: u64,
+  /// HEARTBEAT submessages sent by Writers
+  pub heartbeats_sent: u64,
+  /// Samples sent again by Writers, because a Reader reported them missing
+  pub samples_resent: u64,
+  /// Partially received fragmented samples discarded because the rest of the
+  /// fragments did not arrive in time
+  pub fragment_assembly_timeouts: u64,
+  /// Times received samples were dropped because of ResourceLimits
+  pub samples_rejected: u64,
+}
+
+/// Samples held in the topic cache that a DataReader reads from. Returned by
+/// `DataReader::cache_occupancy`.
+///
+/// The DataReaders of the same Topic in a DomainParticipant share the cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheOccupancy {
+  /// Number of cached samples
+  pub sample_count: usize,
+  /// Reception timestamp of the oldest cached sample
+  pub oldest: Option<Timestamp>,
+  /// Reception timestamp of the newest cached sample
+  pub newest: Option<Timestamp>,
+}
+
+/// Repair load of a reliable DataWriter and the number of DataReaders it
+/// serves. Returned by `DataWriter::fan_out_statistics`.
+///
+/// A growing number of repairs, especially of capped repairs, tells that the
+/// DataWriter spends much of its sending on DataReaders that lose samples. See
+/// [`LargeFanOut`](crate::LargeFanOut) for how to limit this.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FanOutStatistics {
+  /// Number of matched DataReaders
+  pub matched_readers: usize,
+  /// Is the [`LargeFanOut`](crate::LargeFanOut) policy in effect, i.e.
+  /// configured and with enough matched DataReaders?
+  pub large_fan_out: bool,
+  /// Number of samples repaired by unicast to a single DataReader
+  pub unicast_repairs: u64,
+  /// Number of samples repaired by multicast
+  pub multicast_repairs: u64,
+  /// Number of unicast repairs postponed because the DataReader reached its
+  /// repair limit
+  pub capped_repairs: u64,
+  /// Repair load of each matched DataReader that has requested repair,
+  /// heaviest first
+  pub readers: Vec<ReaderRepairLoad>,
+}
+
+/// Repair load caused by one DataReader. Part of [`FanOutStatistics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderRepairLoad {
+  pub reader: GUID,
+  /// Number of samples repaired on request of this DataReader
+  pub repairs: u64,
+  /// Number of repairs to this DataReader postponed by the repair limit
+  pub capped_repairs: u64,
+}
+
 /// Helper to contain same count actions across statuses
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
 pub struct CountWithChange {
   // 2.3. Platform Specific Model defines these as "long", which appears to be 32-bit signed.
   count: i32,
@@ -537,3 +931,61 @@ pub struct QosPolicyCount {
   count: i32,
 }
 */
+
+#[cfg(test)]
+mod tests {
+  use std::{thread, time::Duration as StdDuration};
+
+  use super::*;
+
+  fn received(receiver: &StatusChannelReceiver<i32>) -> Vec<i32> {
+    std::iter::from_fn(|| receiver.try_recv().ok()).collect()
+  }
+
+  #[test]
+  fn status_channel_overflow_policies() {
+    let (sender, receiver) = configured_status_channel(StatusChannelConfig::new(
+      2,
+      StatusOverflowPolicy::DropNewest,
+    ))
+    .unwrap();
+    for i in 1..=3 {
+      sender.try_send(i).unwrap();
+    }
+    assert_eq!(received(&receiver), vec![1, 2]);
+
+    let (sender, receiver) = configured_status_channel(StatusChannelConfig::new(
+      2,
+      StatusOverflowPolicy::DropOldest,
+    ))
+    .unwrap();
+    for i in 1..=3 {
+      sender.try_send(i).unwrap();
+    }
+    assert_eq!(received(&receiver), vec![2, 3]);
+
+    // Dropping the receiver still disconnects the channel.
+    drop(receiver);
+    assert!(sender.try_send(4).is_err());
+  }
+
+  #[test]
+  fn blocking_status_channel_waits_for_receiver() {
+    let (sender, receiver) =
+      configured_status_channel(StatusChannelConfig::new(1, StatusOverflowPolicy::Block)).unwrap();
+    let sending = thread::spawn(move || {
+      for i in 1..=3 {
+        sender.try_send(i).unwrap();
+      }
+    });
+    let mut got = vec![];
+    while got.len() < 3 {
+      match receiver.try_recv() {
+        Ok(i) => got.push(i),
+        Err(_) => thread::sleep(StdDuration::from_millis(1)),
+      }
+    }
+    sending.join().unwrap();
+    assert_eq!(got, vec![1, 2, 3]);
+  }
+}
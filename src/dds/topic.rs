@@ -1,4 +1,4 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
 
 use crate::{
   dds::{
@@ -40,6 +40,75 @@ impl From<&TopicBuiltinTopicData> for TopicData {
   }
 }
 
+/// Expected type names of topics, set with
+/// [`DomainParticipantBuilder::topic_type_registry`](crate::DomainParticipantBuilder::topic_type_registry).
+///
+/// A guardrail against reusing a topic name with a different type, which DDS
+/// only detects as non-matching endpoints. When a topic is registered:
+///
+/// * Creating the Topic, or a DataReader or DataWriter on it, with another type
+///   name fails with
+///   [`CreateError::BadParameter`](crate::dds::CreateError::BadParameter).
+/// * A discovered remote DataReader or DataWriter on the topic with another
+///   type name is reported as
+///   [`DomainParticipantStatusEvent::InconsistentTopic`](crate::DomainParticipantStatusEvent::InconsistentTopic).
+///
+/// Topics that are not registered are not checked.
+///
+/// # Examples
+///
+/// ```
+/// use rustdds::*;
+///
+/// let registry = TopicTypeRegistry::new()
+///   .with_topic("rt/cmd_vel", "geometry_msgs::msg::dds_::Twist_")
+///   .with_topic("rt/odom", "nav_msgs::msg::dds_::Odometry_");
+/// assert_eq!(
+///   registry.expected_type_name("rt/odom"),
+///   Some("nav_msgs::msg::dds_::Odometry_")
+/// );
+/// let domain_participant = DomainParticipantBuilder::new(0)
+///   .topic_type_registry(registry)
+///   .build()
+///   .unwrap();
+/// let qos = QosPolicyBuilder::new().build();
+/// assert!(domain_participant
+///   .create_topic("rt/odom".to_string(), "Odometry".to_string(), &qos, TopicKind::NoKey)
+///   .is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TopicTypeRegistry {
+  types: BTreeMap<String, String>, // topic name -> type name
+}
+
+impl TopicTypeRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Register `type_name` as the type of topic `topic_name`. Registering the
+  /// same topic again replaces its type name.
+  pub fn with_topic(mut self, topic_name: impl Into<String>, type_name: impl Into<String>) -> Self {
+    self.types.insert(topic_name.into(), type_name.into());
+    self
+  }
+
+  /// The registered type name of `topic_name`, if any.
+  pub fn expected_type_name(&self, topic_name: &str) -> Option<&str> {
+    self.types.get(topic_name).map(String::as_str)
+  }
+
+  /// Check that `type_name` is allowed for `topic_name`.
+  pub(crate) fn check(&self, topic_name: &str, type_name: &str) -> Result<(), String> {
+    match self.expected_type_name(topic_name) {
+      Some(expected) if expected != type_name => Err(format!(
+        "Topic {topic_name:?} is registered with type {expected:?}, not {type_name:?}"
+      )),
+      _ => Ok(()),
+    }
+  }
+}
+
 /// DDS Topic
 ///
 /// DDS Specification, Section 2.2.1.2 Conceptual outline:
@@ -0,0 +1,430 @@
+// Wait-sets and Conditions, as in DDS Specification v1.4 Section 2.2.2.1.6
+// "WaitSet Class" and the following Condition classes.
+//
+// These are a synchronous alternative to polling entities with mio or to the
+// async streams. The WaitSet uses its own mio-0.6 Poll, to which the
+// conditions register the notification channels they are based on.
+use std::{
+  collections::BTreeMap,
+  io,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+  time::{Duration, Instant},
+};
+
+use enumflags2::BitFlags;
+use mio_06::{Evented, Events, Poll, PollOpt, Ready, Registration, SetReadiness, Token};
+use mio_extras::channel as mio_channel;
+
+use crate::dds::{
+  result::{WaitError, WaitResult},
+  statusevents::{DataReaderStatus, DataReaderStatusKind, StatusChannelPeeker},
+};
+
+/// A condition that can be attached to a [`WaitSet`].
+///
+/// The condition is registered to the mio-0.6 Poll of the WaitSet, so the
+/// underlying entity must not be registered to another Poll at the same time,
+/// e.g. a DataReader must not be both polled directly and through a
+/// [`DataAvailableCondition`].
+pub trait Condition: Evented + Send {
+  /// Is the condition currently triggered?
+  fn get_trigger_value(&self) -> bool;
+}
+
+/// Identifies a [`Condition`] attached to a [`WaitSet`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConditionId(usize);
+
+/// Blocks the calling thread until one of the attached conditions is
+/// triggered.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rustdds::{GuardCondition, WaitSet};
+///
+/// let mut wait_set = WaitSet::new().unwrap();
+/// let guard = GuardCondition::new();
+/// let guard_id = wait_set.attach_condition(guard.clone()).unwrap();
+///
+/// guard.set_trigger_value(true);
+/// let triggered = wait_set.wait(Duration::from_secs(1)).unwrap();
+/// assert_eq!(triggered, vec![guard_id]);
+/// ```
+pub struct WaitSet {
+  poll: Poll,
+  events: Events,
+  conditions: BTreeMap<ConditionId, Box<dyn Condition>>,
+  next_id: usize,
+}
+
+impl WaitSet {
+  pub fn new() -> io::Result<Self> {
+    Ok(Self {
+      poll: Poll::new()?,
+      events: Events::with_capacity(16),
+      conditions: BTreeMap::new(),
+      next_id: 0,
+    })
+  }
+
+  /// Attaches a condition. The returned id identifies the condition in the
+  /// results of [`wait`](Self::wait).
+  pub fn attach_condition<C: Condition + 'static>(
+    &mut self,
+    condition: C,
+  ) -> io::Result<ConditionId> {
+    let id = ConditionId(self.next_id);
+    self
+      .poll
+      .register(&condition, Token(id.0), Ready::readable(), PollOpt::edge())?;
+    self.next_id += 1;
+    self.conditions.insert(id, Box::new(condition));
+    Ok(id)
+  }
+
+  /// Detaches a condition. Detaching a condition that is not attached does
+  /// nothing.
+  pub fn detach_condition(&mut self, id: ConditionId) -> io::Result<()> {
+    match self.conditions.remove(&id) {
+      Some(condition) => condition.deregister(&self.poll),
+      None => Ok(()),
+    }
+  }
+
+  /// Ids of the attached conditions
+  pub fn conditions(&self) -> impl Iterator<Item = ConditionId> + '_ {
+    self.conditions.keys().copied()
+  }
+
+  /// Waits at most `timeout` until at least one attached condition is
+  /// triggered, and returns the triggered conditions.
+  ///
+  /// Returns immediately, if a condition is already triggered. Waiting does
+  /// not reset the conditions: e.g. a [`DataAvailableCondition`] stays
+  /// triggered until the data is read.
+  pub fn wait(&mut self, timeout: Duration) -> WaitResult<Vec<ConditionId>> {
+    // A timeout too long to represent never expires.
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+      // The trigger values tell what has happened. The Poll only wakes us up
+      // when some of them may have changed. Events that arrive after the check
+      // remain in the Poll, so they are not missed.
+      let triggered: Vec<ConditionId> = self
+        .conditions
+        .iter()
+        .filter(|(_id, c)| c.get_trigger_value())
+        .map(|(id, _c)| *id)
+        .collect();
+      if !triggered.is_empty() {
+        return Ok(triggered);
+      }
+      let remaining = match deadline {
+        Some(deadline) => {
+          let now = Instant::now();
+          if now >= deadline {
+            return Err(WaitError::Timeout);
+          }
+          Some(deadline - now)
+        }
+        None => None,
+      };
+      self.poll.poll(&mut self.events, remaining)?;
+    }
+  }
+}
+
+/// A condition triggered by the application. See DDS Spec v1.4 Section
+/// 2.2.2.1.8 "GuardCondition Class".
+///
+/// Clones share the trigger value, so the application can keep a clone to
+/// trigger a GuardCondition that is attached to a [`WaitSet`], e.g. from
+/// another thread.
+#[derive(Clone)]
+pub struct GuardCondition {
+  registration: Arc<Registration>,
+  set_readiness: SetReadiness,
+  trigger_value: Arc<AtomicBool>,
+}
+
+impl GuardCondition {
+  pub fn new() -> Self {
+    let (registration, set_readiness) = Registration::new2();
+    Self {
+      registration: Arc::new(registration),
+      set_readiness,
+      trigger_value: Arc::new(AtomicBool::new(false)),
+    }
+  }
+
+  pub fn set_trigger_value(&self, value: bool) {
+    self.trigger_value.store(value, Ordering::Release);
+    let readiness = if value {
+      Ready::readable()
+    } else {
+      Ready::empty()
+    };
+    self
+      .set_readiness
+      .set_readiness(readiness)
+      .unwrap_or_else(|e| {
+        log::error!("GuardCondition: cannot set readiness: {e:?}");
+      });
+  }
+}
+
+impl Default for GuardCondition {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Condition for GuardCondition {
+  fn get_trigger_value(&self) -> bool {
+    self.trigger_value.load(Ordering::Acquire)
+  }
+}
+
+impl Evented for GuardCondition {
+  fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+    self.registration.register(poll, token, interest, opts)
+  }
+
+  fn reregister(
+    &self,
+    poll: &Poll,
+    token: Token,
+    interest: Ready,
+    opts: PollOpt,
+  ) -> io::Result<()> {
+    self.registration.reregister(poll, token, interest, opts)
+  }
+
+  fn deregister(&self, poll: &Poll) -> io::Result<()> {
+    <Registration as Evented>::deregister(&self.registration, poll)
+  }
+}
+
+/// Triggered when a DataReader has received samples that it has not yet read
+/// or taken. Corresponds to a DDS ReadCondition that selects samples in any
+/// state, but only new samples count. Created by `DataReader::data_available_condition`.
+///
+/// The condition is reset when the DataReader reads or takes samples, even if
+/// some samples remain unread.
+#[derive(Clone)]
+pub struct DataAvailableCondition {
+  notification_receiver: Arc<Mutex<mio_channel::Receiver<()>>>,
+  data_notified: Arc<AtomicBool>,
+}
+
+impl DataAvailableCondition {
+  pub(crate) fn new(
+    notification_receiver: Arc<Mutex<mio_channel::Receiver<()>>>,
+    data_notified: Arc<AtomicBool>,
+  ) -> Self {
+    Self {
+      notification_receiver,
+      data_notified,
+    }
+  }
+}
+
+impl Condition for DataAvailableCondition {
+  fn get_trigger_value(&self) -> bool {
+    let receiver = self.notification_receiver.lock().unwrap();
+    // The notifications carry no data, so receiving them here is enough to
+    // remember that they arrived.
+    while receiver.try_recv().is_ok() {
+      self.data_notified.store(true, Ordering::Release);
+    }
+    self.data_notified.load(Ordering::Acquire)
+  }
+}
+
+impl Evented for DataAvailableCondition {
+  fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+    self
+      .notification_receiver
+      .lock()
+      .unwrap()
+      .register(poll, token, interest, opts)
+  }
+
+  fn reregister(
+    &self,
+    poll: &Poll,
+    token: Token,
+    interest: Ready,
+    opts: PollOpt,
+  ) -> io::Result<()> {
+    self
+      .notification_receiver
+      .lock()
+      .unwrap()
+      .reregister(poll, token, interest, opts)
+  }
+
+  fn deregister(&self, poll: &Poll) -> io::Result<()> {
+    self.notification_receiver.lock().unwrap().deregister(poll)
+  }
+}
+
+/// Triggered when a DataReader has status events of the enabled kinds that
+/// the application has not received yet. See DDS Spec v1.4 Section 2.2.2.1.9
+/// "StatusCondition Class". Created by `DataReader::status_condition`.
+///
+/// All statuses are enabled initially. Clones share the enabled statuses. The
+/// condition is reset when the application receives the events, e.g. with
+/// [`try_recv_status`](crate::StatusEvented::try_recv_status).
+#[derive(Clone)]
+pub struct StatusCondition {
+  peeker: Arc<StatusChannelPeeker<DataReaderStatus>>,
+  enabled_statuses: Arc<Mutex<BitFlags<DataReaderStatusKind>>>,
+}
+
+impl StatusCondition {
+  pub(crate) fn new(peeker: StatusChannelPeeker<DataReaderStatus>) -> Self {
+    Self {
+      peeker: Arc::new(peeker),
+      enabled_statuses: Arc::new(Mutex::new(DataReaderStatusKind::any())),
+    }
+  }
+
+  pub fn enabled_statuses(&self) -> BitFlags<DataReaderStatusKind> {
+    *self.enabled_statuses.lock().unwrap()
+  }
+
+  pub fn set_enabled_statuses(&self, mask: BitFlags<DataReaderStatusKind>) {
+    *self.enabled_statuses.lock().unwrap() = mask;
+  }
+}
+
+impl Condition for StatusCondition {
+  fn get_trigger_value(&self) -> bool {
+    let mask = self.enabled_statuses();
+    self
+      .peeker
+      .any_pending(|status| mask.contains(status.kind()))
+  }
+}
+
+impl Evented for StatusCondition {
+  fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+    self.peeker.register(poll, token, interest, opts)
+  }
+
+  fn reregister(
+    &self,
+    poll: &Poll,
+    token: Token,
+    interest: Ready,
+    opts: PollOpt,
+  ) -> io::Result<()> {
+    self.peeker.reregister(poll, token, interest, opts)
+  }
+
+  fn deregister(&self, poll: &Poll) -> io::Result<()> {
+    self.peeker.deregister(poll)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::thread;
+
+  use super::*;
+  use crate::dds::statusevents::{sync_status_channel, CountWithChange};
+
+  #[test]
+  fn read_and_guard_conditions_wake_waitset() {
+    let mut wait_set = WaitSet::new().unwrap();
+    let (notification_sender, notification_receiver) = mio_channel::sync_channel::<()>(4);
+    let data_notified = Arc::new(AtomicBool::new(false));
+    let data_condition = DataAvailableCondition::new(
+      Arc::new(Mutex::new(notification_receiver)),
+      Arc::clone(&data_notified),
+    );
+    let guard = GuardCondition::new();
+    let data_id = wait_set.attach_condition(data_condition).unwrap();
+    let guard_id = wait_set.attach_condition(guard.clone()).unwrap();
+
+    assert!(matches!(
+      wait_set.wait(Duration::from_millis(10)),
+      Err(WaitError::Timeout)
+    ));
+
+    // Data arrives while waiting
+    let sender = thread::spawn(move || {
+      thread::sleep(Duration::from_millis(50));
+      notification_sender.send(()).unwrap();
+      notification_sender
+    });
+    let started = Instant::now();
+    assert_eq!(
+      wait_set.wait(Duration::from_secs(5)).unwrap(),
+      vec![data_id]
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let _notification_sender = sender.join().unwrap();
+
+    // Reading the data resets the condition, as drain_read_notifications does.
+    data_notified.store(false, Ordering::Release);
+
+    // The guard is triggered while waiting
+    let trigger = guard.clone();
+    let triggerer = thread::spawn(move || {
+      thread::sleep(Duration::from_millis(50));
+      trigger.set_trigger_value(true);
+    });
+    assert_eq!(
+      wait_set.wait(Duration::from_secs(5)).unwrap(),
+      vec![guard_id]
+    );
+    triggerer.join().unwrap();
+
+    guard.set_trigger_value(false);
+    wait_set.detach_condition(guard_id).unwrap();
+    assert_eq!(wait_set.conditions().collect::<Vec<_>>(), vec![data_id]);
+  }
+
+  #[test]
+  fn status_condition_follows_enabled_statuses() {
+    let mut wait_set = WaitSet::new().unwrap();
+    let (status_sender, status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let condition = StatusCondition::new(status_receiver.peeker());
+    condition.set_enabled_statuses(DataReaderStatusKind::RequestedDeadlineMissed.into());
+    let id = wait_set.attach_condition(condition.clone()).unwrap();
+
+    status_sender
+      .try_send(DataReaderStatus::SampleLost {
+        count: CountWithChange::new(1, 1),
+      })
+      .unwrap();
+    assert!(matches!(
+      wait_set.wait(Duration::from_millis(10)),
+      Err(WaitError::Timeout)
+    ));
+
+    status_sender
+      .try_send(DataReaderStatus::RequestedDeadlineMissed {
+        count: CountWithChange::new(1, 1),
+      })
+      .unwrap();
+    assert_eq!(wait_set.wait(Duration::from_secs(5)).unwrap(), vec![id]);
+
+    // Peeking does not take the events from the application
+    assert!(matches!(
+      status_receiver.try_recv(),
+      Ok(DataReaderStatus::SampleLost { .. })
+    ));
+    assert!(matches!(
+      status_receiver.try_recv(),
+      Ok(DataReaderStatus::RequestedDeadlineMissed { .. })
+    ));
+    assert!(!condition.get_trigger_value());
+  }
+}
@@ -2,7 +2,9 @@ use std::{
   io,
   pin::Pin,
   sync::{Arc, Mutex, MutexGuard},
-  task::{Context, Poll},
+  task::{Context, Poll, Wake, Waker},
+  thread,
+  time::{Duration as StdDuration, Instant},
 };
 
 #[allow(unused_imports)]
@@ -16,12 +18,15 @@ use crate::{
     key::*,
     qos::*,
     readcondition::*,
-    result::ReadResult,
+    result::{CreateResult, ReadResult},
+    sampleinfo::SampleInfo,
     statusevents::*,
+    waitset::{DataAvailableCondition, StatusCondition},
     with_key::{datasample::*, simpledatareader::*},
     ReadError,
   },
   discovery::sedp_messages::PublicationBuiltinTopicData,
+  messages::submessages::elements::serialized_payload::SerializedPayload,
   serialization::CDRDeserializerAdapter,
   structure::{duration::Duration, entity::RTPSEntity, guid::GUID, time::Timestamp},
 };
@@ -87,6 +92,10 @@ where
       datasample_cache: dsc,
     }
   }
+
+  pub(crate) fn set_content_filter(&mut self, filter: ContentFilter<D>) {
+    self.simple_data_reader.set_content_filter(filter);
+  }
 }
 
 impl<D: 'static, DA> DataReader<D, DA>
@@ -251,6 +260,287 @@ where
     Ok(result)
   }
 
+  /// Like [`read`](Self::read), but returns each sample paired with its
+  /// [`SampleInfo`], e.g. to check its view and instance state.
+  #[allow(clippy::type_complexity)]
+  pub fn read_with_info(
+    &mut self,
+    max_samples: usize,
+    read_condition: ReadCondition,
+  ) -> ReadResult<Vec<(Sample<&D, D::K>, SampleInfo)>> {
+    Ok(
+      self
+        .read(max_samples, read_condition)?
+        .into_iter()
+        .map(DataSample::into_parts)
+        .collect(),
+    )
+  }
+
+  /// Like [`take`](Self::take), but returns each sample paired with its
+  /// [`SampleInfo`], e.g. to check its view and instance state.
+  #[allow(clippy::type_complexity)]
+  pub fn take_with_info(
+    &mut self,
+    max_samples: usize,
+    read_condition: ReadCondition,
+  ) -> ReadResult<Vec<(Sample<D, D::K>, SampleInfo)>> {
+    Ok(
+      self
+        .take(max_samples, read_condition)?
+        .into_iter()
+        .map(DataSample::into_parts)
+        .collect(),
+    )
+  }
+
+  /// Takes samples like [`take`](Self::take), but grouped and ordered
+  /// deterministically, for applications that rebuild per-instance state.
+  ///
+  /// The samples are ordered as follows:
+  ///
+  /// * Samples are grouped by instance. All the selected samples of an instance
+  ///   are consecutive.
+  /// * Instances are in ascending order of their key, as given by the `Ord`
+  ///   implementation of `D::K`. This order is the same on every call, and does
+  ///   not depend on when the instances were first seen.
+  /// * Within an instance, samples are in ascending order of their source
+  ///   timestamp. Samples without a source timestamp are ordered by their
+  ///   reception timestamp instead. Samples with equal timestamps are ordered
+  ///   by the GUID of the DataWriter, and then by sequence number.
+  ///
+  /// So the samples of an instance written by a single DataWriter with
+  /// increasing source timestamps, as is the default, are in the order they
+  /// were written.
+  ///
+  /// If more than `max_samples` samples are selected, the first `max_samples`
+  /// of this order are taken, and the rest remain available to the next call.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use serde::{Serialize, Deserialize};
+  /// # use rustdds::*;
+  /// # use rustdds::with_key::DataReader;
+  /// # use rustdds::serialization::CDRDeserializerAdapter;
+  ///
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  /// let subscriber = domain_participant.create_subscriber(&qos).unwrap();
+  /// #
+  /// # #[derive(Serialize, Deserialize)]
+  /// # struct SomeType { a: i32 }
+  /// # impl Keyed for SomeType {
+  /// #   type K = i32;
+  /// #
+  /// #   fn key(&self) -> Self::K {
+  /// #     self.a
+  /// #   }
+  /// # }
+  ///
+  /// // WithKey is important
+  /// let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let mut data_reader = subscriber.create_datareader::<SomeType, CDRDeserializerAdapter<_>>(&topic, None).unwrap();
+  ///
+  /// // Wait for data to arrive...
+  ///
+  /// if let Ok(datas) = data_reader.take_ordered(10, ReadCondition::not_read()) {
+  ///   for data in datas.iter() {
+  ///     // samples of each instance, one instance after another
+  ///   }
+  /// }
+  /// ```
+  pub fn take_ordered(
+    &mut self,
+    max_samples: usize,
+    read_condition: ReadCondition,
+  ) -> ReadResult<Vec<DataSample<D>>> {
+    // Clear notification buffer. This must be done first to avoid race conditions.
+    self.drain_read_notifications();
+
+    self.fill_and_lock_local_datasample_cache()?;
+    let mut selected = self
+      .datasample_cache
+      .select_keys_for_access_ordered(read_condition);
+    selected.truncate(max_samples);
+
+    Ok(self.take_by_keys(&selected))
+  }
+
+  /// Retain the serialized payload of each received sample, so that
+  /// [`take_with_raw`](Self::take_with_raw) can return it. This is a debugging
+  /// aid for deserialization problems, and off by default, because it keeps
+  /// the received bytes in memory until the sample is taken.
+  ///
+  /// Only samples received after this call retain their payload.
+  pub fn set_retain_raw_payloads(&mut self, retain: bool) {
+    self.simple_data_reader.set_retain_raw_payloads(retain);
+  }
+
+  /// Detect samples whose serialized payload is identical to that of the
+  /// previous sample of the same instance, and flag them with
+  /// [`SampleInfo::is_content_duplicate`]. This is useful e.g. for state
+  /// synchronization topics, where a DataWriter may resend unchanged state.
+  ///
+  /// This is off by default, because every received payload is hashed. The
+  /// check is done on reception, so it applies to samples received after this
+  /// call. Only samples that carry a key hash, or a key hash of a disposal,
+  /// can be attributed to their instance this early, so samples without one
+  /// are never flagged.
+  pub fn set_detect_content_duplicates(&mut self, enabled: bool) {
+    self
+      .simple_data_reader
+      .set_detect_content_duplicates(enabled);
+  }
+
+  /// Choose what to do with a received disposal or unregistration whose
+  /// StatusInfo does not tell which one it is. The default is
+  /// [`AmbiguousStatusInfo::NotAlive`]. This applies to changes received after
+  /// this call.
+  pub fn set_ambiguous_status_info(&mut self, handling: AmbiguousStatusInfo) {
+    self.simple_data_reader.set_ambiguous_status_info(handling);
+  }
+
+  /// After answering a HEARTBEAT of a DataWriter, answer its further
+  /// HEARTBEATs only when `duration` has passed (default: zero, i.e. answer
+  /// every HEARTBEAT). This avoids ACKNACK storms when a DataWriter sends
+  /// HEARTBEATs frequently.
+  ///
+  /// HEARTBEATs received within `duration` are answered together at its end,
+  /// so a HEARTBEAT that requires a response still gets one. Repairs of lost
+  /// samples may be delayed by up to `duration`.
+  pub fn set_heartbeat_suppression(&mut self, duration: Duration) {
+    self
+      .simple_data_reader
+      .set_heartbeat_suppression(duration.to_std());
+  }
+
+  /// Choose what to do with a received DATA whose SequenceNumber was already
+  /// received from the same DataWriter. The default is
+  /// [`DuplicateAcceptance::Discard`]. See [`DuplicateAcceptance`] for how an
+  /// accepted duplicate is delivered. This applies to DATA received after
+  /// this call.
+  pub fn set_duplicate_acceptance(&mut self, acceptance: DuplicateAcceptance) {
+    self.simple_data_reader.set_duplicate_acceptance(acceptance);
+  }
+
+  /// Stop taking in samples, e.g. to push back on a DataWriter that produces
+  /// them faster than the application can process them.
+  ///
+  /// A paused Reliable DataReader neither stores nor acknowledges the samples
+  /// it receives. Their DataWriter keeps them unacknowledged, and once its
+  /// reliable send window is full, `write` blocks for at most the
+  /// `max_blocking_time` of its Reliability QoS and then fails with
+  /// [`WriteError::WouldBlock`](crate::dds::WriteError::WouldBlock).
+  /// After [`resume`](Self::resume), the missed samples are repaired as
+  /// usual, when the DataWriter next sends a HEARTBEAT. A paused BestEffort
+  /// DataReader just drops the samples it receives.
+  ///
+  /// Samples already received can still be read and taken while paused.
+  pub fn pause(&self) {
+    self.simple_data_reader.set_paused(true);
+  }
+
+  /// Start taking in samples again after [`pause`](Self::pause).
+  pub fn resume(&self) {
+    self.simple_data_reader.set_paused(false);
+  }
+
+  /// Whether this DataReader is paused. See [`pause`](Self::pause).
+  pub fn is_paused(&self) -> bool {
+    self.simple_data_reader.is_paused()
+  }
+
+  /// Number of samples in the topic cache that this DataReader reads from,
+  /// and the reception timestamps of the oldest and newest of them.
+  ///
+  /// With History KeepAll, the cache grows until
+  /// [`purge_before`](Self::purge_before) is called, or ResourceLimits are
+  /// reached.
+  pub fn cache_occupancy(&self) -> CacheOccupancy {
+    self.simple_data_reader.cache_occupancy()
+  }
+
+  /// Remove the samples received before `timestamp` from the topic cache, to
+  /// reclaim the memory of samples the application has already consumed.
+  /// Compare `timestamp` to
+  /// [`SampleInfo::reception_timestamp`](crate::SampleInfo::reception_timestamp).
+  /// Returns the number of removed samples.
+  ///
+  /// Samples are removed oldest first, regardless of History QoS. A Reliable
+  /// DataReader stops at the first sample it has received, but not yet
+  /// acknowledged, as it cannot have been read yet.
+  ///
+  /// The DataReaders of the same Topic in a DomainParticipant share the topic
+  /// cache, so the samples are removed from all of them, whether they have
+  /// read the samples or not. Samples already read or taken by this DataReader
+  /// are not affected.
+  pub fn purge_before(&self, timestamp: Timestamp) -> usize {
+    self.simple_data_reader.purge_before(timestamp)
+  }
+
+  /// Takes samples like [`take`](Self::take), each with the serialized payload
+  /// it was decoded from, i.e. the bytes and
+  /// [`RepresentationIdentifier`](crate::RepresentationIdentifier) as
+  /// received.
+  ///
+  /// The payload is available only if
+  /// [`set_retain_raw_payloads`](Self::set_retain_raw_payloads) was enabled
+  /// before the sample was received. It is `None` otherwise, and for
+  /// disposals identified only by a key hash. For other disposals it is the
+  /// serialized key.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use serde::{Serialize, Deserialize};
+  /// # use rustdds::*;
+  /// # use rustdds::with_key::DataReader;
+  /// # use rustdds::serialization::CDRDeserializerAdapter;
+  ///
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  /// let subscriber = domain_participant.create_subscriber(&qos).unwrap();
+  /// #
+  /// # #[derive(Serialize, Deserialize)]
+  /// # struct SomeType { a: i32 }
+  /// # impl Keyed for SomeType {
+  /// #   type K = i32;
+  /// #
+  /// #   fn key(&self) -> Self::K {
+  /// #     self.a
+  /// #   }
+  /// # }
+  ///
+  /// let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let mut data_reader = subscriber.create_datareader::<SomeType, CDRDeserializerAdapter<_>>(&topic, None).unwrap();
+  /// data_reader.set_retain_raw_payloads(true);
+  ///
+  /// // Wait for data to arrive...
+  ///
+  /// if let Ok(samples) = data_reader.take_with_raw(10, ReadCondition::not_read()) {
+  ///   for (sample, raw) in samples.iter() {
+  ///     if let Some(raw) = raw {
+  ///       println!("{:?} {:02x?}", raw.representation_identifier, raw.value);
+  ///     }
+  ///   }
+  /// }
+  /// ```
+  pub fn take_with_raw(
+    &mut self,
+    max_samples: usize,
+    read_condition: ReadCondition,
+  ) -> ReadResult<Vec<(DataSample<D>, Option<SerializedPayload>)>> {
+    // Clear notification buffer. This must be done first to avoid race conditions.
+    self.drain_read_notifications();
+
+    self.fill_and_lock_local_datasample_cache()?;
+    let mut selected = self.select_keys_for_access(read_condition);
+    selected.truncate(max_samples);
+
+    Ok(self.datasample_cache.take_with_raw_by_keys(&selected))
+  }
+
   /// Reads next unread sample
   ///
   /// # Examples
@@ -329,6 +619,129 @@ where
     Ok(ds.pop())
   }
 
+  /// Takes the next unread sample like
+  /// [`take_next_sample`](Self::take_next_sample), but if there is none,
+  /// blocks the calling thread until one arrives or `timeout` has passed.
+  /// Returns `Ok(None)` on timeout.
+  ///
+  /// This is a synchronous alternative to polling with mio or to the async
+  /// streams. The thread is woken through the same notification as the async
+  /// streams, so the DataReader can still be registered to a Poll or attached
+  /// to a [`WaitSet`](crate::WaitSet).
+  ///
+  /// New data does not always give a sample to take, e.g. when a content
+  /// filter drops it. Then the wait continues until the timeout.
+  pub fn take_next_sample_timeout(
+    &mut self,
+    timeout: StdDuration,
+  ) -> ReadResult<Option<DataSample<D>>> {
+    // A timeout too long to represent never expires.
+    let deadline = Instant::now().checked_add(timeout);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let result = loop {
+      if let Some(sample) = self.take_next_sample()? {
+        break Ok(Some(sample));
+      }
+      // Store the waker, then take again in case something arrived just now.
+      self.simple_data_reader.set_waker(Some(waker.clone()));
+      if let Some(sample) = self.take_next_sample()? {
+        break Ok(Some(sample));
+      }
+      match deadline {
+        Some(deadline) => {
+          let now = Instant::now();
+          if now >= deadline {
+            break Ok(None);
+          }
+          thread::park_timeout(deadline - now);
+        }
+        None => thread::park(),
+      }
+    };
+    self.simple_data_reader.set_waker(None);
+    result
+  }
+
+  /// Takes the next unread sample like
+  /// [`take_next_sample`](Self::take_next_sample), but deserializes it into
+  /// `target` instead of a new value.
+  ///
+  /// If the DeserializerAdapter supports in-place deserialization, the
+  /// allocations of `target`, e.g. its `Vec` and `String` fields, are reused,
+  /// so that a reader that keeps up with the incoming samples does not
+  /// allocate for them. [`CDRDeserializerAdapter`] supports this for types
+  /// whose `serde::Deserialize` implementation implements
+  /// `deserialize_in_place`. Other adapters and types fall back to
+  /// deserializing a new value and assigning it to `target`.
+  ///
+  /// Returns the [`SampleInfo`] of the sample, or `None` if there is no
+  /// unread sample. If the sample is a disposal, `target` is left unchanged
+  /// and the instance state in the SampleInfo tells so. On a deserialization
+  /// error, `target` may be left partially updated.
+  ///
+  /// Samples already decoded by earlier calls of e.g. [`read`](Self::read)
+  /// are taken first and moved into `target`. Raw payloads are not retained
+  /// for samples taken with this method.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use serde::{Serialize, Deserialize};
+  /// # use rustdds::*;
+  /// # use rustdds::with_key::DataReader;
+  /// # use rustdds::serialization::CDRDeserializerAdapter;
+  /// #
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  /// let subscriber = domain_participant.create_subscriber(&qos).unwrap();
+  /// #
+  /// # #[derive(Serialize, Deserialize, Default)]
+  /// # struct SomeType { a: i32, samples: Vec<f32> }
+  /// # impl Keyed for SomeType {
+  /// #   type K = i32;
+  /// #
+  /// #   fn key(&self) -> Self::K {
+  /// #     self.a
+  /// #   }
+  /// # }
+  ///
+  /// let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let mut data_reader = subscriber.create_datareader::<SomeType, CDRDeserializerAdapter<_>>(&topic, None).unwrap();
+  ///
+  /// let mut data = SomeType::default();
+  /// // Wait for data to arrive...
+  ///
+  /// while let Ok(Some(info)) = data_reader.take_into(&mut data) {
+  ///   if info.instance_state() == InstanceState::Alive {
+  ///     // do something with data
+  ///   }
+  /// }
+  /// ```
+  pub fn take_into(&mut self, target: &mut D) -> ReadResult<Option<SampleInfo>> {
+    // Clear notification buffer. This must be done first to avoid race conditions.
+    self.drain_read_notifications();
+
+    // Samples in the local cache were received before those still in the topic
+    // cache.
+    let mut selected = self.select_keys_for_access(ReadCondition::not_read());
+    if !selected.is_empty() {
+      selected.truncate(1);
+      return Ok(self.take_by_keys(&selected).pop().map(|ds| {
+        if let Sample::Value(value) = ds.value {
+          *target = value;
+        }
+        ds.sample_info
+      }));
+    }
+
+    Ok(
+      self
+        .simple_data_reader
+        .try_take_one_into(target)?
+        .map(|change| self.datasample_cache.take_passed_through(change)),
+    )
+  }
+
   // Iterator interface
 
   fn read_bare(
@@ -706,6 +1119,25 @@ where
     Ok(result)
   }
 
+  /// Returns the handle of the instance with key `key`, or `None` if this
+  /// DataReader has not received any samples of it.
+  ///
+  /// Instances stay known after they are disposed or have lost their writers,
+  /// and after all of their samples are taken, so the handle can be used to
+  /// tell a disposed instance from an unknown one. Their samples are accessed
+  /// with [`read_instance`](Self::read_instance) and
+  /// [`take_instance`](Self::take_instance).
+  pub fn lookup_instance(&mut self, key: &<D as Keyed>::K) -> ReadResult<Option<InstanceHandle>> {
+    self.fill_and_lock_local_datasample_cache()?;
+    Ok(
+      self
+        .datasample_cache
+        .instance_map
+        .contains_key(key)
+        .then(|| key.hash_key(false)),
+    )
+  }
+
   /// Placeholder only — not implemented. **Will panic if called.**
   ///
   /// When implemented, this should return `true` if all historical data was
@@ -732,6 +1164,85 @@ where
     vec![].into_iter()
   }
 
+  /// Current liveliness of the matched DataWriters.
+  ///
+  /// In addition to the counts of the LIVELINESS_CHANGED status, this tells
+  /// which DataWriters are currently considered alive and which are not,
+  /// according to the LIVELINESS QoS they offer. A DataWriter is not alive
+  /// if it has not asserted its liveliness within its lease duration. Lost
+  /// DataWriters are not included.
+  ///
+  /// Changes are also reported as [`DataReaderStatus::LivelinessChanged`]
+  /// events.
+  pub fn liveliness_changed_status(&self) -> LivelinessChangedStatus {
+    self.simple_data_reader.liveliness_changed_status()
+  }
+
+  /// The REQUESTED_DEADLINE_MISSED status: how many times the DEADLINE QoS
+  /// has been missed, in total and since the status was last read.
+  ///
+  /// Reading the status resets its change to zero, while the total count
+  /// persists. Each miss is also reported as a
+  /// [`DataReaderStatus::RequestedDeadlineMissed`] event, whose change is
+  /// likewise counted from the last read.
+  pub fn requested_deadline_missed_status(&self) -> CountWithChange {
+    self.simple_data_reader.requested_deadline_missed_status()
+  }
+
+  /// A condition for a [`WaitSet`](crate::WaitSet) that is triggered when
+  /// this DataReader has received new samples.
+  ///
+  /// Do not use this together with polling the DataReader itself, as both
+  /// rely on the same notifications.
+  pub fn data_available_condition(&self) -> DataAvailableCondition {
+    self.simple_data_reader.data_available_condition()
+  }
+
+  /// A condition for a [`WaitSet`](crate::WaitSet) that is triggered when
+  /// this DataReader has pending status events of the enabled kinds.
+  ///
+  /// Do not use this together with polling the status events of the
+  /// DataReader, as both rely on the same notifications.
+  pub fn status_condition(&self) -> StatusCondition {
+    self.simple_data_reader.status_condition()
+  }
+
+  /// Create another DataReader that shares the RTPS Reader of this one.
+  ///
+  /// Both DataReaders get every sample the Reader receives, but the samples
+  /// are received and stored in the topic cache only once. Each DataReader
+  /// has its own read and take position, so `take` from one of them does not
+  /// remove the sample from the other. Each also has its own notifications
+  /// and status events.
+  ///
+  /// The DataReaders are one DDS entity with one GUID, so settings that the
+  /// Reader applies, such as [`pause`](Self::pause), apply to both. A content
+  /// filter is not shared. The new DataReader starts from the samples still
+  /// in the topic cache. The Reader stays until all DataReaders sharing it are
+  /// dropped.
+  pub fn share(&self) -> CreateResult<Self> {
+    Ok(Self::from_simple_data_reader(
+      self.simple_data_reader.share()?,
+    ))
+  }
+
+  /// When received samples are handed over to the application. See
+  /// [`DeliveryOrder`].
+  pub fn delivery_order(&self) -> DeliveryOrder {
+    self.simple_data_reader.delivery_order()
+  }
+
+  /// Choose when received samples are handed over to the application. The
+  /// default is [`DeliveryOrder::InOrder`].
+  ///
+  /// This only affects Reliable DataReaders, and is best set before reading
+  /// any samples. Switching from [`DeliveryOrder::AsReceived`] to
+  /// [`DeliveryOrder::InOrder`] skips the samples that were still missing
+  /// when the last sample was read.
+  pub fn set_delivery_order(&mut self, delivery_order: DeliveryOrder) {
+    self.simple_data_reader.set_delivery_order(delivery_order);
+  }
+
   /// An async stream for reading the (bare) data samples.
   /// The resulting Stream can be used to get another stream of status events.
   pub fn async_bare_sample_stream(self) -> BareDataReaderStream<D, DA> {
@@ -749,6 +1260,15 @@ where
   }
 } // impl
 
+// Wakes a thread blocked in take_next_sample_timeout.
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+  fn wake(self: Arc<Self>) {
+    self.0.unpark();
+  }
+}
+
 // -------------------
 
 impl<D, DA> mio_06::Evented for DataReader<D, DA>
@@ -1133,8 +1653,12 @@ mod tests {
   use super::*;
   use crate::{
     dds::{
+      ddsdata::DDSData,
+      durability_store::StoredSample,
       participant::DomainParticipant,
+      sampleinfo::{InstanceState, ViewState},
       topic::{TopicDescription, TopicKind},
+      with_key::datawriter::WriteOptions,
     },
     messages::submessages::{
       elements::serialized_payload::SerializedPayload, submessage_flag::*, submessages::Data,
@@ -1147,8 +1671,10 @@ mod tests {
     },
     serialization::to_vec,
     structure::{
+      cache_change::{CacheChange, ChangeKind},
       guid::{EntityId, EntityKind, GuidPrefix},
       sequence_number::SequenceNumber,
+      time::Timestamp,
     },
     test::random_data::*,
     RepresentationIdentifier,
@@ -1206,6 +1732,13 @@ mod tests {
       data_reader_command_receiver: reader_command_receiver,
       data_reader_waker,
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
 
@@ -1214,7 +1747,8 @@ mod tests {
       Rc::new(UDPSender::new_with_random_port().unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
+    )
+    .unwrap();
 
     // Create the corresponding matching DataReader
     let mut datareader = sub
@@ -1338,19 +1872,14 @@ mod tests {
   }
 
   #[test]
-  fn read_and_take_with_instance() {
-    // Test the methods read_instance and take_instance of the DataReader
-
+  fn take_with_raw_returns_received_bytes() {
     let dp = DomainParticipant::new(0).expect("Participant creation failed!");
-
-    let mut qos = QosPolicies::qos_none();
-    qos.history = Some(policy::History::KeepAll); // Just for testing
-
+    let qos = QosPolicies::qos_none();
     let sub = dp.create_subscriber(&qos).unwrap();
     let topic = dp
       .create_topic(
-        "dr read".to_string(),
-        "read fn test?".to_string(),
+        "dr raw".to_string(),
+        "raw fn test?".to_string(),
         &qos,
         TopicKind::WithKey,
       )
@@ -1362,24 +1891,17 @@ mod tests {
         .unwrap()
         .add_new_topic(topic.name(), topic.get_type(), &topic.qos());
 
-    // Create a Reader
     let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
     let (_notification_event_source, notification_event_sender) =
       mio_source::make_poll_channel().unwrap();
-    let data_reader_waker = Arc::new(Mutex::new(None));
-
     let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
     let (participant_status_sender, _participant_status_receiver) =
       sync_status_channel(16).unwrap();
-
     let (_reader_command_sender, reader_command_receiver) =
       mio_channel::sync_channel::<ReaderCommand>(10);
 
-    let default_id = EntityId::default();
-    let reader_guid = GUID::new_with_prefix_and_id(dp.guid_prefix(), default_id);
-
     let reader_ing = ReaderIngredients {
-      guid: reader_guid,
+      guid: GUID::new_with_prefix_and_id(dp.guid_prefix(), EntityId::default()),
       notification_sender,
       status_sender,
       topic_name: topic.name(),
@@ -1387,19 +1909,25 @@ mod tests {
       like_stateless: false,
       qos_policy: QosPolicies::qos_none(),
       data_reader_command_receiver: reader_command_receiver,
-      data_reader_waker,
+      data_reader_waker: Arc::new(Mutex::new(None)),
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
-
     let mut reader = Reader::new(
       reader_ing,
       Rc::new(UDPSender::new_with_random_port().unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
+    )
+    .unwrap();
 
-    // Create the corresponding matching DataReader
     let mut datareader = sub
       .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(&topic, None)
       .unwrap();
@@ -1418,15 +1946,290 @@ mod tests {
     reader.matched_writer_add(
       writer_guid,
       EntityId::UNKNOWN,
-      mr_state.unicast_reply_locator_list.to_vec(),
-      mr_state.multicast_reply_locator_list.to_vec(),
+      vec![],
+      vec![],
       &QosPolicies::qos_none(),
     );
 
-    // Create 4 data items, 3 of which have the same key
-    let data_key1 = RandomData {
-      a: 1,
-      b: ":D".to_string(),
+    let payload = |sn: i64| {
+      let test_data = RandomData {
+        a: sn,
+        b: "raw".to_string(),
+      };
+      SerializedPayload {
+        representation_identifier: RepresentationIdentifier::CDR_LE,
+        representation_options: [0, 0],
+        value: Bytes::from(to_vec::<RandomData, LittleEndian>(&test_data).unwrap()),
+      }
+    };
+    let reader_id = reader.entity_id();
+    let data_msg = |sn: i64| Data {
+      reader_id,
+      writer_id: writer_guid.entity_id,
+      writer_sn: SequenceNumber::from(sn),
+      serialized_payload: Some(payload(sn).into()),
+      ..Data::default()
+    };
+    let data_flags = DATA_Flags::Endianness | DATA_Flags::Data;
+
+    // Payloads are not retained by default
+    let msg = data_msg(1);
+    reader.handle_data_msg(msg, data_flags, &mr_state);
+    let taken = datareader.take_with_raw(10, ReadCondition::any()).unwrap();
+    assert_eq!(taken.len(), 1);
+    assert!(taken[0].1.is_none());
+
+    datareader.set_retain_raw_payloads(true);
+    let msg = data_msg(2);
+    reader.handle_data_msg(msg, data_flags, &mr_state);
+    let taken = datareader.take_with_raw(10, ReadCondition::any()).unwrap();
+    assert_eq!(taken.len(), 1);
+    let (sample, raw) = &taken[0];
+    assert_eq!(sample.value().as_ref().value().map(|d| d.a), Some(2));
+    assert_eq!(raw.as_ref(), Some(&payload(2)));
+  }
+
+  #[test]
+  fn take_into_overwrites_target() {
+    let dp = DomainParticipant::new(0).expect("Participant creation failed!");
+    let mut qos = QosPolicies::qos_none();
+    qos.history = Some(policy::History::KeepAll);
+    let sub = dp.create_subscriber(&qos).unwrap();
+    let topic = dp
+      .create_topic(
+        "dr take_into".to_string(),
+        "take_into fn test?".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+
+    let topic_cache =
+      dp.dds_cache()
+        .write()
+        .unwrap()
+        .add_new_topic(topic.name(), topic.get_type(), &topic.qos());
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_ing = ReaderIngredients {
+      guid: GUID::new_with_prefix_and_id(dp.guid_prefix(), EntityId::default()),
+      notification_sender,
+      status_sender,
+      topic_name: topic.name(),
+      topic_cache_handle: topic_cache,
+      like_stateless: false,
+      qos_policy: qos.clone(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new_with_random_port().unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let mut datareader = sub
+      .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(&topic, Some(qos))
+      .unwrap();
+
+    let writer_guid = GUID {
+      prefix: GuidPrefix::new(&[1; 12]),
+      entity_id: EntityId::create_custom_entity_id(
+        [1; 3],
+        EntityKind::WRITER_WITH_KEY_USER_DEFINED,
+      ),
+    };
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      vec![],
+      vec![],
+      &QosPolicies::qos_none(),
+    );
+
+    let reader_id = reader.entity_id();
+    let data_msg = |sn: i64, a: i64| {
+      let test_data = RandomData {
+        a,
+        b: format!("sample {sn}"),
+      };
+      Data {
+        reader_id,
+        writer_id: writer_guid.entity_id,
+        writer_sn: SequenceNumber::from(sn),
+        serialized_payload: Some(
+          SerializedPayload {
+            representation_identifier: RepresentationIdentifier::CDR_LE,
+            representation_options: [0, 0],
+            value: Bytes::from(to_vec::<RandomData, LittleEndian>(&test_data).unwrap()),
+          }
+          .into(),
+        ),
+        ..Data::default()
+      }
+    };
+    let data_flags = DATA_Flags::Endianness | DATA_Flags::Data;
+
+    let mut target = RandomData {
+      a: 0,
+      b: String::with_capacity(64),
+    };
+    assert_eq!(datareader.take_into(&mut target).unwrap(), None);
+
+    reader.handle_data_msg(data_msg(1, 7), data_flags, &mr_state);
+    let info = datareader.take_into(&mut target).unwrap().unwrap();
+    assert_eq!(target.a, 7);
+    assert_eq!(target.b, "sample 1");
+    assert_eq!(info.sequence_number, SequenceNumber::from(1));
+    assert_eq!(info.instance_state, InstanceState::Alive);
+    assert_eq!(info.view_state, ViewState::New);
+    assert_eq!(datareader.take_into(&mut target).unwrap(), None);
+
+    // A sample already decoded into the local cache, here by read_instance(),
+    // is taken from there. Samples marked read are not taken.
+    reader.handle_data_msg(data_msg(2, 7), data_flags, &mr_state);
+    reader.handle_data_msg(data_msg(3, 8), data_flags, &mr_state);
+    let read = datareader
+      .read_instance(10, ReadCondition::any(), Some(8), SelectByKey::This)
+      .unwrap();
+    assert_eq!(read.len(), 1);
+    let info = datareader.take_into(&mut target).unwrap().unwrap();
+    assert_eq!(info.sequence_number, SequenceNumber::from(2));
+    assert_eq!(info.view_state, ViewState::NotNew); // instance 7 was seen
+    assert_eq!((target.a, target.b.as_str()), (7, "sample 2"));
+    assert_eq!(datareader.take_into(&mut target).unwrap(), None);
+
+    // take_into bookkeeping is visible to the other methods.
+    reader.handle_data_msg(data_msg(4, 9), data_flags, &mr_state);
+    let info = datareader.take_into(&mut target).unwrap().unwrap();
+    assert_eq!(info.view_state, ViewState::New);
+    reader.handle_data_msg(data_msg(5, 9), data_flags, &mr_state);
+    let taken = datareader.take(10, ReadCondition::not_read()).unwrap();
+    assert_eq!(taken.len(), 1);
+    assert_eq!(taken[0].sample_info().view_state, ViewState::NotNew);
+  }
+
+  #[test]
+  fn read_and_take_with_instance() {
+    // Test the methods read_instance and take_instance of the DataReader
+
+    let dp = DomainParticipant::new(0).expect("Participant creation failed!");
+
+    let mut qos = QosPolicies::qos_none();
+    qos.history = Some(policy::History::KeepAll); // Just for testing
+
+    let sub = dp.create_subscriber(&qos).unwrap();
+    let topic = dp
+      .create_topic(
+        "dr read".to_string(),
+        "read fn test?".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+
+    let topic_cache =
+      dp.dds_cache()
+        .write()
+        .unwrap()
+        .add_new_topic(topic.name(), topic.get_type(), &topic.qos());
+
+    // Create a Reader
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let data_reader_waker = Arc::new(Mutex::new(None));
+
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let default_id = EntityId::default();
+    let reader_guid = GUID::new_with_prefix_and_id(dp.guid_prefix(), default_id);
+
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic.name(),
+      topic_cache_handle: topic_cache,
+      like_stateless: false,
+      qos_policy: QosPolicies::qos_none(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker,
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new_with_random_port().unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    // Create the corresponding matching DataReader
+    let mut datareader = sub
+      .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(&topic, None)
+      .unwrap();
+
+    let writer_guid = GUID {
+      prefix: GuidPrefix::new(&[1; 12]),
+      entity_id: EntityId::create_custom_entity_id(
+        [1; 3],
+        EntityKind::WRITER_WITH_KEY_USER_DEFINED,
+      ),
+    };
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      mr_state.unicast_reply_locator_list.to_vec(),
+      mr_state.multicast_reply_locator_list.to_vec(),
+      &QosPolicies::qos_none(),
+    );
+
+    // Create 4 data items, 3 of which have the same key
+    let data_key1 = RandomData {
+      a: 1,
+      b: ":D".to_string(),
     };
     let data_key2_1 = RandomData {
       a: 2,
@@ -1587,4 +2390,405 @@ mod tests {
     assert!(results.is_ok());
     assert!(results.unwrap().is_empty());
   }
+
+  // Add a sample of instance 1 and then a change of `change_kind` to it to the
+  // topic cache. Returns the instance state the DataReader then reports.
+  fn instance_state_after(change_kind: ChangeKind) -> InstanceState {
+    let dp = DomainParticipant::new(0).expect("Participant creation failed!");
+    let mut qos = QosPolicies::qos_none();
+    qos.history = Some(policy::History::KeepAll);
+    let sub = dp.create_subscriber(&qos).unwrap();
+    let topic = dp
+      .create_topic(
+        format!("dr instance state {change_kind:?}"),
+        "instance state test".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+    let mut datareader = sub
+      .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(&topic, None)
+      .unwrap();
+
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_WITH_KEY_USER_DEFINED);
+    let serialized = |value: Vec<u8>| SerializedPayload {
+      representation_identifier: RepresentationIdentifier::CDR_LE,
+      representation_options: [0, 0],
+      value: Bytes::from(value),
+    };
+    let sample = RandomData {
+      a: 1,
+      b: "instance state".to_string(),
+    };
+    let changes = [
+      DDSData::new(serialized(
+        to_vec::<RandomData, LittleEndian>(&sample).unwrap(),
+      )),
+      DDSData::new_disposed_by_key(
+        change_kind,
+        serialized(to_vec::<i64, LittleEndian>(&sample.key()).unwrap()),
+      ),
+    ];
+    let topic_cache = dp
+      .dds_cache()
+      .read()
+      .unwrap()
+      .get_existing_topic_cache(&topic.name())
+      .unwrap();
+    for (sn, data) in (1..).zip(changes) {
+      topic_cache.lock().unwrap().add_change(
+        &Timestamp::now(),
+        CacheChange::new(
+          writer_guid,
+          SequenceNumber::from(sn),
+          WriteOptions::default(),
+          data,
+        ),
+      );
+    }
+
+    let samples = datareader.take(100, ReadCondition::any()).unwrap();
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[1].value(), &Sample::Dispose(1));
+    samples[1].sample_info().instance_state()
+  }
+
+  #[test]
+  fn dispose_leaves_instance_disposed() {
+    assert_eq!(
+      instance_state_after(ChangeKind::NotAliveDisposed),
+      InstanceState::NotAliveDisposed
+    );
+  }
+
+  #[test]
+  fn unregister_leaves_instance_without_writers() {
+    assert_eq!(
+      instance_state_after(ChangeKind::NotAliveUnregistered),
+      InstanceState::NotAliveNoWriters
+    );
+  }
+
+  #[test]
+  fn dispose_and_unregister_leaves_instance_disposed() {
+    assert_eq!(
+      instance_state_after(ChangeKind::NotAliveDisposedUnregistered),
+      InstanceState::NotAliveDisposed
+    );
+  }
+
+  // Feed a Reliable DataReader samples 1 and 3 before 2, as if 2 was lost and
+  // repaired. Returns the values taken after 1 & 3 and after 2 arrived.
+  fn take_out_of_order_samples(delivery_order: DeliveryOrder) -> (Vec<i64>, Vec<i64>) {
+    let dp = DomainParticipant::new(0).expect("Participant creation failed!");
+    let qos = QosPolicies::builder()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: Duration::from_millis(100),
+      })
+      .history(policy::History::KeepAll)
+      .build();
+    let sub = dp.create_subscriber(&qos).unwrap();
+    let topic = dp
+      .create_topic(
+        "dr delivery order".to_string(),
+        "delivery order test".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+    let topic_cache =
+      dp.dds_cache()
+        .write()
+        .unwrap()
+        .add_new_topic(topic.name(), topic.get_type(), &topic.qos());
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_ing = ReaderIngredients {
+      guid: GUID::new_with_prefix_and_id(dp.guid_prefix(), EntityId::default()),
+      notification_sender,
+      status_sender,
+      topic_name: topic.name(),
+      topic_cache_handle: topic_cache,
+      like_stateless: false,
+      qos_policy: qos.clone(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new_with_random_port().unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let mut datareader = sub
+      .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(&topic, Some(qos))
+      .unwrap();
+    datareader.set_delivery_order(delivery_order);
+
+    let writer_guid = GUID {
+      prefix: GuidPrefix::new(&[1; 12]),
+      entity_id: EntityId::create_custom_entity_id(
+        [1; 3],
+        EntityKind::WRITER_WITH_KEY_USER_DEFINED,
+      ),
+    };
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      vec![],
+      vec![],
+      &QosPolicies::qos_none(),
+    );
+
+    let mut feed = |sn: i64| {
+      let sample = RandomData {
+        a: sn,
+        b: "delivery order".to_string(),
+      };
+      let data_msg = Data {
+        reader_id: reader.entity_id(),
+        writer_id: writer_guid.entity_id,
+        writer_sn: SequenceNumber::from(sn),
+        serialized_payload: Some(
+          SerializedPayload {
+            representation_identifier: RepresentationIdentifier::CDR_LE,
+            representation_options: [0, 0],
+            value: Bytes::from(to_vec::<RandomData, LittleEndian>(&sample).unwrap()),
+          }
+          .into(),
+        ),
+        ..Data::default()
+      };
+      reader.handle_data_msg(
+        data_msg,
+        DATA_Flags::Endianness | DATA_Flags::Data,
+        &mr_state,
+      );
+    };
+    let mut take_values = || {
+      let mut values: Vec<i64> = datareader
+        .take(100, ReadCondition::any())
+        .unwrap()
+        .iter()
+        .map(|s| s.value().clone().value().unwrap().a)
+        .collect();
+      values.sort_unstable();
+      values
+    };
+
+    feed(1);
+    feed(3);
+    let before_repair = take_values();
+    feed(2);
+    let after_repair = take_values();
+    (before_repair, after_repair)
+  }
+
+  #[test]
+  fn in_order_delivery_waits_for_repair() {
+    assert_eq!(
+      take_out_of_order_samples(DeliveryOrder::InOrder),
+      (vec![1], vec![2, 3])
+    );
+  }
+
+  #[test]
+  fn as_received_delivery_does_not_wait_for_repair() {
+    assert_eq!(
+      take_out_of_order_samples(DeliveryOrder::AsReceived),
+      (vec![1, 3], vec![2])
+    );
+  }
+
+  #[test]
+  fn replay_to_late_joiner_leaves_gap_of_existing_reader() {
+    let dp = DomainParticipant::new(0).expect("Participant creation failed!");
+    let qos = QosPolicies::builder()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: Duration::from_millis(100),
+      })
+      .history(policy::History::KeepAll)
+      .build();
+    let sub = dp.create_subscriber(&qos).unwrap();
+    let topic = dp
+      .create_topic(
+        "dr durability replay".to_string(),
+        "durability replay test".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .unwrap();
+    let topic_cache =
+      dp.dds_cache()
+        .write()
+        .unwrap()
+        .add_new_topic(topic.name(), topic.get_type(), &topic.qos());
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_ing = ReaderIngredients {
+      guid: GUID::new_with_prefix_and_id(dp.guid_prefix(), EntityId::default()),
+      notification_sender,
+      status_sender,
+      topic_name: topic.name(),
+      topic_cache_handle: topic_cache,
+      like_stateless: false,
+      qos_policy: qos.clone(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new_with_random_port().unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let writer_guid = GUID {
+      prefix: GuidPrefix::new(&[1; 12]),
+      entity_id: EntityId::create_custom_entity_id(
+        [1; 3],
+        EntityKind::WRITER_WITH_KEY_USER_DEFINED,
+      ),
+    };
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      vec![],
+      vec![],
+      &QosPolicies::qos_none(),
+    );
+
+    let payload = |sn: i64| {
+      let sample = RandomData {
+        a: sn,
+        b: "durability replay".to_string(),
+      };
+      SerializedPayload {
+        representation_identifier: RepresentationIdentifier::CDR_LE,
+        representation_options: [0, 0],
+        value: Bytes::from(to_vec::<RandomData, LittleEndian>(&sample).unwrap()),
+      }
+    };
+    let mut feed = |sn: i64| {
+      let data_msg = Data {
+        reader_id: reader.entity_id(),
+        writer_id: writer_guid.entity_id,
+        writer_sn: SequenceNumber::from(sn),
+        serialized_payload: Some(payload(sn).into()),
+        ..Data::default()
+      };
+      reader.handle_data_msg(
+        data_msg,
+        DATA_Flags::Endianness | DATA_Flags::Data,
+        &mr_state,
+      );
+    };
+    // Keep sample `sn` in the durability store, as a Transient DataWriter would.
+    let store = |sn: i64| {
+      let cc = CacheChange::new(
+        writer_guid,
+        SequenceNumber::from(sn),
+        WriteOptions::default(),
+        DDSData::new(payload(sn)),
+      );
+      dp.durability_store().unwrap().store(
+        &topic.name(),
+        &policy::DurabilityService {
+          history: policy::History::KeepAll,
+          ..policy::DurabilityService::default()
+        },
+        StoredSample::new(cc),
+      );
+    };
+    let take_values = |datareader: &mut DataReader<RandomData>| {
+      let mut values: Vec<i64> = datareader
+        .take(100, ReadCondition::any())
+        .unwrap()
+        .iter()
+        .map(|s| s.value().clone().value().unwrap().a)
+        .collect();
+      values.sort_unstable();
+      values
+    };
+
+    let mut existing = sub
+      .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(
+        &topic,
+        Some(qos.clone()),
+      )
+      .unwrap();
+    feed(1);
+    feed(3);
+    assert_eq!(take_values(&mut existing), vec![1]);
+
+    // A Transient DataReader joins while 2 is still being repaired.
+    for sn in 1..=3 {
+      store(sn);
+    }
+    let transient_qos = qos.modify_by(
+      &QosPolicies::builder()
+        .durability(policy::Durability::Transient)
+        .build(),
+    );
+    let mut late_joiner = sub
+      .create_datareader::<RandomData, CDRDeserializerAdapter<RandomData>>(
+        &topic,
+        Some(transient_qos),
+      )
+      .unwrap();
+    assert_eq!(take_values(&mut late_joiner), vec![1, 2, 3]);
+    // The existing DataReader still waits for 2.
+    assert_eq!(take_values(&mut existing), Vec::<i64>::new());
+
+    feed(2);
+    feed(4);
+    assert_eq!(take_values(&mut existing), vec![2, 3, 4]);
+    // The late joiner does not take the replayed samples again.
+    assert_eq!(take_values(&mut late_joiner), vec![4]);
+  }
 }
@@ -1,7 +1,11 @@
 use crate::{
   dds::{key::*, sampleinfo::*, with_key::datawriter::WriteOptions},
+  messages::submessages::elements::serialized_payload::SerializedPayload,
   structure::{
-    cache_change::CacheChange, guid::GUID, sequence_number::SequenceNumber, time::Timestamp,
+    cache_change::{CacheChange, ChangeKind},
+    guid::GUID,
+    sequence_number::SequenceNumber,
+    time::Timestamp,
   },
 };
 
@@ -116,6 +120,11 @@ where
     self.value
   }
 
+  /// Splits this into the sample and its metadata.
+  pub fn into_parts(self) -> (Sample<D, D::K>, SampleInfo) {
+    (self.value, self.sample_info)
+  }
+
   pub fn sample_info(&self) -> &SampleInfo {
     &self.sample_info
   }
@@ -134,10 +143,14 @@ pub struct DeserializedCacheChange<D: Keyed> {
   pub(crate) writer_guid: GUID,               // 8 bytes
   pub(crate) sequence_number: SequenceNumber, // 8 bytes
   pub(crate) write_options: WriteOptions,     // 16 bytes
+  pub(crate) change_kind: ChangeKind,
 
   // the data sample (or key) itself is stored here
   pub(crate) sample: Sample<D, D::K>, /* TODO: make this a Box<> for easier detaching an
                                        * reattaching to somewhere else */
+
+  // The bytes `sample` was decoded from, if the DataReader retains them
+  pub(crate) raw_payload: Option<SerializedPayload>,
 }
 
 impl<D: Keyed> DeserializedCacheChange<D> {
@@ -147,7 +160,40 @@ impl<D: Keyed> DeserializedCacheChange<D> {
       writer_guid: cc.writer_guid,
       sequence_number: cc.sequence_number,
       write_options: cc.write_options.clone(),
+      change_kind: cc.data_value.change_kind(),
       sample: deserialized,
+      raw_payload: None,
+    }
+  }
+
+  pub(crate) fn with_raw_payload(mut self, raw_payload: Option<SerializedPayload>) -> Self {
+    self.raw_payload = raw_payload;
+    self
+  }
+}
+
+// Like DeserializedCacheChange, but the value was deserialized into a
+// caller-provided `D`, so only its instance key is here.
+#[derive(Debug, Clone)]
+pub(crate) struct DeserializedIntoChange<K> {
+  pub(crate) receive_instant: Timestamp,
+  pub(crate) writer_guid: GUID,
+  pub(crate) sequence_number: SequenceNumber,
+  pub(crate) write_options: WriteOptions,
+  pub(crate) instance_key: K,
+  // Anything but Alive leaves the caller's value unchanged.
+  pub(crate) change_kind: ChangeKind,
+}
+
+impl<K> DeserializedIntoChange<K> {
+  pub fn new(receive_instant: Timestamp, cc: &CacheChange, instance_key: K) -> Self {
+    Self {
+      receive_instant,
+      writer_guid: cc.writer_guid,
+      sequence_number: cc.sequence_number,
+      write_options: cc.write_options.clone(),
+      instance_key,
+      change_kind: cc.data_value.change_kind(),
     }
   }
 }
@@ -12,12 +12,27 @@ use crate::{
     qos::{policy, QosPolicies},
     readcondition::ReadCondition,
     sampleinfo::*,
-    with_key::datasample::{DataSample, DeserializedCacheChange, Sample},
+    with_key::datasample::{DataSample, DeserializedCacheChange, DeserializedIntoChange, Sample},
+  },
+  messages::submessages::elements::serialized_payload::SerializedPayload,
+  structure::{
+    cache_change::ChangeKind, guid::GUID, sequence_number::SequenceNumber, time::Timestamp,
   },
-  structure::{guid::GUID, sequence_number::SequenceNumber, time::Timestamp},
   with_key::WriteOptions,
 };
 
+// An unregistration leaves the instance without writers, unless it also
+// disposes the instance.
+fn instance_state_after(change_kind: ChangeKind) -> InstanceState {
+  match change_kind {
+    ChangeKind::Alive => InstanceState::Alive,
+    ChangeKind::NotAliveDisposed | ChangeKind::NotAliveDisposedUnregistered => {
+      InstanceState::NotAliveDisposed
+    }
+    ChangeKind::NotAliveUnregistered => InstanceState::NotAliveNoWriters,
+  }
+}
+
 // use std::num::Zero; unstable
 
 // DataSampleCache is a structure local to DataReader and DataWriter. It acts as
@@ -45,6 +60,7 @@ struct SampleWithMetaData<D: Keyed> {
   // a snapshot of the instance-wide counts
   // at the time this sample was received.
   generation_counts: NotAliveGenerationCounts,
+  receive_timestamp: Timestamp,    // when this was received
   writer_guid: GUID,               // who wrote this
   sequence_number: SequenceNumber, // as sent by the Writer
   write_options: WriteOptions,     // as stamped by Writer
@@ -52,6 +68,9 @@ struct SampleWithMetaData<D: Keyed> {
 
   // the data sample (or key) itself is stored here
   sample: Sample<D, D::K>, // TODO: maybe this should be boxed for moving performance.
+
+  // the bytes `sample` was decoded from, if the DataReader retains them
+  raw_payload: Option<SerializedPayload>,
 }
 
 impl<D> SampleWithMetaData<D>
@@ -86,20 +105,25 @@ where
 
     self.add_sample(
       deserialized_cc.sample,
+      deserialized_cc.change_kind,
       deserialized_cc.writer_guid,
       deserialized_cc.sequence_number,
       deserialized_cc.receive_instant,
       deserialized_cc.write_options,
+      deserialized_cc.raw_payload,
     );
   }
 
+  #[allow(clippy::too_many_arguments)]
   fn add_sample(
     &mut self,
     new_sample: Sample<D, D::K>,
+    change_kind: ChangeKind,
     writer_guid: GUID,
     sequence_number: SequenceNumber,
     receive_timestamp: Timestamp,
     write_options: WriteOptions,
+    raw_payload: Option<SerializedPayload>,
   ) {
     // Defense in depth: the topic cache already hands us strictly monotonic (hence
     // unique) receive timestamps, but if two ever collide here, probe forward by
@@ -114,59 +138,13 @@ where
       Sample::Dispose(k) => k.clone(),
     };
 
-    let new_instance_state = match new_sample {
-      Sample::Value(_) => InstanceState::Alive,
-      Sample::Dispose(_) => InstanceState::NotAliveDisposed,
-    };
-
-    // find or create metadata record
-    let instance_metadata = if let Some(imd) = self.instance_map.get_mut(&instance_key) {
-      imd
-    } else {
-      // not found, create new one.
-      let imd = InstanceMetaData {
-        instance_samples: BTreeSet::new(),
-        instance_state: new_instance_state,
-        latest_generation_available: NotAliveGenerationCounts::zero(), /* this is new instance,
-                                                                        * so start from zero */
-        last_generation_accessed: NotAliveGenerationCounts::sub_zero(), // never accessed
-      };
-      self.instance_map.insert(instance_key.clone(), imd);
-      self
-        .instance_map
-        .get_mut(&instance_key)
-        // must succeed, since this was just inserted
-        .unwrap()
-    };
-
-    // update instance metadata
+    let instance_metadata = Self::update_instance(
+      &mut self.instance_map,
+      instance_key,
+      instance_state_after(change_kind),
+    );
     instance_metadata.instance_samples.insert(receive_timestamp);
 
-    match (instance_metadata.instance_state, new_instance_state) {
-      (InstanceState::Alive, _) => (), // was Alive, does not change counts
-
-      (InstanceState::NotAliveDisposed, InstanceState::Alive) =>
-      // born again
-      {
-        instance_metadata
-          .latest_generation_available
-          .disposed_generation_count += 1;
-      }
-
-      (InstanceState::NotAliveDisposed, _) => (), // you can only die once
-
-      (InstanceState::NotAliveNoWriters, InstanceState::Alive) =>
-      // born again
-      {
-        instance_metadata
-          .latest_generation_available
-          .no_writers_generation_count += 1;
-      }
-
-      (InstanceState::NotAliveNoWriters, _) => (), // you can only die once
-    }
-    instance_metadata.instance_state = new_instance_state;
-
     // insert new_sample to main table
     self
       .datasamples
@@ -174,11 +152,13 @@ where
         receive_timestamp,
         SampleWithMetaData {
           generation_counts: instance_metadata.latest_generation_available,
+          receive_timestamp,
           writer_guid,
           sequence_number,
           write_options,
           sample_has_been_read: false,
           sample: new_sample,
+          raw_payload,
         },
       )
       .map_or_else(
@@ -231,6 +211,50 @@ where
     // sample, i.e.
   }
 
+  // Record a sample of an instance into the instance metadata.
+  fn update_instance(
+    instance_map: &mut BTreeMap<D::K, InstanceMetaData>,
+    instance_key: D::K,
+    new_instance_state: InstanceState,
+  ) -> &mut InstanceMetaData {
+    // find or create metadata record
+    let instance_metadata = instance_map
+      .entry(instance_key)
+      .or_insert_with(|| InstanceMetaData {
+        instance_samples: BTreeSet::new(),
+        instance_state: new_instance_state,
+        latest_generation_available: NotAliveGenerationCounts::zero(), /* this is new instance,
+                                                                        * so start from zero */
+        last_generation_accessed: NotAliveGenerationCounts::sub_zero(), // never accessed
+      });
+
+    match (instance_metadata.instance_state, new_instance_state) {
+      (InstanceState::Alive, _) => (), // was Alive, does not change counts
+
+      (InstanceState::NotAliveDisposed, InstanceState::Alive) =>
+      // born again
+      {
+        instance_metadata
+          .latest_generation_available
+          .disposed_generation_count += 1;
+      }
+
+      (InstanceState::NotAliveDisposed, _) => (), // you can only die once
+
+      (InstanceState::NotAliveNoWriters, InstanceState::Alive) =>
+      // born again
+      {
+        instance_metadata
+          .latest_generation_available
+          .no_writers_generation_count += 1;
+      }
+
+      (InstanceState::NotAliveNoWriters, _) => (), // you can only die once
+    }
+    instance_metadata.instance_state = new_instance_state;
+    instance_metadata
+  }
+
   // Helper for select_keys and select_instance_keys
   //
   // Selection is in timestamp order. If there are samples that have been received
@@ -303,6 +327,37 @@ where
     }
   }
 
+  // Like select_keys_for_access, but grouped by instance in key order, and
+  // within each instance ordered by (source timestamp, writer GUID, sequence
+  // number). Samples without a source timestamp use their reception timestamp
+  // instead. The DataReader documents this as a guarantee.
+  pub fn select_keys_for_access_ordered(&self, rc: ReadCondition) -> Vec<(Timestamp, D::K)> {
+    let mut keys = Vec::new();
+    for (instance, imd) in &self.instance_map {
+      let mut instance_keys: Vec<_> = imd
+        .instance_samples
+        .iter()
+        .filter_map(|ts| {
+          self
+            .datasamples
+            .get(ts)
+            .filter(|ds| self.sample_selector(&rc, imd, ds))
+            .map(|ds| {
+              let order_timestamp = ds.write_options.source_timestamp().unwrap_or(*ts);
+              ((order_timestamp, ds.writer_guid, ds.sequence_number), *ts)
+            })
+        })
+        .collect();
+      instance_keys.sort_unstable();
+      keys.extend(
+        instance_keys
+          .into_iter()
+          .map(|(_, ts)| (ts, instance.clone())),
+      );
+    }
+    keys
+  }
+
   // select helper
   fn sample_selector(
     &self,
@@ -333,13 +388,36 @@ where
     )
   }
 
+  // Computes the (sample_rank, MRSIC generations) pair for each sample in the
+  // collection `keys`. Both are per instance: sample_rank counts the following
+  // samples of the same instance, and the Most Recent Sample In Collection
+  // (MRSIC) is the last sample of the same instance in the collection.
+  fn collection_ranks(&self, keys: &[(Timestamp, D::K)]) -> Vec<(usize, i32)> {
+    let mut following: HashMap<&D::K, (usize, i32)> = HashMap::new();
+    let mut ranks: Vec<(usize, i32)> = keys
+      .iter()
+      .rev()
+      .map(|(ts, key)| {
+        let sample_generations = self.datasamples.get(ts).unwrap().generation_counts.total();
+        let (count, mrsic_generations) = following.entry(key).or_insert((0, sample_generations));
+        let rank = (*count, *mrsic_generations);
+        *count += 1;
+        rank
+      })
+      .collect();
+    ranks.reverse();
+    ranks
+  }
+
   fn make_sample_info(
     dswm: &SampleWithMetaData<D>,
     imd: &InstanceMetaData,
     sample_rank: usize,
-    mrs_generations: i32,
     mrsic_generations: i32,
   ) -> SampleInfo {
+    // The Most Recent Sample (MRS) of the instance has the latest generation
+    // counts available, as the counts only change when a sample is received.
+    let mrs_generations = imd.latest_generation_available.total();
     SampleInfo {
       sample_state: if dswm.sample_has_been_read {
         SampleState::Read
@@ -353,10 +431,11 @@ where
       },
       instance_state: imd.instance_state,
       generation_counts: dswm.generation_counts,
-      sample_rank: sample_rank as i32, // how many samples follow this one
+      sample_rank: sample_rank as i32, // how many samples of this instance follow this one
       generation_rank: mrsic_generations - dswm.generation_counts.total(),
       absolute_generation_rank: mrs_generations - dswm.generation_counts.total(),
       write_options: dswm.write_options.clone(),
+      reception_timestamp: dswm.receive_timestamp,
       publication_handle: dswm.writer_guid,
       sequence_number: dswm.sequence_number,
     }
@@ -415,28 +494,14 @@ where
     }
 
     let mut instance_generations: HashMap<D::K, NotAliveGenerationCounts> = HashMap::new();
-    let mrsic_total = self
-      .instance_map
-      // Keys are guaranteed to be nonempty, because of the length check above.
-      .get(&keys.last().unwrap().1)
-      .unwrap()
-      .latest_generation_available
-      .total();
-    let mrs_total = self
-      .datasamples
-      .iter()
-      .next_back()
-      .unwrap()
-      .1
-      .generation_counts
-      .total();
+    let ranks = self.collection_ranks(keys);
     let mut sample_infos = VecDeque::with_capacity(len);
     // construct SampleInfos and record read/viewed
-    for (index, (ts, key)) in keys.iter().enumerate() {
+    for ((ts, key), (sample_rank, mrsic_total)) in keys.iter().zip(ranks) {
       let dswm = self.datasamples.get_mut(ts).unwrap();
       let imd = self.instance_map.get(key).unwrap();
 
-      let sample_info = Self::make_sample_info(dswm, imd, len - index - 1, mrs_total, mrsic_total);
+      let sample_info = Self::make_sample_info(dswm, imd, sample_rank, mrsic_total);
       dswm.sample_has_been_read = true; // mark as read
       Self::record_instance_generation_viewed(
         &mut instance_generations,
@@ -477,6 +542,18 @@ where
     &mut self,
     keys: &[(Timestamp, D::K)],
   ) -> Vec<DataSample<D>> {
+    self
+      .take_with_raw_by_keys(keys)
+      .into_iter()
+      .map(|(sample, _raw)| sample)
+      .collect()
+  }
+
+  // As take_by_keys, but also returns the retained serialized payloads.
+  pub(in crate::dds::with_key) fn take_with_raw_by_keys(
+    &mut self,
+    keys: &[(Timestamp, D::K)],
+  ) -> Vec<(DataSample<D>, Option<SerializedPayload>)> {
     let len = keys.len();
     let mut result = Vec::with_capacity(len);
 
@@ -485,25 +562,12 @@ where
     }
 
     let mut instance_generations: HashMap<D::K, NotAliveGenerationCounts> = HashMap::new();
-    let mrsic_total = self
-      .instance_map
-      .get(&keys.last().unwrap().1)
-      .unwrap()
-      .latest_generation_available
-      .total();
-    let mrs_total = self
-      .datasamples
-      .iter()
-      .next_back()
-      .unwrap()
-      .1
-      .generation_counts
-      .total();
+    let ranks = self.collection_ranks(keys);
     // collect result
-    for (index, (ts, key)) in keys.iter().enumerate() {
+    for ((ts, key), (sample_rank, mrsic_total)) in keys.iter().zip(ranks) {
       let dswm = self.datasamples.remove(ts).unwrap();
       let imd = self.instance_map.get(key).unwrap();
-      let sample_info = Self::make_sample_info(&dswm, imd, len - index - 1, mrs_total, mrsic_total);
+      let sample_info = Self::make_sample_info(&dswm, imd, sample_rank, mrsic_total);
       // dwsm.sample_has_been_read = true; // no need to mark read, as the dswm is
       // about to be destroyed
       Self::record_instance_generation_viewed(
@@ -511,13 +575,47 @@ where
         dswm.generation_counts,
         key,
       );
-      result.push(DataSample::new(sample_info, dswm.sample));
+      result.push((DataSample::new(sample_info, dswm.sample), dswm.raw_payload));
     }
 
     self.mark_instances_viewed(&instance_generations);
     result
   }
 
+  // Update the instance metadata for a sample that is taken as soon as it is
+  // received, without storing it in the cache. Returns the SampleInfo the
+  // sample would get from adding it and then taking it alone.
+  pub(in crate::dds::with_key) fn take_passed_through(
+    &mut self,
+    change: DeserializedIntoChange<D::K>,
+  ) -> SampleInfo {
+    let imd = Self::update_instance(
+      &mut self.instance_map,
+      change.instance_key,
+      instance_state_after(change.change_kind),
+    );
+    let generation_counts = imd.latest_generation_available;
+    let sample_info = SampleInfo {
+      sample_state: SampleState::NotRead,
+      view_state: if generation_counts.total() > imd.last_generation_accessed.total() {
+        ViewState::New
+      } else {
+        ViewState::NotNew
+      },
+      instance_state: imd.instance_state,
+      generation_counts,
+      sample_rank: 0,
+      generation_rank: 0,
+      absolute_generation_rank: 0,
+      write_options: change.write_options,
+      reception_timestamp: change.receive_instant,
+      publication_handle: change.writer_guid,
+      sequence_number: change.sequence_number,
+    };
+    imd.last_generation_accessed = generation_counts;
+    sample_info
+  }
+
   // Panics: `keys` must only contain (Timestamp,Key)-pairs that were immediately
   // before this call obtained by select_*_for_access functions. This function
   // will blindly assume that the given keys and timestamps are present in the
@@ -622,6 +720,210 @@ mod tests {
   // use crate::dds::traits::key::Keyed;
   // use crate::test::random_data::*;
 
+  use super::*;
+  use crate::{
+    dds::qos::QosPolicyBuilder,
+    structure::guid::{EntityKind, GuidPrefix},
+    test::random_data::RandomData,
+    with_key::WriteOptionsBuilder,
+  };
+
+  #[test]
+  fn dsc_generation_ranks() {
+    let qos = QosPolicyBuilder::new()
+      .history(policy::History::KeepAll)
+      .build();
+    let mut cache = DataSampleCache::<RandomData>::new(qos);
+    let mut sn = SequenceNumber::from(0);
+    let mut add = |cache: &mut DataSampleCache<RandomData>, sample: Sample<RandomData, i64>| {
+      sn = sn + SequenceNumber::from(1);
+      let change_kind = match sample {
+        Sample::Value(_) => ChangeKind::Alive,
+        Sample::Dispose(_) => ChangeKind::NotAliveDisposed,
+      };
+      cache.add_sample(
+        sample,
+        change_kind,
+        GUID::GUID_UNKNOWN,
+        sn,
+        Timestamp::now(),
+        WriteOptions::default(),
+        None,
+      );
+    };
+    let data = |a: i64| {
+      Sample::Value(RandomData {
+        a,
+        b: "x".to_string(),
+      })
+    };
+
+    // Instance 1: alive, disposed, alive again (generation 1), disposed, and
+    // alive again (generation 2). Instance 2: one sample in between.
+    add(&mut cache, data(1));
+    add(&mut cache, Sample::Dispose(1));
+    add(&mut cache, data(2));
+    add(&mut cache, data(1));
+    add(&mut cache, Sample::Dispose(1));
+    add(&mut cache, data(1));
+
+    // Read only the first three samples of instance 1 and the sample of
+    // instance 2. MRSIC of instance 1 is then the first rebirth sample, but
+    // MRS is the second one.
+    let keys = cache.select_keys_for_access(ReadCondition::any());
+    assert_eq!(keys.len(), 6);
+    let keys: Vec<_> = keys[..4].to_vec();
+    let infos: Vec<SampleInfo> = cache
+      .read_by_keys(&keys)
+      .into_iter()
+      .map(|ds| ds.sample_info().clone())
+      .collect();
+
+    let ranks: Vec<(i32, i32, i32)> = infos
+      .iter()
+      .map(|si| {
+        (
+          si.sample_rank(),
+          si.generation_rank(),
+          si.absolute_generation_rank(),
+        )
+      })
+      .collect();
+    assert_eq!(ranks, vec![(2, 1, 2), (1, 1, 2), (0, 0, 0), (0, 0, 1)]);
+  }
+
+  #[test]
+  fn dsc_view_and_sample_states() {
+    let qos = QosPolicyBuilder::new()
+      .history(policy::History::KeepAll)
+      .build();
+    let mut cache = DataSampleCache::<RandomData>::new(qos);
+    let mut sn = 0;
+    let mut add = |cache: &mut DataSampleCache<RandomData>, a: i64| {
+      sn += 1;
+      let received = Timestamp::from_ticks(1000 + sn as u64);
+      cache.add_sample(
+        Sample::Value(RandomData {
+          a,
+          b: "x".to_string(),
+        }),
+        ChangeKind::Alive,
+        GUID::GUID_UNKNOWN,
+        SequenceNumber::from(sn),
+        received,
+        WriteOptions::default(),
+        None,
+      );
+      received
+    };
+    let read_not_read = |cache: &mut DataSampleCache<RandomData>| -> Vec<SampleInfo> {
+      let keys = cache.select_keys_for_access(ReadCondition::not_read());
+      cache
+        .read_by_keys(&keys)
+        .into_iter()
+        .map(|ds| ds.sample_info().clone())
+        .collect()
+    };
+
+    let received = add(&mut cache, 1);
+    let infos = read_not_read(&mut cache);
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].view_state(), ViewState::New);
+    assert_eq!(infos[0].sample_state(), SampleState::NotRead);
+    assert_eq!(infos[0].instance_state(), InstanceState::Alive);
+    assert_eq!(infos[0].reception_timestamp(), received);
+
+    // A later sample of a viewed instance is not new, but one of a new
+    // instance is.
+    add(&mut cache, 1);
+    add(&mut cache, 2);
+    let infos = read_not_read(&mut cache);
+    let view_states: Vec<ViewState> = infos.iter().map(SampleInfo::view_state).collect();
+    assert_eq!(view_states, vec![ViewState::NotNew, ViewState::New]);
+
+    let keys = cache.select_keys_for_access(ReadCondition::any());
+    let sample_states: Vec<SampleState> = cache
+      .read_by_keys(&keys)
+      .iter()
+      .map(|ds| ds.sample_info().sample_state())
+      .collect();
+    assert_eq!(sample_states, vec![SampleState::Read; 3]);
+  }
+
+  #[test]
+  fn dsc_ordered_selection() {
+    let qos = QosPolicyBuilder::new()
+      .history(policy::History::KeepAll)
+      .build();
+    let mut cache = DataSampleCache::<RandomData>::new(qos);
+    let writer_1 = GUID::dummy_test_guid(EntityKind::WRITER_WITH_KEY_USER_DEFINED);
+    let writer_2 = GUID::new_with_prefix_and_id(GuidPrefix::new(&[0xff; 12]), writer_1.entity_id);
+    let mut received = 0;
+    // Samples are received in this order, each with a receive timestamp later
+    // than the previous one.
+    let mut add = |cache: &mut DataSampleCache<RandomData>,
+                   a: i64,
+                   writer: GUID,
+                   sn: i64,
+                   source_timestamp: Option<u64>| {
+      received += 1;
+      let mut write_options = WriteOptions::default();
+      if let Some(st) = source_timestamp {
+        write_options = WriteOptionsBuilder::new()
+          .source_timestamp(Timestamp::from_ticks(st))
+          .build();
+      }
+      cache.add_sample(
+        Sample::Value(RandomData {
+          a,
+          b: format!("{writer:?}/{sn}"),
+        }),
+        ChangeKind::Alive,
+        writer,
+        SequenceNumber::new(sn),
+        Timestamp::from_ticks(1000 + received),
+        write_options,
+        None,
+      );
+    };
+
+    add(&mut cache, 2, writer_1, 2, Some(20)); // arrives before sample 1
+    add(&mut cache, 2, writer_1, 1, Some(10));
+    add(&mut cache, 1, writer_2, 1, Some(30));
+    add(&mut cache, 1, writer_1, 3, Some(30)); // same source timestamp
+    add(&mut cache, 1, writer_1, 4, None); // ordered by reception timestamp
+    add(&mut cache, 2, writer_2, 2, Some(5));
+
+    let order = |cache: &DataSampleCache<RandomData>, rc| {
+      cache
+        .select_keys_for_access_ordered(rc)
+        .iter()
+        .map(|(ts, key)| {
+          let ds = cache.datasamples.get(ts).unwrap();
+          (*key, ds.writer_guid, i64::from(ds.sequence_number))
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let expected = vec![
+      (1, writer_1, 3),
+      (1, writer_2, 1),
+      (1, writer_1, 4),
+      (2, writer_2, 2),
+      (2, writer_1, 1),
+      (2, writer_1, 2),
+    ];
+    assert_eq!(order(&cache, ReadCondition::any()), expected);
+
+    // The order does not change when samples are read, or more arrive.
+    let keys = cache.select_keys_for_access_ordered(ReadCondition::any());
+    cache.read_by_keys(&keys[..2]);
+    add(&mut cache, 0, writer_2, 3, Some(1));
+    let mut expected_after = vec![(0, writer_2, 3)];
+    expected_after.extend_from_slice(&expected[2..]);
+    assert_eq!(order(&cache, ReadCondition::not_read()), expected_after);
+  }
+
   #[test]
   fn dsc_empty_qos() {
     /*
@@ -15,12 +15,14 @@ use crate::{
   dds::{
     adapters::with_key::SerializerAdapter,
     ddsdata::DDSData,
+    key::{Key, KeyHash},
     pubsub::Publisher,
     qos::{
-      policy::{Liveliness, Reliability},
+      policy::{Durability, Liveliness, Reliability},
       HasQoSPolicy, QosPolicies,
     },
     result::{CreateResult, WriteError, WriteResult},
+    sampleinfo::ReceptionContext,
     statusevents::*,
     topic::Topic,
   },
@@ -29,10 +31,14 @@ use crate::{
   rtps::writer_send_buffer::{Admission, WriterSendBuffer},
   serialization::CDRSerializerAdapter,
   structure::{
-    cache_change::ChangeKind, entity::RTPSEntity, guid::GUID, rpc::SampleIdentity,
-    sequence_number::SequenceNumber, time::Timestamp,
+    cache_change::ChangeKind,
+    entity::RTPSEntity,
+    guid::{EntityId, GUID},
+    rpc::SampleIdentity,
+    sequence_number::SequenceNumber,
+    time::Timestamp,
   },
-  Keyed, TopicDescription,
+  Keyed, TopicDescription, TopicKind,
 };
 
 // TODO: Move the write options and the builder type to some lower-level module
@@ -43,6 +49,8 @@ pub struct WriteOptionsBuilder {
   source_timestamp: Option<Timestamp>,
   to_single_reader: Option<GUID>,
   best_effort_may_block: bool,
+  reception_context: Option<Box<ReceptionContext>>,
+  key_hash: Option<KeyHash>,
 }
 
 impl WriteOptionsBuilder {
@@ -56,9 +64,17 @@ impl WriteOptionsBuilder {
       source_timestamp: self.source_timestamp,
       to_single_reader: self.to_single_reader,
       best_effort_may_block: self.best_effort_may_block,
+      reception_context: self.reception_context,
+      key_hash: self.key_hash,
+      content_duplicate: false,
+      coherent_set: None,
     }
   }
 
+  /// Mark the sample as related to another sample, e.g. a reply to a
+  /// request. Sent in the inline QoS, and available to DataReaders from
+  /// [`SampleInfo::related_sample_identity`](crate::SampleInfo::related_sample_identity).
+  /// This is how DDS-RPC correlates replies with requests.
   #[must_use]
   pub fn related_sample_identity(mut self, related_sample_identity: SampleIdentity) -> Self {
     self.related_sample_identity = Some(related_sample_identity);
@@ -106,6 +122,20 @@ impl WriteOptionsBuilder {
     self.best_effort_may_block = may_block;
     self
   }
+
+  // Receiving side only: see `SampleInfo::reception_context`.
+  #[must_use]
+  pub(crate) fn reception_context(mut self, reception_context: ReceptionContext) -> Self {
+    self.reception_context = Some(Box::new(reception_context));
+    self
+  }
+
+  // Receiving side only: the key hash sent in the inline QoS of a sample.
+  #[must_use]
+  pub(crate) fn key_hash(mut self, key_hash: KeyHash) -> Self {
+    self.key_hash = Some(key_hash);
+    self
+  }
 }
 
 /// Type to be used with write_with_options.
@@ -120,6 +150,20 @@ pub struct WriteOptions {
   // Defaults to `false` (DDS v1.4 section 2.2.2.4.2.11: `write` must not block
   // for best-effort reliability). See `WriteOptionsBuilder::best_effort_may_block`.
   best_effort_may_block: bool,
+  // Receiving side only: routing context of a received sample, if recording
+  // it is enabled. Boxed to keep WriteOptions small when it is not.
+  reception_context: Option<Box<ReceptionContext>>,
+  // Key hash of the instance, sent as PID_KEY_HASH in the inline QoS. Set by
+  // DataWriters of WITH_KEY topics, and on the receiving side if the sample
+  // carried one.
+  key_hash: Option<KeyHash>,
+  // Receiving side only: the payload equals that of the previous sample of
+  // the same instance. See `SampleInfo::is_content_duplicate`.
+  content_duplicate: bool,
+  // The first SequenceNumber of the coherent set of the sample, sent as
+  // PID_COHERENT_SET. Set by the send buffer between
+  // Publisher::begin_coherent_changes and end_coherent_changes.
+  coherent_set: Option<SequenceNumber>,
 }
 
 impl WriteOptions {
@@ -145,6 +189,46 @@ impl WriteOptions {
   pub fn best_effort_may_block(&self) -> bool {
     self.best_effort_may_block
   }
+
+  pub(crate) fn reception_context(&self) -> Option<&ReceptionContext> {
+    self.reception_context.as_deref()
+  }
+
+  pub(crate) fn key_hash(&self) -> Option<KeyHash> {
+    self.key_hash
+  }
+
+  #[must_use]
+  pub(crate) fn with_source_timestamp(mut self, source_timestamp: Timestamp) -> Self {
+    self.source_timestamp = Some(source_timestamp);
+    self
+  }
+
+  #[must_use]
+  pub(crate) fn with_key_hash(mut self, key_hash: KeyHash) -> Self {
+    self.key_hash = Some(key_hash);
+    self
+  }
+
+  pub(crate) fn content_duplicate(&self) -> bool {
+    self.content_duplicate
+  }
+
+  #[must_use]
+  pub(crate) fn with_content_duplicate(mut self) -> Self {
+    self.content_duplicate = true;
+    self
+  }
+
+  pub(crate) fn coherent_set(&self) -> Option<SequenceNumber> {
+    self.coherent_set
+  }
+
+  #[must_use]
+  pub(crate) fn with_coherent_set(mut self, coherent_set: SequenceNumber) -> Self {
+    self.coherent_set = Some(coherent_set);
+    self
+  }
 }
 
 impl From<Option<Timestamp>> for WriteOptions {
@@ -154,6 +238,10 @@ impl From<Option<Timestamp>> for WriteOptions {
       source_timestamp,
       to_single_reader: None,
       best_effort_may_block: false,
+      reception_context: None,
+      key_hash: None,
+      content_duplicate: false,
+      coherent_set: None,
     }
   }
 }
@@ -365,11 +453,19 @@ where
     Ok(())
   }
 
+  /// Like [`write`](Self::write), but with [`WriteOptions`] built with
+  /// [`WriteOptionsBuilder`].
+  ///
+  /// Returns the identity of the written sample. DataReaders see it as
+  /// [`SampleInfo::sample_identity`](crate::SampleInfo::sample_identity), so a
+  /// replier can set it as the related sample identity of its reply.
   pub fn write_with_options(
     &self,
     data: D,
     write_options: WriteOptions,
   ) -> WriteResult<SampleIdentity, D> {
+    let write_options =
+      self.with_key_hash(&data.key(), self.with_lifespan_timestamp(write_options));
     // serialize
     let send_buffer = match SA::to_bytes(&data) {
       Ok(b) => b,
@@ -413,6 +509,72 @@ where
     }
   }
 
+  /// Writes `data` like [`write`](Self::write), but only if it could reach a
+  /// DataReader.
+  ///
+  /// If the DataWriter has Volatile Durability (the default) and no DataReader
+  /// is currently matched, the sample is dropped without serializing or
+  /// storing it, and `Ok(false)` is returned. A Volatile sample written with
+  /// no matched readers would not be delivered to anyone, not even to readers
+  /// matched later, so only the serialization work is saved.
+  ///
+  /// With TransientLocal or stronger Durability the sample is always written,
+  /// as it is kept for late-joining DataReaders. Then `Ok(true)` is returned,
+  /// as it is whenever the sample is written.
+  ///
+  /// The match status is updated by Discovery in the background. A DataReader
+  /// matched just before this call may not yet be counted, and a sample
+  /// dropped for that reason is lost. Use [`write`](Self::write) for data that
+  /// every reader must see.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use serde::{Serialize, Deserialize};
+  /// # use rustdds::*;
+  /// # use rustdds::with_key::DataWriter;
+  /// # use rustdds::serialization::CDRSerializerAdapter;
+  /// #
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  /// let publisher = domain_participant.create_publisher(&qos).unwrap();
+  ///
+  /// #[derive(Serialize, Deserialize, Debug)]
+  /// struct SomeType { a: i32 }
+  /// impl Keyed for SomeType {
+  ///   type K = i32;
+  ///
+  ///   fn key(&self) -> Self::K {
+  ///     self.a
+  ///   }
+  /// }
+  ///
+  /// let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let data_writer = publisher.create_datawriter::<SomeType, CDRSerializerAdapter<_>>(&topic, None).unwrap();
+  ///
+  /// // Nobody is subscribed yet, so nothing is written.
+  /// assert!(!data_writer.write_if_matched(SomeType { a: 1 }, None).unwrap());
+  /// ```
+  pub fn write_if_matched(
+    &self,
+    data: D,
+    source_timestamp: Option<Timestamp>,
+  ) -> WriteResult<bool, D> {
+    let volatile = matches!(
+      self.qos_policy.durability.unwrap_or(Durability::Volatile),
+      Durability::Volatile
+    );
+    if volatile && self.send_buffer.matched_readers() == 0 {
+      trace!(
+        "write_if_matched: no matched readers, dropping sample. topic={:?}",
+        self.my_topic.name()
+      );
+      return Ok(false);
+    }
+    self.write(data, source_timestamp)?;
+    Ok(true)
+  }
+
   /// This operation blocks the calling thread until either all data written by
   /// the reliable DataWriter entities is acknowledged by all
   /// matched reliable DataReader entities, or else the duration specified by
@@ -468,6 +630,120 @@ where
     } // match
   }
 
+  /// Blocks the calling thread until this DataWriter's history cache is
+  /// empty, or the duration specified by `max_wait` elapses, whichever happens
+  /// first.
+  ///
+  /// This is a stronger guarantee than
+  /// [`wait_for_acknowledgments`](Self::wait_for_acknowledgments): the
+  /// samples have not only been delivered to and acknowledged by all matched
+  /// reliable DataReaders, but also released from the history. With VOLATILE
+  /// durability (the default), delivered samples are released right away.
+  /// With other durability kinds, the history is retained for late-joining
+  /// DataReaders, so it drains only when samples are evicted from it.
+  ///
+  /// Return values
+  /// * `Ok(true)` - history is empty
+  /// * `Ok(false)`- timed out waiting for the history to drain
+  /// * `Err(_)` - something went wrong
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use serde::{Serialize, Deserialize};
+  /// # use rustdds::*;
+  /// #
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  /// let publisher = domain_participant.create_publisher(&qos).unwrap();
+  ///
+  /// #[derive(Serialize, Deserialize, Debug)]
+  /// struct SomeType { a: i32 }
+  /// impl Keyed for SomeType {
+  ///   type K = i32;
+  ///
+  ///   fn key(&self) -> Self::K {
+  ///     self.a
+  ///   }
+  /// }
+  ///
+  /// let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let data_writer = publisher.create_datawriter::<SomeType, CDRSerializerAdapter<_>>(&topic, None).unwrap();
+  ///
+  /// data_writer.write(SomeType { a: 1 }, None).unwrap();
+  /// data_writer.wait_for_history_drain(std::time::Duration::from_millis(100));
+  /// ```
+  pub fn wait_for_history_drain(&self, max_wait: Duration) -> WriteResult<bool, ()> {
+    Ok(self.send_buffer.wait_for_drain(max_wait))
+  }
+
+  /// Statistics of coalescing samples into datagrams, including the latency
+  /// currently added by holding samples back. See
+  /// [`DomainParticipantBuilder::max_coalesce_delay`](crate::DomainParticipantBuilder::max_coalesce_delay).
+  pub fn coalescing_statistics(&self) -> CoalescingStatistics {
+    self.send_buffer.coalescing_statistics()
+  }
+
+  /// Let this DataWriter hold a sample back for at most `max_delay` to send
+  /// it together with later samples in one datagram. This overrides
+  /// [`DomainParticipantBuilder::max_coalesce_delay`](crate::DomainParticipantBuilder::max_coalesce_delay)
+  /// for this DataWriter only. A zero `max_delay` disables holding, so that a
+  /// latency-sensitive DataWriter sends every sample right away.
+  ///
+  /// Samples too large for one datagram are still sent fragmented, and are
+  /// never held.
+  pub fn set_max_coalesce_delay(&self, max_delay: Duration) {
+    self.send_buffer.set_max_coalesce_delay(Some(max_delay));
+    // Apply a shorter delay to samples already held.
+    self.ring_doorbell();
+  }
+
+  /// Send the samples held back for coalescing now, without waiting for the
+  /// datagram to fill up or for the coalescing delay to elapse. The samples
+  /// are still packed into as few datagrams as possible.
+  ///
+  /// This does not wait for the samples to be sent. Use
+  /// [`wait_for_acknowledgments`](Self::wait_for_acknowledgments) for that.
+  pub fn flush(&self) {
+    self.send_buffer.request_flush();
+    self.ring_doorbell();
+  }
+
+  /// Number of matched readers and the repair (resend) load they cause. See
+  /// [`DomainParticipantBuilder::large_fan_out`](crate::DomainParticipantBuilder::large_fan_out).
+  pub fn fan_out_statistics(&self) -> FanOutStatistics {
+    self.send_buffer.fan_out_statistics()
+  }
+
+  /// Path MTU to each matched DataReader, by DataReader GUID. Samples too
+  /// large for the path MTU to a reader are fragmented. See
+  /// [`DomainParticipantBuilder::path_mtu`](crate::DomainParticipantBuilder::path_mtu).
+  pub fn matched_reader_path_mtus(&self) -> Vec<(GUID, u32)> {
+    self.send_buffer.reader_path_mtus()
+  }
+
+  /// Number of samples written, and still in the history, that the reliable
+  /// DataReader `reader` has not acknowledged yet. An application can use this
+  /// to slow down when a DataReader falls behind, before `write` blocks.
+  ///
+  /// This is zero for a BestEffort DataWriter, which gets no
+  /// acknowledgements, and for DataReaders that are BestEffort or not matched.
+  pub fn unacked_sample_count(&self, reader: GUID) -> usize {
+    match &self.qos_policy.reliability {
+      None | Some(Reliability::BestEffort) => 0,
+      Some(Reliability::Reliable { .. }) => self.send_buffer.unacked_sample_count(reader),
+    }
+  }
+
+  /// The largest [`unacked_sample_count`](Self::unacked_sample_count) over
+  /// all matched reliable DataReaders, i.e. how far behind the slowest one is.
+  pub fn max_unacked_across_readers(&self) -> usize {
+    match &self.qos_policy.reliability {
+      None | Some(Reliability::BestEffort) => 0,
+      Some(Reliability::Reliable { .. }) => self.send_buffer.max_unacked_sample_count(),
+    }
+  }
+
   /*
 
   /// Unimplemented. <b>Do not use</b>.
@@ -764,14 +1040,50 @@ where
     }
   }
 
-  /// Placeholder only — not implemented. **Will panic if called.**
-  ///
-  /// # Panics
+  // Spec calls for two separate functions:
+  // get_matched_subscriptions returns a list of handles
+  // get_matched_subscription_data returns SubscriptionBuiltinTopicData for a
+  // handle. Here the GUIDs of the DataReaders serve as handles.
+
+  /// The DataReaders currently matched with this DataWriter, with the QoS
+  /// they requested, e.g. reliability and partition.
   ///
-  /// Always panics. This method is a placeholder and is not implemented.
-  #[deprecated(note = "placeholder only; will panic if called")]
+  /// The result is a snapshot: DataReaders matched or lost, also by losing
+  /// their participant, are either all included or all excluded. The topic
+  /// and type names are those of this DataWriter, as a DataReader can only
+  /// match with the same topic.
   pub fn get_matched_subscriptions(&self) -> Vec<SubscriptionBuiltinTopicData> {
-    unreachable!("get_matched_subscriptions is a placeholder only and must not be called")
+    self
+      .send_buffer
+      .matched_reader_qos()
+      .iter()
+      .map(|(guid, qos)| self.subscription_data(*guid, qos))
+      .collect()
+  }
+
+  /// The matched DataReader `reader`, as in
+  /// [`get_matched_subscriptions`](Self::get_matched_subscriptions), or `None`
+  /// if it is not matched.
+  pub fn get_matched_subscription_data(
+    &self,
+    reader: GUID,
+  ) -> Option<SubscriptionBuiltinTopicData> {
+    self
+      .send_buffer
+      .matched_reader_qos()
+      .get(&reader)
+      .map(|qos| self.subscription_data(reader, qos))
+  }
+
+  fn subscription_data(&self, reader: GUID, qos: &QosPolicies) -> SubscriptionBuiltinTopicData {
+    SubscriptionBuiltinTopicData::new(
+      reader,
+      Some(GUID::new(reader.prefix, EntityId::PARTICIPANT)),
+      self.my_topic.name(),
+      self.my_topic.get_type().name().to_string(),
+      qos,
+      None,
+    )
   }
 
   /// Disposes data instance with specified key
@@ -827,28 +1139,186 @@ where
     key: &<D as Keyed>::K,
     source_timestamp: Option<Timestamp>,
   ) -> WriteResult<(), ()> {
+    self.dispose_with_options(key, WriteOptions::from(source_timestamp))?;
+    Ok(())
+  }
+
+  /// Disposes the instance with the specified key, and then blocks the calling
+  /// thread until all matched reliable DataReaders have acknowledged the
+  /// disposal, or `max_wait` elapses.
+  ///
+  /// When this returns `Ok(true)`, every matched reliable DataReader has
+  /// received the disposal, so the key can be reused without readers mixing
+  /// the new instance with the old one.
+  ///
+  /// As with [`wait_for_acknowledgments`](Self::wait_for_acknowledgments), the
+  /// call succeeds immediately if this DataWriter is not Reliable or there are
+  /// no matched reliable DataReaders. Storing the disposal may block for the
+  /// `max_blocking_time` of Reliability first, as in
+  /// [`dispose`](Self::dispose); that time is not included in `max_wait`.
+  ///
+  /// Return values
+  /// * `Ok(true)` - disposal acknowledged
+  /// * `Ok(false)`- timed out waiting for acknowledgments
+  /// * `Err(_)` - disposal could not be written
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use serde::{Serialize, Deserialize};
+  /// # use rustdds::*;
+  /// # use rustdds::with_key::DataWriter;
+  /// # use rustdds::serialization::CDRSerializerAdapter;
+  /// #
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  /// let publisher = domain_participant.create_publisher(&qos).unwrap();
+  ///
+  /// #[derive(Serialize, Deserialize, Debug)]
+  /// struct SomeType { a: i32, val: usize }
+  /// impl Keyed for SomeType {
+  ///   type K = i32;
+  ///
+  ///   fn key(&self) -> Self::K {
+  ///     self.a
+  ///   }
+  /// }
+  ///
+  /// let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let data_writer = publisher.create_datawriter::<SomeType, CDRSerializerAdapter<_>>(&topic, None).unwrap();
+  ///
+  /// data_writer.write(SomeType { a: 1, val: 3 }, None).unwrap();
+  /// if data_writer.dispose_and_wait(&1, std::time::Duration::from_millis(100)).unwrap() {
+  ///   // all readers know instance 1 is gone
+  /// }
+  /// ```
+  pub fn dispose_and_wait(
+    &self,
+    key: &<D as Keyed>::K,
+    max_wait: Duration,
+  ) -> WriteResult<bool, ()> {
+    let sequence_number = self.dispose_with_options(key, WriteOptions::default())?;
+    match &self.qos_policy.reliability {
+      None | Some(Reliability::BestEffort) => Ok(true),
+      Some(Reliability::Reliable { .. }) => Ok(
+        self
+          .send_buffer
+          .wait_for_acked_through(sequence_number, max_wait),
+      ),
+    }
+  }
+
+  /// Unregisters the instance with the specified key: this DataWriter will no
+  /// longer update it. Unlike [`dispose`](Self::dispose), this does not
+  /// declare that the instance is gone. Writing a sample with the same key
+  /// registers the instance again.
+  ///
+  /// Matched DataReaders are notified with the Unregistered flag of the
+  /// StatusInfo inline QoS (RTPS spec v2.5 Section 8.7.4). Note that a RustDDS
+  /// DataReader delivers the unregistration like a disposal, as a
+  /// [`Sample::Dispose`](crate::with_key::Sample::Dispose).
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use serde::{Serialize, Deserialize};
+  /// # use rustdds::*;
+  /// # use rustdds::with_key::DataWriter;
+  /// # use rustdds::serialization::CDRSerializerAdapter;
+  /// #
+  /// let domain_participant = DomainParticipant::new(0).unwrap();
+  /// let qos = QosPolicyBuilder::new().build();
+  /// let publisher = domain_participant.create_publisher(&qos).unwrap();
+  ///
+  /// #[derive(Serialize, Deserialize, Debug)]
+  /// struct SomeType { a: i32, val: usize }
+  /// impl Keyed for SomeType {
+  ///   type K = i32;
+  ///
+  ///   fn key(&self) -> Self::K {
+  ///     self.a
+  ///   }
+  /// }
+  ///
+  /// let topic = domain_participant.create_topic("some_topic".to_string(), "SomeType".to_string(), &qos, TopicKind::WithKey).unwrap();
+  /// let data_writer = publisher.create_datawriter::<SomeType, CDRSerializerAdapter<_>>(&topic, None).unwrap();
+  ///
+  /// data_writer.write(SomeType { a: 1, val: 3 }, None).unwrap();
+  /// data_writer.unregister_instance(&1, None).unwrap();
+  /// // registers instance 1 again
+  /// data_writer.write(SomeType { a: 1, val: 4 }, None).unwrap();
+  /// ```
+  pub fn unregister_instance(
+    &self,
+    key: &<D as Keyed>::K,
+    source_timestamp: Option<Timestamp>,
+  ) -> WriteResult<(), ()> {
+    self.write_state_change(
+      key,
+      ChangeKind::NotAliveUnregistered,
+      WriteOptions::from(source_timestamp),
+    )?;
+    Ok(())
+  }
+
+  fn dispose_with_options(
+    &self,
+    key: &<D as Keyed>::K,
+    write_options: WriteOptions,
+  ) -> WriteResult<SequenceNumber, ()> {
+    self.write_state_change(key, ChangeKind::NotAliveDisposed, write_options)
+  }
+
+  // Send the key of an instance with the StatusInfo of `change_kind`.
+  fn write_state_change(
+    &self,
+    key: &<D as Keyed>::K,
+    change_kind: ChangeKind,
+    write_options: WriteOptions,
+  ) -> WriteResult<SequenceNumber, ()> {
+    let write_options = self.with_key_hash(key, self.with_lifespan_timestamp(write_options));
     let send_buffer = SA::key_to_bytes(key).map_err(|e| WriteError::Serialization {
       reason: format!("{e}"),
       data: (),
     })?; // serialize key
 
     let ddsdata = DDSData::new_disposed_by_key(
-      ChangeKind::NotAliveDisposed,
+      change_kind,
       SerializedPayload::new_from_bytes(SA::output_encoding(), send_buffer),
     );
     let timeout = self.qos().reliable_max_blocking_time().map(|d| d.to_std());
     match self
       .send_buffer
-      .admit_blocking(WriteOptions::from(source_timestamp), ddsdata, timeout)
+      .admit_blocking(write_options, ddsdata, timeout)
     {
-      Admission::Admitted(_seq) => {
+      Admission::Admitted(sequence_number) => {
         self.ring_doorbell();
         self.refresh_manual_liveliness();
-        Ok(())
+        Ok(sequence_number)
       }
       Admission::WouldBlock => Err(WriteError::WouldBlock { data: () }),
     }
   }
+
+  // Samples of a WITH_KEY topic carry the key hash in their inline QoS. It is
+  // always computed from the big-endian CDR encoding of the key (RTPS spec v2.5
+  // Section 9.6.4.8), regardless of the encoding of the payload.
+  fn with_key_hash(&self, key: &<D as Keyed>::K, write_options: WriteOptions) -> WriteOptions {
+    match self.my_topic.kind() {
+      TopicKind::WithKey => write_options.with_key_hash(key.hash_key(false)),
+      TopicKind::NoKey => write_options,
+    }
+  }
+
+  // The LIFESPAN of a sample counts from its source timestamp, so samples
+  // written without one are stamped with the current time for the Writer to
+  // expire them.
+  fn with_lifespan_timestamp(&self, write_options: WriteOptions) -> WriteOptions {
+    match (self.qos().lifespan, write_options.source_timestamp()) {
+      (Some(_), None) => write_options.with_source_timestamp(Timestamp::now()),
+      _ => write_options,
+    }
+  }
 }
 
 impl<'a, D, SA> StatusEvented<'a, DataWriterStatus, StatusReceiverStream<'a, DataWriterStatus>>
@@ -1031,6 +1501,8 @@ where
   ) -> WriteResult<SampleIdentity, D> {
     // Construct a future for an async write operation and await for its completion
 
+    let write_options =
+      self.with_key_hash(&data.key(), self.with_lifespan_timestamp(write_options));
     let send_buffer = match SA::to_bytes(&data) {
       Ok(s) => s,
       Err(e) => {
@@ -1062,6 +1534,12 @@ where
 
   /// Like the synchronous version.
   /// But there is no timeout. Use asyncs to bring your own timeout.
+  ///
+  /// The future resolves when all matched Reliable DataReaders have
+  /// acknowledged the samples written before the call. It is cancellation
+  /// safe: it can be dropped at any time, e.g. when it loses a `select`
+  /// against [`as_async_status_stream`](StatusEvented::as_async_status_stream)
+  /// or a timer, without affecting the DataWriter.
   pub async fn async_wait_for_acknowledgments(&self) -> WriteResult<bool, ()> {
     match &self.qos_policy.reliability {
       None | Some(Reliability::BestEffort) => Ok(true),
@@ -1083,12 +1561,25 @@ mod tests {
 
   use byteorder::LittleEndian;
   use log::info;
+  use serde::{Deserialize, Serialize};
+  use speedy::Endianness;
 
   use super::*;
   use crate::{
-    dds::{key::Key, participant::DomainParticipant},
-    structure::topic_kind::TopicKind,
+    dds::{
+      key::{CdrEncodingSize, Key},
+      participant::DomainParticipant,
+    },
+    messages::submessages::{
+      elements::{inline_qos::InlineQos, parameter_list::ParameterList},
+      submessages::WriterSubmessage,
+    },
+    rtps::{MessageBuilder, SubmessageBody},
+    structure::{
+      cache_change::CacheChange, guid::EntityId, parameter_id::ParameterId, topic_kind::TopicKind,
+    },
     test::random_data::*,
+    RepresentationIdentifier,
   };
 
   #[test]
@@ -1173,6 +1664,47 @@ mod tests {
     // TODO: verify that dispose is sent correctly
   }
 
+  #[test]
+  fn dw_dispose_and_wait_test() {
+    let domain_participant = DomainParticipant::new(0).expect("Participant creation failed!");
+    let qos = QosPolicies::qos_none();
+    let publisher = domain_participant
+      .create_publisher(&qos)
+      .expect("Failed to create publisher");
+    let topic = domain_participant
+      .create_topic(
+        "Aasii".to_string(),
+        "Huh?".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .expect("Failed to create topic");
+
+    let reliable_qos = QosPolicies::builder()
+      .reliability(Reliability::Reliable {
+        max_blocking_time: crate::Duration::from_millis(100),
+      })
+      .build();
+    let data_writer: DataWriter<RandomData, CDRSerializerAdapter<RandomData, LittleEndian>> =
+      publisher
+        .create_datawriter(&topic, Some(reliable_qos))
+        .expect("Failed to create datawriter");
+
+    let data = RandomData {
+      a: 4,
+      b: "Fobar".to_string(),
+    };
+    data_writer
+      .write(data.clone(), None)
+      .expect("Unable to write data");
+
+    // No reliable readers are matched, so the disposal is confirmed at once.
+    let confirmed = data_writer
+      .dispose_and_wait(&data.key(), Duration::from_secs(2))
+      .expect("Unable to dispose data");
+    assert!(confirmed);
+  }
+
   #[test]
   fn dw_wait_for_ack_test() {
     let domain_participant = DomainParticipant::new(0).expect("Participant creation failed!");
@@ -1207,4 +1739,273 @@ mod tests {
     assert!(res); // we should get "true" immediately, because we have
                   // no Reliable QoS
   }
+
+  #[test]
+  fn dw_wait_for_history_drain_test() {
+    let domain_participant = DomainParticipant::new(0).expect("Participant creation failed!");
+    let qos = QosPolicies::qos_none();
+    let publisher = domain_participant
+      .create_publisher(&qos)
+      .expect("Failed to create publisher");
+    let topic = domain_participant
+      .create_topic(
+        "Aasii".to_string(),
+        "Huh?".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .expect("Failed to create topic");
+
+    let data_writer: DataWriter<RandomData, CDRSerializerAdapter<RandomData, LittleEndian>> =
+      publisher
+        .create_datawriter(&topic, None)
+        .expect("Failed to create datawriter");
+
+    data_writer
+      .write(
+        RandomData {
+          a: 4,
+          b: "Fobar".to_string(),
+        },
+        None,
+      )
+      .expect("Unable to write data");
+
+    // Volatile writer without reliable readers: drains as soon as the event
+    // loop has processed the sample.
+    let res = data_writer
+      .wait_for_history_drain(Duration::from_secs(2))
+      .unwrap();
+    assert!(res);
+  }
+
+  #[test]
+  fn dw_write_if_matched_test() {
+    let domain_participant = DomainParticipant::new(0).expect("Participant creation failed!");
+    let qos = QosPolicies::qos_none();
+    let publisher = domain_participant
+      .create_publisher(&qos)
+      .expect("Failed to create publisher");
+    let topic = domain_participant
+      .create_topic(
+        "write_if_matched".to_string(),
+        "Huh?".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .expect("Failed to create topic");
+    let data = || RandomData {
+      a: 4,
+      b: "Fobar".to_string(),
+    };
+
+    let volatile: DataWriter<RandomData, CDRSerializerAdapter<RandomData, LittleEndian>> =
+      publisher
+        .create_datawriter(&topic, None)
+        .expect("Failed to create datawriter");
+    assert!(!volatile.write_if_matched(data(), None).unwrap());
+    assert_eq!(
+      volatile.send_buffer.last_change_sequence_number(),
+      SequenceNumber::zero()
+    );
+    // as the Writer does when a reader is matched
+    volatile.send_buffer.set_fan_out(1, false);
+    assert!(volatile.write_if_matched(data(), None).unwrap());
+    assert_eq!(
+      volatile.send_buffer.last_change_sequence_number(),
+      SequenceNumber::new(1)
+    );
+
+    // Durable samples are kept for late joiners.
+    let durable_qos = QosPolicies::builder()
+      .durability(Durability::TransientLocal)
+      .build();
+    let durable: DataWriter<RandomData, CDRSerializerAdapter<RandomData, LittleEndian>> = publisher
+      .create_datawriter(&topic, Some(durable_qos))
+      .expect("Failed to create datawriter");
+    assert!(durable.write_if_matched(data(), None).unwrap());
+    assert_eq!(
+      durable.send_buffer.last_change_sequence_number(),
+      SequenceNumber::new(1)
+    );
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone)]
+  struct Reading {
+    sensor: SensorKey,
+    value: f64,
+  }
+
+  #[derive(
+    Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, CdrEncodingSize,
+  )]
+  struct SensorKey {
+    id: u16,
+    zone: u32,
+  }
+
+  impl Key for SensorKey {}
+
+  impl Keyed for Reading {
+    type K = SensorKey;
+    fn key(&self) -> SensorKey {
+      self.sensor.clone()
+    }
+  }
+
+  // The inline QoS of DATA submessages built for `change`
+  fn transmitted_inline_qos(change: &CacheChange, writer_guid: GUID) -> Option<ParameterList> {
+    let message = MessageBuilder::new()
+      .data_msg(
+        change,
+        EntityId::UNKNOWN,
+        writer_guid,
+        Endianness::LittleEndian,
+        None,
+      )
+      .add_header_and_build(writer_guid.prefix);
+    match &message.submessages[0].body {
+      SubmessageBody::Writer(WriterSubmessage::Data(data, _)) => data.inline_qos.clone(),
+      other => panic!("Expected DATA, got {other:?}"),
+    }
+  }
+
+  // The key hash in the inline QoS of DATA submessages built for `change`
+  fn transmitted_key_hash(change: &CacheChange, writer_guid: GUID) -> Option<KeyHash> {
+    InlineQos::key_hash(&transmitted_inline_qos(change, writer_guid)?).unwrap()
+  }
+
+  // The PID_STATUS_INFO value in DATA submessages built for `change`
+  fn transmitted_status_info(change: &CacheChange, writer_guid: GUID) -> Option<Vec<u8>> {
+    transmitted_inline_qos(change, writer_guid)?
+      .parameters
+      .into_iter()
+      .find(|p| p.parameter_id == ParameterId::PID_STATUS_INFO)
+      .map(|p| p.value)
+  }
+
+  #[test]
+  fn dw_key_hash_is_big_endian_cdr_of_key() {
+    let domain_participant = DomainParticipant::new(0).expect("Participant creation failed!");
+    let qos = QosPolicies::qos_none();
+    let publisher = domain_participant
+      .create_publisher(&qos)
+      .expect("Failed to create publisher");
+    let topic = domain_participant
+      .create_topic(
+        "key_hash".to_string(),
+        "Reading".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .expect("Failed to create topic");
+    let data_writer: DataWriter<Reading, CDRSerializerAdapter<Reading, LittleEndian>> = publisher
+      .create_datawriter(&topic, None)
+      .expect("Failed to create datawriter");
+
+    let sensor = SensorKey {
+      id: 0x1234,
+      zone: 0xdead_beef,
+    };
+    // Big-endian CDR of the key, with alignment padding before `zone`, padded
+    // with zeros to 16 bytes.
+    let expected = KeyHash::from_pl_cdr_bytes(vec![
+      0x12, 0x34, 0, 0, 0xde, 0xad, 0xbe, 0xef, 0, 0, 0, 0, 0, 0, 0, 0,
+    ])
+    .unwrap();
+
+    let sample_identity = data_writer
+      .write_with_options(
+        Reading {
+          sensor: sensor.clone(),
+          value: 1.5,
+        },
+        WriteOptions::default(),
+      )
+      .expect("Unable to write data");
+    let change = data_writer
+      .send_buffer
+      .get_by_sn(sample_identity.sequence_number)
+      .unwrap();
+    match &change.data_value {
+      DDSData::Data { serialized_payload } => assert_eq!(
+        serialized_payload.representation_identifier,
+        RepresentationIdentifier::CDR_LE
+      ),
+      other => panic!("Expected data, got {other:?}"),
+    }
+    assert_eq!(
+      transmitted_key_hash(&change, data_writer.guid()),
+      Some(expected)
+    );
+
+    let disposal = data_writer
+      .dispose_with_options(&sensor, WriteOptions::default())
+      .expect("Unable to dispose");
+    let change = data_writer.send_buffer.get_by_sn(disposal).unwrap();
+    assert_eq!(
+      transmitted_key_hash(&change, data_writer.guid()),
+      Some(expected)
+    );
+  }
+
+  #[test]
+  fn dw_state_changes_carry_status_info() {
+    let domain_participant = DomainParticipant::new(0).expect("Participant creation failed!");
+    let qos = QosPolicies::qos_none();
+    let publisher = domain_participant
+      .create_publisher(&qos)
+      .expect("Failed to create publisher");
+    let topic = domain_participant
+      .create_topic(
+        "status_info".to_string(),
+        "Reading".to_string(),
+        &qos,
+        TopicKind::WithKey,
+      )
+      .expect("Failed to create topic");
+    let data_writer: DataWriter<Reading, CDRSerializerAdapter<Reading, LittleEndian>> = publisher
+      .create_datawriter(&topic, None)
+      .expect("Failed to create datawriter");
+    let guid = data_writer.guid();
+    let sensor = SensorKey { id: 1, zone: 2 };
+    let reading = Reading {
+      sensor: sensor.clone(),
+      value: 1.5,
+    };
+
+    // The flags are D = 1, U = 2, RTPS spec v2.5 Section 9.6.4.9
+    let write = |data_writer: &DataWriter<_, _>| {
+      let sn = data_writer
+        .write_with_options(reading.clone(), WriteOptions::default())
+        .expect("Unable to write data")
+        .sequence_number;
+      transmitted_status_info(&data_writer.send_buffer.get_by_sn(sn).unwrap(), guid)
+    };
+    let change_state = |data_writer: &DataWriter<_, _>, change_kind| {
+      let sn = data_writer
+        .write_state_change(&sensor, change_kind, WriteOptions::default())
+        .expect("Unable to change state");
+      transmitted_status_info(&data_writer.send_buffer.get_by_sn(sn).unwrap(), guid)
+    };
+
+    assert_eq!(write(&data_writer), None);
+    assert_eq!(
+      change_state(&data_writer, ChangeKind::NotAliveUnregistered),
+      Some(vec![0, 0, 0, 2])
+    );
+    // re-register
+    assert_eq!(write(&data_writer), None);
+    assert_eq!(
+      change_state(&data_writer, ChangeKind::NotAliveDisposed),
+      Some(vec![0, 0, 0, 1])
+    );
+    assert_eq!(
+      change_state(&data_writer, ChangeKind::NotAliveDisposedUnregistered),
+      Some(vec![0, 0, 0, 3])
+    );
+    data_writer
+      .unregister_instance(&sensor, None)
+      .expect("Unable to unregister");
+  }
 }
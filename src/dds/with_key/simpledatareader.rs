@@ -1,10 +1,13 @@
 use std::{
   cmp::max,
   collections::BTreeMap,
-  io,
+  fmt, io,
   marker::PhantomData,
   pin::Pin,
-  sync::{Arc, Mutex, MutexGuard},
+  sync::{
+    atomic::{self, AtomicBool},
+    Arc, Mutex, MutexGuard,
+  },
   task::{Context, Poll, Waker},
 };
 
@@ -24,10 +27,12 @@ use crate::{
     result::*,
     statusevents::*,
     topic::{Topic, TopicDescription},
-    with_key::datasample::{DeserializedCacheChange, Sample},
+    waitset::{DataAvailableCondition, StatusCondition},
+    with_key::datasample::{DeserializedCacheChange, DeserializedIntoChange, Sample},
   },
   discovery::discovery::DiscoveryCommand,
-  mio_source::PollEventSource,
+  messages::submessages::elements::serialized_payload::SerializedPayload,
+  mio_source::{make_poll_channel, PollEventSender, PollEventSource},
   serialization::CDRDeserializerAdapter,
   structure::{
     cache_change::CacheChange,
@@ -37,20 +42,78 @@ use crate::{
     sequence_number::SequenceNumber,
     time::Timestamp,
   },
+  create_error_dropped, RepresentationIdentifier,
 };
 
 #[derive(Clone, Debug)]
 pub(crate) enum ReaderCommand {
-  #[allow(dead_code)] // TODO: Implement this (resetting) feature
-  ResetRequestedDeadlineStatus,
+  // The DataReader has read the REQUESTED_DEADLINE_MISSED status, which had
+  // this count.
+  ResetRequestedDeadlineStatus { read_count: i32 },
+  // Another DataReader shares the Reader. See SimpleDataReader::share.
+  AttachDataReader(DataReaderNotifier),
+  // See DataReader::set_heartbeat_suppression.
+  SetHeartbeatSuppression(std::time::Duration),
+}
+
+// How a Reader tells one of its DataReaders about new samples and statuses.
+#[derive(Clone)]
+pub(crate) struct DataReaderNotifier {
+  pub notification_sender: mio_channel::SyncSender<()>,
+  pub status_sender: StatusChannelSender<DataReaderStatus>,
+  pub data_reader_waker: Arc<Mutex<Option<Waker>>>,
+  pub poll_event_sender: PollEventSender,
+}
+
+impl fmt::Debug for DataReaderNotifier {
+  // Channels cannot be Debug formatted.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("DataReaderNotifier").finish_non_exhaustive()
+  }
+}
+
+// Keeps the Reader alive, and removes it when the last DataReader sharing it
+// is dropped.
+struct ReaderRegistration {
+  subscriber: Subscriber,
+  guid: GUID,
+  discovery_command: mio_channel::SyncSender<DiscoveryCommand>,
+}
+
+impl Drop for ReaderRegistration {
+  fn drop(&mut self) {
+    // Tell dp_event_loop
+    self.subscriber.remove_reader(self.guid);
+
+    // Tell discovery
+    match self
+      .discovery_command
+      .send(DiscoveryCommand::RemoveLocalReader { guid: self.guid })
+    {
+      Ok(_) => {}
+      Err(mio_channel::SendError::Disconnected(_)) => {
+        debug!("Failed to send DiscoveryCommand::RemoveLocalReader . Maybe shutting down?");
+      }
+      Err(e) => error!("Failed to send DiscoveryCommand::RemoveLocalReader. {e:?}"),
+    }
+  }
 }
 
+// Selects the samples a content-filtered DataReader delivers.
+pub(crate) type ContentFilter<D> = Box<dyn Fn(&D) -> bool + Send + Sync>;
+
 // This is helper struct.
 // All mutable state needed for reading should go here.
 pub(crate) struct ReadState<K: Key> {
   latest_instant: Timestamp, /* This is used as a read pointer from dds_cache for BEST_EFFORT
                               * reading */
   last_read_sn: BTreeMap<GUID, SequenceNumber>, // collection of read pointers for RELIABLE reading
+  writer_restarts: BTreeMap<GUID, u32>,         // as last seen in the topic cache
+  // How many of the changes replayed from the DurabilityStore have been read,
+  // and the last replayed SequenceNumber of each Writer. Cached changes up to
+  // that are copies of replayed ones.
+  stored_read: usize,
+  stored_last_sn: BTreeMap<GUID, SequenceNumber>,
   /// hash_to_key_map is used for decoding received key hashes back to original
   /// key values. This is needed when we receive a dispose message via hash
   /// only.
@@ -62,39 +125,187 @@ impl<K: Key> ReadState<K> {
     ReadState {
       latest_instant: Timestamp::ZERO,
       last_read_sn: BTreeMap::new(),
+      writer_restarts: BTreeMap::new(),
+      stored_read: 0,
+      stored_last_sn: BTreeMap::new(),
       hash_to_key_map: BTreeMap::<KeyHash, K>::new(),
     }
   }
 
-  // This is a helper function so that borrow checker understands
-  // that we are splitting one mutable borrow into two _disjoint_ mutable
-  // borrows.
-  fn get_sn_map_and_hash_map(
-    &mut self,
-  ) -> (
-    &mut BTreeMap<GUID, SequenceNumber>,
-    &mut BTreeMap<KeyHash, K>,
-  ) {
-    let ReadState {
-      last_read_sn,
-      hash_to_key_map,
-      ..
-    } = self;
-    (last_read_sn, hash_to_key_map)
+  // Start reading `stored` changes replayed from the DurabilityStore.
+  fn replay_stored(&mut self, stored: &[CacheChange]) {
+    self.stored_read = 0;
+    self.stored_last_sn = BTreeMap::new();
+    for cc in stored {
+      let last = self
+        .stored_last_sn
+        .entry(cc.writer_guid)
+        .or_insert(cc.sequence_number);
+      *last = max(*last, cc.sequence_number);
+    }
+  }
+
+  // A restarted Writer numbers its samples again from 1, so the read pointer
+  // of each Writer that has restarted since the last read must be reset.
+  // Returns the Writers that have restarted.
+  fn follow_writer_restarts(&mut self, writer_restarts: &BTreeMap<GUID, u32>) -> Vec<GUID> {
+    let mut restarted = Vec::new();
+    if self.writer_restarts != *writer_restarts {
+      for (writer, restarts) in writer_restarts {
+        if self.writer_restarts.get(writer) != Some(restarts) {
+          self.last_read_sn.remove(writer);
+          self.stored_last_sn.remove(writer);
+          restarted.push(*writer);
+        }
+      }
+      self.writer_restarts = writer_restarts.clone();
+    }
+    restarted
+  }
+}
+
+// Is `cc` a cached copy of a change replayed from the DurabilityStore? See
+// ReadState::replay_stored.
+fn is_replayed(stored_last_sn: &BTreeMap<GUID, SequenceNumber>, cc: &CacheChange) -> bool {
+  stored_last_sn
+    .get(&cc.writer_guid)
+    .is_some_and(|last| cc.sequence_number <= *last)
+}
+
+/// When a Reliable DataReader hands received samples over to the application.
+///
+/// Samples from a DataWriter may arrive out of order, e.g. when a lost sample
+/// is repaired after the ones following it have already been received.
+/// Best-effort DataReaders always deliver samples as they arrive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeliveryOrder {
+  /// A sample is delivered only after all the preceding samples from the same
+  /// DataWriter have been received, or are known to be irrelevant. The
+  /// application sees the samples of each DataWriter in sequence number order
+  /// without gaps.
+  #[default]
+  InOrder,
+  /// Samples are delivered as soon as they are received. This avoids waiting
+  /// for repairs, but sample 5 may be delivered before sample 4.
+  AsReceived,
+}
+
+/// What a DataReader does with a state change whose StatusInfo is ambiguous.
+///
+/// A DATA submessage without a data payload announces that an instance was
+/// disposed or unregistered, and its StatusInfo inline QoS tells which.
+/// Sometimes the StatusInfo is missing, or it has neither the Disposed nor the
+/// Unregistered flag, so it says the instance is still ALIVE. Reserved
+/// StatusInfo flags are not looked at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AmbiguousStatusInfo {
+  /// Assume the worst: the instance is disposed, or unregistered if it has no
+  /// DataWriters left.
+  #[default]
+  NotAlive,
+  /// Log and drop the change. The instance state does not change.
+  Ignore,
+}
+
+/// What a DataReader does with a DATA whose SequenceNumber was already received
+/// from the same DataWriter.
+///
+/// A conforming DataWriter never reuses a SequenceNumber, so a repeated one is
+/// a retransmission and is dropped. Some implementations reuse the
+/// SequenceNumber when they resend updated data on certain topics, and
+/// accepting duplicates lets such updates through.
+///
+/// An accepted duplicate is delivered as a new sample, but it does not count as
+/// received for reliability: acknowledgements, and how far a Reliable
+/// DataReader may read in order, still go by the original samples. A Reliable
+/// DataReader reading in [`DeliveryOrder::InOrder`] does not deliver a
+/// SequenceNumber again after it has read it. Best-effort DataReaders and
+/// [`DeliveryOrder::AsReceived`] do.
+#[derive(Clone, Default)]
+pub enum DuplicateAcceptance {
+  /// Drop repeated SequenceNumbers.
+  #[default]
+  Discard,
+  /// Deliver every repeated SequenceNumber.
+  Accept,
+  /// Deliver a repeated SequenceNumber, if the predicate returns true for the
+  /// DataWriter GUID and the SequenceNumber.
+  AcceptIf(Arc<dyn Fn(GUID, SequenceNumber) -> bool + Send + Sync>),
+}
+
+impl DuplicateAcceptance {
+  pub(crate) fn accepts(&self, writer: GUID, sn: SequenceNumber) -> bool {
+    match self {
+      Self::Discard => false,
+      Self::Accept => true,
+      Self::AcceptIf(predicate) => predicate(writer, sn),
+    }
   }
 }
 
+impl fmt::Debug for DuplicateAcceptance {
+  // The predicate cannot be Debug formatted.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Discard => write!(f, "Discard"),
+      Self::Accept => write!(f, "Accept"),
+      Self::AcceptIf(_) => write!(f, "AcceptIf(..)"),
+    }
+  }
+}
+
+/// Which samples a new DataReader starts from.
+///
+/// This is a local choice of the application, unlike the Durability QoS,
+/// which also decides if DataWriters keep history for late joiners. See
+/// [`Subscriber::create_datareader_with_start`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadStart {
+  /// All samples that are available: those already cached in the
+  /// participant, and the history that DataWriters send to a late joiner.
+  #[default]
+  AllAvailable,
+  /// Only samples written after the DataReader was created. Samples cached in
+  /// the participant at creation are skipped, as is the history of each
+  /// DataWriter matched later, up to the last sample announced in its first
+  /// HEARTBEAT. A Reliable DataReader does not request that history at all.
+  ///
+  /// Discovery takes a while, so samples written before the DataReader has
+  /// matched a DataWriter may also count as history of that DataWriter.
+  FromNow,
+}
+
+// Per DataWriter, the last SequenceNumber that a `ReadStart::FromNow`
+// DataReader skips. Shared by the DataReader, which fills it in from the topic
+// cache at creation, and the Reader, which adds the DataWriters matched later.
+pub(crate) type HistoryBoundaries = Arc<Mutex<BTreeMap<GUID, SequenceNumber>>>;
+
 /// SimpleDataReaders can only do "take" semantics and does not have
 /// any deduplication or other DataSampleCache functionality.
 pub struct SimpleDataReader<D: Keyed, DA: DeserializerAdapter<D> = CDRDeserializerAdapter<D>> {
   my_subscriber: Subscriber,
+  // Shared by the DataReaders that share the Reader.
+  registration: Arc<ReaderRegistration>,
 
   my_topic: Topic,
   qos_policy: QosPolicies,
+  delivery_order: DeliveryOrder,
+  retain_raw_payloads: bool,
+  content_filter: Option<ContentFilter<D>>,
+  history_boundaries: Option<HistoryBoundaries>, // with ReadStart::FromNow
+  // Changes replayed from the DurabilityStore to this DataReader only, read
+  // before the topic cache. They are not added to the topic cache, so that
+  // other DataReaders of the Topic are not affected.
+  stored_changes: Vec<CacheChange>,
+  stored_at: Timestamp,
   my_guid: GUID,
 
   // mio_channel::Receiver is not thread-safe, so Mutex protects it.
-  pub(crate) notification_receiver: Mutex<mio_channel::Receiver<()>>,
+  // Shared with DataAvailableConditions.
+  pub(crate) notification_receiver: Arc<Mutex<mio_channel::Receiver<()>>>,
+  // Set by a DataAvailableCondition that has received a notification, cleared
+  // when the notifications are drained.
+  data_notified: Arc<AtomicBool>,
 
   // SimpleDataReader stores a pointer to a mutex on the topic cache
   topic_cache: Arc<Mutex<TopicCache>>,
@@ -103,40 +314,23 @@ pub struct SimpleDataReader<D: Keyed, DA: DeserializerAdapter<D> = CDRDeserializ
 
   deserializer_type: PhantomData<DA>, // This is to provide use for DA
 
-  discovery_command: mio_channel::SyncSender<DiscoveryCommand>,
   status_receiver: StatusChannelReceiver<DataReaderStatus>,
 
-  #[allow(dead_code)] // TODO: This is currently unused, because we do not implement
-  // resetting deadline missed status. Remove attribute when it is supported.
   reader_command: mio_channel::SyncSender<ReaderCommand>,
   data_reader_waker: Arc<Mutex<Option<Waker>>>,
+  // Liveliness of matched writers, maintained by the Reader in the event loop
+  liveliness_status: Arc<Mutex<LivelinessChangedStatus>>,
+  // Maintained by the Reader in the event loop
+  requested_deadline_missed_status: Arc<Mutex<CountWithChange>>,
+  // Read by the Reader in the event loop
+  detect_content_duplicates: Arc<AtomicBool>,
+  ignore_ambiguous_status_info: Arc<AtomicBool>,
+  duplicate_acceptance: Arc<Mutex<DuplicateAcceptance>>,
+  paused: Arc<AtomicBool>,
 
   event_source: PollEventSource,
 }
 
-impl<D, DA> Drop for SimpleDataReader<D, DA>
-where
-  D: Keyed,
-  DA: DeserializerAdapter<D>,
-{
-  fn drop(&mut self) {
-    // Tell dp_event_loop
-    self.my_subscriber.remove_reader(self.my_guid);
-
-    // Tell discovery
-    match self
-      .discovery_command
-      .send(DiscoveryCommand::RemoveLocalReader { guid: self.my_guid })
-    {
-      Ok(_) => {}
-      Err(mio_channel::SendError::Disconnected(_)) => {
-        debug!("Failed to send DiscoveryCommand::RemoveLocalReader . Maybe shutting down?");
-      }
-      Err(e) => error!("Failed to send DiscoveryCommand::RemoveLocalReader. {e:?}"),
-    }
-  }
-}
-
 impl<D: 'static, DA> SimpleDataReader<D, DA>
 where
   D: Keyed,
@@ -155,6 +349,12 @@ where
     status_receiver: StatusChannelReceiver<DataReaderStatus>,
     reader_command: mio_channel::SyncSender<ReaderCommand>,
     data_reader_waker: Arc<Mutex<Option<Waker>>>,
+    liveliness_status: Arc<Mutex<LivelinessChangedStatus>>,
+    requested_deadline_missed_status: Arc<Mutex<CountWithChange>>,
+    detect_content_duplicates: Arc<AtomicBool>,
+    ignore_ambiguous_status_info: Arc<AtomicBool>,
+    duplicate_acceptance: Arc<Mutex<DuplicateAcceptance>>,
+    paused: Arc<AtomicBool>,
     event_source: PollEventSource,
   ) -> CreateResult<Self> {
     let dp = match subscriber.participant() {
@@ -181,31 +381,121 @@ where
     }
 
     Ok(Self {
+      registration: Arc::new(ReaderRegistration {
+        subscriber: subscriber.clone(),
+        guid: my_guid,
+        discovery_command,
+      }),
       my_subscriber: subscriber,
       qos_policy,
+      delivery_order: DeliveryOrder::default(),
+      retain_raw_payloads: false,
+      content_filter: None,
+      history_boundaries: None,
+      stored_changes: Vec::new(),
+      stored_at: Timestamp::ZERO,
       my_guid,
-      notification_receiver: Mutex::new(notification_receiver),
+      notification_receiver: Arc::new(Mutex::new(notification_receiver)),
+      data_notified: Arc::new(AtomicBool::new(false)),
       topic_cache,
       read_state: Mutex::new(ReadState::new()),
       my_topic: topic,
       deserializer_type: PhantomData,
-      discovery_command,
       status_receiver,
       reader_command,
       data_reader_waker,
+      liveliness_status,
+      requested_deadline_missed_status,
+      detect_content_duplicates,
+      ignore_ambiguous_status_info,
+      duplicate_acceptance,
+      paused,
       event_source,
     })
   }
+
+  // A new DataReader that shares the Reader of this one: it gets the same
+  // samples, but reads them with its own read pointer. Read conditions and
+  // status events are its own, whereas settings applied by the Reader (pause,
+  // duplicate detection, StatusInfo handling) are shared. The Reader is
+  // removed when the last DataReader sharing it is dropped.
+  pub(crate) fn share(&self) -> CreateResult<Self> {
+    let Some(dp) = self.my_subscriber.participant() else {
+      return create_error_dropped!("DomainParticipant doesn't exist anymore.");
+    };
+    let (notification_sender, notification_receiver) = mio_channel::sync_channel::<()>(4);
+    let (status_sender, status_receiver) =
+      configured_status_channel::<DataReaderStatus>(dp.datareader_status_channel())?;
+    let data_reader_waker = Arc::new(Mutex::new(None));
+    let (event_source, poll_event_sender) = make_poll_channel()?;
+    let mut read_state = ReadState::new();
+    read_state.replay_stored(&self.stored_changes);
+
+    let notifier = DataReaderNotifier {
+      notification_sender,
+      status_sender,
+      data_reader_waker: Arc::clone(&data_reader_waker),
+      poll_event_sender,
+    };
+    if let Err(e) = self
+      .reader_command
+      .send(ReaderCommand::AttachDataReader(notifier))
+    {
+      return create_error_dropped!("Cannot attach to the Reader: {e:?}");
+    }
+
+    Ok(Self {
+      my_subscriber: self.my_subscriber.clone(),
+      registration: Arc::clone(&self.registration),
+      my_topic: self.my_topic.clone(),
+      qos_policy: self.qos_policy.clone(),
+      delivery_order: self.delivery_order,
+      retain_raw_payloads: self.retain_raw_payloads,
+      content_filter: None,
+      history_boundaries: self.history_boundaries.clone(),
+      stored_changes: self.stored_changes.clone(),
+      stored_at: self.stored_at,
+      my_guid: self.my_guid,
+      notification_receiver: Arc::new(Mutex::new(notification_receiver)),
+      data_notified: Arc::new(AtomicBool::new(false)),
+      topic_cache: Arc::clone(&self.topic_cache),
+      read_state: Mutex::new(read_state),
+      deserializer_type: PhantomData,
+      status_receiver,
+      reader_command: self.reader_command.clone(),
+      data_reader_waker,
+      liveliness_status: Arc::clone(&self.liveliness_status),
+      requested_deadline_missed_status: Arc::clone(&self.requested_deadline_missed_status),
+      detect_content_duplicates: Arc::clone(&self.detect_content_duplicates),
+      ignore_ambiguous_status_info: Arc::clone(&self.ignore_ambiguous_status_info),
+      duplicate_acceptance: Arc::clone(&self.duplicate_acceptance),
+      paused: Arc::clone(&self.paused),
+      event_source,
+    })
+  }
+
   pub(crate) fn set_waker(&self, w: Option<Waker>) {
     *self.data_reader_waker.lock().unwrap() = w;
   }
 
   pub(crate) fn drain_read_notifications(&self) {
     let rec = self.notification_receiver.lock().unwrap();
+    self.data_notified.store(false, atomic::Ordering::Release);
     while rec.try_recv().is_ok() {}
     self.event_source.drain();
   }
 
+  pub(crate) fn data_available_condition(&self) -> DataAvailableCondition {
+    DataAvailableCondition::new(
+      Arc::clone(&self.notification_receiver),
+      Arc::clone(&self.data_notified),
+    )
+  }
+
+  pub(crate) fn status_condition(&self) -> StatusCondition {
+    StatusCondition::new(self.status_receiver.peeker())
+  }
+
   fn try_take_undecoded<'a, 'b: 'a>(
     is_reliable: bool,
     topic_cache: &'a TopicCache,
@@ -217,13 +507,38 @@ where
 
   fn update_hash_to_key_map(
     hash_to_key_map: &mut BTreeMap<KeyHash, D::K>,
+    cc: &CacheChange,
     deserialized: &Sample<D, D::K>,
   ) {
     let instance_key = match deserialized {
       Sample::Value(d) => d.key(),
       Sample::Dispose(k) => k.clone(),
     };
-    hash_to_key_map.insert(instance_key.hash_key(false), instance_key);
+    Self::remember_key_hash(hash_to_key_map, cc, instance_key);
+  }
+
+  // Remember the key hash of `key`, so that a later dispose by key hash can be
+  // resolved. The key hash is computed from the big-endian CDR encoding of the
+  // key, as the writer does, regardless of the encoding of the payload. If the
+  // writer sent a key hash that differs, that one is remembered too.
+  fn remember_key_hash(hash_to_key_map: &mut BTreeMap<KeyHash, D::K>, cc: &CacheChange, key: D::K) {
+    let key_hash = key.hash_key(false);
+    if let Some(received) = cc.write_options.key_hash() {
+      // The DDS Security spec requires an MD5 key hash for encrypted payloads,
+      // whatever the size of the key.
+      if received != key_hash && received != key.hash_key(true) {
+        warn_throttled!(
+          "Key hash {:?} from writer {:?} does not match the CDR big-endian key hash {:?}",
+          received,
+          cc.writer_guid,
+          key_hash
+        );
+      }
+      if received != key_hash {
+        hash_to_key_map.insert(received, key.clone());
+      }
+    }
+    hash_to_key_map.insert(key_hash, key);
   }
 
   fn deserialize_with<S>(
@@ -240,43 +555,18 @@ where
       DDSData::Data {
         ref serialized_payload,
       } => {
-        // what is our data serialization format (representation identifier) ?
-        if let Some(recognized_rep_id) = DA::supported_encodings()
-          .iter()
-          .find(|r| **r == serialized_payload.representation_identifier)
-        {
-          match DA::from_bytes_with(&serialized_payload.value, *recognized_rep_id, decoder) {
-            // Data update, decoded ok
-            Ok(payload) => {
-              let p = Sample::Value(payload);
-              Self::update_hash_to_key_map(hash_to_key_map, &p);
-              Ok(DeserializedCacheChange::new(timestamp, cc, p))
-            }
-            Err(e) => Err(ReadError::Deserialization {
-              reason: format!(
-                "Failed to deserialize sample bytes: {}, , Topic = {}, Type = {:?}",
-                e,
-                self.my_topic.name(),
-                self.my_topic.get_type()
-              ),
-            }),
+        let recognized_rep_id = self.recognized_encoding(serialized_payload)?;
+        match DA::from_bytes_with(&serialized_payload.value, recognized_rep_id, decoder) {
+          // Data update, decoded ok
+          Ok(payload) => {
+            let p = Sample::Value(payload);
+            Self::update_hash_to_key_map(hash_to_key_map, cc, &p);
+            Ok(
+              DeserializedCacheChange::new(timestamp, cc, p)
+                .with_raw_payload(self.retain_raw_payloads.then(|| serialized_payload.clone())),
+            )
           }
-        } else {
-          info!(
-            "Unknown representation id: {:?} , Topic = {}, Type = {:?} data = {:02x?}",
-            serialized_payload.representation_identifier,
-            self.my_topic.name(),
-            self.my_topic.get_type(),
-            serialized_payload.value,
-          );
-          Err(ReadError::Deserialization {
-            reason: format!(
-              "Unknown representation id {:?} , Topic = {}, Type = {:?}",
-              serialized_payload.representation_identifier,
-              self.my_topic.name(),
-              self.my_topic.get_type()
-            ),
-          })
+          Err(e) => Err(self.sample_deserialization_error(e)),
         }
       }
 
@@ -291,8 +581,11 @@ where
         ) {
           Ok(key) => {
             let k = Sample::Dispose(key);
-            Self::update_hash_to_key_map(hash_to_key_map, &k);
-            Ok(DeserializedCacheChange::new(timestamp, cc, k))
+            Self::update_hash_to_key_map(hash_to_key_map, cc, &k);
+            Ok(
+              DeserializedCacheChange::new(timestamp, cc, k)
+                .with_raw_payload(self.retain_raw_payloads.then(|| serialized_key.clone())),
+            )
           }
           Err(e) => Err(ReadError::Deserialization {
             reason: format!(
@@ -325,9 +618,91 @@ where
           })
         }
       }
+
+      // The Reader consumes end markers, they are never cached.
+      DDSData::CoherentSetEnd => Err(ReadError::Internal {
+        reason: "Coherent set end marker in topic cache".to_string(),
+      }),
     } // match
   }
 
+  // what is our data serialization format (representation identifier) ?
+  fn recognized_encoding(
+    &self,
+    serialized_payload: &SerializedPayload,
+  ) -> ReadResult<RepresentationIdentifier> {
+    if let Some(recognized_rep_id) = DA::supported_encodings()
+      .iter()
+      .find(|r| **r == serialized_payload.representation_identifier)
+    {
+      Ok(*recognized_rep_id)
+    } else {
+      info!(
+        "Unknown representation id: {:?} , Topic = {}, Type = {:?} data = {:02x?}",
+        serialized_payload.representation_identifier,
+        self.my_topic.name(),
+        self.my_topic.get_type(),
+        serialized_payload.value,
+      );
+      Err(ReadError::Deserialization {
+        reason: format!(
+          "Unknown representation id {:?} , Topic = {}, Type = {:?}",
+          serialized_payload.representation_identifier,
+          self.my_topic.name(),
+          self.my_topic.get_type()
+        ),
+      })
+    }
+  }
+
+  fn sample_deserialization_error(&self, e: impl std::fmt::Display) -> ReadError {
+    ReadError::Deserialization {
+      reason: format!(
+        "Failed to deserialize sample bytes: {}, , Topic = {}, Type = {:?}",
+        e,
+        self.my_topic.name(),
+        self.my_topic.get_type()
+      ),
+    }
+  }
+
+  // As deserialize_with, but a data sample is deserialized into `target`.
+  fn deserialize_into_with<S>(
+    &self,
+    timestamp: Timestamp,
+    cc: &CacheChange,
+    hash_to_key_map: &mut BTreeMap<KeyHash, D::K>,
+    decoder: S,
+    target: &mut D,
+  ) -> ReadResult<DeserializedIntoChange<D::K>>
+  where
+    S: for<'de> Decode<'de, DA::Decoded, DA::DecodedKey, Error = DA::Error>,
+  {
+    let DDSData::Data {
+      ref serialized_payload,
+    } = cc.data_value
+    else {
+      // Disposals carry only a key, so there is nothing to reuse.
+      let dcc = self.deserialize_with(timestamp, cc, hash_to_key_map, decoder)?;
+      let key = match dcc.sample {
+        Sample::Value(d) => d.key(), // not produced from a disposal
+        Sample::Dispose(k) => k,
+      };
+      return Ok(DeserializedIntoChange::new(timestamp, cc, key));
+    };
+    let recognized_rep_id = self.recognized_encoding(serialized_payload)?;
+    DA::from_bytes_into_with(
+      &serialized_payload.value,
+      recognized_rep_id,
+      decoder,
+      target,
+    )
+    .map_err(|e| self.sample_deserialization_error(e))?;
+    let key = target.key();
+    Self::remember_key_hash(hash_to_key_map, cc, key.clone());
+    Ok(DeserializedIntoChange::new(timestamp, cc, key))
+  }
+
   /// Note: Always remember to call .drain_read_notifications() just before
   /// calling this one. Otherwise, new notifications may not appear.
   pub fn try_take_one(&self) -> ReadResult<Option<DeserializedCacheChange<D>>>
@@ -344,72 +719,312 @@ where
   where
     S: for<'de> Decode<'de, DA::Decoded, DA::DecodedKey, Error = DA::Error> + Clone,
   {
+    self.try_take_next(|timestamp, cc, hash_to_key_map| {
+      let dcc = self.deserialize_with(timestamp, cc, hash_to_key_map, decoder.clone())?;
+      Ok(self.passes_content_filter(&dcc.sample).then_some(dcc))
+    })
+  }
+
+  /// Like [`try_take_one`](Self::try_take_one), but a data sample is
+  /// deserialized into `target`, reusing its allocations if the
+  /// DeserializerAdapter supports that. A disposal leaves `target` unchanged.
+  ///
+  /// Note: Always remember to call .drain_read_notifications() just before
+  /// calling this one. Otherwise, new notifications may not appear.
+  pub(crate) fn try_take_one_into(
+    &self,
+    target: &mut D,
+  ) -> ReadResult<Option<DeserializedIntoChange<D::K>>>
+  where
+    DA: DeserializerAdapter<D> + DefaultDecoder<D>,
+  {
+    if self.content_filter.is_none() {
+      return self.try_take_next(|timestamp, cc, hash_to_key_map| {
+        self
+          .deserialize_into_with(timestamp, cc, hash_to_key_map, DA::DECODER, target)
+          .map(Some)
+      });
+    }
+    // Filtered out samples must not overwrite `target`, so the allocations
+    // of `target` cannot be reused.
+    self.try_take_next(|timestamp, cc, hash_to_key_map| {
+      let dcc = self.deserialize_with(timestamp, cc, hash_to_key_map, DA::DECODER)?;
+      if !self.passes_content_filter(&dcc.sample) {
+        return Ok(None);
+      }
+      Ok(Some(match dcc.sample {
+        Sample::Value(value) => {
+          let key = value.key();
+          *target = value;
+          DeserializedIntoChange::new(timestamp, cc, key)
+        }
+        Sample::Dispose(key) => DeserializedIntoChange::new(timestamp, cc, key),
+      }))
+    })
+  }
+
+  fn passes_content_filter(&self, sample: &Sample<D, D::K>) -> bool {
+    match (sample, &self.content_filter) {
+      (Sample::Value(value), Some(filter)) => filter(value),
+      // Disposals pass, so that instance states stay correct.
+      _ => true,
+    }
+  }
+
+  // Take the next change from the topic cache and decode it with `decode`.
+  // Changes that `decode` filters out, by returning `None`, are skipped.
+  fn try_take_next<R>(
+    &self,
+    mut decode: impl FnMut(
+      Timestamp,
+      &CacheChange,
+      &mut BTreeMap<KeyHash, D::K>,
+    ) -> ReadResult<Option<R>>,
+  ) -> ReadResult<Option<R>> {
     let is_reliable = matches!(
       self.qos_policy.reliability(),
       Some(policy::Reliability::Reliable { .. })
-    );
+    ) && self.delivery_order == DeliveryOrder::InOrder;
 
     let topic_cache = self.acquire_the_topic_cache_guard();
 
     let mut read_state_ref = self.read_state.lock().unwrap();
-    let latest_instant = read_state_ref.latest_instant;
-    let (last_read_sn, hash_to_key_map) = read_state_ref.get_sn_map_and_hash_map();
+    let restarted = read_state_ref.follow_writer_restarts(topic_cache.writer_restarts());
+    if let Some(boundaries) = &self.history_boundaries {
+      // Everything a restarted Writer sends was written after we started.
+      let mut boundaries = boundaries.lock().unwrap();
+      for writer in restarted {
+        boundaries.insert(writer, SequenceNumber::zero());
+      }
+    }
+
+    // Changes replayed from the DurabilityStore come first.
+    while let Some(cc) = self.stored_changes.get(read_state_ref.stored_read) {
+      read_state_ref.stored_read += 1;
+      let last_read = read_state_ref
+        .last_read_sn
+        .entry(cc.writer_guid)
+        .or_insert(cc.sequence_number);
+      *last_read = max(*last_read, cc.sequence_number);
+      match decode(self.stored_at, cc, &mut read_state_ref.hash_to_key_map) {
+        // unknown key hash or filtered out, try next
+        Err(ReadError::UnknownKey { .. }) | Ok(None) => {}
+        result => return result,
+      }
+    }
+
+    let ReadState {
+      latest_instant,
+      last_read_sn,
+      stored_last_sn,
+      hash_to_key_map,
+      ..
+    } = &mut *read_state_ref;
 
     let mut changes =
-      Self::try_take_undecoded(is_reliable, &topic_cache, latest_instant, last_read_sn);
+      Self::try_take_undecoded(is_reliable, &topic_cache, *latest_instant, last_read_sn);
 
+    // Changes that have been read, and so must not be read again.
+    let mut consumed = Vec::new();
     // loop in case we get a sample that should be ignored, so we try next.
-    loop {
+    let result = loop {
       let (timestamp, cc) = match changes.next() {
-        None => return Ok(None), // no more data available right now
+        None => break Ok(None), // no more data available right now
         Some((ts, cc)) => (ts, cc),
       };
 
-      let result = self.deserialize_with(timestamp, cc, hash_to_key_map, decoder.clone());
-
-      if let Err(ReadError::UnknownKey { .. }) = result {
+      if self.is_skipped_history(cc) || is_replayed(stored_last_sn, cc) {
+        consumed.push((timestamp, cc.writer_guid, cc.sequence_number));
+        continue;
+      }
+      match decode(timestamp, cc, hash_to_key_map) {
         // ignore unknown key hash, continue looping
-      } else {
-        // explicitly drop the cache changes to update the reader state
-        drop(changes);
-
-        // return with this result
-        // make copies of guid and SN to calm down borrow checker.
-        let writer_guid = cc.writer_guid;
-        let sequence_number = cc.sequence_number;
-        // Advance read pointer, error or not, because otherwise
-        // the SimpleDatareader is stuck.
-        read_state_ref.latest_instant = max(latest_instant, timestamp);
-        read_state_ref
-          .last_read_sn
-          .insert(writer_guid, sequence_number);
-
-        // // Debug sanity check:
-        // use crate::Duration;
-        // if Timestamp::now().duration_since(timestamp) > Duration::from_secs(1) {
-        //   error!("Sample delayed by {:?} , Topic = {} {:?}",
-        //     Timestamp::now().duration_since(timestamp), self.topic().name(),
-        //     sequence_number,
-        //      );
-        // }
-
-        return result.map(Some);
+        Err(ReadError::UnknownKey { .. }) => {}
+        // filtered out, continue looping
+        Ok(None) => consumed.push((timestamp, cc.writer_guid, cc.sequence_number)),
+        result => {
+          // Advance read pointer, error or not, because otherwise
+          // the SimpleDatareader is stuck.
+          consumed.push((timestamp, cc.writer_guid, cc.sequence_number));
+          break result;
+        }
       }
+    };
+    // explicitly drop the cache changes to update the reader state
+    drop(changes);
+
+    for (timestamp, writer_guid, sequence_number) in consumed {
+      read_state_ref.latest_instant = max(read_state_ref.latest_instant, timestamp);
+      read_state_ref
+        .last_read_sn
+        .insert(writer_guid, sequence_number);
     }
+    result
   }
 
   pub fn qos(&self) -> &QosPolicies {
     &self.qos_policy
   }
 
+  pub fn delivery_order(&self) -> DeliveryOrder {
+    self.delivery_order
+  }
+
+  /// Choose when received samples are delivered. See [`DeliveryOrder`].
+  ///
+  /// This is best set before reading any samples. Switching from
+  /// [`DeliveryOrder::AsReceived`] to [`DeliveryOrder::InOrder`] skips the
+  /// samples that were still missing when the last sample was read.
+  pub fn set_delivery_order(&mut self, delivery_order: DeliveryOrder) {
+    self.delivery_order = delivery_order;
+  }
+
+  // Keep the serialized payload with each decoded sample. See
+  // DataReader::set_retain_raw_payloads.
+  pub(crate) fn set_retain_raw_payloads(&mut self, retain: bool) {
+    self.retain_raw_payloads = retain;
+  }
+
+  // Deliver only data samples that pass `filter`. Disposals are always
+  // delivered. See Subscriber::create_content_filtered_datareader.
+  pub(crate) fn set_content_filter(&mut self, filter: ContentFilter<D>) {
+    self.content_filter = Some(filter);
+  }
+
+  // Deliver `changes` replayed from the DurabilityStore before the cached
+  // ones, and skip the cached copies of them.
+  pub(crate) fn set_stored_changes(&mut self, changes: Vec<CacheChange>) {
+    self.read_state.lock().unwrap().replay_stored(&changes);
+    self.stored_changes = changes;
+    self.stored_at = Timestamp::now();
+  }
+
+  // Skip the samples cached so far, and the history of DataWriters matched
+  // later. See ReadStart::FromNow.
+  pub(crate) fn start_from_now(&mut self, boundaries: HistoryBoundaries) {
+    let topic_cache = self.acquire_the_topic_cache_guard();
+    let last_sequence_numbers = topic_cache.last_sequence_numbers();
+    let mut read_state = self.read_state.lock().unwrap();
+    read_state.latest_instant = topic_cache.last_added_instant();
+    read_state.writer_restarts = topic_cache.writer_restarts().clone();
+    read_state.last_read_sn = last_sequence_numbers.clone();
+    drop(read_state);
+    drop(topic_cache);
+    *boundaries.lock().unwrap() = last_sequence_numbers;
+    self.history_boundaries = Some(boundaries);
+  }
+
+  // Is this sample history that a ReadStart::FromNow DataReader skips?
+  fn is_skipped_history(&self, cc: &CacheChange) -> bool {
+    self.history_boundaries.as_ref().is_some_and(|boundaries| {
+      boundaries
+        .lock()
+        .unwrap()
+        .get(&cc.writer_guid)
+        .is_some_and(|last_skipped| cc.sequence_number <= *last_skipped)
+    })
+  }
+
+  // Flag samples whose payload repeats the previous one of their instance. See
+  // DataReader::set_detect_content_duplicates.
+  pub(crate) fn set_detect_content_duplicates(&self, enabled: bool) {
+    self
+      .detect_content_duplicates
+      .store(enabled, atomic::Ordering::Relaxed);
+  }
+
+  // See DataReader::set_ambiguous_status_info.
+  pub(crate) fn set_ambiguous_status_info(&self, handling: AmbiguousStatusInfo) {
+    self.ignore_ambiguous_status_info.store(
+      handling == AmbiguousStatusInfo::Ignore,
+      atomic::Ordering::Relaxed,
+    );
+  }
+
+  // See DataReader::set_heartbeat_suppression.
+  pub(crate) fn set_heartbeat_suppression(&self, duration: std::time::Duration) {
+    let command = ReaderCommand::SetHeartbeatSuppression(duration);
+    self.reader_command.try_send(command).unwrap_or_else(|e| {
+      error!("Cannot set heartbeat suppression: {e:?}");
+    });
+  }
+
+  // See DataReader::set_duplicate_acceptance.
+  pub(crate) fn set_duplicate_acceptance(&self, acceptance: DuplicateAcceptance) {
+    *self.duplicate_acceptance.lock().unwrap() = acceptance;
+  }
+
+  // Stop or restart taking in received samples. See DataReader::pause.
+  pub(crate) fn set_paused(&self, paused: bool) {
+    self.paused.store(paused, atomic::Ordering::Relaxed);
+  }
+
+  pub(crate) fn is_paused(&self) -> bool {
+    self.paused.load(atomic::Ordering::Relaxed)
+  }
+
   pub fn guid(&self) -> GUID {
     self.my_guid
   }
 
+  /// Samples in the topic cache that this DataReader reads from. See
+  /// [`DataReader::cache_occupancy`](crate::with_key::DataReader::cache_occupancy).
+  pub fn cache_occupancy(&self) -> CacheOccupancy {
+    self.acquire_the_topic_cache_guard().occupancy()
+  }
+
+  /// Remove samples received before `timestamp` from the topic cache. See
+  /// [`DataReader::purge_before`](crate::with_key::DataReader::purge_before).
+  pub fn purge_before(&self, timestamp: Timestamp) -> usize {
+    let reliable = matches!(
+      self.qos_policy.reliability(),
+      Some(policy::Reliability::Reliable { .. })
+    );
+    self
+      .acquire_the_topic_cache_guard()
+      .purge_before(timestamp, reliable)
+  }
+
   pub fn topic(&self) -> &Topic {
     &self.my_topic
   }
 
+  /// Current liveliness of the matched DataWriters, including which ones are
+  /// alive and which are not. See [`LivelinessChangedStatus`].
+  pub fn liveliness_changed_status(&self) -> LivelinessChangedStatus {
+    self
+      .liveliness_status
+      .lock()
+      .map(|status| status.clone())
+      .unwrap_or_default()
+  }
+
+  /// The REQUESTED_DEADLINE_MISSED status: how many times the DEADLINE QoS has
+  /// been missed in total, and how many times since the status was last read.
+  ///
+  /// Reading the status resets the change to zero.
+  pub fn requested_deadline_missed_status(&self) -> CountWithChange {
+    let mut shared = match self.requested_deadline_missed_status.lock() {
+      Ok(shared) => shared,
+      Err(e) => {
+        error!("Cannot read requested deadline missed status: {e:?}");
+        return CountWithChange::default();
+      }
+    };
+    let status = *shared;
+    if status.count_change() != 0 {
+      *shared = CountWithChange::new(status.count(), 0);
+      // The Reader keeps counting the change from what was read here.
+      let command = ReaderCommand::ResetRequestedDeadlineStatus {
+        read_count: status.count(),
+      };
+      self.reader_command.try_send(command).unwrap_or_else(|e| {
+        error!("Cannot reset requested deadline missed status: {e:?}");
+      });
+    }
+    status
+  }
+
   pub fn as_async_stream<S>(&self) -> SimpleDataReaderStream<'_, D, S, DA>
   where
     DA: DefaultDecoder<D, Decoder = S>,
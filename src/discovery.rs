@@ -3,6 +3,8 @@ pub(crate) mod content_filter_property;
 #[allow(clippy::module_inception)]
 pub(crate) mod discovery;
 pub(crate) mod discovery_db;
+pub(crate) mod discovery_dump;
+pub(crate) mod in_process;
 
 #[cfg(feature = "security")]
 pub(crate) mod secure_discovery;
@@ -10,5 +12,6 @@ pub(crate) mod secure_discovery;
 pub(crate) mod sedp_messages;
 pub(crate) mod spdp_participant_data;
 
+pub use discovery_dump::*;
 pub use sedp_messages::*;
 pub use spdp_participant_data::*;
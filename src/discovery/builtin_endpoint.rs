@@ -50,6 +50,14 @@ impl BuiltinEndpointSet {
   pub fn contains(&self, other: u32) -> bool {
     (self.value & other) == other
   }
+
+  /// Keep only the endpoints in `mask`.
+  #[must_use]
+  pub fn intersection(self, mask: u32) -> Self {
+    Self {
+      value: self.value & mask,
+    }
+  }
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Readable, Writable)]
@@ -24,7 +24,9 @@ use crate::{
     statusevents::{DomainParticipantStatusEvent, LostReason, StatusChannelSender},
   },
   discovery::{
+    builtin_endpoint::BuiltinEndpointSet,
     discovery_db::{discovery_db_read, discovery_db_write, DiscoveredVia, DiscoveryDB},
+    in_process::{InProcessAnnouncement, InProcessRegistration},
     sedp_messages::{
       DiscoveredReaderData, DiscoveredTopicData, DiscoveredWriterData, Endpoint_GUID,
       ParticipantMessageData, ParticipantMessageDataKind,
@@ -93,6 +95,7 @@ pub enum DiscoveryCommand {
     guid: GUID,
   },
   ManualAssertLiveliness,
+  RefreshLocators,
   AssertTopicLiveliness {
     writer_guid: GUID,
     manual_assertion: bool,
@@ -197,6 +200,52 @@ enum DiscoveryTimerEvent {
   CachedSecureMessageResend,
 }
 
+// Is a remote participant with `prefix` admitted under the tenant filter?
+// Participants from other vendors cannot carry a tenant id, so they never
+// match an active filter.
+fn tenant_matches(tenant_filter: Option<u16>, prefix: GuidPrefix) -> bool {
+  tenant_filter.is_none_or(|tenant| prefix.tenant_id() == Some(tenant))
+}
+
+// In lightweight discovery, participants are known only through SPDP. Hiding
+// the other built-in endpoints of a remote participant keeps them from being
+// matched with ours, and hiding ours keeps remote participants from matching
+// theirs with us.
+fn lightweight_participant_data(
+  participant_data: &SpdpDiscoveredParticipantData,
+) -> SpdpDiscoveredParticipantData {
+  SpdpDiscoveredParticipantData {
+    available_builtin_endpoints: participant_data.available_builtin_endpoints.intersection(
+      BuiltinEndpointSet::PARTICIPANT_ANNOUNCER | BuiltinEndpointSet::PARTICIPANT_DETECTOR,
+    ),
+    ..participant_data.clone()
+  }
+}
+
+// A read-only participant has no DataWriters, so its built-in writers of
+// publications, topics and participant messages have nothing to send. They
+// are hidden from remote participants, and the remote built-in readers that
+// they would be matched with are hidden from us. See
+// `DomainParticipantBuilder::read_only`.
+const READ_ONLY_IDLE_WRITERS: u32 = BuiltinEndpointSet::PUBLICATIONS_ANNOUNCER
+  | BuiltinEndpointSet::TOPICS_ANNOUNCER
+  | BuiltinEndpointSet::PARTICIPANT_MESSAGE_DATA_WRITER;
+const READ_ONLY_IDLE_REMOTE_READERS: u32 = BuiltinEndpointSet::PUBLICATIONS_DETECTOR
+  | BuiltinEndpointSet::TOPICS_DETECTOR
+  | BuiltinEndpointSet::PARTICIPANT_MESSAGE_DATA_READER;
+
+fn read_only_participant_data(
+  participant_data: &SpdpDiscoveredParticipantData,
+  hidden_endpoints: u32,
+) -> SpdpDiscoveredParticipantData {
+  SpdpDiscoveredParticipantData {
+    available_builtin_endpoints: participant_data
+      .available_builtin_endpoints
+      .intersection(!hidden_endpoints),
+    ..participant_data.clone()
+  }
+}
+
 // Enum indicating if secure discovery allows normal discovery to process
 // something
 #[derive(PartialEq)]
@@ -221,6 +270,19 @@ pub(crate) struct Discovery {
 
   liveliness_state: LivelinessState,
 
+  // If set, only remote participants carrying this tenant id in their GUID
+  // prefix are admitted to the Discovery DB.
+  tenant_filter: Option<u16>,
+
+  // Present if in-process discovery is enabled. SPDP announcements are
+  // exchanged with the other registered participants of this process via this.
+  in_process: Option<InProcessRegistration>,
+
+  // If true, only participants are discovered. See `lightweight_participant_data`.
+  lightweight: bool,
+  // If true, we never publish. See `read_only_participant_data`.
+  read_only: bool,
+
   participant_status_sender: StatusChannelSender<DomainParticipantStatusEvent>,
 
   // DDS Subscriber and Publisher for Discovery
@@ -311,6 +373,10 @@ impl Discovery {
     spdp_liveness_receiver: mio_channel::Receiver<GuidPrefix>,
     participant_status_sender: StatusChannelSender<DomainParticipantStatusEvent>,
     security_plugins_opt: Option<SecurityPluginsHandle>,
+    tenant_filter: Option<u16>,
+    in_process_discovery: bool,
+    lightweight: bool,
+    read_only: bool,
   ) -> CreateResult<Self> {
     // helper macro to handle initialization failures.
     macro_rules! try_construct {
@@ -434,6 +500,25 @@ impl Discovery {
       "Failed to register Discovery poll."
     );
 
+    let in_process = if in_process_discovery {
+      let registration = InProcessRegistration::register(
+        domain_participant.domain_id(),
+        domain_participant.guid().prefix,
+      );
+      try_construct!(
+        poll.register(
+          registration.receiver(),
+          IN_PROCESS_DISCOVERY_TOKEN,
+          Ready::readable(),
+          PollOpt::edge(),
+        ),
+        "Failed to register Discovery poll."
+      );
+      Some(registration)
+    } else {
+      None
+    };
+
     // Participant
     let dcps_participant = construct_topic_and_poll!(
       PlCdr,
@@ -675,6 +760,10 @@ impl Discovery {
       participant_status_sender,
 
       liveliness_state: LivelinessState::new(),
+      tenant_filter,
+      in_process,
+      lightweight,
+      read_only,
 
       // discovery_subscriber,
       // discovery_publisher,
@@ -707,6 +796,14 @@ impl Discovery {
   pub fn discovery_event_loop(&mut self) {
     self.initialize_participant();
 
+    // Let the other participants of this process know about us right away,
+    // instead of at the first SPDP announcement.
+    if self.in_process.is_some() {
+      if let Some(dp) = self.domain_participant.clone().upgrade() {
+        self.spdp_publish(&dp);
+      }
+    }
+
     // Send out info about user (=non-built-in) Writers and Readers that we have.
     // We are just initializing local participant, so likely there are none yet,
     // but better make sure.
@@ -784,6 +881,16 @@ impl Discovery {
                     .liveliness_state
                     .manual_participant_liveness_refresh_requested = true;
                 }
+                DiscoveryCommand::RefreshLocators => {
+                  if let Some(dp) = self.domain_participant.clone().upgrade() {
+                    self.refresh_local_locators(&dp);
+                  } else {
+                    // It is being dropped, and the stop signal is on its way.
+                    info!("DomainParticipant doesn't exist anymore, exiting Discovery.");
+                    self.on_participant_shutting_down();
+                    return;
+                  }
+                }
                 DiscoveryCommand::AssertTopicLiveliness {
                   writer_guid,
                   manual_assertion,
@@ -847,7 +954,9 @@ impl Discovery {
                   if let Some(dp) = self.domain_participant.clone().upgrade() {
                     self.spdp_publish(&dp);
                   } else {
-                    error!("DomainParticipant doesn't exist anymore, exiting Discovery.");
+                    // It is being dropped, and the stop signal is on its way.
+                    info!("DomainParticipant doesn't exist anymore, exiting Discovery.");
+                    self.on_participant_shutting_down();
                     return;
                   };
                   // Only the periodic announce reschedules itself; the one-shot
@@ -908,6 +1017,9 @@ impl Discovery {
               discovery_db_write(&self.discovery_db).participant_is_alive(guid_prefix);
             }
           }
+          IN_PROCESS_DISCOVERY_TOKEN => {
+            self.in_process_receive();
+          }
           P2P_PARTICIPANT_STATELESS_MESSAGE_TOKEN => {
             #[cfg(feature = "security")]
             self.receive_participant_stateless_message();
@@ -968,6 +1080,38 @@ impl Discovery {
     });
   }
 
+  // Our locators have changed. Update our own participant and endpoints in
+  // Discovery DB, and announce the new locators without waiting for the next
+  // periodic announcement.
+  fn refresh_local_locators(&self, dp: &DomainParticipant) {
+    self.initialize_participant();
+
+    let self_locators = dp.self_locators();
+    let unicast_locators = self_locators
+      .get(&USER_TRAFFIC_LISTENER_TOKEN)
+      .cloned()
+      .unwrap_or_default();
+    let multicast_locators = self_locators
+      .get(&USER_TRAFFIC_MUL_LISTENER_TOKEN)
+      .cloned()
+      .unwrap_or_default();
+
+    let mut db = discovery_db_write(&self.discovery_db);
+    db.update_local_endpoint_locators(&unicast_locators, &multicast_locators);
+    drop(db);
+
+    let db = discovery_db_read(&self.discovery_db);
+    for reader in db.get_all_local_topic_readers() {
+      self.sedp_publish_single_user_reader(reader);
+    }
+    for writer in db.get_all_local_topic_writers() {
+      self.sedp_publish_single_user_writer(writer);
+    }
+    drop(db);
+
+    self.spdp_publish(dp);
+  }
+
   pub fn spdp_receive(&mut self) {
     loop {
       let s = self.dcps_participant.reader.take_next_sample();
@@ -1046,6 +1190,26 @@ impl Discovery {
     &mut self,
     participant_data: &SpdpDiscoveredParticipantData,
   ) {
+    let remote_prefix = participant_data.participant_guid.prefix;
+    if !tenant_matches(self.tenant_filter, remote_prefix) {
+      trace!(
+        "Ignoring participant {remote_prefix:?} from another tenant (ours is {:?})",
+        self.tenant_filter
+      );
+      return;
+    }
+    let is_remote = remote_prefix != self.domain_participant.guid().prefix;
+    let masked_data = if is_remote && self.lightweight {
+      Some(lightweight_participant_data(participant_data))
+    } else if is_remote && self.read_only {
+      Some(read_only_participant_data(
+        participant_data,
+        READ_ONLY_IDLE_REMOTE_READERS,
+      ))
+    } else {
+      None
+    };
+    let participant_data = masked_data.as_ref().unwrap_or(participant_data);
     let was_new = discovery_db_write(&self.discovery_db).update_participant(participant_data);
     let guid_prefix = participant_data.participant_guid.prefix;
 
@@ -1088,7 +1252,37 @@ impl Discovery {
     }
   }
 
+  // SPDP announcements from participants of this process. These bypass the
+  // transport, but are otherwise processed like received SPDP samples.
+  // In-process discovery cannot be combined with security, so there is no
+  // authentication to do here.
+  fn in_process_receive(&mut self) {
+    let announcements: Vec<InProcessAnnouncement> = match self.in_process.as_ref() {
+      Some(registration) => {
+        std::iter::from_fn(|| registration.receiver().try_recv().ok()).collect()
+      }
+      None => return,
+    };
+    for announcement in announcements {
+      match announcement {
+        InProcessAnnouncement::Alive(participant_data) => {
+          debug!(
+            "in_process_receive discovered {:?}",
+            participant_data.participant_guid
+          );
+          self.process_discovered_participant_data(&participant_data);
+        }
+        InProcessAnnouncement::Gone(guid_prefix) => {
+          self.process_participant_dispose(guid_prefix);
+        }
+      }
+    }
+  }
+
   fn process_participant_dispose(&mut self, participant_guidp: GuidPrefix) {
+    if !tenant_matches(self.tenant_filter, participant_guidp) {
+      return;
+    }
     discovery_db_write(&self.discovery_db).remove_participant(participant_guidp, true); // true = actively removed
     self.send_discovery_notification(DiscoveryNotificationType::ParticipantLost {
       guid_prefix: participant_guidp,
@@ -1147,6 +1341,11 @@ impl Discovery {
       .writer
       .dispose(&Participant_GUID(self.domain_participant.guid()), None)
       .unwrap_or(());
+    if let Some(registration) = self.in_process.take() {
+      registration.announce(&InProcessAnnouncement::Gone(
+        self.domain_participant.guid().prefix,
+      ));
+    }
     #[cfg(feature = "security")]
     self
       .dcps_participant_secure
@@ -1157,10 +1356,18 @@ impl Discovery {
 
   // Check if there are messages about new Readers
   pub fn sedp_receive_subscription(&mut self, read_history: Option<GuidPrefix>) {
+    let tenant_filter = self.tenant_filter;
     let drds: Vec<Sample<DiscoveredReaderData, GUID>> =
       match self.dcps_subscription.reader.into_iterator() {
         Ok(ds) => ds
           .map(|d| d.map_dispose(|g| g.0)) // map_dispose removes Endpoint_GUID wrapper around GUID
+          .filter(|d| {
+            let prefix = match d {
+              Sample::Value(drd) => drd.reader_proxy.remote_reader_guid.prefix,
+              Sample::Dispose(guid) => guid.prefix,
+            };
+            tenant_matches(tenant_filter, prefix)
+          })
           .filter(|d|
               // If a participant was specified, we must match its GUID prefix.
               match (read_history, d) {
@@ -1227,6 +1434,7 @@ impl Discovery {
   }
 
   pub fn sedp_receive_publication(&mut self, read_history: Option<GuidPrefix>) {
+    let tenant_filter = self.tenant_filter;
     let dwds: Vec<Sample<DiscoveredWriterData, GUID>> =
       match self.dcps_publication.reader.into_iterator() {
         // a lot of cloning here, but we must copy the data out of the
@@ -1234,6 +1442,13 @@ impl Discovery {
         // a reader and thus self
         Ok(ds) => ds
           .map(|d| d.map_dispose(|g| g.0)) // map_dispose removes Endpoint_GUID wrapper around GUID
+          .filter(|d| {
+            let prefix = match d {
+              Sample::Value(dwd) => dwd.writer_proxy.remote_writer_guid.prefix,
+              Sample::Dispose(guid) => guid.prefix,
+            };
+            tenant_matches(tenant_filter, prefix)
+          })
           // If a participant was specified, we must match its GUID prefix.
           .filter(|d| match (read_history, d) {
             (None, _) => true, // Not asked to filter by participant
@@ -1417,23 +1632,42 @@ impl Discovery {
     let mut db = discovery_db_write(&self.discovery_db);
     for msg in msgs {
       db.update_lease_duration(&msg);
+      let manual_assertion = match msg.kind {
+        ParticipantMessageDataKind::AUTOMATIC_LIVELINESS_UPDATE => false,
+        ParticipantMessageDataKind::MANUAL_LIVELINESS_UPDATE => true,
+        _ => continue, // not a liveliness message
+      };
+      self.send_discovery_notification(DiscoveryNotificationType::ParticipantLivelinessAsserted {
+        guid_prefix: msg.guid,
+        manual_assertion,
+      });
     }
   }
 
   fn spdp_publish(&self, local_dp: &DomainParticipant) {
     // setting 5 times the duration so lease doesn't break if update fails once or
     // twice
-    let data = SpdpDiscoveredParticipantData::from_local_participant(
+    let mut data = SpdpDiscoveredParticipantData::from_local_participant(
       local_dp,
       &self.security_opt,
       5.0 * Duration::from(Self::SPDP_PUBLISH_PERIOD),
     );
+    if self.lightweight {
+      data = lightweight_participant_data(&data);
+    }
+    if self.read_only {
+      data = read_only_participant_data(&data, READ_ONLY_IDLE_WRITERS);
+    }
 
     #[cfg(feature = "security")]
     if let Some(security) = self.security_opt.as_ref() {
       security.secure_spdp_publish(&self.dcps_participant_secure.writer, data.clone());
     }
 
+    if let Some(registration) = self.in_process.as_ref() {
+      registration.announce(&InProcessAnnouncement::Alive(Box::new(data.clone())));
+    }
+
     self
       .dcps_participant
       .writer
@@ -1929,10 +2163,9 @@ impl Discovery {
 
   pub fn sedp_publish_topic(&self, topic_name: &str) {
     let db = discovery_db_read(&self.discovery_db);
-    // We might have multiple topics with the same name (but different Qos etc..),
-    // and the following call gets just one of them. Should we publish all of
-    // them or is this enough?
-    let topic_data = match db.get_topic(topic_name) {
+    // Publish only our own definition of the topic. Others may have defined the
+    // topic with different QoS, but those are for them to publish.
+    let topic_data = match db.get_local_topic(topic_name) {
       Some(data) => data,
       None => {
         warn!("Did not find topic data with topic name {topic_name}");
@@ -2136,28 +2369,117 @@ mod tests {
     dds::adapters::no_key::DeserializerAdapter,
     discovery::sedp_messages::TopicBuiltinTopicData,
     messages::submessages::submessages::{InterpreterSubmessage, WriterSubmessage},
-    network::{constant::*, udp_listener::UDPListener, udp_sender::UDPSender},
+    network::{
+      constant::*, transport::TransportListener, udp_listener::UDPListener, udp_sender::UDPSender,
+    },
     rtps::submessage::*,
     test::{
       shape_type::ShapeType,
       test_data::{
-        create_cdr_pl_rtps_data_message, spdp_participant_msg_mod, spdp_publication_msg,
-        spdp_subscription_msg,
+        create_cdr_pl_rtps_data_message, spdp_participant_data, spdp_participant_msg_mod,
+        spdp_publication_msg, spdp_subscription_msg,
       },
     },
     RepresentationIdentifier,
   };
 
+  #[test]
+  fn tenant_filter_test() {
+    let ours = GuidPrefix::random_for_tenant(7);
+    let other_tenant = GuidPrefix::random_for_tenant(8);
+    let other_vendor = GuidPrefix::new(b"FakeTestGUID");
+
+    assert!(tenant_matches(Some(7), ours));
+    assert!(!tenant_matches(Some(7), other_tenant));
+    assert!(!tenant_matches(Some(7), other_vendor));
+    // No filter: everyone is admitted.
+    assert!(tenant_matches(None, other_tenant));
+    assert!(tenant_matches(None, other_vendor));
+  }
+
+  #[test]
+  fn lightweight_participant_data_test() {
+    let data = spdp_participant_data().unwrap();
+    assert!(data
+      .available_builtin_endpoints
+      .contains(BuiltinEndpointSet::PUBLICATIONS_DETECTOR));
+
+    let lightweight = lightweight_participant_data(&data);
+    let endpoints = lightweight.available_builtin_endpoints;
+    assert!(endpoints.contains(
+      BuiltinEndpointSet::PARTICIPANT_ANNOUNCER | BuiltinEndpointSet::PARTICIPANT_DETECTOR
+    ));
+    for sedp_or_liveliness in [
+      BuiltinEndpointSet::PUBLICATIONS_ANNOUNCER,
+      BuiltinEndpointSet::PUBLICATIONS_DETECTOR,
+      BuiltinEndpointSet::SUBSCRIPTIONS_ANNOUNCER,
+      BuiltinEndpointSet::SUBSCRIPTIONS_DETECTOR,
+      BuiltinEndpointSet::PARTICIPANT_MESSAGE_DATA_WRITER,
+      BuiltinEndpointSet::PARTICIPANT_MESSAGE_DATA_READER,
+      BuiltinEndpointSet::TOPICS_ANNOUNCER,
+      BuiltinEndpointSet::TOPICS_DETECTOR,
+    ] {
+      assert!(!endpoints.contains(sedp_or_liveliness));
+    }
+    assert_eq!(lightweight.participant_guid, data.participant_guid);
+    assert_eq!(
+      lightweight.metatraffic_unicast_locators,
+      data.metatraffic_unicast_locators
+    );
+  }
+
+  #[test]
+  fn read_only_participant_data_test() {
+    let data = spdp_participant_data().unwrap();
+    let all = data.available_builtin_endpoints;
+
+    // Our announcement keeps only the writers needed for subscribing.
+    let local = read_only_participant_data(&data, READ_ONLY_IDLE_WRITERS);
+    let endpoints = local.available_builtin_endpoints;
+    for kept in [
+      BuiltinEndpointSet::PARTICIPANT_ANNOUNCER,
+      BuiltinEndpointSet::PARTICIPANT_DETECTOR,
+      BuiltinEndpointSet::PUBLICATIONS_DETECTOR,
+      BuiltinEndpointSet::SUBSCRIPTIONS_ANNOUNCER,
+      BuiltinEndpointSet::SUBSCRIPTIONS_DETECTOR,
+      BuiltinEndpointSet::PARTICIPANT_MESSAGE_DATA_READER,
+    ] {
+      assert_eq!(endpoints.contains(kept), all.contains(kept));
+    }
+    for hidden in [
+      BuiltinEndpointSet::PUBLICATIONS_ANNOUNCER,
+      BuiltinEndpointSet::TOPICS_ANNOUNCER,
+      BuiltinEndpointSet::PARTICIPANT_MESSAGE_DATA_WRITER,
+    ] {
+      assert!(!endpoints.contains(hidden));
+    }
+
+    // Remote readers of what we do not publish are not matched.
+    let remote = read_only_participant_data(&data, READ_ONLY_IDLE_REMOTE_READERS);
+    let endpoints = remote.available_builtin_endpoints;
+    assert!(endpoints.contains(
+      BuiltinEndpointSet::PUBLICATIONS_ANNOUNCER | BuiltinEndpointSet::SUBSCRIPTIONS_DETECTOR
+    ));
+    for hidden in [
+      BuiltinEndpointSet::PUBLICATIONS_DETECTOR,
+      BuiltinEndpointSet::TOPICS_DETECTOR,
+      BuiltinEndpointSet::PARTICIPANT_MESSAGE_DATA_READER,
+    ] {
+      assert!(!endpoints.contains(hidden));
+    }
+    assert_eq!(remote.participant_guid, data.participant_guid);
+  }
+
   #[test]
   fn discovery_participant_data_test() {
     let poll = Poll::new().unwrap();
     const LISTENER_PORT: u16 = spdp_well_known_unicast_port(12, 0);
 
-    let mut udp_listener =
+    let udp_listener =
       UDPListener::new_unicast("127.0.0.1", LISTENER_PORT).expect("udp listener creation");
     poll
       .register(
-        udp_listener.mio_socket(),
+        udp_listener.evented(),
         Token(0),
         Ready::readable(),
         PollOpt::edge(),
@@ -2218,10 +2540,10 @@ mod tests {
     let poll: Poll = Poll::new().unwrap();
     const LISTENER_PORT: u16 = spdp_well_known_unicast_port(14, 0);
 
-    let mut udp_listener = UDPListener::new_unicast("127.0.0.1", LISTENER_PORT).unwrap();
+    let udp_listener = UDPListener::new_unicast("127.0.0.1", LISTENER_PORT).unwrap();
     poll
       .register(
-        udp_listener.mio_socket(),
+        udp_listener.evented(),
         Token(0),
         Ready::readable(),
         PollOpt::edge(),
@@ -2308,7 +2630,7 @@ mod tests {
     let mut udp_listener = UDPListener::new_unicast("127.0.0.1", 0).unwrap();
     poll
       .register(
-        udp_listener.mio_socket(),
+        udp_listener.evented(),
         Token(0),
         Ready::readable(),
         PollOpt::edge(),
@@ -2379,6 +2701,7 @@ mod tests {
         history: None,
         resource_limits: None,
         ownership: None,
+        durability_service: None,
       },
     );
 
@@ -13,7 +13,7 @@ use crate::{
     participant::DomainParticipant,
     qos::HasQoSPolicy,
     statusevents::{DomainParticipantStatusEvent, LostReason, StatusChannelSender},
-    topic::{Topic, TopicDescription},
+    topic::{Topic, TopicData, TopicDescription, TopicTypeRegistry},
   },
   rtps::{
     reader::ReaderIngredients, rtps_reader_proxy::RtpsReaderProxy,
@@ -22,10 +22,14 @@ use crate::{
   structure::{
     duration::Duration,
     entity::RTPSEntity,
-    guid::{EntityId, GuidPrefix, GUID},
+    guid::{EntityId, EntityKind, GuidPrefix, GUID},
+    locator::Locator,
   },
 };
 use super::{
+  discovery_dump::{
+    DiscoveryDump, DumpedParticipant, DumpedReader, DumpedWriter, EndpointMatch, MatchStatus,
+  },
   sedp_messages::{
     topics_inconsistent, DiscoveredReaderData, DiscoveredTopicData, DiscoveredWriterData,
     ParticipantMessageData, ReaderProxy, SubscriptionBuiltinTopicData, TopicBuiltinTopicData,
@@ -64,6 +68,8 @@ pub(crate) struct DiscoveryDB {
   // remote readers and writers (via discovery)
   external_topic_readers: BTreeMap<GUID, DiscoveredReaderData>,
   external_topic_writers: BTreeMap<GUID, DiscoveredWriterData>,
+  // When the discovery data of a remote reader or writer was last received
+  external_endpoint_last_seen: BTreeMap<GUID, Instant>,
 
   // These are "attic" storages for readers and writers whose participant
   // was lost due to time-out. If we have a
@@ -74,17 +80,22 @@ pub(crate) struct DiscoveryDB {
   // Outer level key is topic name
   // Inner key is topic data sender.
   topics: BTreeMap<String, BTreeMap<GUID, (DiscoveredVia, DiscoveredTopicData)>>,
+  // Keys of the DCPSTopic instances of our own topics, by topic name
+  local_topic_keys: BTreeMap<String, GUID>,
 
   // sender for notifying (potential) waiters in participant.find_topic() call
   topic_updated_sender: mio_extras::channel::SyncSender<()>,
 
   participant_status_sender: StatusChannelSender<DomainParticipantStatusEvent>,
+
+  // Expected type names of topics
+  topic_type_registry: Arc<TopicTypeRegistry>,
 }
 
 // How did we discover this topic
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub(crate) enum DiscoveredVia {
-  Topic,        // explicitly, via the topic topic (does this actually occur?)
+  Topic,        // explicitly, via the topic topic
   Publication,  // we discovered there is a writer on this topic
   Subscription, // we discovered a reader on this topic
   SelfDefined,  // not discovered, but defined by the local DomainParticipant
@@ -135,14 +146,22 @@ impl DiscoveryDB {
       local_topic_readers: BTreeMap::new(),
       external_topic_readers: BTreeMap::new(),
       external_topic_writers: BTreeMap::new(),
+      external_endpoint_last_seen: BTreeMap::new(),
       external_topic_readers_attic: BTreeMap::new(),
       external_topic_writers_attic: BTreeMap::new(),
       topics: BTreeMap::new(),
+      local_topic_keys: BTreeMap::new(),
       topic_updated_sender,
       participant_status_sender,
+      topic_type_registry: Arc::default(),
     }
   }
 
+  pub fn with_topic_type_registry(mut self, topic_type_registry: Arc<TopicTypeRegistry>) -> Self {
+    self.topic_type_registry = topic_type_registry;
+    self
+  }
+
   fn send_participant_status(&self, event: DomainParticipantStatusEvent) {
     self
       .participant_status_sender
@@ -267,12 +286,14 @@ impl DiscoveryDB {
       .collect();
     for guid in to_remove {
       self.external_topic_readers.remove(&guid);
+      self.external_endpoint_last_seen.remove(&guid);
     }
   }
 
   pub fn remove_topic_reader(&mut self, guid: GUID) {
     info!("remove_topic_reader {guid:?}");
     self.external_topic_readers.remove(&guid);
+    self.external_endpoint_last_seen.remove(&guid);
   }
 
   #[cfg(feature = "security")]
@@ -295,11 +316,13 @@ impl DiscoveryDB {
       .collect();
     for guid in to_remove {
       self.external_topic_writers.remove(&guid);
+      self.external_endpoint_last_seen.remove(&guid);
     }
   }
 
   pub fn remove_topic_writer(&mut self, guid: GUID) {
     self.external_topic_writers.remove(&guid);
+    self.external_endpoint_last_seen.remove(&guid);
   }
 
   // Delete participant proxies, if we have not heard of them within
@@ -460,6 +483,9 @@ impl DiscoveryDB {
     };
 
     self.external_topic_readers.insert(guid, enriched.clone());
+    self
+      .external_endpoint_last_seen
+      .insert(guid, Instant::now());
     debug!("External reader: {enriched:?}");
 
     // Now the topic update:
@@ -517,6 +543,9 @@ impl DiscoveryDB {
     };
 
     self.external_topic_writers.insert(guid, enriched.clone());
+    self
+      .external_endpoint_last_seen
+      .insert(guid, Instant::now());
     debug!("External writer: {enriched:?}");
 
     // Now the topic update:
@@ -532,10 +561,11 @@ impl DiscoveryDB {
 
   // This is for local participant updating the topic table
   pub fn update_topic_data_p(&mut self, topic: &Topic) {
+    let key = self.local_topic_key(&topic.name());
     let topic_data = DiscoveredTopicData::new(
       Utc::now(),
       TopicBuiltinTopicData::new(
-        None,
+        Some(key),
         topic.name(),
         topic.get_type().name().to_owned(),
         &topic.qos(),
@@ -544,6 +574,24 @@ impl DiscoveryDB {
     self.update_topic_data(&topic_data, self.my_guid, DiscoveredVia::SelfDefined);
   }
 
+  // Each of our topics is a separate instance of DCPSTopic, so it needs a key
+  // of its own. The entity kind is not used by any endpoint, so the key does
+  // not collide with an endpoint GUID.
+  fn local_topic_key(&mut self, topic_name: &str) -> GUID {
+    let next_key = self.local_topic_keys.len() as u32 + 1;
+    let my_prefix = self.my_guid.prefix;
+    *self
+      .local_topic_keys
+      .entry(topic_name.to_string())
+      .or_insert_with(|| {
+        let [_, a, b, c] = next_key.to_be_bytes();
+        GUID::new(
+          my_prefix,
+          EntityId::new([a, b, c], EntityKind::UNKNOWN_USER_DEFINED),
+        )
+      })
+  }
+
   // Topic update sends notifications, in case someone was waiting to find a
   // topic. Return value indicates whether the topic (name) was new to us. This
   // is used to add
@@ -558,6 +606,31 @@ impl DiscoveryDB {
     let mut notify = false;
     let mut inconsistency_event_to_send = None;
 
+    // Check the type name of a topic from a remote participant against the
+    // registry, once per source.
+    let new_source = !self
+      .topics
+      .get(&topic_name)
+      .is_some_and(|t| t.contains_key(&updater));
+    if new_source && updater.prefix != self.my_guid.prefix {
+      if let Err(e) = self
+        .topic_type_registry
+        .check(&topic_name, &dtd.topic_data.type_name)
+      {
+        warn!("Inconsistent topic from {updater:?}: {e}");
+        let mut registered_topic_data: TopicData = (&dtd.topic_data).into();
+        if let Some(expected) = self.topic_type_registry.expected_type_name(&topic_name) {
+          registered_topic_data.type_name = expected.to_string();
+        }
+        inconsistency_event_to_send = Some(DomainParticipantStatusEvent::InconsistentTopic {
+          previous_topic_data: Box::new(registered_topic_data),
+          previous_source: self.my_guid,
+          discovered_topic_data: Box::new((&dtd.topic_data).into()),
+          discovery_source: updater,
+        });
+      }
+    }
+
     if let Some(t) = self.topics.get_mut(&dtd.topic_data.name) {
       if let Some(old_dtd) = t.get_mut(&updater) {
         // already have it from the same source, do some checking(?) and merging
@@ -616,9 +689,11 @@ impl DiscoveryDB {
       let mut b = BTreeMap::new();
       b.insert(updater, (discovered_via, dtd.clone()));
       self.topics.insert(topic_name, b);
+      notify = true;
       self.send_participant_status(DomainParticipantStatusEvent::TopicDetected {
         name: dtd.topic_data.name.clone(),
         type_name: dtd.topic_data.type_name.clone(),
+        qos: Box::new(dtd.topic_data.qos()),
       });
     };
     if let Some(ev) = inconsistency_event_to_send {
@@ -682,6 +757,23 @@ impl DiscoveryDB {
     self.local_topic_writers.get(&guid)
   }
 
+  // Replace the advertised locators of all local user-defined Readers and
+  // Writers, e.g. after our network addresses have changed.
+  pub fn update_local_endpoint_locators(
+    &mut self,
+    unicast_locators: &[Locator],
+    multicast_locators: &[Locator],
+  ) {
+    for reader in self.local_topic_readers.values_mut() {
+      reader.reader_proxy.unicast_locator_list = unicast_locators.to_vec();
+      reader.reader_proxy.multicast_locator_list = multicast_locators.to_vec();
+    }
+    // Writers do not advertise multicast locators. See DiscoveredWriterData::new
+    for writer in self.local_topic_writers.values_mut() {
+      writer.writer_proxy.unicast_locator_list = unicast_locators.to_vec();
+    }
+  }
+
   pub fn get_all_local_topic_readers(&self) -> impl Iterator<Item = &DiscoveredReaderData> {
     self.local_topic_readers.values()
   }
@@ -705,13 +797,28 @@ impl DiscoveryDB {
   // a Topic may have multiple definitions, because there may be multiple
   // participants publishing the topic information.
   // At least the QoS details may be different.
-  // This just returns the first one found in the database, which is indexed by
-  // GUID.
+  // This returns the most authoritative one: our own definition, else one
+  // published to DCPSTopic, else one inferred from a remote Reader or Writer.
+  // Inferred definitions carry the endpoint QoS, not the topic QoS.
   pub fn get_topic(&self, topic_name: &str) -> Option<&DiscoveredTopicData> {
+    self.topics.get(topic_name).and_then(|m| {
+      m.values()
+        .min_by_key(|(via, _)| match via {
+          DiscoveredVia::SelfDefined => 0,
+          DiscoveredVia::Topic => 1,
+          DiscoveredVia::Publication | DiscoveredVia::Subscription => 2,
+        })
+        .map(|t| &t.1)
+    })
+  }
+
+  // Our own definition of the topic, if we have one
+  pub fn get_local_topic(&self, topic_name: &str) -> Option<&DiscoveredTopicData> {
     self
       .topics
       .get(topic_name)
-      .and_then(|m| m.values().next().map(|t| &t.1))
+      .and_then(|m| m.get(&self.my_guid))
+      .map(|t| &t.1)
   }
 
   pub fn writers_on_topic_and_participant(
@@ -789,6 +896,121 @@ impl DiscoveryDB {
       .collect()
   }
 
+  // Snapshot of the whole database. Match status is computed only between
+  // local and remote endpoints, in the same QoS direction as the Reader and
+  // Writer do it when matching.
+  pub fn dump(&self) -> DiscoveryDump {
+    let now = Instant::now();
+    let utc_now = Utc::now();
+    let to_utc = |instant: &Instant| {
+      chrono::Duration::from_std(now.saturating_duration_since(*instant))
+        .ok()
+        .and_then(|ago| utc_now.checked_sub_signed(ago))
+    };
+
+    let participants = self
+      .participant_proxies
+      .iter()
+      .map(|(prefix, data)| DumpedParticipant {
+        data: data.clone(),
+        last_seen: self
+          .participant_last_life_signs
+          .get(prefix)
+          .and_then(to_utc),
+      })
+      .collect();
+
+    let readers = self
+      .local_topic_readers
+      .values()
+      .map(|data| (data, true))
+      .chain(
+        self
+          .external_topic_readers
+          .values()
+          .map(|data| (data, false)),
+      )
+      .map(|(data, local)| DumpedReader {
+        data: data.clone(),
+        local,
+        last_seen: self
+          .external_endpoint_last_seen
+          .get(&data.reader_proxy.remote_reader_guid)
+          .filter(|_| !local)
+          .and_then(to_utc),
+      })
+      .collect();
+
+    let writers = self
+      .local_topic_writers
+      .values()
+      .map(|data| (data, true))
+      .chain(
+        self
+          .external_topic_writers
+          .values()
+          .map(|data| (data, false)),
+      )
+      .map(|(data, local)| DumpedWriter {
+        data: data.clone(),
+        local,
+        last_seen: self
+          .external_endpoint_last_seen
+          .get(&data.writer_proxy.remote_writer_guid)
+          .filter(|_| !local)
+          .and_then(to_utc),
+      })
+      .collect();
+
+    let match_of = |reader: &DiscoveredReaderData, writer: &DiscoveredWriterData| {
+      let topic_name = reader.subscription_topic_data.topic_name();
+      (*topic_name == writer.publication_topic_data.topic_name).then(|| EndpointMatch {
+        topic_name: topic_name.clone(),
+        reader: reader.reader_proxy.remote_reader_guid,
+        writer: writer.writer_proxy.remote_writer_guid,
+        status: match writer
+          .publication_topic_data
+          .qos()
+          .compliance_failure_wrt(&reader.subscription_topic_data.qos())
+        {
+          None => MatchStatus::Matched,
+          Some(policy) => MatchStatus::IncompatibleQos(policy),
+        },
+      })
+    };
+    let remote_readers = self
+      .external_topic_readers
+      .iter()
+      .filter(|(guid, _)| !self.local_topic_readers.contains_key(guid))
+      .map(|(_, data)| data);
+    let remote_writers = self
+      .external_topic_writers
+      .iter()
+      .filter(|(guid, _)| !self.local_topic_writers.contains_key(guid))
+      .map(|(_, data)| data);
+    let mut matches: Vec<EndpointMatch> = self
+      .local_topic_readers
+      .values()
+      .flat_map(|reader| {
+        remote_writers
+          .clone()
+          .filter_map(move |writer| match_of(reader, writer))
+      })
+      .collect();
+    matches.extend(self.local_topic_writers.values().flat_map(|writer| {
+      remote_readers
+        .clone()
+        .filter_map(move |reader| match_of(reader, writer))
+    }));
+
+    DiscoveryDump {
+      participants,
+      readers,
+      writers,
+      matches,
+    }
+  }
+
   pub fn update_lease_duration(&mut self, data: &ParticipantMessageData) {
     let now = Instant::now();
     let prefix = data.guid;
@@ -823,7 +1045,7 @@ mod tests {
   use super::*;
   use crate::{
     dds::{
-      qos::QosPolicies,
+      qos::{policy::Reliability, QosPolicies, QosPolicyBuilder},
       statusevents::{sync_status_channel, DataReaderStatus},
       topic::TopicKind,
       with_key::simpledatareader::ReaderCommand,
@@ -864,6 +1086,123 @@ mod tests {
     // TODO: more operations tests
   }
 
+  #[test]
+  fn discdb_topic_type_registry() {
+    let (discovery_db_event_sender, _discovery_db_event_receiver) =
+      mio_channel::sync_channel::<()>(4);
+    let (status_sender, status_receiver) = sync_status_channel(16).unwrap();
+    let my_guid = GUID::new_participant_guid();
+    let mut discovery_db = DiscoveryDB::new(my_guid, discovery_db_event_sender, status_sender)
+      .with_topic_type_registry(Arc::new(
+        TopicTypeRegistry::new().with_topic("odom", "Odometry"),
+      ));
+
+    let mut update = |topic_name: &str, type_name: &str, updater: GUID| {
+      let dtd = DiscoveredTopicData::new(
+        Utc::now(),
+        TopicBuiltinTopicData::new(
+          None,
+          topic_name.to_string(),
+          type_name.to_string(),
+          &QosPolicies::qos_none(),
+        ),
+      );
+      discovery_db.update_topic_data(&dtd, updater, DiscoveredVia::Publication);
+      std::iter::from_fn(|| status_receiver.try_recv().ok())
+        .filter_map(|ev| match ev {
+          DomainParticipantStatusEvent::InconsistentTopic {
+            previous_topic_data,
+            discovered_topic_data,
+            ..
+          } => Some((
+            previous_topic_data.type_name,
+            discovered_topic_data.type_name,
+          )),
+          _ => None,
+        })
+        .collect::<Vec<_>>()
+    };
+    let remote_writer = |n| {
+      GUID::new(
+        GuidPrefix::new(&[n; 12]),
+        EntityId::new([0, 0, 1], EntityKind::WRITER_WITH_KEY_USER_DEFINED),
+      )
+    };
+
+    assert!(update("odom", "Odometry", remote_writer(1)).is_empty());
+    assert_eq!(
+      update("odom", "Twist", remote_writer(2)),
+      vec![("Odometry".to_string(), "Twist".to_string())]
+    );
+    // Reported once per source
+    assert!(update("odom", "Twist", remote_writer(2)).is_empty());
+    // Unregistered topics are not checked.
+    assert!(update("cmd_vel", "Twist", remote_writer(3)).is_empty());
+    // Local topics are checked at creation, not here.
+    assert!(update("other", "Twist", my_guid).is_empty());
+  }
+
+  #[test]
+  fn discdb_topic_definitions() {
+    let (discovery_db_event_sender, discovery_db_event_receiver) =
+      mio_channel::sync_channel::<()>(4);
+    let (status_sender, _status_receiver) = sync_status_channel(16).unwrap();
+    let my_guid = GUID::new_participant_guid();
+    let mut discovery_db = DiscoveryDB::new(my_guid, discovery_db_event_sender, status_sender);
+
+    // Each local topic has a key of its own.
+    let odom_key = discovery_db.local_topic_key("odom");
+    assert_eq!(odom_key.prefix, my_guid.prefix);
+    assert_eq!(discovery_db.local_topic_key("odom"), odom_key);
+    assert_ne!(discovery_db.local_topic_key("cmd_vel"), odom_key);
+
+    // Returns the reliability of the topic, and whether we have our own
+    // definition of it.
+    let mut update = |reliability, updater, discovered_via| {
+      let qos = QosPolicyBuilder::new().reliability(reliability).build();
+      let dtd = DiscoveredTopicData::new(
+        Utc::now(),
+        TopicBuiltinTopicData::new(None, "odom".to_string(), "Odometry".to_string(), &qos),
+      );
+      discovery_db.update_topic_data(&dtd, updater, discovered_via);
+      (
+        discovery_db
+          .get_topic("odom")
+          .and_then(|d| d.topic_data.reliability),
+        discovery_db.get_local_topic("odom").is_some(),
+      )
+    };
+    let reliable = Reliability::Reliable {
+      max_blocking_time: Duration::ZERO,
+    };
+    let remote = |kind| GUID::new(GuidPrefix::new(&[1; 12]), EntityId::new([0, 0, 1], kind));
+
+    assert_eq!(
+      update(
+        Reliability::BestEffort,
+        remote(EntityKind::WRITER_WITH_KEY_USER_DEFINED),
+        DiscoveredVia::Publication
+      ),
+      (Some(Reliability::BestEffort), false)
+    );
+    // Waiters in find_topic are notified of a new topic.
+    assert!(discovery_db_event_receiver.try_recv().is_ok());
+    // The published topic QoS overrides the QoS of an endpoint.
+    assert_eq!(
+      update(
+        reliable,
+        remote(EntityKind::WRITER_WITH_KEY_BUILT_IN),
+        DiscoveredVia::Topic
+      ),
+      (Some(reliable), false)
+    );
+    // Our own definition overrides all others.
+    assert_eq!(
+      update(Reliability::BestEffort, my_guid, DiscoveredVia::SelfDefined),
+      (Some(Reliability::BestEffort), true)
+    );
+  }
+
   #[test]
   fn discdb_writer_proxies() {
     let (discovery_db_event_sender, _discovery_db_event_receiver) =
@@ -973,6 +1312,99 @@ mod tests {
     // TODO: there might be a need for different scenarios
   }
 
+  #[test]
+  fn discdb_dump_shows_endpoints_and_match_status() {
+    let (discovery_db_event_sender, _discovery_db_event_receiver) =
+      mio_channel::sync_channel::<()>(4);
+    let (status_sender, _status_receiver) = sync_status_channel(16).unwrap();
+    let mut discovery_db = DiscoveryDB::new(
+      GUID::new_participant_guid(),
+      discovery_db_event_sender,
+      status_sender,
+    );
+
+    let dp = DomainParticipant::new(0).expect("Failed to create participant");
+    let topic = dp
+      .create_topic(
+        "Foobar".to_string(),
+        "RandomData".to_string(),
+        &QosPolicies::qos_none(),
+        TopicKind::WithKey,
+      )
+      .unwrap();
+    let publisher = dp.create_publisher(&QosPolicies::qos_none()).unwrap();
+    let dw = publisher
+      .create_datawriter::<RandomData, CDRSerializerAdapter<RandomData, LittleEndian>>(&topic, None)
+      .unwrap();
+    let writer_guid = dw.guid();
+    discovery_db.update_local_topic_writer(DiscoveredWriterData::new(&dw, &topic, &dp, None));
+
+    let participant = spdp_participant_data().unwrap();
+    discovery_db.update_participant(&participant);
+
+    let remote_reader = |entity_key: u8, qos: &QosPolicies| {
+      let guid = GUID::new(
+        participant.participant_guid.prefix,
+        EntityId::new([0, 0, entity_key], EntityKind::READER_WITH_KEY_USER_DEFINED),
+      );
+      DiscoveredReaderData {
+        reader_proxy: ReaderProxy::new(guid, false, vec![], vec![]),
+        subscription_topic_data: SubscriptionBuiltinTopicData::new(
+          guid,
+          None,
+          topic.name(),
+          "RandomData".to_string(),
+          qos,
+          None,
+        ),
+        content_filter: None,
+        user_data: Vec::new(),
+      }
+    };
+    let compatible = remote_reader(1, &QosPolicies::qos_none());
+    let incompatible = remote_reader(
+      2,
+      &QosPolicies::builder()
+        .durability(crate::dds::qos::policy::Durability::TransientLocal)
+        .build(),
+    );
+    discovery_db.update_subscription(&compatible);
+    discovery_db.update_subscription(&incompatible);
+
+    let dump = discovery_db.dump();
+    assert_eq!(dump.participants.len(), 1);
+    assert!(dump.participants[0].last_seen.is_some());
+    assert_eq!(dump.writers.len(), 1);
+    assert!(dump.writers[0].local && dump.writers[0].last_seen.is_none());
+    assert_eq!(dump.readers.len(), 2);
+    assert!(dump
+      .readers
+      .iter()
+      .all(|r| !r.local && r.last_seen.is_some()));
+    // Remote readers got the default locators of their participant
+    assert_eq!(
+      dump.readers[0].data.reader_proxy.unicast_locator_list,
+      participant.default_unicast_locators
+    );
+
+    assert_eq!(dump.matches.len(), 2);
+    assert_eq!(
+      dump.match_status(compatible.reader_proxy.remote_reader_guid, writer_guid),
+      Some(MatchStatus::Matched)
+    );
+    assert_eq!(
+      dump.match_status(incompatible.reader_proxy.remote_reader_guid, writer_guid),
+      Some(MatchStatus::IncompatibleQos(
+        crate::dds::qos::QosPolicyId::Durability
+      ))
+    );
+
+    discovery_db.remove_topic_reader(incompatible.reader_proxy.remote_reader_guid);
+    let dump = discovery_db.dump();
+    assert_eq!(dump.readers.len(), 1);
+    assert_eq!(dump.matches.len(), 1);
+  }
+
   #[test]
   fn discdb_local_topic_reader() {
     let (discovery_db_event_sender, _discovery_db_event_receiver) =
@@ -1021,6 +1453,13 @@ mod tests {
       data_reader_command_receiver: reader_command_receiver1,
       data_reader_waker: data_reader_waker1,
       poll_event_sender: notification_event_sender1,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
 
@@ -1058,6 +1497,13 @@ mod tests {
       data_reader_command_receiver: reader_command_receiver2,
       data_reader_waker: data_reader_waker2,
       poll_event_sender: notification_event_sender2,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
 
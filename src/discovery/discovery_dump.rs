@@ -0,0 +1,97 @@
+//! Read-only snapshot of the discovery database, for debugging.
+
+use chrono::Utc;
+
+use crate::{
+  dds::qos::QosPolicyId,
+  discovery::{DiscoveredReaderData, DiscoveredWriterData, SpdpDiscoveredParticipantData},
+  structure::guid::GUID,
+};
+
+/// Complete contents of the discovery database of a
+/// [`DomainParticipant`](crate::DomainParticipant), as returned by
+/// [`DomainParticipant::discovery_dump`](crate::DomainParticipant::discovery_dump).
+///
+/// This is a snapshot: it is not updated when discovery proceeds.
+#[derive(Debug, Clone)]
+pub struct DiscoveryDump {
+  /// All known participants, including the local one.
+  pub participants: Vec<DumpedParticipant>,
+  /// Local and remote DataReaders.
+  pub readers: Vec<DumpedReader>,
+  /// Local and remote DataWriters.
+  pub writers: Vec<DumpedWriter>,
+  /// Match status of each pair of a local and a remote endpoint on the same
+  /// topic.
+  pub matches: Vec<EndpointMatch>,
+}
+
+/// A participant known from SPDP. The locators, lease duration, vendor and
+/// protocol version are in `data`.
+#[derive(Debug, Clone)]
+pub struct DumpedParticipant {
+  pub data: SpdpDiscoveredParticipantData,
+  /// When the participant last sent an SPDP announcement or other sign of
+  /// life.
+  pub last_seen: Option<chrono::DateTime<Utc>>,
+}
+
+/// A DataReader known to discovery.
+#[derive(Debug, Clone)]
+pub struct DumpedReader {
+  /// Topic, type, QoS, GUID and locators of the reader
+  pub data: DiscoveredReaderData,
+  /// Created by the local participant?
+  pub local: bool,
+  /// When a subscription announcement of a remote reader was last received.
+  /// `None` for local readers.
+  pub last_seen: Option<chrono::DateTime<Utc>>,
+}
+
+/// A DataWriter known to discovery.
+#[derive(Debug, Clone)]
+pub struct DumpedWriter {
+  /// Topic, type, QoS, GUID and locators of the writer
+  pub data: DiscoveredWriterData,
+  /// Created by the local participant?
+  pub local: bool,
+  /// When a publication announcement of a remote writer was last received.
+  /// `None` for local writers.
+  pub last_seen: Option<chrono::DateTime<Utc>>,
+}
+
+/// A local reader and a remote writer, or a local writer and a remote
+/// reader, on the same topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointMatch {
+  pub topic_name: String,
+  pub reader: GUID,
+  pub writer: GUID,
+  pub status: MatchStatus,
+}
+
+/// Whether discovery matches two endpoints on the same topic.
+///
+/// Only the QoS is compared, in the same way as when endpoints are matched.
+/// Type names are not compared, and with the security feature, access
+/// control may still refuse a match shown here as
+/// [`Matched`](MatchStatus::Matched).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchStatus {
+  Matched,
+  /// The offered QoS of the writer does not satisfy the requested QoS of the
+  /// reader. This is the first failing policy.
+  IncompatibleQos(QosPolicyId),
+}
+
+impl DiscoveryDump {
+  /// Match status of the given reader and writer, if one of them is local
+  /// and the other remote.
+  pub fn match_status(&self, reader: GUID, writer: GUID) -> Option<MatchStatus> {
+    self
+      .matches
+      .iter()
+      .find(|m| m.reader == reader && m.writer == writer)
+      .map(|m| m.status)
+  }
+}
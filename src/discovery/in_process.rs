@@ -0,0 +1,137 @@
+//! Registry of the participants of this process that use in-process
+//! discovery. See
+//! [`DomainParticipantBuilder::in_process_discovery`](crate::DomainParticipantBuilder::in_process_discovery).
+//!
+//! The registry only replaces the transport of SPDP announcements between
+//! participants of the same process. Endpoint discovery (SEDP) and user
+//! traffic still go over the normal RTPS transport.
+
+use std::sync::Mutex;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+use mio_extras::channel as mio_channel;
+
+use crate::{discovery::SpdpDiscoveredParticipantData, structure::guid::GuidPrefix};
+
+// Announcements are sent at least as often as SPDP, and a lost one is
+// repeated by the next SPDP period, so a small queue is enough.
+const ANNOUNCEMENT_QUEUE_LENGTH: usize = 16;
+
+#[derive(Debug, Clone)]
+pub(crate) enum InProcessAnnouncement {
+  Alive(Box<SpdpDiscoveredParticipantData>),
+  Gone(GuidPrefix),
+}
+
+struct RegisteredParticipant {
+  domain_id: u16,
+  guid_prefix: GuidPrefix,
+  sender: mio_channel::SyncSender<InProcessAnnouncement>,
+}
+
+static REGISTRY: Mutex<Vec<RegisteredParticipant>> = Mutex::new(Vec::new());
+
+fn with_registry<R>(f: impl FnOnce(&mut Vec<RegisteredParticipant>) -> R) -> R {
+  // A panic while holding the lock cannot leave the Vec inconsistent, so
+  // poisoning can be ignored.
+  let mut registry = REGISTRY
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner());
+  f(&mut registry)
+}
+
+/// Membership of a participant in the registry. The participant is removed
+/// from the registry when this is dropped.
+pub(crate) struct InProcessRegistration {
+  domain_id: u16,
+  guid_prefix: GuidPrefix,
+  receiver: mio_channel::Receiver<InProcessAnnouncement>,
+}
+
+impl InProcessRegistration {
+  pub fn register(domain_id: u16, guid_prefix: GuidPrefix) -> Self {
+    let (sender, receiver) = mio_channel::sync_channel(ANNOUNCEMENT_QUEUE_LENGTH);
+    with_registry(|registry| {
+      registry.retain(|p| p.guid_prefix != guid_prefix);
+      registry.push(RegisteredParticipant {
+        domain_id,
+        guid_prefix,
+        sender,
+      });
+    });
+    Self {
+      domain_id,
+      guid_prefix,
+      receiver,
+    }
+  }
+
+  /// Announcements of the other registered participants in the same domain
+  /// arrive from here.
+  pub fn receiver(&self) -> &mio_channel::Receiver<InProcessAnnouncement> {
+    &self.receiver
+  }
+
+  /// Deliver `announcement` to all other registered participants in the same
+  /// domain.
+  pub fn announce(&self, announcement: &InProcessAnnouncement) {
+    announce(self.domain_id, self.guid_prefix, announcement);
+  }
+}
+
+impl Drop for InProcessRegistration {
+  fn drop(&mut self) {
+    with_registry(|registry| registry.retain(|p| p.guid_prefix != self.guid_prefix));
+  }
+}
+
+// Participants whose receiver is gone are dropped from the registry.
+fn announce(domain_id: u16, from: GuidPrefix, announcement: &InProcessAnnouncement) {
+  with_registry(|registry| {
+    registry.retain(|p| {
+      if p.domain_id != domain_id || p.guid_prefix == from {
+        return true;
+      }
+      match p.sender.try_send(announcement.clone()) {
+        Ok(()) => true,
+        Err(mio_channel::TrySendError::Full(_)) => {
+          debug!(
+            "In-process discovery queue of {:?} is full. Dropping announcement.",
+            p.guid_prefix
+          );
+          true
+        }
+        Err(_) => false, // receiver is gone
+      }
+    });
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::structure::guid::GUID;
+
+  #[test]
+  fn announcements_reach_other_participants_of_the_domain() {
+    let a = InProcessRegistration::register(1000, GUID::new_participant_guid().prefix);
+    let b = InProcessRegistration::register(1000, GUID::new_participant_guid().prefix);
+    let c = InProcessRegistration::register(1001, GUID::new_participant_guid().prefix);
+
+    a.announce(&InProcessAnnouncement::Gone(a.guid_prefix));
+    assert!(a.receiver().try_recv().is_err()); // not to self
+    assert!(matches!(
+      b.receiver().try_recv(),
+      Ok(InProcessAnnouncement::Gone(p)) if p == a.guid_prefix
+    ));
+    assert!(c.receiver().try_recv().is_err()); // not to another domain
+
+    let b_prefix = b.guid_prefix;
+    drop(b);
+    assert!(with_registry(|r| r
+      .iter()
+      .all(|p| p.guid_prefix != b_prefix)));
+    drop(c);
+  }
+}
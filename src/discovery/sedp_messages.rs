@@ -14,9 +14,9 @@ use crate::{
     participant::DomainParticipant,
     qos::{
       policy::{
-        DataRepresentation, Deadline, DestinationOrder, Durability, History, LatencyBudget,
-        Lifespan, Liveliness, Ownership, Presentation, Reliability, ResourceLimits,
-        TimeBasedFilter,
+        DataRepresentation, Deadline, DestinationOrder, Durability, DurabilityService, History,
+        LatencyBudget, Lifespan, Liveliness, Ownership, Partition, Presentation, Reliability,
+        ResourceLimits, TimeBasedFilter,
       },
       HasQoSPolicy, QosPolicies,
     },
@@ -28,8 +28,10 @@ use crate::{
     parameter::Parameter,
     parameter_list::{ParameterList, ParameterListable},
   },
-  network::{constant::user_traffic_unicast_port, util::get_local_unicast_locators_filtered},
-  rtps::{rtps_reader_proxy::RtpsReaderProxy, rtps_writer_proxy::RtpsWriterProxy},
+  rtps::{
+    constant::USER_TRAFFIC_LISTENER_TOKEN, rtps_reader_proxy::RtpsReaderProxy,
+    rtps_writer_proxy::RtpsWriterProxy,
+  },
   serialization::{
     pl_cdr_adapters::{
       PlCdrDeserialize, PlCdrDeserializeError, PlCdrSerialize, PlCdrSerializeError,
@@ -138,7 +140,13 @@ impl From<RtpsReaderProxy> for ReaderProxy {
     Self {
       remote_reader_guid: rtps_reader_proxy.remote_reader_guid,
       expects_inline_qos: rtps_reader_proxy.expects_inline_qos(),
-      unicast_locator_list: rtps_reader_proxy.unicast_locator_list,
+      // Keep the loopback locators, so that they are not lost when a proxy is
+      // rebuilt from this.
+      unicast_locator_list: rtps_reader_proxy
+        .unicast_locator_list
+        .into_iter()
+        .chain(rtps_reader_proxy.loopback_unicast_locators)
+        .collect(),
       multicast_locator_list: rtps_reader_proxy.multicast_locator_list,
     }
   }
@@ -166,7 +174,7 @@ pub struct SubscriptionBuiltinTopicData {
   // pub user_data: Option<UserData>,
   time_based_filter: Option<TimeBasedFilter>,
   presentation: Option<Presentation>,
-  // pub partition: Option<Partition>,
+  partition: Option<Partition>,
   // pub topic_data: Option<TopicData>,
   // pub group_data: Option<GroupData>,
   // pub durability_service: Option<DurabilityService>,
@@ -209,6 +217,7 @@ impl SubscriptionBuiltinTopicData {
       ownership: None,
       destination_order: None,
       time_based_filter: None,
+      partition: None,
       presentation: None,
       lifespan: None,
       data_representation: None,
@@ -258,6 +267,7 @@ impl SubscriptionBuiltinTopicData {
     self.destination_order = qos.destination_order;
     self.time_based_filter = qos.time_based_filter;
     self.presentation = qos.presentation;
+    self.partition = qos.partition.clone();
     self.lifespan = qos.lifespan;
     self.data_representation = qos.data_representation.clone();
     // history does not exist
@@ -273,11 +283,13 @@ impl SubscriptionBuiltinTopicData {
       ownership: self.ownership,
       liveliness: self.liveliness,
       time_based_filter: self.time_based_filter,
+      partition: self.partition.clone(),
       reliability: self.reliability,
       destination_order: self.destination_order,
       history: None, // SubscriptionBuiltinTopicData does not contain History QoS
       resource_limits: None, // nor Resource Limits, see Figure 8.30 in RTPS spec 2.5
       lifespan: self.lifespan,
+      durability_service: None, // nor Durability Service
       data_representation: self.data_representation.clone(),
 
       #[cfg(feature = "security")]
@@ -479,6 +491,7 @@ impl ParameterListable for DiscoveredReaderData {
           destination_order: _,
           time_based_filter: _,
           presentation: _,
+          partition: _,
           lifespan: _,
           data_representation: _,
 
@@ -632,10 +645,12 @@ pub struct PublicationBuiltinTopicData {
   pub liveliness: Option<Liveliness>,
   pub reliability: Option<Reliability>,
   pub lifespan: Option<Lifespan>,
+  pub durability_service: Option<DurabilityService>,
   pub time_based_filter: Option<TimeBasedFilter>,
   pub ownership: Option<Ownership>,
   pub destination_order: Option<DestinationOrder>,
   pub presentation: Option<Presentation>,
+  pub partition: Option<Partition>,
   pub data_representation: Option<DataRepresentation>,
 
   // From Remote Procedure Call over DDS:
@@ -671,7 +686,9 @@ impl PublicationBuiltinTopicData {
       liveliness: None,
       reliability: None,
       lifespan: None,
+      durability_service: None,
       time_based_filter: None,
+      partition: None,
       ownership: None,
       destination_order: None,
       presentation: None,
@@ -706,10 +723,12 @@ impl PublicationBuiltinTopicData {
     self.liveliness = qos.liveliness;
     self.reliability = qos.reliability;
     self.lifespan = qos.lifespan;
+    self.durability_service = qos.durability_service;
     self.time_based_filter = qos.time_based_filter;
     self.ownership = qos.ownership;
     self.destination_order = qos.destination_order;
     self.presentation = qos.presentation;
+    self.partition = qos.partition.clone();
     self.data_representation = qos.data_representation.clone();
   }
 
@@ -722,11 +741,13 @@ impl PublicationBuiltinTopicData {
       ownership: self.ownership,
       liveliness: self.liveliness,
       time_based_filter: self.time_based_filter,
+      partition: self.partition.clone(),
       reliability: self.reliability,
       destination_order: self.destination_order,
       history: None,         // PublicationBuiltinTopicData does not contain History QoS
       resource_limits: None, // nor Resource Limits, see Figure 8.30 in RTPS spec 2.5
       lifespan: self.lifespan,
+      durability_service: self.durability_service,
       data_representation: self.data_representation.clone(),
       #[cfg(feature = "security")]
       property: None, // TODO: no property Qos?
@@ -784,9 +805,11 @@ impl DiscoveredWriterData {
     dp: &DomainParticipant,
     security_info: Option<EndpointSecurityInfo>,
   ) -> Self {
-    let unicast_port = user_traffic_unicast_port(dp.domain_id(), dp.participant_id());
-    let only_nets = dp.only_networks();
-    let unicast_addresses = get_local_unicast_locators_filtered(unicast_port, only_nets.as_deref());
+    // The locators of our user traffic listener(s), whatever the transport.
+    let unicast_addresses = dp
+      .self_locators()
+      .remove(&USER_TRAFFIC_LISTENER_TOKEN)
+      .unwrap_or_default();
     // TODO: Why empty vector below? No multicast?
     let writer_proxy = WriterProxy::new(writer.guid(), vec![], unicast_addresses);
     let publication_topic_data = PublicationBuiltinTopicData::new_with_qos(
@@ -939,7 +962,9 @@ impl ParameterListable for DiscoveredWriterData {
           destination_order: _,
           time_based_filter: _,
           presentation: _,
+          partition: _,
           lifespan: _,
+          durability_service: _,
           data_representation: _,
 
           service_instance_name,
@@ -1050,6 +1075,7 @@ pub struct TopicBuiltinTopicData {
   pub history: Option<History>,
   pub resource_limits: Option<ResourceLimits>,
   pub ownership: Option<Ownership>,
+  pub durability_service: Option<DurabilityService>,
 }
 
 impl TopicBuiltinTopicData {
@@ -1069,6 +1095,7 @@ impl TopicBuiltinTopicData {
       history: qos.history(),
       resource_limits: qos.resource_limits(),
       ownership: qos.ownership(),
+      durability_service: qos.durability_service(),
     }
   }
 }
@@ -1083,11 +1110,13 @@ impl HasQoSPolicy for TopicBuiltinTopicData {
       ownership: self.ownership,
       liveliness: self.liveliness,
       time_based_filter: None,
+      partition: None,
       reliability: self.reliability,
       destination_order: self.destination_order,
       history: self.history,
       resource_limits: self.resource_limits,
       lifespan: self.lifespan,
+      durability_service: self.durability_service,
       data_representation: None, // Topic-level DATA_REPRESENTATION not tracked
       #[cfg(feature = "security")]
       property: None, // TODO: no property Qos?
@@ -1214,6 +1243,7 @@ impl PlCdrSerialize for DiscoveredTopicData {
           presentation: _,
           lifespan: _,
           resource_limits: _,
+          durability_service: _,
         },
     } = self;
 
@@ -1458,6 +1488,241 @@ mod tests {
     assert_eq!(sdata, sdata2);
   }
 
+  // Expected Parameter id and little-endian value
+  type ExpectedParameter = (ParameterId, Vec<u8>);
+
+  // Each QoS policy carried in SEDP, set alone, with the Parameters it must be
+  // sent as.
+  fn single_policy_cases() -> Vec<(QosPolicies, Vec<ExpectedParameter>)> {
+    use crate::{dds::qos::policy::*, Duration};
+    let b = QosPolicies::builder;
+    vec![
+      (
+        b().durability(Durability::TransientLocal).build(),
+        vec![(ParameterId::PID_DURABILITY, vec![1, 0, 0, 0])],
+      ),
+      (
+        b()
+          .presentation(Presentation {
+            access_scope: PresentationAccessScope::Topic,
+            coherent_access: true,
+            ordered_access: false,
+          })
+          .build(),
+        vec![(ParameterId::PID_PRESENTATION, vec![1, 0, 0, 0, 1, 0])],
+      ),
+      (
+        b().deadline(Deadline(Duration::from_secs(2))).build(),
+        vec![(ParameterId::PID_DEADLINE, vec![2, 0, 0, 0, 0, 0, 0, 0])],
+      ),
+      (
+        b()
+          .latency_budget(LatencyBudget {
+            duration: Duration::from_secs(3),
+          })
+          .build(),
+        vec![(
+          ParameterId::PID_LATENCY_BUDGET,
+          vec![3, 0, 0, 0, 0, 0, 0, 0],
+        )],
+      ),
+      (
+        b().ownership(Ownership::Shared).build(),
+        vec![(ParameterId::PID_OWNERSHIP, vec![0, 0, 0, 0])],
+      ),
+      (
+        b().ownership(Ownership::Exclusive { strength: 5 }).build(),
+        vec![
+          (ParameterId::PID_OWNERSHIP, vec![1, 0, 0, 0]),
+          (ParameterId::PID_OWNERSHIP_STRENGTH, vec![5, 0, 0, 0]),
+        ],
+      ),
+      (
+        b()
+          .liveliness(Liveliness::ManualByTopic {
+            lease_duration: Duration::from_secs(4),
+          })
+          .build(),
+        vec![(
+          ParameterId::PID_LIVELINESS,
+          vec![2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0],
+        )],
+      ),
+      (
+        b()
+          .time_based_filter(TimeBasedFilter {
+            minimum_separation: Duration::from_secs(1),
+          })
+          .build(),
+        vec![(
+          ParameterId::PID_TIME_BASED_FILTER,
+          vec![1, 0, 0, 0, 0, 0, 0, 0],
+        )],
+      ),
+      (
+        b()
+          .reliability(Reliability::Reliable {
+            max_blocking_time: Duration::from_secs(1),
+          })
+          .build(),
+        vec![(
+          ParameterId::PID_RELIABILITY,
+          vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0],
+        )],
+      ),
+      (
+        b().reliability(Reliability::BestEffort).build(),
+        vec![(
+          ParameterId::PID_RELIABILITY,
+          vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        )],
+      ),
+      (
+        b()
+          .destination_order(DestinationOrder::BySourceTimeStamp)
+          .build(),
+        vec![(ParameterId::PID_DESTINATION_ORDER, vec![1, 0, 0, 0])],
+      ),
+      (
+        b()
+          .lifespan(Lifespan {
+            duration: Duration::from_secs(6),
+          })
+          .build(),
+        vec![(ParameterId::PID_LIFESPAN, vec![6, 0, 0, 0, 0, 0, 0, 0])],
+      ),
+      (
+        QosPolicies::qos_none().with_data_representation(DataRepresentation {
+          value: vec![XCDR2_DATA_REPRESENTATION, XCDR_DATA_REPRESENTATION],
+        }),
+        vec![(
+          ParameterId::PID_DATA_REPRESENTATION,
+          vec![2, 0, 0, 0, 2, 0, 0, 0],
+        )],
+      ),
+    ]
+  }
+
+  // Check that `bytes` carry exactly the expected QoS Parameters. Values are
+  // padded to a multiple of 4 bytes on the wire.
+  fn assert_policy_parameters(bytes: &[u8], expected: &[ExpectedParameter]) {
+    let pl =
+      ParameterList::read_from_buffer_with_ctx(speedy::Endianness::LittleEndian, bytes).unwrap();
+    let pl_map = pl.to_map();
+    for (pid, value) in expected {
+      let sent = &pl_map.get(pid).unwrap_or_else(|| panic!("{pid:?} missing"))[0].value;
+      assert_eq!(&sent[..value.len()], &value[..], "{pid:?}");
+      assert!(sent[value.len()..].iter().all(|b| *b == 0), "{pid:?}");
+    }
+  }
+
+  #[test]
+  fn each_qos_policy_round_trips_in_discovered_reader_data() {
+    for (qos, expected) in single_policy_cases() {
+      let mut sub_topic_data = subscription_builtin_topic_data().unwrap();
+      sub_topic_data.set_qos(&qos);
+      let mut reader_proxy = reader_proxy_data().unwrap();
+      reader_proxy.remote_reader_guid = sub_topic_data.key;
+      let drd = DiscoveredReaderData {
+        reader_proxy,
+        subscription_topic_data: sub_topic_data,
+        content_filter: None,
+        user_data: Vec::new(),
+      };
+
+      let sdata = drd
+        .to_pl_cdr_bytes(RepresentationIdentifier::PL_CDR_LE)
+        .unwrap();
+      assert_policy_parameters(&sdata, &expected);
+      let drd2: DiscoveredReaderData =
+        PlCdrDeserializerAdapter::from_bytes(&sdata, RepresentationIdentifier::PL_CDR_LE).unwrap();
+      assert_eq!(drd2.subscription_topic_data.qos(), qos);
+    }
+  }
+
+  #[test]
+  fn each_qos_policy_round_trips_in_discovered_writer_data() {
+    for (qos, expected) in single_policy_cases() {
+      let mut pub_topic_data = publication_builtin_topic_data().unwrap();
+      pub_topic_data.set_qos(&qos);
+      let mut writer_proxy = writer_proxy_data().unwrap();
+      writer_proxy.remote_writer_guid = pub_topic_data.key;
+      let dwd = DiscoveredWriterData {
+        last_updated: Instant::now(),
+        writer_proxy,
+        publication_topic_data: pub_topic_data,
+        user_data: Vec::new(),
+      };
+
+      let sdata = dwd
+        .to_pl_cdr_bytes(RepresentationIdentifier::PL_CDR_LE)
+        .unwrap();
+      assert_policy_parameters(&sdata, &expected);
+      let dwd2: DiscoveredWriterData =
+        PlCdrDeserializerAdapter::from_bytes(&sdata, RepresentationIdentifier::PL_CDR_LE).unwrap();
+      assert_eq!(dwd2.publication_topic_data.qos(), qos);
+    }
+  }
+
+  #[test]
+  fn unknown_parameters_are_skipped() {
+    let mut reader_proxy = reader_proxy_data().unwrap();
+    let sub_topic_data = subscription_builtin_topic_data().unwrap();
+    reader_proxy.remote_reader_guid = sub_topic_data.key;
+    let drd = DiscoveredReaderData {
+      reader_proxy,
+      subscription_topic_data: sub_topic_data,
+      content_filter: None,
+      user_data: Vec::new(),
+    };
+
+    let mut pl = drd
+      .to_parameter_list(RepresentationIdentifier::PL_CDR_LE)
+      .unwrap();
+    // A vendor-specific id and an id not assigned by the specification.
+    for id in [[0x01, 0x80], [0x77, 0x00]] {
+      let pid =
+        ParameterId::read_from_buffer_with_ctx(speedy::Endianness::LittleEndian, &id).unwrap();
+      pl.parameters
+        .insert(1, Parameter::new(pid, vec![0xde, 0xad, 0xbe, 0xef]));
+    }
+    let sdata = pl
+      .serialize_to_bytes(speedy::Endianness::LittleEndian)
+      .unwrap();
+
+    let drd2: DiscoveredReaderData =
+      PlCdrDeserializerAdapter::from_bytes(&sdata, RepresentationIdentifier::PL_CDR_LE).unwrap();
+    assert_eq!(drd, drd2);
+  }
+
+  #[test]
+  fn ownership_strength_is_optional() {
+    let ctx = speedy::Endianness::LittleEndian;
+    let parse = |parameters: Vec<Parameter>| {
+      let pl = ParameterList { parameters };
+      QosPolicies::from_parameter_list(ctx, &pl.to_map())
+        .unwrap()
+        .ownership
+    };
+
+    // Shared with a strength, as some implementations send it
+    assert_eq!(
+      parse(vec![
+        Parameter::new(ParameterId::PID_OWNERSHIP, vec![0, 0, 0, 0]),
+        Parameter::new(ParameterId::PID_OWNERSHIP_STRENGTH, vec![7, 0, 0, 0]),
+      ]),
+      Some(Ownership::Shared)
+    );
+    // Exclusive without a strength defaults to zero
+    assert_eq!(
+      parse(vec![Parameter::new(
+        ParameterId::PID_OWNERSHIP,
+        vec![1, 0, 0, 0]
+      )]),
+      Some(Ownership::Exclusive { strength: 0 })
+    );
+  }
+
   // Do not test ser/deser. This is never seen on the wire out of
   // DiscoveredTopicData #[test]
   // fn td_topic_data_ser_deser() {
@@ -58,7 +58,8 @@
 //!   ([specification, Section 15.3](https://www.omg.org/cgi-bin/doc?formal/02-06-51)) , which
 //!   is the DDS default, then use [`CDRSerializerAdapter`] and
 //!   [`CDRDeserializerAdapter`]
-//!   when such adapters are required. If you need to use another serialization format, then you should find or write
+//!   when such adapters are required. For the XCDR2 encoding of DDS-XTypes, use
+//!   [`CDR2SerializerAdapter`]; [`CDRDeserializerAdapter`] decodes both. If you need to use another serialization format, then you should find or write
 //!   a [Serde data format](https://serde.rs/data-format.html) implementation and wrap it as a (De)SerializerAdapter.
 //!
 //! # Polling multiple DataReaders
@@ -90,6 +91,8 @@
 //! * [`crate::dds::with_key::BareDataReaderStream::async_event_stream`] or
 //!   [`crate::dds::with_key::DataReaderStream::async_event_stream`] to get data
 //!   status events
+//! * [`SampleStreamExt::next_with_timeout`] to wait for a sample for a limited
+//!   time, without having to `select!` on a separate timer
 //!
 //! See exampe `async_shapes_demo`.
 //!
@@ -171,6 +174,8 @@ mod polling;
 mod serialization_test;
 #[macro_use]
 mod checked_impl;
+#[macro_use]
+mod log_throttle;
 #[doc(hidden)]
 pub mod discovery; // to access some Discovered data in e.g. ros2-client crate
 mod messages;
@@ -190,6 +195,11 @@ pub(crate) mod structure;
 #[cfg(test)]
 mod test;
 
+/// Harness for driving a single Reader or Writer in tests. Requires feature
+/// "test-util".
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
 mod mio_source;
 
 // Public modules
@@ -203,34 +213,67 @@ pub mod serialization;
 // Re-exports from crate root to simplify usage
 #[doc(inline)]
 pub use dds::{
-  key::{Key, Keyed},
-  participant::{DomainParticipant, DomainParticipantBuilder, DomainParticipantStatusListener},
+  durability_store::{DurabilityStore, InMemoryDurabilityStore, StoredSample},
+  key::{InstanceHandle, Key, Keyed},
+  participant::{
+    BestEffortReordering, DeadlineTimestamp, DomainParticipant, DomainParticipantBuilder,
+    DomainParticipantStatusListener, FragmentAssembly, HeartbeatBackoff, HeartbeatDelivery,
+    LargeFanOut,
+  },
   pubsub::{Publisher, Subscriber},
   qos,
   qos::{policy, QosPolicies, QosPolicyBuilder},
   readcondition::ReadCondition,
-  sampleinfo::{InstanceState, NotAliveGenerationCounts, SampleInfo, SampleState, ViewState},
+  sample_stream::{NextWithTimeout, SampleStreamExt},
+  sampleinfo::{
+    InstanceState, NotAliveGenerationCounts, ReceptionContext, SampleInfo, SampleState, ViewState,
+  },
   statusevents::{
-    DataReaderStatus, DataWriterStatus, DomainParticipantStatusEvent, EndpointDescription,
-    LostReason, ParticipantDescription, StatusEvented,
+    CacheOccupancy, CoalescingStatistics, CountWithChange, DataReaderStatus, DataReaderStatusKind,
+    DataWriterStatus, DomainParticipantStatusEvent, EndpointDescription, FanOutStatistics,
+    LivelinessChangedStatus, LostReason, ParticipantDescription, ReaderRepairLoad,
+    StatusChannelConfig, StatusEvented, StatusOverflowPolicy,
   },
-  topic::{Topic, TopicDescription, TopicKind},
+  topic::{Topic, TopicDescription, TopicKind, TopicTypeRegistry},
   typedesc::TypeDesc,
-  with_key::{datareader::SelectByKey, WriteOptions, WriteOptionsBuilder},
+  waitset::{
+    Condition, ConditionId, DataAvailableCondition, GuardCondition, StatusCondition, WaitSet,
+  },
+  with_key::{
+    datareader::SelectByKey, AmbiguousStatusInfo, DeliveryOrder, DuplicateAcceptance, ReadStart,
+    WriteOptions, WriteOptionsBuilder,
+  },
 };
 /// Needed to specify serialized data representation in case it is other than
 /// CDR.
 pub use serialization::RepresentationIdentifier;
 #[doc(inline)]
 pub use serialization::{
-  CDRDeserializerAdapter, CDRSerializerAdapter, CdrDeserializer, CdrSerializer,
+  CDR2SerializerAdapter, CDRDeserializerAdapter, CDRSerializerAdapter, CdrDeserializer,
+  CdrSerializer,
 };
+#[cfg(feature = "json")]
+#[doc(inline)]
+pub use serialization::JsonAdapter;
 /// Part of RTPS DATA submessage: 4-byte header + serialized data
 pub use messages::submessages::elements::serialized_payload::SerializedPayload;
 pub use structure::{
-  duration::Duration, entity::RTPSEntity, guid::GUID, rpc::SampleIdentity,
-  sequence_number::SequenceNumber, time::Timestamp,
+  duration::Duration,
+  entity::RTPSEntity,
+  guid::{GuidPrefix, GUID},
+  locator::{Locator, NotIpLocatorError},
+  rpc::SampleIdentity,
+  sequence_number::SequenceNumber,
+  time::{Clock, SystemClock, Timestamp},
+};
+pub use network::{
+  interface_filter::InterfaceFilter,
+  multicast::{DiscoveryMulticast, IpVersion, MulticastScope},
+  path_mtu::PathMtu,
 };
+#[cfg(feature = "tcp")]
+pub use network::tcp_transport::TcpTransport;
+pub use rtps::vendor_quirks::{QuirkMode, VendorQuirks};
 // re-export from a helper crate
 /// Helper pacakge to compute the CDR-serialized size of data
 pub use cdr_encoding_size;
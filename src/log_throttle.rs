@@ -0,0 +1,203 @@
+//! Throttling of log sites in per-message paths.
+//!
+//! A misbehaving or merely busy peer can trigger the same warning for every
+//! message it sends. The `*_throttled!` macros log the first occurrence at a
+//! site as usual, and then only count repeats until `LOG_THROTTLE_PERIOD` has
+//! passed. The next occurrence after that is logged together with the number
+//! of suppressed ones. If the condition does not recur, the count is logged
+//! on its own by [`flush_suppressed`], which the event loop of each
+//! participant calls periodically.
+//!
+//! Each macro invocation has its own throttle, shared by all participants of
+//! the process. Repeats are counted only while the log level is enabled.
+
+use std::{
+  sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Mutex, OnceLock,
+  },
+  time::{Duration, Instant},
+};
+
+pub(crate) const LOG_THROTTLE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Throttle state of a single log site.
+pub(crate) struct LogThrottle {
+  level: log::Level,
+  module: &'static str,
+  line: u32,
+  // Nanoseconds since `epoch()` when the next occurrence may be logged.
+  next_report: AtomicU64,
+  suppressed: AtomicU64,
+  // In `suppressing()`
+  registered: AtomicBool,
+}
+
+fn epoch() -> Instant {
+  static EPOCH: OnceLock<Instant> = OnceLock::new();
+  *EPOCH.get_or_init(Instant::now)
+}
+
+fn now() -> u64 {
+  u64::try_from(epoch().elapsed().as_nanos()).unwrap_or(u64::MAX)
+}
+
+// Throttles that have suppressed something, for `flush_suppressed`
+fn suppressing() -> &'static Mutex<Vec<&'static LogThrottle>> {
+  static SUPPRESSING: Mutex<Vec<&'static LogThrottle>> = Mutex::new(Vec::new());
+  &SUPPRESSING
+}
+
+/// Log the counts of suppressed occurrences whose log site has not logged in
+/// `LOG_THROTTLE_PERIOD`, so that a condition that stops recurring still gets
+/// its count logged.
+pub(crate) fn flush_suppressed() {
+  let now = now();
+  let throttles = suppressing().lock().unwrap().clone();
+  for throttle in throttles {
+    if let Some(suppressed) = throttle.flush_at(now, LOG_THROTTLE_PERIOD) {
+      log::log!(
+        target: throttle.module,
+        throttle.level,
+        "{suppressed} similar messages from {}:{} suppressed",
+        throttle.module,
+        throttle.line
+      );
+    }
+  }
+}
+
+impl LogThrottle {
+  pub const fn new(level: log::Level, module: &'static str, line: u32) -> Self {
+    Self {
+      level,
+      module,
+      line,
+      next_report: AtomicU64::new(0),
+      suppressed: AtomicU64::new(0),
+      registered: AtomicBool::new(false),
+    }
+  }
+
+  /// Should this occurrence be logged? If yes, returns the number of
+  /// occurrences suppressed since the previous one was logged.
+  pub fn check(&'static self) -> Option<u64> {
+    let report = self.check_at(now(), LOG_THROTTLE_PERIOD);
+    if report.is_none() && !self.registered.swap(true, Ordering::Relaxed) {
+      suppressing().lock().unwrap().push(self);
+    }
+    report
+  }
+
+  fn check_at(&self, now: u64, period: Duration) -> Option<u64> {
+    if self.may_report(now, period) {
+      Some(self.suppressed.swap(0, Ordering::Relaxed))
+    } else {
+      self.suppressed.fetch_add(1, Ordering::Relaxed);
+      None
+    }
+  }
+
+  // The number of suppressed occurrences, if there are any and they may be
+  // reported now.
+  fn flush_at(&self, now: u64, period: Duration) -> Option<u64> {
+    if self.suppressed.load(Ordering::Relaxed) > 0 && self.may_report(now, period) {
+      Some(self.suppressed.swap(0, Ordering::Relaxed)).filter(|s| *s > 0)
+    } else {
+      None
+    }
+  }
+
+  // Has the period passed since the last report? If yes, a new period starts.
+  fn may_report(&self, now: u64, period: Duration) -> bool {
+    let next_report = self.next_report.load(Ordering::Relaxed);
+    let period = u64::try_from(period.as_nanos()).unwrap_or(u64::MAX);
+    // Only one of concurrent callers wins the exchange and reports.
+    now >= next_report
+      && self
+        .next_report
+        .compare_exchange(
+          next_report,
+          now.saturating_add(period),
+          Ordering::Relaxed,
+          Ordering::Relaxed,
+        )
+        .is_ok()
+  }
+}
+
+// Log at `$level` through a throttle of this call site.
+macro_rules! log_throttled {
+  ($level:expr, $($arg:tt)+) => {{
+    static THROTTLE: $crate::log_throttle::LogThrottle =
+      $crate::log_throttle::LogThrottle::new($level, module_path!(), line!());
+    if log::log_enabled!($level) {
+      match THROTTLE.check() {
+        Some(0) => log::log!($level, $($arg)+),
+        Some(suppressed) => log::log!(
+          $level,
+          "{} ({} similar messages suppressed)",
+          format_args!($($arg)+),
+          suppressed
+        ),
+        None => (),
+      }
+    }
+  }};
+}
+
+macro_rules! warn_throttled {
+  ($($arg:tt)+) => {
+    log_throttled!(log::Level::Warn, $($arg)+)
+  };
+}
+
+macro_rules! info_throttled {
+  ($($arg:tt)+) => {
+    log_throttled!(log::Level::Info, $($arg)+)
+  };
+}
+
+macro_rules! debug_throttled {
+  ($($arg:tt)+) => {
+    log_throttled!(log::Level::Debug, $($arg)+)
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn repeats_are_counted_until_the_period_ends() {
+    let throttle = LogThrottle::new(log::Level::Warn, module_path!(), line!());
+    let period = Duration::from_nanos(100);
+
+    assert_eq!(throttle.check_at(1000, period), Some(0));
+    assert_eq!(throttle.check_at(1010, period), None);
+    assert_eq!(throttle.check_at(1099, period), None);
+    assert_eq!(throttle.check_at(1100, period), Some(2));
+    assert_eq!(throttle.check_at(1150, period), None);
+    // A quiet period does not lose the count.
+    assert_eq!(throttle.check_at(5000, period), Some(1));
+    assert_eq!(throttle.check_at(5100, period), Some(0));
+  }
+
+  #[test]
+  fn suppressed_count_is_flushed_after_the_period() {
+    let throttle = LogThrottle::new(log::Level::Warn, module_path!(), line!());
+    let period = Duration::from_nanos(100);
+
+    assert_eq!(throttle.check_at(1000, period), Some(0));
+    assert_eq!(throttle.flush_at(1050, period), None);
+    assert_eq!(throttle.check_at(1060, period), None);
+    assert_eq!(throttle.check_at(1070, period), None);
+    // Not before the period ends
+    assert_eq!(throttle.flush_at(1099, period), None);
+    assert_eq!(throttle.flush_at(1100, period), Some(2));
+    assert_eq!(throttle.flush_at(1120, period), None);
+    // The flush started a new period.
+    assert_eq!(throttle.check_at(1150, period), None);
+    assert_eq!(throttle.check_at(1200, period), Some(1));
+  }
+}
@@ -8,9 +8,14 @@ use log::{debug, error, info, trace, warn};
 
 use crate::{
   dds::key::KeyHash,
-  messages::submessages::elements::{parameter_list::ParameterList, RepresentationIdentifier},
+  messages::submessages::elements::{
+    parameter::Parameter, parameter_list::ParameterList, RepresentationIdentifier,
+  },
   serialization::{pl_cdr_adapters::PlCdrDeserializeError, speedy_pl_cdr_helpers::*},
-  structure::{cache_change::ChangeKind, parameter_id::ParameterId, rpc::SampleIdentity},
+  structure::{
+    cache_change::ChangeKind, parameter_id::ParameterId, rpc::SampleIdentity,
+    sequence_number::SequenceNumber,
+  },
 };
 #[cfg(test)]
 use crate::{
@@ -43,6 +48,22 @@ impl InlineQos {
     Ok(status_info)
   }
 
+  // SequenceNumber::UNKNOWN marks the end of a coherent set.
+  pub fn coherent_set(
+    params: &ParameterList,
+    rep_id: RepresentationIdentifier,
+  ) -> Result<Option<SequenceNumber>, PlCdrDeserializeError> {
+    let coherent_set = params
+      .parameters
+      .iter()
+      .find(|p| p.parameter_id == ParameterId::PID_COHERENT_SET);
+    let ctx = pl_cdr_rep_id_to_speedy_d(rep_id)?;
+    Ok(match coherent_set {
+      Some(p) => Some(SequenceNumber::read_from_buffer_with_ctx(ctx, &p.value)?),
+      None => None,
+    })
+  }
+
   pub fn key_hash(params: &ParameterList) -> Result<Option<KeyHash>, PlCdrDeserializeError> {
     let key_hash = params
       .parameters
@@ -119,7 +140,8 @@ pub struct StatusInfo {
                                  *
                                  * There may be several flags set at the same time.
                                  *
-                                 * Disposed & Unregistered:
+                                 * Disposed & Unregistered => DataWriter disposed and
+                                 * unregistered the instance in one change.
                                  *
                                  * Meanings of some combinations are unknown:
                                  * Disposed & Filtered : ???
@@ -162,20 +184,54 @@ impl StatusInfo {
     }
   }
 
+  pub fn new(disposed: bool, unregistered: bool, filtered: bool) -> Self {
+    let mut si = BitFlags::empty();
+    if disposed {
+      si |= StatusInfoEnum::Disposed;
+    }
+    if unregistered {
+      si |= StatusInfoEnum::Unregistered;
+    }
+    if filtered {
+      si |= StatusInfoEnum::Filtered;
+    }
+    Self { em: [0; 3], si }
+  }
+
+  /// StatusInfo announcing `change_kind`, as in RTPS spec v2.5 Section 8.7.4
+  /// "Changes in the Instance State".
+  pub fn from_change_kind(change_kind: ChangeKind) -> Self {
+    match change_kind {
+      ChangeKind::Alive => Self::empty(),
+      ChangeKind::NotAliveDisposed => Self::new(true, false, false),
+      ChangeKind::NotAliveUnregistered => Self::new(false, true, false),
+      ChangeKind::NotAliveDisposedUnregistered => Self::new(true, true, false),
+    }
+  }
+
   pub fn contains(&self, sie: StatusInfoEnum) -> bool {
     self.si.contains(sie)
   }
 
   pub fn change_kind(&self) -> ChangeKind {
-    if self.contains(StatusInfoEnum::Disposed) {
-      // DISPOSED is strongest
-      ChangeKind::NotAliveDisposed
-    } else if self.contains(StatusInfoEnum::Unregistered) {
-      // Checking unregistered second
-      ChangeKind::NotAliveUnregistered
-    } else {
+    match (
+      self.contains(StatusInfoEnum::Disposed),
+      self.contains(StatusInfoEnum::Unregistered),
+    ) {
+      (true, true) => ChangeKind::NotAliveDisposedUnregistered,
+      (true, false) => ChangeKind::NotAliveDisposed,
+      (false, true) => ChangeKind::NotAliveUnregistered,
       // Even if filtered is set it is still alive
-      ChangeKind::Alive
+      (false, false) => ChangeKind::Alive,
+    }
+  }
+
+  // The PID_STATUS_INFO parameter. The flags are in the last octet, so the
+  // encoding does not depend on endianness.
+  pub fn into_parameter(self) -> Parameter {
+    Parameter {
+      parameter_id: ParameterId::PID_STATUS_INFO,
+      value: vec![0, 0, 0, self.si.bits()],
     }
   }
 
@@ -241,4 +297,50 @@ mod tests {
       }
     );
   }
+
+  #[test]
+  fn status_info_bit_combinations() {
+    // (D, U, F) flags => ChangeKind, RTPS spec v2.5 Section 9.6.4.9
+    let table = [
+      (0b000, ChangeKind::Alive),
+      (0b001, ChangeKind::NotAliveDisposed),
+      (0b010, ChangeKind::NotAliveUnregistered),
+      (0b011, ChangeKind::NotAliveDisposedUnregistered),
+      (0b100, ChangeKind::Alive),
+      (0b101, ChangeKind::NotAliveDisposed),
+      (0b110, ChangeKind::NotAliveUnregistered),
+      (0b111, ChangeKind::NotAliveDisposedUnregistered),
+    ];
+    for (bits, change_kind) in table {
+      let status_info = StatusInfo::new(bits & 1 != 0, bits & 2 != 0, bits & 4 != 0);
+      let parameter = status_info.into_parameter();
+      assert_eq!(parameter.value, vec![0, 0, 0, bits]);
+
+      let mut params = ParameterList::new();
+      params.push(parameter);
+      for rep_id in [
+        RepresentationIdentifier::PL_CDR_LE,
+        RepresentationIdentifier::PL_CDR_BE,
+      ] {
+        let received = InlineQos::status_info(&params, rep_id).unwrap();
+        assert_eq!(received, status_info);
+        assert_eq!(received.change_kind(), change_kind, "flags {bits:#05b}");
+      }
+      if bits & 4 == 0 {
+        assert_eq!(StatusInfo::from_change_kind(change_kind), status_info);
+      }
+    }
+
+    // Reserved bits are ignored, and a missing StatusInfo means alive.
+    let mut params = ParameterList::new();
+    params.push(Parameter {
+      parameter_id: ParameterId::PID_STATUS_INFO,
+      value: vec![0xff, 0xff, 0xff, 0xf9],
+    });
+    let received = InlineQos::status_info(&params, RepresentationIdentifier::PL_CDR_LE).unwrap();
+    assert_eq!(received.change_kind(), ChangeKind::NotAliveDisposed);
+    let received =
+      InlineQos::status_info(&ParameterList::new(), RepresentationIdentifier::PL_CDR_LE).unwrap();
+    assert_eq!(received.change_kind(), ChangeKind::Alive);
+  }
 }
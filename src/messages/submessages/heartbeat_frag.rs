@@ -1,10 +1,21 @@
+use std::mem::size_of;
+
+use enumflags2::BitFlags;
 use speedy::{Readable, Writable};
 
-use crate::structure::{
-  guid::EntityId,
-  sequence_number::{FragmentNumber, SequenceNumber},
+use crate::{
+  messages::submessages::submessages::SubmessageHeader,
+  rtps::{Submessage, SubmessageBody},
+  structure::{
+    guid::EntityId,
+    sequence_number::{FragmentNumber, SequenceNumber},
+  },
+};
+use super::{
+  submessage::{HasEntityIds, WriterSubmessage},
+  submessage_flag::HEARTBEATFRAG_Flags,
+  submessage_kind::SubmessageKind,
 };
-use super::submessage::HasEntityIds;
 
 /// When fragmenting data and until all fragments are available, the
 /// HeartbeatFrag Submessage is sent from an RTPS Writer to an RTPS Reader to
@@ -37,6 +48,28 @@ pub struct HeartbeatFrag {
   pub count: i32,
 }
 
+#[allow(dead_code)] // RustDDS Writers do not send HEARTBEAT_FRAG yet
+impl HeartbeatFrag {
+  pub fn create_submessage(self, flags: BitFlags<HEARTBEATFRAG_Flags>) -> Submessage {
+    Submessage {
+      header: SubmessageHeader {
+        kind: SubmessageKind::HEARTBEAT_FRAG,
+        flags: flags.bits(),
+        content_length: self.len_serialized() as u16,
+      },
+      body: SubmessageBody::Writer(WriterSubmessage::HeartbeatFrag(self, flags)),
+      original_bytes: None,
+    }
+  }
+
+  pub fn len_serialized(&self) -> usize {
+    size_of::<EntityId>() * 2
+      + size_of::<SequenceNumber>()
+      + size_of::<FragmentNumber>()
+      + size_of::<i32>()
+  }
+}
+
 impl HasEntityIds for HeartbeatFrag {
   fn receiver_entity_id(&self) -> EntityId {
     self.reader_id
@@ -25,7 +25,6 @@ pub enum WriterSubmessage {
   DataFrag(DataFrag, BitFlags<DATAFRAG_Flags>),
   Gap(Gap, BitFlags<GAP_Flags>),
   Heartbeat(Heartbeat, BitFlags<HEARTBEAT_Flags>),
-  #[allow(dead_code)] // Functionality not yet implemented
   HeartbeatFrag(HeartbeatFrag, BitFlags<HEARTBEATFRAG_Flags>),
 }
 
@@ -1,4 +1,14 @@
 pub mod constant;
+pub mod interface_filter;
+pub mod multicast;
+pub mod path_mtu;
+#[cfg(feature = "tcp")]
+pub mod tcp_listener;
+#[cfg(feature = "tcp")]
+pub mod tcp_sender;
+#[cfg(feature = "tcp")]
+pub mod tcp_transport;
+pub mod transport;
 pub mod udp_listener;
 pub mod udp_sender;
 pub mod util;
@@ -24,6 +24,12 @@ pub const fn user_traffic_unicast_port(domain_id: u16, participant_id: u16) -> u
   PB + DG * domain_id + D3 + PG * participant_id
 }
 
+// Participant ids are in 0..MAX_PARTICIPANT_ID. The spec does not limit them,
+// but the number comes from RTPS spec 2.5 Section "9.6.2.3 Default Port
+// Numbers", which shows that the unicast ports of participant 120 would
+// collide with the multicast ports of the next domain.
+pub const MAX_PARTICIPANT_ID: u16 = 120;
+
 /// Default number of same-host participant slots probed by unicast SPDP
 /// "localhost peers". SPDP is additionally sent to
 /// `127.0.0.1:spdp_well_known_unicast_port(domain, pid)` for
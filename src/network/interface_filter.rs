@@ -0,0 +1,200 @@
+//! Selection of the local network interfaces a participant uses.
+
+use std::net::IpAddr;
+
+use crate::network::util::IfAddr;
+
+/// Network interface selection, set with
+/// [`DomainParticipantBuilder::network_interfaces`](crate::DomainParticipantBuilder::network_interfaces).
+///
+/// Interfaces are identified by OS interface name (e.g. `eth0`) or by one of
+/// their addresses. An address selects the whole interface it is assigned to,
+/// including its other addresses.
+///
+/// If any interface is allowed, only the allowed interfaces are used.
+/// Otherwise all interfaces are used. Denied interfaces are never used, even if
+/// also allowed.
+///
+/// The participant advertises unicast locators, joins multicast groups and
+/// sends multicast only on the selected interfaces. They are the same
+/// interfaces for all three, so replies to multicast discovery arrive on a
+/// selected interface. Unicast sockets still bind to the wildcard address.
+///
+/// The filter applies to enumerated interfaces only. Addresses configured with
+/// [`advertised_addresses`](crate::DomainParticipantBuilder::advertised_addresses)
+/// or
+/// [`fallback_addresses`](crate::DomainParticipantBuilder::fallback_addresses)
+/// are used as given. If no interface is selected,
+/// [`build`](crate::DomainParticipantBuilder::build) fails, because the
+/// participant would have no locators to advertise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InterfaceFilter {
+  allowed: Vec<InterfaceId>,
+  denied: Vec<InterfaceId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum InterfaceId {
+  Name(String),
+  Address(IpAddr),
+}
+
+impl InterfaceId {
+  // Does `ifa` belong to the interface identified by `self`? `ifaces` is the
+  // whole interface table, to find the other addresses of an interface.
+  fn matches(&self, ifa: &IfAddr, ifaces: &[IfAddr]) -> bool {
+    match self {
+      Self::Name(name) => ifa.name == *name,
+      Self::Address(addr) => {
+        ifa.ip == *addr
+          || (!ifa.name.is_empty()
+            && ifaces
+              .iter()
+              .any(|other| other.name == ifa.name && other.ip == *addr))
+      }
+    }
+  }
+}
+
+impl InterfaceFilter {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Use the interface named `name`.
+  pub fn allow_name(mut self, name: impl Into<String>) -> Self {
+    self.allowed.push(InterfaceId::Name(name.into()));
+    self
+  }
+
+  /// Use the interface that has the address `addr`.
+  pub fn allow_address(mut self, addr: impl Into<IpAddr>) -> Self {
+    self.allowed.push(InterfaceId::Address(addr.into()));
+    self
+  }
+
+  /// Do not use the interface named `name`.
+  pub fn deny_name(mut self, name: impl Into<String>) -> Self {
+    self.denied.push(InterfaceId::Name(name.into()));
+    self
+  }
+
+  /// Do not use the interface that has the address `addr`.
+  pub fn deny_address(mut self, addr: impl Into<IpAddr>) -> Self {
+    self.denied.push(InterfaceId::Address(addr.into()));
+    self
+  }
+
+  /// Does the filter select all interfaces?
+  pub fn is_empty(&self) -> bool {
+    self.allowed.is_empty() && self.denied.is_empty()
+  }
+
+  /// Check that interface names are not empty and addresses are not
+  /// unspecified.
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    for id in self.allowed.iter().chain(&self.denied) {
+      match id {
+        InterfaceId::Name(name) if name.is_empty() => {
+          return Err("Interface name is empty".to_string());
+        }
+        InterfaceId::Address(addr) if addr.is_unspecified() => {
+          return Err(format!("{addr} does not identify an interface"));
+        }
+        _ => {}
+      }
+    }
+    Ok(())
+  }
+
+  /// The addresses of `ifaces` that are on selected interfaces.
+  pub(crate) fn apply(&self, ifaces: Vec<IfAddr>) -> Vec<IfAddr> {
+    if self.is_empty() {
+      return ifaces;
+    }
+    let selected = |ifa: &IfAddr| {
+      (self.allowed.is_empty() || self.allowed.iter().any(|id| id.matches(ifa, &ifaces)))
+        && !self.denied.iter().any(|id| id.matches(ifa, &ifaces))
+    };
+    let keep: Vec<bool> = ifaces.iter().map(selected).collect();
+    ifaces
+      .into_iter()
+      .zip(keep)
+      .filter_map(|(ifa, keep)| keep.then_some(ifa))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+  }
+
+  fn iface(name: &str, addr: &str) -> IfAddr {
+    IfAddr {
+      name: name.to_string(),
+      ip: ip(addr),
+      index: 1,
+      is_loopback: name == "lo",
+      is_multicast: name != "lo",
+      netmask: None,
+      mtu: None,
+    }
+  }
+
+  fn table() -> Vec<IfAddr> {
+    vec![
+      iface("lo", "127.0.0.1"),
+      iface("eth0", "192.168.1.10"),
+      iface("eth0", "fe80::1"),
+      iface("wlan0", "10.0.0.7"),
+    ]
+  }
+
+  fn ips(ifaces: Vec<IfAddr>) -> Vec<IpAddr> {
+    ifaces.into_iter().map(|ifa| ifa.ip).collect()
+  }
+
+  #[test]
+  fn empty_filter_selects_everything() {
+    assert_eq!(InterfaceFilter::new().apply(table()), table());
+  }
+
+  #[test]
+  fn allowed_interface_is_selected_with_all_its_addresses() {
+    let by_name = InterfaceFilter::new().allow_name("eth0");
+    assert_eq!(
+      ips(by_name.apply(table())),
+      vec![ip("192.168.1.10"), ip("fe80::1")]
+    );
+    let by_address = InterfaceFilter::new().allow_address(ip("192.168.1.10"));
+    assert_eq!(by_address.apply(table()), by_name.apply(table()));
+  }
+
+  #[test]
+  fn denied_interface_is_not_selected() {
+    let filter = InterfaceFilter::new().deny_name("wlan0");
+    assert_eq!(
+      ips(filter.apply(table())),
+      vec![ip("127.0.0.1"), ip("192.168.1.10"), ip("fe80::1")]
+    );
+    let filter = InterfaceFilter::new()
+      .allow_name("eth0")
+      .allow_name("wlan0")
+      .deny_address(ip("fe80::1"));
+    assert_eq!(ips(filter.apply(table())), vec![ip("10.0.0.7")]);
+  }
+
+  #[test]
+  fn invalid_configurations_are_rejected() {
+    assert!(InterfaceFilter::new().validate().is_ok());
+    assert!(InterfaceFilter::new().allow_name("").validate().is_err());
+    assert!(InterfaceFilter::new()
+      .deny_address(ip("0.0.0.0"))
+      .validate()
+      .is_err());
+  }
+}
@@ -0,0 +1,260 @@
+//! Multicast group, scope and TTL used for discovery.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// IP version of discovery multicast.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IpVersion {
+  #[default]
+  V4,
+  V6,
+}
+
+impl IpVersion {
+  pub(crate) fn of(addr: &IpAddr) -> Self {
+    match addr {
+      IpAddr::V4(_) => Self::V4,
+      IpAddr::V6(_) => Self::V6,
+    }
+  }
+}
+
+/// How far discovery multicast is allowed to travel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MulticastScope {
+  /// The local link only. Routers do not forward discovery traffic, so only
+  /// participants on the same subnet are discovered by multicast.
+  #[default]
+  LinkLocal,
+  /// A site, e.g. a building or campus network of several routed subnets.
+  SiteLocal,
+  /// The routed networks of an organization, possibly spanning several sites.
+  OrganizationLocal,
+}
+
+impl MulticastScope {
+  /// Default IPv4 TTL or IPv6 hop limit for multicast of this scope. These are
+  /// the conventional scope thresholds of IPv4 multicast routing.
+  pub fn default_ttl(self) -> u32 {
+    match self {
+      Self::LinkLocal => 1,
+      Self::SiteLocal => 32,
+      Self::OrganizationLocal => 64,
+    }
+  }
+
+  // Value of the scope field of an IPv6 multicast address (RFC 4291).
+  fn ipv6_scope(self) -> u16 {
+    match self {
+      Self::LinkLocal => 0x2,
+      Self::SiteLocal => 0x5,
+      Self::OrganizationLocal => 0x8,
+    }
+  }
+}
+
+/// Discovery (SPDP) multicast configuration, set with
+/// [`DomainParticipantBuilder::discovery_multicast`](crate::DomainParticipantBuilder::discovery_multicast).
+///
+/// By default the group is chosen by scope and IP version:
+///
+/// * IPv4: 239.255.0.1, the group of the RTPS specification, for every scope.
+///   IPv4 scope is enforced by the TTL.
+/// * IPv6: the IPv4 group mapped into an IPv6 multicast address of the scope,
+///   i.e. `ff02::ffff:239.255.0.1` (link), `ff05::ffff:239.255.0.1` (site) or
+///   `ff08::ffff:239.255.0.1` (organization).
+///
+/// The TTL (IPv4) or hop limit (IPv6) defaults to
+/// [`MulticastScope::default_ttl`]. It is set on all multicast sender sockets
+/// of the chosen IP version, so it applies to user traffic sent by multicast,
+/// too.
+///
+/// Other implementations discover a RustDDS participant by multicast only if
+/// they use the same group, so non-default groups and IPv6 need matching
+/// configuration on the remote side.
+///
+/// With IPv6, the participant listens to unicast on IPv6 in addition to IPv4,
+/// and advertises locators of both. User traffic multicast stays on IPv4.
+/// Locators do not carry an IPv6 scope id, so link-local peers are sent to
+/// through the first interface that has a link-local address. On hosts
+/// attached to several links, advertise global or unique local addresses with
+/// [`advertised_addresses`](crate::DomainParticipantBuilder::advertised_addresses).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiscoveryMulticast {
+  scope: MulticastScope,
+  ip_version: IpVersion,
+  group: Option<IpAddr>,
+  ttl: Option<u32>,
+}
+
+impl DiscoveryMulticast {
+  /// The SPDP multicast group of the RTPS specification.
+  pub const DEFAULT_IPV4_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 0, 1);
+
+  pub fn new(scope: MulticastScope, ip_version: IpVersion) -> Self {
+    Self {
+      scope,
+      ip_version,
+      group: None,
+      ttl: None,
+    }
+  }
+
+  /// Use `group` instead of the default group of the scope.
+  pub fn with_group(mut self, group: IpAddr) -> Self {
+    self.group = Some(group);
+    self
+  }
+
+  /// Use `ttl` as the IPv4 TTL or IPv6 hop limit instead of the default of the
+  /// scope.
+  pub fn with_ttl(mut self, ttl: u32) -> Self {
+    self.ttl = Some(ttl);
+    self
+  }
+
+  pub fn scope(&self) -> MulticastScope {
+    self.scope
+  }
+
+  pub fn ip_version(&self) -> IpVersion {
+    self.ip_version
+  }
+
+  /// The multicast group discovery is sent to and listened on.
+  pub fn group(&self) -> IpAddr {
+    self.group.unwrap_or_else(|| match self.ip_version {
+      IpVersion::V4 => IpAddr::V4(Self::DEFAULT_IPV4_GROUP),
+      IpVersion::V6 => {
+        let [a, b, c, d] = Self::DEFAULT_IPV4_GROUP.octets();
+        IpAddr::V6(Ipv6Addr::new(
+          0xff00 | self.scope.ipv6_scope(),
+          0,
+          0,
+          0,
+          0,
+          0xffff,
+          u16::from_be_bytes([a, b]),
+          u16::from_be_bytes([c, d]),
+        ))
+      }
+    })
+  }
+
+  /// The IPv4 TTL or IPv6 hop limit of multicast sent by the participant.
+  pub fn ttl(&self) -> u32 {
+    self.ttl.unwrap_or_else(|| self.scope.default_ttl())
+  }
+
+  /// Check that the group is a multicast address of the configured IP version
+  /// and scope, and that the TTL is usable.
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    let group = self.group();
+    if !group.is_multicast() {
+      return Err(format!("{group} is not a multicast address"));
+    }
+    if IpVersion::of(&group) != self.ip_version {
+      return Err(format!(
+        "Multicast group {group} is not an {:?} address",
+        self.ip_version
+      ));
+    }
+    match group {
+      // 224.0.0.0/24 is never forwarded by routers.
+      IpAddr::V4(g)
+        if g.octets()[..3] == [224, 0, 0] && self.scope != MulticastScope::LinkLocal =>
+      {
+        return Err(format!(
+          "Multicast group {group} is link-local, but scope is {:?}",
+          self.scope
+        ));
+      }
+      IpAddr::V6(g) if g.segments()[0] & 0x000f != self.scope.ipv6_scope() => {
+        return Err(format!(
+          "Scope of multicast group {group} does not match scope {:?}",
+          self.scope
+        ));
+      }
+      _ => {}
+    }
+    if !(1..=255).contains(&self.ttl()) {
+      return Err(format!(
+        "Multicast TTL must be in range 1..=255, got {}",
+        self.ttl()
+      ));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn default_groups_and_ttls() {
+    let default = DiscoveryMulticast::default();
+    assert_eq!(default.group(), IpAddr::from([239, 255, 0, 1]));
+    assert_eq!(default.ttl(), 1);
+    assert!(default.validate().is_ok());
+
+    let site_v4 = DiscoveryMulticast::new(MulticastScope::SiteLocal, IpVersion::V4);
+    assert_eq!(site_v4.group(), IpAddr::from([239, 255, 0, 1]));
+    assert_eq!(site_v4.ttl(), 32);
+    assert!(site_v4.validate().is_ok());
+
+    let org_v6 = DiscoveryMulticast::new(MulticastScope::OrganizationLocal, IpVersion::V6);
+    assert_eq!(
+      org_v6.group(),
+      "ff08::ffff:239.255.0.1".parse::<IpAddr>().unwrap()
+    );
+    assert_eq!(org_v6.ttl(), 64);
+    assert!(org_v6.validate().is_ok());
+
+    let link_v6 = DiscoveryMulticast::new(MulticastScope::LinkLocal, IpVersion::V6);
+    assert_eq!(
+      link_v6.group(),
+      "ff02::ffff:239.255.0.1".parse::<IpAddr>().unwrap()
+    );
+    assert!(link_v6.validate().is_ok());
+  }
+
+  #[test]
+  fn invalid_configurations_are_rejected() {
+    let site_v4 = DiscoveryMulticast::new(MulticastScope::SiteLocal, IpVersion::V4);
+    let site_v6 = DiscoveryMulticast::new(MulticastScope::SiteLocal, IpVersion::V6);
+
+    // not multicast
+    assert!(site_v4
+      .with_group(IpAddr::from([10, 0, 0, 1]))
+      .validate()
+      .is_err());
+    // wrong IP version
+    assert!(site_v4
+      .with_group("ff05::1".parse().unwrap())
+      .validate()
+      .is_err());
+    assert!(site_v6
+      .with_group(IpAddr::from([239, 255, 0, 1]))
+      .validate()
+      .is_err());
+    // wrong scope
+    assert!(site_v6
+      .with_group("ff02::1".parse().unwrap())
+      .validate()
+      .is_err());
+    assert!(site_v4
+      .with_group(IpAddr::from([224, 0, 0, 200]))
+      .validate()
+      .is_err());
+    // bad TTL
+    assert!(site_v4.with_ttl(0).validate().is_err());
+    assert!(site_v4.with_ttl(256).validate().is_err());
+
+    assert!(site_v6
+      .with_group("ff05::1:2".parse().unwrap())
+      .with_ttl(255)
+      .validate()
+      .is_ok());
+  }
+}
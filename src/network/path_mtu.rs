@@ -0,0 +1,217 @@
+//! Configured path MTU to remote destinations.
+
+use std::net::IpAddr;
+
+use crate::network::util::{interface_mtu_for_peer, IfAddr};
+
+/// Path MTU configuration, set with
+/// [`DomainParticipantBuilder::path_mtu`](crate::DomainParticipantBuilder::path_mtu).
+///
+/// A DataWriter packs submessages and decides whether to fragment a sample by
+/// the path MTU to each matched DataReader. The path MTU to a reader locator
+/// is, in order of preference:
+///
+/// 1. The MTU of the most specific configured destination network containing
+///    the locator address, see [`with_destination`](Self::with_destination).
+/// 2. The MTU of the local interface, if the locator is in the IPv4 subnet of a
+///    local interface.
+/// 3. The default MTU, 1500 bytes unless set with
+///    [`with_default`](Self::with_default).
+///
+/// The smallest path MTU over the locators of a reader is used for that
+/// reader. Configuring a destination is needed when a peer is behind a link
+/// with a smaller MTU than the local network, e.g. a VPN or a tunnel, to avoid
+/// IP fragmentation.
+///
+/// The fragment size of DATAFRAG submessages does not depend on the path MTU,
+/// because it must be the same for all readers of a writer. The path MTU
+/// decides how large a sample is sent without fragmenting, and how many
+/// fragments are packed in one datagram.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathMtu {
+  destinations: Vec<Destination>,
+  default_mtu: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Destination {
+  network: IpAddr,
+  prefix_len: u8,
+  mtu: u32,
+}
+
+impl Destination {
+  fn contains(&self, addr: IpAddr) -> bool {
+    match (self.network, addr) {
+      (IpAddr::V4(net), IpAddr::V4(addr)) => {
+        let mask = u32::MAX
+          .checked_shl(32u32.saturating_sub(self.prefix_len.into()))
+          .unwrap_or(0);
+        u32::from(net) & mask == u32::from(addr) & mask
+      }
+      (IpAddr::V6(net), IpAddr::V6(addr)) => {
+        let mask = u128::MAX
+          .checked_shl(128u32.saturating_sub(self.prefix_len.into()))
+          .unwrap_or(0);
+        u128::from(net) & mask == u128::from(addr) & mask
+      }
+      _ => false,
+    }
+  }
+}
+
+impl PathMtu {
+  /// The path MTU used when nothing else is known.
+  pub const DEFAULT_MTU: u32 = 1500;
+
+  /// The smallest MTU accepted. Every IPv4 host must accept datagrams of this
+  /// size.
+  pub const MIN_MTU: u32 = 576;
+
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Use `mtu` as the path MTU to the addresses in `network`/`prefix_len`,
+  /// e.g. `10.8.0.0`/`16`. If several configured networks contain an
+  /// address, the one with the longest prefix is used.
+  pub fn with_destination(mut self, network: IpAddr, prefix_len: u8, mtu: u32) -> Self {
+    self.destinations.push(Destination {
+      network,
+      prefix_len,
+      mtu,
+    });
+    self
+  }
+
+  /// Use `mtu` as the path MTU to destinations that are neither configured nor
+  /// in a local subnet, instead of [`DEFAULT_MTU`](Self::DEFAULT_MTU).
+  pub fn with_default(mut self, mtu: u32) -> Self {
+    self.default_mtu = Some(mtu);
+    self
+  }
+
+  /// The path MTU to destinations that are neither configured nor in a local
+  /// subnet.
+  pub fn default_mtu(&self) -> u32 {
+    self.default_mtu.unwrap_or(Self::DEFAULT_MTU)
+  }
+
+  /// The configured path MTU to `destination`, if it is in a configured
+  /// destination network.
+  pub fn configured_mtu(&self, destination: IpAddr) -> Option<u32> {
+    self
+      .destinations
+      .iter()
+      .filter(|d| d.contains(destination))
+      .max_by_key(|d| d.prefix_len)
+      .map(|d| d.mtu)
+  }
+
+  /// Check that the prefix lengths fit the IP versions of the networks, and
+  /// that the MTUs are in range.
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    let check_mtu = |mtu: u32| {
+      if (Self::MIN_MTU..=u32::from(u16::MAX)).contains(&mtu) {
+        Ok(())
+      } else {
+        Err(format!(
+          "Path MTU must be in range {}..={}, got {mtu}",
+          Self::MIN_MTU,
+          u16::MAX
+        ))
+      }
+    };
+    for d in &self.destinations {
+      let max_prefix_len = if d.network.is_ipv4() { 32 } else { 128 };
+      if d.prefix_len > max_prefix_len {
+        return Err(format!(
+          "Prefix length of {}/{} is longer than the address",
+          d.network, d.prefix_len
+        ));
+      }
+      check_mtu(d.mtu)?;
+    }
+    check_mtu(self.default_mtu())
+  }
+
+  /// The path MTU to `destination`, preferring a configured one over the MTU
+  /// of the local interface in the same subnet over the default.
+  pub(crate) fn mtu_for(&self, local_interfaces: &[IfAddr], destination: IpAddr) -> u32 {
+    self
+      .configured_mtu(destination)
+      .or_else(|| interface_mtu_for_peer(local_interfaces, destination))
+      .unwrap_or_else(|| self.default_mtu())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::net::Ipv4Addr;
+
+  use super::*;
+
+  fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+  }
+
+  fn iface(addr: [u8; 4], prefix: u8, mtu: u32) -> IfAddr {
+    IfAddr {
+      name: "eth0".to_string(),
+      ip: IpAddr::from(addr),
+      index: 1,
+      is_loopback: false,
+      is_multicast: true,
+      netmask: Some(Ipv4Addr::from(u32::MAX << (32 - prefix))),
+      mtu: Some(mtu),
+    }
+  }
+
+  #[test]
+  fn longest_configured_prefix_wins() {
+    let path_mtu = PathMtu::new()
+      .with_destination(ip("10.0.0.0"), 8, 1400)
+      .with_destination(ip("10.8.0.0"), 16, 1280)
+      .with_destination(ip("fd00::"), 8, 1300);
+
+    assert_eq!(path_mtu.configured_mtu(ip("10.1.2.3")), Some(1400));
+    assert_eq!(path_mtu.configured_mtu(ip("10.8.2.3")), Some(1280));
+    assert_eq!(path_mtu.configured_mtu(ip("fd12::1")), Some(1300));
+    assert_eq!(path_mtu.configured_mtu(ip("192.168.1.1")), None);
+    assert_eq!(path_mtu.configured_mtu(ip("fe80::1")), None);
+  }
+
+  #[test]
+  fn configured_mtu_overrides_interface_mtu() {
+    let ifaces = [iface([192, 168, 1, 10], 24, 9000)];
+    let path_mtu = PathMtu::new().with_destination(ip("192.168.1.128"), 25, 1400);
+
+    assert_eq!(path_mtu.mtu_for(&ifaces, ip("192.168.1.200")), 1400);
+    assert_eq!(path_mtu.mtu_for(&ifaces, ip("192.168.1.20")), 9000);
+    assert_eq!(path_mtu.mtu_for(&ifaces, ip("172.16.0.1")), 1500);
+    assert_eq!(
+      PathMtu::new()
+        .with_default(1400)
+        .mtu_for(&ifaces, ip("172.16.0.1")),
+      1400
+    );
+  }
+
+  #[test]
+  fn invalid_configurations_are_rejected() {
+    assert!(PathMtu::default().validate().is_ok());
+    assert!(PathMtu::new()
+      .with_destination(ip("10.0.0.0"), 33, 1400)
+      .validate()
+      .is_err());
+    assert!(PathMtu::new()
+      .with_destination(ip("fd00::"), 64, 1280)
+      .validate()
+      .is_ok());
+    assert!(PathMtu::new()
+      .with_destination(ip("10.0.0.0"), 8, 500)
+      .validate()
+      .is_err());
+    assert!(PathMtu::new().with_default(70000).validate().is_err());
+  }
+}
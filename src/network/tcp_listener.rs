@@ -0,0 +1,601 @@
+use std::{
+  collections::{HashMap, HashSet},
+  io::{self, Write},
+  net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream},
+  sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+  thread,
+  time::Duration,
+};
+
+use bytes::Bytes;
+use log::{debug, info, trace, warn};
+use mio_extras::channel as mio_channel;
+
+use crate::{
+  messages::protocol_version::ProtocolVersion,
+  network::{
+    multicast::IpVersion,
+    tcp_transport::{
+      read_frame, set_tcp_keep_alive, tcp_locator, Control, ControlMessage, ResponseCode,
+      CONTROL_PORT,
+    },
+    transport::TransportListener,
+    udp_listener::PacketOrigin,
+    util::{get_local_unicast_locators_filtered, InterfaceConfig},
+  },
+  structure::locator::Locator,
+};
+
+// Received messages waiting for the event loop. When this is full, the
+// connections stop being read, so that TCP flow control slows the senders.
+const RECEIVE_QUEUE_LEN: usize = 1024;
+
+// A connection is closed when nothing, not even a keep-alive, has been
+// received on it in this many keep-alive intervals.
+const IDLE_KEEP_ALIVE_INTERVALS: u32 = 3;
+
+/// Accepts TCP connections and receives RTPS messages from them.
+///
+/// Connections are read by threads of their own, which answer the control
+/// messages of the TCP/IP PSM and pass the RTPS messages to the event loop
+/// through a channel. RTPS messages are accepted only on bound connections,
+/// and only at the logical ports the peer has opened. Connections beyond the
+/// maximum number are closed right away. With keep-alives, connections that
+/// stay silent for too long are closed.
+pub struct TCPListener {
+  local_addr: SocketAddr,
+  // Logical ports that can be opened. The first one is advertised.
+  logical_ports: Arc<[u16]>,
+  receiver: mio_channel::Receiver<(Bytes, PacketOrigin)>,
+  connections: Arc<Connections>,
+}
+
+// Open connections, so that they can be closed when the listener is dropped.
+#[derive(Default)]
+struct Connections {
+  closing: AtomicBool,
+  next_id: AtomicU64,
+  streams: Mutex<HashMap<u64, TcpStream>>,
+}
+
+impl Drop for TCPListener {
+  fn drop(&mut self) {
+    self.connections.closing.store(true, Ordering::Release);
+    for stream in self.connections.streams.lock().unwrap().values() {
+      stream.shutdown(Shutdown::Both).unwrap_or_else(|e| {
+        debug!("Cannot shut down TCP connection: {e}");
+      });
+    }
+    // Wake up the accepting thread, so that it sees we are closing.
+    let wake_ip = match self.local_addr.ip() {
+      IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+      IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+      ip => ip,
+    };
+    if let Err(e) = TcpStream::connect(SocketAddr::new(wake_ip, self.local_addr.port())) {
+      debug!("Cannot wake up TCP accept thread: {e}");
+    }
+  }
+}
+
+impl TCPListener {
+  pub fn new(
+    host: &str,
+    port: u16,
+    logical_ports: &[u16],
+    keep_alive: Option<Duration>,
+    max_connections: usize,
+  ) -> io::Result<Self> {
+    let address = SocketAddr::new(host.parse().map_err(io::Error::other)?, port);
+    let listener = TcpListener::bind(address).inspect_err(|e| {
+      info!("TCPListener - cannot bind to {address}: {e:?}");
+    })?;
+    let local_addr = listener.local_addr()?;
+    let (sender, receiver) = mio_channel::sync_channel(RECEIVE_QUEUE_LEN);
+    let connections = Arc::new(Connections::default());
+    let logical_ports: Arc<[u16]> = Arc::from(logical_ports);
+
+    let accept_connections = Arc::clone(&connections);
+    let accept_logical_ports = Arc::clone(&logical_ports);
+    thread::Builder::new()
+      .name(format!("RustDDS TCP accept {local_addr}"))
+      .spawn(move || {
+        accept_loop(
+          &listener,
+          &sender,
+          &accept_connections,
+          &accept_logical_ports,
+          keep_alive,
+          max_connections,
+        );
+      })?;
+    info!("TCPListener: listening on {local_addr}");
+
+    Ok(Self {
+      local_addr,
+      logical_ports,
+      receiver,
+      connections,
+    })
+  }
+
+  pub fn port(&self) -> u16 {
+    self.local_addr.port()
+  }
+}
+
+fn accept_loop(
+  listener: &TcpListener,
+  sender: &mio_channel::SyncSender<(Bytes, PacketOrigin)>,
+  connections: &Arc<Connections>,
+  logical_ports: &Arc<[u16]>,
+  keep_alive: Option<Duration>,
+  max_connections: usize,
+) {
+  for stream in listener.incoming() {
+    if connections.closing.load(Ordering::Acquire) {
+      return;
+    }
+    let stream = match stream {
+      Ok(stream) => stream,
+      Err(e) => {
+        warn!("Cannot accept TCP connection: {e}");
+        continue;
+      }
+    };
+    let source = stream.peer_addr().ok();
+    if connections.streams.lock().unwrap().len() >= max_connections {
+      warn!("Already {max_connections} TCP connections, closing the one from {source:?}");
+      continue; // dropping the stream closes it
+    }
+    debug!("Accepted TCP connection from {source:?}");
+    if let Some(interval) = keep_alive {
+      set_tcp_keep_alive(&stream, interval);
+      stream
+        .set_read_timeout(Some(interval * IDLE_KEEP_ALIVE_INTERVALS))
+        .unwrap_or_else(|e| debug!("Cannot set read timeout on TCP connection: {e}"));
+    }
+    let id = connections.next_id.fetch_add(1, Ordering::Relaxed);
+    match stream.try_clone() {
+      Ok(s) => {
+        connections.streams.lock().unwrap().insert(id, s);
+      }
+      Err(e) => warn!("Cannot keep track of TCP connection from {source:?}: {e}"),
+    }
+
+    let sender = sender.clone();
+    let connections = Arc::clone(connections);
+    let logical_ports = Arc::clone(logical_ports);
+    let spawned = thread::Builder::new()
+      .name(format!("RustDDS TCP receive {source:?}"))
+      .spawn(move || {
+        receive_loop(stream, source, &sender, logical_ports);
+        connections.streams.lock().unwrap().remove(&id);
+      });
+    if let Err(e) = spawned {
+      warn!("Cannot start receiving from TCP connection {source:?}: {e}");
+    }
+  }
+}
+
+fn receive_loop(
+  mut stream: TcpStream,
+  source: Option<SocketAddr>,
+  sender: &mio_channel::SyncSender<(Bytes, PacketOrigin)>,
+  logical_ports: Arc<[u16]>,
+) {
+  let origin = PacketOrigin {
+    source,
+    local_if: None,
+  };
+  let mut binding = Binding {
+    locator: stream
+      .local_addr()
+      .map_or(Locator::Invalid, |a| tcp_locator(a, logical_ports[0])),
+    logical_ports,
+    client: None,
+    open_ports: HashSet::new(),
+    next_transaction: 0,
+  };
+  loop {
+    let response = match read_frame(&mut stream) {
+      Ok(Some((CONTROL_PORT, message))) => match ControlMessage::from_message(&message) {
+        Ok(ControlMessage {
+          control: Control::UnbindConnectionRequest,
+          ..
+        }) => {
+          debug!("TCP connection from {source:?} unbound");
+          return;
+        }
+        Ok(request) => binding.answer(request),
+        Err(e) => {
+          warn!("Closing TCP connection from {source:?}: {e}");
+          return;
+        }
+      },
+      Ok(Some((port, message))) if binding.open_ports.contains(&port) => {
+        if sender.send((Bytes::from(message), origin)).is_err() {
+          return; // listener is gone
+        }
+        None
+      }
+      Ok(Some((port, _))) => {
+        debug!("Logical port {port} is not open on TCP connection from {source:?}");
+        Some(binding.request(Control::LogicalPortIsClosedRequest { port }))
+      }
+      Ok(None) => {
+        debug!("TCP connection from {source:?} closed");
+        return;
+      }
+      Err(e)
+        if matches!(
+          e.kind(),
+          io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ) =>
+      {
+        info!("TCP connection from {source:?} is idle, closing it");
+        return;
+      }
+      Err(e) => {
+        warn!("Closing TCP connection from {source:?}: {e}");
+        return;
+      }
+    };
+    if let Some(response) = response {
+      if let Err(e) = stream.write_all(&response.to_frame()) {
+        warn!("Closing TCP connection from {source:?}: {e}");
+        return;
+      }
+    }
+  }
+}
+
+// The server side of the control messages of an accepted connection.
+struct Binding {
+  // Where this listener listens
+  locator: Locator,
+  logical_ports: Arc<[u16]>,
+  // Where the client listens, once the connection is bound
+  client: Option<Locator>,
+  open_ports: HashSet<u16>,
+  next_transaction: u64,
+}
+
+impl Binding {
+  // The response to a control message, if it needs one
+  fn answer(&mut self, message: ControlMessage) -> Option<ControlMessage> {
+    let bound = self.client.is_some();
+    let unless_unbound = |code| {
+      if bound {
+        code
+      } else {
+        ResponseCode::BAD_REQUEST
+      }
+    };
+    let control = match message.control {
+      Control::BindConnectionRequest {
+        protocol_version, ..
+      } if protocol_version.major != ProtocolVersion::THIS_IMPLEMENTATION.major => {
+        Control::BindConnectionResponse {
+          code: ResponseCode::INCOMPATIBLE_VERSION,
+          locator: self.locator,
+        }
+      }
+      Control::BindConnectionRequest { locator, .. } => {
+        debug!("TCP connection bound by {locator:?}");
+        self.client = Some(locator);
+        Control::BindConnectionResponse {
+          code: ResponseCode::OK,
+          locator: self.locator,
+        }
+      }
+      Control::OpenLogicalPortRequest { port } => {
+        let code = if self.logical_ports.contains(&port) {
+          if bound {
+            self.open_ports.insert(port);
+          }
+          unless_unbound(ResponseCode::OK)
+        } else {
+          unless_unbound(ResponseCode::INVALID_PORT)
+        };
+        Control::OpenLogicalPortResponse { code }
+      }
+      Control::CheckLogicalPortRequest { ports } => Control::CheckLogicalPortResponse {
+        code: unless_unbound(ResponseCode::OK),
+        ports: ports
+          .into_iter()
+          .filter(|port| self.logical_ports.contains(port))
+          .collect(),
+      },
+      Control::KeepAliveRequest { locator } => Control::KeepAliveResponse {
+        code: if self.client.is_some_and(|client| client != locator) {
+          ResponseCode::UNKNOWN_LOCATOR
+        } else {
+          unless_unbound(ResponseCode::OK)
+        },
+      },
+      other => {
+        trace!("Ignoring TCP control message {other:?}");
+        return None;
+      }
+    };
+    Some(ControlMessage {
+      transaction_id: message.transaction_id,
+      control,
+    })
+  }
+
+  // A request of our own to the client
+  fn request(&mut self, control: Control) -> ControlMessage {
+    self.next_transaction += 1;
+    ControlMessage::request(self.next_transaction, control)
+  }
+}
+
+impl TransportListener for TCPListener {
+  fn evented(&self) -> &dyn mio_06::Evented {
+    &self.receiver
+  }
+
+  fn messages_bounded(&mut self, max_messages: usize) -> Vec<(Bytes, PacketOrigin)> {
+    let mut messages = Vec::new();
+    while messages.len() < max_messages {
+      match self.receiver.try_recv() {
+        Ok(message) => messages.push(message),
+        Err(_) => break, // empty
+      }
+    }
+    messages
+  }
+
+  fn to_locator_address(&self, interfaces: &InterfaceConfig) -> io::Result<Vec<Locator>> {
+    let ip_versions: &[IpVersion] = match self.local_addr.ip() {
+      // An unspecified IPv6 address accepts IPv4 connections, too.
+      IpAddr::V6(ip) if ip.is_unspecified() => &[IpVersion::V4, IpVersion::V6],
+      ip => &[IpVersion::of(&ip)],
+    };
+    Ok(
+      get_local_unicast_locators_filtered(self.port(), interfaces, ip_versions)
+        .into_iter()
+        .filter_map(|l| SocketAddr::try_from(l).ok())
+        .flat_map(|a| self.logical_ports.first().map(|port| tcp_locator(a, *port)))
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Read;
+
+  use super::*;
+  use crate::{
+    messages::vendor_id::VendorId,
+    network::tcp_transport::{frame, TransactionId},
+  };
+
+  const PORT: u16 = 7410;
+
+  fn listener(keep_alive: Option<Duration>, max_connections: usize) -> TCPListener {
+    TCPListener::new("127.0.0.1", 0, &[PORT, 7400], keep_alive, max_connections).unwrap()
+  }
+
+  fn client_locator() -> Locator {
+    Locator::tcp_v4(Ipv4Addr::LOCALHOST, 7420, 7420)
+  }
+
+  // Send a control message and read the control message sent back
+  fn request(stream: &mut TcpStream, control: Control) -> Control {
+    let message = ControlMessage {
+      transaction_id: [7; 12],
+      control,
+    };
+    stream.write_all(&message.to_frame()).unwrap();
+    read_control(stream, Some([7; 12]))
+  }
+
+  fn read_control(stream: &mut TcpStream, transaction_id: Option<TransactionId>) -> Control {
+    let (port, message) = read_frame(stream).unwrap().unwrap();
+    assert_eq!(port, CONTROL_PORT);
+    let message = ControlMessage::from_message(&message).unwrap();
+    if let Some(id) = transaction_id {
+      assert_eq!(message.transaction_id, id);
+    }
+    message.control
+  }
+
+  fn bind(stream: &mut TcpStream) -> Control {
+    request(
+      stream,
+      Control::BindConnectionRequest {
+        protocol_version: ProtocolVersion::THIS_IMPLEMENTATION,
+        vendor_id: VendorId::THIS_IMPLEMENTATION,
+        locator: client_locator(),
+      },
+    )
+  }
+
+  // A connection to `listener`, bound and with `PORT` open
+  fn connect(listener: &TCPListener) -> TcpStream {
+    let mut stream = TcpStream::connect(("127.0.0.1", listener.port())).unwrap();
+    assert!(matches!(
+      bind(&mut stream),
+      Control::BindConnectionResponse {
+        code: ResponseCode::OK,
+        ..
+      }
+    ));
+    assert_eq!(
+      request(&mut stream, Control::OpenLogicalPortRequest { port: PORT }),
+      Control::OpenLogicalPortResponse {
+        code: ResponseCode::OK
+      }
+    );
+    stream
+  }
+
+  fn receive(listener: &mut TCPListener, count: usize) -> Vec<(Bytes, PacketOrigin)> {
+    let mut received = Vec::new();
+    for _ in 0..50 {
+      received.extend(listener.messages_bounded(usize::MAX));
+      if received.len() >= count {
+        break;
+      }
+      thread::sleep(Duration::from_millis(20));
+    }
+    received
+  }
+
+  #[test]
+  fn tcpl_receives_messages_at_open_logical_ports() {
+    let mut listener = listener(None, 64);
+    let mut stream = connect(&listener);
+    assert_eq!(
+      request(&mut stream, Control::OpenLogicalPortRequest { port: 7412 }),
+      Control::OpenLogicalPortResponse {
+        code: ResponseCode::INVALID_PORT
+      }
+    );
+    assert_eq!(
+      request(
+        &mut stream,
+        Control::CheckLogicalPortRequest {
+          ports: vec![7400, 7412]
+        }
+      ),
+      Control::CheckLogicalPortResponse {
+        code: ResponseCode::OK,
+        ports: vec![7400]
+      }
+    );
+    stream.write_all(&frame(PORT, b"first")).unwrap();
+    stream.write_all(&frame(7400, b"not open")).unwrap();
+    assert_eq!(
+      read_control(&mut stream, None),
+      Control::LogicalPortIsClosedRequest { port: 7400 }
+    );
+    stream.write_all(&frame(PORT, b"second")).unwrap();
+
+    let received = receive(&mut listener, 2);
+    let payloads: Vec<&[u8]> = received.iter().map(|(m, _)| m.as_ref()).collect();
+    assert_eq!(payloads, vec![&b"first"[..], &b"second"[..]]);
+    assert_eq!(received[0].1.source, stream.local_addr().ok());
+
+    let locators = listener
+      .to_locator_address(&InterfaceConfig::default())
+      .unwrap();
+    assert!(locators.iter().all(|l| l.is_tcp()
+      && l.tcp_physical_address().map(|a| a.port()) == Some(listener.port())
+      && l.tcp_logical_port() == Some(PORT)));
+  }
+
+  #[test]
+  fn tcpl_requires_binding_first() {
+    let mut listener = listener(None, 64);
+    let mut stream = TcpStream::connect(("127.0.0.1", listener.port())).unwrap();
+    assert_eq!(
+      request(&mut stream, Control::OpenLogicalPortRequest { port: PORT }),
+      Control::OpenLogicalPortResponse {
+        code: ResponseCode::BAD_REQUEST
+      }
+    );
+    stream.write_all(&frame(PORT, b"unbound")).unwrap();
+    assert_eq!(
+      read_control(&mut stream, None),
+      Control::LogicalPortIsClosedRequest { port: PORT }
+    );
+
+    let mut old = TcpStream::connect(("127.0.0.1", listener.port())).unwrap();
+    let old_version = request(
+      &mut old,
+      Control::BindConnectionRequest {
+        protocol_version: ProtocolVersion::PROTOCOLVERSION_1_1,
+        vendor_id: VendorId::THIS_IMPLEMENTATION,
+        locator: client_locator(),
+      },
+    );
+    assert!(matches!(
+      old_version,
+      Control::BindConnectionResponse {
+        code: ResponseCode::INCOMPATIBLE_VERSION,
+        ..
+      }
+    ));
+    assert!(listener.messages_bounded(usize::MAX).is_empty());
+  }
+
+  #[test]
+  fn tcpl_answers_keep_alives_and_closes_idle_connections() {
+    let keep_alive = Duration::from_millis(50);
+    let listener = listener(Some(keep_alive), 64);
+    let mut stream = connect(&listener);
+    assert_eq!(
+      request(
+        &mut stream,
+        Control::KeepAliveRequest {
+          locator: client_locator()
+        }
+      ),
+      Control::KeepAliveResponse {
+        code: ResponseCode::OK
+      }
+    );
+    assert_eq!(
+      request(
+        &mut stream,
+        Control::KeepAliveRequest {
+          locator: Locator::Invalid
+        }
+      ),
+      Control::KeepAliveResponse {
+        code: ResponseCode::UNKNOWN_LOCATOR
+      }
+    );
+
+    // Silent from now on, so the listener closes the connection.
+    stream
+      .set_read_timeout(Some(Duration::from_secs(5)))
+      .unwrap();
+    assert_eq!(stream.read(&mut [0; 1]).unwrap(), 0);
+  }
+
+  #[test]
+  fn tcpl_closes_connection_on_unbind() {
+    let listener = listener(None, 64);
+    let mut stream = connect(&listener);
+    stream
+      .write_all(
+        &ControlMessage {
+          transaction_id: [1; 12],
+          control: Control::UnbindConnectionRequest,
+        }
+        .to_frame(),
+      )
+      .unwrap();
+    stream
+      .set_read_timeout(Some(Duration::from_secs(5)))
+      .unwrap();
+    assert_eq!(stream.read(&mut [0; 1]).unwrap(), 0);
+  }
+
+  #[test]
+  fn tcpl_closes_connections_beyond_maximum() {
+    let mut listener = listener(None, 1);
+    // The first connection is tracked once it has been answered.
+    let mut first = connect(&listener);
+
+    let mut second = TcpStream::connect(("127.0.0.1", listener.port())).unwrap();
+    second
+      .set_read_timeout(Some(Duration::from_secs(5)))
+      .unwrap();
+    assert_eq!(second.read(&mut [0; 1]).unwrap(), 0);
+
+    first.write_all(&frame(PORT, b"again")).unwrap();
+    let received = receive(&mut listener, 1);
+    let payloads: Vec<&[u8]> = received.iter().map(|(m, _)| m.as_ref()).collect();
+    assert_eq!(payloads, vec![&b"again"[..]]);
+  }
+}
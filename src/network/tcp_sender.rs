@@ -0,0 +1,658 @@
+use std::{
+  cell::RefCell,
+  collections::{HashMap, HashSet},
+  io::{self, Write},
+  net::{Shutdown, SocketAddr, TcpStream},
+  sync::mpsc,
+  thread,
+  time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use log::{debug, info, trace, warn};
+use mio_extras::channel as mio_channel;
+
+use crate::{
+  messages::{protocol_version::ProtocolVersion, vendor_id::VendorId},
+  network::{
+    tcp_transport::{
+      frame, read_frame, set_tcp_keep_alive, Control, ControlMessage, ResponseCode, TransactionId,
+      CONTROL_PORT, MAX_MESSAGE_LEN,
+    },
+    transport::{Transport, TransportListener},
+    udp_listener::PacketOrigin,
+    util::InterfaceConfig,
+  },
+  rtps::{outbound::SocketId, transmit::InterfaceSelector},
+  structure::locator::Locator,
+};
+
+// Messages waiting to be written to one peer. When this is full, e.g. because
+// the peer is unreachable, further messages to it are dropped. RTPS recovers
+// them like lost UDP datagrams.
+const SEND_QUEUE_LEN: usize = 1024;
+
+// Messages received back on our connections, waiting for the event loop
+const RETURN_QUEUE_LEN: usize = 1024;
+
+// A logical port and a message to it
+type PortMessage = (u16, Vec<u8>);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+// How long to wait for the response to a control message
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sends RTPS messages over TCP.
+///
+/// Each destination has a connection of its own, written to by a thread of
+/// its own, so that a slow or unreachable peer does not block the event loop.
+/// Connections are opened and bound on first send, and each logical port is
+/// opened on the first message to it, as in the TCP/IP PSM. With keep-alives,
+/// idle connections get a keep-alive request every interval, and a failed
+/// connection is reopened on the next interval even if there is nothing to
+/// send.
+///
+/// When a connection is opened after messages to its peer have been lost, the
+/// peer address is sent to `reconnected`, so that the event loop can get the
+/// lost messages repaired. RTPS messages that peers send back on the
+/// connections are received by the [`TCPReturnListener`] of `returned`.
+pub struct TCPSender {
+  peers: RefCell<HashMap<SocketAddr, mpsc::SyncSender<PortMessage>>>,
+  keep_alive: Option<Duration>,
+  // Where our TCPListener listens, as told to peers when binding
+  local_locator: Locator,
+  reconnected: mio_channel::Sender<SocketAddr>,
+  returned: mio_channel::SyncSender<(Bytes, PacketOrigin)>,
+}
+
+impl TCPSender {
+  pub fn new(
+    keep_alive: Option<Duration>,
+    local_locator: Locator,
+    reconnected: mio_channel::Sender<SocketAddr>,
+    returned: mio_channel::SyncSender<(Bytes, PacketOrigin)>,
+  ) -> Self {
+    Self {
+      peers: RefCell::new(HashMap::new()),
+      keep_alive,
+      local_locator,
+      reconnected,
+      returned,
+    }
+  }
+
+  fn send_to(&self, buffer: &[u8], locator: &Locator) {
+    if !self.supports_locator(locator) {
+      trace!("TCPSender: not a TCP locator, skipping {locator:?}");
+      return;
+    }
+    let (Some(address), Some(logical_port)) =
+      (locator.tcp_physical_address(), locator.tcp_logical_port())
+    else {
+      trace!("TCPSender: cannot send to {locator:?}");
+      return;
+    };
+    if buffer.len() > MAX_MESSAGE_LEN {
+      warn!(
+        "TCPSender: message of {} bytes to {locator:?} is too large, dropping it",
+        buffer.len()
+      );
+      return;
+    }
+
+    let mut peers = self.peers.borrow_mut();
+    let mut message = (logical_port, buffer.to_vec());
+    if let Some(queue) = peers.get(&address) {
+      match queue.try_send(message) {
+        Ok(()) => return,
+        Err(mpsc::TrySendError::Full(_)) => {
+          debug!("TCPSender: send queue to {address} is full, dropping message");
+          return;
+        }
+        // The connection thread has stopped. Start a new one.
+        Err(mpsc::TrySendError::Disconnected(m)) => message = m,
+      }
+    }
+
+    let (queue, messages) = mpsc::sync_channel(SEND_QUEUE_LEN);
+    let connection = Connection {
+      address,
+      keep_alive: self.keep_alive,
+      local_locator: self.local_locator,
+      reconnected: self.reconnected.clone(),
+      returned: self.returned.clone(),
+      session: None,
+      lost_messages: false,
+    };
+    let spawned = thread::Builder::new()
+      .name(format!("RustDDS TCP send {address}"))
+      .spawn(move || connection.send_loop(&messages));
+    match spawned {
+      Ok(_) => {
+        // The queue is new, so there is room.
+        queue.try_send(message).ok();
+        peers.insert(address, queue);
+      }
+      Err(e) => warn!("TCPSender: cannot start sending to {address}: {e}"),
+    }
+  }
+}
+
+// Connection to one peer, owned by its sending thread.
+struct Connection {
+  address: SocketAddr,
+  keep_alive: Option<Duration>,
+  local_locator: Locator,
+  reconnected: mio_channel::Sender<SocketAddr>,
+  returned: mio_channel::SyncSender<(Bytes, PacketOrigin)>,
+  // The bound connection, if open
+  session: Option<Session>,
+  // Messages have been lost since the connection was last open.
+  lost_messages: bool,
+}
+
+impl Connection {
+  // Ends when the TCPSender is dropped.
+  fn send_loop(mut self, messages: &mpsc::Receiver<PortMessage>) {
+    loop {
+      let (port, message) = match self.keep_alive {
+        Some(interval) => match messages.recv_timeout(interval) {
+          Ok(message) => message,
+          Err(mpsc::RecvTimeoutError::Timeout) if self.session.is_some() => {
+            self.keep_alive();
+            continue;
+          }
+          // Reconnect only if there is something to repair.
+          Err(mpsc::RecvTimeoutError::Timeout) if self.lost_messages => {
+            self.connect();
+            continue;
+          }
+          Err(mpsc::RecvTimeoutError::Timeout) => continue,
+          Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        },
+        None => match messages.recv() {
+          Ok(message) => message,
+          Err(mpsc::RecvError) => break,
+        },
+      };
+      self.send(port, &message);
+    }
+    if let Some(mut session) = self.session.take() {
+      if let Err(e) = session.send(Control::UnbindConnectionRequest) {
+        debug!("TCPSender: cannot unbind from {}: {e}", self.address);
+      }
+    }
+  }
+
+  fn send(&mut self, port: u16, message: &[u8]) {
+    if self.session.is_none() {
+      self.connect();
+    }
+    let Some(session) = self.session.as_mut() else {
+      self.lost_messages = true;
+      return; // drop the message, retry with the next one
+    };
+    let result = session.open_port(port).and_then(|open| {
+      if open {
+        session.stream.write_all(&frame(port, message))
+      } else {
+        trace!(
+          "TCPSender: {} does not serve logical port {port}",
+          self.address
+        );
+        Ok(())
+      }
+    });
+    if let Err(e) = result {
+      warn!("TCPSender: connection to {} failed: {e}", self.address);
+      self.session = None;
+      self.lost_messages = true;
+    }
+  }
+
+  // The response to the previous keep-alive request must have arrived by now.
+  fn keep_alive(&mut self) {
+    let Some(session) = self.session.as_mut() else {
+      return;
+    };
+    let result = session.handle_controls().and_then(|()| {
+      if session.keep_alive_pending.is_some() {
+        return Err(io::Error::new(
+          io::ErrorKind::TimedOut,
+          "no response to keep-alive",
+        ));
+      }
+      let locator = self.local_locator;
+      session.keep_alive_pending = Some(session.send(Control::KeepAliveRequest { locator })?);
+      Ok(())
+    });
+    if let Err(e) = result {
+      warn!("TCPSender: connection to {} failed: {e}", self.address);
+      self.session = None;
+      self.lost_messages = true;
+    }
+  }
+
+  fn connect(&mut self) {
+    let address = self.address;
+    match self.open_session() {
+      Ok(session) => {
+        debug!("TCPSender: connected to {address}");
+        self.session = Some(session);
+        if self.lost_messages {
+          info!("TCPSender: reconnected to {address}");
+          self.lost_messages = false;
+          self.reconnected.send(address).unwrap_or_else(|e| {
+            debug!("TCPSender: cannot report reconnection to {address}: {e}");
+          });
+        }
+      }
+      Err(e) => {
+        debug!("TCPSender: cannot connect to {address}: {e}");
+      }
+    }
+  }
+
+  // Connect and bind the connection
+  fn open_session(&self) -> io::Result<Session> {
+    let address = self.address;
+    let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_nodelay(true).unwrap_or_else(|e| {
+      debug!("Cannot set TCP_NODELAY on connection to {address}: {e}");
+    });
+    if let Some(interval) = self.keep_alive {
+      set_tcp_keep_alive(&stream, interval);
+    }
+    let (controls_sender, controls) = mpsc::channel();
+    let reader = stream.try_clone()?;
+    let returned = self.returned.clone();
+    thread::Builder::new()
+      .name(format!("RustDDS TCP send {address} responses"))
+      .spawn(move || read_loop(reader, address, &controls_sender, &returned))?;
+
+    let mut session = Session {
+      stream,
+      controls,
+      open_ports: HashSet::new(),
+      keep_alive_pending: None,
+      next_transaction: 0,
+    };
+    let response = session.request(Control::BindConnectionRequest {
+      protocol_version: ProtocolVersion::THIS_IMPLEMENTATION,
+      vendor_id: VendorId::THIS_IMPLEMENTATION,
+      locator: self.local_locator,
+    })?;
+    match response {
+      Control::BindConnectionResponse {
+        code: ResponseCode::OK,
+        ..
+      } => Ok(session),
+      other => Err(io::Error::new(
+        io::ErrorKind::ConnectionRefused,
+        format!("binding refused: {other:?}"),
+      )),
+    }
+  }
+}
+
+// A bound connection. Dropping it closes the connection.
+struct Session {
+  stream: TcpStream,
+  // Control messages received on the connection
+  controls: mpsc::Receiver<ControlMessage>,
+  open_ports: HashSet<u16>,
+  keep_alive_pending: Option<TransactionId>,
+  next_transaction: u64,
+}
+
+impl Drop for Session {
+  fn drop(&mut self) {
+    // Also stops the thread reading the connection.
+    self.stream.shutdown(Shutdown::Both).unwrap_or_else(|e| {
+      debug!("Cannot shut down TCP connection: {e}");
+    });
+  }
+}
+
+impl Session {
+  fn send(&mut self, control: Control) -> io::Result<TransactionId> {
+    self.next_transaction += 1;
+    let message = ControlMessage::request(self.next_transaction, control);
+    self.stream.write_all(&message.to_frame())?;
+    Ok(message.transaction_id)
+  }
+
+  // Send a request and wait for its response
+  fn request(&mut self, control: Control) -> io::Result<Control> {
+    let transaction_id = self.send(control)?;
+    let deadline = Instant::now() + RESPONSE_TIMEOUT;
+    loop {
+      let timeout = deadline.saturating_duration_since(Instant::now());
+      match self.controls.recv_timeout(timeout) {
+        Ok(message) if message.transaction_id == transaction_id => return Ok(message.control),
+        Ok(message) => self.handle(message),
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+          return Err(io::Error::new(io::ErrorKind::TimedOut, "no response"))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+          return Err(io::ErrorKind::ConnectionAborted.into())
+        }
+      }
+    }
+  }
+
+  // Handle the control messages received so far. Fails if the connection has
+  // been closed.
+  fn handle_controls(&mut self) -> io::Result<()> {
+    loop {
+      match self.controls.try_recv() {
+        Ok(message) => self.handle(message),
+        Err(mpsc::TryRecvError::Empty) => return Ok(()),
+        Err(mpsc::TryRecvError::Disconnected) => {
+          return Err(io::ErrorKind::ConnectionAborted.into())
+        }
+      }
+    }
+  }
+
+  // Handle a control message that is not a response being waited for
+  fn handle(&mut self, message: ControlMessage) {
+    match message.control {
+      Control::KeepAliveResponse { code }
+        if self.keep_alive_pending == Some(message.transaction_id) =>
+      {
+        if code != ResponseCode::OK {
+          // The connection stays up, as the peer keeps answering.
+          debug!("TCPSender: keep-alive response {code:?}");
+        }
+        self.keep_alive_pending = None;
+      }
+      Control::LogicalPortIsClosedRequest { port } => {
+        debug!("TCPSender: logical port {port} is closed, reopening it on next send");
+        self.open_ports.remove(&port);
+      }
+      other => trace!("TCPSender: ignoring TCP control message {other:?}"),
+    }
+  }
+
+  // Open `port` unless already open. `false` if the peer does not serve it.
+  fn open_port(&mut self, port: u16) -> io::Result<bool> {
+    self.handle_controls()?;
+    if self.open_ports.contains(&port) {
+      return Ok(true);
+    }
+    match self.request(Control::OpenLogicalPortRequest { port })? {
+      Control::OpenLogicalPortResponse {
+        code: ResponseCode::OK,
+      } => {
+        self.open_ports.insert(port);
+        Ok(true)
+      }
+      _ => Ok(false),
+    }
+  }
+}
+
+// Passes control messages received on a connection to its Session, and RTPS
+// messages to the TCPReturnListener. Ends when the connection is closed.
+fn read_loop(
+  mut stream: TcpStream,
+  address: SocketAddr,
+  controls: &mpsc::Sender<ControlMessage>,
+  returned: &mio_channel::SyncSender<(Bytes, PacketOrigin)>,
+) {
+  let origin = PacketOrigin {
+    source: Some(address),
+    local_if: None,
+  };
+  loop {
+    match read_frame(&mut stream) {
+      Ok(Some((CONTROL_PORT, message))) => match ControlMessage::from_message(&message) {
+        Ok(control) => {
+          if controls.send(control).is_err() {
+            return; // session is gone
+          }
+        }
+        Err(e) => {
+          warn!("TCPSender: closing connection to {address}: {e}");
+          stream.shutdown(Shutdown::Both).ok();
+          return;
+        }
+      },
+      Ok(Some((_, message))) => {
+        if returned.send((Bytes::from(message), origin)).is_err() {
+          return; // event loop is gone
+        }
+      }
+      Ok(None) => {
+        debug!("TCPSender: connection to {address} closed");
+        return;
+      }
+      Err(e) => {
+        debug!("TCPSender: connection to {address} failed: {e}");
+        return;
+      }
+    }
+  }
+}
+
+impl Transport for TCPSender {
+  fn supports_locator(&self, locator: &Locator) -> bool {
+    locator.is_tcp()
+  }
+
+  fn send_to_locator(&self, buffer: &[u8], locator: &Locator) {
+    self.send_to(buffer, locator);
+  }
+
+  // TCP has no multicast.
+  fn send_to_multicast_locator_via(
+    &self,
+    _buffer: &[u8],
+    locator: &Locator,
+    _interface: &InterfaceSelector,
+  ) {
+    trace!("TCPSender: cannot multicast to {locator:?}");
+  }
+
+  // Connections are written to by their own threads, so they never block the
+  // caller.
+  fn try_send_to_locator(&self, buffer: &[u8], locator: &Locator) -> Vec<SocketId> {
+    self.send_to(buffer, locator);
+    vec![]
+  }
+
+  fn try_send_to_multicast_locator_via(
+    &self,
+    _buffer: &[u8],
+    locator: &Locator,
+    _interface: &InterfaceSelector,
+  ) -> Vec<SocketId> {
+    trace!("TCPSender: cannot multicast to {locator:?}");
+    vec![]
+  }
+
+  fn multicast_interfaces(&self) -> Vec<InterfaceSelector> {
+    vec![]
+  }
+}
+
+/// Receives the RTPS messages that peers send back on the connections of a
+/// [`TCPSender`]. It has no locators of its own.
+pub struct TCPReturnListener {
+  receiver: mio_channel::Receiver<(Bytes, PacketOrigin)>,
+}
+
+impl TCPReturnListener {
+  /// The listener, and the sender to give to the [`TCPSender`]
+  pub fn new() -> (Self, mio_channel::SyncSender<(Bytes, PacketOrigin)>) {
+    let (sender, receiver) = mio_channel::sync_channel(RETURN_QUEUE_LEN);
+    (Self { receiver }, sender)
+  }
+}
+
+impl TransportListener for TCPReturnListener {
+  fn evented(&self) -> &dyn mio_06::Evented {
+    &self.receiver
+  }
+
+  fn messages_bounded(&mut self, max_messages: usize) -> Vec<(Bytes, PacketOrigin)> {
+    let mut messages = Vec::new();
+    while messages.len() < max_messages {
+      match self.receiver.try_recv() {
+        Ok(message) => messages.push(message),
+        Err(_) => break, // empty
+      }
+    }
+    messages
+  }
+
+  fn to_locator_address(&self, _interfaces: &InterfaceConfig) -> io::Result<Vec<Locator>> {
+    Ok(vec![])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::net::TcpListener;
+
+  use super::*;
+  use crate::network::{tcp_listener::TCPListener, tcp_transport::tcp_locator};
+
+  const PORT: u16 = 7410;
+
+  fn tcp_sender(keep_alive: Option<Duration>) -> (TCPSender, mio_channel::Receiver<SocketAddr>) {
+    let (reconnected, reconnections) = mio_channel::channel();
+    let (_, returned) = TCPReturnListener::new();
+    let local_locator = Locator::tcp_v4([127, 0, 0, 1].into(), 7420, 7420);
+    (
+      TCPSender::new(keep_alive, local_locator, reconnected, returned),
+      reconnections,
+    )
+  }
+
+  fn receive(listener: &mut impl TransportListener, count: usize) -> Vec<(Bytes, PacketOrigin)> {
+    let mut received = Vec::new();
+    for _ in 0..50 {
+      received.extend(listener.messages_bounded(usize::MAX));
+      if received.len() >= count {
+        break;
+      }
+      thread::sleep(Duration::from_millis(20));
+    }
+    received
+  }
+
+  #[test]
+  fn tcps_sends_to_tcp_listener() {
+    let mut listener = TCPListener::new("127.0.0.1", 0, &[PORT], None, 64).unwrap();
+    let (sender, _) = tcp_sender(None);
+    let address = SocketAddr::from(([127, 0, 0, 1], listener.port()));
+    sender.send_to_locator(b"first", &tcp_locator(address, PORT));
+    // UDP locators and logical ports that the listener does not serve are
+    // skipped
+    sender.send_to_locator_list(
+      b"second",
+      &[
+        tcp_locator(address, PORT),
+        tcp_locator(address, PORT + 2),
+        Locator::from(address),
+      ],
+    );
+    sender.send_to_locator(&vec![0; MAX_MESSAGE_LEN + 1], &tcp_locator(address, PORT));
+    sender.send_to_locator(b"third", &tcp_locator(address, PORT));
+
+    let received = receive(&mut listener, 3);
+    let payloads: Vec<&[u8]> = received.iter().map(|(m, _)| m.as_ref()).collect();
+    assert_eq!(payloads, vec![&b"first"[..], &b"second"[..], &b"third"[..]]);
+  }
+
+  #[test]
+  fn tcps_reconnects_after_connection_loss() {
+    let keep_alive = Duration::from_millis(50);
+    let listener = TCPListener::new("127.0.0.1", 0, &[PORT], Some(keep_alive), 64).unwrap();
+    let port = listener.port();
+    let (sender, reconnections) = tcp_sender(Some(keep_alive));
+    let address = SocketAddr::from(([127, 0, 0, 1], port));
+    sender.send_to_locator(b"first", &tcp_locator(address, PORT));
+
+    // The keep-alives fail while the peer is gone, and then reconnect.
+    drop(listener);
+    thread::sleep(keep_alive * 4);
+    let mut listener = TCPListener::new("127.0.0.1", port, &[PORT], Some(keep_alive), 64).unwrap();
+    let mut reconnected_to = None;
+    for _ in 0..100 {
+      reconnected_to = reconnections.try_recv().ok();
+      if reconnected_to.is_some() {
+        break;
+      }
+      thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(reconnected_to, Some(address));
+
+    sender.send_to_locator(b"second", &tcp_locator(address, PORT));
+    let received = receive(&mut listener, 1);
+    let payloads: Vec<&[u8]> = received.iter().map(|(m, _)| m.as_ref()).collect();
+    assert_eq!(payloads, vec![&b"second"[..]]);
+  }
+
+  #[test]
+  fn tcps_receives_messages_sent_back_and_reopens_closed_ports() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+    let (reconnected, _) = mio_channel::channel();
+    let (mut return_listener, returned) = TCPReturnListener::new();
+    let local_locator = Locator::tcp_v4([127, 0, 0, 1].into(), 7420, 7420);
+    let sender = TCPSender::new(None, local_locator, reconnected, returned);
+    sender.send_to_locator(b"first", &tcp_locator(address, PORT));
+
+    // A peer that answers every request with OK, and tells that the port has
+    // closed after the first message.
+    let (mut stream, _) = server.accept().unwrap();
+    let mut requests = Vec::new();
+    let mut rtps = Vec::new();
+    while rtps.len() < 2 {
+      let (port, message) = read_frame(&mut stream).unwrap().unwrap();
+      if port != CONTROL_PORT {
+        rtps.push(message);
+        if rtps.len() == 1 {
+          let closed = ControlMessage::request(1, Control::LogicalPortIsClosedRequest { port });
+          stream.write_all(&closed.to_frame()).unwrap();
+          stream.write_all(&frame(7420, b"back")).unwrap();
+          // Wait for the returned message, so that the port is closed
+          // before the next send.
+          assert_eq!(receive(&mut return_listener, 1)[0].0.as_ref(), b"back");
+          sender.send_to_locator(b"second", &tcp_locator(address, PORT));
+        }
+        continue;
+      }
+      let request = ControlMessage::from_message(&message).unwrap();
+      let control = match request.control {
+        Control::BindConnectionRequest { locator, .. } => {
+          assert_eq!(locator, local_locator);
+          Control::BindConnectionResponse {
+            code: ResponseCode::OK,
+            locator: tcp_locator(address, PORT),
+          }
+        }
+        Control::OpenLogicalPortRequest { .. } => Control::OpenLogicalPortResponse {
+          code: ResponseCode::OK,
+        },
+        ref other => panic!("unexpected {other:?}"),
+      };
+      requests.push(request.control);
+      let response = ControlMessage {
+        transaction_id: request.transaction_id,
+        control,
+      };
+      stream.write_all(&response.to_frame()).unwrap();
+    }
+    assert_eq!(rtps, vec![b"first".to_vec(), b"second".to_vec()]);
+    let opened = requests
+      .iter()
+      .filter(|r| **r == Control::OpenLogicalPortRequest { port: PORT })
+      .count();
+    assert_eq!(opened, 2);
+  }
+}
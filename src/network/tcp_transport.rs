@@ -0,0 +1,674 @@
+//! RTPS over TCP: configuration, and the framing and control messages of the
+//! DDS Interoperability Wire Protocol TCP/IP PSM.
+
+use std::{
+  io::{self, Read},
+  net::{SocketAddr, TcpStream},
+  time::Duration,
+};
+
+use log::debug;
+use socket2::{SockRef, TcpKeepalive};
+use speedy::{Endianness, Readable};
+
+use crate::{
+  messages::{protocol_version::ProtocolVersion, vendor_id::VendorId},
+  network::constant::spdp_well_known_unicast_port,
+  structure::locator::Locator,
+};
+
+/// RTPS over TCP configuration, set with
+/// [`DomainParticipantBuilder::tcp_transport`](crate::DomainParticipantBuilder::tcp_transport).
+///
+/// With TCP, the participant sends and receives all RTPS messages, Discovery
+/// included, over TCP connections instead of UDP. This gets through firewalls
+/// and NATs that block UDP or multicast.
+///
+/// The connections carry RTPS messages as in the OMG DDS Interoperability
+/// Wire Protocol TCP/IP PSM. A participant connects to a peer, binds the
+/// connection, and opens a logical port on it for each RTPS endpoint it sends
+/// to, with control messages of the PSM. A TCP locator holds both the TCP
+/// listen port (the physical port) and the logical port.
+///
+/// TCP cannot multicast, so Discovery (SPDP) is sent to a static list of
+/// initial peers, given as the addresses of their TCP listen ports. Peers
+/// learned from them are then connected to as usual. A participant listens on
+/// the port set with [`with_listen_port`](Self::with_listen_port), or by
+/// default on the SPDP unicast port of its participant id, i.e. 7410 for
+/// participant 0 of domain 0. Each further participant id is 2 higher, and
+/// each further domain 250.
+///
+/// Discovery and user traffic of a participant share its logical port, which
+/// is the SPDP unicast port of its participant id. Initial peers are sent to
+/// at the SPDP unicast port of participant 0, which every participant serves
+/// as well, so that the participant ids of the peers need not be known.
+///
+/// A participant opens a connection to each peer it sends to, and receives on
+/// the connections the peers have opened to it. So all participants must be
+/// able to accept connections from their peers. RTPS messages that a peer
+/// sends back on a connection opened by this participant are received, too.
+///
+/// Idle connections are kept up with keep-alives, so that firewalls and NATs
+/// do not drop them. See [`with_keep_alive`](Self::with_keep_alive).
+///
+/// A participant accepts at most
+/// [`with_max_connections`](Self::with_max_connections) connections at a
+/// time, and closes further ones right away.
+///
+/// RTPS messages of up to 64 KiB are carried. A peer that sends a larger one
+/// is disconnected, and larger messages to peers are dropped. RustDDS Writers
+/// fragment their samples to fit in UDP datagrams, so their messages are
+/// always small enough.
+///
+/// Available only with the feature `tcp`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpTransport {
+  initial_peers: Vec<SocketAddr>,
+  listen_port: Option<u16>,
+  keep_alive: Option<Duration>,
+  max_connections: usize,
+}
+
+// Keep-alive interval unless configured otherwise.
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+// Accepted connections at a time unless configured otherwise.
+const DEFAULT_MAX_CONNECTIONS: usize = 64;
+
+impl TcpTransport {
+  pub fn new(initial_peers: impl IntoIterator<Item = SocketAddr>) -> Self {
+    Self {
+      initial_peers: initial_peers.into_iter().collect(),
+      listen_port: None,
+      keep_alive: Some(DEFAULT_KEEP_ALIVE),
+      max_connections: DEFAULT_MAX_CONNECTIONS,
+    }
+  }
+
+  /// Listen on `port` instead of the SPDP unicast port of the participant id.
+  pub fn with_listen_port(mut self, port: u16) -> Self {
+    self.listen_port = Some(port);
+    self
+  }
+
+  /// Keep idle connections up by sending on them every `interval` (default: 30
+  /// seconds). `None` disables keep-alives.
+  ///
+  /// A connection that has been idle for `interval` gets a keep-alive
+  /// request, and is considered failed if the peer has not responded by the
+  /// next interval. TCP keep-alive (`SO_KEEPALIVE`) probes start after the
+  /// same time. A connection that has received nothing in three intervals is
+  /// considered dead and closed, so all participants should use the same
+  /// interval. A participant reconnects to a peer when a connection to it has
+  /// failed, and then prompts the peer's matched endpoints to repair the
+  /// samples lost in between.
+  pub fn with_keep_alive(mut self, interval: Option<Duration>) -> Self {
+    self.keep_alive = interval;
+    self
+  }
+
+  /// Accept at most `max` connections at a time (default: 64). Each accepted
+  /// connection is read by a thread of its own. A peer whose connection is
+  /// closed for this reason cannot send to this participant.
+  pub fn with_max_connections(mut self, max: usize) -> Self {
+    self.max_connections = max;
+    self
+  }
+
+  pub fn initial_peers(&self) -> &[SocketAddr] {
+    &self.initial_peers
+  }
+
+  pub fn listen_port(&self) -> Option<u16> {
+    self.listen_port
+  }
+
+  pub fn keep_alive(&self) -> Option<Duration> {
+    self.keep_alive
+  }
+
+  pub fn max_connections(&self) -> usize {
+    self.max_connections
+  }
+
+  pub(crate) fn initial_peer_locators(&self, domain_id: u16) -> Vec<Locator> {
+    let logical_port = spdp_well_known_unicast_port(domain_id, 0);
+    self
+      .initial_peers
+      .iter()
+      .map(|peer| tcp_locator(*peer, logical_port))
+      .collect()
+  }
+}
+
+impl Default for TcpTransport {
+  fn default() -> Self {
+    Self::new([])
+  }
+}
+
+pub(crate) fn tcp_locator(address: SocketAddr, logical_port: u16) -> Locator {
+  match address {
+    SocketAddr::V4(a) => Locator::tcp_v4(*a.ip(), a.port(), logical_port),
+    SocketAddr::V6(a) => Locator::tcp_v6(*a.ip(), a.port(), logical_port),
+  }
+}
+
+// TCP header of the TCP/IP PSM, in front of every message on a connection:
+// "RTCP", the length of the frame including the header, a checksum of the
+// message, and the logical port. The numbers are little-endian.
+const TCP_HEADER_LEN: usize = 14;
+const TCP_HEADER_MAGIC: [u8; 4] = *b"RTCP";
+
+/// Largest message accepted in a frame. Writers size their messages to fit in
+/// UDP datagrams, so RustDDS never sends larger ones.
+pub(crate) const MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+/// Logical port of control messages. RTPS messages go to the other ports.
+pub(crate) const CONTROL_PORT: u16 = 0;
+
+/// `message` with the TCP header, ready to be written to a connection.
+pub(crate) fn frame(logical_port: u16, message: &[u8]) -> Vec<u8> {
+  let len = (TCP_HEADER_LEN + message.len()) as u32;
+  let mut framed = Vec::with_capacity(TCP_HEADER_LEN + message.len());
+  framed.extend_from_slice(&TCP_HEADER_MAGIC);
+  framed.extend_from_slice(&len.to_le_bytes());
+  framed.extend_from_slice(&checksum(message).to_le_bytes());
+  framed.extend_from_slice(&logical_port.to_le_bytes());
+  framed.extend_from_slice(message);
+  framed
+}
+
+// Sum of the bytes, with the carry added back in.
+fn checksum(message: &[u8]) -> u32 {
+  message.iter().fold(0u32, |sum, &byte| {
+    let (sum, carry) = sum.overflowing_add(byte.into());
+    sum + u32::from(carry)
+  })
+}
+
+/// Turn on TCP keep-alive probes after `idle` time on `stream`.
+pub(crate) fn set_tcp_keep_alive(stream: &TcpStream, idle: Duration) {
+  let keep_alive = TcpKeepalive::new().with_time(idle);
+  SockRef::from(stream)
+    .set_tcp_keepalive(&keep_alive)
+    .unwrap_or_else(|e| debug!("Cannot set SO_KEEPALIVE: {e}"));
+}
+
+/// Read the next frame from a connection: its logical port and message.
+/// `None` if the connection was closed between frames.
+pub(crate) fn read_frame(reader: &mut impl Read) -> io::Result<Option<(u16, Vec<u8>)>> {
+  let mut header = [0u8; TCP_HEADER_LEN];
+  match reader.read_exact(&mut header) {
+    Ok(()) => {}
+    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+    Err(e) => return Err(e),
+  }
+  if header[0..4] != TCP_HEADER_MAGIC {
+    return Err(invalid_data("Frame does not start with RTCP".to_string()));
+  }
+  let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+  let len = len
+    .checked_sub(TCP_HEADER_LEN)
+    .ok_or_else(|| invalid_data(format!("Frame length {len} is shorter than the header")))?;
+  if len > MAX_MESSAGE_LEN {
+    return Err(invalid_data(format!(
+      "Message of {len} bytes is larger than {MAX_MESSAGE_LEN}"
+    )));
+  }
+  let sum = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+  let logical_port = u16::from_le_bytes([header[12], header[13]]);
+  let mut message = vec![0; len];
+  reader.read_exact(&mut message)?;
+  // Zero means that the sender did not compute a checksum.
+  if sum != 0 && sum != checksum(&message) {
+    return Err(invalid_data("Frame checksum does not match".to_string()));
+  }
+  Ok(Some((logical_port, message)))
+}
+
+fn invalid_data(reason: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+/// Response code of a control message response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ResponseCode(pub u32);
+
+impl ResponseCode {
+  pub const OK: Self = Self(1);
+  pub const UNKNOWN_LOCATOR: Self = Self(3);
+  pub const INVALID_PORT: Self = Self(4);
+  pub const INCOMPATIBLE_VERSION: Self = Self(5);
+  pub const BAD_REQUEST: Self = Self(6);
+}
+
+/// A control message, sent on [`CONTROL_PORT`].
+///
+/// The client, i.e. the side that opened the connection, binds the connection
+/// first, and then opens each logical port before sending RTPS messages to
+/// it. The server tells when a message arrives at a logical port that is not
+/// open. Either side may send RTPS messages once a port is open.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Control {
+  BindConnectionRequest {
+    protocol_version: ProtocolVersion,
+    vendor_id: VendorId,
+    // Where the client listens
+    locator: Locator,
+  },
+  BindConnectionResponse {
+    code: ResponseCode,
+    // Where the server listens
+    locator: Locator,
+  },
+  OpenLogicalPortRequest {
+    port: u16,
+  },
+  OpenLogicalPortResponse {
+    code: ResponseCode,
+  },
+  CheckLogicalPortRequest {
+    ports: Vec<u16>,
+  },
+  CheckLogicalPortResponse {
+    code: ResponseCode,
+    // The requested ports that are served
+    ports: Vec<u16>,
+  },
+  KeepAliveRequest {
+    locator: Locator,
+  },
+  KeepAliveResponse {
+    code: ResponseCode,
+  },
+  LogicalPortIsClosedRequest {
+    port: u16,
+  },
+  UnbindConnectionRequest,
+}
+
+// Control message kinds
+const BIND_CONNECTION_REQUEST: u8 = 0xD1;
+const OPEN_LOGICAL_PORT_REQUEST: u8 = 0xD2;
+const CHECK_LOGICAL_PORT_REQUEST: u8 = 0xD3;
+const KEEP_ALIVE_REQUEST: u8 = 0xD4;
+const LOGICAL_PORT_IS_CLOSED_REQUEST: u8 = 0xD5;
+const UNBIND_CONNECTION_REQUEST: u8 = 0xD6;
+const BIND_CONNECTION_RESPONSE: u8 = 0xE1;
+const OPEN_LOGICAL_PORT_RESPONSE: u8 = 0xE2;
+const CHECK_LOGICAL_PORT_RESPONSE: u8 = 0xE3;
+const KEEP_ALIVE_RESPONSE: u8 = 0xE4;
+
+// Control message header: kind, flags, length of the control message
+// including the header, and transaction id.
+const CONTROL_HEADER_LEN: usize = 16;
+const FLAG_LITTLE_ENDIAN: u8 = 0x01;
+const FLAG_PAYLOAD: u8 = 0x02;
+const FLAG_REQUIRES_RESPONSE: u8 = 0x04;
+
+/// Pairs a response with its request.
+pub(crate) type TransactionId = [u8; 12];
+
+/// A control message with its transaction id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ControlMessage {
+  pub transaction_id: TransactionId,
+  pub control: Control,
+}
+
+impl ControlMessage {
+  /// Request number `transaction` of a connection.
+  pub fn request(transaction: u64, control: Control) -> Self {
+    let mut transaction_id = TransactionId::default();
+    transaction_id[..8].copy_from_slice(&transaction.to_le_bytes());
+    Self {
+      transaction_id,
+      control,
+    }
+  }
+
+  /// The control message in a frame, ready to be written to a connection.
+  /// RustDDS writes control messages little-endian.
+  pub fn to_frame(&self) -> Vec<u8> {
+    let mut payload = Vec::new();
+    let put_locator = |payload: &mut Vec<u8>, locator: &Locator| {
+      payload.extend_from_slice(&locator.kind().to_le_bytes());
+      payload.extend_from_slice(&locator.port().to_le_bytes());
+      payload.extend_from_slice(&locator.address());
+    };
+    let put_ports = |payload: &mut Vec<u8>, ports: &[u16]| {
+      payload.extend_from_slice(&(ports.len() as u32).to_le_bytes());
+      for port in ports {
+        payload.extend_from_slice(&port.to_le_bytes());
+      }
+    };
+    let kind = match &self.control {
+      Control::BindConnectionRequest {
+        protocol_version,
+        vendor_id,
+        locator,
+      } => {
+        payload.extend_from_slice(&[protocol_version.major, protocol_version.minor]);
+        payload.extend_from_slice(&vendor_id.as_bytes());
+        put_locator(&mut payload, locator);
+        BIND_CONNECTION_REQUEST
+      }
+      Control::BindConnectionResponse { code, locator } => {
+        payload.extend_from_slice(&code.0.to_le_bytes());
+        put_locator(&mut payload, locator);
+        BIND_CONNECTION_RESPONSE
+      }
+      Control::OpenLogicalPortRequest { port } => {
+        payload.extend_from_slice(&port.to_le_bytes());
+        OPEN_LOGICAL_PORT_REQUEST
+      }
+      Control::OpenLogicalPortResponse { code } => {
+        payload.extend_from_slice(&code.0.to_le_bytes());
+        OPEN_LOGICAL_PORT_RESPONSE
+      }
+      Control::CheckLogicalPortRequest { ports } => {
+        put_ports(&mut payload, ports);
+        CHECK_LOGICAL_PORT_REQUEST
+      }
+      Control::CheckLogicalPortResponse { code, ports } => {
+        payload.extend_from_slice(&code.0.to_le_bytes());
+        put_ports(&mut payload, ports);
+        CHECK_LOGICAL_PORT_RESPONSE
+      }
+      Control::KeepAliveRequest { locator } => {
+        put_locator(&mut payload, locator);
+        KEEP_ALIVE_REQUEST
+      }
+      Control::KeepAliveResponse { code } => {
+        payload.extend_from_slice(&code.0.to_le_bytes());
+        KEEP_ALIVE_RESPONSE
+      }
+      Control::LogicalPortIsClosedRequest { port } => {
+        payload.extend_from_slice(&port.to_le_bytes());
+        LOGICAL_PORT_IS_CLOSED_REQUEST
+      }
+      Control::UnbindConnectionRequest => UNBIND_CONNECTION_REQUEST,
+    };
+    let mut flags = FLAG_LITTLE_ENDIAN;
+    if !payload.is_empty() {
+      flags |= FLAG_PAYLOAD;
+    }
+    if matches!(
+      kind,
+      BIND_CONNECTION_REQUEST
+        | OPEN_LOGICAL_PORT_REQUEST
+        | CHECK_LOGICAL_PORT_REQUEST
+        | KEEP_ALIVE_REQUEST
+    ) {
+      flags |= FLAG_REQUIRES_RESPONSE;
+    }
+    let mut message = Vec::with_capacity(CONTROL_HEADER_LEN + payload.len());
+    message.extend_from_slice(&[kind, flags]);
+    message.extend_from_slice(&((CONTROL_HEADER_LEN + payload.len()) as u16).to_le_bytes());
+    message.extend_from_slice(&self.transaction_id);
+    message.extend_from_slice(&payload);
+    frame(CONTROL_PORT, &message)
+  }
+
+  /// Parse the message of a frame received on [`CONTROL_PORT`].
+  pub fn from_message(message: &[u8]) -> io::Result<Self> {
+    let malformed = || invalid_data(format!("Malformed TCP control message {message:02x?}"));
+    if message.len() < CONTROL_HEADER_LEN {
+      return Err(malformed());
+    }
+    let kind = message[0];
+    let endianness = if message[1] & FLAG_LITTLE_ENDIAN != 0 {
+      Endianness::LittleEndian
+    } else {
+      Endianness::BigEndian
+    };
+    let mut transaction_id = TransactionId::default();
+    transaction_id.copy_from_slice(&message[4..CONTROL_HEADER_LEN]);
+    // The frame length is what counts, so the length field is not checked.
+    let mut payload = Payload {
+      bytes: &message[CONTROL_HEADER_LEN..],
+      endianness,
+    };
+    let control = match kind {
+      BIND_CONNECTION_REQUEST => {
+        let version = payload.take(2).ok_or_else(malformed)?;
+        let vendor_id = payload.take(2).ok_or_else(malformed)?;
+        Control::BindConnectionRequest {
+          protocol_version: ProtocolVersion {
+            major: version[0],
+            minor: version[1],
+          },
+          vendor_id: VendorId {
+            vendor_id: [vendor_id[0], vendor_id[1]],
+          },
+          locator: payload.locator().ok_or_else(malformed)?,
+        }
+      }
+      BIND_CONNECTION_RESPONSE => Control::BindConnectionResponse {
+        code: payload.code().ok_or_else(malformed)?,
+        locator: payload.locator().ok_or_else(malformed)?,
+      },
+      OPEN_LOGICAL_PORT_REQUEST => Control::OpenLogicalPortRequest {
+        port: payload.u16().ok_or_else(malformed)?,
+      },
+      OPEN_LOGICAL_PORT_RESPONSE => Control::OpenLogicalPortResponse {
+        code: payload.code().ok_or_else(malformed)?,
+      },
+      CHECK_LOGICAL_PORT_REQUEST => Control::CheckLogicalPortRequest {
+        ports: payload.ports().ok_or_else(malformed)?,
+      },
+      CHECK_LOGICAL_PORT_RESPONSE => Control::CheckLogicalPortResponse {
+        code: payload.code().ok_or_else(malformed)?,
+        ports: payload.ports().ok_or_else(malformed)?,
+      },
+      KEEP_ALIVE_REQUEST => Control::KeepAliveRequest {
+        locator: payload.locator().ok_or_else(malformed)?,
+      },
+      KEEP_ALIVE_RESPONSE => Control::KeepAliveResponse {
+        code: payload.code().ok_or_else(malformed)?,
+      },
+      LOGICAL_PORT_IS_CLOSED_REQUEST => Control::LogicalPortIsClosedRequest {
+        port: payload.u16().ok_or_else(malformed)?,
+      },
+      UNBIND_CONNECTION_REQUEST => Control::UnbindConnectionRequest,
+      _ => return Err(malformed()),
+    };
+    Ok(Self {
+      transaction_id,
+      control,
+    })
+  }
+}
+
+// Reads the payload of a control message
+struct Payload<'a> {
+  bytes: &'a [u8],
+  endianness: Endianness,
+}
+
+impl<'a> Payload<'a> {
+  fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+    if self.bytes.len() < len {
+      return None;
+    }
+    let (taken, rest) = self.bytes.split_at(len);
+    self.bytes = rest;
+    Some(taken)
+  }
+
+  fn u16(&mut self) -> Option<u16> {
+    let bytes = self.take(2)?.try_into().ok()?;
+    Some(match self.endianness {
+      Endianness::LittleEndian => u16::from_le_bytes(bytes),
+      Endianness::BigEndian => u16::from_be_bytes(bytes),
+    })
+  }
+
+  fn u32(&mut self) -> Option<u32> {
+    let bytes = self.take(4)?.try_into().ok()?;
+    Some(match self.endianness {
+      Endianness::LittleEndian => u32::from_le_bytes(bytes),
+      Endianness::BigEndian => u32::from_be_bytes(bytes),
+    })
+  }
+
+  fn code(&mut self) -> Option<ResponseCode> {
+    self.u32().map(ResponseCode)
+  }
+
+  fn locator(&mut self) -> Option<Locator> {
+    let bytes = self.take(24)?;
+    Locator::read_from_buffer_with_ctx(self.endianness, bytes).ok()
+  }
+
+  fn ports(&mut self) -> Option<Vec<u16>> {
+    let count = self.u32()? as usize;
+    if count > self.bytes.len() / 2 {
+      return None;
+    }
+    (0..count).map(|_| self.u16()).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn frames_are_read_back() {
+    let mut stream = Vec::new();
+    stream.extend(frame(7410, b"RTPS one"));
+    stream.extend(frame(CONTROL_PORT, b""));
+    stream.extend(frame(7412, b"RTPS two"));
+
+    let mut reader = stream.as_slice();
+    assert_eq!(
+      read_frame(&mut reader).unwrap().unwrap(),
+      (7410, b"RTPS one".to_vec())
+    );
+    assert_eq!(read_frame(&mut reader).unwrap().unwrap(), (0, vec![]));
+    assert_eq!(
+      read_frame(&mut reader).unwrap().unwrap(),
+      (7412, b"RTPS two".to_vec())
+    );
+    assert_eq!(read_frame(&mut reader).unwrap(), None);
+  }
+
+  #[test]
+  fn tcp_header_is_as_in_the_psm() {
+    let framed = frame(0x1234, &[0xff, 0xff, 0xff, 0xff, 2]);
+    assert_eq!(&framed[0..4], b"RTCP");
+    assert_eq!(framed[4..8], 19u32.to_le_bytes());
+    assert_eq!(framed[8..12], (4 * 0xff + 2u32).to_le_bytes());
+    assert_eq!(framed[12..14], [0x34, 0x12]);
+  }
+
+  #[test]
+  fn malformed_frames_are_errors() {
+    let framed = frame(7410, b"RTPS message");
+    let mut truncated = &framed[..framed.len() - 1];
+    assert!(read_frame(&mut truncated).is_err());
+
+    let mut bad_magic = framed.clone();
+    bad_magic[0] = b'X';
+    assert_eq!(
+      read_frame(&mut bad_magic.as_slice()).unwrap_err().kind(),
+      io::ErrorKind::InvalidData
+    );
+
+    let mut bad_checksum = framed.clone();
+    *bad_checksum.last_mut().unwrap() ^= 1;
+    assert_eq!(
+      read_frame(&mut bad_checksum.as_slice()).unwrap_err().kind(),
+      io::ErrorKind::InvalidData
+    );
+
+    let mut no_checksum = framed;
+    no_checksum[8..12].copy_from_slice(&[0; 4]);
+    assert!(read_frame(&mut no_checksum.as_slice()).unwrap().is_some());
+
+    let mut oversized = frame(7410, b"");
+    let len = (TCP_HEADER_LEN + MAX_MESSAGE_LEN + 1) as u32;
+    oversized[4..8].copy_from_slice(&len.to_le_bytes());
+    assert_eq!(
+      read_frame(&mut oversized.as_slice()).unwrap_err().kind(),
+      io::ErrorKind::InvalidData
+    );
+  }
+
+  #[test]
+  fn control_messages_are_read_back() {
+    let locator = Locator::tcp_v4("10.0.0.1".parse().unwrap(), 7410, 7410);
+    let controls = [
+      Control::BindConnectionRequest {
+        protocol_version: ProtocolVersion::THIS_IMPLEMENTATION,
+        vendor_id: VendorId::THIS_IMPLEMENTATION,
+        locator,
+      },
+      Control::BindConnectionResponse {
+        code: ResponseCode::OK,
+        locator,
+      },
+      Control::OpenLogicalPortRequest { port: 7412 },
+      Control::OpenLogicalPortResponse {
+        code: ResponseCode::INVALID_PORT,
+      },
+      Control::CheckLogicalPortRequest {
+        ports: vec![7410, 7412],
+      },
+      Control::CheckLogicalPortResponse {
+        code: ResponseCode::OK,
+        ports: vec![7410],
+      },
+      Control::KeepAliveRequest { locator },
+      Control::KeepAliveResponse {
+        code: ResponseCode::UNKNOWN_LOCATOR,
+      },
+      Control::LogicalPortIsClosedRequest { port: 7412 },
+      Control::UnbindConnectionRequest,
+    ];
+    for (n, control) in controls.into_iter().enumerate() {
+      let message = ControlMessage {
+        transaction_id: [n as u8; 12],
+        control,
+      };
+      let (port, payload) = read_frame(&mut message.to_frame().as_slice())
+        .unwrap()
+        .unwrap();
+      assert_eq!(port, CONTROL_PORT);
+      assert_eq!(ControlMessage::from_message(&payload).unwrap(), message);
+    }
+  }
+
+  #[test]
+  fn big_endian_control_message_is_read() {
+    // OPEN_LOGICAL_PORT_REQUEST of port 7412, with the payload flag only
+    let mut message = vec![0xD2, FLAG_PAYLOAD, 0, 18];
+    message.extend_from_slice(&[9; 12]);
+    message.extend_from_slice(&7412u16.to_be_bytes());
+    assert_eq!(
+      ControlMessage::from_message(&message).unwrap(),
+      ControlMessage {
+        transaction_id: [9; 12],
+        control: Control::OpenLogicalPortRequest { port: 7412 },
+      }
+    );
+    assert!(ControlMessage::from_message(&message[..17]).is_err());
+    message[0] = 0xAA;
+    assert!(ControlMessage::from_message(&message).is_err());
+  }
+
+  #[test]
+  fn initial_peers_are_tcp_locators() {
+    let tcp = TcpTransport::new(["192.168.1.2:7420".parse().unwrap()]);
+    let locators = tcp.initial_peer_locators(1);
+    assert_eq!(locators.len(), 1);
+    assert!(locators[0].is_tcp());
+    assert_eq!(
+      locators[0].tcp_physical_address(),
+      Some("192.168.1.2:7420".parse().unwrap())
+    );
+    assert_eq!(locators[0].tcp_logical_port(), Some(7660));
+  }
+}
@@ -0,0 +1,112 @@
+//! Abstraction of how RTPS messages are sent and received: over UDP (the
+//! default) or over TCP.
+
+use std::{collections::HashMap, io};
+
+use bytes::Bytes;
+use log::error;
+
+use crate::{
+  network::{udp_listener::PacketOrigin, util::InterfaceConfig},
+  rtps::{
+    constant::{
+      DISCOVERY_LISTENER_TOKEN, DISCOVERY_LISTENER_V6_TOKEN, TCP_LISTENER_TOKEN,
+      TCP_RETURN_LISTENER_TOKEN, USER_TRAFFIC_LISTENER_TOKEN, USER_TRAFFIC_LISTENER_V6_TOKEN,
+    },
+    outbound::SocketId,
+    transmit::InterfaceSelector,
+  },
+  structure::locator::Locator,
+};
+
+/// Sends RTPS messages to Locators. Readers and Writers send through this.
+///
+/// Control traffic (`send_*`) is never dropped by the transport itself. Bulk
+/// traffic (`try_send_*`) returns the sockets that could not take the message
+/// right now, so that the Writer can back off until they are writable.
+pub(crate) trait Transport {
+  /// Can this transport send to `locator` at all? Other locators are skipped.
+  fn supports_locator(&self, locator: &Locator) -> bool;
+
+  fn send_to_locator(&self, buffer: &[u8], locator: &Locator);
+
+  fn send_to_locator_list(&self, buffer: &[u8], ll: &[Locator]) {
+    for loc in ll {
+      self.send_to_locator(buffer, loc);
+    }
+  }
+
+  /// Send to a multicast locator out of a single local interface.
+  fn send_to_multicast_locator_via(
+    &self,
+    buffer: &[u8],
+    locator: &Locator,
+    interface: &InterfaceSelector,
+  );
+
+  fn try_send_to_locator(&self, buffer: &[u8], locator: &Locator) -> Vec<SocketId>;
+
+  fn try_send_to_multicast_locator_via(
+    &self,
+    buffer: &[u8],
+    locator: &Locator,
+    interface: &InterfaceSelector,
+  ) -> Vec<SocketId>;
+
+  /// The local interfaces this transport can send multicast on.
+  fn multicast_interfaces(&self) -> Vec<InterfaceSelector>;
+}
+
+/// Receives RTPS messages. The event loop polls each listener with its own
+/// token, and reads the messages when it is readable.
+pub(crate) trait TransportListener: Send {
+  /// The source of readiness events to register in the poll. It must be
+  /// registered level-triggered.
+  fn evented(&self) -> &dyn mio_06::Evented;
+
+  /// Take up to `max_messages` received messages.
+  fn messages_bounded(&mut self, max_messages: usize) -> Vec<(Bytes, PacketOrigin)>;
+
+  /// The locators to advertise for this listener.
+  fn to_locator_address(&self, interfaces: &InterfaceConfig) -> io::Result<Vec<Locator>>;
+
+  /// Called after a network configuration change.
+  fn refresh_interfaces(&mut self, _interfaces: &InterfaceConfig) {}
+}
+
+/// The listeners of a participant, keyed by their poll token.
+pub(crate) type Listeners = HashMap<mio_06::Token, Box<dyn TransportListener>>;
+
+/// The locators of `listeners`, keyed by their poll token. The IPv6 unicast
+/// listeners are advertised together with their IPv4 counterparts, and the
+/// TCP listener as both the discovery and the user traffic listener. The
+/// listener of messages sent back on TCP connections is not advertised.
+pub(crate) fn self_locators(
+  listeners: &Listeners,
+  interfaces: &InterfaceConfig,
+) -> HashMap<mio_06::Token, Vec<Locator>> {
+  let mut self_locators: HashMap<mio_06::Token, Vec<Locator>> = HashMap::new();
+  for (token, listener) in listeners {
+    let locators = match listener.to_locator_address(interfaces) {
+      Ok(locs) => locs,
+      Err(e) => {
+        error!("No local network address for token {token:?}: {e:?}");
+        vec![]
+      }
+    };
+    let tokens: &[mio_06::Token] = match *token {
+      DISCOVERY_LISTENER_V6_TOKEN => &[DISCOVERY_LISTENER_TOKEN],
+      USER_TRAFFIC_LISTENER_V6_TOKEN => &[USER_TRAFFIC_LISTENER_TOKEN],
+      TCP_LISTENER_TOKEN => &[DISCOVERY_LISTENER_TOKEN, USER_TRAFFIC_LISTENER_TOKEN],
+      TCP_RETURN_LISTENER_TOKEN => &[],
+      _ => std::slice::from_ref(token),
+    };
+    for t in tokens {
+      self_locators
+        .entry(*t)
+        .or_default()
+        .extend(locators.iter().copied());
+    }
+  }
+  self_locators
+}
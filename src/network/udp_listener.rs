@@ -9,9 +9,13 @@ use socket2::{Domain, Protocol, SockAddr, Socket, Type};
 use bytes::{Bytes, BytesMut};
 
 use crate::{
-  network::util::{
-    build_ifindex_to_interface_map, get_local_multicast_ip_addrs_filtered,
-    get_local_multicast_locators, get_local_unicast_locators_filtered,
+  network::{
+    multicast::IpVersion,
+    transport::TransportListener,
+    util::{
+      build_ifindex_to_interface_map, get_local_multicast_interfaces_filtered,
+      get_local_multicast_locators, get_local_unicast_locators_filtered, InterfaceConfig,
+    },
   },
   rtps::transmit::InterfaceSelector,
   serialization::padding_needed_for_alignment_4,
@@ -43,6 +47,13 @@ const MAX_MESSAGE_SIZE: usize = 64 * 1024; // This is max we can get from UDP.
 const MESSAGE_BUFFER_ALLOCATION_CHUNK: usize = 256 * 1024; // must be >= MAX_MESSAGE_SIZE
 static_assertions::const_assert!(MESSAGE_BUFFER_ALLOCATION_CHUNK > MAX_MESSAGE_SIZE);
 
+// A datagram read by `UDPListener::recv_into`.
+struct Received {
+  nbytes: usize,
+  truncated: bool, // the datagram did not fit into the buffer
+  origin: PacketOrigin,
+}
+
 /// Listens to messages coming to specified host port combination.
 /// Only messages from added listen addressed are read when get_all_messages is
 /// called.
@@ -50,7 +61,7 @@ static_assertions::const_assert!(MESSAGE_BUFFER_ALLOCATION_CHUNK > MAX_MESSAGE_S
 pub struct UDPListener {
   socket: mio_06::net::UdpSocket,
   receive_buffer: BytesMut,
-  multicast_group: Option<Ipv4Addr>,
+  multicast_group: Option<IpAddr>,
   has_multicast_join: bool,
   // Cached OS interface-index -> local interface map, used to resolve the
   // receiving interface reported by IP_PKTINFO. Built once at construction.
@@ -59,13 +70,16 @@ pub struct UDPListener {
 
 impl Drop for UDPListener {
   fn drop(&mut self) {
-    if let Some(mcg) = self.multicast_group {
-      self
+    match self.multicast_group {
+      Some(IpAddr::V4(mcg)) => self
         .socket
         .leave_multicast_v4(&mcg, &Ipv4Addr::UNSPECIFIED)
         .unwrap_or_else(|e| {
           error!("leave_multicast_group: {e:?}");
-        });
+        }),
+      // IPv6 memberships are per interface index. They are dropped when the
+      // socket is closed.
+      Some(IpAddr::V6(_)) | None => {}
     }
   }
 }
@@ -77,7 +91,17 @@ impl UDPListener {
     reuse_addr: bool,
     recv_buffer_size: usize,
   ) -> io::Result<mio_06::net::UdpSocket> {
-    let raw_socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    let address = SocketAddr::new(host.parse().map_err(io::Error::other)?, port);
+    let raw_socket = Socket::new(
+      Domain::for_address(address),
+      Type::DGRAM,
+      Some(Protocol::UDP),
+    )?;
+    if address.is_ipv6() {
+      // Do not receive IPv4 traffic through this socket, so that it does not
+      // clash with an IPv4 listener on the same port.
+      raw_socket.set_only_v6(true)?;
+    }
 
     if recv_buffer_size > 0 {
       raw_socket
@@ -131,7 +155,7 @@ impl UDPListener {
     // which local interface each one arrived on. Best-effort: if it fails we
     // simply lose interface metadata and fall back to the legacy send path.
     #[cfg(unix)]
-    {
+    if address.is_ipv4() {
       if let Err(e) = nix::sys::socket::setsockopt(
         &raw_socket,
         nix::sys::socket::sockopt::Ipv4PacketInfo,
@@ -144,8 +168,6 @@ impl UDPListener {
       }
     }
 
-    let address = SocketAddr::new(host.parse().map_err(io::Error::other)?, port);
-
     if let Err(e) = raw_socket.bind(&SockAddr::from(address)) {
       info!("new_socket - cannot bind socket: {e:?}");
       return Err(e);
@@ -166,19 +188,6 @@ impl UDPListener {
     Ok(mio_socket)
   }
 
-  pub fn to_locator_address(&self, only_networks: Option<&[IpAddr]>) -> io::Result<Vec<Locator>> {
-    let local_port = self.socket.local_addr()?.port();
-
-    match self.multicast_group {
-      Some(_ipv4_addr) if self.has_multicast_join => Ok(get_local_multicast_locators(local_port)),
-      Some(_ipv4_addr) => Ok(vec![]),
-      None => Ok(get_local_unicast_locators_filtered(
-        local_port,
-        only_networks,
-      )),
-    }
-  }
-
   #[cfg(test)]
   pub fn new_unicast(host: &str, port: u16) -> io::Result<Self> {
     Self::new_unicast_with_buf_size(host, port, 0)
@@ -201,51 +210,68 @@ impl UDPListener {
   }
 
   #[cfg(test)]
-  pub fn new_multicast(host: &str, port: u16, multicast_group: Ipv4Addr) -> io::Result<Self> {
-    Self::new_multicast_with_buf_size(host, port, multicast_group, 0, None)
+  pub fn new_multicast(host: &str, port: u16, multicast_group: IpAddr) -> io::Result<Self> {
+    use crate::network::multicast::{DiscoveryMulticast, MulticastScope};
+
+    let interfaces = InterfaceConfig {
+      discovery_multicast: DiscoveryMulticast::new(
+        MulticastScope::LinkLocal,
+        IpVersion::of(&multicast_group),
+      ),
+      ..Default::default()
+    };
+    Self::new_multicast_with_buf_size(host, port, multicast_group, 0, &interfaces)
   }
 
   pub fn new_multicast_with_buf_size(
     host: &str,
     port: u16,
-    multicast_group: Ipv4Addr,
+    multicast_group: IpAddr,
     recv_buffer_size: usize,
-    only_networks: Option<&[IpAddr]>,
+    interfaces: &InterfaceConfig,
   ) -> io::Result<Self> {
     if !multicast_group.is_multicast() {
       return io::Result::Err(io::Error::other("Not a multicast address"));
     }
 
     let mio_socket = Self::new_listening_socket(host, port, true, recv_buffer_size)?;
+    let joined_multicast = Self::join_multicast_group(&mio_socket, multicast_group, interfaces)?;
+
+    Ok(Self {
+      socket: mio_socket,
+      receive_buffer: BytesMut::with_capacity(MESSAGE_BUFFER_ALLOCATION_CHUNK),
+      multicast_group: Some(multicast_group),
+      has_multicast_join: joined_multicast,
+      ifindex_map: build_ifindex_to_interface_map(),
+    })
+  }
+
+  // Joins `multicast_group` on every multicast-capable local interface of the
+  // same IP version. Returns true if the socket is a member of the group on at
+  // least one interface. An interface we have already joined counts as a
+  // success, so this can be called again after the interface set has changed.
+  fn join_multicast_group(
+    socket: &mio_06::net::UdpSocket,
+    multicast_group: IpAddr,
+    interfaces: &InterfaceConfig,
+  ) -> io::Result<bool> {
     let mut joined_multicast = false;
 
-    for multicast_if_ipaddr in get_local_multicast_ip_addrs_filtered(only_networks)? {
-      match multicast_if_ipaddr {
-        IpAddr::V4(a) => mio_socket
-          .join_multicast_v4(&multicast_group, &a)
-          .map(|()| {
-            joined_multicast = true;
-          })
-          .unwrap_or_else(|e| {
-            warn!(
-              "join_multicast_v4 failed: {e:?}. multicast_group [{multicast_group:?}] interface \
-               [{a:?}]"
-            );
-          }),
-
-        IpAddr::V6(addr) => {
-          mio_socket
-            .join_multicast_v6(&addr, 0)
-            .map(|()| {
-              joined_multicast = true;
-            })
-            .unwrap_or_else(|e| {
-              warn!(
-                "join_multicast_v6 failed. err: {e}. mcast group: [{multicast_group:?}], \
-                 addr:[{addr:?}]"
-              );
-            });
-        }
+    let ip_version = IpVersion::of(&multicast_group);
+    for multicast_if in get_local_multicast_interfaces_filtered(interfaces, ip_version)? {
+      let multicast_if_ipaddr = multicast_if.ip;
+      let join_result = match (multicast_group, multicast_if_ipaddr) {
+        (IpAddr::V4(group), IpAddr::V4(a)) => socket.join_multicast_v4(&group, &a),
+        (IpAddr::V6(group), _) => socket.join_multicast_v6(&group, multicast_if.index),
+        (IpAddr::V4(_), IpAddr::V6(_)) => continue,
+      };
+      match join_result {
+        Ok(()) => joined_multicast = true,
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => joined_multicast = true, /* already a member */
+        Err(e) => warn!(
+          "Joining multicast group [{multicast_group:?}] on interface [{multicast_if_ipaddr:?}] \
+           failed: {e:?}"
+        ),
       }
     }
 
@@ -255,21 +281,9 @@ impl UDPListener {
          be advertised."
       );
     }
-
-    Ok(Self {
-      socket: mio_socket,
-      receive_buffer: BytesMut::with_capacity(MESSAGE_BUFFER_ALLOCATION_CHUNK),
-      multicast_group: Some(multicast_group),
-      has_multicast_join: joined_multicast,
-      ifindex_map: build_ifindex_to_interface_map(),
-    })
-  }
-
-  pub fn mio_socket(&mut self) -> &mut mio_06::net::UdpSocket {
-    &mut self.socket
+    Ok(joined_multicast)
   }
 
-  #[cfg(test)]
   pub fn port(&self) -> u16 {
     match self.socket.local_addr() {
       Ok(add) => add.port(),
@@ -281,15 +295,15 @@ impl UDPListener {
   // We cannot read a single packet only, because we use edge-triggered polls.
   #[cfg(test)]
   pub fn get_message(&self) -> Vec<u8> {
-    let mut buf: [u8; MAX_MESSAGE_SIZE] = [0; MAX_MESSAGE_SIZE];
+    let mut buf = Vec::with_capacity(MAX_MESSAGE_SIZE);
 
     // try getting the message several times
     for _ in 0..10 {
-      match self.socket.recv(&mut buf) {
+      match self.get_message_into(&mut buf) {
         Ok(nbytes) => {
           assert!(nbytes > 0, "tests should always read data");
 
-          return buf[..nbytes].to_vec();
+          return buf;
         }
         Err(e) => {
           // handle EAGAIN on UNIX platforms.
@@ -308,6 +322,171 @@ impl UDPListener {
     panic!("test helper didn't recv message after ten attempts.");
   }
 
+  /// Receive a single datagram into `buf`, reusing its allocation, and return
+  /// its length.
+  ///
+  /// The datagram is received into the capacity of `buf`, which is not
+  /// zeroed or grown, so allocate it once with e.g.
+  /// `Vec::with_capacity(64 * 1024)` and pass it to every call. On success
+  /// the length of `buf` is the length of the datagram, otherwise `buf` is
+  /// left empty.
+  ///
+  /// Returns an error of kind [`io::ErrorKind::WouldBlock`] if no datagram is
+  /// waiting, and of kind [`io::ErrorKind::InvalidData`] if the datagram was
+  /// larger than the capacity of `buf`. The datagram is discarded in the
+  /// latter case, because UDP cannot receive the rest of it later. On
+  /// non-Unix platforms, the error of a too large datagram is the one
+  /// reported by the OS.
+  #[allow(dead_code)] // The event loop receives through `messages_bounded` instead.
+  pub fn get_message_into(&self, buf: &mut Vec<u8>) -> io::Result<usize> {
+    buf.clear();
+    unsafe {
+      // We do not read undefined data, because the recv() will overwrite
+      // this space and we truncate the rest away below.
+      buf.set_len(buf.capacity());
+    }
+    let received = Self::recv_into(&self.socket, &self.ifindex_map, buf);
+    buf.truncate(match received {
+      Ok(Some(Received {
+        nbytes,
+        truncated: false,
+        ..
+      })) => nbytes,
+      _ => 0,
+    });
+    match received {
+      Ok(Some(Received {
+        truncated: true, ..
+      })) => Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+          "Datagram larger than the receive buffer of {} bytes",
+          buf.capacity()
+        ),
+      )),
+      Ok(Some(Received { nbytes, .. })) => Ok(nbytes),
+      Ok(None) => Err(io::ErrorKind::WouldBlock.into()),
+      Err(e) => Err(e),
+    }
+  }
+
+  /// Receive a single datagram into `self.receive_buffer`, capturing its
+  /// [`PacketOrigin`]. Returns `Ok(None)` when the socket would block.
+  fn recv_one(&mut self) -> io::Result<Option<(usize, PacketOrigin)>> {
+    let received = Self::recv_into(
+      &self.socket,
+      &self.ifindex_map,
+      &mut self.receive_buffer[..MAX_MESSAGE_SIZE],
+    )?;
+    // No UDP datagram is larger than MAX_MESSAGE_SIZE, so none is truncated.
+    Ok(received.map(|r| (r.nbytes, r.origin)))
+  }
+
+  /// Receive a single datagram into `buf`. Returns `Ok(None)` when the socket
+  /// would block.
+  #[cfg(unix)]
+  fn recv_into(
+    socket: &mio_06::net::UdpSocket,
+    ifindex_map: &HashMap<u32, InterfaceSelector>,
+    buf: &mut [u8],
+  ) -> io::Result<Option<Received>> {
+    use std::{io::IoSliceMut, os::unix::io::AsRawFd};
+
+    use nix::{
+      errno::Errno,
+      sys::socket::{recvmsg, ControlMessageOwned, MsgFlags, SockaddrStorage},
+    };
+
+    let fd = socket.as_raw_fd();
+    let mut cmsg_space = nix::cmsg_space!(nix::libc::in_pktinfo);
+
+    // Read the datagram and pull out the Copy metadata; the borrow of
+    // `buf` (through `iov`) ends when this block ends.
+    let (nbytes, truncated, source, ifindex, spec_dst) = {
+      let mut iov = [IoSliceMut::new(buf)];
+      let msg =
+        match recvmsg::<SockaddrStorage>(fd, &mut iov, Some(&mut cmsg_space), MsgFlags::empty()) {
+          Ok(m) => m,
+          Err(Errno::EAGAIN) => return Ok(None),
+          Err(e) => return Err(io::Error::from_raw_os_error(e as i32)),
+        };
+
+      let nbytes = msg.bytes;
+      let truncated = msg.flags.contains(MsgFlags::MSG_TRUNC);
+      let source = msg.address.and_then(sockaddr_storage_to_socketaddr);
+
+      let mut ifindex = 0u32;
+      let mut spec_dst: Option<IpAddr> = None;
+      for cmsg in msg.cmsgs()? {
+        if let ControlMessageOwned::Ipv4PacketInfo(info) = cmsg {
+          ifindex = info.ipi_ifindex as u32;
+          let addr = Ipv4Addr::from(u32::from_be(info.ipi_spec_dst.s_addr));
+          if !addr.is_unspecified() {
+            spec_dst = Some(IpAddr::V4(addr));
+          }
+        }
+      }
+      (nbytes, truncated, source, ifindex, spec_dst)
+    };
+
+    // Prefer the exact local destination address (matches sender interface
+    // keys directly); otherwise resolve the interface index.
+    let local_if = spec_dst
+      .map(InterfaceSelector::Ip)
+      .or_else(|| ifindex_map.get(&ifindex).copied());
+
+    Ok(Some(Received {
+      nbytes,
+      truncated,
+      origin: PacketOrigin { source, local_if },
+    }))
+  }
+
+  /// Non-Unix fallback: capture the source address only (no interface info).
+  /// The OS reports truncated datagrams as errors.
+  #[cfg(not(unix))]
+  fn recv_into(
+    socket: &mio_06::net::UdpSocket,
+    _ifindex_map: &HashMap<u32, InterfaceSelector>,
+    buf: &mut [u8],
+  ) -> io::Result<Option<Received>> {
+    match socket.recv_from(buf) {
+      Ok((nbytes, source)) => Ok(Some(Received {
+        nbytes,
+        truncated: false,
+        origin: PacketOrigin {
+          source: Some(source),
+          local_if: None,
+        },
+      })),
+      Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+      Err(e) => Err(e),
+    }
+  }
+
+  #[cfg(test)] // normally done in .drop()
+  pub fn leave_multicast(&self, address: &IpAddr) -> io::Result<()> {
+    match address {
+      IpAddr::V4(group) if group.is_multicast() => self
+        .socket
+        .leave_multicast_v4(group, &Ipv4Addr::UNSPECIFIED),
+      IpAddr::V6(group) if group.is_multicast() => {
+        let interfaces = InterfaceConfig::default();
+        for multicast_if in get_local_multicast_interfaces_filtered(&interfaces, IpVersion::V6)? {
+          self.socket.leave_multicast_v6(group, multicast_if.index)?;
+        }
+        Ok(())
+      }
+      _ => io::Result::Err(io::Error::other("Not a multicast address")),
+    }
+  }
+}
+
+impl TransportListener for UDPListener {
+  fn evented(&self) -> &dyn mio_06::Evented {
+    &self.socket
+  }
+
   /// Drain up to `max_messages` datagrams waiting in the socket, each paired
   /// with its [`PacketOrigin`] (source address + receiving interface, when
   /// available). Pass `usize::MAX` to read everything currently queued. Used
@@ -316,7 +495,7 @@ impl UDPListener {
   /// (single-threaded) loop from servicing discovery/control sockets. Relies
   /// on the listener being registered level-triggered, so undrained data
   /// re-fires on the next poll.
-  pub fn messages_bounded(&mut self, max_messages: usize) -> Vec<(Bytes, PacketOrigin)> {
+  fn messages_bounded(&mut self, max_messages: usize) -> Vec<(Bytes, PacketOrigin)> {
     let mut messages = Vec::with_capacity(4);
 
     loop {
@@ -390,86 +569,37 @@ impl UDPListener {
     // Answer: https://github.com/rust-lang/rust/issues/46500
   }
 
-  /// Receive a single datagram into `self.receive_buffer`, capturing its
-  /// [`PacketOrigin`]. Returns `Ok(None)` when the socket would block.
-  #[cfg(unix)]
-  fn recv_one(&mut self) -> io::Result<Option<(usize, PacketOrigin)>> {
-    use std::{io::IoSliceMut, os::unix::io::AsRawFd};
-
-    use nix::{
-      errno::Errno,
-      sys::socket::{recvmsg, ControlMessageOwned, MsgFlags, SockaddrStorage},
-    };
-
-    let fd = self.socket.as_raw_fd();
-    let mut cmsg_space = nix::cmsg_space!(nix::libc::in_pktinfo);
-
-    // Read the datagram and pull out the Copy metadata; the borrow of
-    // `receive_buffer` (through `iov`) ends when this block ends.
-    let (nbytes, source, ifindex, spec_dst) = {
-      let mut iov = [IoSliceMut::new(
-        &mut self.receive_buffer[..MAX_MESSAGE_SIZE],
-      )];
-      let msg =
-        match recvmsg::<SockaddrStorage>(fd, &mut iov, Some(&mut cmsg_space), MsgFlags::empty()) {
-          Ok(m) => m,
-          Err(Errno::EAGAIN) => return Ok(None),
-          Err(e) => return Err(io::Error::from_raw_os_error(e as i32)),
-        };
-
-      let nbytes = msg.bytes;
-      let source = msg.address.and_then(sockaddr_storage_to_socketaddr);
-
-      let mut ifindex = 0u32;
-      let mut spec_dst: Option<IpAddr> = None;
-      for cmsg in msg.cmsgs()? {
-        if let ControlMessageOwned::Ipv4PacketInfo(info) = cmsg {
-          ifindex = info.ipi_ifindex as u32;
-          let addr = Ipv4Addr::from(u32::from_be(info.ipi_spec_dst.s_addr));
-          if !addr.is_unspecified() {
-            spec_dst = Some(IpAddr::V4(addr));
-          }
-        }
-      }
-      (nbytes, source, ifindex, spec_dst)
-    };
+  fn to_locator_address(&self, interfaces: &InterfaceConfig) -> io::Result<Vec<Locator>> {
+    let local_addr = self.socket.local_addr()?;
+    let local_port = local_addr.port();
 
-    // Prefer the exact local destination address (matches sender interface
-    // keys directly); otherwise resolve the interface index.
-    let local_if = spec_dst
-      .map(InterfaceSelector::Ip)
-      .or_else(|| self.ifindex_map.get(&ifindex).copied());
-
-    Ok(Some((nbytes, PacketOrigin { source, local_if })))
-  }
-
-  /// Non-Unix fallback: capture the source address only (no interface info).
-  #[cfg(not(unix))]
-  fn recv_one(&mut self) -> io::Result<Option<(usize, PacketOrigin)>> {
-    match self
-      .socket
-      .recv_from(&mut self.receive_buffer[..MAX_MESSAGE_SIZE])
-    {
-      Ok((nbytes, source)) => Ok(Some((
-        nbytes,
-        PacketOrigin {
-          source: Some(source),
-          local_if: None,
-        },
-      ))),
-      Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
-      Err(e) => Err(e),
+    match self.multicast_group {
+      Some(group) if self.has_multicast_join => Ok(get_local_multicast_locators(group, local_port)),
+      Some(_group) => Ok(vec![]),
+      // A socket receives only from addresses of its own IP version.
+      None => Ok(get_local_unicast_locators_filtered(
+        local_port,
+        interfaces,
+        &[IpVersion::of(&local_addr.ip())],
+      )),
     }
   }
 
-  #[cfg(test)] // normally done in .drop()
-  pub fn leave_multicast(&self, address: &Ipv4Addr) -> io::Result<()> {
-    if address.is_multicast() {
-      return self
-        .socket
-        .leave_multicast_v4(address, &Ipv4Addr::UNSPECIFIED);
+  /// Re-reads the local network interfaces after a network configuration
+  /// change: refreshes the interface index map used to resolve the receiving
+  /// interface, and joins our multicast group (if any) on interfaces that have
+  /// appeared since construction.
+  fn refresh_interfaces(&mut self, interfaces: &InterfaceConfig) {
+    self.ifindex_map = build_ifindex_to_interface_map();
+    if let Some(multicast_group) = self.multicast_group {
+      match Self::join_multicast_group(&self.socket, multicast_group, interfaces) {
+        Ok(joined) => self.has_multicast_join = joined,
+        Err(e) => {
+          error!("Cannot refresh multicast group membership: {e:?}");
+          self.has_multicast_join = false;
+        }
+      }
     }
-    io::Result::Err(io::Error::other("Not a multicast address"))
   }
 }
 
@@ -498,7 +628,11 @@ mod tests {
   use std::{thread, time};
 
   use super::*;
-  use crate::network::udp_sender::*;
+  use crate::network::{
+    multicast::{DiscoveryMulticast, MulticastScope},
+    transport::Transport,
+    udp_sender::*,
+  };
 
   #[test]
   fn udpl_single_address() {
@@ -518,8 +652,8 @@ mod tests {
 
   #[test]
   fn udpl_multicast_address() {
-    let listener =
-      UDPListener::new_multicast("0.0.0.0", 10002, Ipv4Addr::new(239, 255, 0, 1)).unwrap();
+    let group = IpAddr::V4(Ipv4Addr::new(239, 255, 0, 1));
+    let listener = UDPListener::new_multicast("0.0.0.0", 10002, group).unwrap();
     let sender = UDPSender::new_with_random_port().unwrap();
 
     // setsockopt(sender.socket.as_raw_fd(), IpMulticastLoop, &true)
@@ -528,18 +662,75 @@ mod tests {
     let data: Vec<u8> = vec![2, 4, 6];
 
     sender
-      .send_multicast(&data, Ipv4Addr::new(239, 255, 0, 1), 10002)
+      .send_multicast(&data, group, 10002)
       .expect("Failed to send multicast");
 
     thread::sleep(time::Duration::from_secs(1));
 
     let rec_data = listener.get_message();
 
-    listener
-      .leave_multicast(&Ipv4Addr::new(239, 255, 0, 1))
-      .unwrap();
+    listener.leave_multicast(&group).unwrap();
 
     assert_eq!(rec_data.len(), 3);
     assert_eq!(rec_data, data);
   }
+
+  #[test]
+  fn udpl_ipv6_address() {
+    let listener_v6 = UDPListener::new_unicast("::1", 10003).unwrap();
+    let listener_v4 = UDPListener::new_unicast("127.0.0.1", 10003).unwrap();
+
+    let v6_locator = Locator::from(SocketAddr::new("::1".parse().unwrap(), 10003));
+    let v4_locator = Locator::from(SocketAddr::new("127.0.0.1".parse().unwrap(), 10003));
+    let interfaces = InterfaceConfig {
+      discovery_multicast: DiscoveryMulticast::new(MulticastScope::LinkLocal, IpVersion::V6),
+      ..Default::default()
+    };
+    // Each listener advertises only addresses of its own IP version.
+    let locators = listener_v6.to_locator_address(&interfaces).unwrap();
+    assert!(locators.contains(&v6_locator));
+    assert!(locators.iter().all(|l| matches!(l, Locator::UdpV6(_))));
+    assert!(!listener_v4
+      .to_locator_address(&interfaces)
+      .unwrap()
+      .contains(&v6_locator));
+
+    // With IPv6 discovery, the sender reaches both.
+    let sender = UDPSender::new_with_networks(0, &interfaces, 0).unwrap();
+    let data: Vec<u8> = vec![6, 4, 6];
+    sender.send_to_locator(&data, &v6_locator);
+    sender.send_to_locator(&data, &v4_locator);
+
+    assert_eq!(listener_v6.get_message(), data);
+    assert_eq!(listener_v4.get_message(), data);
+  }
+
+  #[test]
+  fn udpl_receive_into_reused_buffer() {
+    let listener = UDPListener::new_unicast("127.0.0.1", 10004).unwrap();
+    let sender = UDPSender::new_with_random_port().unwrap();
+    let addrs = vec![SocketAddr::new("127.0.0.1".parse().unwrap(), 10004)];
+
+    let mut buf = Vec::with_capacity(16);
+    let allocation = buf.as_ptr();
+    let err = listener.get_message_into(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    sender.send_to_all(&[1, 2, 3], &addrs);
+    sender.send_to_all(&[0; 17], &addrs);
+    sender.send_to_all(&[4, 5], &addrs);
+    thread::sleep(time::Duration::from_millis(100));
+
+    assert_eq!(listener.get_message_into(&mut buf).unwrap(), 3);
+    assert_eq!(buf, [1, 2, 3]);
+    // A datagram larger than the buffer is an error, not a partial message.
+    let err = listener.get_message_into(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(buf.is_empty());
+    // The rest of the truncated datagram does not come out as a message.
+    assert_eq!(listener.get_message_into(&mut buf).unwrap(), 2);
+    assert_eq!(buf, [4, 5]);
+    assert_eq!(buf.as_ptr(), allocation);
+    assert_eq!(buf.capacity(), 16);
+  }
 }
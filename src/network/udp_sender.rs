@@ -2,10 +2,9 @@ use std::{
   cell::RefCell,
   collections::HashMap,
   io,
-  net::{IpAddr, SocketAddr, UdpSocket},
+  net::{IpAddr, SocketAddr, SocketAddrV6, UdpSocket},
+  sync::Arc,
 };
-#[cfg(test)]
-use std::net::Ipv4Addr;
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, RawFd};
 
@@ -14,19 +13,37 @@ use log::{debug, error, info, trace, warn};
 use socket2::{Domain, Protocol, SockAddr, Socket, Type};
 
 use crate::{
-  network::util::get_local_multicast_ip_addrs_filtered,
+  network::{
+    multicast::IpVersion,
+    transport::Transport,
+    util::{
+      get_local_multicast_interfaces_filtered, is_ipv6_unicast_link_local, link_local_scope_id,
+      InterfaceConfig,
+    },
+  },
   rtps::{
     outbound::{ControlQueue, Datagram, SendOutcome, SocketId, CONTROL_QUEUE_WARN_LEN},
+    statistics::StatisticsCounters,
     transmit::InterfaceSelector,
   },
   structure::locator::Locator,
 };
 
+// A datagram recorded by a capturing sender: (destination, bytes)
+#[cfg(any(test, feature = "test-util"))]
+pub(crate) type CapturedSend = (SocketAddr, Vec<u8>);
+
 // We need one multicast sender socket per interface
 
 #[derive(Debug)]
 pub struct UDPSender {
   unicast_socket: UdpSocket,
+  // The unicast socket is an IPv6 socket that also sends to IPv4 destinations
+  // (as IPv4-mapped addresses). True when discovery uses IPv6.
+  unicast_dual_stack: bool,
+  // Interface to send to IPv6 link-local destinations through. Locators do
+  // not carry the scope id, so it is filled in from here.
+  link_local_scope_id: Option<u32>,
   // One multicast sender socket per local interface, keyed by the interface it
   // was bound to (its `InterfaceSelector`). This lets us target a single
   // interface instead of sending on all of them.
@@ -38,12 +55,20 @@ pub struct UDPSender {
   // sent immediately. Drained on write readiness by `flush_control`.
   // (see src/rtps/nonblocking_transmit_design.md)
   control_queues: RefCell<HashMap<SocketId, ControlQueue>>,
+
+  // Counts the datagrams sent. Shared with the DomainParticipant.
+  statistics: Arc<StatisticsCounters>,
+
+  // Test harness: when present, every datagram is recorded here (destination,
+  // bytes) instead of being sent. See `crate::test_util`.
+  #[cfg(any(test, feature = "test-util"))]
+  captured: Option<RefCell<Vec<CapturedSend>>>,
 }
 
 impl UDPSender {
   #[cfg(test)]
   pub fn new(sender_port: u16) -> io::Result<Self> {
-    Self::new_with_networks(sender_port, None, 0)
+    Self::new_with_networks(sender_port, &InterfaceConfig::default(), 0)
   }
 
   // Request (and verify) SO_SNDBUF on a sender socket. `size == 0` leaves the
@@ -73,30 +98,45 @@ impl UDPSender {
 
   pub fn new_with_networks(
     sender_port: u16,
-    only_networks: Option<&[IpAddr]>,
+    interfaces: &InterfaceConfig,
     send_buffer_size: usize,
   ) -> io::Result<Self> {
-    let unicast_socket = {
-      let saddr: SocketAddr = SocketAddr::new("0.0.0.0".parse().unwrap(), sender_port);
-      let raw_socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
-      Self::set_and_verify_send_buffer(&raw_socket, send_buffer_size);
-      raw_socket.bind(&SockAddr::from(saddr))?;
-      let socket = UdpSocket::from(raw_socket);
-      // nonblocking-transmit: the socket must never stall the event loop.
-      socket.set_nonblocking(true)?;
-      socket
+    let discovery_multicast = interfaces.discovery_multicast;
+
+    // With IPv6 discovery, unicast goes to both IPv4 and IPv6 locators, so the
+    // unicast socket is a dual-stack IPv6 socket. Fall back to IPv4 if the
+    // host does not support that.
+    let dual_stack_socket = if discovery_multicast.ip_version() == IpVersion::V6 {
+      Self::new_unicast_socket(IpVersion::V6, sender_port, send_buffer_size)
+        .inspect_err(|e| {
+          warn!("Cannot create an IPv6 unicast sender socket: {e}. Sending over IPv4 only.");
+        })
+        .ok()
+    } else {
+      None
+    };
+    let unicast_dual_stack = dual_stack_socket.is_some();
+    let unicast_socket = match dual_stack_socket {
+      Some(socket) => socket,
+      None => Self::new_unicast_socket(IpVersion::V4, sender_port, send_buffer_size)?,
     };
 
-    // We set multicasting loop on so that we can hear other DomainParticipant
-    // instances running on the same host.
-    unicast_socket
-      .set_multicast_loop_v4(true)
-      .unwrap_or_else(|e| {
-        error!("Cannot set multicast loop on: {e:?}");
-      });
+    // IPv4 multicast sockets are always needed, as user traffic multicast uses
+    // IPv4. IPv6 ones only if discovery is configured to use IPv6.
+    let mut multicast_ifs = get_local_multicast_interfaces_filtered(interfaces, IpVersion::V4)?;
+    if discovery_multicast.ip_version() == IpVersion::V6 {
+      multicast_ifs.extend(get_local_multicast_interfaces_filtered(
+        interfaces,
+        IpVersion::V6,
+      )?);
+    }
+    // The TTL / hop limit is set on the sockets of the discovery IP version.
+    // Others keep the OS default.
+    let multicast_ttl = discovery_multicast.ttl();
 
     let mut multicast_sockets = Vec::with_capacity(1);
-    for multicast_if_ipaddr in get_local_multicast_ip_addrs_filtered(only_networks)? {
+    for multicast_if in multicast_ifs {
+      let multicast_if_ipaddr = multicast_if.ip;
       // beef: specify output interface
       trace!("UDPSender: Multicast sender on interface {multicast_if_ipaddr:?}");
 
@@ -122,6 +162,13 @@ impl UDPSender {
           mc_socket.set_multicast_loop_v4(true).unwrap_or_else(|e| {
             error!("Cannot set IPv4 multicast loop. err: {e}");
           });
+          if discovery_multicast.ip_version() == IpVersion::V4 {
+            mc_socket
+              .set_multicast_ttl_v4(multicast_ttl)
+              .unwrap_or_else(|e| {
+                error!("Cannot set IPv4 multicast TTL to {multicast_ttl}. err: {e}");
+              });
+          }
           // nonblocking-transmit: mio requires the socket be non-blocking, and
           // we must never let a full kernel buffer stall the event loop.
           mc_socket.set_nonblocking(true).unwrap_or_else(|e| {
@@ -131,13 +178,19 @@ impl UDPSender {
         }
 
         // ipv6
-        IpAddr::V6(addr) => {
+        IpAddr::V6(_) => {
           let raw_socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP))?;
           Self::set_and_verify_send_buffer(&raw_socket, send_buffer_size);
 
-          // note: you don't need to use set_multicast_if for ipv6 multicast.
-          // it comes for free!
-          raw_socket.bind(&SocketAddr::new(addr.into(), 0).into())?;
+          // IPv6 multicast is sent out of an interface index. Binding to the
+          // interface address would need its scope id, if it is link-local.
+          raw_socket.set_multicast_if_v6(multicast_if.index)?;
+          raw_socket.bind(&SocketAddr::new("::".parse().unwrap(), 0).into())?;
+          raw_socket
+            .set_multicast_hops_v6(multicast_ttl)
+            .unwrap_or_else(|e| {
+              error!("Cannot set IPv6 multicast hop limit to {multicast_ttl}. err: {e}");
+            });
 
           // make multicast sock
           let mc_socket = UdpSocket::from(raw_socket);
@@ -156,20 +209,89 @@ impl UDPSender {
       multicast_sockets.push((InterfaceSelector::Ip(multicast_if_ipaddr), mc_socket));
     } // end for
 
+    let link_local_scope_id = if unicast_dual_stack {
+      link_local_scope_id(interfaces)
+    } else {
+      None
+    };
+
     let sender = Self {
       unicast_socket,
+      unicast_dual_stack,
+      link_local_scope_id,
       multicast_sockets,
       control_queues: RefCell::new(HashMap::new()),
+      statistics: Arc::new(StatisticsCounters::default()),
+      #[cfg(any(test, feature = "test-util"))]
+      captured: None,
     };
     info!("UDPSender::new() --> {sender:?}");
     Ok(sender)
   }
 
+  fn new_unicast_socket(
+    ip_version: IpVersion,
+    sender_port: u16,
+    send_buffer_size: usize,
+  ) -> io::Result<UdpSocket> {
+    let raw_socket = match ip_version {
+      IpVersion::V4 => Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?,
+      IpVersion::V6 => {
+        let raw_socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP))?;
+        raw_socket.set_only_v6(false)?;
+        raw_socket
+      }
+    };
+    Self::set_and_verify_send_buffer(&raw_socket, send_buffer_size);
+    let saddr = match ip_version {
+      IpVersion::V4 => SocketAddr::new("0.0.0.0".parse().unwrap(), sender_port),
+      IpVersion::V6 => SocketAddr::new("::".parse().unwrap(), sender_port),
+    };
+    raw_socket.bind(&SockAddr::from(saddr))?;
+    let socket = UdpSocket::from(raw_socket);
+    // nonblocking-transmit: the socket must never stall the event loop.
+    socket.set_nonblocking(true)?;
+
+    // We set multicasting loop on so that we can hear other DomainParticipant
+    // instances running on the same host.
+    match ip_version {
+      IpVersion::V4 => socket.set_multicast_loop_v4(true),
+      IpVersion::V6 => socket.set_multicast_loop_v6(true),
+    }
+    .unwrap_or_else(|e| {
+      error!("Cannot set multicast loop on: {e:?}");
+    });
+    Ok(socket)
+  }
+
   #[cfg(test)]
   pub fn new_with_random_port() -> io::Result<Self> {
     Self::new(0)
   }
 
+  pub(crate) fn set_statistics(&mut self, statistics: Arc<StatisticsCounters>) {
+    self.statistics = statistics;
+  }
+
+  /// A sender that records datagrams instead of sending them. Sockets are
+  /// still created (so socket enumeration works), but never written to.
+  #[cfg(any(test, feature = "test-util"))]
+  pub fn new_capturing() -> io::Result<Self> {
+    let mut sender = Self::new_with_networks(0, &InterfaceConfig::default(), 0)?;
+    sender.captured = Some(RefCell::new(Vec::new()));
+    Ok(sender)
+  }
+
+  /// Take the datagrams recorded so far by a capturing sender, in send order.
+  #[cfg(any(test, feature = "test-util"))]
+  pub fn take_captured(&self) -> Vec<CapturedSend> {
+    self
+      .captured
+      .as_ref()
+      .map(|c| std::mem::take(&mut *c.borrow_mut()))
+      .unwrap_or_default()
+  }
+
   // --- nonblocking-transmit: socket enumeration & raw non-blocking send ------
 
   fn socket_ref(&self, id: SocketId) -> Option<&UdpSocket> {
@@ -194,10 +316,23 @@ impl UDPSender {
 
   /// One non-blocking datagram send. Never blocks; classifies the result.
   fn raw_send(&self, id: SocketId, addr: SocketAddr, buffer: &[u8]) -> SendOutcome {
+    #[cfg(any(test, feature = "test-util"))]
+    if let Some(captured) = &self.captured {
+      captured.borrow_mut().push((addr, buffer.to_vec()));
+      self.statistics.message_sent(buffer.len());
+      return SendOutcome::Sent;
+    }
     let Some(socket) = self.socket_ref(id) else {
       error!("raw_send: no socket for {id:?}");
       return SendOutcome::Dropped;
     };
+    let addr = match addr {
+      // An IPv6 socket reaches IPv4 destinations through IPv4-mapped addresses.
+      SocketAddr::V4(v4) if id == SocketId::Unicast && self.unicast_dual_stack => {
+        SocketAddr::V6(SocketAddrV6::new(v4.ip().to_ipv6_mapped(), v4.port(), 0, 0))
+      }
+      _ => addr,
+    };
     match socket.send_to(buffer, addr) {
       Ok(bytes_sent) => {
         if bytes_sent != buffer.len() {
@@ -206,6 +341,7 @@ impl UDPSender {
             buffer.len()
           );
         }
+        self.statistics.message_sent(bytes_sent);
         SendOutcome::Sent
       }
       Err(e) if e.kind() == io::ErrorKind::WouldBlock => SendOutcome::WouldBlock,
@@ -289,7 +425,24 @@ impl UDPSender {
   fn locator_socket_addr(&self, locator: &Locator, ctx: &str) -> Option<SocketAddr> {
     match locator {
       Locator::UdpV4(sa) => Some(SocketAddr::from(*sa)),
-      Locator::UdpV6(sa) => Some(SocketAddr::from(*sa)),
+      Locator::UdpV6(sa) => {
+        let mut sa = *sa;
+        if !sa.ip().is_multicast() && !self.unicast_dual_stack {
+          // Normal: peers using IPv6 discovery advertise IPv6 locators also.
+          trace!("{ctx}: No IPv6 unicast socket for {sa}");
+          return None;
+        }
+        if sa.scope_id() == 0 && is_ipv6_unicast_link_local(&IpAddr::V6(*sa.ip())) {
+          match self.link_local_scope_id {
+            Some(scope_id) => sa.set_scope_id(scope_id),
+            None => {
+              debug!("{ctx}: No interface to send to link-local {sa}");
+              return None;
+            }
+          }
+        }
+        Some(SocketAddr::V6(sa))
+      }
       Locator::Invalid | Locator::Reserved => {
         error!("{ctx}: Cannot send to {locator:?}");
         None
@@ -302,16 +455,96 @@ impl UDPSender {
     }
   }
 
-  pub fn send_to_locator_list(&self, buffer: &[u8], ll: &[Locator]) {
-    for loc in ll {
-      self.send_to_locator(buffer, loc);
+  // The multicast socket of `interface`, if it can send to `addr`.
+  fn multicast_socket_id_for(
+    &self,
+    interface: &InterfaceSelector,
+    addr: &SocketAddr,
+  ) -> Option<SocketId> {
+    self
+      .multicast_sockets
+      .iter()
+      .position(|(iface, _)| iface == interface && Self::can_send_to(iface, addr))
+      .map(SocketId::Multicast)
+  }
+
+  // All multicast sockets that can send to `addr`.
+  fn multicast_socket_ids_for(&self, addr: &SocketAddr) -> Vec<SocketId> {
+    self
+      .multicast_sockets
+      .iter()
+      .enumerate()
+      .filter(|(_, (iface, _))| Self::can_send_to(iface, addr))
+      .map(|(i, _)| SocketId::Multicast(i))
+      .collect()
+  }
+
+  // An IPv4 socket cannot send to an IPv6 group, and vice versa.
+  fn can_send_to(iface: &InterfaceSelector, addr: &SocketAddr) -> bool {
+    let InterfaceSelector::Ip(ip) = iface;
+    ip.is_ipv4() == addr.is_ipv4()
+  }
+
+  // --- nonblocking-transmit: bulk path (flow-controlled, backpressured) ------
+
+  // One bulk datagram attempt to a socket. Control has strict priority: if the
+  // socket still has queued control, report WouldBlock so the writer backs off
+  // and resumes only after control has drained.
+  fn bulk_send_one(&self, id: SocketId, addr: SocketAddr, buffer: &[u8]) -> SendOutcome {
+    if self.control_queue_nonempty(id) {
+      return SendOutcome::WouldBlock;
+    }
+    self.raw_send(id, addr, buffer)
+  }
+
+  #[cfg(test)]
+  pub fn send_to_all(&self, buffer: &[u8], addresses: &[SocketAddr]) {
+    let buf_len = buffer.len();
+
+    for address in addresses.iter() {
+      // try sending the addr a message
+      match self.unicast_socket.send_to(buffer, *address) {
+        Ok(bytes_sent) => {
+          // error if we didn't send the whole buffer.
+          if bytes_sent != buffer.len() {
+            panic!("tried to send `{buf_len}` bytes, sent only `{bytes_sent}`!");
+          }
+        }
+
+        // it's a problem if we couldn't send anything - so we'll panic!
+        Err(e) => {
+          panic!("Unable to send to `{address}`. err: {e}");
+        }
+      }
+    }
+  }
+
+  #[cfg(test)]
+  pub fn send_multicast(self, buffer: &[u8], address: IpAddr, port: u16) -> io::Result<usize> {
+    if address.is_multicast() {
+      let address = SocketAddr::new(address, port);
+      let mut size = 0;
+      for id in self.multicast_socket_ids_for(&address) {
+        if let Some(s) = self.socket_ref(id) {
+          size = s.send_to(buffer, address)?;
+        }
+      }
+      Ok(size)
+    } else {
+      io::Result::Err(io::Error::other("Not a multicast address"))
     }
   }
+}
+
+impl Transport for UDPSender {
+  fn supports_locator(&self, locator: &Locator) -> bool {
+    locator.is_udp()
+  }
 
   /// Control-path send to a locator. A multicast locator fans out to every
   /// multicast interface (legacy reachability). Datagrams are queued (never
   /// dropped) if the socket is congested.
-  pub fn send_to_locator(&self, buffer: &[u8], locator: &Locator) {
+  fn send_to_locator(&self, buffer: &[u8], locator: &Locator) {
     if buffer.len() > 1500 {
       warn!("send_to_locator: Message size = {}", buffer.len());
     }
@@ -319,36 +552,18 @@ impl UDPSender {
       return;
     };
     if socket_address.ip().is_multicast() {
-      for id in 0..self.multicast_sockets.len() {
-        self.control_send_one(SocketId::Multicast(id), socket_address, buffer);
+      for id in self.multicast_socket_ids_for(&socket_address) {
+        self.control_send_one(id, socket_address, buffer);
       }
     } else {
       self.control_send_one(SocketId::Unicast, socket_address, buffer);
     }
   }
 
-  /// The set of local interfaces on which this sender can emit multicast.
-  /// Used by route resolution to validate an observed interface is usable.
-  pub fn multicast_interfaces(&self) -> Vec<InterfaceSelector> {
-    self
-      .multicast_sockets
-      .iter()
-      .map(|(iface, _)| *iface)
-      .collect()
-  }
-
-  fn multicast_socket_id_for(&self, interface: &InterfaceSelector) -> Option<SocketId> {
-    self
-      .multicast_sockets
-      .iter()
-      .position(|(iface, _)| iface == interface)
-      .map(SocketId::Multicast)
-  }
-
   /// Control-path multicast send out of a single, specific local interface.
   /// Falls back to all interfaces if the requested one is unknown, so a
   /// stale/misresolved interface never silently drops traffic.
-  pub fn send_to_multicast_locator_via(
+  fn send_to_multicast_locator_via(
     &self,
     buffer: &[u8],
     locator: &Locator,
@@ -371,32 +586,20 @@ impl UDPSender {
       return;
     }
 
-    match self.multicast_socket_id_for(interface) {
+    match self.multicast_socket_id_for(interface, &socket_address) {
       Some(id) => self.control_send_one(id, socket_address, buffer),
       None => {
         trace!("send_to_multicast_locator_via: interface {interface:?} not found, sending on all");
-        for id in 0..self.multicast_sockets.len() {
-          self.control_send_one(SocketId::Multicast(id), socket_address, buffer);
+        for id in self.multicast_socket_ids_for(&socket_address) {
+          self.control_send_one(id, socket_address, buffer);
         }
       }
     }
   }
 
-  // --- nonblocking-transmit: bulk path (flow-controlled, backpressured) ------
-
-  // One bulk datagram attempt to a socket. Control has strict priority: if the
-  // socket still has queued control, report WouldBlock so the writer backs off
-  // and resumes only after control has drained.
-  fn bulk_send_one(&self, id: SocketId, addr: SocketAddr, buffer: &[u8]) -> SendOutcome {
-    if self.control_queue_nonempty(id) {
-      return SendOutcome::WouldBlock;
-    }
-    self.raw_send(id, addr, buffer)
-  }
-
   /// Bulk send to a locator. Returns the sockets that could not accept the
   /// datagram (WouldBlock), so the caller can stop and arm write readiness.
-  pub(crate) fn try_send_to_locator(&self, buffer: &[u8], locator: &Locator) -> Vec<SocketId> {
+  fn try_send_to_locator(&self, buffer: &[u8], locator: &Locator) -> Vec<SocketId> {
     if buffer.len() > 1500 {
       warn!("try_send_to_locator: Message size = {}", buffer.len());
     }
@@ -405,7 +608,7 @@ impl UDPSender {
       return blocked;
     };
     if socket_address.ip().is_multicast() {
-      for id in (0..self.multicast_sockets.len()).map(SocketId::Multicast) {
+      for id in self.multicast_socket_ids_for(&socket_address) {
         if self.bulk_send_one(id, socket_address, buffer) == SendOutcome::WouldBlock {
           blocked.push(id);
         }
@@ -420,7 +623,7 @@ impl UDPSender {
 
   /// Bulk multicast send out of a single interface (fallback: all). Returns the
   /// sockets that could not accept the datagram (WouldBlock).
-  pub(crate) fn try_send_to_multicast_locator_via(
+  fn try_send_to_multicast_locator_via(
     &self,
     buffer: &[u8],
     locator: &Locator,
@@ -444,11 +647,9 @@ impl UDPSender {
       }
       return blocked;
     }
-    let ids: Vec<SocketId> = match self.multicast_socket_id_for(interface) {
+    let ids: Vec<SocketId> = match self.multicast_socket_id_for(interface, &socket_address) {
       Some(id) => vec![id],
-      None => (0..self.multicast_sockets.len())
-        .map(SocketId::Multicast)
-        .collect(),
+      None => self.multicast_socket_ids_for(&socket_address),
     };
     for id in ids {
       if self.bulk_send_one(id, socket_address, buffer) == SendOutcome::WouldBlock {
@@ -458,40 +659,14 @@ impl UDPSender {
     blocked
   }
 
-  #[cfg(test)]
-  pub fn send_to_all(&self, buffer: &[u8], addresses: &[SocketAddr]) {
-    let buf_len = buffer.len();
-
-    for address in addresses.iter() {
-      // try sending the addr a message
-      match self.unicast_socket.send_to(buffer, *address) {
-        Ok(bytes_sent) => {
-          // error if we didn't send the whole buffer.
-          if bytes_sent != buffer.len() {
-            panic!("tried to send `{buf_len}` bytes, sent only `{bytes_sent}`!");
-          }
-        }
-
-        // it's a problem if we couldn't send anything - so we'll panic!
-        Err(e) => {
-          panic!("Unable to send to `{address}`. err: {e}");
-        }
-      }
-    }
-  }
-
-  #[cfg(test)]
-  pub fn send_multicast(self, buffer: &[u8], address: Ipv4Addr, port: u16) -> io::Result<usize> {
-    if address.is_multicast() {
-      let address = SocketAddr::new(IpAddr::V4(address), port);
-      let mut size = 0;
-      for (_iface, s) in self.multicast_sockets {
-        size = s.send_to(buffer, address)?;
-      }
-      Ok(size)
-    } else {
-      io::Result::Err(io::Error::other("Not a multicast address"))
-    }
+  /// The set of local interfaces on which this sender can emit multicast.
+  /// Used by route resolution to validate an observed interface is usable.
+  fn multicast_interfaces(&self) -> Vec<InterfaceSelector> {
+    self
+      .multicast_sockets
+      .iter()
+      .map(|(iface, _)| *iface)
+      .collect()
   }
 }
 
@@ -538,4 +713,26 @@ mod tests {
     assert_eq!(rec_data_2.len(), 6);
     assert_eq!(rec_data_2, data);
   }
+
+  #[test]
+  fn multicast_ttl_follows_discovery_scope() {
+    use crate::network::multicast::{DiscoveryMulticast, IpVersion, MulticastScope};
+
+    let interfaces = InterfaceConfig {
+      // "Any" interface: lets the OS choose the outgoing one.
+      advertised_addresses: Some(vec![IpAddr::from([0, 0, 0, 0])].into()),
+      discovery_multicast: DiscoveryMulticast::new(MulticastScope::SiteLocal, IpVersion::V4),
+      ..Default::default()
+    };
+    let sender = UDPSender::new_with_networks(0, &interfaces, 0).unwrap();
+    assert_eq!(sender.multicast_sockets.len(), 1);
+    assert_eq!(
+      sender.multicast_sockets[0].1.multicast_ttl_v4().unwrap(),
+      32
+    );
+
+    // An IPv6 group cannot be sent through the IPv4 socket.
+    let v6_group = SocketAddr::new("ff05::ffff:239.255.0.1".parse().unwrap(), 7400);
+    assert!(sender.multicast_socket_ids_for(&v6_group).is_empty());
+  }
 }
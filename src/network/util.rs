@@ -2,17 +2,22 @@ use std::{
   collections::HashMap,
   io,
   net::{IpAddr, Ipv4Addr, SocketAddr},
+  sync::Arc,
 };
 
 use log::{error, warn};
 
 use crate::{
-  rtps::{
-    constant::{payload_budget_for_mtu, FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE},
-    transmit::InterfaceSelector,
+  network::{
+    interface_filter::InterfaceFilter,
+    multicast::{DiscoveryMulticast, IpVersion},
+    path_mtu::PathMtu,
   },
+  rtps::transmit::InterfaceSelector,
   structure::locator::Locator,
 };
+#[cfg(feature = "tcp")]
+use crate::network::tcp_transport::TcpTransport;
 
 /// Platform-neutral view of one network-interface address.
 ///
@@ -22,6 +27,8 @@ use crate::{
 /// of any enumeration dependency and makes them trivially unit-testable.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct IfAddr {
+  /// OS interface name. Empty when unknown.
+  pub name: String,
   /// An IP address bound to the interface.
   pub ip: IpAddr,
   /// OS interface index. `0` when unknown / not applicable.
@@ -37,6 +44,58 @@ pub(crate) struct IfAddr {
   pub mtu: Option<u32>,
 }
 
+impl IfAddr {
+  /// An interface entry for a user-configured address that was not (or could
+  /// not be) obtained by enumeration. Nothing but the IP itself is known, so
+  /// the interface is assumed multicast-capable unless it is loopback.
+  fn from_configured(ip: IpAddr) -> Self {
+    IfAddr {
+      name: String::new(),
+      ip,
+      index: 0,
+      is_loopback: ip.is_loopback(),
+      is_multicast: !ip.is_loopback(),
+      netmask: None,
+      mtu: None,
+    }
+  }
+}
+
+/// Participant-level choice of which local IP addresses are used for
+/// multicast and advertised in discovery, of the discovery multicast group,
+/// and of the transport.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InterfaceConfig {
+  /// Only interfaces with one of these IPs are used (see
+  /// `DomainParticipantBuilder::with_only_networks`).
+  pub only_networks: Option<Arc<[IpAddr]>>,
+  /// Enumerated interfaces to use (see
+  /// `DomainParticipantBuilder::network_interfaces`).
+  pub interface_filter: InterfaceFilter,
+  /// Use exactly these addresses and skip interface enumeration altogether.
+  pub advertised_addresses: Option<Arc<[IpAddr]>>,
+  /// Use these addresses if interface enumeration fails or finds nothing.
+  pub fallback_addresses: Option<Arc<[IpAddr]>>,
+  /// Discovery multicast group, scope and TTL.
+  pub discovery_multicast: DiscoveryMulticast,
+  /// Configured path MTU to remote destinations.
+  pub path_mtu: Arc<PathMtu>,
+  /// RTPS over TCP instead of UDP, if set.
+  #[cfg(feature = "tcp")]
+  pub tcp_transport: Option<TcpTransport>,
+  /// Unicast SPDP destinations in addition to multicast (see
+  /// `DomainParticipantBuilder::initial_peers`).
+  pub initial_peers: Arc<[SocketAddr]>,
+  /// Neither send nor listen to discovery multicast.
+  pub unicast_discovery_only: bool,
+}
+
+impl InterfaceConfig {
+  pub fn only_networks(&self) -> Option<&[IpAddr]> {
+    self.only_networks.as_deref()
+  }
+}
+
 // ---------------------------------------------------------------------------
 // Interface enumeration (cross-platform, via netdev)
 // ---------------------------------------------------------------------------
@@ -49,9 +108,9 @@ pub(crate) struct IfAddr {
 /// the existing locator/multicast/ifindex helpers keep working unchanged, while
 /// the new fields (`netmask`, `mtu`) enable per-peer path-MTU resolution.
 ///
-/// This is infallible in practice (netdev returns an empty list rather than an
-/// error), but the signature is kept as `io::Result` for API compatibility with
-/// the previous per-platform implementations.
+/// netdev reports failure by returning an empty list rather than an error. A
+/// working host always has at least a loopback interface, so an empty result
+/// is turned into an error here.
 fn enumerate_interfaces() -> io::Result<Vec<IfAddr>> {
   let mut result = Vec::new();
 
@@ -63,6 +122,7 @@ fn enumerate_interfaces() -> io::Result<Vec<IfAddr>> {
 
     for net in &iface.ipv4 {
       result.push(IfAddr {
+        name: iface.name.clone(),
         ip: IpAddr::V4(net.addr()),
         index,
         is_loopback,
@@ -73,6 +133,7 @@ fn enumerate_interfaces() -> io::Result<Vec<IfAddr>> {
     }
     for net in &iface.ipv6 {
       result.push(IfAddr {
+        name: iface.name.clone(),
         ip: IpAddr::V6(net.addr()),
         index,
         is_loopback,
@@ -84,9 +145,55 @@ fn enumerate_interfaces() -> io::Result<Vec<IfAddr>> {
     }
   }
 
+  if result.is_empty() {
+    return Err(io::Error::new(
+      io::ErrorKind::NotFound,
+      "network interface enumeration returned no interfaces",
+    ));
+  }
   Ok(result)
 }
 
+/// The local interface addresses to use under `config`: the configured
+/// advertised addresses if any, else the enumerated interfaces selected by the
+/// interface filter, else the configured fallback addresses.
+fn local_interfaces(config: &InterfaceConfig) -> io::Result<Vec<IfAddr>> {
+  match config.advertised_addresses {
+    Some(ref addrs) => Ok(configured_interfaces(addrs)),
+    None => resolve_enumerated_interfaces(enumerate_interfaces(), config),
+  }
+}
+
+fn resolve_enumerated_interfaces(
+  enumerated: io::Result<Vec<IfAddr>>,
+  config: &InterfaceConfig,
+) -> io::Result<Vec<IfAddr>> {
+  match (enumerated, config.fallback_addresses.as_deref()) {
+    (Ok(ifaces), _) => {
+      let selected = config.interface_filter.apply(ifaces);
+      if selected.is_empty() {
+        warn!(
+          "Interface filter {:?} matched no local interfaces.",
+          config.interface_filter
+        );
+      }
+      Ok(selected)
+    }
+    (Err(e), Some(fallback)) => {
+      warn!(
+        "Cannot enumerate local interfaces ({e}). Using configured fallback addresses \
+         {fallback:?}."
+      );
+      Ok(configured_interfaces(fallback))
+    }
+    (Err(e), None) => Err(e),
+  }
+}
+
+fn configured_interfaces(addrs: &[IpAddr]) -> Vec<IfAddr> {
+  addrs.iter().copied().map(IfAddr::from_configured).collect()
+}
+
 // ---------------------------------------------------------------------------
 // Per-peer path-MTU resolution
 // ---------------------------------------------------------------------------
@@ -94,8 +201,8 @@ fn enumerate_interfaces() -> io::Result<Vec<IfAddr>> {
 /// Snapshot of the local interface table, used by writers to resolve a per-peer
 /// datagram budget. Built once (and refreshed on interface-set changes) and
 /// shared with each `Writer` via the event loop.
-pub fn local_interface_table() -> Vec<IfAddr> {
-  match enumerate_interfaces() {
+pub fn local_interface_table(config: &InterfaceConfig) -> Vec<IfAddr> {
+  match local_interfaces(config) {
     Ok(ifaces) => ifaces,
     Err(e) => {
       error!("Cannot enumerate local interfaces for path-MTU resolution: {e:?}");
@@ -104,37 +211,24 @@ pub fn local_interface_table() -> Vec<IfAddr> {
   }
 }
 
-/// Resolve the per-peer UDP-payload budget (bytes available for RTPS
-/// submessages in one datagram) for `peer_ip`, using the "local
-/// egress-interface MTU" heuristic:
+/// The MTU of the local interface through which `peer_ip` is reachable in one
+/// hop, i.e. of the local IPv4 interface whose subnet contains `peer_ip`.
+/// Loopback peers (e.g. `127.0.0.1`) naturally match `lo`/`lo0` and inherit its
+/// (often large) MTU.
 ///
-/// * If some local IPv4 interface's subnet contains `peer_ip`, the peer is
-///   reachable in one hop, so we use that interface's MTU (minus headers).
-///   Loopback peers (e.g. `127.0.0.1`) naturally match `lo`/`lo0` and inherit
-///   its (often large) MTU.
-/// * Otherwise the peer is behind a router (or IPv6 / unresolved), where the
-///   real path MTU is unknown; fall back to
-///   [`FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE`] (a conservative
-///   ~1500-byte-Ethernet budget).
-///
-/// An overestimate is a soft failure (IP fragmentation), never data loss, so
-/// this errs toward the safe default when unsure.
-pub fn path_mtu_payload_for_peer(ifaces: &[IfAddr], peer_ip: IpAddr) -> usize {
-  if let IpAddr::V4(peer) = peer_ip {
-    for ifa in ifaces {
-      let (IpAddr::V4(local), Some(mask)) = (ifa.ip, ifa.netmask) else {
-        continue;
-      };
-      if same_subnet_v4(local, mask, peer) {
-        if let Some(mtu) = ifa.mtu {
-          return payload_budget_for_mtu(mtu);
-        }
-        // Same subnet but MTU unknown: keep scanning in case another matching
-        // interface reports one; otherwise we fall through to the default.
-      }
-    }
-  }
-  FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE
+/// `None` when the peer is behind a router (or IPv6 / unresolved), or the OS
+/// does not report the MTU. Then the real path MTU is unknown, and
+/// [`PathMtu`](crate::PathMtu) falls back to a conservative default.
+pub fn interface_mtu_for_peer(ifaces: &[IfAddr], peer_ip: IpAddr) -> Option<u32> {
+  let IpAddr::V4(peer) = peer_ip else {
+    return None;
+  };
+  // Same subnet but MTU unknown: keep scanning in case another matching
+  // interface reports one.
+  ifaces.iter().find_map(|ifa| match (ifa.ip, ifa.netmask) {
+    (IpAddr::V4(local), Some(mask)) if same_subnet_v4(local, mask, peer) => ifa.mtu,
+    _ => None,
+  })
 }
 
 /// True when `local` and `peer` share the IPv4 subnet defined by `mask`.
@@ -147,8 +241,8 @@ fn same_subnet_v4(local: Ipv4Addr, mask: Ipv4Addr, peer: Ipv4Addr) -> bool {
 // Public API
 // ---------------------------------------------------------------------------
 
-pub fn get_local_multicast_locators(port: u16) -> Vec<Locator> {
-  let saddr = SocketAddr::new("239.255.0.1".parse().unwrap(), port);
+pub fn get_local_multicast_locators(multicast_group: IpAddr, port: u16) -> Vec<Locator> {
+  let saddr = SocketAddr::new(multicast_group, port);
   vec![Locator::from(saddr)]
 }
 
@@ -177,15 +271,18 @@ pub fn localhost_spdp_peer_locators(
     .collect()
 }
 
+/// Unicast locators of the local interface addresses of `ip_versions`.
 pub fn get_local_unicast_locators_filtered(
   port: u16,
-  only_networks: Option<&[IpAddr]>,
+  config: &InterfaceConfig,
+  ip_versions: &[IpVersion],
 ) -> Vec<Locator> {
-  match enumerate_interfaces() {
+  match local_interfaces(config) {
     Ok(ifaces) => {
-      let result = get_local_unicast_locators_inner(&ifaces, port, only_networks);
+      let result =
+        get_local_unicast_locators_inner(&ifaces, port, config.only_networks(), ip_versions);
       if result.is_empty() {
-        if let Some(nets) = only_networks {
+        if let Some(nets) = config.only_networks() {
           warn!(
             "only_networks filter {:?} matched no unicast interfaces; this participant will be \
              invisible to peers.",
@@ -206,40 +303,90 @@ fn get_local_unicast_locators_inner(
   ifaces: &[IfAddr],
   port: u16,
   only_networks: Option<&[IpAddr]>,
+  ip_versions: &[IpVersion],
 ) -> Vec<Locator> {
   ifaces
     .iter()
     .filter(|ifa| only_networks.is_none_or(|nets| nets.contains(&ifa.ip)))
+    .filter(|ifa| ip_versions.contains(&IpVersion::of(&ifa.ip)))
     .map(|ifa| Locator::from(SocketAddr::new(ifa.ip, port)))
     .collect()
 }
 
-/// Enumerates local interfaces that we may use for multicasting.
+/// Scope id (interface index) to send to IPv6 link-local addresses with.
+///
+/// Locators do not carry a scope id, so a link-local address received in
+/// discovery does not tell through which interface it is reachable. This is
+/// the index of the first non-loopback interface that has a link-local
+/// address, which is right on hosts attached to a single link. `None` if
+/// there is no such interface.
+pub(crate) fn link_local_scope_id(config: &InterfaceConfig) -> Option<u32> {
+  match local_interfaces(config) {
+    Ok(ifaces) => link_local_scope_id_inner(&ifaces),
+    Err(e) => {
+      error!("Cannot get local network interfaces: {e:?}");
+      None
+    }
+  }
+}
+
+fn link_local_scope_id_inner(ifaces: &[IfAddr]) -> Option<u32> {
+  ifaces
+    .iter()
+    .find(|ifa| !ifa.is_loopback && ifa.index != 0 && is_ipv6_unicast_link_local(&ifa.ip))
+    .map(|ifa| ifa.index)
+}
+
+/// True for IPv6 addresses in fe80::/10.
+pub(crate) fn is_ipv6_unicast_link_local(ip: &IpAddr) -> bool {
+  match ip {
+    IpAddr::V6(ip) => ip.segments()[0] & 0xffc0 == 0xfe80,
+    IpAddr::V4(_) => false,
+  }
+}
+
+/// Enumerates local interfaces of the given IP version that we may use for
+/// multicasting.
 ///
 /// The result of this function is used to set up senders and listeners.
-/// When `only_networks` is `Some`, only interfaces with a matching IP are
-/// included.
-pub fn get_local_multicast_ip_addrs_filtered(
-  only_networks: Option<&[IpAddr]>,
-) -> io::Result<Vec<IpAddr>> {
-  let ifaces = enumerate_interfaces()?;
-  let result = get_local_multicast_ip_addrs_inner(&ifaces, only_networks);
-  Ok(result)
+/// When `config.only_networks` is `Some`, only interfaces with a matching IP
+/// are included. IPv4 interfaces are identified by address, so there is one
+/// entry per address. IPv6 multicast is bound to an interface index, so there
+/// is one entry per interface, and interfaces with an unknown index are left
+/// out.
+pub(crate) fn get_local_multicast_interfaces_filtered(
+  config: &InterfaceConfig,
+  ip_version: IpVersion,
+) -> io::Result<Vec<IfAddr>> {
+  let ifaces = local_interfaces(config)?;
+  Ok(get_local_multicast_interfaces_inner(
+    &ifaces,
+    config.only_networks(),
+    ip_version,
+  ))
 }
 
-/// Inner implementation of [`get_local_multicast_ip_addrs_filtered`], factored
-/// out so tests can supply a mock interface list.
-fn get_local_multicast_ip_addrs_inner(
+/// Inner implementation of [`get_local_multicast_interfaces_filtered`],
+/// factored out so tests can supply a mock interface list.
+fn get_local_multicast_interfaces_inner(
   ifaces: &[IfAddr],
   only_networks: Option<&[IpAddr]>,
-) -> Vec<IpAddr> {
-  ifaces
+  ip_version: IpVersion,
+) -> Vec<IfAddr> {
+  let mut result: Vec<IfAddr> = Vec::new();
+  for ifa in ifaces
     .iter()
     .filter(|ifa| ifa.is_multicast)
     .filter(|ifa| only_networks.is_none_or(|nets| nets.contains(&ifa.ip)))
-    .map(|ifa| ifa.ip)
-    .filter(IpAddr::is_ipv4)
-    .collect()
+    .filter(|ifa| IpVersion::of(&ifa.ip) == ip_version)
+  {
+    let duplicate_v6_index = ip_version == IpVersion::V6
+      && (ifa.index == 0 || result.iter().any(|r| r.index == ifa.index));
+    if !duplicate_v6_index {
+      result.push(ifa.clone());
+    }
+  }
+  result
 }
 
 /// Builds a mapping from OS interface index to an [`InterfaceSelector`].
@@ -281,15 +428,20 @@ fn build_ifindex_map_inner(ifaces: &[IfAddr]) -> HashMap<u32, InterfaceSelector>
 
 #[cfg(test)]
 mod tests {
-  use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+  use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+  };
 
   use super::{
-    build_ifindex_map_inner, get_local_multicast_ip_addrs_inner, get_local_unicast_locators_inner,
-    localhost_spdp_peer_locators, path_mtu_payload_for_peer, IfAddr, InterfaceSelector,
+    build_ifindex_map_inner, InterfaceFilter, get_local_multicast_interfaces_inner,
+    get_local_unicast_locators_inner, link_local_scope_id_inner, localhost_spdp_peer_locators,
+    resolve_enumerated_interfaces, IfAddr, InterfaceConfig, InterfaceSelector,
   };
   use crate::{
-    network::constant::spdp_well_known_unicast_port,
-    rtps::constant::FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE, structure::locator::Locator,
+    network::{constant::spdp_well_known_unicast_port, multicast::IpVersion, path_mtu::PathMtu},
+    rtps::constant::{payload_budget_for_mtu, FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE},
+    structure::locator::Locator,
   };
 
   fn v4(a: u8, b: u8, c: u8, d: u8) -> IpAddr {
@@ -298,6 +450,7 @@ mod tests {
 
   fn iface(ip: IpAddr, index: u32, is_loopback: bool, is_multicast: bool) -> IfAddr {
     IfAddr {
+      name: format!("if{index}"),
       ip,
       index,
       is_loopback,
@@ -316,6 +469,7 @@ mod tests {
       u32::MAX << (32 - u32::from(prefix))
     };
     IfAddr {
+      name: "if1".to_string(),
       ip: v4(a, b, c, d),
       index: 1,
       is_loopback,
@@ -325,6 +479,17 @@ mod tests {
     }
   }
 
+  // IPv4 multicast interface addresses
+  fn get_local_multicast_ip_addrs_inner(
+    ifaces: &[IfAddr],
+    only_networks: Option<&[IpAddr]>,
+  ) -> Vec<IpAddr> {
+    get_local_multicast_interfaces_inner(ifaces, only_networks, IpVersion::V4)
+      .into_iter()
+      .map(|ifa| ifa.ip)
+      .collect()
+  }
+
   #[test]
   fn test_get_local_multicast_ip_addrs() {
     let ifaces = vec![
@@ -389,6 +554,25 @@ mod tests {
     assert_eq!(ips, vec![v4(10, 0, 0, 10)]);
   }
 
+  #[test]
+  fn ipv6_multicast_interfaces_are_listed_once_per_index() {
+    let v6 = |s: &str| IpAddr::V6(s.parse::<Ipv6Addr>().unwrap());
+    let ifaces = vec![
+      iface(v4(192, 168, 0, 10), 1, false, true),
+      iface(v6("fe80::1"), 1, false, true),
+      iface(v6("2001:db8::1"), 1, false, true),
+      iface(v6("fe80::2"), 2, false, true),
+      iface(v6("fe80::3"), 0, false, true),
+    ];
+
+    let v6_ifaces = get_local_multicast_interfaces_inner(&ifaces, None, IpVersion::V6);
+    let indices: Vec<u32> = v6_ifaces.iter().map(|ifa| ifa.index).collect();
+    assert_eq!(indices, vec![1, 2]);
+
+    let v4_ifaces = get_local_multicast_interfaces_inner(&ifaces, None, IpVersion::V4);
+    assert_eq!(v4_ifaces.len(), 1);
+  }
+
   #[test]
   fn unicast_filter_respects_only_networks() {
     let only_networks = [v4(10, 0, 0, 10)];
@@ -397,7 +581,8 @@ mod tests {
       iface(v4(10, 0, 0, 10), 2, false, true),
     ];
 
-    let filtered = get_local_unicast_locators_inner(&ifaces, 7412, Some(&only_networks));
+    let filtered =
+      get_local_unicast_locators_inner(&ifaces, 7412, Some(&only_networks), &[IpVersion::V4]);
 
     assert_eq!(
       filtered,
@@ -405,6 +590,42 @@ mod tests {
     );
   }
 
+  #[test]
+  fn unicast_locators_are_filtered_by_ip_version() {
+    let v6 = IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 2));
+    let ifaces = vec![
+      iface(v4(192, 168, 0, 10), 1, false, true),
+      iface(v6, 1, false, true),
+    ];
+    let locators = |ip_versions: &[IpVersion]| {
+      get_local_unicast_locators_inner(&ifaces, 7412, None, ip_versions)
+    };
+
+    assert_eq!(
+      locators(&[IpVersion::V4]),
+      vec![Locator::from(SocketAddr::new(v4(192, 168, 0, 10), 7412))]
+    );
+    assert_eq!(
+      locators(&[IpVersion::V6]),
+      vec![Locator::from(SocketAddr::new(v6, 7412))]
+    );
+    assert_eq!(locators(&[IpVersion::V4, IpVersion::V6]).len(), 2);
+  }
+
+  #[test]
+  fn link_local_scope_is_the_first_non_loopback_link() {
+    let v6 = |s: &str| IpAddr::V6(s.parse::<Ipv6Addr>().unwrap());
+    let ifaces = vec![
+      iface(v6("fe80::1"), 1, true, false),
+      iface(v4(192, 168, 0, 10), 2, false, true),
+      iface(v6("2001:db8::1"), 2, false, true),
+      iface(v6("fe80::2"), 3, false, true),
+      iface(v6("fe80::3"), 4, false, true),
+    ];
+    assert_eq!(link_local_scope_id_inner(&ifaces), Some(3));
+    assert_eq!(link_local_scope_id_inner(&ifaces[..3]), None);
+  }
+
   #[test]
   fn ifindex_map_prefers_ipv4_and_skips_index_zero() {
     let ifaces = vec![
@@ -429,6 +650,11 @@ mod tests {
     );
   }
 
+  // Datagram budget to `peer_ip` without configured path MTUs.
+  fn path_mtu_payload_for_peer(ifaces: &[IfAddr], peer_ip: IpAddr) -> usize {
+    payload_budget_for_mtu(PathMtu::default().mtu_for(ifaces, peer_ip))
+  }
+
   // A peer on the same /24 as a local interface uses that interface's MTU
   // (minus the 48-byte IPv4+UDP+RTPS header overhead).
   #[test]
@@ -515,6 +741,77 @@ mod tests {
     assert_eq!(peers, expected);
     assert!(peers
       .iter()
-      .all(|l| SocketAddr::from(*l).ip().is_loopback()));
+      .all(|l| l.ip_addr().is_some_and(|ip| ip.is_loopback())));
+  }
+
+  #[test]
+  fn failed_enumeration_uses_fallback_addresses() {
+    let config = InterfaceConfig {
+      fallback_addresses: Some(vec![v4(10, 0, 0, 5)].into()),
+      ..Default::default()
+    };
+    let failed = Err(io::Error::new(io::ErrorKind::NotFound, "no interfaces"));
+    let ifaces = resolve_enumerated_interfaces(failed, &config).unwrap();
+
+    assert_eq!(
+      get_local_unicast_locators_inner(&ifaces, 7412, None, &[IpVersion::V4]),
+      vec![Locator::from(SocketAddr::new(v4(10, 0, 0, 5), 7412))]
+    );
+    assert_eq!(
+      get_local_multicast_ip_addrs_inner(&ifaces, None),
+      vec![v4(10, 0, 0, 5)]
+    );
+  }
+
+  #[test]
+  fn interface_filter_selects_advertised_and_multicast_interfaces() {
+    let v6 = IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 2));
+    let enumerated = vec![
+      iface(IpAddr::V4(Ipv4Addr::LOCALHOST), 1, true, false),
+      iface(v4(192, 168, 0, 10), 2, false, true),
+      iface(v6, 2, false, true),
+      iface(v4(10, 0, 0, 10), 3, false, true),
+    ];
+    let config = InterfaceConfig {
+      interface_filter: InterfaceFilter::new().allow_name("if3"),
+      ..Default::default()
+    };
+    let ifaces = resolve_enumerated_interfaces(Ok(enumerated.clone()), &config).unwrap();
+    assert_eq!(
+      get_local_unicast_locators_inner(&ifaces, 7412, None, &[IpVersion::V4, IpVersion::V6]),
+      vec![Locator::from(SocketAddr::new(v4(10, 0, 0, 10), 7412))]
+    );
+    assert_eq!(
+      get_local_multicast_ip_addrs_inner(&ifaces, None),
+      vec![v4(10, 0, 0, 10)]
+    );
+
+    // Denying an address leaves out the other addresses of its interface too.
+    let config = InterfaceConfig {
+      interface_filter: InterfaceFilter::new().deny_address(v6),
+      ..Default::default()
+    };
+    let ifaces = resolve_enumerated_interfaces(Ok(enumerated), &config).unwrap();
+    assert_eq!(
+      get_local_multicast_ip_addrs_inner(&ifaces, None),
+      vec![v4(10, 0, 0, 10)]
+    );
+  }
+
+  #[test]
+  fn failed_enumeration_without_fallback_is_an_error() {
+    let failed = Err(io::Error::new(io::ErrorKind::NotFound, "no interfaces"));
+    assert!(resolve_enumerated_interfaces(failed, &InterfaceConfig::default()).is_err());
+  }
+
+  #[test]
+  fn successful_enumeration_ignores_fallback() {
+    let config = InterfaceConfig {
+      fallback_addresses: Some(vec![v4(10, 0, 0, 5)].into()),
+      ..Default::default()
+    };
+    let enumerated = vec![iface(v4(192, 168, 0, 10), 1, false, true)];
+    let ifaces = resolve_enumerated_interfaces(Ok(enumerated.clone()), &config).unwrap();
+    assert_eq!(ifaces, enumerated);
   }
 }
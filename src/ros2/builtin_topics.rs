@@ -24,6 +24,7 @@ impl ROSDiscoveryTopic {
       lease_duration: Duration::INFINITE,
     }),
     time_based_filter: None,
+    partition: None,
     reliability: Some(Reliability::Reliable {
       max_blocking_time: Duration::ZERO,
     }),
@@ -33,6 +34,7 @@ impl ROSDiscoveryTopic {
     lifespan: Some(Lifespan {
       duration: Duration::INFINITE,
     }),
+    durability_service: None,
     data_representation: None,
     #[cfg(feature = "security")]
     property: None,
@@ -65,6 +67,7 @@ impl ParameterEventsTopic {
     ownership: None,
     liveliness: None,
     time_based_filter: None,
+    partition: None,
     reliability: Some(Reliability::Reliable {
       max_blocking_time: Duration::ZERO,
     }),
@@ -72,6 +75,7 @@ impl ParameterEventsTopic {
     history: Some(History::KeepLast { depth: 1 }),
     resource_limits: None,
     lifespan: None,
+    durability_service: None,
     data_representation: None,
     #[cfg(feature = "security")]
     property: None,
@@ -108,6 +112,7 @@ impl RosOutTopic {
       lease_duration: Duration::INFINITE,
     }),
     time_based_filter: None,
+    partition: None,
     reliability: Some(Reliability::Reliable {
       max_blocking_time: Duration::ZERO,
     }),
@@ -117,6 +122,7 @@ impl RosOutTopic {
     lifespan: Some(Lifespan {
       duration: Duration::from_secs(10),
     }),
+    durability_service: None,
     data_representation: None,
     #[cfg(feature = "security")]
     property: None,
@@ -1,15 +1,19 @@
 #[allow(dead_code)] // We allow this, since extra constants are not too harmful.
 pub(crate) mod constant;
 
+pub(crate) mod coherent_sets;
 pub(crate) mod dp_event_loop;
 pub(crate) mod fragment_assembler;
 pub(crate) mod message_receiver;
 pub(crate) mod outbound;
 pub(crate) mod reader;
+pub(crate) mod reorder_buffer;
 pub(crate) mod rtps_reader_proxy;
 pub(crate) mod rtps_writer_proxy;
+pub(crate) mod statistics;
 pub(crate) mod timed_event;
 pub(crate) mod transmit;
+pub(crate) mod vendor_quirks;
 pub(crate) mod writer;
 pub(crate) mod writer_send_buffer;
 
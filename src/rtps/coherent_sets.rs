@@ -0,0 +1,196 @@
+// Coherent sets received from one Writer, RTPS spec v2.5 Section 8.7.5.
+//
+// A sample that belongs to a coherent set carries the SequenceNumber of the
+// first sample of the set. The set ends at the first SequenceNumber that is not
+// a member: an end marker, a sample of another set, or a sample outside of any
+// set. Members are held here until the set is complete, and then delivered
+// together. A set that cannot be completed is discarded as a whole.
+//
+// The members of a set are numbered consecutively from its first
+// SequenceNumber, so the set is complete when all SequenceNumbers up to its end
+// have been received as members.
+
+use std::collections::BTreeMap;
+
+use crate::structure::sequence_number::SequenceNumber;
+
+struct OpenSet<T> {
+  samples: BTreeMap<SequenceNumber, T>,
+  // The first SequenceNumber after the set, once known.
+  end: Option<SequenceNumber>,
+}
+
+impl<T> OpenSet<T> {
+  fn is_complete(&self, start: SequenceNumber) -> bool {
+    self
+      .end
+      .is_some_and(|end| i64::from(end - start) == self.samples.len() as i64)
+  }
+}
+
+pub(crate) struct CoherentSets<T> {
+  // Open sets by their first SequenceNumber
+  open: BTreeMap<SequenceNumber, OpenSet<T>>,
+  // Samples before this belong to sets that were delivered or discarded.
+  finished_before: SequenceNumber,
+}
+
+impl<T> Default for CoherentSets<T> {
+  fn default() -> Self {
+    Self {
+      open: BTreeMap::new(),
+      finished_before: SequenceNumber::zero(),
+    }
+  }
+}
+
+impl<T> CoherentSets<T> {
+  /// Add a received member `sn` of the set starting at `set`.
+  pub fn insert(&mut self, set: SequenceNumber, sn: SequenceNumber, sample: T) {
+    if sn < set || sn < self.finished_before {
+      return; // invalid, or too late
+    }
+    // The other sets ended before this sample, and before this set started.
+    for (start, other) in self.open.range_mut(..sn) {
+      if *start != set {
+        let other_end = if *start < set { set } else { sn };
+        other.end = Some(other.end.map_or(other_end, |end| end.min(other_end)));
+      }
+    }
+    let next_start = self.open.range(set + SequenceNumber::new(1)..).next();
+    let end = next_start.map(|(start, _)| *start);
+    let open_set = self.open.entry(set).or_insert_with(|| OpenSet {
+      samples: BTreeMap::new(),
+      end: None,
+    });
+    if let Some(end) = end {
+      open_set.end = Some(open_set.end.map_or(end, |e| e.min(end)));
+    }
+    if open_set.end.is_none_or(|end| sn < end) {
+      open_set.samples.insert(sn, sample);
+    }
+  }
+
+  /// `sn` is not a member of any set, so it ends the sets started before it.
+  pub fn note_non_member(&mut self, sn: SequenceNumber) {
+    for open_set in self.open.range_mut(..sn).map(|(_, s)| s) {
+      open_set.end = Some(open_set.end.map_or(sn, |end| end.min(sn)));
+    }
+  }
+
+  /// Take the sets that are finished, in order. All SequenceNumbers before
+  /// `received_before` have been received or will never be. If `may_wait` is
+  /// false, missing members are not waited for.
+  ///
+  /// Returns the members of complete sets, and the number of samples in
+  /// discarded, incomplete sets.
+  pub fn take_finished(
+    &mut self,
+    received_before: SequenceNumber,
+    may_wait: bool,
+  ) -> (Vec<Vec<(SequenceNumber, T)>>, usize) {
+    let mut complete = Vec::new();
+    let mut discarded = 0;
+    while let Some(entry) = self.open.first_entry() {
+      let start = *entry.key();
+      let Some(end) = entry.get().end else {
+        break; // still open
+      };
+      if entry.get().is_complete(start) {
+        complete.push(entry.remove().samples.into_iter().collect());
+      } else if !may_wait || received_before >= end {
+        discarded += entry.remove().samples.len();
+      } else {
+        break; // waiting for missing members
+      }
+      self.finished_before = end;
+    }
+    (complete, discarded)
+  }
+
+  /// The first SequenceNumber of the first unfinished set.
+  pub fn first_pending(&self) -> Option<SequenceNumber> {
+    self.open.keys().next().copied()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.open.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sn(n: i64) -> SequenceNumber {
+    SequenceNumber::new(n)
+  }
+
+  fn sns(sets: &[Vec<(SequenceNumber, i64)>]) -> Vec<Vec<i64>> {
+    sets
+      .iter()
+      .map(|set| set.iter().map(|(_, v)| *v).collect())
+      .collect()
+  }
+
+  #[test]
+  fn set_is_delivered_when_complete_and_ended() {
+    let mut sets = CoherentSets::default();
+    sets.insert(sn(3), sn(4), 4);
+    sets.insert(sn(3), sn(3), 3);
+    assert_eq!(sets.first_pending(), Some(sn(3)));
+    let (complete, discarded) = sets.take_finished(sn(5), true);
+    assert!(complete.is_empty() && discarded == 0, "set has not ended");
+
+    sets.note_non_member(sn(5));
+    let (complete, discarded) = sets.take_finished(sn(6), true);
+    assert_eq!(sns(&complete), vec![vec![3, 4]]);
+    assert_eq!(discarded, 0);
+    assert!(sets.is_empty());
+  }
+
+  #[test]
+  fn reliable_set_waits_for_missing_members() {
+    let mut sets = CoherentSets::default();
+    sets.insert(sn(1), sn(1), 1);
+    sets.insert(sn(1), sn(3), 3);
+    sets.note_non_member(sn(4));
+    assert_eq!(sets.take_finished(sn(2), true).0.len(), 0);
+
+    sets.insert(sn(1), sn(2), 2);
+    let (complete, _) = sets.take_finished(sn(5), true);
+    assert_eq!(sns(&complete), vec![vec![1, 2, 3]]);
+  }
+
+  #[test]
+  fn incomplete_set_is_discarded() {
+    // Reliable: #2 will never come.
+    let mut sets = CoherentSets::default();
+    sets.insert(sn(1), sn(1), 1);
+    sets.insert(sn(1), sn(3), 3);
+    sets.note_non_member(sn(4));
+    assert_eq!(sets.take_finished(sn(5), true), (vec![], 2));
+    assert!(sets.is_empty());
+    // A late member of a finished set is dropped.
+    sets.insert(sn(1), sn(2), 2);
+    assert!(sets.is_empty());
+
+    // BestEffort: no waiting for #2.
+    let mut sets = CoherentSets::default();
+    sets.insert(sn(1), sn(1), 1);
+    sets.insert(sn(1), sn(3), 3);
+    sets.note_non_member(sn(4));
+    assert_eq!(sets.take_finished(sn(1), false), (vec![], 2));
+  }
+
+  #[test]
+  fn next_set_ends_the_previous_one() {
+    let mut sets = CoherentSets::default();
+    sets.insert(sn(1), sn(1), 1);
+    sets.insert(sn(1), sn(2), 2);
+    sets.insert(sn(3), sn(3), 3);
+    let (complete, _) = sets.take_finished(sn(4), true);
+    assert_eq!(sns(&complete), vec![vec![1, 2]]);
+    assert_eq!(sets.first_pending(), Some(sn(3)));
+  }
+}
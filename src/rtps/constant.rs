@@ -86,6 +86,11 @@ pub const DATAGRAM_HEADER_OVERHEAD: usize = 48;
 // into each DATAFRAG submessage / datagram, never the fragment size itself.
 pub const FRAGMENT_SIZE: usize = 256;
 
+// Default upper limit of the sample size a Reader accepts in DATAFRAGs. The
+// reassembly buffer is allocated at the size announced by the remote writer,
+// so without a limit a single DATAFRAG could make us allocate up to 4 GiB.
+pub const DEFAULT_MAX_FRAGMENTED_SAMPLE_SIZE: usize = 256 * 1024 * 1024;
+
 /// Convert an interface MTU into the RTPS-message (UDP payload) budget usable
 /// for submessage/fragment packing: `mtu - DATAGRAM_HEADER_OVERHEAD`, floored
 /// so a pathologically small MTU still yields a usable (if tiny) budget. The
@@ -311,6 +316,9 @@ pub const STOP_POLL_TOKEN: Token = Token(PTB);
 
 // pub const DISCOVERY_SENDER_TOKEN: Token = Token(1 + PTB);
 // pub const USER_TRAFFIC_SENDER_TOKEN: Token = Token(2 + PTB);
+// IPv6 unicast listeners, used if discovery is configured to use IPv6.
+pub const DISCOVERY_LISTENER_V6_TOKEN: Token = Token(3 + PTB);
+pub const USER_TRAFFIC_LISTENER_V6_TOKEN: Token = Token(4 + PTB);
 
 // pub const DATA_SEND_TOKEN: Token = Token(5 + PTB);
 
@@ -339,6 +347,10 @@ pub const ACKNACK_MESSAGE_TO_LOCAL_WRITER_TOKEN: Token = Token(20 + PTB);
 pub const DISCOVERY_UPDATE_NOTIFICATION_TOKEN: Token = Token(21 + PTB);
 pub const DISCOVERY_COMMAND_TOKEN: Token = Token(22 + PTB);
 pub const SPDP_LIVENESS_TOKEN: Token = Token(23 + PTB);
+pub const IN_PROCESS_DISCOVERY_TOKEN: Token = Token(24 + PTB);
+pub const TCP_LISTENER_TOKEN: Token = Token(25 + PTB);
+pub const TCP_RECONNECTED_TOKEN: Token = Token(26 + PTB);
+pub const TCP_RETURN_LISTENER_TOKEN: Token = Token(27 + PTB);
 
 pub const DISCOVERY_PARTICIPANT_DATA_TOKEN: Token = Token(30 + PTB);
 pub const DISCOVERY_PARTICIPANT_CLEANUP_TOKEN: Token = Token(31 + PTB);
@@ -436,6 +448,12 @@ pub(crate) enum DiscoveryNotificationType {
     writer_guid: GUID,
     manual_assertion: bool,
   },
+  // A remote participant asserted its liveliness via the built-in participant
+  // message topic
+  ParticipantLivelinessAsserted {
+    guid_prefix: GuidPrefix,
+    manual_assertion: bool,
+  },
   #[cfg(feature = "security")]
   ParticipantAuthenticationStatusChanged {
     guid_prefix: GuidPrefix,
@@ -1,11 +1,12 @@
 use std::{
   cell::RefCell,
   collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
-  net::IpAddr,
   rc::Rc,
   sync::{Arc, RwLock},
   time::{Duration, Instant},
 };
+#[cfg(feature = "tcp")]
+use std::net::SocketAddr;
 
 use chrono::Utc;
 use log::{debug, error, info, trace, warn};
@@ -13,7 +14,12 @@ use mio_06::{Event, Events, Poll, PollOpt, Ready, Token};
 use mio_extras::channel as mio_channel;
 
 use crate::{
+  log_throttle,
   dds::{
+    participant::{
+      BestEffortReordering, DeadlineTimestamp, FragmentAssembly, HeartbeatBackoff,
+      HeartbeatDelivery, LargeFanOut,
+    },
     qos::policy,
     result::{CreateError, CreateResult},
     statusevents::{DomainParticipantStatusEvent, StatusChannelSender},
@@ -26,9 +32,9 @@ use crate::{
   messages::submessages::submessages::AckSubmessage,
   network::{
     constant::SPDP_LOCALHOST_PEER_COUNT,
-    udp_listener::UDPListener,
+    transport::{self, Listeners, Transport},
     udp_sender::UDPSender,
-    util::{local_interface_table, localhost_spdp_peer_locators, IfAddr},
+    util::{local_interface_table, localhost_spdp_peer_locators, IfAddr, InterfaceConfig},
   },
   polling::{new_shared_timer, SharedTimer},
   //qos::HasQoSPolicy,
@@ -39,19 +45,28 @@ use crate::{
     reader::{Reader, ReaderIngredients},
     rtps_reader_proxy::RtpsReaderProxy,
     rtps_writer_proxy::RtpsWriterProxy,
+    statistics::StatisticsCounters,
     timed_event::DpTimerEvent,
     transmit::InterfaceObservations,
+    vendor_quirks::VendorQuirks,
     writer::{Writer, WriterIngredients},
   },
   structure::{
     dds_cache::DDSCache,
     entity::RTPSEntity,
     guid::{EntityId, GuidPrefix, TokenDecode, GUID},
+    locator::Locator,
+    time::Clock,
   },
   //QosPolicyBuilder,
   //QosPolicies,
   EndpointDescription,
 };
+#[cfg(feature = "tcp")]
+use crate::network::{
+  tcp_sender::{TCPReturnListener, TCPSender},
+  transport::TransportListener,
+};
 #[cfg(feature = "security")]
 use crate::{
   discovery::secure_discovery::AuthenticationStatus,
@@ -79,8 +94,16 @@ pub struct DomainInfo {
 }
 
 pub(crate) enum EventLoopCommand {
-  Stop,
+  // Send what the Writers still hold, for at most `grace_period`, and stop.
+  Stop {
+    grace_period: Duration,
+  },
   PrepareStop,
+  // Re-read local network interfaces. The new self locators of each listener
+  // are sent back via the reply channel.
+  RefreshInterfaces {
+    reply: std::sync::mpsc::SyncSender<HashMap<Token, Vec<Locator>>>,
+  },
 }
 
 pub struct DPEventLoop {
@@ -88,7 +111,7 @@ pub struct DPEventLoop {
   poll: Poll,
   dds_cache: Arc<RwLock<DDSCache>>,
   discovery_db: Arc<RwLock<DiscoveryDB>>,
-  udp_listeners: HashMap<Token, UDPListener>,
+  listeners: Listeners,
   message_receiver: MessageReceiver, // This contains our Readers
 
   // If security is enabled, this contains the security plugins
@@ -109,6 +132,12 @@ pub struct DPEventLoop {
 
   writers: HashMap<EntityId, Writer>,
   udp_sender: Rc<UDPSender>,
+  // What Readers and Writers send through: the TCPSender in TCP mode,
+  // otherwise `udp_sender`.
+  transport: Rc<dyn Transport>,
+  // Peers that the TCPSender has reconnected to after losing messages.
+  #[cfg(feature = "tcp")]
+  tcp_reconnected_receiver: mio_channel::Receiver<SocketAddr>,
 
   // nonblocking-transmit: per-socket round-robin of writers that have bulk DATA
   // to send but hit WouldBlock. Served on write readiness, control first.
@@ -127,6 +156,7 @@ pub struct DPEventLoop {
   // with every Writer. Rebuilt on interface-set changes (same trigger points as
   // the send-route recompute).
   local_interfaces: Rc<[IfAddr]>,
+  interfaces: InterfaceConfig,
 
   // One timer shared by all Readers, Writers and the periodic loop tasks.
   // Endpoints hold cloned handles to schedule timeouts; the loop owns it,
@@ -143,6 +173,62 @@ pub struct DPEventLoop {
   // route same-host peers over loopback. See
   // `src/rtps/loopback_same_host_design.md`.
   same_host_loopback: bool,
+
+  // HEARTBEAT backoff towards unresponsive readers (participant-builder
+  // `heartbeat_backoff` knob), given to every Writer. None = disabled.
+  heartbeat_backoff: Option<HeartbeatBackoff>,
+
+  // Period of HEARTBEATs from up-to-date Writers (participant-builder
+  // `heartbeat_period` knob), given to every user-defined Writer. None =
+  // default.
+  heartbeat_period: Option<Duration>,
+
+  // Longest delay of ACKNACK responses to HEARTBEATs (participant-builder
+  // `heartbeat_response_delay` knob), given to every user-defined Reader.
+  heartbeat_response_delay: Duration,
+
+  // Multicast or unicast HEARTBEATs (participant-builder `heartbeat_delivery`
+  // knob), given to every Writer.
+  heartbeat_delivery: HeartbeatDelivery,
+
+  // Repair policy of Writers with many matched readers (participant-builder
+  // `large_fan_out` knob), given to every user-defined Writer. None = disabled.
+  large_fan_out: Option<LargeFanOut>,
+
+  // Longest hold of samples for coalescing (participant-builder
+  // `max_coalesce_delay` knob), given to every user-defined Writer.
+  max_coalesce_delay: Option<Duration>,
+
+  // Largest sample accepted in DATA_FRAGs (participant-builder
+  // `max_fragmented_sample_size` knob), given to every Reader.
+  max_fragmented_sample_size: usize,
+
+  // Reordering of samples from BestEffort Writers (participant-builder
+  // `best_effort_reordering` knob), given to every user-defined Reader. None =
+  // disabled.
+  best_effort_reordering: Option<BestEffortReordering>,
+
+  // Delay before requesting fragment gaps without a HEARTBEAT
+  // (participant-builder `proactive_nackfrag_delay` knob), given to every
+  // user-defined Reader. None = disabled.
+  proactive_nackfrag_delay: Option<Duration>,
+
+  // Expiry of partially received fragmented samples (participant-builder
+  // `fragment_assembly` knob), given to every Reader.
+  fragment_assembly: FragmentAssembly,
+
+  // Timestamp that requested deadlines are measured against
+  // (participant-builder `deadline_timestamp` knob), given to every
+  // user-defined Reader.
+  deadline_timestamp: DeadlineTimestamp,
+
+  // Participant clock (participant-builder `clock` knob), given to every
+  // Reader and Writer.
+  clock: Arc<dyn Clock>,
+
+  // Traffic counters shared with the DomainParticipant, given to the UDP
+  // sender and every Reader and Writer.
+  statistics: Arc<StatisticsCounters>,
 }
 
 impl DPEventLoop {
@@ -151,7 +237,7 @@ impl DPEventLoop {
   pub(crate) fn new(
     domain_info: DomainInfo,
     dds_cache: Arc<RwLock<DDSCache>>,
-    udp_listeners: HashMap<Token, UDPListener>,
+    listeners: Listeners,
     discovery_db: Arc<RwLock<DiscoveryDB>>,
     participant_guid_prefix: GuidPrefix,
     add_reader_receiver: TokenReceiverPair<ReaderIngredients>,
@@ -164,9 +250,25 @@ impl DPEventLoop {
     spdp_liveness_sender: mio_channel::SyncSender<GuidPrefix>,
     participant_status_sender: StatusChannelSender<DomainParticipantStatusEvent>,
     security_plugins_opt: Option<SecurityPluginsHandle>,
-    only_networks: Option<Arc<[IpAddr]>>,
+    interfaces: InterfaceConfig,
     socket_send_buffer_size: usize,
     same_host_loopback: bool,
+    accept_own_spdp: bool,
+    heartbeat_backoff: Option<HeartbeatBackoff>,
+    heartbeat_period: Option<Duration>,
+    heartbeat_response_delay: Duration,
+    heartbeat_delivery: HeartbeatDelivery,
+    large_fan_out: Option<LargeFanOut>,
+    max_coalesce_delay: Option<Duration>,
+    vendor_quirks: VendorQuirks,
+    record_reception_context: bool,
+    max_fragmented_sample_size: usize,
+    best_effort_reordering: Option<BestEffortReordering>,
+    proactive_nackfrag_delay: Option<Duration>,
+    fragment_assembly: FragmentAssembly,
+    deadline_timestamp: DeadlineTimestamp,
+    clock: Arc<dyn Clock>,
+    statistics: Arc<StatisticsCounters>,
   ) -> CreateResult<Self> {
     macro_rules! try_init {
       ($result:expr, $msg:literal) => {
@@ -184,11 +286,10 @@ impl DPEventLoop {
     let poll = try_init!(Poll::new(), "Unable to create new poll");
     let (acknack_sender, acknack_receiver) =
       mio_channel::sync_channel::<(GuidPrefix, AckSubmessage)>(100);
-    let mut udp_listeners = udp_listeners;
-    for (token, listener) in &mut udp_listeners {
+    for (token, listener) in &listeners {
       try_init!(
         poll.register(
-          listener.mio_socket(),
+          listener.evented(),
           *token,
           Ready::readable(),
           PollOpt::level(),
@@ -286,33 +387,88 @@ impl DPEventLoop {
     }
 
     // port number 0 means OS chooses an available port number.
-    let udp_sender = try_init!(
-      UDPSender::new_with_networks(0, only_networks.as_deref(), socket_send_buffer_size),
+    let mut udp_sender = try_init!(
+      UDPSender::new_with_networks(0, &interfaces, socket_send_buffer_size),
       "UDPSender construction fail"
     );
+    udp_sender.set_statistics(Arc::clone(&statistics));
+    let udp_sender = Rc::new(udp_sender);
+    #[cfg(feature = "tcp")]
+    let (tcp_reconnected_sender, tcp_reconnected_receiver) = mio_channel::channel();
+    #[cfg(feature = "tcp")]
+    try_init!(
+      poll.register(
+        &tcp_reconnected_receiver,
+        TCP_RECONNECTED_TOKEN,
+        Ready::readable(),
+        PollOpt::edge(),
+      ),
+      "Failed to register TCP reconnection channel"
+    );
+    #[cfg(feature = "tcp")]
+    let mut listeners = listeners;
+    #[cfg(feature = "tcp")]
+    let transport: Rc<dyn Transport> = match &interfaces.tcp_transport {
+      Some(tcp) => {
+        let (return_listener, returned_sender) = TCPReturnListener::new();
+        try_init!(
+          poll.register(
+            return_listener.evented(),
+            TCP_RETURN_LISTENER_TOKEN,
+            Ready::readable(),
+            PollOpt::level(),
+          ),
+          "Failed to register TCP return listener"
+        );
+        // Peers are told where our TCPListener listens when we bind
+        // connections to them.
+        let local_locator = transport::self_locators(&listeners, &interfaces)
+          .get(&DISCOVERY_LISTENER_TOKEN)
+          .and_then(|locators| locators.first().copied())
+          .unwrap_or(Locator::Invalid);
+        listeners.insert(TCP_RETURN_LISTENER_TOKEN, Box::new(return_listener));
+        Rc::new(TCPSender::new(
+          tcp.keep_alive(),
+          local_locator,
+          tcp_reconnected_sender,
+          returned_sender,
+        ))
+      }
+      None => udp_sender.clone(),
+    };
+    #[cfg(not(feature = "tcp"))]
+    let transport: Rc<dyn Transport> = udp_sender.clone();
 
     #[cfg(not(feature = "security"))]
     let security_plugins_opt = security_plugins_opt.and(None); // make sure it is None an consume value
 
     let interface_observations = Rc::new(RefCell::new(InterfaceObservations::new()));
-    let local_interfaces: Rc<[IfAddr]> = Rc::from(local_interface_table());
+    let mut message_receiver = MessageReceiver::new(
+      participant_guid_prefix,
+      acknack_sender,
+      spdp_liveness_sender,
+      security_plugins_opt.clone(),
+      Rc::clone(&interface_observations),
+    );
+    message_receiver.set_accept_own_spdp(accept_own_spdp);
+    message_receiver.set_vendor_quirks(vendor_quirks);
+    message_receiver.set_record_reception_context(record_reception_context);
+    let local_interfaces: Rc<[IfAddr]> = Rc::from(local_interface_table(&interfaces));
 
     Ok(Self {
       domain_info,
       poll,
       dds_cache,
       discovery_db,
-      udp_listeners,
-      udp_sender: Rc::new(udp_sender),
-      message_receiver: MessageReceiver::new(
-        participant_guid_prefix,
-        acknack_sender,
-        spdp_liveness_sender,
-        security_plugins_opt.clone(),
-        Rc::clone(&interface_observations),
-      ),
+      listeners,
+      udp_sender,
+      transport,
+      #[cfg(feature = "tcp")]
+      tcp_reconnected_receiver,
+      message_receiver,
       interface_observations,
       local_interfaces,
+      interfaces,
       #[cfg(feature = "security")]
       security_plugins_opt,
       add_reader_receiver,
@@ -329,6 +485,19 @@ impl DPEventLoop {
       participant_status_sender,
       discovery_command_sender,
       same_host_loopback,
+      heartbeat_backoff,
+      heartbeat_period,
+      heartbeat_response_delay,
+      heartbeat_delivery,
+      large_fan_out,
+      max_coalesce_delay,
+      max_fragmented_sample_size,
+      best_effort_reordering,
+      proactive_nackfrag_delay,
+      fragment_assembly,
+      deadline_timestamp,
+      clock,
+      statistics,
     })
   }
 
@@ -386,10 +555,17 @@ impl DPEventLoop {
                       // There could still be an EventLoopCommand::Stop coming. Keep on receiving.
                       try_recv_more = true;
                     }
-                    Ok(EventLoopCommand::Stop) => {
+                    Ok(EventLoopCommand::Stop { grace_period }) => {
                       info!("Stopping dp_event_loop");
+                      ev_wrapper.flush_before_stop(grace_period);
                       return;
                     }
+                    Ok(EventLoopCommand::RefreshInterfaces { reply }) => {
+                      let self_locators = ev_wrapper.refresh_interfaces();
+                      if reply.send(self_locators).is_err() {
+                        warn!("Nobody is waiting for refreshed locators.");
+                      }
+                    }
                     Err(err) => match err {
                       TryRecvError::Empty => {
                         try_recv_more = false;
@@ -405,25 +581,33 @@ impl DPEventLoop {
                 }
               }
               DISCOVERY_LISTENER_TOKEN
+              | DISCOVERY_LISTENER_V6_TOKEN
               | DISCOVERY_MUL_LISTENER_TOKEN
               | USER_TRAFFIC_LISTENER_TOKEN
-              | USER_TRAFFIC_MUL_LISTENER_TOKEN => {
-                let udp_messages = ev_wrapper
-                  .udp_listeners
-                  .get_mut(&event.token())
-                  .map_or_else(
-                    || {
-                      error!("No listener with token {:?}", event.token());
-                      vec![]
-                    },
-                    |l| l.messages_bounded(MAX_LISTENER_MESSAGES_PER_POLL),
-                  );
-                for (packet, origin) in udp_messages {
+              | USER_TRAFFIC_LISTENER_V6_TOKEN
+              | USER_TRAFFIC_MUL_LISTENER_TOKEN
+              | TCP_LISTENER_TOKEN
+              | TCP_RETURN_LISTENER_TOKEN => {
+                let messages = ev_wrapper.listeners.get_mut(&event.token()).map_or_else(
+                  || {
+                    error!("No listener with token {:?}", event.token());
+                    vec![]
+                  },
+                  |l| l.messages_bounded(MAX_LISTENER_MESSAGES_PER_POLL),
+                );
+                for (packet, origin) in messages {
+                  ev_wrapper.statistics.message_received(packet.len());
                   ev_wrapper
                     .message_receiver
                     .handle_received_packet(&packet, origin);
                 }
               }
+              #[cfg(feature = "tcp")]
+              TCP_RECONNECTED_TOKEN => {
+                while let Ok(address) = ev_wrapper.tcp_reconnected_receiver.try_recv() {
+                  ev_wrapper.tcp_peer_reconnected(address);
+                }
+              }
               ADD_READER_TOKEN | REMOVE_READER_TOKEN => {
                 ev_wrapper.handle_reader_action(&event);
               }
@@ -467,6 +651,15 @@ impl DPEventLoop {
                         .map(|w| w.handle_heartbeat_tick(manual_assertion));
                     }
 
+                    ParticipantLivelinessAsserted {
+                      guid_prefix,
+                      manual_assertion,
+                    } => {
+                      for reader in ev_wrapper.message_receiver.available_readers.values_mut() {
+                        reader.participant_liveliness_asserted(guid_prefix, manual_assertion);
+                      }
+                    }
+
                     #[cfg(feature = "security")]
                     ParticipantAuthenticationStatusChanged { guid_prefix } => {
                       ev_wrapper.on_remote_participant_authentication_status_changed(guid_prefix);
@@ -498,6 +691,7 @@ impl DPEventLoop {
                     DpTimerEvent::CacheGc => {
                       debug!("Clean DDSCache on timer");
                       ev_wrapper.dds_cache.write().unwrap().garbage_collect();
+                      log_throttle::flush_suppressed();
                       ev_wrapper
                         .shared_timer
                         .borrow_mut()
@@ -511,6 +705,9 @@ impl DPEventLoop {
                         trace!("Timed event for unknown reader {entity_id:?}");
                       }
                     }
+                    DpTimerEvent::DisposeReader { entity_id } => {
+                      ev_wrapper.dispose_abandoned_reader(entity_id);
+                    }
                     DpTimerEvent::Writer { entity_id, event } => {
                       // A stale timeout for an already-removed writer is harmless.
                       if let Some(writer) = ev_wrapper.writers.get_mut(&entity_id) {
@@ -686,6 +883,33 @@ impl DPEventLoop {
     }
   }
 
+  // Participant shutdown: send what the Writers still hold, notably the
+  // disposals Discovery wrote on its way out, so that remote participants
+  // forget us at once instead of waiting for our lease to expire. Congested
+  // sockets are retried until `grace_period` is over.
+  fn flush_before_stop(&mut self, grace_period: Duration) {
+    let deadline = Instant::now() + grace_period;
+    let mut blocked_writers = Vec::new();
+    for (eid, writer) in self.writers.iter_mut() {
+      writer.process_pending();
+      blocked_writers.extend(writer.take_blocked_sockets().into_iter().map(|s| (s, *eid)));
+    }
+    for (sid, eid) in blocked_writers {
+      self.mark_writer_willing(sid, eid);
+    }
+    while self.has_pending_outbound() && Instant::now() < deadline {
+      for sid in self.udp_sender.socket_ids() {
+        self.on_socket_writable(sid);
+      }
+      if self.has_pending_outbound() {
+        std::thread::sleep(Duration::from_millis(1));
+      }
+    }
+    if self.has_pending_outbound() {
+      warn!("Participant shutdown: some messages were not sent within {grace_period:?}");
+    }
+  }
+
   // Fallback for platforms without EventedFd: flush/serve every socket each loop
   // iteration (the loop uses a short poll timeout while anything is pending).
   #[cfg(not(unix))]
@@ -745,6 +969,24 @@ impl DPEventLoop {
     }
   }
 
+  // Called after a network configuration change. Listeners are bound to the
+  // unspecified address, so they keep receiving on new addresses, but
+  // multicast group memberships, the interface table, and the locators we
+  // advertise must be brought up to date.
+  fn refresh_interfaces(&mut self) -> HashMap<Token, Vec<Locator>> {
+    info!("Refreshing local network interfaces.");
+    for listener in self.listeners.values_mut() {
+      listener.refresh_interfaces(&self.interfaces);
+    }
+    let self_locators = transport::self_locators(&self.listeners, &self.interfaces);
+
+    self.local_interfaces = Rc::from(local_interface_table(&self.interfaces));
+    for writer in self.writers.values_mut() {
+      writer.set_local_interfaces(Rc::clone(&self.local_interfaces));
+    }
+    self_locators
+  }
+
   fn handle_writer_acknack_action(&mut self, _event: &Event) {
     while let Ok((acknack_sender_prefix, acknack_submessage)) = self.ack_nack_receiver.try_recv() {
       let writer_guid = GUID::new_with_prefix_and_id(
@@ -946,6 +1188,19 @@ impl DPEventLoop {
     }
   }
 
+  // Messages to a TCP peer have been lost while the connection to it was down.
+  // Reliable Writers send a HEARTBEAT now instead of at the next periodic one,
+  // so that Readers missing samples ask for them. Readers likewise ask Writers
+  // they have not yet heard from.
+  #[cfg(feature = "tcp")]
+  fn tcp_peer_reconnected(&mut self, address: SocketAddr) {
+    info!("Reconnected to TCP peer {address}, requesting repairs");
+    for writer in self.writers.values_mut().filter(|w| w.is_reliable()) {
+      writer.handle_heartbeat_tick(false);
+    }
+    self.message_receiver.send_preemptive_acknacks();
+  }
+
   fn remote_reader_discovered(&mut self, remote_reader: &DiscoveredReaderData) {
     debug!(
       "remote_reader_discovered on {:?}",
@@ -1130,12 +1385,28 @@ impl DPEventLoop {
   fn add_local_reader(&mut self, reader_ing: ReaderIngredients) {
     // The reader schedules its timeouts on the loop's shared timer (already
     // registered in `new()`), so there is no per-reader timer to register.
-    let mut new_reader = Reader::new(
+    let mut new_reader = match Reader::new(
       reader_ing,
-      self.udp_sender.clone(),
+      self.transport.clone(),
       self.shared_timer.clone(),
       self.participant_status_sender.clone(),
-    );
+    ) {
+      Ok(reader) => reader,
+      Err(e) => {
+        error!("Cannot add local reader: {e}");
+        return;
+      }
+    };
+    new_reader.set_max_fragmented_sample_size(self.max_fragmented_sample_size);
+    new_reader.set_fragment_assembly(self.fragment_assembly);
+    new_reader.set_clock(Arc::clone(&self.clock));
+    new_reader.set_statistics(Arc::clone(&self.statistics));
+    if !new_reader.guid().entity_id.kind().is_built_in() {
+      new_reader.set_best_effort_reordering(self.best_effort_reordering);
+      new_reader.set_proactive_nackfrag_delay(self.proactive_nackfrag_delay);
+      new_reader.set_heartbeat_response_delay(self.heartbeat_response_delay);
+      new_reader.set_deadline_timestamp(self.deadline_timestamp);
+    }
 
     // Non-timed action polling
     self
@@ -1180,12 +1451,56 @@ impl DPEventLoop {
     }
   }
 
+  // Remove a Reader whose DataReader has disappeared without removing it. The
+  // topic cache is shared with other Readers of the topic, so only our handle
+  // to it is dropped along with the Reader.
+  fn dispose_abandoned_reader(&mut self, entity_id: EntityId) {
+    let Some(reader) = self.message_receiver.reader_mut(entity_id) else {
+      // Already removed by the DataReader
+      return;
+    };
+    let reader_guid = reader.guid();
+    self.remove_local_reader(reader_guid);
+
+    // Tell discovery, so that remote Writers stop sending to us.
+    if let Err(e) = self
+      .discovery_command_sender
+      .try_send(DiscoveryCommand::RemoveLocalReader { guid: reader_guid })
+    {
+      error!("Failed to inform Discovery about disposed Reader {reader_guid:?}: {e}");
+    }
+  }
+
+  // Unicast destinations of SPDP, in addition to multicast.
+  fn spdp_unicast_peers(&self) -> Vec<Locator> {
+    #[cfg(feature = "tcp")]
+    if let Some(tcp) = &self.interfaces.tcp_transport {
+      // TCP cannot multicast, so Discovery goes to the configured peers.
+      return tcp.initial_peer_locators(self.domain_info.domain_id);
+    }
+    // Configured initial peers, for networks where multicast does not work.
+    let mut peers: Vec<Locator> = self
+      .interfaces
+      .initial_peers
+      .iter()
+      .map(|peer| Locator::from(*peer))
+      .collect();
+    if self.same_host_loopback {
+      peers.extend(localhost_spdp_peer_locators(
+        self.domain_info.domain_id,
+        self.domain_info.participant_id,
+        SPDP_LOCALHOST_PEER_COUNT,
+      ));
+    }
+    peers
+  }
+
   fn add_local_writer(&mut self, writer_ing: WriterIngredients) {
     // The writer schedules its timeouts on the loop's shared timer (already
     // registered in `new()`), so there is no per-writer timer to register.
     let mut new_writer = Writer::new(
       writer_ing,
-      self.udp_sender.clone(),
+      self.transport.clone(),
       self.shared_timer.clone(),
       self.participant_status_sender.clone(),
       Rc::clone(&self.interface_observations),
@@ -1199,14 +1514,20 @@ impl DPEventLoop {
     // - every writer may route a confirmed same-host peer over loopback.
     // See `src/rtps/loopback_same_host_design.md`.
     new_writer.set_prefer_loopback_same_host(self.same_host_loopback);
-    if self.same_host_loopback
-      && new_writer.guid().entity_id == EntityId::SPDP_BUILTIN_PARTICIPANT_WRITER
-    {
-      new_writer.set_extra_unicast_destinations(localhost_spdp_peer_locators(
-        self.domain_info.domain_id,
-        self.domain_info.participant_id,
-        SPDP_LOCALHOST_PEER_COUNT,
-      ));
+    new_writer.set_heartbeat_backoff(self.heartbeat_backoff);
+    new_writer.set_heartbeat_delivery(self.heartbeat_delivery);
+    new_writer.set_clock(Arc::clone(&self.clock));
+    new_writer.set_statistics(Arc::clone(&self.statistics));
+    new_writer.set_path_mtu(Arc::clone(&self.interfaces.path_mtu));
+    if !new_writer.guid().entity_id.kind().is_built_in() {
+      new_writer.set_max_coalesce_delay(self.max_coalesce_delay);
+      new_writer.set_large_fan_out(self.large_fan_out);
+      if let Some(period) = self.heartbeat_period {
+        new_writer.set_heartbeat_period(period);
+      }
+    }
+    if new_writer.guid().entity_id == EntityId::SPDP_BUILTIN_PARTICIPANT_WRITER {
+      new_writer.set_extra_unicast_destinations(self.spdp_unicast_peers());
     }
 
     self
@@ -1365,6 +1686,7 @@ mod tests {
       with_key::simpledatareader::ReaderCommand,
     },
     mio_source,
+    structure::time::SystemClock,
   };
 
   //#[test]
@@ -1442,9 +1764,25 @@ mod tests {
         spdp_liveness_sender,
         participant_status_sender,
         None,
-        None,
+        InterfaceConfig::default(),
         0,
         true,
+        false,
+        None,
+        None,
+        Duration::ZERO,
+        HeartbeatDelivery::default(),
+        None,
+        None,
+        VendorQuirks::default(),
+        false,
+        DEFAULT_MAX_FRAGMENTED_SAMPLE_SIZE,
+        None,
+        None,
+        FragmentAssembly::default(),
+        DeadlineTimestamp::default(),
+        Arc::new(SystemClock),
+        Arc::new(StatisticsCounters::default()),
       )
       .expect("DPEventLoop::new in test");
       dp_event_loop
@@ -1495,6 +1833,13 @@ mod tests {
         data_reader_command_receiver: reader_command_receiver,
         data_reader_waker: data_reader_waker.clone(),
         poll_event_sender: notification_event_sender,
+        liveliness_status: Default::default(),
+        requested_deadline_missed_status: Default::default(),
+        detect_content_duplicates: Default::default(),
+        ignore_ambiguous_status_info: Default::default(),
+        duplicate_acceptance: Default::default(),
+        paused: Default::default(),
+        history_boundaries: None,
         security_plugins: None,
       };
 
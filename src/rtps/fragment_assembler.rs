@@ -35,11 +35,17 @@ struct AssemblyBuffer {
 }
 
 impl AssemblyBuffer {
-  pub fn new(datafrag: &DataFrag) -> Option<Self> {
+  pub fn new(datafrag: &DataFrag, max_sample_size: usize) -> Option<Self> {
     let data_size: usize = datafrag.data_size.try_into().ok()?;
     let fragment_size: u16 = datafrag.fragment_size;
     debug!("new AssemblyBuffer data_size={data_size} frag_size={fragment_size}");
 
+    // Check before allocating: the size is announced by the remote writer.
+    if data_size > max_sample_size {
+      error!("Cannot create AssemblyBuffer: data_size={data_size} exceeds limit {max_sample_size}");
+      return None;
+    }
+
     if fragment_size == 0 || fragment_size as usize > data_size {
       error!("Cannot create AssemblyBuffer: fragment_size={fragment_size} data_size={data_size}");
       return None;
@@ -189,6 +195,7 @@ const MAX_ASSEMBLY_BUFFERS: usize = 128;
 // So there is only one sequence of SNs
 pub(crate) struct FragmentAssembler {
   fragment_size: u16, // number of bytes per fragment. Each writer must select one constant value.
+  max_sample_size: usize, // larger samples are rejected without allocating a buffer
   assembly_buffers: BTreeMap<SequenceNumber, AssemblyBuffer>,
 }
 
@@ -201,10 +208,11 @@ impl fmt::Debug for FragmentAssembler {
 }
 
 impl FragmentAssembler {
-  pub fn new(fragment_size: u16) -> Self {
+  pub fn new(fragment_size: u16, max_sample_size: usize) -> Self {
     debug!("new FragmentAssembler. frag_size = {fragment_size}");
     Self {
       fragment_size,
+      max_sample_size,
       assembly_buffers: BTreeMap::new(),
     }
   }
@@ -221,7 +229,7 @@ impl FragmentAssembler {
     let sn = datafrag.writer_sn;
     match self.assembly_buffers.entry(sn) {
       Entry::Vacant(v) => {
-        let Some(buf) = AssemblyBuffer::new(datafrag) else {
+        let Some(buf) = AssemblyBuffer::new(datafrag, self.max_sample_size) else {
           error!("new_datafrag: failed to create AssemblyBuffer for {sn:?}");
           return None;
         };
@@ -276,14 +284,26 @@ impl FragmentAssembler {
     }
   }
 
-  pub fn garbage_collect_before(&mut self, expire_before: Timestamp) {
+  // Drops the assembly buffers not modified since expire_before. Returns the
+  // sequence number and the number of missing fragments of each.
+  pub fn garbage_collect_before(
+    &mut self,
+    expire_before: Timestamp,
+  ) -> Vec<(SequenceNumber, usize)> {
+    let mut dropped = Vec::new();
     self.assembly_buffers.retain(|sn, ab| {
       let retain = ab.modified_time >= expire_before;
       if !retain {
         info!("AssemblyBuffer dropping {sn:?}");
+        dropped.push((*sn, ab.received_bitmap.iter().filter(|r| !r).count()));
       }
       retain
     });
+    dropped
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.assembly_buffers.is_empty()
   }
 
   // pub fn partially_received_sequence_numbers_iterator(&self) -> Box<dyn
@@ -299,6 +319,13 @@ impl FragmentAssembler {
     // frags but not all
   }
 
+  // Highest fragment number received of a partially received sample
+  pub fn highest_received_frag(&self, seq: SequenceNumber) -> Option<FragmentNumber> {
+    let ab = self.assembly_buffers.get(&seq)?;
+    let f = ab.received_bitmap.iter().rposition(|received| received)?;
+    Some(FragmentNumber::new((f + 1).try_into().ok()?))
+  }
+
   pub fn missing_frags_for(
     &self,
     seq: SequenceNumber,
@@ -348,7 +375,7 @@ mod tests {
 
     // First submessage packs fragments 1 and 2 (K = 2, 2048 payload bytes).
     let first = datafrag(1, 2, frag_size, data_size, whole[0..2048].to_vec());
-    let mut ab = AssemblyBuffer::new(&first).expect("valid first fragment");
+    let mut ab = AssemblyBuffer::new(&first, usize::MAX).expect("valid first fragment");
     assert!(!ab.is_complete());
     ab.insert_frags(&first, frag_size);
     assert!(!ab.is_complete(), "still missing the tail fragment");
@@ -372,7 +399,7 @@ mod tests {
     let whole: Vec<u8> = (0..data_size as usize).map(|i| (i % 97) as u8).collect();
 
     let all = datafrag(1, 3, frag_size, data_size, whole.clone());
-    let mut ab = AssemblyBuffer::new(&all).expect("valid fragment set");
+    let mut ab = AssemblyBuffer::new(&all, usize::MAX).expect("valid fragment set");
     ab.insert_frags(&all, frag_size);
     assert!(ab.is_complete());
     assert_eq!(&ab.buffer_bytes[..], &whole[..]);
@@ -385,7 +412,7 @@ mod tests {
     let frag_size = 256u16;
     let data_size = 512u32; // 2 fragments total
     let bad = datafrag(2, 2, frag_size, data_size, vec![0u8; 256]);
-    let mut ab = AssemblyBuffer::new(&bad).expect("buffer for valid data_size");
+    let mut ab = AssemblyBuffer::new(&bad, usize::MAX).expect("buffer for valid data_size");
     assert!(!ab.insert_frags(&bad, frag_size));
     assert!(!ab.is_complete());
   }
@@ -400,9 +427,35 @@ mod tests {
     let frag_size = 256u16;
     let data_size = 512u32;
     let bad = datafrag(2, 2, frag_size, data_size, vec![0u8; 256]);
-    let mut fa = FragmentAssembler::new(frag_size);
+    let mut fa = FragmentAssembler::new(frag_size, usize::MAX);
     assert!(fa
       .new_datafrag(&bad, BitFlags::<DATAFRAG_Flags>::empty())
       .is_none());
   }
+
+  // A DATAFRAG announcing a huge sample must be rejected before its reassembly
+  // buffer is allocated.
+  #[test]
+  fn fragment_assembler_rejects_oversized_sample() {
+    use enumflags2::BitFlags;
+
+    use super::FragmentAssembler;
+    use crate::messages::submessages::submessages::DATAFRAG_Flags;
+
+    let frag_size = 1024u16;
+    let huge = datafrag(1, 1, frag_size, u32::MAX, vec![0u8; 1024]);
+    let mut fa = FragmentAssembler::new(frag_size, 1024 * 1024);
+    assert!(fa
+      .new_datafrag(&huge, BitFlags::<DATAFRAG_Flags>::empty())
+      .is_none());
+    assert!(!fa.is_partially_received(huge.writer_sn));
+    assert!(AssemblyBuffer::new(&huge, 1024 * 1024).is_none());
+
+    // A sample within the limit is still accepted.
+    let small = datafrag(1, 1, frag_size, 2048, vec![0u8; 1024]);
+    assert!(fa
+      .new_datafrag(&small, BitFlags::<DATAFRAG_Flags>::empty())
+      .is_none());
+    assert!(fa.is_partially_received(small.writer_sn));
+  }
 }
@@ -13,7 +13,7 @@ use crate::{
     protocol_id::ProtocolId,
     protocol_version::ProtocolVersion,
     submessages::{
-      elements::{parameter::Parameter, parameter_list::ParameterList},
+      elements::{inline_qos::StatusInfo, parameter::Parameter, parameter_list::ParameterList},
       submessage_flag::endianness_flag,
       submessages::*,
     },
@@ -266,34 +266,38 @@ impl MessageBuilder {
 
     let mut param_list = ParameterList::new(); // inline QoS goes here
 
-    // Check if we are disposing (by key or by key hash).
-    // If yes, then Indicate Dispose by PID_STATUS_INFO in Inline QoS
-    // RTPS Spec v2.5 Section "9.6.4.9 StatusInfo_t (PID_STATUS_INFO)"
-    // Dispose must be indicated in Inline QoS:
-    // RTPS Spec v2.5 Section "8.7.4 Changes in the Instance State"
-    //
-    // When disposing, we also indicate "unregistered", although we do not
-    // support whole register/unregister mechanism at all. TODO: Does this
-    // make sense?
+    // Check if the instance state changes (dispose or unregister, by key or by
+    // key hash). If yes, then indicate the change by PID_STATUS_INFO in Inline
+    // QoS: RTPS Spec v2.5 Sections "8.7.4 Changes in the Instance State" and
+    // "9.6.4.9 StatusInfo_t (PID_STATUS_INFO)"
     match cache_change.data_value {
-      DDSData::Data { .. } => (), // data sample, not dispose
+      DDSData::Data { .. } => (), // data sample, no state change
 
-      DDSData::DisposeByKey { .. } => {
-        param_list.push(Parameter::create_pid_status_info_parameter(
-          /* disposed */ true, /* unregistered */ true, /* filtered */ false,
-        ));
+      DDSData::DisposeByKey { change_kind, .. } => {
+        param_list.push(StatusInfo::from_change_kind(change_kind).into_parameter());
       }
-      DDSData::DisposeByKeyHash { key_hash, .. } => {
+      DDSData::DisposeByKeyHash {
+        change_kind,
+        key_hash,
+      } => {
         // yes, insert key hash to inline QoS
         param_list.push(Parameter {
           parameter_id: ParameterId::PID_KEY_HASH,
           value: key_hash.to_vec(),
         });
         // ... and tell what the key_hash means
-        let status_info = Parameter::create_pid_status_info_parameter(
-          /* disposed */ true, /* unregistered */ true, /* filtered */ false,
-        );
-        param_list.push(status_info);
+        param_list.push(StatusInfo::from_change_kind(change_kind).into_parameter());
+      }
+      DDSData::CoherentSetEnd => (), // PID_COHERENT_SET only
+    }
+    // Key hash of a sample or a key, if the topic is keyed. A dispose by key hash
+    // carries it already.
+    if let Some(key_hash) = cache_change.write_options.key_hash() {
+      if !matches!(cache_change.data_value, DDSData::DisposeByKeyHash { .. }) {
+        param_list.push(Parameter {
+          parameter_id: ParameterId::PID_KEY_HASH,
+          value: key_hash.to_vec(),
+        });
       }
     }
 
@@ -319,12 +323,26 @@ impl MessageBuilder {
       });
     }
 
+    // The coherent set of the sample, or UNKNOWN in a coherent set end marker.
+    if let Some(coherent_set) = cache_change.write_options.coherent_set() {
+      match coherent_set.write_to_vec_with_ctx(endianness) {
+        Ok(value) => param_list.push(Parameter {
+          parameter_id: ParameterId::PID_COHERENT_SET,
+          value,
+        }),
+        Err(e) => {
+          error!("failed to serialize coherent set: {e:?}");
+          return self;
+        }
+      }
+    }
+
     let serialized_payload = match cache_change.data_value {
       DDSData::Data {
         ref serialized_payload,
       } => Some(serialized_payload.clone()), // contents is Bytes
       DDSData::DisposeByKey { ref key, .. } => Some(key.clone()),
-      DDSData::DisposeByKeyHash { .. } => None,
+      DDSData::DisposeByKeyHash { .. } | DDSData::CoherentSetEnd => None,
     };
 
     #[cfg(not(feature = "security"))]
@@ -381,7 +399,7 @@ impl MessageBuilder {
       | (match cache_change.data_value {
         DDSData::Data { .. } => BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data),
         DDSData::DisposeByKey { .. } => BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Key),
-        DDSData::DisposeByKeyHash { .. } => {
+        DDSData::DisposeByKeyHash { .. } | DDSData::CoherentSetEnd => {
           BitFlags::<DATA_Flags>::from_flag(DATA_Flags::InlineQos)
         }
       })
@@ -439,10 +457,15 @@ impl MessageBuilder {
 
     // Check if we are disposing by key hash
     match cache_change.data_value {
-      DDSData::Data { .. } | DDSData::DisposeByKey { .. } => (), // no => ok
-      DDSData::DisposeByKeyHash { .. } => {
+      DDSData::Data { .. } => (), // no => ok
+      DDSData::DisposeByKey { change_kind, .. } => {
+        // State change of an instance with a large key
+        param_list.push(StatusInfo::from_change_kind(change_kind).into_parameter());
+      }
+      DDSData::DisposeByKeyHash { .. } | DDSData::CoherentSetEnd => {
         error!(
-          "data_frag_msg: Called with DDSData::DisposeByKeyHash. This is not legit! Discarding."
+          "data_frag_msg: Called with DDSData::{:?}. This is not legit! Discarding.",
+          cache_change.data_value
         );
         // DataFrag must contain either data or key payload, disposing by key hash
         // sent in inline QoS (without key or data) is not possible like in Data
@@ -451,6 +474,12 @@ impl MessageBuilder {
         return self;
       }
     }
+    if let Some(key_hash) = cache_change.write_options.key_hash() {
+      param_list.push(Parameter {
+        parameter_id: ParameterId::PID_KEY_HASH,
+        value: key_hash.to_vec(),
+      });
+    }
 
     // If we are sending related sample identity, then insert that.
     if let Some(si) = cache_change.write_options.related_sample_identity() {
@@ -474,6 +503,20 @@ impl MessageBuilder {
       });
     }
 
+    // The coherent set of the sample, or UNKNOWN in a coherent set end marker.
+    if let Some(coherent_set) = cache_change.write_options.coherent_set() {
+      match coherent_set.write_to_vec_with_ctx(endianness) {
+        Ok(value) => param_list.push(Parameter {
+          parameter_id: ParameterId::PID_COHERENT_SET,
+          value,
+        }),
+        Err(e) => {
+          error!("failed to serialize coherent set: {e:?}");
+          return self;
+        }
+      }
+    }
+
     let have_inline_qos = !param_list.is_empty(); // we need this later also
 
     // fragments are numbered starting from 1, not 0. This submessage carries the
@@ -553,7 +596,7 @@ impl MessageBuilder {
       | (match cache_change.data_value {
         DDSData::Data { .. } => BitFlags::<DATAFRAG_Flags>::empty(),
         DDSData::DisposeByKey { .. } => BitFlags::<DATAFRAG_Flags>::from_flag(DATAFRAG_Flags::Key),
-        DDSData::DisposeByKeyHash { .. } => unreachable!(),
+        DDSData::DisposeByKeyHash { .. } | DDSData::CoherentSetEnd => unreachable!(),
       })
       // inline QoS flag
       | (if have_inline_qos {
@@ -681,6 +724,31 @@ impl MessageBuilder {
     self
   }
 
+  // RustDDS Writers do not send HEARTBEAT_FRAG, but the test harness does.
+  #[cfg(any(test, feature = "test-util"))]
+  pub fn heartbeat_frag_msg(
+    mut self,
+    writer_entity_id: EntityId,
+    writer_sn: SequenceNumber,
+    last_fragment_num: FragmentNumber,
+    heartbeat_frag_count: i32,
+    endianness: Endianness,
+    reader_entity_id: EntityId,
+  ) -> Self {
+    let heartbeat_frag = HeartbeatFrag {
+      reader_id: reader_entity_id,
+      writer_id: writer_entity_id,
+      writer_sn,
+      last_fragment_num,
+      count: heartbeat_frag_count,
+    };
+    let flags = BitFlags::<HEARTBEATFRAG_Flags>::from_endianness(endianness);
+    self
+      .submessages
+      .push(heartbeat_frag.create_submessage(flags));
+    self
+  }
+
   pub fn add_header_and_build(self, guid_prefix: GuidPrefix) -> Message {
     Message {
       header: Header {
@@ -11,9 +11,15 @@ use log::{debug, error, info, trace, warn};
 use bytes::Bytes;
 
 use crate::{
+  dds::sampleinfo::ReceptionContext,
   messages::{protocol_version::ProtocolVersion, submessages::submessages::*, vendor_id::VendorId},
   network::udp_listener::PacketOrigin,
-  rtps::{reader::Reader, transmit::InterfaceObservations, Message, Submessage, SubmessageBody},
+  rtps::{
+    reader::Reader,
+    transmit::InterfaceObservations,
+    vendor_quirks::{ActiveQuirks, VendorQuirks},
+    Message, Submessage, SubmessageBody,
+  },
   structure::{
     entity::RTPSEntity,
     guid::{EntityId, GuidPrefix, GUID},
@@ -83,11 +89,31 @@ pub struct MessageReceiverState<'a> {
 
   pub source_timestamp: Option<Timestamp>,
 
+  // Workarounds to apply to the source of this message
+  pub quirks: ActiveQuirks,
+
+  // GuidPrefix from INFO_DESTINATION, if present in this message
+  pub info_destination: Option<GuidPrefix>,
+  // Should Readers store the routing context with each sample?
+  pub record_reception_context: bool,
+
   #[allow(dead_code)] // TODO: Remove this when/if SecureWrapping actually does something.
   #[cfg(feature = "security")]
   pub secure_rtps_wrapped: Option<SecureWrapping>,
 }
 
+impl MessageReceiverState<'_> {
+  pub fn reception_context(&self) -> ReceptionContext {
+    ReceptionContext {
+      source_guid_prefix: self.source_guid_prefix,
+      dest_guid_prefix: self.info_destination,
+      source_timestamp: self.source_timestamp,
+      unicast_reply_locators: self.unicast_reply_locator_list.to_vec(),
+      multicast_reply_locators: self.multicast_reply_locator_list.to_vec(),
+    }
+  }
+}
+
 impl Default for MessageReceiverState<'_> {
   fn default() -> Self {
     Self {
@@ -95,6 +121,9 @@ impl Default for MessageReceiverState<'_> {
       unicast_reply_locator_list: &[],
       multicast_reply_locator_list: &[],
       source_timestamp: Some(Timestamp::INVALID),
+      quirks: ActiveQuirks::default(),
+      info_destination: None,
+      record_reception_context: false,
       #[cfg(feature = "security")]
       secure_rtps_wrapped: None,
     }
@@ -124,10 +153,20 @@ pub(crate) struct MessageReceiver {
   interface_observations: Rc<RefCell<InterfaceObservations>>,
 
   own_guid_prefix: GuidPrefix,
+  // If false, SPDP announcements sent by ourselves, e.g. looped back
+  // via multicast, are dropped.
+  accept_own_spdp: bool,
+  // Workarounds for other implementations, applied according to the source
+  // vendor and version of each message.
+  vendor_quirks: VendorQuirks,
+  // If true, Readers store a ReceptionContext with each received sample.
+  record_reception_context: bool,
   pub source_version: ProtocolVersion,
   pub source_vendor_id: VendorId,
   pub source_guid_prefix: GuidPrefix,
   pub dest_guid_prefix: GuidPrefix,
+  // GuidPrefix of INFO_DESTINATION as received, if there was one
+  pub info_destination: Option<GuidPrefix>,
   pub unicast_reply_locator_list: Vec<Locator>,
   pub multicast_reply_locator_list: Vec<Locator>,
   pub source_timestamp: Option<Timestamp>,
@@ -157,11 +196,15 @@ impl MessageReceiver {
       security_plugins,
       interface_observations,
       own_guid_prefix: participant_guid_prefix,
+      accept_own_spdp: false,
+      vendor_quirks: VendorQuirks::default(),
+      record_reception_context: false,
 
       source_version: ProtocolVersion::THIS_IMPLEMENTATION,
       source_vendor_id: VendorId::VENDOR_UNKNOWN,
       source_guid_prefix: GuidPrefix::UNKNOWN,
       dest_guid_prefix: GuidPrefix::UNKNOWN,
+      info_destination: None,
       unicast_reply_locator_list: vec![Locator::Invalid],
       multicast_reply_locator_list: vec![Locator::Invalid],
       source_timestamp: None,
@@ -176,11 +219,24 @@ impl MessageReceiver {
     }
   }
 
+  pub fn set_accept_own_spdp(&mut self, accept: bool) {
+    self.accept_own_spdp = accept;
+  }
+
+  pub fn set_vendor_quirks(&mut self, vendor_quirks: VendorQuirks) {
+    self.vendor_quirks = vendor_quirks;
+  }
+
+  pub fn set_record_reception_context(&mut self, record: bool) {
+    self.record_reception_context = record;
+  }
+
   pub fn reset(&mut self) {
     self.source_version = ProtocolVersion::THIS_IMPLEMENTATION;
     self.source_vendor_id = VendorId::VENDOR_UNKNOWN;
     self.source_guid_prefix = GuidPrefix::UNKNOWN;
     self.dest_guid_prefix = GuidPrefix::UNKNOWN;
+    self.info_destination = None;
     self.unicast_reply_locator_list.clear();
     self.multicast_reply_locator_list.clear();
     self.source_timestamp = None;
@@ -209,6 +265,11 @@ impl MessageReceiver {
       unicast_reply_locator_list: &self.unicast_reply_locator_list,
       multicast_reply_locator_list: &self.multicast_reply_locator_list,
       source_timestamp: self.source_timestamp,
+      quirks: self
+        .vendor_quirks
+        .for_source(self.source_vendor_id, self.source_version),
+      info_destination: self.info_destination,
+      record_reception_context: self.record_reception_context,
       #[cfg(feature = "security")]
       secure_rtps_wrapped: self.secure_rtps_wrapped.clone(),
     };
@@ -260,10 +321,10 @@ impl MessageReceiver {
         // RTI Connext sends also packets with magic RTPX in the header.
         // We do not know are these really same as RTPS or different, so let's
         // ignore those.
-        info!("Received message with RTPX header. Ignoring.");
+        info_throttled!("Received message with RTPX header. Ignoring.");
         return;
       } else {
-        warn!("Received message with unknown start of header {magic:x?}. Ignoring.");
+        warn_throttled!("Received message with unknown start of header {magic:x?}. Ignoring.");
         return;
       }
     }
@@ -273,7 +334,7 @@ impl MessageReceiver {
     let rtps_message = match Message::read_from_buffer(msg_bytes) {
       Ok(m) => m,
       Err(speedy_err) => {
-        warn!("RTPS deserialize error {speedy_err:?}");
+        warn_throttled!("RTPS deserialize error {speedy_err:?}");
         debug!("Data was {msg_bytes:?}");
         return;
       }
@@ -593,6 +654,19 @@ impl MessageReceiver {
       return;
     }
 
+    // Our own participant data is inserted to Discovery DB locally at startup,
+    // so there is no need to discover ourselves from our own SPDP announcements,
+    // e.g. when they are looped back via multicast. Only participant
+    // announcements are dropped: local Writers reach local Readers through the
+    // network, too.
+    if self.source_guid_prefix == self.own_guid_prefix
+      && target_reader_entity_id == EntityId::SPDP_BUILTIN_PARTICIPANT_READER
+      && !self.accept_own_spdp
+    {
+      trace!("Dropping SPDP message from ourselves.");
+      return;
+    }
+
     #[cfg(feature = "security")]
     if self.must_be_rtps_protection_special_case {
       match target_reader_entity_id {
@@ -653,6 +727,7 @@ impl MessageReceiver {
         target_reader.handle_heartbeat_msg(
           &heartbeat,
           flags.contains(HEARTBEAT_Flags::Final),
+          flags.contains(HEARTBEAT_Flags::Liveliness),
           &mr_state,
         );
       }
@@ -869,7 +944,7 @@ impl MessageReceiver {
         {
           Ok(_) => (),
           Err(TrySendError::Full(_)) => {
-            info!("AckNack pipe full. Looks like I am very busy. Discarding submessage.");
+            info_throttled!("AckNack pipe full. Looks like I am very busy. Discarding submessage.");
           }
           Err(e) => warn!("AckNack pipe fail: {e:?}"),
         }
@@ -886,7 +961,9 @@ impl MessageReceiver {
         {
           Ok(_) => (),
           Err(TrySendError::Full(_)) => {
-            info!("NackFrag pipe full. Looks like I am very busy. Discarding submessage.");
+            info_throttled!(
+              "NackFrag pipe full. Looks like I am very busy. Discarding submessage."
+            );
           }
           Err(e) => warn!("NackFrag pipe fail: {e:?}"),
         }
@@ -1062,6 +1139,7 @@ impl MessageReceiver {
         };
       }
       InterpreterSubmessage::InfoDestination(info_dest, _flags) => {
+        self.info_destination = Some(info_dest.guid_prefix);
         if info_dest.guid_prefix == GUID::GUID_UNKNOWN.prefix {
           self.dest_guid_prefix = self.own_guid_prefix;
         } else {
@@ -1232,6 +1310,13 @@ mod tests {
       data_reader_command_receiver: reader_command_receiver,
       data_reader_waker: data_reader_waker.clone(),
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
 
@@ -1240,7 +1325,8 @@ mod tests {
       Rc::new(UDPSender::new_with_random_port().unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
+    )
+    .unwrap();
 
     // Add info of the writer to the reader
     new_reader.matched_writer_add(
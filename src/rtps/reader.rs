@@ -1,10 +1,17 @@
 use std::{
-  collections::BTreeMap,
-  fmt, iter,
+  cell::Cell,
+  cmp::min,
+  collections::{hash_map::DefaultHasher, BTreeMap},
+  fmt,
+  hash::{Hash, Hasher},
+  iter,
   rc::Rc,
-  sync::{Arc, Mutex, MutexGuard},
+  sync::{
+    atomic::{self, AtomicBool},
+    Arc, Mutex, MutexGuard,
+  },
   task::Waker,
-  time::Duration as StdDuration,
+  time::{Duration as StdDuration, Instant},
 };
 
 use mio_06::Token;
@@ -16,15 +23,23 @@ use speedy::Endianness;
 use speedy::Writable;
 
 use crate::{
+  create_error_internal, create_error_poisoned,
   dds::{
     ddsdata::DDSData,
+    key::KeyHash,
+    participant::{BestEffortReordering, DeadlineTimestamp, FragmentAssembly},
     qos::{policy, HasQoSPolicy, QosPolicies},
+    result::{CreateError, CreateResult},
     statusevents::{
-      CountWithChange, DataReaderStatus, DomainParticipantStatusEvent, StatusChannelSender,
+      CountWithChange, DataReaderStatus, DomainParticipantStatusEvent, LivelinessChangedStatus,
+      SampleRejectedStatusKind, StatusChannelSender,
     },
     with_key::{
       datawriter::{WriteOptions, WriteOptionsBuilder},
-      simpledatareader::ReaderCommand,
+      simpledatareader::{
+        AmbiguousStatusInfo, DataReaderNotifier, DuplicateAcceptance, HistoryBoundaries,
+        ReaderCommand,
+      },
     },
   },
   messages::{
@@ -40,11 +55,14 @@ use crate::{
     vendor_id::VendorId,
   },
   mio_source,
-  network::udp_sender::UDPSender,
+  network::transport::Transport,
   polling::SharedTimer,
   rtps::{
+    coherent_sets::CoherentSets, constant::DEFAULT_MAX_FRAGMENTED_SAMPLE_SIZE,
     fragment_assembler::FragmentAssembler, message_receiver::MessageReceiverState,
-    rtps_writer_proxy::RtpsWriterProxy, timed_event::DpTimerEvent, Message,
+    reorder_buffer::ReorderBuffer, rtps_writer_proxy::RtpsWriterProxy,
+    statistics::StatisticsCounters, timed_event::DpTimerEvent, vendor_quirks::ActiveQuirks,
+    Message,
   },
   structure::{
     cache_change::{CacheChange, ChangeKind},
@@ -54,7 +72,7 @@ use crate::{
     guid::{EntityId, GuidPrefix, GUID},
     locator::Locator,
     sequence_number::{FragmentNumber, FragmentNumberSet, SequenceNumber, SequenceNumberSet},
-    time::Timestamp,
+    time::{Clock, SystemClock, Timestamp},
   },
 };
 #[cfg(feature = "security")]
@@ -67,6 +85,35 @@ use crate::no_security::SecurityPluginsHandle;
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum TimedEvent {
   DeadlineMissedCheck,
+  LivelinessCheck,
+  ReorderFlush,
+  ProactiveNackFrag,
+  FragmentGarbageCollect,
+  HeartbeatResponse,
+}
+
+// A received sample held for reordering, or in an unfinished coherent set.
+struct ReceivedSample {
+  dds_data: DDSData,
+  receive_timestamp: Timestamp,
+  write_options: WriteOptions,
+}
+
+// A response to a HEARTBEAT, waiting for the heartbeat response delay.
+struct PendingHeartbeatResponse {
+  first_sn: SequenceNumber,
+  last_sn: SequenceNumber,
+  response_required: bool,
+  // From the message that carried the HEARTBEAT. Empty if none were given.
+  unicast_reply_locators: Vec<Locator>,
+  due: Instant,
+}
+
+// The Writer that owns an instance under EXCLUSIVE ownership.
+struct InstanceOwner {
+  writer: GUID,
+  // Reception time of the last sample of the instance from the owner
+  last_sample: Timestamp,
 }
 
 // Some pieces necessary to construct a reader.
@@ -83,10 +130,43 @@ pub(crate) struct ReaderIngredients {
   pub data_reader_command_receiver: mio_channel::Receiver<ReaderCommand>,
   pub(crate) data_reader_waker: Arc<Mutex<Option<Waker>>>,
   pub(crate) poll_event_sender: mio_source::PollEventSender,
+  // Liveliness of matched writers, shared with the DataReader
+  pub(crate) liveliness_status: Arc<Mutex<LivelinessChangedStatus>>,
+  // REQUESTED_DEADLINE_MISSED status, shared with the DataReader
+  pub(crate) requested_deadline_missed_status: Arc<Mutex<CountWithChange>>,
+  // Set by the DataReader: flag samples whose payload equals the previous one
+  pub(crate) detect_content_duplicates: Arc<AtomicBool>,
+  // Set by the DataReader: ignore state changes whose StatusInfo is ambiguous
+  pub(crate) ignore_ambiguous_status_info: Arc<AtomicBool>,
+  // Set by the DataReader: deliver repeated SequenceNumbers or not
+  pub(crate) duplicate_acceptance: Arc<Mutex<DuplicateAcceptance>>,
+  // Set by the DataReader: drop received samples and do not acknowledge them
+  pub(crate) paused: Arc<AtomicBool>,
+  // Shared with a DataReader created with ReadStart::FromNow, else None
+  pub(crate) history_boundaries: Option<HistoryBoundaries>,
 
   pub(crate) security_plugins: Option<SecurityPluginsHandle>,
 }
 
+impl ReaderIngredients {
+  // The Reader stores the samples it receives into the topic cache, so the
+  // cache must be the one of the Reader's topic.
+  pub(crate) fn check_topic_cache(&self) -> CreateResult<()> {
+    let topic_cache_name = match self.topic_cache_handle.lock() {
+      Ok(tc) => tc.topic_name(),
+      Err(e) => return create_error_poisoned!("Cannot lock topic cache. Error: {}", e),
+    };
+    if self.topic_name != topic_cache_name {
+      return create_error_internal!(
+        "Cannot create a Reader for topic {}: it is associated with the topic cache of topic {}",
+        self.topic_name,
+        topic_cache_name
+      );
+    }
+    Ok(())
+  }
+}
+
 impl fmt::Debug for ReaderIngredients {
   // Need manual implementation, because channels cannot be Debug formatted.
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -99,10 +179,10 @@ impl fmt::Debug for ReaderIngredients {
 }
 
 pub(crate) struct Reader {
-  // Should the instant be sent?
-  notification_sender: mio_channel::SyncSender<()>,
-  status_sender: StatusChannelSender<DataReaderStatus>,
-  udp_sender: Rc<UDPSender>,
+  // The DataReaders sharing this Reader. The first one is the DataReader the
+  // Reader was created for.
+  data_readers: Vec<DataReaderNotifier>,
+  transport: Rc<dyn Transport>,
 
   // By default, this reader is a StatefulReader (see RTPS spec section 8.4.12)
   // If like_stateless is true, then the reader mimics the behavior of a StatelessReader
@@ -115,7 +195,7 @@ pub(crate) struct Reader {
   // Reader stores a pointer to a mutex on the topic cache
   topic_cache: Arc<Mutex<TopicCache>>,
 
-  #[cfg(test)]
+  #[cfg(any(test, feature = "test-util"))]
   seqnum_instant_map: BTreeMap<SequenceNumber, Timestamp>,
 
   topic_name: String,
@@ -123,26 +203,96 @@ pub(crate) struct Reader {
 
   my_guid: GUID,
 
+  // Longest delay of the ACKNACK response to a HEARTBEAT (participant-builder
+  // `heartbeat_response_delay` knob). Zero = respond at once.
   heartbeat_response_delay: StdDuration,
+  pending_heartbeat_responses: BTreeMap<GUID, PendingHeartbeatResponse>,
+  // Is a TimedEvent::HeartbeatResponse pending in the timer?
+  heartbeat_response_scheduled: bool,
 
-  // TODO: Implement (use) this
-  #[allow(dead_code)]
+  // After answering a HEARTBEAT, the further HEARTBEATs of the same Writer are
+  // answered only when this has passed. Zero = no suppression.
   heartbeat_suppression_duration: StdDuration,
 
   received_heartbeat_count: i32,
 
   fragment_assemblers: BTreeMap<GUID, FragmentAssembler>,
   last_fragment_garbage_collect: Timestamp,
+  // Expiry of partially received samples (participant-builder
+  // `fragment_assembly` knob)
+  fragment_assembly: FragmentAssembly,
+  // Is a TimedEvent::FragmentGarbageCollect pending in the timer?
+  fragment_gc_scheduled: bool,
+  // Larger samples announced in DATAFRAGs are dropped.
+  max_fragmented_sample_size: usize,
+  // Source of reception timestamps and of the time for deadline and lifespan
+  // checks. Fragment garbage collection stays on the system clock.
+  clock: Arc<dyn Clock>,
+  // Participant-wide counters of ACKNACKs sent, fragment timeouts etc.
+  statistics: Arc<StatisticsCounters>,
   matched_writers: BTreeMap<GUID, RtpsWriterProxy>,
   writer_match_count_total: i32, // total count, never decreases
 
-  requested_deadline_missed_count: i32,
+  requested_deadline_missed_count: i32, // total count, never decreases
+  // requested_deadline_missed_count when the DataReader last read the status
+  requested_deadline_missed_reported: i32,
   offered_incompatible_qos_count: i32,
+  writer_restart_count: i32,
+  sample_rejected_count: i32,
+  // Is a TimedEvent::LivelinessCheck pending in the timer?
+  liveliness_check_scheduled: bool,
+
+  // Reordering of samples from each matched Writer, if this is a BestEffort
+  // Reader and the participant-builder `best_effort_reordering` knob is set.
+  best_effort_reordering: Option<BestEffortReordering>,
+  reorder_buffers: BTreeMap<GUID, ReorderBuffer<ReceivedSample>>,
+  // Is a TimedEvent::ReorderFlush pending in the timer?
+  reorder_flush_scheduled: bool,
+  // Unfinished coherent sets from each matched Writer
+  coherent_sets: BTreeMap<GUID, CoherentSets<ReceivedSample>>,
+
+  // Delay before requesting fragment gaps without a HEARTBEAT, if the
+  // participant-builder `proactive_nackfrag_delay` knob is set.
+  proactive_nackfrag_delay: Option<StdDuration>,
+  // When to request the fragment gap of each partially received sample
+  nackfrag_gap_deadlines: BTreeMap<(GUID, SequenceNumber), Instant>,
+  // Is a TimedEvent::ProactiveNackFrag pending in the timer?
+  proactive_nackfrag_scheduled: bool,
+
+  // Timestamp of received samples that the requested deadline is measured
+  // against (participant-builder `deadline_timestamp` knob)
+  deadline_timestamp: DeadlineTimestamp,
+
+  // Reception time of the last delivered sample of each instance, for the
+  // TimeBasedFilter QoS. The single instance of a NO_KEY topic has
+  // KeyHash::zero().
+  time_based_filter_last_delivered: BTreeMap<KeyHash, Timestamp>,
+
+  // Current owner of each instance, for the EXCLUSIVE Ownership QoS
+  instance_owners: BTreeMap<KeyHash, InstanceOwner>,
+
+  // Hash of the payload of the last delivered sample of each instance, if
+  // detect_content_duplicates is set.
+  detect_content_duplicates: Arc<AtomicBool>,
+  last_content_hash: BTreeMap<KeyHash, u64>,
+
+  // See DataReader::set_ambiguous_status_info
+  ignore_ambiguous_status_info: Arc<AtomicBool>,
+  // See DataReader::set_duplicate_acceptance
+  duplicate_acceptance: Arc<Mutex<DuplicateAcceptance>>,
+
+  // See DataReader::pause
+  paused: Arc<AtomicBool>,
+  // See ReadStart::FromNow
+  history_boundaries: Option<HistoryBoundaries>,
 
   pub(crate) timed_event_timer: SharedTimer<DpTimerEvent>,
+  // Is a DpTimerEvent::DisposeReader pending in the timer? Set when we notice
+  // that our DataReader is gone.
+  disposal_scheduled: Cell<bool>,
   pub(crate) data_reader_command_receiver: mio_channel::Receiver<ReaderCommand>,
-  data_reader_waker: Arc<Mutex<Option<Waker>>>,
-  poll_event_sender: mio_source::PollEventSender,
+  liveliness_status: Arc<Mutex<LivelinessChangedStatus>>,
+  requested_deadline_missed_status: Arc<Mutex<CountWithChange>>,
 
   participant_status_sender: StatusChannelSender<DomainParticipantStatusEvent>,
 
@@ -150,37 +300,29 @@ pub(crate) struct Reader {
   security_plugins: Option<SecurityPluginsHandle>,
 }
 
-// If we are assembling a fragment, but it does not receive any updates
-// for this time, the AssemblyBuffer is just dropped.
-const FRAGMENT_ASSEMBLY_TIMEOUT: Duration = Duration::from_secs(10);
-// minimum interval (max frequency) of AssemblyBuffer GC
-const MIN_FRAGMENT_GC_INTERVAL: Duration = Duration::from_secs(2);
-
 impl Reader {
   pub(crate) fn new(
     i: ReaderIngredients,
-    udp_sender: Rc<UDPSender>,
+    transport: Rc<dyn Transport>,
     timed_event_timer: SharedTimer<DpTimerEvent>,
     participant_status_sender: StatusChannelSender<DomainParticipantStatusEvent>,
-  ) -> Self {
+  ) -> CreateResult<Self> {
     // Verify that the topic cache corresponds to the topic of the Reader
-    let topic_cache_name = i.topic_cache_handle.lock().unwrap().topic_name();
-    if i.topic_name != topic_cache_name {
-      panic!(
-        "RustDDS internal bug: topic name {} and topic cache name {} differ when creating a Reader",
-        i.topic_name, topic_cache_name
-      );
-    }
+    i.check_topic_cache()?;
 
     // If reader should be stateless, only BestEffort QoS is supported
     if i.like_stateless && i.qos_policy.is_reliable() {
       panic!("RustDDS internal bug: attempted to create a stateless Reader with Reliable QoS");
     }
 
-    Self {
-      notification_sender: i.notification_sender,
-      status_sender: i.status_sender,
-      udp_sender,
+    Ok(Self {
+      data_readers: vec![DataReaderNotifier {
+        notification_sender: i.notification_sender,
+        status_sender: i.status_sender,
+        data_reader_waker: i.data_reader_waker,
+        poll_event_sender: i.poll_event_sender,
+      }],
+      transport,
       like_stateless: i.like_stateless,
       reliability: i
         .qos_policy
@@ -190,27 +332,55 @@ impl Reader {
       topic_name: i.topic_name,
       qos_policy: i.qos_policy,
 
-      #[cfg(test)]
+      #[cfg(any(test, feature = "test-util"))]
       seqnum_instant_map: BTreeMap::new(),
       my_guid: i.guid,
 
-      heartbeat_response_delay: StdDuration::new(0, 500_000_000), // 0,5sec
-      heartbeat_suppression_duration: StdDuration::new(0, 0),
+      heartbeat_response_delay: StdDuration::ZERO,
+      pending_heartbeat_responses: BTreeMap::new(),
+      heartbeat_response_scheduled: false,
+      heartbeat_suppression_duration: StdDuration::ZERO,
       received_heartbeat_count: 0,
       fragment_assemblers: BTreeMap::new(),
       last_fragment_garbage_collect: Timestamp::now(),
+      fragment_assembly: FragmentAssembly::default(),
+      fragment_gc_scheduled: false,
+      max_fragmented_sample_size: DEFAULT_MAX_FRAGMENTED_SAMPLE_SIZE,
+      clock: Arc::new(SystemClock),
+      statistics: Arc::new(StatisticsCounters::default()),
       matched_writers: BTreeMap::new(),
       writer_match_count_total: 0,
       requested_deadline_missed_count: 0,
+      requested_deadline_missed_reported: 0,
       offered_incompatible_qos_count: 0,
+      writer_restart_count: 0,
+      sample_rejected_count: 0,
+      liveliness_check_scheduled: false,
+      best_effort_reordering: None,
+      reorder_buffers: BTreeMap::new(),
+      reorder_flush_scheduled: false,
+      coherent_sets: BTreeMap::new(),
+      proactive_nackfrag_delay: None,
+      nackfrag_gap_deadlines: BTreeMap::new(),
+      proactive_nackfrag_scheduled: false,
+      deadline_timestamp: DeadlineTimestamp::default(),
+      time_based_filter_last_delivered: BTreeMap::new(),
+      instance_owners: BTreeMap::new(),
+      detect_content_duplicates: i.detect_content_duplicates,
+      last_content_hash: BTreeMap::new(),
+      ignore_ambiguous_status_info: i.ignore_ambiguous_status_info,
+      duplicate_acceptance: i.duplicate_acceptance,
+      paused: i.paused,
+      history_boundaries: i.history_boundaries,
       timed_event_timer,
+      disposal_scheduled: Cell::new(false),
       data_reader_command_receiver: i.data_reader_command_receiver,
-      data_reader_waker: i.data_reader_waker,
-      poll_event_sender: i.poll_event_sender,
+      liveliness_status: i.liveliness_status,
+      requested_deadline_missed_status: i.requested_deadline_missed_status,
       participant_status_sender,
 
       security_plugins: i.security_plugins,
-    }
+    })
   }
   // TODO: check if it's necessary to implement different handlers for discovery
   // and user messages
@@ -221,6 +391,51 @@ impl Reader {
     self.guid().entity_id.as_token()
   }
 
+  pub fn set_max_fragmented_sample_size(&mut self, max_sample_size: usize) {
+    self.max_fragmented_sample_size = max_sample_size;
+  }
+
+  /// See the participant-builder `fragment_assembly` knob.
+  pub fn set_fragment_assembly(&mut self, timing: FragmentAssembly) {
+    self.fragment_assembly = timing;
+  }
+
+  /// See the participant-builder `clock` knob.
+  pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+    self.clock = clock;
+  }
+
+  pub fn set_statistics(&mut self, statistics: Arc<StatisticsCounters>) {
+    self.statistics = statistics;
+  }
+
+  /// See the participant-builder `best_effort_reordering` knob. Applies only
+  /// to BestEffort Readers.
+  pub fn set_best_effort_reordering(&mut self, policy: Option<BestEffortReordering>) {
+    self.best_effort_reordering = policy;
+  }
+
+  /// See the participant-builder `proactive_nackfrag_delay` knob. Applies only
+  /// to Reliable Readers.
+  pub fn set_proactive_nackfrag_delay(&mut self, delay: Option<StdDuration>) {
+    self.proactive_nackfrag_delay = delay;
+  }
+
+  /// See the participant-builder `heartbeat_response_delay` knob.
+  pub fn set_heartbeat_response_delay(&mut self, delay: StdDuration) {
+    self.heartbeat_response_delay = delay;
+  }
+
+  /// See DataReader::set_heartbeat_suppression.
+  pub fn set_heartbeat_suppression_duration(&mut self, duration: StdDuration) {
+    self.heartbeat_suppression_duration = duration;
+  }
+
+  /// See the participant-builder `deadline_timestamp` knob.
+  pub fn set_deadline_timestamp(&mut self, timestamp: DeadlineTimestamp) {
+    self.deadline_timestamp = timestamp;
+  }
+
   pub fn set_requested_deadline_check_timer(&mut self) {
     if let Some(deadline) = self.qos_policy.deadline {
       debug!(
@@ -244,24 +459,35 @@ impl Reader {
   }
 
   pub fn send_status_change(&self, change: DataReaderStatus) {
-    match self.status_sender.try_send(change) {
-      Ok(()) => (), // expected result
-      Err(mio_channel::TrySendError::Full(_)) => {
-        trace!("Reader cannot send new status changes, datareader is full.");
-        // It is perfectly normal to fail due to full channel, because
-        // no-one is required to be listening to these.
-      }
-      Err(mio_channel::TrySendError::Disconnected(_)) => {
-        // If we get here, our DataReader has died. The Reader should now dispose
-        // itself. Or possibly it has lost the receiver object, which is sort of
-        // sloppy, but does not necessarily mean the end of the world.
-        // TODO: Implement Reader disposal.
-        info!("send_status_change - cannot send status, DataReader Disconnected.");
-      }
-      Err(mio_channel::TrySendError::Io(e)) => {
-        error!("send_status_change - cannot send status: {e:?}");
+    let Some((last, others)) = self.data_readers.split_last() else {
+      self.schedule_disposal();
+      return;
+    };
+    let mut sent: Vec<_> = others
+      .iter()
+      .map(|data_reader| data_reader.status_sender.try_send(change.clone()))
+      .collect();
+    sent.push(last.status_sender.try_send(change));
+    let mut disconnected = 0;
+    for result in sent {
+      match result {
+        Ok(()) => (), // expected result
+        Err(mio_channel::TrySendError::Full(_)) => {
+          trace!("Reader cannot send new status changes, datareader is full.");
+          // It is perfectly normal to fail due to full channel, because
+          // no-one is required to be listening to these.
+        }
+        Err(mio_channel::TrySendError::Disconnected(_)) => disconnected += 1,
+        Err(mio_channel::TrySendError::Io(e)) => {
+          error!("send_status_change - cannot send status: {e:?}");
+        }
       }
     }
+    if disconnected == self.data_readers.len() {
+      // If we get here, our DataReaders have died.
+      info!("send_status_change - cannot send status, DataReader Disconnected.");
+      self.schedule_disposal();
+    }
   }
 
   fn send_participant_status(&self, event: DomainParticipantStatusEvent) {
@@ -283,33 +509,48 @@ impl Reader {
       Some(policy::Deadline(deadline_duration)) => deadline_duration,
     };
 
-    let mut changes: Vec<DataReaderStatus> = vec![];
-    let now = Timestamp::now();
-    for writer_proxy in self.matched_writers.values_mut() {
-      if let Some(last_change) = writer_proxy.last_change_timestamp() {
-        let since_last = now.duration_since(last_change);
-        // if time singe last received message is greater than deadline increase status
-        // and return notification.
-        trace!("Comparing deadlines: {since_last:?} - {deadline_duration:?}");
-        if since_last > deadline_duration {
-          debug!("Deadline missed: {since_last:?} - {deadline_duration:?}");
-          self.requested_deadline_missed_count += 1;
-          changes.push(DataReaderStatus::RequestedDeadlineMissed {
-            count: CountWithChange::start_from(self.requested_deadline_missed_count, 1),
-          });
-        }
-      } else {
-        // no messages received ever so deadline must be missed.
-        // TODO: But what if the Reader or WriterProxy was just created?
-        self.requested_deadline_missed_count += 1;
-        changes.push(DataReaderStatus::RequestedDeadlineMissed {
-          count: CountWithChange::start_from(self.requested_deadline_missed_count, 1),
-        });
+    let mut missed = 0;
+    let now = self.clock.now();
+    for writer_proxy in self.matched_writers.values() {
+      let last_change = match self.deadline_timestamp {
+        DeadlineTimestamp::Reception => writer_proxy.last_change_timestamp(),
+        DeadlineTimestamp::Source => writer_proxy.last_change_source_timestamp(),
+      };
+      // A Writer that has not sent anything yet has had the deadline since it
+      // was matched, so that a newly matched Writer is not reported at once.
+      let since_last = now.duration_since(last_change.unwrap_or(writer_proxy.matched_at));
+      // if time singe last received message is greater than deadline increase status
+      // and return notification.
+      trace!("Comparing deadlines: {since_last:?} - {deadline_duration:?}");
+      if since_last > deadline_duration {
+        debug!("Deadline missed: {since_last:?} - {deadline_duration:?}");
+        missed += 1;
       }
     } // for
-    changes
+    (0..missed)
+      .map(|_| {
+        self.requested_deadline_missed_count += 1;
+        DataReaderStatus::RequestedDeadlineMissed {
+          count: self.update_requested_deadline_missed_status(),
+        }
+      })
+      .collect()
   } // fn
 
+  // Publish the REQUESTED_DEADLINE_MISSED status to the DataReader. The change
+  // is counted from when the DataReader last read the status.
+  fn update_requested_deadline_missed_status(&self) -> CountWithChange {
+    let status = CountWithChange::new(
+      self.requested_deadline_missed_count,
+      self.requested_deadline_missed_count - self.requested_deadline_missed_reported,
+    );
+    match self.requested_deadline_missed_status.lock() {
+      Ok(mut shared) => *shared = status,
+      Err(e) => error!("Cannot update requested deadline missed status: {e:?}"),
+    }
+    status
+  }
+
   // Handle a single timed event. The shared timer is drained by the event loop,
   // which dispatches each expired event to the addressed Reader.
   pub fn handle_timed_event(&mut self, event: TimedEvent) {
@@ -318,6 +559,153 @@ impl Reader {
         self.handle_requested_deadline_event();
         self.set_requested_deadline_check_timer(); // re-prime timer
       }
+      TimedEvent::LivelinessCheck => {
+        self.liveliness_check_scheduled = false;
+        self.handle_liveliness_check();
+        self.schedule_liveliness_check(); // re-prime timer for remaining
+                                          // writers
+      }
+      TimedEvent::ReorderFlush => {
+        self.reorder_flush_scheduled = false;
+        self.handle_reorder_flush();
+        self.schedule_reorder_flush();
+      }
+      TimedEvent::ProactiveNackFrag => {
+        self.proactive_nackfrag_scheduled = false;
+        self.handle_proactive_nackfrags();
+        self.schedule_proactive_nackfrag();
+      }
+      TimedEvent::HeartbeatResponse => {
+        self.heartbeat_response_scheduled = false;
+        self.handle_heartbeat_responses();
+        self.schedule_heartbeat_response();
+      }
+      TimedEvent::FragmentGarbageCollect => {
+        self.fragment_gc_scheduled = false;
+        self.garbage_collect_fragments_now();
+        self.schedule_fragment_garbage_collect();
+      }
+    }
+  }
+
+  // Liveliness of matched remote Writers.
+  // See DDS Spec v1.4 Section 2.2.3.11 LIVELINESS and Section 2.2.4.1
+  // LIVELINESS_CHANGED.
+
+  fn current_liveliness_status(&self) -> LivelinessChangedStatus {
+    let (alive, not_alive): (Vec<&RtpsWriterProxy>, Vec<&RtpsWriterProxy>) =
+      self.matched_writers.values().partition(|wp| wp.is_alive);
+    LivelinessChangedStatus::new(
+      alive.iter().map(|wp| wp.remote_writer_guid).collect(),
+      not_alive.iter().map(|wp| wp.remote_writer_guid).collect(),
+    )
+  }
+
+  // Publish the current liveliness of matched Writers to the DataReader, both
+  // as a status event and as the snapshot behind
+  // DataReader::liveliness_changed_status.
+  fn send_liveliness_changed(&self, alive_change: i32, not_alive_change: i32) {
+    let status = self.current_liveliness_status();
+    self.send_status_change(DataReaderStatus::LivelinessChanged {
+      alive_total: CountWithChange::new(status.alive_count(), alive_change),
+      not_alive_total: CountWithChange::new(status.not_alive_count(), not_alive_change),
+    });
+    match self.liveliness_status.lock() {
+      Ok(mut shared) => *shared = status,
+      Err(e) => error!("Cannot update liveliness status: {e:?}"),
+    }
+  }
+
+  // Arm the timer to fire when the earliest liveliness lease of an alive
+  // Writer expires. Only one check is kept pending at a time.
+  fn schedule_liveliness_check(&mut self) {
+    if self.liveliness_check_scheduled {
+      return;
+    }
+    let earliest_expiry = self
+      .matched_writers
+      .values()
+      .filter(|wp| wp.is_alive)
+      .filter_map(RtpsWriterProxy::liveliness_expiry)
+      .min();
+    if let Some(expiry) = earliest_expiry {
+      self.timed_event_timer.borrow_mut().set_timeout(
+        expiry.saturating_duration_since(Instant::now()),
+        DpTimerEvent::Reader {
+          entity_id: self.my_guid.entity_id,
+          event: TimedEvent::LivelinessCheck,
+        },
+      );
+      self.liveliness_check_scheduled = true;
+    }
+  }
+
+  fn handle_liveliness_check(&mut self) {
+    let now = Instant::now();
+    let mut lost_count = 0;
+    for writer_proxy in self.matched_writers.values_mut() {
+      if writer_proxy.is_alive && writer_proxy.liveliness_expiry().is_some_and(|e| e <= now) {
+        debug!(
+          "Liveliness lost: writer={:?} topic={:?}",
+          writer_proxy.remote_writer_guid, self.topic_name
+        );
+        writer_proxy.is_alive = false;
+        lost_count += 1;
+      }
+    }
+    if lost_count > 0 {
+      self.send_liveliness_changed(-lost_count, lost_count);
+    }
+  }
+
+  // Record a liveliness assertion from a matched Writer, and notify the
+  // DataReader if the Writer was considered not alive.
+  fn writer_liveliness_asserted(&mut self, writer_guid: GUID) {
+    let became_alive = self
+      .matched_writer_mut(writer_guid)
+      .is_some_and(RtpsWriterProxy::assert_liveliness);
+    if became_alive {
+      debug!(
+        "Liveliness regained: writer={writer_guid:?} topic={:?}",
+        self.topic_name
+      );
+      self.send_liveliness_changed(1, -1);
+      self.schedule_liveliness_check();
+    }
+  }
+
+  // A HEARTBEAT from a Writer asserts its liveliness if the Writer is
+  // AUTOMATIC, or if the Writer has set the Liveliness flag, i.e. the
+  // HEARTBEAT was sent due to manual assertion.
+  fn handle_writer_liveliness_heartbeat(&mut self, writer_guid: GUID, liveliness_flag: bool) {
+    let asserts = self.matched_writer(writer_guid).is_some_and(|wp| {
+      liveliness_flag
+        || matches!(
+          wp.offered_liveliness,
+          Some(policy::Liveliness::Automatic { .. })
+        )
+    });
+    if asserts {
+      self.writer_liveliness_asserted(writer_guid);
+    }
+  }
+
+  // A remote participant has asserted its liveliness via the built-in
+  // participant message topic. Automatic assertions cover AUTOMATIC Writers,
+  // manual assertions cover also MANUAL_BY_PARTICIPANT Writers.
+  pub fn participant_liveliness_asserted(&mut self, guid_prefix: GuidPrefix, manual: bool) {
+    let asserted_writers: Vec<GUID> = self
+      .matched_writers
+      .range(guid_prefix.range())
+      .filter(|(_, wp)| match wp.offered_liveliness {
+        Some(policy::Liveliness::Automatic { .. }) => true,
+        Some(policy::Liveliness::ManualByParticipant { .. }) => manual,
+        Some(policy::Liveliness::ManualByTopic { .. }) | None => false,
+      })
+      .map(|(g, _)| *g)
+      .collect();
+    for writer in asserted_writers {
+      self.writer_liveliness_asserted(writer);
     }
   }
 
@@ -326,9 +714,16 @@ impl Reader {
     loop {
       use std::sync::mpsc::TryRecvError;
       match self.data_reader_command_receiver.try_recv() {
-        Ok(ReaderCommand::ResetRequestedDeadlineStatus) => {
-          warn!("RESET_REQUESTED_DEADLINE_STATUS not implemented!");
-          // TODO: This should be implemented.
+        Ok(ReaderCommand::ResetRequestedDeadlineStatus { read_count }) => {
+          // Misses after the read stay in the change.
+          self.requested_deadline_missed_reported = read_count;
+          self.update_requested_deadline_missed_status();
+        }
+        Ok(ReaderCommand::AttachDataReader(data_reader)) => {
+          self.data_readers.push(data_reader);
+        }
+        Ok(ReaderCommand::SetHeartbeatSuppression(duration)) => {
+          self.set_heartbeat_suppression_duration(duration);
         }
         // Disconnected is normal when terminating
         Err(TryRecvError::Disconnected) => {
@@ -351,7 +746,7 @@ impl Reader {
   }
 
   // TODO Used for test/debugging purposes
-  #[cfg(test)]
+  #[cfg(any(test, feature = "test-util"))]
   pub fn history_cache_change_data(&self, sequence_number: SequenceNumber) -> Option<DDSData> {
     let topic_cache = self.acquire_the_topic_cache_guard();
     let cc = self
@@ -376,6 +771,84 @@ impl Reader {
     }
   }
 
+  // Count the ACKNACKs and NACK_FRAGs to `writer_guid` from `count` on.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn set_next_acknack_count(&mut self, writer_guid: GUID, count: i32) {
+    if let Some(writer_proxy) = self.matched_writer_mut(writer_guid) {
+      writer_proxy.sent_ack_nack_count = count;
+    }
+  }
+
+  // Sequence numbers of all samples stored into the topic cache, in order.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn received_sequence_numbers(&self) -> Vec<SequenceNumber> {
+    self.seqnum_instant_map.keys().copied().collect()
+  }
+
+  // Sequence numbers of the samples in the topic cache, in the order a
+  // BestEffort DataReader reads them.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn cached_sequence_numbers(&self) -> Vec<SequenceNumber> {
+    self
+      .acquire_the_topic_cache_guard()
+      .get_changes_in_range(false, Timestamp::ZERO, &BTreeMap::new())
+      .map(|(_, cc)| cc.sequence_number)
+      .collect()
+  }
+
+  // Sequence numbers of the samples in the topic cache, in the order a
+  // Reliable DataReader reads them in DeliveryOrder::InOrder.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn reliably_cached_sequence_numbers(&self) -> Vec<SequenceNumber> {
+    self
+      .acquire_the_topic_cache_guard()
+      .get_changes_in_range(true, Timestamp::ZERO, &BTreeMap::new())
+      .map(|(_, cc)| cc.sequence_number)
+      .collect()
+  }
+
+  // Sequence numbers of the samples in the topic cache that are flagged as
+  // content duplicates.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn content_duplicate_sequence_numbers(&self) -> Vec<SequenceNumber> {
+    self
+      .acquire_the_topic_cache_guard()
+      .get_changes_in_range(false, Timestamp::ZERO, &BTreeMap::new())
+      .filter(|(_, cc)| cc.write_options.content_duplicate())
+      .map(|(_, cc)| cc.sequence_number)
+      .collect()
+  }
+
+  // See DataReader::set_detect_content_duplicates.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn set_detect_content_duplicates(&self, enabled: bool) {
+    self
+      .detect_content_duplicates
+      .store(enabled, atomic::Ordering::Relaxed);
+  }
+
+  // See DataReader::set_duplicate_acceptance.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn set_duplicate_acceptance(&self, acceptance: DuplicateAcceptance) {
+    *self.duplicate_acceptance.lock().unwrap() = acceptance;
+  }
+
+  // See DataReader::pause.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn set_paused(&self, paused: bool) {
+    self.paused.store(paused, atomic::Ordering::Relaxed);
+  }
+
+  fn is_paused(&self) -> bool {
+    self.paused.load(atomic::Ordering::Relaxed)
+  }
+
+  // Reception timestamp of the stored sample `sequence_number`.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn reception_timestamp(&self, sequence_number: SequenceNumber) -> Option<Timestamp> {
+    self.seqnum_instant_map.get(&sequence_number).copied()
+  }
+
   // updates or adds a new writer proxy, doesn't touch changes
   pub fn update_writer_proxy(&mut self, proxy: RtpsWriterProxy, offered_qos: &QosPolicies) {
     if self.like_stateless {
@@ -392,6 +865,12 @@ impl Reader {
     match offered_qos.compliance_failure_wrt(&self.qos_policy) {
       None => {
         // success, update or insert
+        let mut proxy = proxy;
+        proxy.offered_liveliness = offered_qos.liveliness;
+        proxy.ownership_strength = match offered_qos.ownership {
+          Some(policy::Ownership::Exclusive { strength }) => strength,
+          Some(policy::Ownership::Shared) | None => 0,
+        };
         let count_change = self.matched_writer_update(proxy);
         if count_change > 0 {
           self.writer_match_count_total += count_change;
@@ -405,14 +884,26 @@ impl Reader {
             remote_writer: writer,
           });
 
+          // A newly matched Writer is considered alive
+          self.send_liveliness_changed(count_change, 0);
           info!(
             "Matched new remote writer on topic={:?} writer={:?}",
             self.topic_name, writer
           );
         }
+        self.schedule_liveliness_check();
       }
       Some(bad_policy_id) => {
         // no QoS match.
+        // If the writer was matched before, it has changed its QoS to something
+        // we cannot accept, so unmatch it.
+        if self.matched_writers.contains_key(&writer) {
+          info!(
+            "Writer changed to incompatible QoS, unmatching: topic={:?} writer={:?}",
+            self.topic_name, writer
+          );
+          self.remove_writer_proxy(writer);
+        }
         self.offered_incompatible_qos_count += 1;
         self.send_status_change(DataReaderStatus::RequestedIncompatibleQos {
           count: CountWithChange::new(self.offered_incompatible_qos_count, 1),
@@ -428,10 +919,12 @@ impl Reader {
           offered_qos: Box::new(offered_qos.clone()),
         });
 
-        warn!("update_writer_proxy - QoS mismatch {bad_policy_id:?}");
-        info!(
+        warn_throttled!("update_writer_proxy - QoS mismatch {bad_policy_id:?}");
+        info_throttled!(
           "update_writer_proxy - QoS mismatch: topic={:?} requested={:?}  offered={:?}",
-          self.topic_name, self.qos_policy, offered_qos
+          self.topic_name,
+          self.qos_policy,
+          offered_qos
         );
       }
     }
@@ -439,18 +932,37 @@ impl Reader {
 
   // return value counts how many new proxies were added
   fn matched_writer_update(&mut self, proxy: RtpsWriterProxy) -> i32 {
+    let offered_liveliness = proxy.offered_liveliness;
+    let ownership_strength = proxy.ownership_strength;
     if let Some(op) = self.matched_writer_mut(proxy.remote_writer_guid) {
       op.update_contents(proxy);
+      op.offered_liveliness = offered_liveliness;
+      op.ownership_strength = ownership_strength;
       0
     } else {
+      let mut proxy = proxy;
+      proxy.matched_at = self.clock.now();
       self.matched_writers.insert(proxy.remote_writer_guid, proxy);
       1
     }
   }
 
   pub fn remove_writer_proxy(&mut self, writer_guid: GUID) {
-    if self.matched_writers.contains_key(&writer_guid) {
-      self.matched_writers.remove(&writer_guid);
+    // Samples held for reordering are not waiting for anything anymore.
+    if let Some(mut buffer) = self.reorder_buffers.remove(&writer_guid) {
+      self.deliver_reordered(writer_guid, buffer.flush_all());
+    }
+    // Unfinished coherent sets of the Writer can never be completed.
+    self.coherent_sets.remove(&writer_guid);
+    // Instances owned by the Writer are free to be taken by another one.
+    self
+      .instance_owners
+      .retain(|_, owner| owner.writer != writer_guid);
+    self
+      .nackfrag_gap_deadlines
+      .retain(|(writer, _), _| *writer != writer_guid);
+    self.pending_heartbeat_responses.remove(&writer_guid);
+    if let Some(removed_proxy) = self.matched_writers.remove(&writer_guid) {
       #[cfg(feature = "security")]
       if let Some(security_plugins_handle) = &self.security_plugins {
         security_plugins_handle
@@ -463,6 +975,11 @@ impl Reader {
         current: CountWithChange::new(self.matched_writers.len() as i32, -1),
         writer: writer_guid,
       });
+      if removed_proxy.is_alive {
+        self.send_liveliness_changed(-1, 0);
+      } else {
+        self.send_liveliness_changed(0, -1);
+      }
     }
   }
 
@@ -491,7 +1008,7 @@ impl Reader {
     }
   }
 
-  #[cfg(test)]
+  #[cfg(any(test, feature = "test-util"))]
   pub(crate) fn matched_writer_add(
     &mut self,
     remote_writer_guid: GUID,
@@ -517,6 +1034,12 @@ impl Reader {
     self.matched_writers.get_mut(&remote_writer_guid)
   }
 
+  // A writer submessage is for this Reader if its reader_id is either ours or
+  // UNKNOWN, which means all matched Readers of the writer.
+  fn is_addressed_to_this_reader(&self, reader_id: EntityId) -> bool {
+    reader_id == EntityId::UNKNOWN || reader_id == self.entity_id()
+  }
+
   // handles regular data message and updates history cache
   pub fn handle_data_msg(
     &mut self,
@@ -525,7 +1048,17 @@ impl Reader {
     mr_state: &MessageReceiverState,
   ) {
     // trace!("handle_data_msg entry");
-    let receive_timestamp = Timestamp::now();
+    if !self.is_addressed_to_this_reader(data.reader_id) {
+      debug!(
+        "Dropping DATA addressed to reader {:?}, not this reader {:?}. writer={:?} sn={:?}",
+        data.reader_id,
+        self.entity_id(),
+        data.writer_id,
+        data.writer_sn
+      );
+      return;
+    }
+    let receive_timestamp = self.clock.now();
 
     // parse write_options out of the message
     let mut write_options_b = WriteOptionsBuilder::new();
@@ -546,18 +1079,50 @@ impl Reader {
     {
       write_options_b = write_options_b.related_sample_identity(related_sample_identity);
     }
+    if let Some(key_hash) = data.inline_qos.as_ref().and_then(|inline_qos_parameters| {
+      InlineQos::key_hash(inline_qos_parameters).unwrap_or_else(|e| {
+        error!("Deserializing key_hash: {:?}", e);
+        None
+      })
+    }) {
+      write_options_b = write_options_b.key_hash(key_hash);
+    }
+    if mr_state.record_reception_context {
+      write_options_b = write_options_b.reception_context(mr_state.reception_context());
+    }
 
     let writer_guid = GUID::new_with_prefix_and_id(mr_state.source_guid_prefix, data.writer_id);
     let writer_seq_num = data.writer_sn; // for borrow checker
 
+    let coherent_set = data.inline_qos.as_ref().and_then(|inline_qos_parameters| {
+      InlineQos::coherent_set(inline_qos_parameters, representation_identifier).unwrap_or_else(
+        |e| {
+          error!("Deserializing coherent_set: {:?}", e);
+          None
+        },
+      )
+    });
+    let mut write_options = write_options_b.build();
+    match coherent_set {
+      // End marker: DATA with only Inline QoS
+      Some(SequenceNumber::UNKNOWN) if data.serialized_payload.is_none() => {
+        self.process_coherent_set_end(writer_guid, writer_seq_num, receive_timestamp);
+        return;
+      }
+      Some(SequenceNumber::UNKNOWN) | None => (),
+      Some(coherent_set) => write_options = write_options.with_coherent_set(coherent_set),
+    }
+
     match self.data_to_dds_data(data, data_flags) {
-      Ok(dds_data) => self.process_received_data(
+      Ok(Some(dds_data)) => self.process_received_data(
         dds_data,
         receive_timestamp,
-        write_options_b.build(),
+        write_options,
         writer_guid,
         writer_seq_num,
+        mr_state.quirks,
       ),
+      Ok(None) => self.ignore_change(writer_guid, writer_seq_num),
       Err(e) => debug!("Parsing DATA to DDSData failed: {e}"),
     }
   }
@@ -568,9 +1133,19 @@ impl Reader {
     datafrag_flags: BitFlags<DATAFRAG_Flags>,
     mr_state: &MessageReceiverState,
   ) {
+    if !self.is_addressed_to_this_reader(datafrag.reader_id) {
+      debug!(
+        "Dropping DATA_FRAG addressed to reader {:?}, not this reader {:?}. writer={:?} sn={:?}",
+        datafrag.reader_id,
+        self.entity_id(),
+        datafrag.writer_id,
+        datafrag.writer_sn
+      );
+      return;
+    }
     let writer_guid = GUID::new_with_prefix_and_id(mr_state.source_guid_prefix, datafrag.writer_id);
     let seq_num = datafrag.writer_sn;
-    let receive_timestamp = Timestamp::now();
+    let receive_timestamp = self.clock.now();
     //trace!("DATAFRAG received topic={:?}", self.topic_name);
 
     // check if this submessage is expired already
@@ -581,9 +1156,12 @@ impl Reader {
     {
       let elapsed = receive_timestamp.duration_since(source_timestamp);
       if lifespan.duration < elapsed {
-        info!(
+        info_throttled!(
           "DataFrag {:?} from {:?} lifespan exceeded. duration={:?} elapsed={:?}",
-          seq_num, writer_guid, lifespan.duration, elapsed
+          seq_num,
+          writer_guid,
+          lifespan.duration,
+          elapsed
         );
         return;
       }
@@ -612,26 +1190,95 @@ impl Reader {
     {
       write_options_b = write_options_b.related_sample_identity(related_sample_identity);
     }
+    if let Some(key_hash) = datafrag
+      .inline_qos
+      .as_ref()
+      .and_then(|inline_qos_parameters| {
+        InlineQos::key_hash(inline_qos_parameters).unwrap_or_else(|e| {
+          error!("Deserializing key_hash: {:?}", e);
+          None
+        })
+      })
+    {
+      write_options_b = write_options_b.key_hash(key_hash);
+    }
+    if mr_state.record_reception_context {
+      write_options_b = write_options_b.reception_context(mr_state.reception_context());
+    }
+    let coherent_set = datafrag
+      .inline_qos
+      .as_ref()
+      .and_then(|inline_qos_parameters| {
+        InlineQos::coherent_set(inline_qos_parameters, representation_identifier).unwrap_or_else(
+          |e| {
+            error!("Deserializing coherent_set: {:?}", e);
+            None
+          },
+        )
+      })
+      .filter(|coherent_set| *coherent_set != SequenceNumber::UNKNOWN);
+
+    // The announced sample size decides the size of the reassembly buffer, so
+    // check it before anything is allocated.
+    if usize::try_from(datafrag.data_size)
+      .map_or(true, |size| size > self.max_fragmented_sample_size)
+    {
+      warn_throttled!(
+        "Dropping DATAFRAG {:?} from writer {:?}: announced sample size {} exceeds limit {}. \
+         topic={:?}",
+        seq_num,
+        writer_guid,
+        datafrag.data_size,
+        self.max_fragmented_sample_size,
+        self.topic_name
+      );
+      // Do not request the sample again, as it would be rejected again.
+      if let Some(writer_proxy) = self.matched_writers.get_mut(&writer_guid) {
+        writer_proxy.set_irrelevant_change(seq_num);
+      }
+      return;
+    }
 
     // Feed to fragment assembler ...
     let writer_seq_num = datafrag.writer_sn; // for borrow checker
+    let ambiguous_status_info = self.ambiguous_status_info();
     let completed_dds_data = self
       .fragment_assembler_mutable(writer_guid, datafrag.fragment_size)
-      .new_datafrag(datafrag, datafrag_flags);
+      .new_datafrag(datafrag, datafrag_flags)
+      .map(|dds_data| match dds_data {
+        // A fragmented key announces a state change, like a key in DATA.
+        DDSData::DisposeByKey { key, .. } => Self::deduce_change_kind(
+          &datafrag.inline_qos,
+          false,
+          representation_identifier,
+          ambiguous_status_info,
+        )
+        .map(|change_kind| DDSData::new_disposed_by_key(change_kind, key)),
+        other => Some(other),
+      });
 
     // ... and continue processing, if data was completed.
-    if let Some(dds_data) = completed_dds_data {
+    if let Some(None) = completed_dds_data {
+      self.ignore_change(writer_guid, writer_seq_num);
+    } else if let Some(Some(dds_data)) = completed_dds_data {
       // Source timestamp (if any) will be the timestamp of the last fragment (that
       // completes the sample).
+      let write_options = write_options_b.build();
       self.process_received_data(
         dds_data,
         receive_timestamp,
-        write_options_b.build(),
+        match coherent_set {
+          Some(coherent_set) => write_options.with_coherent_set(coherent_set),
+          None => write_options,
+        },
         writer_guid,
         writer_seq_num,
+        mr_state.quirks,
       );
     } else {
+      self.note_fragment_gap(writer_guid, writer_seq_num);
       self.garbage_collect_fragments();
+      self.schedule_fragment_garbage_collect();
     }
   }
 
@@ -643,7 +1290,7 @@ impl Reader {
     self
       .fragment_assemblers
       .entry(writer_guid)
-      .or_insert_with(|| FragmentAssembler::new(frag_size))
+      .or_insert_with(|| FragmentAssembler::new(frag_size, self.max_fragmented_sample_size))
   }
 
   fn garbage_collect_fragments(&mut self) {
@@ -651,22 +1298,60 @@ impl Reader {
     // fragment assemblers and discard those assembly buffers whose
     // creation / modification timestamps look like it is no longer receiving
     // data and can therefore be discarded.
-    let now = Timestamp::now();
-    if now - self.last_fragment_garbage_collect > MIN_FRAGMENT_GC_INTERVAL {
-      self.last_fragment_garbage_collect = now;
+    if Timestamp::now() - self.last_fragment_garbage_collect
+      > Duration::from(self.fragment_assembly.gc_interval)
+    {
+      self.garbage_collect_fragments_now();
+    } else {
+      trace!("Not yet AssemblyBuffer GC time.");
+    }
+  }
 
-      let expire_before = now - FRAGMENT_ASSEMBLY_TIMEOUT;
+  fn garbage_collect_fragments_now(&mut self) {
+    let now = Timestamp::now();
+    self.last_fragment_garbage_collect = now;
+    let expire_before = now - Duration::from(self.fragment_assembly.timeout);
+
+    let mut failed = Vec::new();
+    for (writer, fa) in &mut self.fragment_assemblers {
+      debug!("AssemblyBuffer GC writer {writer:?}");
+      let dropped = fa.garbage_collect_before(expire_before);
+      self.statistics.fragment_assemblies_timed_out(dropped.len());
+      failed.extend(
+        dropped
+          .into_iter()
+          .map(|(seq, missing_count)| (*writer, seq, missing_count)),
+      );
+    }
+    for (writer, seq, missing_count) in failed {
+      self.nackfrag_gap_deadlines.remove(&(writer, seq));
+      self.send_status_change(DataReaderStatus::SampleReassemblyFailed {
+        writer,
+        seq,
+        missing_count,
+      });
+    }
+  }
 
-      self
+  // Partially received samples are collected also when no more fragments
+  // arrive, which would otherwise be the only trigger.
+  fn schedule_fragment_garbage_collect(&mut self) {
+    if self.fragment_gc_scheduled
+      || self
         .fragment_assemblers
-        .iter_mut()
-        .for_each(|(writer, fa)| {
-          debug!("AssemblyBuffer GC writer {writer:?}");
-          fa.garbage_collect_before(expire_before);
-        });
-    } else {
-      trace!("Not yet AssemblyBuffer GC time.");
+        .values()
+        .all(FragmentAssembler::is_empty)
+    {
+      return;
     }
+    self.timed_event_timer.borrow_mut().set_timeout(
+      self.fragment_assembly.gc_interval,
+      DpTimerEvent::Reader {
+        entity_id: self.my_guid.entity_id,
+        event: TimedEvent::FragmentGarbageCollect,
+      },
+    );
+    self.fragment_gc_scheduled = true;
   }
 
   fn missing_frags_for(
@@ -680,6 +1365,23 @@ impl Reader {
     )
   }
 
+  // The missing fragments of a partially received sample that are below the
+  // highest received fragment, limited to what can be sent in a NACK_FRAG.
+  // Fragments above it may just not have been sent yet.
+  fn fragment_gap(&self, writer_guid: GUID, seq: SequenceNumber) -> Option<FragmentNumberSet> {
+    let fa = self.fragment_assemblers.get(&writer_guid)?;
+    let highest = fa.highest_received_frag(seq)?;
+    let mut gap = fa
+      .missing_frags_for(seq)
+      .take_while(|f| *f < highest)
+      .peekable();
+    let first = *gap.peek()?;
+    let gap_set = gap
+      .take_while(|f| *f < first + FragmentNumber::new(256))
+      .collect();
+    Some(FragmentNumberSet::from_base_and_set(first, &gap_set))
+  }
+
   fn is_frag_partially_received(&self, writer_guid: GUID, seq: SequenceNumber) -> bool {
     self
       .fragment_assemblers
@@ -696,6 +1398,7 @@ impl Reader {
     write_options: WriteOptions,
     writer_guid: GUID,
     writer_sn: SequenceNumber,
+    quirks: ActiveQuirks,
   ) {
     trace!(
       "handle_data_msg from {:?} seq={:?} topic={:?} reliability={:?} stateless={:?}",
@@ -705,28 +1408,47 @@ impl Reader {
       self.reliability,
       self.like_stateless,
     );
+    if self.is_paused() {
+      // The sample is not recorded as received, so a Reliable Writer repairs
+      // it after the Reader is resumed.
+      trace!("handle_data_msg: Reader is paused. Dropping seq={writer_sn:?}");
+      self.writer_liveliness_asserted(writer_guid);
+      return;
+    }
     if !self.like_stateless {
       let my_entity_id = self.my_guid.entity_id; // to please borrow checker
-      if let Some(writer_proxy) = self.matched_writer_mut(writer_guid) {
-        if writer_proxy.should_ignore_change(writer_sn) {
-          // change already present
-          trace!("handle_data_msg already have this seq={writer_sn:?}");
-          if my_entity_id == EntityId::SPDP_BUILTIN_PARTICIPANT_READER {
-            debug!("Accepting duplicate message to participant reader.");
-            // This is an attempted workaround to eProsima FastRTPS not
-            // incrementing sequence numbers. (eProsima shapes demo 2.1.0 from
-            // 2021)
-          } else {
-            return;
-          }
+      if self
+        .matched_writer(writer_guid)
+        .is_some_and(|wp| wp.should_ignore_change(writer_sn))
+      {
+        // change already present
+        trace!("handle_data_msg already have this seq={writer_sn:?}");
+        if self.accepts_duplicate(writer_guid, writer_sn, quirks) {
+          let sample = ReceivedSample {
+            dds_data,
+            receive_timestamp,
+            write_options,
+          };
+          self.deliver_duplicate(writer_guid, writer_sn, sample);
         }
+        return;
+      }
+      if let Some(writer_proxy) = self.matched_writer_mut(writer_guid) {
         // Add the change and get the instant
-        writer_proxy.received_changes_add(writer_sn, receive_timestamp);
+        writer_proxy.received_changes_add(
+          writer_sn,
+          receive_timestamp,
+          write_options.source_timestamp(),
+        );
+        // Writing data asserts liveliness of any kind of Writer
+        self.writer_liveliness_asserted(writer_guid);
       } else {
         // no writer proxy found
-        debug!(
+        debug_throttled!(
           "handle_data_msg in stateful Reader {:?} has no writer proxy for {:?} topic={:?}",
-          my_entity_id, writer_guid, self.topic_name,
+          my_entity_id,
+          writer_guid,
+          self.topic_name,
         );
         // This is normal if the DATA was broadcast, but it was from another topic.
         // We just ignore the data in such a case
@@ -739,27 +1461,468 @@ impl Reader {
       // stateless reader: nothing to do before making cache change
     }
 
-    self.make_cache_change(
+    let sample = ReceivedSample {
       dds_data,
       receive_timestamp,
       write_options,
-      writer_guid,
-      writer_sn,
-    );
+    };
+    if !self.like_stateless && self.matched_writers.contains_key(&writer_guid) {
+      if let Some(coherent_set) = sample.write_options.coherent_set() {
+        // Held until the set is finished
+        self
+          .coherent_sets
+          .entry(writer_guid)
+          .or_default()
+          .insert(coherent_set, writer_sn, sample);
+        self.deliver_coherent_sets(writer_guid);
+        self.check_stream_synced(writer_guid);
+        return;
+      }
+      if let Some(sets) = self.coherent_sets.get_mut(&writer_guid) {
+        sets.note_non_member(writer_sn);
+        self.deliver_coherent_sets(writer_guid);
+      }
+    }
+    match self.best_effort_reordering {
+      Some(policy)
+        if self.reliability == policy::Reliability::BestEffort
+          && !self.like_stateless
+          && self.matched_writers.contains_key(&writer_guid) =>
+      {
+        let delivered = self
+          .reorder_buffers
+          .entry(writer_guid)
+          .or_insert_with(|| ReorderBuffer::new(policy))
+          .insert(writer_sn, sample, Instant::now());
+        self.deliver_reordered(writer_guid, delivered);
+        self.schedule_reorder_flush();
+      }
+      _ => {
+        self.deliver_received_sample(writer_guid, writer_sn, sample);
+        self.notify_cache_change();
+      }
+    }
+    if !self.like_stateless {
+      self.check_stream_synced(writer_guid);
+    }
+  }
+
+  // A coherent set end marker from `writer_guid` was received. It is not a
+  // sample, only the end of the sets before it.
+  fn process_coherent_set_end(
+    &mut self,
+    writer_guid: GUID,
+    writer_sn: SequenceNumber,
+    receive_timestamp: Timestamp,
+  ) {
+    if self.is_paused() || self.like_stateless {
+      return;
+    }
+    let Some(writer_proxy) = self.matched_writer_mut(writer_guid) else {
+      return;
+    };
+    if writer_proxy.should_ignore_change(writer_sn) {
+      return;
+    }
+    writer_proxy.received_changes_add(writer_sn, receive_timestamp, None);
+    self.writer_liveliness_asserted(writer_guid);
+    if let Some(sets) = self.coherent_sets.get_mut(&writer_guid) {
+      sets.note_non_member(writer_sn);
+    }
+    self.deliver_coherent_sets(writer_guid);
+    self.check_stream_synced(writer_guid);
+  }
+
+  // Deliver the finished coherent sets of `writer_guid`, each at once.
+  fn deliver_coherent_sets(&mut self, writer_guid: GUID) {
+    let Some(received_before) = self
+      .matched_writer(writer_guid)
+      .map(RtpsWriterProxy::all_ackable_before)
+    else {
+      return;
+    };
+    let may_wait = self.reliability != policy::Reliability::BestEffort;
+    let Some(sets) = self.coherent_sets.get_mut(&writer_guid) else {
+      return;
+    };
+    let (complete, discarded) = sets.take_finished(received_before, may_wait);
+    if sets.is_empty() {
+      self.coherent_sets.remove(&writer_guid);
+    }
+    if discarded > 0 {
+      info!(
+        "Discarded incomplete coherent set of {} samples from {:?} topic={:?}",
+        discarded, writer_guid, self.topic_name
+      );
+    }
+    let delivered = !complete.is_empty();
+    for set in complete {
+      self.deliver_received_samples(writer_guid, set);
+    }
+    // A finished set does not hold back the Reliable stream anymore.
+    let reliably_received_before = self.reliably_received_before(writer_guid, received_before);
+    let marker_moved = self
+      .acquire_the_topic_cache_guard()
+      .mark_reliably_received_before(writer_guid, reliably_received_before);
+    if delivered || marker_moved {
+      self.notify_cache_change();
+    }
+  }
+
+  // Should a DATA with a SequenceNumber already received from `writer_guid` be
+  // delivered again? See DataReader::set_duplicate_acceptance.
+  fn accepts_duplicate(
+    &self,
+    writer_guid: GUID,
+    writer_sn: SequenceNumber,
+    quirks: ActiveQuirks,
+  ) -> bool {
+    if self.my_guid.entity_id == EntityId::SPDP_BUILTIN_PARTICIPANT_READER
+      && quirks.duplicate_spdp_sequence_numbers
+    {
+      // Workaround for peers that do not increment sequence numbers of
+      // participant data. See `VendorQuirks`.
+      debug!("Accepting duplicate message to participant reader.");
+      return true;
+    }
+    match self.duplicate_acceptance.lock() {
+      Ok(acceptance) => acceptance.accepts(writer_guid, writer_sn),
+      Err(e) => {
+        error!(
+          "Cannot read duplicate acceptance of {:?}: {e:?}",
+          self.my_guid
+        );
+        false
+      }
+    }
+  }
+
+  // Deliver a sample whose SequenceNumber was already received, as a new
+  // sample. It is not recorded as received in the writer proxy, so the
+  // acknowledgements and the reliable reception mark stay where the original
+  // left them. Coherent sets and reordering are about new SequenceNumbers, so
+  // they are bypassed.
+  fn deliver_duplicate(
+    &mut self,
+    writer_guid: GUID,
+    writer_sn: SequenceNumber,
+    sample: ReceivedSample,
+  ) {
+    self.writer_liveliness_asserted(writer_guid);
+    let Some(sample) = self.filter_received_sample(writer_guid, writer_sn, sample) else {
+      return;
+    };
+    let cache_change = CacheChange::new(
+      writer_guid,
+      writer_sn,
+      sample.write_options,
+      sample.dds_data,
+    );
+    let limit_hit = self
+      .acquire_the_topic_cache_guard()
+      .add_repeated_change(&sample.receive_timestamp, cache_change);
+    self.report_limit_hits(limit_hit);
+    self.notify_cache_change();
+  }
+
+  // A DataReader may read the Reliable stream of `writer_guid` up to
+  // `all_ackable_before`, but not into an unfinished coherent set.
+  fn reliably_received_before(
+    &self,
+    writer_guid: GUID,
+    all_ackable_before: SequenceNumber,
+  ) -> SequenceNumber {
+    self
+      .coherent_sets
+      .get(&writer_guid)
+      .and_then(CoherentSets::first_pending)
+      .map_or(all_ackable_before, |first_pending| {
+        min(first_pending, all_ackable_before)
+      })
+  }
+
+  // Report, if a Reliable stream from `writer_guid` has just caught up with
+  // what the Writer has announced.
+  fn check_stream_synced(&mut self, writer_guid: GUID) {
+    if self.reliability == policy::Reliability::BestEffort {
+      return;
+    }
+    let synced = self
+      .matched_writer_mut(writer_guid)
+      .is_some_and(RtpsWriterProxy::update_synced);
+    if synced {
+      self.send_status_change(DataReaderStatus::ReliableStreamSynced {
+        writer: writer_guid,
+      });
+    }
+  }
+
+  fn deliver_received_sample(
+    &mut self,
+    writer_guid: GUID,
+    writer_sn: SequenceNumber,
+    sample: ReceivedSample,
+  ) {
+    if let Some(sample) = self.filter_received_sample(writer_guid, writer_sn, sample) {
+      self.make_cache_changes(writer_guid, [(writer_sn, sample)]);
+    }
+  }
+
+  // Deliver samples from `writer_guid` to the topic cache in one go, so that a
+  // DataReader sees either all or none of them.
+  fn deliver_received_samples(
+    &mut self,
+    writer_guid: GUID,
+    samples: Vec<(SequenceNumber, ReceivedSample)>,
+  ) {
+    let accepted: Vec<_> = samples
+      .into_iter()
+      .filter_map(|(writer_sn, sample)| {
+        self
+          .filter_received_sample(writer_guid, writer_sn, sample)
+          .map(|sample| (writer_sn, sample))
+      })
+      .collect();
+    self.make_cache_changes(writer_guid, accepted);
+  }
+
+  // Apply the filters of this Reader to a received sample. Returns the sample,
+  // if it passes.
+  fn filter_received_sample(
+    &mut self,
+    writer_guid: GUID,
+    writer_sn: SequenceNumber,
+    mut sample: ReceivedSample,
+  ) -> Option<ReceivedSample> {
+    if !self.is_from_instance_owner(writer_guid, &sample) {
+      trace!(
+        "Exclusive ownership dropped {:?} from {:?} topic={:?}",
+        writer_sn,
+        writer_guid,
+        self.topic_name
+      );
+      return None;
+    }
+    if self.time_based_filter_drops(&sample) {
+      trace!(
+        "TimeBasedFilter dropped {:?} from {:?} topic={:?}",
+        writer_sn,
+        writer_guid,
+        self.topic_name
+      );
+      return None;
+    }
+    if self.is_content_duplicate(&sample) {
+      sample.write_options = sample.write_options.with_content_duplicate();
+    }
 
     // Add to own track-keeping data structure
-    #[cfg(test)]
-    self.seqnum_instant_map.insert(writer_sn, receive_timestamp);
+    #[cfg(any(test, feature = "test-util"))]
+    self
+      .seqnum_instant_map
+      .insert(writer_sn, sample.receive_timestamp);
+    Some(sample)
+  }
+
+  // The instance of `sample`, if it is known without deserializing the key.
+  // The single instance of a NO_KEY topic has KeyHash::zero(). A sample of a
+  // WITH_KEY topic is known only if it has a key hash.
+  fn instance_of(&self, sample: &ReceivedSample) -> Option<KeyHash> {
+    match (&sample.dds_data, sample.write_options.key_hash()) {
+      (DDSData::DisposeByKeyHash { key_hash, .. }, _) => Some(*key_hash),
+      (_, Some(key_hash)) => Some(key_hash),
+      _ if !self.my_guid.entity_id.entity_kind.is_with_key() => Some(KeyHash::zero()),
+      _ => None,
+    }
+  }
+
+  // Exclusive Ownership QoS: Is `sample` from the Writer that owns its
+  // instance? The owner is the Writer with the highest strength, of those that
+  // write the instance. The owner loses the instance if it is unmatched, loses
+  // its liveliness, misses the requested deadline for the instance, or
+  // unregisters the instance. Then the next sample from any Writer takes the
+  // ownership. Ties in strength are decided by the smaller GUID. See DDS Spec
+  // v1.4 Section 2.2.3.9.2 EXCLUSIVE kind.
+  //
+  // A sample of an unknown instance is always accepted.
+  fn is_from_instance_owner(&mut self, writer_guid: GUID, sample: &ReceivedSample) -> bool {
+    if !matches!(
+      self.qos_policy.ownership,
+      Some(policy::Ownership::Exclusive { .. })
+    ) {
+      return true;
+    }
+    let Some(instance) = self.instance_of(sample) else {
+      return true;
+    };
+    let Some(writer) = self.matched_writers.get(&writer_guid) else {
+      return true; // not arbitrated, e.g. built-in Writers
+    };
+    let strength = writer.ownership_strength;
+
+    let takes_over = match self.instance_owners.get(&instance) {
+      None => true,
+      Some(owner) if owner.writer == writer_guid => true,
+      Some(owner) => match self.matched_writers.get(&owner.writer) {
+        Some(owner_proxy) if owner_proxy.is_alive => {
+          let deadline_missed = self.qos_policy.deadline.is_some_and(|deadline| {
+            sample.receive_timestamp.duration_since(owner.last_sample) > deadline.0
+          });
+          deadline_missed
+            || (strength, std::cmp::Reverse(writer_guid))
+              > (
+                owner_proxy.ownership_strength,
+                std::cmp::Reverse(owner.writer),
+              )
+        }
+        _ => true,
+      },
+    };
+    if !takes_over {
+      return false;
+    }
+    let unregisters = match &sample.dds_data {
+      DDSData::Data { .. } | DDSData::CoherentSetEnd => false,
+      DDSData::DisposeByKey { change_kind, .. } | DDSData::DisposeByKeyHash { change_kind, .. } => {
+        matches!(
+          change_kind,
+          ChangeKind::NotAliveUnregistered | ChangeKind::NotAliveDisposedUnregistered
+        )
+      }
+    };
+    if unregisters {
+      self.instance_owners.remove(&instance);
+    } else {
+      self.instance_owners.insert(
+        instance,
+        InstanceOwner {
+          writer: writer_guid,
+          last_sample: sample.receive_timestamp,
+        },
+      );
+    }
+    true
+  }
+
+  // TimeBasedFilter QoS: Should `sample` be dropped, because it arrived sooner
+  // than minimum_separation after the last delivered sample of its instance?
+  // Instance state changes are never dropped, and they start the instance
+  // anew. A sample of an unknown instance is never dropped.
+  fn time_based_filter_drops(&mut self, sample: &ReceivedSample) -> bool {
+    let minimum_separation = match self.qos_policy.time_based_filter {
+      Some(policy::TimeBasedFilter { minimum_separation })
+        if minimum_separation > Duration::ZERO =>
+      {
+        minimum_separation
+      }
+      _ => return false,
+    };
+    let Some(instance) = self.instance_of(sample) else {
+      return false;
+    };
+    if !matches!(sample.dds_data, DDSData::Data { .. }) {
+      self.time_based_filter_last_delivered.remove(&instance);
+      return false;
+    }
+    match self.time_based_filter_last_delivered.get(&instance) {
+      Some(last) if sample.receive_timestamp.duration_since(*last) < minimum_separation => true,
+      _ => {
+        self
+          .time_based_filter_last_delivered
+          .insert(instance, sample.receive_timestamp);
+        false
+      }
+    }
+  }
+
+  // Is the payload of `sample` equal to that of the previous delivered sample
+  // of its instance? Checked only if the DataReader has asked for it. The
+  // payloads are compared by their 64-bit hash. Instance state changes are
+  // never duplicates, and they start the instance anew. A sample of an
+  // unknown instance is never a duplicate.
+  fn is_content_duplicate(&mut self, sample: &ReceivedSample) -> bool {
+    if !self
+      .detect_content_duplicates
+      .load(atomic::Ordering::Relaxed)
+    {
+      if !self.last_content_hash.is_empty() {
+        self.last_content_hash.clear();
+      }
+      return false;
+    }
+    let Some(instance) = self.instance_of(sample) else {
+      return false;
+    };
+    let DDSData::Data { serialized_payload } = &sample.dds_data else {
+      self.last_content_hash.remove(&instance);
+      return false;
+    };
+    let mut hasher = DefaultHasher::new();
+    serialized_payload
+      .representation_identifier
+      .hash(&mut hasher);
+    serialized_payload.value.hash(&mut hasher);
+    let hash = hasher.finish();
+    self.last_content_hash.insert(instance, hash) == Some(hash)
+  }
 
+  // Deliver samples released by the reorder buffer of `writer_guid`.
+  fn deliver_reordered(
+    &mut self,
+    writer_guid: GUID,
+    delivered: Vec<(SequenceNumber, ReceivedSample)>,
+  ) {
+    if delivered.is_empty() {
+      return;
+    }
+    for (writer_sn, sample) in delivered {
+      self.deliver_received_sample(writer_guid, writer_sn, sample);
+    }
     self.notify_cache_change();
   }
 
+  // Arm the timer to fire when the first held sample has waited long enough.
+  // Only one flush is kept pending at a time.
+  fn schedule_reorder_flush(&mut self) {
+    if self.reorder_flush_scheduled {
+      return;
+    }
+    let earliest_deadline = self
+      .reorder_buffers
+      .values()
+      .filter_map(ReorderBuffer::flush_deadline)
+      .min();
+    if let Some(deadline) = earliest_deadline {
+      self.timed_event_timer.borrow_mut().set_timeout(
+        deadline.saturating_duration_since(Instant::now()),
+        DpTimerEvent::Reader {
+          entity_id: self.my_guid.entity_id,
+          event: TimedEvent::ReorderFlush,
+        },
+      );
+      self.reorder_flush_scheduled = true;
+    }
+  }
+
+  fn handle_reorder_flush(&mut self) {
+    let now = Instant::now();
+    let flushed: Vec<(GUID, Vec<(SequenceNumber, ReceivedSample)>)> = self
+      .reorder_buffers
+      .iter_mut()
+      .map(|(writer_guid, buffer)| (*writer_guid, buffer.flush_expired(now)))
+      .collect();
+    for (writer_guid, delivered) in flushed {
+      self.deliver_reordered(writer_guid, delivered);
+    }
+  }
+
   fn data_to_dds_data(
     &self,
     data: Data,
     data_flags: BitFlags<DATA_Flags>,
-  ) -> Result<DDSData, String> {
+  ) -> Result<Option<DDSData>, String> {
     let representation_identifier = DATA_Flags::cdr_representation_identifier(data_flags);
+    let ambiguous_status_info = self.ambiguous_status_info();
 
     match (
       data.serialized_payload,
@@ -768,17 +1931,24 @@ impl Reader {
     ) {
       (Some(serialized_payload), true, false) => {
         // data
-        Ok(DDSData::new(
+        Ok(Some(DDSData::new(
           SerializedPayload::from_bytes(&serialized_payload).map_err(|e| format!("{e:?}"))?,
-        ))
+        )))
       }
 
       (Some(serialized_payload), false, true) => {
         // key
-        Ok(DDSData::new_disposed_by_key(
-          Self::deduce_change_kind(&data.inline_qos, false, representation_identifier),
-          SerializedPayload::from_bytes(&serialized_payload).map_err(|e| format!("{e:?}"))?,
-        ))
+        let key =
+          SerializedPayload::from_bytes(&serialized_payload).map_err(|e| format!("{e:?}"))?;
+        Ok(
+          Self::deduce_change_kind(
+            &data.inline_qos,
+            false,
+            representation_identifier,
+            ambiguous_status_info,
+          )
+          .map(|change_kind| DDSData::new_disposed_by_key(change_kind, key)),
+        )
       }
 
       (None, false, false) => {
@@ -793,20 +1963,26 @@ impl Reader {
         }) {
           Ok(h)
         } else {
-          info!("Received DATA that has no payload and no key_hash inline QoS - discarding");
+          info_throttled!(
+            "Received DATA that has no payload and no key_hash inline QoS - discarding"
+          );
           // Note: This case is normal when handling coherent sets.
           // The coherent set end marker is sent as DATA with no payload and not key, only
           // Inline QoS.
           Err("DATA with no contents".to_string())
         }?;
         // now, let's try to determine what is the dispose reason
-        let change_kind =
-          Self::deduce_change_kind(&data.inline_qos, false, representation_identifier);
+        let change_kind = Self::deduce_change_kind(
+          &data.inline_qos,
+          false,
+          representation_identifier,
+          ambiguous_status_info,
+        );
         info!(
           "status change by Inline QoS: topic={:?} change={:?}",
           self.topic_name, change_kind
         );
-        Ok(DDSData::new_disposed_by_key_hash(change_kind, key_hash))
+        Ok(change_kind.map(|change_kind| DDSData::new_disposed_by_key_hash(change_kind, key_hash)))
       }
 
       (Some(_), true, true) => {
@@ -853,6 +2029,60 @@ impl Reader {
     }
   }
 
+  // A matched Writer has restarted with the same GUID, and numbers its samples
+  // again from 1. Start tracking it from scratch, so that repair does not
+  // chase the SequenceNumbers of its earlier incarnation, and samples of the
+  // new one are not discarded as duplicates.
+  fn reset_restarted_writer(&mut self, writer_proxy: &mut RtpsWriterProxy) {
+    let writer_guid = writer_proxy.remote_writer_guid;
+    warn!(
+      "Writer {:?} restarted its SequenceNumbers. Was at {:?}. Resetting. topic={:?} reader={:?}",
+      writer_guid,
+      writer_proxy.all_ackable_before(),
+      self.topic_name,
+      self.my_guid
+    );
+    writer_proxy.reset_sequence_numbers();
+    self.fragment_assemblers.remove(&writer_guid);
+    self.coherent_sets.remove(&writer_guid);
+    self
+      .acquire_the_topic_cache_guard()
+      .writer_restarted(writer_guid);
+
+    if let Some(boundaries) = &self.history_boundaries {
+      // Everything a restarted Writer sends was written after we started.
+      boundaries
+        .lock()
+        .unwrap()
+        .insert(writer_guid, SequenceNumber::zero());
+    }
+
+    self.writer_restart_count += 1;
+    self.send_status_change(DataReaderStatus::WriterRestarted {
+      count: CountWithChange::new(self.writer_restart_count, 1),
+      writer: writer_guid,
+    });
+  }
+
+  // A DataReader created with ReadStart::FromNow skips what a Writer had
+  // written before it was matched: up to the last sample announced by its
+  // first HEARTBEAT, but not the samples received before that HEARTBEAT.
+  // The skipped ones are not requested from the Writer.
+  fn skip_history_of_writer(&self, writer_proxy: &mut RtpsWriterProxy, hb_last_sn: SequenceNumber) {
+    let Some(boundaries) = &self.history_boundaries else {
+      return;
+    };
+    let last_skipped = *boundaries
+      .lock()
+      .unwrap()
+      .entry(writer_proxy.remote_writer_guid)
+      .or_insert_with(|| match writer_proxy.first_received_sn() {
+        Some(first_received) => min(hb_last_sn, first_received - SequenceNumber::new(1)),
+        None => hb_last_sn,
+      });
+    writer_proxy.irrelevant_changes_up_to(last_skipped.plus_1());
+  }
+
   // Returns if responding with ACKNACK?
   // TODO: Return value seems to go unused in callers.
   // ...except in test cases, but not sure if this is strictly necessary to have.
@@ -860,16 +2090,22 @@ impl Reader {
     &mut self,
     heartbeat: &Heartbeat,
     final_flag_set: bool,
+    liveliness_flag_set: bool,
     mr_state: &MessageReceiverState,
   ) -> bool {
     let writer_guid =
       GUID::new_with_prefix_and_id(mr_state.source_guid_prefix, heartbeat.writer_id);
 
+    // Liveliness is tracked also by BestEffort Readers.
+    self.handle_writer_liveliness_heartbeat(writer_guid, liveliness_flag_set);
+
     if self.reliability == policy::Reliability::BestEffort || self.like_stateless {
-      debug!(
+      debug_throttled!(
         "HEARTBEAT from {:?}, but this Reader is BestEffort or stateless. Ignoring. topic={:?} \
          reader={:?}",
-        writer_guid, self.topic_name, self.my_guid
+        writer_guid,
+        self.topic_name,
+        self.my_guid
       );
       // BestEffort Reader reacts only to DATA and GAP
       // See RTPS Spec Section "8.4.11 RTPS StatelessReader Behavior":
@@ -880,9 +2116,11 @@ impl Reader {
     }
 
     if !self.matched_writers.contains_key(&writer_guid) {
-      debug!(
+      debug_throttled!(
         "HEARTBEAT from {:?}, but no writer proxy available. topic={:?} reader={:?}",
-        writer_guid, self.topic_name, self.my_guid
+        writer_guid,
+        self.topic_name,
+        self.my_guid
       );
       return false;
     }
@@ -898,27 +2136,44 @@ impl Reader {
       .with_mutable_writer_proxy(writer_guid, |this, writer_proxy| {
         // Note: This is worker closure. Use `this` instead of `self`.
 
+        // A restarted Writer also restarts its heartbeat count, so this must be
+        // checked before discarding already seen heartbeats.
+        if writer_proxy.heartbeat_shows_restart(heartbeat.last_sn, heartbeat.count) {
+          this.reset_restarted_writer(writer_proxy);
+        }
+
         if heartbeat.count <= writer_proxy.received_heartbeat_count {
           // This heartbeat was already seen an processed.
           return false;
         }
         writer_proxy.received_heartbeat_count = heartbeat.count;
 
+        // Before first_sn moves ack_base, which tells what has been received.
+        this.skip_history_of_writer(writer_proxy, heartbeat.last_sn);
         // remove changes until first_sn.
         writer_proxy.irrelevant_changes_up_to(heartbeat.first_sn);
 
+        let reliably_received_before =
+          this.reliably_received_before(writer_guid, writer_proxy.all_ackable_before());
         let marker_moved = this
           .acquire_the_topic_cache_guard()
-          .mark_reliably_received_before(writer_guid, writer_proxy.all_ackable_before());
+          .mark_reliably_received_before(writer_guid, reliably_received_before);
         if marker_moved {
           this.notify_cache_change();
         }
 
-        // let received_before = writer_proxy.all_ackable_before();
-        let reader_id = this.entity_id();
+        writer_proxy.heartbeat_announced(heartbeat.last_sn);
+        if writer_proxy.update_synced() {
+          this.send_status_change(DataReaderStatus::ReliableStreamSynced {
+            writer: writer_guid,
+          });
+        }
 
-        // See if ACKNACK is needed, and generate one.
-        let missing_seqnums = writer_proxy.missing_seqnums(heartbeat.first_sn, heartbeat.last_sn);
+        // A paused Reader does not acknowledge anything, so that the Writer keeps
+        // the samples it has sent meanwhile. See DataReader::pause.
+        if this.is_paused() {
+          return false;
+        }
 
         // Interpretation of final flag in RTPS spec
         // 8.4.2.3.1 Readers must respond eventually after receiving a HEARTBEAT with
@@ -928,158 +2183,300 @@ impl Reader {
         // respond with an ACKNACK Message. The ACKNACK Message may acknowledge
         // having received all the data samples or may indicate that some data
         // samples are missing. The response may be delayed to avoid message storms.
-
-        if !missing_seqnums.is_empty() || !final_flag_set {
-          let mut partially_received = Vec::new();
-          // report of what we have.
-          // We claim to have received all SNs before "base" and produce a set of missing
-          // sequence numbers that are >= base.
-          let reader_sn_state = match missing_seqnums.first() {
-            Some(&first_missing) => {
-              // Here we assume missing_seqnums are returned in order.
-              // Limit the set to maximum that can be sent in acknack submessage.
-
-              SequenceNumberSet::from_base_and_set(
-                first_missing,
-                &missing_seqnums
-                  .iter()
-                  .copied()
-                  .take_while(|sn| sn < &(first_missing + SequenceNumber::new(256)))
-                  .filter(|sn| {
-                    if this.is_frag_partially_received(writer_guid, *sn) {
-                      partially_received.push(*sn);
-                      false
-                    } else {
-                      true
-                    }
-                  })
-                  .collect(),
-              )
-            }
-
-            // Nothing missing. Report that we have all we have.
-            None => SequenceNumberSet::new_empty(writer_proxy.all_ackable_before()),
-          };
-
-          let response_ack_nack = AckNack {
-            reader_id,
-            writer_id: heartbeat.writer_id,
-            reader_sn_state,
-            count: writer_proxy.next_ack_nack_sequence_number(),
-          };
-
-          // Sanity check
-          //
-          // Wrong. This sanity check is invalid. The condition
-          // ack_base > heartbeat.last_sn + 1
-          // May be legitimately true, if there are some changes available, and a GAP
-          // after that. E.g. HEARTBEAT 1..8 and GAP 9..10. Then acknack_base == 11
-          // and 11 > 8 + 1.
-          //
-          //
-          // if response_ack_nack.reader_sn_state.base() > heartbeat.last_sn +
-          // SequenceNumber::new(1) {   error!(
-          //     "OOPS! AckNack sanity check tripped: HEARTBEAT = {:?} ACKNACK = {:?}
-          // missing_seqnums = {:?} all_ackable_before = {:?} writer={:?}",
-          //     &heartbeat, &response_ack_nack, missing_seqnums,
-          // writer_proxy.all_ackable_before(), writer_guid,   );
-          // }
-
-          // The acknack can be sent now or later. The rest of the RTPS message
-          // needs to be constructed. p. 48
-          let acknack_flags = BitFlags::<ACKNACK_Flags>::from_flag(ACKNACK_Flags::Endianness)
-            | BitFlags::<ACKNACK_Flags>::from_flag(ACKNACK_Flags::Final);
-
-          let nackfrag_flags = BitFlags::<NACKFRAG_Flags>::from_flag(NACKFRAG_Flags::Endianness);
-
-          // send NackFrags, if any
-          let mut nackfrags = Vec::new();
-          for sn in partially_received {
-            let count = writer_proxy.next_ack_nack_sequence_number();
-            let mut missing_frags = this.missing_frags_for(writer_guid, sn);
-            let first_missing = missing_frags.next();
-            if let Some(first) = first_missing {
-              let missing_frags_set = iter::once(first).chain(missing_frags).collect(); // "undo" the .next() above
-              let nf = NackFrag {
-                reader_id,
-                writer_id: writer_proxy.remote_writer_guid.entity_id,
-                writer_sn: sn,
-                fragment_number_state: FragmentNumberSet::from_base_and_set(
-                  first,
-                  &missing_frags_set,
-                ),
-                count,
-              };
-              nackfrags.push(nf);
-            } else {
-              error!("The dog ate my missing fragments.");
-              // Really, this should not happen, as we are above checking
-              // that this SN is really partially (and not fully) received.
-            }
-          }
-
-          // Decide where should we send a reply, i.e. ACKNACK
-          let reply_locators = match mr_state.unicast_reply_locator_list {
-            [] | [Locator::Invalid] => &writer_proxy.unicast_locator_list,
-            //TODO: What is writer_proxy has an empty list?
-            others => others,
-          };
-
-          if !nackfrags.is_empty() {
-            this.send_nackfrags_to(
-              nackfrag_flags,
-              nackfrags,
-              InfoDestination {
-                guid_prefix: mr_state.source_guid_prefix,
-              },
-              reply_locators,
-              writer_guid,
-            );
-          }
-
-          this.send_acknack_to(
-            acknack_flags,
-            response_ack_nack,
-            InfoDestination {
-              guid_prefix: mr_state.source_guid_prefix,
-            },
-            reply_locators,
+        //
+        // A HEARTBEAT with the Liveliness flag set is a manual liveliness assertion
+        // of the Writer (RTPS spec v2.5 Section "8.3.7.5 Heartbeat"). If there is
+        // nothing to repair, it is not answered, even without the final flag.
+        let response_required = !final_flag_set && !liveliness_flag_set;
+
+        // A HEARTBEAT within the suppression window of the previous response is
+        // not answered now, but at the end of the window, so that a required
+        // response is still sent eventually.
+        let window_end = writer_proxy
+          .last_heartbeat_response
+          .map(|responded| responded + this.heartbeat_suppression_duration)
+          .filter(|window_end| *window_end > Instant::now());
+        if let Some(window_end) = window_end {
+          trace!("Suppressing response to HEARTBEAT from {writer_guid:?} until {window_end:?}");
+          this.defer_heartbeat_response(
             writer_guid,
+            heartbeat,
+            response_required,
+            mr_state.unicast_reply_locator_list,
+            window_end,
           );
+          return false;
+        }
 
-          return true;
+        if !this.heartbeat_response_delay.is_zero() {
+          // Spread randomly over the latter half of the delay, so that the
+          // DataReaders of a Writer do not all answer at once.
+          let max_delay = this.heartbeat_response_delay;
+          let delay = max_delay / 2 + max_delay.mul_f64(rand::random::<f64>() / 2.0);
+          this.defer_heartbeat_response(
+            writer_guid,
+            heartbeat,
+            response_required,
+            mr_state.unicast_reply_locator_list,
+            Instant::now() + delay,
+          );
+          return false;
         }
 
-        false
+        this.send_heartbeat_response(
+          writer_proxy,
+          heartbeat.first_sn,
+          heartbeat.last_sn,
+          response_required,
+          mr_state.unicast_reply_locator_list,
+        )
       }) // worker fn
       .unwrap_or(false) // default false: no writer_proxy -> no acknack
   } // fn
 
-  pub fn handle_gap_msg(&mut self, gap: &Gap, mr_state: &MessageReceiverState) {
-    // ATM all things related to groups is ignored. TODO?
-
-    let writer_guid = GUID::new_with_prefix_and_id(mr_state.source_guid_prefix, gap.writer_id);
+  // Respond to a HEARTBEAT announcing samples first_sn..=last_sn with an
+  // ACKNACK, and NACK_FRAGs for partially received samples. Returns true if
+  // something was sent, which starts the heartbeat suppression window.
+  fn send_heartbeat_response(
+    &mut self,
+    writer_proxy: &mut RtpsWriterProxy,
+    first_sn: SequenceNumber,
+    last_sn: SequenceNumber,
+    response_required: bool,
+    unicast_reply_locators: &[Locator],
+  ) -> bool {
+    let writer_guid = writer_proxy.remote_writer_guid;
+    let reader_id = self.entity_id();
 
-    if self.like_stateless {
-      debug!(
-        "GAP from {:?}, but reader is stateless. Ignoring. topic={:?} reader={:?}",
-        writer_guid, self.topic_name, self.my_guid
-      );
-      return;
+    // See if ACKNACK is needed, and generate one.
+    let missing_seqnums = writer_proxy.missing_seqnums(first_sn, last_sn);
+    if missing_seqnums.is_empty() && !response_required {
+      return false;
     }
-    let all_ackable_before;
-    {
-      let writer_proxy = if let Some(wp) = self.matched_writer_mut(writer_guid) {
-        wp
-      } else {
-        info!(
-          "GAP from {:?}, but no writer proxy available. topic={:?} reader={:?}",
-          writer_guid, self.topic_name, self.my_guid
-        );
-        return;
-      };
 
-      // Check validity of the GAP message (Section 8.3.8.4.3)
+    let mut partially_received = Vec::new();
+    // report of what we have.
+    // We claim to have received all SNs before "base" and produce a set of missing
+    // sequence numbers that are >= base.
+    let reader_sn_state = match missing_seqnums.first() {
+      Some(&first_missing) => {
+        // Here we assume missing_seqnums are returned in order.
+        // Limit the set to maximum that can be sent in acknack submessage.
+
+        SequenceNumberSet::from_base_and_set(
+          first_missing,
+          &missing_seqnums
+            .iter()
+            .copied()
+            .take_while(|sn| sn < &(first_missing + SequenceNumber::new(256)))
+            .filter(|sn| {
+              if self.is_frag_partially_received(writer_guid, *sn) {
+                partially_received.push(*sn);
+                false
+              } else {
+                true
+              }
+            })
+            .collect(),
+        )
+      }
+
+      // Nothing missing. Report that we have all we have.
+      None => SequenceNumberSet::new_empty(writer_proxy.all_ackable_before()),
+    };
+
+    let response_ack_nack = AckNack {
+      reader_id,
+      writer_id: writer_guid.entity_id,
+      reader_sn_state,
+      count: writer_proxy.next_ack_nack_sequence_number(),
+    };
+
+    // Sanity check
+    //
+    // Wrong. This sanity check is invalid. The condition
+    // ack_base > heartbeat.last_sn + 1
+    // May be legitimately true, if there are some changes available, and a GAP
+    // after that. E.g. HEARTBEAT 1..8 and GAP 9..10. Then acknack_base == 11
+    // and 11 > 8 + 1.
+    //
+    //
+    // if response_ack_nack.reader_sn_state.base() > heartbeat.last_sn +
+    // SequenceNumber::new(1) {   error!(
+    //     "OOPS! AckNack sanity check tripped: HEARTBEAT = {:?} ACKNACK = {:?}
+    // missing_seqnums = {:?} all_ackable_before = {:?} writer={:?}",
+    //     &heartbeat, &response_ack_nack, missing_seqnums,
+    // writer_proxy.all_ackable_before(), writer_guid,   );
+    // }
+
+    // The acknack can be sent now or later. The rest of the RTPS message
+    // needs to be constructed. p. 48
+    let acknack_flags = BitFlags::<ACKNACK_Flags>::from_flag(ACKNACK_Flags::Endianness)
+      | BitFlags::<ACKNACK_Flags>::from_flag(ACKNACK_Flags::Final);
+
+    let nackfrag_flags = BitFlags::<NACKFRAG_Flags>::from_flag(NACKFRAG_Flags::Endianness);
+
+    // send NackFrags, if any
+    let mut nackfrags = Vec::new();
+    for sn in partially_received {
+      let count = writer_proxy.next_ack_nack_sequence_number();
+      let mut missing_frags = self.missing_frags_for(writer_guid, sn);
+      let first_missing = missing_frags.next();
+      if let Some(first) = first_missing {
+        let missing_frags_set = iter::once(first).chain(missing_frags).collect(); // "undo" the .next() above
+        let nf = NackFrag {
+          reader_id,
+          writer_id: writer_proxy.remote_writer_guid.entity_id,
+          writer_sn: sn,
+          fragment_number_state: FragmentNumberSet::from_base_and_set(first, &missing_frags_set),
+          count,
+        };
+        nackfrags.push(nf);
+      } else {
+        error!("The dog ate my missing fragments.");
+        // Really, this should not happen, as we are above checking
+        // that this SN is really partially (and not fully) received.
+      }
+    }
+
+    // Decide where should we send a reply, i.e. ACKNACK
+    let reply_locators = match unicast_reply_locators {
+      [] | [Locator::Invalid] => &writer_proxy.unicast_locator_list,
+      //TODO: What is writer_proxy has an empty list?
+      others => others,
+    };
+
+    if !nackfrags.is_empty() {
+      self.send_nackfrags_to(
+        nackfrag_flags,
+        nackfrags,
+        InfoDestination {
+          guid_prefix: writer_guid.prefix,
+        },
+        reply_locators,
+        writer_guid,
+      );
+    }
+
+    self.send_acknack_to(
+      acknack_flags,
+      response_ack_nack,
+      InfoDestination {
+        guid_prefix: writer_guid.prefix,
+      },
+      reply_locators,
+      writer_guid,
+    );
+    writer_proxy.last_heartbeat_response = Some(Instant::now());
+
+    true
+  }
+
+  // Respond to a HEARTBEAT at `due`. Later HEARTBEATs from the Writer replace
+  // the pending one, but do not postpone it.
+  fn defer_heartbeat_response(
+    &mut self,
+    writer_guid: GUID,
+    heartbeat: &Heartbeat,
+    response_required: bool,
+    unicast_reply_locators: &[Locator],
+    due: Instant,
+  ) {
+    let pending = self
+      .pending_heartbeat_responses
+      .entry(writer_guid)
+      .or_insert_with(|| PendingHeartbeatResponse {
+        first_sn: heartbeat.first_sn,
+        last_sn: heartbeat.last_sn,
+        response_required: false,
+        unicast_reply_locators: Vec::new(),
+        due,
+      });
+    pending.first_sn = heartbeat.first_sn;
+    pending.last_sn = heartbeat.last_sn;
+    pending.response_required |= response_required;
+    pending.unicast_reply_locators = unicast_reply_locators.to_vec();
+    self.schedule_heartbeat_response();
+  }
+
+  fn schedule_heartbeat_response(&mut self) {
+    if self.heartbeat_response_scheduled {
+      return;
+    }
+    if let Some(due) = self
+      .pending_heartbeat_responses
+      .values()
+      .map(|p| p.due)
+      .min()
+    {
+      self.timed_event_timer.borrow_mut().set_timeout(
+        due.saturating_duration_since(Instant::now()),
+        DpTimerEvent::Reader {
+          entity_id: self.my_guid.entity_id,
+          event: TimedEvent::HeartbeatResponse,
+        },
+      );
+      self.heartbeat_response_scheduled = true;
+    }
+  }
+
+  fn handle_heartbeat_responses(&mut self) {
+    let now = Instant::now();
+    let due_writers: Vec<GUID> = self
+      .pending_heartbeat_responses
+      .iter()
+      .filter(|(_, pending)| pending.due <= now)
+      .map(|(writer_guid, _)| *writer_guid)
+      .collect();
+    let due: Vec<(GUID, PendingHeartbeatResponse)> = due_writers
+      .into_iter()
+      .filter_map(|w| Some((w, self.pending_heartbeat_responses.remove(&w)?)))
+      .collect();
+    if self.is_paused() {
+      return;
+    }
+    for (writer_guid, pending) in due {
+      // The response reports what has been received by now, not at the time of
+      // the HEARTBEAT.
+      self.with_mutable_writer_proxy(writer_guid, |this, writer_proxy| {
+        this.send_heartbeat_response(
+          writer_proxy,
+          pending.first_sn,
+          pending.last_sn,
+          pending.response_required,
+          &pending.unicast_reply_locators,
+        )
+      });
+    }
+  }
+
+  pub fn handle_gap_msg(&mut self, gap: &Gap, mr_state: &MessageReceiverState) {
+    // ATM all things related to groups is ignored. TODO?
+
+    let writer_guid = GUID::new_with_prefix_and_id(mr_state.source_guid_prefix, gap.writer_id);
+
+    if self.like_stateless {
+      debug!(
+        "GAP from {:?}, but reader is stateless. Ignoring. topic={:?} reader={:?}",
+        writer_guid, self.topic_name, self.my_guid
+      );
+      return;
+    }
+    let all_ackable_before;
+    {
+      let writer_proxy = if let Some(wp) = self.matched_writer_mut(writer_guid) {
+        wp
+      } else {
+        info_throttled!(
+          "GAP from {:?}, but no writer proxy available. topic={:?} reader={:?}",
+          writer_guid,
+          self.topic_name,
+          self.my_guid
+        );
+        return;
+      };
+
+      // Check validity of the GAP message (Section 8.3.8.4.3)
       if gap.gap_start <= SequenceNumber::new(0) {
         debug!(
           "Invalid GAP from {:?}: gap_start={:?} is zero or negative. topic={:?} reader={:?}",
@@ -1116,10 +2513,17 @@ impl Reader {
       all_ackable_before = writer_proxy.all_ackable_before();
     }
 
+    // Members of a coherent set may have been declared irrelevant, so that the
+    // set can never be completed.
+    if self.coherent_sets.contains_key(&writer_guid) {
+      self.deliver_coherent_sets(writer_guid);
+    }
+
     // Get the topic cache and mark progress
+    let reliably_received_before = self.reliably_received_before(writer_guid, all_ackable_before);
     let marker_moved = self
       .acquire_the_topic_cache_guard()
-      .mark_reliably_received_before(writer_guid, all_ackable_before);
+      .mark_reliably_received_before(writer_guid, reliably_received_before);
 
     // Receiving a GAP could make a Reliable stream.
     // E.g. we had #2, but were missing #1. Now GAP says that #1 does not exist.
@@ -1130,6 +2534,8 @@ impl Reader {
     // able to move forward, i.e. hand over data to application, if
     // we now know that nothing is missng from the past.
 
+    self.check_stream_synced(writer_guid);
+
     // TODO: If receiving GAP actually moved the reliably received mark forward
     // in the Topic Cache, then we should generate a SAMPLE_LOST status event
     // from our Datareader (DDS Spec Section 2.2.4.1)
@@ -1139,25 +2545,204 @@ impl Reader {
     // as "lost" but "filtered".
   }
 
+  // A HEARTBEAT_FRAG announces the fragments of a sample that the Writer has
+  // available, while the sample is still being fragmented out. If we have
+  // received the sample partially, request the missing fragments with a
+  // NACK_FRAG. Otherwise the transfer would stall until the next HEARTBEAT,
+  // if the DATA_FRAG that would have completed the sample was lost. See RTPS
+  // spec v2.5 Section "8.3.7.6 HeartbeatFrag".
+  //
+  // A sample with no fragments received at all is repaired by the ordinary
+  // HEARTBEAT / ACKNACK exchange.
   pub fn handle_heartbeatfrag_msg(
     &mut self,
     heartbeatfrag: &HeartbeatFrag,
-    _mr_state: &MessageReceiverState,
+    mr_state: &MessageReceiverState,
   ) {
-    info!(
-      "HeartbeatFrag handling not implemented. topic={:?}   {:?}",
-      self.topic_name, heartbeatfrag
-    );
+    let writer_guid =
+      GUID::new_with_prefix_and_id(mr_state.source_guid_prefix, heartbeatfrag.writer_id);
+
+    if self.like_stateless || self.reliability == policy::Reliability::BestEffort {
+      debug!(
+        "HEARTBEAT_FRAG from {:?}, but reader is stateless or BestEffort. Ignoring. topic={:?} \
+         reader={:?}",
+        writer_guid, self.topic_name, self.my_guid
+      );
+      return;
+    }
+    if !self.matched_writers.contains_key(&writer_guid) {
+      debug_throttled!(
+        "HEARTBEAT_FRAG from {:?}, but no writer proxy available. topic={:?} reader={:?}",
+        writer_guid,
+        self.topic_name,
+        self.my_guid
+      );
+      return;
+    }
+    if self.is_paused() {
+      // Missing fragments are requested after the Reader is resumed.
+      return;
+    }
+
+    self.with_mutable_writer_proxy(writer_guid, |this, writer_proxy| {
+      if heartbeatfrag.count <= writer_proxy.received_heartbeatfrag_count {
+        // This HEARTBEAT_FRAG was already seen and processed.
+        return;
+      }
+      writer_proxy.received_heartbeatfrag_count = heartbeatfrag.count;
+
+      let writer_sn = heartbeatfrag.writer_sn;
+      if !this.is_frag_partially_received(writer_guid, writer_sn) {
+        return;
+      }
+      let mut missing_frags = this
+        .missing_frags_for(writer_guid, writer_sn)
+        .take_while(|f| *f <= heartbeatfrag.last_fragment_num)
+        .peekable();
+      let Some(&first) = missing_frags.peek() else {
+        return; // We have all the fragments the Writer has.
+      };
+      // Limit the set to maximum that can be sent in a NACK_FRAG.
+      let missing_frags_set = missing_frags
+        .take_while(|f| *f < first + FragmentNumber::new(256))
+        .collect();
+
+      let nackfrag = NackFrag {
+        reader_id: this.entity_id(),
+        writer_id: heartbeatfrag.writer_id,
+        writer_sn,
+        fragment_number_state: FragmentNumberSet::from_base_and_set(first, &missing_frags_set),
+        count: writer_proxy.next_ack_nack_sequence_number(),
+      };
+      let reply_locators = match mr_state.unicast_reply_locator_list {
+        [] | [Locator::Invalid] => &writer_proxy.unicast_locator_list,
+        others => others,
+      };
+      this.send_nackfrags_to(
+        BitFlags::<NACKFRAG_Flags>::from_flag(NACKFRAG_Flags::Endianness),
+        vec![nackfrag],
+        InfoDestination {
+          guid_prefix: mr_state.source_guid_prefix,
+        },
+        reply_locators,
+        writer_guid,
+      );
+    });
+  }
+
+  // Proactive NACK_FRAGs (participant-builder `proactive_nackfrag_delay`
+  // knob): when a DATA_FRAG leaves a gap in the fragments of a sample, the gap
+  // is requested after the delay, unless it has been filled by then. Each gap
+  // is requested once. Another request is made only if a later DATA_FRAG
+  // still leaves a gap, so repairs cannot storm the Writer.
+  fn note_fragment_gap(&mut self, writer_guid: GUID, writer_sn: SequenceNumber) {
+    let Some(delay) = self.proactive_nackfrag_delay else {
+      return;
+    };
+    if self.like_stateless
+      || self.reliability == policy::Reliability::BestEffort
+      || !self.matched_writers.contains_key(&writer_guid)
+      || self
+        .nackfrag_gap_deadlines
+        .contains_key(&(writer_guid, writer_sn))
+      || self.fragment_gap(writer_guid, writer_sn).is_none()
+    {
+      return;
+    }
+    self
+      .nackfrag_gap_deadlines
+      .insert((writer_guid, writer_sn), Instant::now() + delay);
+    self.schedule_proactive_nackfrag();
+  }
+
+  // A NACK_FRAG for the sample was just sent, e.g. in response to a
+  // HEARTBEAT. Give the Writer time to respond before requesting it again.
+  fn postpone_proactive_nackfrag(&mut self, writer_guid: GUID, writer_sn: SequenceNumber) {
+    if let (Some(delay), Some(deadline)) = (
+      self.proactive_nackfrag_delay,
+      self
+        .nackfrag_gap_deadlines
+        .get_mut(&(writer_guid, writer_sn)),
+    ) {
+      *deadline = Instant::now() + delay;
+    }
+  }
+
+  fn schedule_proactive_nackfrag(&mut self) {
+    if self.proactive_nackfrag_scheduled {
+      return;
+    }
+    if let Some(deadline) = self.nackfrag_gap_deadlines.values().min() {
+      self.timed_event_timer.borrow_mut().set_timeout(
+        deadline.saturating_duration_since(Instant::now()),
+        DpTimerEvent::Reader {
+          entity_id: self.my_guid.entity_id,
+          event: TimedEvent::ProactiveNackFrag,
+        },
+      );
+      self.proactive_nackfrag_scheduled = true;
+    }
+  }
+
+  fn handle_proactive_nackfrags(&mut self) {
+    let now = Instant::now();
+    let mut due: BTreeMap<GUID, Vec<SequenceNumber>> = BTreeMap::new();
+    self
+      .nackfrag_gap_deadlines
+      .retain(|(writer_guid, sn), deadline| {
+        if *deadline <= now {
+          due.entry(*writer_guid).or_default().push(*sn);
+        }
+        *deadline > now
+      });
+    if self.is_paused() {
+      // Missing fragments are requested after the Reader is resumed.
+      return;
+    }
+
+    for (writer_guid, sns) in due {
+      if !self.matched_writers.contains_key(&writer_guid) {
+        continue;
+      }
+      self.with_mutable_writer_proxy(writer_guid, |this, writer_proxy| {
+        let nackfrags: Vec<NackFrag> = sns
+          .into_iter()
+          .filter_map(|writer_sn| {
+            // The gap may have been filled meanwhile.
+            let fragment_number_state = this.fragment_gap(writer_guid, writer_sn)?;
+            Some(NackFrag {
+              reader_id: this.entity_id(),
+              writer_id: writer_guid.entity_id,
+              writer_sn,
+              fragment_number_state,
+              count: writer_proxy.next_ack_nack_sequence_number(),
+            })
+          })
+          .collect();
+        if !nackfrags.is_empty() {
+          this.send_nackfrags_to(
+            BitFlags::<NACKFRAG_Flags>::from_flag(NACKFRAG_Flags::Endianness),
+            nackfrags,
+            InfoDestination {
+              guid_prefix: writer_guid.prefix,
+            },
+            &writer_proxy.unicast_locator_list,
+            writer_guid,
+          );
+        }
+      });
+    }
   }
 
   // This is used to determine exact change kind in case we do not get a data
-  // payload in DATA submessage
+  // payload in DATA submessage. None means that the change is to be ignored.
   fn deduce_change_kind(
     inline_qos: &Option<ParameterList>,
     no_writers: bool,
     representation_identifier: RepresentationIdentifier,
-  ) -> ChangeKind {
-    match inline_qos.as_ref().and_then(|inline_qos_parameters| {
+    ambiguous_status_info: AmbiguousStatusInfo,
+  ) -> Option<ChangeKind> {
+    let status_info = inline_qos.as_ref().and_then(|inline_qos_parameters| {
       InlineQos::status_info(inline_qos_parameters, representation_identifier).map_or_else(
         |e| {
           error!("Deserializing status_info: {:?}", e);
@@ -1165,70 +2750,143 @@ impl Reader {
         },
         Some,
       )
-    }) {
-      Some(si) => si.change_kind(), // get from inline QoS
-      // TODO: What if si.change_kind() gives ALIVE ??
-      None => {
-        if no_writers {
-          ChangeKind::NotAliveUnregistered
-        } else {
-          ChangeKind::NotAliveDisposed
-        } // TODO: Is this reasonable default?
-      }
+    });
+    match status_info.map(|si| si.change_kind()) {
+      // get from inline QoS
+      Some(change_kind) if change_kind != ChangeKind::Alive => Some(change_kind),
+      // No StatusInfo, or it has neither the Disposed nor the Unregistered flag.
+      // A DATA without payload still announces a state change, but which one?
+      _ => match ambiguous_status_info {
+        AmbiguousStatusInfo::NotAlive if no_writers => Some(ChangeKind::NotAliveUnregistered),
+        AmbiguousStatusInfo::NotAlive => Some(ChangeKind::NotAliveDisposed),
+        AmbiguousStatusInfo::Ignore => {
+          info!("Ignoring state change with ambiguous StatusInfo {status_info:?}");
+          None
+        }
+      },
+    }
+  }
+
+  fn ambiguous_status_info(&self) -> AmbiguousStatusInfo {
+    if self
+      .ignore_ambiguous_status_info
+      .load(atomic::Ordering::Relaxed)
+    {
+      AmbiguousStatusInfo::Ignore
+    } else {
+      AmbiguousStatusInfo::NotAlive
     }
   }
 
-  // Convert DATA submessage into a CacheChange and update history cache
-  fn make_cache_change(
+  // A received change is not delivered. Do not request it again.
+  fn ignore_change(&mut self, writer_guid: GUID, writer_sn: SequenceNumber) {
+    if let Some(writer_proxy) = self.matched_writers.get_mut(&writer_guid) {
+      writer_proxy.set_irrelevant_change(writer_sn);
+    }
+  }
+
+  // Convert received samples into CacheChanges and update history cache
+  fn make_cache_changes(
     &mut self,
-    data: DDSData,
-    receive_timestamp: Timestamp,
-    write_options: WriteOptions,
     writer_guid: GUID,
-    writer_sn: SequenceNumber,
+    samples: impl IntoIterator<Item = (SequenceNumber, ReceivedSample)>,
   ) {
-    let cache_change = CacheChange::new(writer_guid, writer_sn, write_options, data);
+    // Mark seqnums as received if not behaving statelessly
+    let reliably_received_before = if self.like_stateless {
+      None
+    } else {
+      self
+        .matched_writer(writer_guid)
+        .map(|wp| self.reliably_received_before(writer_guid, wp.all_ackable_before()))
+    };
 
     // Get the topic cache
     let mut tc = self.acquire_the_topic_cache_guard();
 
-    tc.add_change(&receive_timestamp, cache_change);
-    // Mark seqnums as received if not behaving statelessly
-    if !self.like_stateless {
-      self.matched_writer(writer_guid).map(|wp| {
-        tc.mark_reliably_received_before(writer_guid, wp.all_ackable_before());
-        // Here we do not need to notify waiting DataReader, because
-        // the upper call level from here does it.
+    let mut limit_hits = Vec::new();
+    for (writer_sn, sample) in samples {
+      let cache_change = CacheChange::new(
+        writer_guid,
+        writer_sn,
+        sample.write_options,
+        sample.dds_data,
+      );
+      limit_hits.extend(tc.add_change(&sample.receive_timestamp, cache_change));
+    }
+    if let Some(reliably_received_before) = reliably_received_before {
+      tc.mark_reliably_received_before(writer_guid, reliably_received_before);
+      // Here we do not need to notify waiting DataReader, because
+      // the upper call level from here does it.
+    }
+    drop(tc);
+    self.report_limit_hits(limit_hits);
+  }
+
+  // Samples were dropped from the cache because of ResourceLimits, so they
+  // may never reach the DataReader. Do not lose them silently.
+  fn report_limit_hits(&mut self, limit_hits: impl IntoIterator<Item = SampleRejectedStatusKind>) {
+    for last_reason in limit_hits {
+      self.sample_rejected_count += 1;
+      self.statistics.sample_rejected();
+      self.send_status_change(DataReaderStatus::SampleRejected {
+        count: CountWithChange::new(self.sample_rejected_count, 1),
+        last_reason,
       });
     }
   }
 
+  // Our DataReader is gone, so nobody will ever read our samples or statuses.
+  // Ask the event loop to remove this Reader. This normally happens already
+  // when the DataReader is dropped, but if that message was lost, we would keep
+  // receiving data for nobody. The event loop cannot be called from here, so
+  // the removal goes through the timer.
+  fn schedule_disposal(&self) {
+    if self.disposal_scheduled.replace(true) {
+      return;
+    }
+    info!(
+      "DataReader of {:?} is gone. Disposing Reader.",
+      self.my_guid
+    );
+    self.timed_event_timer.borrow_mut().set_timeout(
+      StdDuration::ZERO,
+      DpTimerEvent::DisposeReader {
+        entity_id: self.my_guid.entity_id,
+      },
+    );
+  }
+
   // notifies DataReaders (or any listeners that history cache has changed for
   // this reader) likely use of mio channel
   pub fn notify_cache_change(&mut self) {
-    // async notify mechanism
-    self
-      .data_reader_waker
-      .lock()
-      .unwrap() // TODO: unwrap
-      .take() // Take to nullify the reference
-      .map(|w| w.wake_by_ref()); // If Some, call wake_by_ref
-
-    // mio-0.8 notify
-    self.poll_event_sender.send();
-
-    // mio-0.6 notify
-    match self.notification_sender.try_send(()) {
-      Ok(()) => (),
-      Err(mio_channel::TrySendError::Full(_)) => (),
-      // This is harmless. There is a notification in already.
-      Err(mio_channel::TrySendError::Disconnected(_)) => {
-        // If we get here, our DataReader has died. The Reader should now
-        // dispose itself. TODO: Implement Reader disposal.
-      }
-      Err(mio_channel::TrySendError::Io(_)) => {
-        // TODO: What does this mean? Can we ever get here?
+    self.data_readers.retain(|data_reader| {
+      // async notify mechanism
+      data_reader
+        .data_reader_waker
+        .lock()
+        .unwrap() // TODO: unwrap
+        .take() // Take to nullify the reference
+        .map(|w| w.wake_by_ref()); // If Some, call wake_by_ref
+
+      // mio-0.8 notify
+      data_reader.poll_event_sender.send();
+
+      // mio-0.6 notify
+      match data_reader.notification_sender.try_send(()) {
+        Ok(()) => true,
+        // This is harmless. There is a notification in already.
+        Err(mio_channel::TrySendError::Full(_)) => true,
+        // This DataReader has died.
+        Err(mio_channel::TrySendError::Disconnected(_)) => false,
+        Err(mio_channel::TrySendError::Io(_)) => {
+          // TODO: What does this mean? Can we ever get here?
+          true
+        }
       }
+    });
+    if self.data_readers.is_empty() {
+      // If we get here, all our DataReaders have died.
+      self.schedule_disposal();
     }
   }
 
@@ -1248,7 +2906,7 @@ impl Reader {
     };
     let _dummy = message; // consume it to avoid clippy warning
     self
-      .udp_sender
+      .transport
       .send_to_locator_list(&bytes, dst_locator_list);
   }
 
@@ -1269,7 +2927,7 @@ impl Reader {
           }
         };
         self
-          .udp_sender
+          .transport
           .send_to_locator_list(&bytes, dst_locator_list);
       }
       Err(e) => error!("Failed to send message to writers. Encoding failed: {e:?}"),
@@ -1340,17 +2998,21 @@ impl Reader {
 
     message.add_submessage(acknack.create_submessage(flags));
 
+    self.statistics.acknack_sent();
     self.encode_and_send(message, destination_guid, dst_locator_list);
   }
 
   fn send_nackfrags_to(
-    &self,
+    &mut self,
     flags: BitFlags<NACKFRAG_Flags>,
     nackfrags: Vec<NackFrag>,
     info_dst: InfoDestination,
     dst_locator_list: &[Locator],
     destination_guid: GUID,
   ) {
+    for nf in &nackfrags {
+      self.postpone_proactive_nackfrag(destination_guid, nf.writer_sn);
+    }
     let infodst_flags =
       BitFlags::<INFODESTINATION_Flags>::from_flag(INFODESTINATION_Flags::Endianness);
 
@@ -1363,6 +3025,7 @@ impl Reader {
 
     message.add_submessage(info_dst.create_submessage(infodst_flags));
 
+    self.statistics.nackfrags_sent(nackfrags.len());
     for nf in nackfrags {
       message.add_submessage(nf.create_submessage(flags));
     }
@@ -1449,6 +3112,10 @@ impl fmt::Debug for Reader {
       .field("topic_name", &self.topic_name)
       .field("my_guid", &self.my_guid)
       .field("heartbeat_response_delay", &self.heartbeat_response_delay)
+      .field(
+        "heartbeat_suppression_duration",
+        &self.heartbeat_suppression_duration,
+      )
       .field("received_heartbeat_count", &self.received_heartbeat_count)
       .finish()
   }
@@ -1456,15 +3123,71 @@ impl fmt::Debug for Reader {
 
 #[cfg(test)]
 mod tests {
-  use std::sync::RwLock;
+  use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::RwLock,
+  };
 
   use crate::{
-    dds::{qos::policy::Reliability, statusevents::sync_status_channel, typedesc::TypeDesc},
+    dds::{
+      qos::policy::Reliability, sampleinfo::ReceptionContext, statusevents::sync_status_channel,
+      typedesc::TypeDesc,
+    },
+    network::udp_sender::UDPSender,
     structure::{dds_cache::DDSCache, guid::EntityKind},
+    test_util::{CapturedSubmessage, ReaderHarness},
     QosPolicyBuilder,
   };
   use super::*;
 
+  #[test]
+  fn reader_with_wrong_topic_cache_is_not_created() {
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let qos_policy = QosPolicies::qos_none();
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      "other_topic".to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_ing = ReaderIngredients {
+      guid: GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED),
+      notification_sender,
+      status_sender,
+      topic_name: "test_name".to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy,
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    );
+    assert!(matches!(reader, Err(CreateError::Internal { .. })));
+  }
+
   #[test]
   fn reader_sends_notification_when_receiving_data() {
     // 1. Create a reader
@@ -1510,6 +3233,13 @@ mod tests {
       data_reader_command_receiver: reader_command_receiver,
       data_reader_waker,
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
     let mut reader = Reader::new(
@@ -1517,7 +3247,8 @@ mod tests {
       Rc::new(UDPSender::new(0).unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
+    )
+    .unwrap();
 
     // 2. Add info of a matched writer to the reader
     let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
@@ -1556,9 +3287,7 @@ mod tests {
   }
 
   #[test]
-  fn reader_sends_data_to_topic_cache() {
-    // 1. Create a reader
-    // Create the DDS cache and a topic
+  fn reader_ignores_data_addressed_to_another_reader() {
     let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
     let topic_name = "test_name";
     let qos_policy = QosPolicies::qos_none();
@@ -1569,8 +3298,98 @@ mod tests {
       &qos_policy,
     );
 
-    // Create mechanisms for notifications, statuses & commands
-    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (notification_sender, notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy,
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      mr_state.unicast_reply_locator_list.to_vec(),
+      mr_state.multicast_reply_locator_list.to_vec(),
+      &QosPolicies::qos_none(),
+    );
+    let data_flags = BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data);
+
+    // DATA for some other Reader is dropped
+    let other_reader_id = EntityId::new([0xaa, 0xbb, 0xcc], EntityKind::READER_NO_KEY_USER_DEFINED);
+    assert_ne!(other_reader_id, reader_guid.entity_id);
+    let misaddressed = Data {
+      reader_id: other_reader_id,
+      writer_id: writer_guid.entity_id,
+      writer_sn: SequenceNumber::new(1),
+      ..Data::default()
+    };
+    reader.handle_data_msg(misaddressed, data_flags, &mr_state);
+    assert!(notification_receiver.try_recv().is_err());
+    assert!(reader.seqnum_instant_map.is_empty());
+
+    // DATA with UNKNOWN reader_id is for all matched Readers
+    let broadcast = Data {
+      reader_id: EntityId::UNKNOWN,
+      writer_id: writer_guid.entity_id,
+      writer_sn: SequenceNumber::new(1),
+      ..Data::default()
+    };
+    reader.handle_data_msg(broadcast, data_flags, &mr_state);
+    assert!(notification_receiver.try_recv().is_ok());
+  }
+
+  #[test]
+  fn reader_sends_data_to_topic_cache() {
+    // 1. Create a reader
+    // Create the DDS cache and a topic
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicies::qos_none();
+
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    // Create mechanisms for notifications, statuses & commands
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
     let (_notification_event_source, notification_event_sender) =
       mio_source::make_poll_channel().unwrap();
     let data_reader_waker = Arc::new(Mutex::new(None));
@@ -1595,6 +3414,13 @@ mod tests {
       data_reader_command_receiver: reader_command_receiver,
       data_reader_waker,
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
     let mut reader = Reader::new(
@@ -1602,7 +3428,8 @@ mod tests {
       Rc::new(UDPSender::new(0).unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
+    )
+    .unwrap();
 
     // 2. Add info of a matched writer to the reader
     let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
@@ -1701,6 +3528,13 @@ mod tests {
       data_reader_command_receiver: reader_command_receiver,
       data_reader_waker,
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
     let mut reader = Reader::new(
@@ -1708,7 +3542,8 @@ mod tests {
       Rc::new(UDPSender::new(0).unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
+    )
+    .unwrap();
 
     // 2. Add info of a matched writer to the reader
     let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
@@ -1735,7 +3570,7 @@ mod tests {
       last_sn: SequenceNumber::new(0),
       count: 1,
     };
-    assert!(!reader.handle_heartbeat_msg(&hb_new, true, &mr_state)); // should be false, no ack
+    assert!(!reader.handle_heartbeat_msg(&hb_new, true, false, &mr_state)); // should be false, no ack
 
     // 4. Send the first proper heartbeat, reader should respond with acknack
     let hb_one = Heartbeat {
@@ -1745,12 +3580,12 @@ mod tests {
       last_sn: SequenceNumber::new(1),
       count: 2,
     };
-    assert!(reader.handle_heartbeat_msg(&hb_one, false, &mr_state)); // Should send an ack_nack
+    assert!(reader.handle_heartbeat_msg(&hb_one, false, false, &mr_state)); // Should send an ack_nack
 
     // 5. Send a duplicate of the first heartbeat, reader should not respond with
     // acknack
     let hb_one2 = hb_one.clone();
-    assert!(!reader.handle_heartbeat_msg(&hb_one2, false, &mr_state)); // No acknack
+    assert!(!reader.handle_heartbeat_msg(&hb_one2, false, false, &mr_state)); // No acknack
 
     // 6. Send a second proper heartbeat, reader should respond with acknack
     let hb_2 = Heartbeat {
@@ -1760,7 +3595,7 @@ mod tests {
       last_sn: SequenceNumber::new(3),  // writer has written 3 samples
       count: 3,
     };
-    assert!(reader.handle_heartbeat_msg(&hb_2, false, &mr_state)); // Should send an ack_nack
+    assert!(reader.handle_heartbeat_msg(&hb_2, false, false, &mr_state)); // Should send an ack_nack
 
     // 7. Count of acknack sent should be 2
     // The count is verified from the writer proxy
@@ -1771,9 +3606,8 @@ mod tests {
   }
 
   #[test]
-  fn reader_handles_gaps() {
-    // 1. Create a reader
-    // Create the DDS cache and a topic
+  fn spdp_reader_accepts_duplicate_sn_only_with_quirk() {
+    // 1. Create the built-in participant reader
     let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
     let topic_name = "test_name";
     let qos_policy = QosPolicies::qos_none();
@@ -1784,21 +3618,19 @@ mod tests {
       &qos_policy,
     );
 
-    // Create mechanisms for notifications, statuses & commands
-    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (notification_sender, notification_receiver) = mio_channel::sync_channel::<()>(100);
     let (_notification_event_source, notification_event_sender) =
       mio_source::make_poll_channel().unwrap();
-    let data_reader_waker = Arc::new(Mutex::new(None));
-
     let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
     let (participant_status_sender, _participant_status_receiver) =
       sync_status_channel(16).unwrap();
-
     let (_reader_command_sender, reader_command_receiver) =
       mio_channel::sync_channel::<ReaderCommand>(10);
 
-    // Then create the reader
-    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_guid = GUID::new(
+      GuidPrefix::random_for_this_participant(),
+      EntityId::SPDP_BUILTIN_PARTICIPANT_READER,
+    );
     let reader_ing = ReaderIngredients {
       guid: reader_guid,
       notification_sender,
@@ -1808,8 +3640,15 @@ mod tests {
       like_stateless: false,
       qos_policy,
       data_reader_command_receiver: reader_command_receiver,
-      data_reader_waker,
+      data_reader_waker: Arc::new(Mutex::new(None)),
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
     let mut reader = Reader::new(
@@ -1817,104 +3656,168 @@ mod tests {
       Rc::new(UDPSender::new(0).unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
-
-    // 2. Add info of a matched writer to the reader
-    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
-
-    let mr_state = MessageReceiverState {
-      source_guid_prefix: writer_guid.prefix,
-      ..Default::default()
-    };
+    )
+    .unwrap();
 
+    let writer_guid = GUID::new(
+      GuidPrefix::new(&[7; 12]),
+      EntityId::SPDP_BUILTIN_PARTICIPANT_WRITER,
+    );
     reader.matched_writer_add(
       writer_guid,
       EntityId::UNKNOWN,
-      mr_state.unicast_reply_locator_list.to_vec(),
-      mr_state.multicast_reply_locator_list.to_vec(),
+      vec![],
+      vec![],
       &QosPolicies::qos_none(),
     );
 
-    // 3. Feed the reader a gap message which marks sequence numbers 1-2 & 4 as
-    // irrelevant
-    let gap_start = SequenceNumber::new(1);
-    let gap_list_base = SequenceNumber::new(3);
-    let mut gap_list = SequenceNumberSet::new(gap_list_base, 7);
-    gap_list.test_insert(SequenceNumber::new(4));
-
-    let gap = Gap {
-      reader_id: reader.entity_id(),
+    let data = Data {
+      reader_id: reader_guid.entity_id,
       writer_id: writer_guid.entity_id,
-      gap_start,
-      gap_list,
+      writer_sn: SequenceNumber::new(1),
+      ..Data::default()
+    };
+    let data_flags = BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data);
+    let conformant_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
     };
-    reader.handle_gap_msg(&gap, &mr_state);
 
-    // 4. Verify that the writer proxy reports seqnums below 3 as ackable
-    // This should be the case since seqnums 1-2 were marked as irrelevant
-    assert_eq!(
-      reader
-        .matched_writer(writer_guid)
-        .unwrap()
-        .all_ackable_before(),
-      SequenceNumber::new(3)
-    );
+    // 2. First DATA is accepted
+    reader.handle_data_msg(data.clone(), data_flags, &conformant_state);
+    assert!(notification_receiver.try_recv().is_ok());
 
-    // 5. Feed the reader a data message with sequence number 3
-    let data = Data {
-      writer_id: writer_guid.entity_id,
-      writer_sn: SequenceNumber::new(3),
+    // 3. Duplicate from a conformant peer is dropped
+    reader.handle_data_msg(data.clone(), data_flags, &conformant_state);
+    assert!(notification_receiver.try_recv().is_err());
+
+    // 4. Duplicate from a peer needing the workaround is accepted
+    let legacy_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      quirks: ActiveQuirks {
+        duplicate_spdp_sequence_numbers: true,
+      },
       ..Default::default()
     };
-    let data_flags = BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data);
+    reader.handle_data_msg(data, data_flags, &legacy_state);
+    assert!(notification_receiver.try_recv().is_ok());
+  }
 
-    reader.handle_data_msg(data, data_flags, &mr_state);
+  #[test]
+  fn reader_records_reception_context_only_when_enabled() {
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicies::qos_none();
 
-    // 6. Verify that the writer proxy reports seqnums below 5 as ackable
-    // This should be the case since reader received data with seqnum 3 and seqnum 4
-    // was marked irrelevant before
-    assert_eq!(
-      reader
-        .matched_writer(writer_guid)
-        .unwrap()
-        .all_ackable_before(),
-      SequenceNumber::new(5)
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
     );
 
-    // 7. Feed the reader a gap message which marks the sequence number 5 as
-    // irrelevant
-    let gap_start = SequenceNumber::new(5);
-    let gap_list_base = SequenceNumber::new(5);
-    let mut gap_list = SequenceNumberSet::new(gap_list_base, 7);
-    gap_list.test_insert(SequenceNumber::new(5));
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
 
-    let gap = Gap {
-      reader_id: reader.entity_id(),
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle: topic_cache_handle.clone(),
+      like_stateless: false,
+      qos_policy,
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let writer_guid = GUID::new(
+      GuidPrefix::new(&[7; 12]),
+      EntityId::new([0, 0, 1], EntityKind::WRITER_NO_KEY_USER_DEFINED),
+    );
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      vec![],
+      vec![],
+      &QosPolicies::qos_none(),
+    );
+
+    let reply_locators = vec![Locator::from(SocketAddr::new(
+      Ipv4Addr::new(192, 168, 1, 2).into(),
+      7412,
+    ))];
+    let data_flags = BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data);
+    let data = |sn| Data {
+      reader_id: reader_guid.entity_id,
       writer_id: writer_guid.entity_id,
-      gap_start,
-      gap_list,
+      writer_sn: SequenceNumber::new(sn),
+      ..Data::default()
+    };
+    let stored_context = |reader: &Reader, sn| {
+      let instant = reader.seqnum_instant_map[&SequenceNumber::new(sn)];
+      topic_cache_handle
+        .lock()
+        .unwrap()
+        .get_change(&instant)
+        .unwrap()
+        .write_options
+        .reception_context()
+        .cloned()
     };
-    reader.handle_gap_msg(&gap, &mr_state);
 
-    // 8. Verify that the writer proxy reports seqnums below 6 as ackable
+    // Not recorded by default
+    let mut mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      unicast_reply_locator_list: &reply_locators,
+      info_destination: Some(reader_guid.prefix),
+      ..Default::default()
+    };
+    reader.handle_data_msg(data(1), data_flags, &mr_state);
+    assert_eq!(stored_context(&reader, 1), None);
+
+    // Recorded when enabled
+    mr_state.record_reception_context = true;
+    reader.handle_data_msg(data(2), data_flags, &mr_state);
     assert_eq!(
-      reader
-        .matched_writer(writer_guid)
-        .unwrap()
-        .all_ackable_before(),
-      SequenceNumber::new(6)
+      stored_context(&reader, 2),
+      Some(ReceptionContext {
+        source_guid_prefix: writer_guid.prefix,
+        dest_guid_prefix: Some(reader_guid.prefix),
+        source_timestamp: Some(Timestamp::INVALID),
+        unicast_reply_locators: reply_locators.clone(),
+        multicast_reply_locators: vec![],
+      })
     );
   }
 
   #[test]
-  fn stateless_reader_does_not_contain_writer_proxies() {
-    // 1. Create a stateless-like reader
-    // Create the DDS cache and a topic
+  fn reader_drops_datafrag_of_oversized_sample() {
     let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
     let topic_name = "test_name";
-    let qos_policy = QosPolicies::builder()
-      .reliability(Reliability::BestEffort) // Stateless needs to be BestEffort
-      .build();
+    let qos_policy = QosPolicies::qos_none();
 
     let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
       topic_name.to_string(),
@@ -1922,20 +3825,15 @@ mod tests {
       &qos_policy,
     );
 
-    // Create mechanisms for notifications, statuses & commands
     let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
     let (_notification_event_source, notification_event_sender) =
       mio_source::make_poll_channel().unwrap();
-    let data_reader_waker = Arc::new(Mutex::new(None));
-
     let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
     let (participant_status_sender, _participant_status_receiver) =
       sync_status_channel(16).unwrap();
-
     let (_reader_command_sender, reader_command_receiver) =
       mio_channel::sync_channel::<ReaderCommand>(10);
 
-    let like_stateless = true;
     let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
     let reader_ing = ReaderIngredients {
       guid: reader_guid,
@@ -1943,11 +3841,18 @@ mod tests {
       status_sender,
       topic_name: topic_name.to_string(),
       topic_cache_handle,
-      like_stateless,
+      like_stateless: false,
       qos_policy,
       data_reader_command_receiver: reader_command_receiver,
-      data_reader_waker,
+      data_reader_waker: Arc::new(Mutex::new(None)),
       poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
       security_plugins: None,
     };
     let mut reader = Reader::new(
@@ -1955,26 +3860,1808 @@ mod tests {
       Rc::new(UDPSender::new(0).unwrap()),
       crate::polling::new_shared_timer(),
       participant_status_sender,
-    );
-
-    // 2. Attempt to add info of a matched writer to the reader
-    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
-
-    let mr_state = MessageReceiverState {
-      source_guid_prefix: writer_guid.prefix,
-      ..Default::default()
-    };
+    )
+    .unwrap();
+    reader.set_max_fragmented_sample_size(1024 * 1024);
 
+    let writer_guid = GUID::new(
+      GuidPrefix::new(&[7; 12]),
+      EntityId::new([0, 0, 1], EntityKind::WRITER_NO_KEY_USER_DEFINED),
+    );
     reader.matched_writer_add(
       writer_guid,
       EntityId::UNKNOWN,
-      mr_state.unicast_reply_locator_list.to_vec(),
-      mr_state.multicast_reply_locator_list.to_vec(),
+      vec![],
+      vec![],
       &QosPolicies::qos_none(),
     );
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
 
-    // 3. Verify that the reader does not contain a writer proxy for the writer that
-    // we attempted to add
-    assert!(reader.matched_writer(writer_guid).is_none());
+    // A single fragment claiming to belong to a 4 GB sample
+    let datafrag = DataFrag {
+      reader_id: reader_guid.entity_id,
+      writer_id: writer_guid.entity_id,
+      writer_sn: SequenceNumber::new(1),
+      fragment_starting_num: FragmentNumber::new(1),
+      fragments_in_submessage: 1,
+      fragment_size: 1024,
+      data_size: u32::MAX,
+      serialized_payload: bytes::Bytes::from(vec![0u8; 1024]),
+      ..Default::default()
+    };
+    reader.handle_datafrag_msg(&datafrag, BitFlags::empty(), &mr_state);
+
+    // Nothing was allocated for reassembly, and the sample is not requested
+    // again.
+    assert!(reader.fragment_assemblers.is_empty());
+    assert!(!reader.is_frag_partially_received(writer_guid, SequenceNumber::new(1)));
+    assert_eq!(
+      reader
+        .matched_writer(writer_guid)
+        .unwrap()
+        .all_ackable_before(),
+      SequenceNumber::new(2)
+    );
+  }
+
+  #[test]
+  fn reader_resets_restarted_writer() {
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let reliable_qos = QosPolicyBuilder::new()
+      .reliability(Reliability::Reliable {
+        max_blocking_time: Duration::from_millis(100),
+      })
+      .build();
+
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &reliable_qos,
+    );
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, status_receiver) = sync_status_channel::<DataReaderStatus>(16).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle: topic_cache_handle.clone(),
+      like_stateless: false,
+      qos_policy: reliable_qos.clone(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      vec![],
+      vec![],
+      &reliable_qos,
+    );
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+    let data_flags = BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data);
+    let data = |sn| Data {
+      reader_id: reader_guid.entity_id,
+      writer_id: writer_guid.entity_id,
+      writer_sn: SequenceNumber::new(sn),
+      ..Data::default()
+    };
+    let heartbeat = |last_sn, count| Heartbeat {
+      reader_id: reader_guid.entity_id,
+      writer_id: writer_guid.entity_id,
+      first_sn: SequenceNumber::new(1),
+      last_sn: SequenceNumber::new(last_sn),
+      count,
+    };
+    let restarts = || {
+      let mut restarts = vec![];
+      while let Ok(status) = status_receiver.try_recv() {
+        if let DataReaderStatus::WriterRestarted { count, writer } = status {
+          restarts.push((count, writer));
+        }
+      }
+      restarts
+    };
+    let ackable_before = |reader: &Reader| {
+      reader
+        .matched_writer(writer_guid)
+        .unwrap()
+        .all_ackable_before()
+    };
+
+    // The writer sends samples 1..=3
+    for sn in 1..=3 {
+      reader.handle_data_msg(data(sn), data_flags, &mr_state);
+    }
+    reader.handle_heartbeat_msg(&heartbeat(3, 5), true, false, &mr_state);
+    assert_eq!(ackable_before(&reader), SequenceNumber::new(4));
+
+    // The writer restarts with the same GUID, and its first heartbeat announces
+    // only sample 1. This alone could be a late heartbeat, so nothing happens
+    // yet.
+    reader.handle_heartbeat_msg(&heartbeat(1, 1), true, false, &mr_state);
+    assert!(restarts().is_empty());
+    assert_eq!(ackable_before(&reader), SequenceNumber::new(4));
+
+    // The next heartbeat confirms the restart. The writer proxy is reset, so
+    // the sample is requested.
+    assert!(reader.handle_heartbeat_msg(&heartbeat(1, 2), true, false, &mr_state));
+    assert_eq!(restarts(), vec![(CountWithChange::new(1, 1), writer_guid)]);
+    assert_eq!(ackable_before(&reader), SequenceNumber::new(1));
+
+    // The new sample 1 is not discarded as a duplicate, and only it is
+    // available for reliable reading.
+    reader.handle_data_msg(data(1), data_flags, &mr_state);
+    assert_eq!(ackable_before(&reader), SequenceNumber::new(2));
+    let topic_cache = topic_cache_handle.lock().unwrap();
+    assert_eq!(topic_cache.writer_restarts().get(&writer_guid), Some(&1));
+    let readable: Vec<_> = topic_cache
+      .get_changes_in_range_reliable(&BTreeMap::new())
+      .map(|(_, cc)| cc.sequence_number)
+      .collect();
+    assert_eq!(readable, vec![SequenceNumber::new(1)]);
+  }
+
+  #[test]
+  fn reader_follows_writer_qos_changes() {
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicyBuilder::new()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: Duration::from_millis(100),
+      })
+      .build();
+
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let data_reader_waker = Arc::new(Mutex::new(None));
+
+    let (status_sender, status_receiver) = sync_status_channel::<DataReaderStatus>(16).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_ing = ReaderIngredients {
+      guid: GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED),
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy: qos_policy.clone(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker,
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let statuses = || {
+      let mut statuses = vec![];
+      while let Ok(status) = status_receiver.try_recv() {
+        match status {
+          DataReaderStatus::SubscriptionMatched { current, .. } => {
+            statuses.push(format!("matched {}", current.count_change()));
+          }
+          DataReaderStatus::RequestedIncompatibleQos { last_policy_id, .. } => {
+            statuses.push(format!("incompatible {last_policy_id:?}"));
+          }
+          _ => (),
+        }
+      }
+      statuses
+    };
+
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    let best_effort = QosPolicyBuilder::new()
+      .reliability(policy::Reliability::BestEffort)
+      .build();
+
+    // 1. A compatible writer is matched
+    reader.matched_writer_add(writer_guid, EntityId::UNKNOWN, vec![], vec![], &qos_policy);
+    assert_eq!(statuses(), vec!["matched 1"]);
+    assert!(reader.matched_writer(writer_guid).is_some());
+
+    // 2. The writer changes to an incompatible QoS. It is unmatched.
+    reader.matched_writer_add(writer_guid, EntityId::UNKNOWN, vec![], vec![], &best_effort);
+    assert_eq!(statuses(), vec!["matched -1", "incompatible Reliability"]);
+    assert!(reader.matched_writer(writer_guid).is_none());
+
+    // 3. The writer changes back to a compatible QoS. It is matched again.
+    reader.matched_writer_add(writer_guid, EntityId::UNKNOWN, vec![], vec![], &qos_policy);
+    assert_eq!(statuses(), vec!["matched 1"]);
+    assert!(reader.matched_writer(writer_guid).is_some());
+  }
+
+  #[test]
+  fn reader_tracks_writer_liveliness() {
+    // 1. Create a reader
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicies::qos_none();
+
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let data_reader_waker = Arc::new(Mutex::new(None));
+
+    let (status_sender, status_receiver) = sync_status_channel::<DataReaderStatus>(16).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy,
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker,
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let liveliness_changes = || {
+      let mut changes = vec![];
+      while let Ok(status) = status_receiver.try_recv() {
+        if let DataReaderStatus::LivelinessChanged {
+          alive_total,
+          not_alive_total,
+        } = status
+        {
+          changes.push((alive_total, not_alive_total));
+        }
+      }
+      changes
+    };
+
+    // 2. Match a writer offering automatic liveliness with a short lease
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    let offered_qos = QosPolicyBuilder::new()
+      .liveliness(policy::Liveliness::Automatic {
+        lease_duration: Duration::from_millis(50),
+      })
+      .build();
+    reader.matched_writer_add(writer_guid, EntityId::UNKNOWN, vec![], vec![], &offered_qos);
+    assert_eq!(
+      liveliness_changes(),
+      vec![(CountWithChange::new(1, 1), CountWithChange::new(0, 0))]
+    );
+
+    // 3. Let the lease expire. The writer is no longer alive.
+    std::thread::sleep(StdDuration::from_millis(100));
+    reader.handle_timed_event(TimedEvent::LivelinessCheck);
+    assert_eq!(
+      liveliness_changes(),
+      vec![(CountWithChange::new(0, -1), CountWithChange::new(1, 1))]
+    );
+    // The DataReader sees which writer is not alive
+    assert_eq!(
+      *reader.liveliness_status.lock().unwrap(),
+      LivelinessChangedStatus::new([].into(), [writer_guid].into())
+    );
+
+    // 4. A heartbeat from an automatic writer asserts its liveliness again
+    reader.handle_writer_liveliness_heartbeat(writer_guid, false);
+    assert_eq!(
+      liveliness_changes(),
+      vec![(CountWithChange::new(1, 1), CountWithChange::new(0, -1))]
+    );
+    assert_eq!(
+      *reader.liveliness_status.lock().unwrap(),
+      LivelinessChangedStatus::new([writer_guid].into(), [].into())
+    );
+
+    // 5. Losing the writer removes it from the alive count
+    reader.remove_writer_proxy(writer_guid);
+    assert_eq!(
+      liveliness_changes(),
+      vec![(CountWithChange::new(0, -1), CountWithChange::new(0, 0))]
+    );
+  }
+
+  #[test]
+  fn reading_requested_deadline_missed_status_resets_change() {
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicyBuilder::new()
+      .deadline(policy::Deadline(Duration::from_millis(10)))
+      .build();
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, status_receiver) = sync_status_channel::<DataReaderStatus>(16).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+    let requested_deadline_missed_status = Arc::new(Mutex::new(CountWithChange::default()));
+
+    let reader_ing = ReaderIngredients {
+      guid: GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED),
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy: qos_policy.clone(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: requested_deadline_missed_status.clone(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let missed = || {
+      let mut missed = vec![];
+      while let Ok(status) = status_receiver.try_recv() {
+        if let DataReaderStatus::RequestedDeadlineMissed { count } = status {
+          missed.push(count);
+        }
+      }
+      missed
+    };
+    // What SimpleDataReader::requested_deadline_missed_status does
+    let read_status = || {
+      let mut shared = requested_deadline_missed_status.lock().unwrap();
+      let status = *shared;
+      *shared = CountWithChange::new(status.count(), 0);
+      reader_command_sender
+        .try_send(ReaderCommand::ResetRequestedDeadlineStatus {
+          read_count: status.count(),
+        })
+        .unwrap();
+      status
+    };
+
+    // A writer that never sends anything misses every deadline, once the
+    // deadline has passed since matching
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    reader.matched_writer_add(writer_guid, EntityId::UNKNOWN, vec![], vec![], &qos_policy);
+    reader.handle_timed_event(TimedEvent::DeadlineMissedCheck);
+    assert_eq!(missed(), vec![]);
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    reader.handle_timed_event(TimedEvent::DeadlineMissedCheck);
+    reader.handle_timed_event(TimedEvent::DeadlineMissedCheck);
+    assert_eq!(
+      missed(),
+      vec![CountWithChange::new(1, 1), CountWithChange::new(2, 2)]
+    );
+
+    // Reading resets the change, but not the count
+    assert_eq!(read_status(), CountWithChange::new(2, 2));
+    reader.process_command();
+    assert_eq!(
+      *requested_deadline_missed_status.lock().unwrap(),
+      CountWithChange::new(2, 0)
+    );
+    assert_eq!(read_status(), CountWithChange::new(2, 0));
+    reader.process_command();
+
+    // The change is counted from the last read
+    reader.handle_timed_event(TimedEvent::DeadlineMissedCheck);
+    assert_eq!(missed(), vec![CountWithChange::new(3, 1)]);
+    assert_eq!(
+      *requested_deadline_missed_status.lock().unwrap(),
+      CountWithChange::new(3, 1)
+    );
+  }
+
+  #[test]
+  fn reader_schedules_disposal_when_data_reader_is_gone() {
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicies::qos_none();
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    let (notification_sender, notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, status_receiver) = sync_status_channel::<DataReaderStatus>(16).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy,
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let timer = crate::polling::new_shared_timer();
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      timer.clone(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    let disposals = || {
+      // Let the timer tick past the zero timeout
+      std::thread::sleep(StdDuration::from_millis(20));
+      let mut disposals = vec![];
+      while let Some(event) = timer.borrow_mut().poll() {
+        if let DpTimerEvent::DisposeReader { entity_id } = event {
+          disposals.push(entity_id);
+        }
+      }
+      disposals
+    };
+
+    // The DataReader is alive
+    reader.notify_cache_change();
+    reader.send_status_change(DataReaderStatus::SampleLost {
+      count: CountWithChange::new(1, 1),
+    });
+    assert_eq!(disposals(), vec![]);
+
+    // The DataReader is dropped. Disposal is requested only once.
+    drop(notification_receiver);
+    drop(status_receiver);
+    reader.notify_cache_change();
+    reader.send_status_change(DataReaderStatus::SampleLost {
+      count: CountWithChange::new(2, 1),
+    });
+    assert_eq!(disposals(), vec![reader_guid.entity_id]);
+  }
+
+  #[test]
+  fn liveliness_heartbeat_asserts_liveliness_without_acknack() {
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let reliable_qos = QosPolicyBuilder::new()
+      .reliability(Reliability::Reliable {
+        max_blocking_time: Duration::from_millis(100),
+      })
+      .build();
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &reliable_qos,
+    );
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(16).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy: reliable_qos.clone(),
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker: Arc::new(Mutex::new(None)),
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    // A writer with manual liveliness, which a plain heartbeat does not assert
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    let offered_qos = reliable_qos.modify_by(
+      &QosPolicyBuilder::new()
+        .liveliness(policy::Liveliness::ManualByTopic {
+          lease_duration: Duration::from_millis(50),
+        })
+        .build(),
+    );
+    reader.matched_writer_add(writer_guid, EntityId::UNKNOWN, vec![], vec![], &offered_qos);
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+    let heartbeat = |last_sn, count| Heartbeat {
+      reader_id: reader_guid.entity_id,
+      writer_id: writer_guid.entity_id,
+      first_sn: SequenceNumber::new(1),
+      last_sn: SequenceNumber::new(last_sn),
+      count,
+    };
+    let is_alive = |reader: &Reader| reader.matched_writer(writer_guid).unwrap().is_alive;
+
+    // The writer has sent sample 1, and the reader has it.
+    reader.handle_data_msg(
+      Data {
+        reader_id: reader_guid.entity_id,
+        writer_id: writer_guid.entity_id,
+        writer_sn: SequenceNumber::new(1),
+        ..Data::default()
+      },
+      BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data),
+      &mr_state,
+    );
+
+    std::thread::sleep(StdDuration::from_millis(100));
+    reader.handle_timed_event(TimedEvent::LivelinessCheck);
+    assert!(!is_alive(&reader));
+
+    // A plain heartbeat without the final flag is answered, but does not assert
+    // manual liveliness.
+    assert!(reader.handle_heartbeat_msg(&heartbeat(1, 1), false, false, &mr_state));
+    assert!(!is_alive(&reader));
+
+    // A liveliness heartbeat resets the lease, and is not answered, as there is
+    // nothing to repair.
+    let before = Instant::now();
+    assert!(!reader.handle_heartbeat_msg(&heartbeat(1, 2), false, true, &mr_state));
+    assert!(is_alive(&reader));
+    let expiry = reader
+      .matched_writer(writer_guid)
+      .unwrap()
+      .liveliness_expiry()
+      .unwrap();
+    assert!(expiry >= before + StdDuration::from_millis(50));
+
+    // A liveliness heartbeat announcing missing samples is still answered.
+    assert!(reader.handle_heartbeat_msg(&heartbeat(2, 3), false, true, &mr_state));
+  }
+
+  #[test]
+  fn reader_handles_gaps() {
+    // 1. Create a reader
+    // Create the DDS cache and a topic
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicies::qos_none();
+
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    // Create mechanisms for notifications, statuses & commands
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let data_reader_waker = Arc::new(Mutex::new(None));
+
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    // Then create the reader
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless: false,
+      qos_policy,
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker,
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    // 2. Add info of a matched writer to the reader
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      mr_state.unicast_reply_locator_list.to_vec(),
+      mr_state.multicast_reply_locator_list.to_vec(),
+      &QosPolicies::qos_none(),
+    );
+
+    // 3. Feed the reader a gap message which marks sequence numbers 1-2 & 4 as
+    // irrelevant
+    let gap_start = SequenceNumber::new(1);
+    let gap_list_base = SequenceNumber::new(3);
+    let mut gap_list = SequenceNumberSet::new(gap_list_base, 7);
+    gap_list.test_insert(SequenceNumber::new(4));
+
+    let gap = Gap {
+      reader_id: reader.entity_id(),
+      writer_id: writer_guid.entity_id,
+      gap_start,
+      gap_list,
+    };
+    reader.handle_gap_msg(&gap, &mr_state);
+
+    // 4. Verify that the writer proxy reports seqnums below 3 as ackable
+    // This should be the case since seqnums 1-2 were marked as irrelevant
+    assert_eq!(
+      reader
+        .matched_writer(writer_guid)
+        .unwrap()
+        .all_ackable_before(),
+      SequenceNumber::new(3)
+    );
+
+    // 5. Feed the reader a data message with sequence number 3
+    let data = Data {
+      writer_id: writer_guid.entity_id,
+      writer_sn: SequenceNumber::new(3),
+      ..Default::default()
+    };
+    let data_flags = BitFlags::<DATA_Flags>::from_flag(DATA_Flags::Data);
+
+    reader.handle_data_msg(data, data_flags, &mr_state);
+
+    // 6. Verify that the writer proxy reports seqnums below 5 as ackable
+    // This should be the case since reader received data with seqnum 3 and seqnum 4
+    // was marked irrelevant before
+    assert_eq!(
+      reader
+        .matched_writer(writer_guid)
+        .unwrap()
+        .all_ackable_before(),
+      SequenceNumber::new(5)
+    );
+
+    // 7. Feed the reader a gap message which marks the sequence number 5 as
+    // irrelevant
+    let gap_start = SequenceNumber::new(5);
+    let gap_list_base = SequenceNumber::new(5);
+    let mut gap_list = SequenceNumberSet::new(gap_list_base, 7);
+    gap_list.test_insert(SequenceNumber::new(5));
+
+    let gap = Gap {
+      reader_id: reader.entity_id(),
+      writer_id: writer_guid.entity_id,
+      gap_start,
+      gap_list,
+    };
+    reader.handle_gap_msg(&gap, &mr_state);
+
+    // 8. Verify that the writer proxy reports seqnums below 6 as ackable
+    assert_eq!(
+      reader
+        .matched_writer(writer_guid)
+        .unwrap()
+        .all_ackable_before(),
+      SequenceNumber::new(6)
+    );
+  }
+
+  #[test]
+  fn stateless_reader_does_not_contain_writer_proxies() {
+    // 1. Create a stateless-like reader
+    // Create the DDS cache and a topic
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_name = "test_name";
+    let qos_policy = QosPolicies::builder()
+      .reliability(Reliability::BestEffort) // Stateless needs to be BestEffort
+      .build();
+
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new("test_type".to_string()),
+      &qos_policy,
+    );
+
+    // Create mechanisms for notifications, statuses & commands
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel::<()>(100);
+    let (_notification_event_source, notification_event_sender) =
+      mio_source::make_poll_channel().unwrap();
+    let data_reader_waker = Arc::new(Mutex::new(None));
+
+    let (status_sender, _status_receiver) = sync_status_channel::<DataReaderStatus>(4).unwrap();
+    let (participant_status_sender, _participant_status_receiver) =
+      sync_status_channel(16).unwrap();
+
+    let (_reader_command_sender, reader_command_receiver) =
+      mio_channel::sync_channel::<ReaderCommand>(10);
+
+    let like_stateless = true;
+    let reader_guid = GUID::dummy_test_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    let reader_ing = ReaderIngredients {
+      guid: reader_guid,
+      notification_sender,
+      status_sender,
+      topic_name: topic_name.to_string(),
+      topic_cache_handle,
+      like_stateless,
+      qos_policy,
+      data_reader_command_receiver: reader_command_receiver,
+      data_reader_waker,
+      poll_event_sender: notification_event_sender,
+      liveliness_status: Default::default(),
+      requested_deadline_missed_status: Default::default(),
+      detect_content_duplicates: Default::default(),
+      ignore_ambiguous_status_info: Default::default(),
+      duplicate_acceptance: Default::default(),
+      paused: Default::default(),
+      history_boundaries: None,
+      security_plugins: None,
+    };
+    let mut reader = Reader::new(
+      reader_ing,
+      Rc::new(UDPSender::new(0).unwrap()),
+      crate::polling::new_shared_timer(),
+      participant_status_sender,
+    )
+    .unwrap();
+
+    // 2. Attempt to add info of a matched writer to the reader
+    let writer_guid = GUID::dummy_test_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+
+    let mr_state = MessageReceiverState {
+      source_guid_prefix: writer_guid.prefix,
+      ..Default::default()
+    };
+
+    reader.matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      mr_state.unicast_reply_locator_list.to_vec(),
+      mr_state.multicast_reply_locator_list.to_vec(),
+      &QosPolicies::qos_none(),
+    );
+
+    // 3. Verify that the reader does not contain a writer proxy for the writer that
+    // we attempted to add
+    assert!(reader.matched_writer(writer_guid).is_none());
+  }
+
+  #[test]
+  fn change_kind_is_deduced_from_status_info() {
+    use crate::{
+      messages::submessages::elements::{inline_qos::StatusInfo, parameter::Parameter},
+      structure::parameter_id::ParameterId,
+    };
+
+    let deduce = |inline_qos: Option<ParameterList>, ambiguous| {
+      Reader::deduce_change_kind(
+        &inline_qos,
+        false,
+        RepresentationIdentifier::PL_CDR_BE,
+        ambiguous,
+      )
+    };
+    let status_info = |bits: u8| {
+      let mut inline_qos = ParameterList::new();
+      inline_qos.push(Parameter {
+        parameter_id: ParameterId::PID_STATUS_INFO,
+        value: vec![0, 0, 0, bits],
+      });
+      Some(inline_qos)
+    };
+
+    // Flags (D, U, F) of a DATA without payload => ChangeKind, when ambiguous
+    // StatusInfo is taken as NOT_ALIVE, and when it is ignored
+    let not_alive_d = Some(ChangeKind::NotAliveDisposed);
+    let not_alive_u = Some(ChangeKind::NotAliveUnregistered);
+    let not_alive_du = Some(ChangeKind::NotAliveDisposedUnregistered);
+    let table = [
+      (false, false, false, not_alive_d, None),
+      (true, false, false, not_alive_d, not_alive_d),
+      (false, true, false, not_alive_u, not_alive_u),
+      (true, true, false, not_alive_du, not_alive_du),
+      (false, false, true, not_alive_d, None),
+      (true, false, true, not_alive_d, not_alive_d),
+      (false, true, true, not_alive_u, not_alive_u),
+      (true, true, true, not_alive_du, not_alive_du),
+    ];
+    for (disposed, unregistered, filtered, not_alive, ignore) in table {
+      let mut inline_qos = ParameterList::new();
+      inline_qos.push(StatusInfo::new(disposed, unregistered, filtered).into_parameter());
+      assert_eq!(
+        deduce(Some(inline_qos.clone()), AmbiguousStatusInfo::NotAlive),
+        not_alive,
+        "D={disposed} U={unregistered} F={filtered}"
+      );
+      assert_eq!(
+        deduce(Some(inline_qos), AmbiguousStatusInfo::Ignore),
+        ignore,
+        "D={disposed} U={unregistered} F={filtered}"
+      );
+    }
+
+    // Reserved flags are masked away
+    assert_eq!(
+      deduce(status_info(0b1111_1010), AmbiguousStatusInfo::Ignore),
+      Some(ChangeKind::NotAliveUnregistered)
+    );
+    assert_eq!(
+      deduce(status_info(0b1111_1000), AmbiguousStatusInfo::NotAlive),
+      Some(ChangeKind::NotAliveDisposed)
+    );
+    assert_eq!(
+      deduce(status_info(0b1111_1000), AmbiguousStatusInfo::Ignore),
+      None
+    );
+
+    // Without StatusInfo
+    assert_eq!(
+      deduce(None, AmbiguousStatusInfo::NotAlive),
+      Some(ChangeKind::NotAliveDisposed)
+    );
+    assert_eq!(
+      Reader::deduce_change_kind(
+        &None,
+        true,
+        RepresentationIdentifier::PL_CDR_LE,
+        AmbiguousStatusInfo::NotAlive
+      ),
+      Some(ChangeKind::NotAliveUnregistered)
+    );
+    assert_eq!(deduce(None, AmbiguousStatusInfo::Ignore), None);
+  }
+
+  // The tests below drive a Reader through a `ReaderHarness`, which injects
+  // RTPS messages and captures what the Reader sends.
+
+  #[derive(Debug)]
+  struct ManualClock(Mutex<Timestamp>);
+
+  impl ManualClock {
+    fn new() -> Arc<Self> {
+      Arc::new(ManualClock(Mutex::new(Timestamp::now())))
+    }
+
+    fn set(&self, now: Timestamp) {
+      *self.0.lock().unwrap() = now;
+    }
+  }
+
+  impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+      *self.0.lock().unwrap()
+    }
+  }
+
+  fn harness_writer(id: u8) -> GUID {
+    GUID::new(
+      GuidPrefix::new(&[id; 12]),
+      EntityId::new([0, 0, 1], EntityKind::WRITER_WITH_KEY_USER_DEFINED),
+    )
+  }
+
+  fn reliable_qos() -> QosPolicies {
+    QosPolicyBuilder::new()
+      .reliability(Reliability::Reliable {
+        max_blocking_time: Duration::from_millis(100),
+      })
+      .build()
+  }
+
+  // A harnessed Reader with `qos`, matched with `harness_writer(1)`. The
+  // Reader is configured before the Writer is matched, and the messages sent
+  // at matching are discarded.
+  fn reader_harness(
+    qos: &QosPolicies,
+    configure: impl FnOnce(&mut ReaderHarness),
+  ) -> ReaderHarness {
+    let mut h = ReaderHarness::new("test_topic", qos).unwrap();
+    configure(&mut h);
+    h.add_matched_writer(
+      harness_writer(1),
+      vec![Locator::from(SocketAddr::from(([192, 0, 2, 1], 7411)))],
+      qos,
+    );
+    h.take_sent();
+    h
+  }
+
+  fn key_hash(instance: u8) -> KeyHash {
+    KeyHash::from_pl_cdr_bytes(vec![instance; 16]).unwrap()
+  }
+
+  // Inject samples with the given sequence numbers from `harness_writer(1)`.
+  fn inject_samples(h: &mut ReaderHarness, sns: &[i64]) {
+    for &sn in sns {
+      let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![sn as u8; 4]);
+      h.inject_data(harness_writer(1), SequenceNumber::new(sn), payload);
+    }
+  }
+
+  // Inject the given fragments of sample 1, which has 4 fragments of 16 bytes.
+  fn inject_frags(h: &mut ReaderHarness, frags: &[u32]) {
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![7; 60]);
+    for &frag in frags {
+      h.inject_data_frag(
+        harness_writer(1),
+        SequenceNumber::new(1),
+        payload.clone(),
+        frag,
+        1,
+        16,
+      );
+    }
+  }
+
+  fn as_i64(sns: Vec<SequenceNumber>) -> Vec<i64> {
+    sns.into_iter().map(i64::from).collect()
+  }
+
+  fn delivered(h: &mut ReaderHarness) -> Vec<i64> {
+    as_i64(h.delivered_sequence_numbers())
+  }
+
+  // (base, missing) of the ACKNACKs sent since the previous call
+  fn sent_acknacks(h: &mut ReaderHarness) -> Vec<(i64, Vec<i64>)> {
+    h.take_sent()
+      .iter()
+      .flat_map(|d| d.submessages().unwrap())
+      .filter_map(|sm| match sm {
+        CapturedSubmessage::AckNack { base, missing, .. } => {
+          Some((i64::from(base), as_i64(missing)))
+        }
+        _ => None,
+      })
+      .collect()
+  }
+
+  // (writer_sn, missing) of the NACK_FRAGs sent since the previous call
+  fn sent_nackfrags(h: &mut ReaderHarness) -> Vec<(i64, Vec<u32>)> {
+    h.take_sent()
+      .iter()
+      .flat_map(|d| d.submessages().unwrap())
+      .filter_map(|sm| match sm {
+        CapturedSubmessage::NackFrag {
+          writer_sn, missing, ..
+        } => Some((i64::from(writer_sn), missing)),
+        _ => None,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn best_effort_samples_are_delivered_as_they_arrive_by_default() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |_| {});
+    inject_samples(&mut h, &[1, 3, 2]);
+    assert_eq!(delivered(&mut h), vec![1, 3, 2]);
+  }
+
+  #[test]
+  fn reordered_best_effort_samples_are_delivered_in_sequence() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_best_effort_reordering(Some(BestEffortReordering::default()));
+    });
+    inject_samples(&mut h, &[1, 3, 4]);
+    assert_eq!(delivered(&mut h), vec![1]);
+    inject_samples(&mut h, &[2, 5]);
+    assert_eq!(delivered(&mut h), vec![1, 2, 3, 4, 5]);
+  }
+
+  #[test]
+  fn lost_best_effort_sample_is_given_up_after_max_delay() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_best_effort_reordering(Some(BestEffortReordering {
+        max_delay: StdDuration::from_millis(20),
+        max_samples: 64,
+      }));
+    });
+    inject_samples(&mut h, &[1, 3, 4]);
+    assert_eq!(delivered(&mut h), vec![1]);
+
+    h.run_timers_for(StdDuration::from_millis(100));
+    assert_eq!(delivered(&mut h), vec![1, 3, 4]);
+    // Too late to be delivered in sequence.
+    inject_samples(&mut h, &[2]);
+    assert_eq!(delivered(&mut h), vec![1, 3, 4]);
+  }
+
+  #[test]
+  fn held_samples_are_delivered_when_the_writer_is_lost() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_best_effort_reordering(Some(BestEffortReordering::default()));
+    });
+    inject_samples(&mut h, &[1, 3]);
+    h.remove_matched_writer(harness_writer(1));
+    assert_eq!(delivered(&mut h), vec![1, 3]);
+  }
+
+  fn inject_heartbeat_up_to(h: &mut ReaderHarness, last_sn: i64, count: i32) {
+    h.inject_heartbeat(
+      harness_writer(1),
+      SequenceNumber::new(1),
+      SequenceNumber::new(last_sn),
+      count,
+      true,
+    );
+  }
+
+  fn stream_synced_count(h: &mut ReaderHarness) -> usize {
+    let mut count = 0;
+    while let Some(status) = h.try_recv_status() {
+      if let DataReaderStatus::ReliableStreamSynced { writer } = status {
+        assert_eq!(writer, harness_writer(1));
+        count += 1;
+      }
+    }
+    count
+  }
+
+  #[test]
+  fn stream_sync_is_reported_when_missing_samples_arrive() {
+    let mut h = reader_harness(&reliable_qos(), |_| {});
+    // Nothing is known about the writer before its first HEARTBEAT.
+    inject_samples(&mut h, &[1]);
+    assert_eq!(stream_synced_count(&mut h), 0);
+
+    inject_heartbeat_up_to(&mut h, 3, 1);
+    assert_eq!(stream_synced_count(&mut h), 0);
+    inject_samples(&mut h, &[3]);
+    assert_eq!(stream_synced_count(&mut h), 0);
+    inject_samples(&mut h, &[2]);
+    assert_eq!(stream_synced_count(&mut h), 1);
+
+    // Staying in sync is not reported again.
+    inject_samples(&mut h, &[4]);
+    inject_heartbeat_up_to(&mut h, 4, 2);
+    assert_eq!(stream_synced_count(&mut h), 0);
+  }
+
+  #[test]
+  fn stream_sync_is_reported_again_after_repair() {
+    let mut h = reader_harness(&reliable_qos(), |_| {});
+    inject_samples(&mut h, &[1, 2]);
+    inject_heartbeat_up_to(&mut h, 2, 1);
+    assert_eq!(stream_synced_count(&mut h), 1);
+
+    // Sample 3 is lost, and 4 is declared unavailable.
+    inject_samples(&mut h, &[5]);
+    inject_heartbeat_up_to(&mut h, 5, 2);
+    h.inject_gap(harness_writer(1), &[SequenceNumber::new(4)]);
+    assert_eq!(stream_synced_count(&mut h), 0);
+    inject_samples(&mut h, &[3]);
+    assert_eq!(stream_synced_count(&mut h), 1);
+  }
+
+  #[test]
+  fn empty_writer_is_in_sync_at_first_heartbeat() {
+    let mut h = reader_harness(&reliable_qos(), |_| {});
+    inject_heartbeat_up_to(&mut h, 0, 1);
+    assert_eq!(stream_synced_count(&mut h), 1);
+  }
+
+  // Inject samples 1..=count at 10 Hz, alternating between `instances`.
+  fn publish_at_10_hz(
+    h: &mut ReaderHarness,
+    clock: &ManualClock,
+    count: i64,
+    instances: &[Option<KeyHash>],
+  ) {
+    let start = clock.now();
+    for sn in 1..=count {
+      clock.set(start + Duration::from_millis(100 * (sn - 1)));
+      let mut options = WriteOptionsBuilder::new();
+      if let Some(kh) = instances[(sn - 1) as usize % instances.len()] {
+        options = options.key_hash(kh);
+      }
+      let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![sn as u8; 4]);
+      h.inject_data_with_options(
+        harness_writer(1),
+        SequenceNumber::new(sn),
+        payload,
+        options.build(),
+      );
+    }
+  }
+
+  fn time_based_filter_harness(minimum_separation: Duration) -> (ReaderHarness, Arc<ManualClock>) {
+    // KeepAll, so that the cache shows all samples that passed the filter.
+    let qos = QosPolicyBuilder::new()
+      .time_based_filter(policy::TimeBasedFilter { minimum_separation })
+      .history(policy::History::KeepAll)
+      .build();
+    let clock = ManualClock::new();
+    let h = reader_harness(&qos, |h| h.set_clock(clock.clone()));
+    (h, clock)
+  }
+
+  #[test]
+  fn samples_closer_than_minimum_separation_are_dropped() {
+    let (mut h, clock) = time_based_filter_harness(Duration::from_millis(500));
+    // 2 seconds at 10 Hz is seen at 2 Hz.
+    publish_at_10_hz(&mut h, &clock, 20, &[None]);
+    assert_eq!(delivered(&mut h), vec![1, 6, 11, 16]);
+  }
+
+  #[test]
+  fn instances_are_filtered_separately() {
+    let (mut h, clock) = time_based_filter_harness(Duration::from_millis(300));
+    // Each instance gets a sample every 200 ms.
+    publish_at_10_hz(&mut h, &clock, 12, &[Some(key_hash(1)), Some(key_hash(2))]);
+    assert_eq!(delivered(&mut h), vec![1, 2, 5, 6, 9, 10]);
+  }
+
+  #[test]
+  fn zero_separation_delivers_everything() {
+    let (mut h, clock) = time_based_filter_harness(Duration::ZERO);
+    publish_at_10_hz(&mut h, &clock, 5, &[None]);
+    assert_eq!(delivered(&mut h), vec![1, 2, 3, 4, 5]);
+  }
+
+  // Inject one sample per `(instance, payload byte)`, numbered from 1.
+  fn publish_payloads(h: &mut ReaderHarness, samples: &[(Option<u8>, u8)]) {
+    for (sn, (instance, byte)) in (1..).zip(samples) {
+      let mut options = WriteOptionsBuilder::new();
+      if let Some(i) = instance {
+        options = options.key_hash(key_hash(*i));
+      }
+      let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![*byte; 4]);
+      h.inject_data_with_options(
+        harness_writer(1),
+        SequenceNumber::new(sn),
+        payload,
+        options.build(),
+      );
+    }
+  }
+
+  #[test]
+  fn repeated_payloads_are_flagged_only_when_enabled() {
+    let samples = [(None, 1), (None, 1), (None, 2), (None, 2), (None, 1)];
+
+    let mut h = reader_harness(&QosPolicies::qos_none(), |_| {});
+    publish_payloads(&mut h, &samples);
+    assert_eq!(
+      as_i64(h.content_duplicate_sequence_numbers()),
+      Vec::<i64>::new()
+    );
+
+    let mut h = reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_detect_content_duplicates(true);
+    });
+    publish_payloads(&mut h, &samples);
+    assert_eq!(h.delivered_sequence_numbers().len(), 5);
+    assert_eq!(as_i64(h.content_duplicate_sequence_numbers()), vec![2, 4]);
+  }
+
+  #[test]
+  fn instances_are_compared_separately_for_content_duplicates() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_detect_content_duplicates(true);
+    });
+    publish_payloads(
+      &mut h,
+      &[(Some(1), 1), (Some(2), 1), (Some(1), 1), (Some(2), 2)],
+    );
+    assert_eq!(as_i64(h.content_duplicate_sequence_numbers()), vec![3]);
+  }
+
+  fn duplicate_acceptance_harness(acceptance: DuplicateAcceptance) -> ReaderHarness {
+    reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_duplicate_acceptance(acceptance);
+    })
+  }
+
+  // Inject sample `sn` with a payload of `byte`s.
+  fn inject_sample_bytes(h: &mut ReaderHarness, sn: i64, byte: u8) {
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![byte; 4]);
+    h.inject_data(harness_writer(1), SequenceNumber::new(sn), payload);
+  }
+
+  #[test]
+  fn duplicates_are_discarded_by_default() {
+    let mut h = duplicate_acceptance_harness(DuplicateAcceptance::default());
+    inject_sample_bytes(&mut h, 1, 1);
+    inject_sample_bytes(&mut h, 1, 2);
+    assert_eq!(delivered(&mut h), vec![1]);
+    let payload = h.received_payload(SequenceNumber::new(1)).unwrap();
+    assert_eq!(payload.value, vec![1; 4]);
+  }
+
+  #[test]
+  fn accepted_duplicate_is_delivered_again() {
+    let mut h = duplicate_acceptance_harness(DuplicateAcceptance::Accept);
+    inject_sample_bytes(&mut h, 1, 1);
+    inject_sample_bytes(&mut h, 2, 2);
+    inject_sample_bytes(&mut h, 1, 3);
+    assert_eq!(delivered(&mut h), vec![1, 2, 1]);
+    // The SequenceNumber refers to the resent sample now.
+    let payload = h.received_payload(SequenceNumber::new(1)).unwrap();
+    assert_eq!(payload.value, vec![3; 4]);
+  }
+
+  #[test]
+  fn accepted_duplicate_does_not_move_reliable_mark() {
+    let mut h = duplicate_acceptance_harness(DuplicateAcceptance::Accept);
+    inject_sample_bytes(&mut h, 1, 1);
+    inject_sample_bytes(&mut h, 3, 3);
+    assert_eq!(as_i64(h.reliably_delivered_sequence_numbers()), vec![1]);
+
+    // Sample 2 is still missing, so 3 must stay unreadable in order.
+    inject_sample_bytes(&mut h, 3, 3);
+    assert_eq!(delivered(&mut h), vec![1, 3, 3]);
+    assert_eq!(as_i64(h.reliably_delivered_sequence_numbers()), vec![1]);
+
+    inject_sample_bytes(&mut h, 2, 2);
+    assert_eq!(
+      as_i64(h.reliably_delivered_sequence_numbers()),
+      vec![1, 2, 3]
+    );
+  }
+
+  #[test]
+  fn predicate_selects_duplicates() {
+    let accept_even = DuplicateAcceptance::AcceptIf(Arc::new(|_, sn| i64::from(sn) % 2 == 0));
+    let mut h = duplicate_acceptance_harness(accept_even);
+    for sn in 1..=4 {
+      inject_sample_bytes(&mut h, sn, 0);
+    }
+    for sn in 1..=4 {
+      inject_sample_bytes(&mut h, sn, 1);
+    }
+    assert_eq!(delivered(&mut h), vec![1, 2, 3, 4, 2, 4]);
+  }
+
+  fn exclusive_qos(strength: i32, deadline: Option<Duration>) -> QosPolicies {
+    // KeepAll, so that the cache shows all samples that were delivered.
+    let b = QosPolicyBuilder::new()
+      .ownership(policy::Ownership::Exclusive { strength })
+      .history(policy::History::KeepAll);
+    match deadline {
+      Some(d) => b.deadline(policy::Deadline(d)),
+      None => b,
+    }
+    .build()
+  }
+
+  // A Reader matched with writer 10 of strength 10 and writer 20 of strength
+  // 20.
+  fn exclusive_ownership_harness(deadline: Option<Duration>) -> (ReaderHarness, Arc<ManualClock>) {
+    let mut h = ReaderHarness::new("test_topic", &exclusive_qos(0, deadline)).unwrap();
+    let clock = ManualClock::new();
+    h.set_clock(clock.clone());
+    for (id, strength) in [(10, 10), (20, 20)] {
+      h.add_matched_writer(
+        harness_writer(id),
+        vec![Locator::from(SocketAddr::from(([192, 0, 2, id], 7411)))],
+        &exclusive_qos(strength, deadline),
+      );
+    }
+    (h, clock)
+  }
+
+  // Writer `id` writes sample `sn` of the single instance.
+  fn write_owned_instance(h: &mut ReaderHarness, id: u8, sn: i64) {
+    let options = WriteOptionsBuilder::new().key_hash(key_hash(1)).build();
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![id; 4]);
+    h.inject_data_with_options(
+      harness_writer(id),
+      SequenceNumber::new(sn),
+      payload,
+      options,
+    );
+  }
+
+  #[test]
+  fn only_the_strongest_writer_is_delivered_until_it_is_removed() {
+    let (mut h, _clock) = exclusive_ownership_harness(None);
+    // Writer 10 uses sequence numbers 1.., writer 20 uses 101..
+    write_owned_instance(&mut h, 10, 1);
+    write_owned_instance(&mut h, 20, 101);
+    write_owned_instance(&mut h, 10, 2);
+    write_owned_instance(&mut h, 20, 102);
+    write_owned_instance(&mut h, 10, 3);
+    assert_eq!(delivered(&mut h), vec![1, 101, 102]);
+
+    h.remove_matched_writer(harness_writer(20));
+    write_owned_instance(&mut h, 10, 4);
+    write_owned_instance(&mut h, 10, 5);
+    assert_eq!(delivered(&mut h), vec![1, 101, 102, 4, 5]);
+  }
+
+  #[test]
+  fn ownership_fails_over_when_the_owner_misses_the_deadline() {
+    let (mut h, clock) = exclusive_ownership_harness(Some(Duration::from_secs(1)));
+    let start = clock.now();
+    let at = |ms| clock.set(start + Duration::from_millis(ms));
+
+    at(0);
+    write_owned_instance(&mut h, 20, 101);
+    at(500);
+    write_owned_instance(&mut h, 10, 1);
+    // The owner has not written the instance within the deadline.
+    at(1600);
+    write_owned_instance(&mut h, 10, 2);
+    // The stronger writer takes the instance back.
+    at(1700);
+    write_owned_instance(&mut h, 20, 102);
+    at(1800);
+    write_owned_instance(&mut h, 10, 3);
+    assert_eq!(delivered(&mut h), vec![101, 2, 102]);
+  }
+
+  // A Reader with a deadline of 100 ms
+  fn requested_deadline_harness(
+    configure: impl FnOnce(&mut ReaderHarness),
+  ) -> (ReaderHarness, Arc<ManualClock>) {
+    let qos = QosPolicyBuilder::new()
+      .deadline(policy::Deadline(Duration::from_millis(100)))
+      .build();
+    let clock = ManualClock::new();
+    let h = reader_harness(&qos, |h| {
+      h.set_clock(clock.clone());
+      configure(h);
+    });
+    (h, clock)
+  }
+
+  // Run the deadline checks for a while and count the misses. The clock does
+  // not advance meanwhile.
+  fn deadline_misses(h: &mut ReaderHarness) -> usize {
+    h.run_timers_for(StdDuration::from_millis(150));
+    std::iter::from_fn(|| h.try_recv_status())
+      .filter(|status| matches!(status, DataReaderStatus::RequestedDeadlineMissed { .. }))
+      .count()
+  }
+
+  #[test]
+  fn newly_matched_writer_has_the_deadline_to_write() {
+    let (mut h, clock) = requested_deadline_harness(|_| {});
+    assert_eq!(deadline_misses(&mut h), 0);
+    let matched_at = clock.now();
+    clock.set(matched_at + Duration::from_millis(150));
+    assert!(deadline_misses(&mut h) > 0);
+  }
+
+  // Count the deadline misses after a sample written 150 ms before it was
+  // received.
+  fn deadline_misses_after_late_sample(timestamp: DeadlineTimestamp) -> usize {
+    let (mut h, clock) = requested_deadline_harness(|h| h.set_deadline_timestamp(timestamp));
+    let options = WriteOptionsBuilder::new()
+      .source_timestamp(clock.now() - Duration::from_millis(150))
+      .build();
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![1; 4]);
+    h.inject_data_with_options(harness_writer(1), SequenceNumber::new(1), payload, options);
+    deadline_misses(&mut h)
+  }
+
+  #[test]
+  fn deadline_is_measured_from_reception_by_default() {
+    assert_eq!(
+      deadline_misses_after_late_sample(DeadlineTimestamp::default()),
+      0
+    );
+  }
+
+  #[test]
+  fn deadline_can_be_measured_from_source_timestamp() {
+    assert!(deadline_misses_after_late_sample(DeadlineTimestamp::Source) > 0);
+  }
+
+  #[test]
+  fn missing_fragments_are_requested() {
+    let mut h = reader_harness(&reliable_qos(), |_| {});
+    inject_frags(&mut h, &[1, 3]);
+    h.take_sent();
+    assert!(h.delivered_sequence_numbers().is_empty());
+
+    h.inject_heartbeat_frag(harness_writer(1), SequenceNumber::new(1), 4, 1);
+    assert_eq!(sent_nackfrags(&mut h), vec![(1, vec![2, 4])]);
+
+    // Duplicate count
+    h.inject_heartbeat_frag(harness_writer(1), SequenceNumber::new(1), 4, 1);
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+
+    // Only fragments the Writer has announced are requested.
+    h.inject_heartbeat_frag(harness_writer(1), SequenceNumber::new(1), 2, 2);
+    assert_eq!(sent_nackfrags(&mut h), vec![(1, vec![2])]);
+  }
+
+  #[test]
+  fn heartbeat_frag_of_unknown_sample_or_to_best_effort_reader_is_ignored() {
+    let mut h = reader_harness(&reliable_qos(), |_| {});
+    inject_frags(&mut h, &[1, 3]);
+    h.take_sent();
+    h.inject_heartbeat_frag(harness_writer(1), SequenceNumber::new(2), 4, 1);
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+
+    let mut h = reader_harness(&QosPolicies::qos_none(), |_| {});
+    inject_frags(&mut h, &[1, 3]);
+    h.take_sent();
+    h.inject_heartbeat_frag(harness_writer(1), SequenceNumber::new(1), 4, 1);
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+  }
+
+  fn proactive_nackfrag_harness(qos: &QosPolicies, delay: Option<StdDuration>) -> ReaderHarness {
+    reader_harness(qos, |h| h.set_proactive_nackfrag_delay(delay))
+  }
+
+  #[test]
+  fn fragment_gap_is_requested_after_delay() {
+    let mut h = proactive_nackfrag_harness(&reliable_qos(), Some(StdDuration::from_millis(20)));
+    inject_frags(&mut h, &[1, 3]);
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+
+    // Fragment 4 may not have been sent yet, so only the gap is requested.
+    h.run_timers_for(StdDuration::from_millis(100));
+    assert_eq!(sent_nackfrags(&mut h), vec![(1, vec![2])]);
+    // Once per gap
+    h.run_timers_for(StdDuration::from_millis(100));
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+  }
+
+  #[test]
+  fn filled_fragment_gaps_are_not_requested() {
+    let mut h = proactive_nackfrag_harness(&reliable_qos(), Some(StdDuration::from_millis(20)));
+    inject_frags(&mut h, &[1, 3, 2]);
+    h.run_timers_for(StdDuration::from_millis(100));
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+  }
+
+  #[test]
+  fn heartbeat_frag_postpones_proactive_nackfrag() {
+    let mut h = proactive_nackfrag_harness(&reliable_qos(), Some(StdDuration::from_millis(100)));
+    inject_frags(&mut h, &[1, 3]);
+    std::thread::sleep(StdDuration::from_millis(60));
+    h.inject_heartbeat_frag(harness_writer(1), SequenceNumber::new(1), 4, 1);
+    assert_eq!(sent_nackfrags(&mut h), vec![(1, vec![2, 4])]);
+
+    // The Writer was just asked, so the gap is not requested at the original
+    // deadline.
+    h.run_timers_for(StdDuration::from_millis(60));
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+    h.run_timers_for(StdDuration::from_millis(150));
+    assert_eq!(sent_nackfrags(&mut h), vec![(1, vec![2])]);
+  }
+
+  #[test]
+  fn proactive_nackfrag_is_disabled_by_default_and_for_best_effort_readers() {
+    let mut h = proactive_nackfrag_harness(&reliable_qos(), None);
+    inject_frags(&mut h, &[1, 3]);
+    h.run_timers_for(StdDuration::from_millis(50));
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+
+    let mut h =
+      proactive_nackfrag_harness(&QosPolicies::qos_none(), Some(StdDuration::from_millis(20)));
+    inject_frags(&mut h, &[1, 3]);
+    h.run_timers_for(StdDuration::from_millis(50));
+    assert_eq!(sent_nackfrags(&mut h), vec![]);
+  }
+
+  fn inject_heartbeats(h: &mut ReaderHarness, last_sns: &[i64]) {
+    for (count, &last_sn) in (1..).zip(last_sns) {
+      h.inject_heartbeat(
+        harness_writer(1),
+        SequenceNumber::new(1),
+        SequenceNumber::new(last_sn),
+        count,
+        false,
+      );
+    }
+  }
+
+  #[test]
+  fn heartbeat_response_is_delayed_and_merged() {
+    let mut h = reader_harness(&reliable_qos(), |h| {
+      h.set_heartbeat_response_delay(StdDuration::from_millis(50));
+    });
+    let start = Instant::now();
+    inject_heartbeats(&mut h, &[2, 3]);
+    assert_eq!(sent_acknacks(&mut h), vec![]);
+
+    // One ACKNACK answers both HEARTBEATs, no sooner than half the delay.
+    let mut acknacks = Vec::new();
+    while acknacks.is_empty() {
+      assert!(start.elapsed() < StdDuration::from_millis(200));
+      h.run_timers_for(StdDuration::from_millis(5));
+      acknacks = sent_acknacks(&mut h);
+    }
+    assert!(start.elapsed() >= StdDuration::from_millis(25));
+    assert_eq!(acknacks, vec![(1, vec![1, 2, 3])]);
+    h.run_timers_for(StdDuration::from_millis(100));
+    assert_eq!(sent_acknacks(&mut h), vec![]);
+  }
+
+  #[test]
+  fn zero_heartbeat_response_delay_responds_at_once() {
+    let mut h = reader_harness(&reliable_qos(), |h| {
+      h.set_heartbeat_response_delay(StdDuration::ZERO);
+    });
+    inject_heartbeats(&mut h, &[2]);
+    assert_eq!(sent_acknacks(&mut h), vec![(1, vec![1, 2])]);
+  }
+
+  fn heartbeat_suppression_harness(suppression: StdDuration) -> ReaderHarness {
+    reader_harness(&reliable_qos(), |h| {
+      h.set_heartbeat_suppression_duration(suppression);
+    })
+  }
+
+  #[test]
+  fn heartbeats_within_suppression_window_are_answered_once() {
+    let mut h = heartbeat_suppression_harness(StdDuration::from_millis(200));
+    inject_heartbeats(&mut h, &[2, 3]);
+    assert_eq!(sent_acknacks(&mut h), vec![(1, vec![1, 2])]);
+  }
+
+  #[test]
+  fn suppressed_heartbeat_response_is_sent_after_window() {
+    let mut h = heartbeat_suppression_harness(StdDuration::from_millis(50));
+    inject_heartbeats(&mut h, &[2, 3, 4]);
+    assert_eq!(sent_acknacks(&mut h), vec![(1, vec![1, 2])]);
+
+    // The HEARTBEATs within the window are answered together at its end.
+    h.run_timers_for(StdDuration::from_millis(100));
+    assert_eq!(sent_acknacks(&mut h), vec![(1, vec![1, 2, 3, 4])]);
+  }
+
+  #[test]
+  fn zero_suppression_answers_every_heartbeat() {
+    let mut h = heartbeat_suppression_harness(StdDuration::ZERO);
+    inject_heartbeats(&mut h, &[2, 3]);
+    assert_eq!(
+      sent_acknacks(&mut h),
+      vec![(1, vec![1, 2]), (1, vec![1, 2, 3])]
+    );
+  }
+
+  fn reassembly_failures(h: &ReaderHarness) -> Vec<(GUID, i64, usize)> {
+    std::iter::from_fn(|| h.try_recv_status())
+      .filter_map(|status| match status {
+        DataReaderStatus::SampleReassemblyFailed {
+          writer,
+          seq,
+          missing_count,
+        } => Some((writer, i64::from(seq), missing_count)),
+        _ => None,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn stuck_sample_is_dropped_without_further_fragments() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_fragment_assembly(FragmentAssembly {
+        timeout: StdDuration::from_millis(30),
+        gc_interval: StdDuration::from_millis(10),
+      });
+    });
+    inject_frags(&mut h, &[1, 3]);
+    assert_eq!(reassembly_failures(&h), vec![]);
+
+    // No more fragments arrive, so only the timer collects the sample.
+    h.run_timers_for(StdDuration::from_millis(150));
+    assert_eq!(reassembly_failures(&h), vec![(harness_writer(1), 1, 2)]);
+
+    // The remaining fragments start a new sample, which is not completed.
+    inject_frags(&mut h, &[2, 4]);
+    assert!(h.delivered_sequence_numbers().is_empty());
+  }
+
+  #[test]
+  fn completed_and_recent_fragmented_samples_are_kept() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |h| {
+      h.set_fragment_assembly(FragmentAssembly::default());
+    });
+    inject_frags(&mut h, &[1, 3]);
+    h.run_timers_for(StdDuration::from_millis(50));
+    assert_eq!(reassembly_failures(&h), vec![]);
+
+    inject_frags(&mut h, &[2, 4]);
+    assert_eq!(delivered(&mut h), vec![1]);
+    assert_eq!(reassembly_failures(&h), vec![]);
+  }
+
+  #[test]
+  fn paused_reliable_reader_neither_stores_nor_acknowledges() {
+    let mut h = reader_harness(&reliable_qos(), |_| {});
+    inject_samples(&mut h, &[1]);
+    h.set_paused(true);
+    inject_samples(&mut h, &[2]);
+    inject_heartbeats(&mut h, &[2]);
+    assert_eq!(delivered(&mut h), vec![1]);
+    assert_eq!(sent_acknacks(&mut h), vec![]);
+
+    // After resume, the next HEARTBEAT gets the missed sample repaired.
+    h.set_paused(false);
+    h.inject_heartbeat(
+      harness_writer(1),
+      SequenceNumber::new(1),
+      SequenceNumber::new(2),
+      2,
+      false,
+    );
+    assert_eq!(sent_acknacks(&mut h), vec![(2, vec![2])]);
+    inject_samples(&mut h, &[2]);
+    assert_eq!(delivered(&mut h), vec![1, 2]);
+  }
+
+  #[test]
+  fn paused_best_effort_reader_drops_samples() {
+    let mut h = reader_harness(&QosPolicies::qos_none(), |_| {});
+    h.set_paused(true);
+    inject_samples(&mut h, &[1]);
+    h.set_paused(false);
+    inject_samples(&mut h, &[2]);
+    assert_eq!(delivered(&mut h), vec![2]);
+  }
+
+  #[test]
+  fn dropping_a_sample_over_the_instance_limit_is_reported() {
+    let qos = QosPolicyBuilder::new()
+      .history(policy::History::KeepAll)
+      .resource_limits(policy::ResourceLimits {
+        max_samples: 64,
+        max_instances: 64,
+        max_samples_per_instance: 2,
+      })
+      .build();
+    let mut h = reader_harness(&qos, |_| {});
+    while h.try_recv_status().is_some() {}
+
+    for sn in 1..=3 {
+      let options = WriteOptionsBuilder::new().key_hash(key_hash(1)).build();
+      let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![sn as u8; 4]);
+      h.inject_data_with_options(harness_writer(1), SequenceNumber::new(sn), payload, options);
+    }
+
+    assert_eq!(delivered(&mut h), vec![2, 3]);
+    match h.try_recv_status() {
+      Some(DataReaderStatus::SampleRejected { count, last_reason }) => {
+        assert_eq!(count.count(), 1);
+        assert_eq!(
+          last_reason,
+          SampleRejectedStatusKind::BySamplesPerInstanceLimit
+        );
+      }
+      other => panic!("Expected SampleRejected, got {other:?}"),
+    }
   }
 }
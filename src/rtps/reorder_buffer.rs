@@ -0,0 +1,207 @@
+// Reordering of samples from one BestEffort Writer, see the participant-builder
+// `best_effort_reordering` knob.
+//
+// A sample that arrives in sequence is delivered at once. A sample that arrives
+// after a gap is held until the gap is filled, but at most `max_delay` and for
+// at most `max_samples` held samples. Then the gap is given up as lost, and the
+// held samples are delivered in sequence. A sample arriving after its gap was
+// given up is too late to be delivered in order, and is dropped.
+
+use std::{
+  collections::BTreeMap,
+  time::{Duration, Instant},
+};
+
+use crate::{dds::participant::BestEffortReordering, structure::sequence_number::SequenceNumber};
+
+struct HeldSample<T> {
+  sample: T,
+  held_since: Instant,
+}
+
+pub(crate) struct ReorderBuffer<T> {
+  max_delay: Duration,
+  max_samples: usize,
+  // The next SequenceNumber to deliver. None until the first sample, which is
+  // delivered as it arrives.
+  next: Option<SequenceNumber>,
+  held: BTreeMap<SequenceNumber, HeldSample<T>>,
+}
+
+impl<T> ReorderBuffer<T> {
+  pub fn new(policy: BestEffortReordering) -> Self {
+    Self {
+      max_delay: policy.max_delay,
+      max_samples: policy.max_samples.max(1),
+      next: None,
+      held: BTreeMap::new(),
+    }
+  }
+
+  /// Add a received sample. Returns the samples that can now be delivered, in
+  /// sequence.
+  pub fn insert(
+    &mut self,
+    sn: SequenceNumber,
+    sample: T,
+    now: Instant,
+  ) -> Vec<(SequenceNumber, T)> {
+    let mut deliverable = Vec::new();
+    match self.next {
+      Some(next) if sn < next => return deliverable, // too late, or duplicate
+      Some(next) if sn > next || !self.held.is_empty() => {
+        self.held.insert(
+          sn,
+          HeldSample {
+            sample,
+            held_since: now,
+          },
+        );
+        self.release_in_sequence(&mut deliverable);
+        while self.held.len() > self.max_samples {
+          self.skip_gap(&mut deliverable);
+        }
+      }
+      _ => {
+        self.next = Some(sn + SequenceNumber::new(1));
+        deliverable.push((sn, sample));
+      }
+    }
+    deliverable
+  }
+
+  /// Give up the gaps that have held a sample for `max_delay`. Returns the
+  /// samples that can now be delivered, in sequence.
+  pub fn flush_expired(&mut self, now: Instant) -> Vec<(SequenceNumber, T)> {
+    let mut deliverable = Vec::new();
+    while self
+      .flush_deadline()
+      .is_some_and(|deadline| deadline <= now)
+    {
+      self.skip_gap(&mut deliverable);
+    }
+    deliverable
+  }
+
+  /// Deliver all held samples in sequence, regardless of gaps.
+  pub fn flush_all(&mut self) -> Vec<(SequenceNumber, T)> {
+    let mut deliverable = Vec::new();
+    while !self.held.is_empty() {
+      self.skip_gap(&mut deliverable);
+    }
+    deliverable
+  }
+
+  /// When the oldest held sample has waited for `max_delay`.
+  pub fn flush_deadline(&self) -> Option<Instant> {
+    self
+      .held
+      .values()
+      .map(|h| h.held_since)
+      .min()
+      .map(|oldest| oldest + self.max_delay)
+  }
+
+  // Consider the samples before the first held one lost.
+  fn skip_gap(&mut self, deliverable: &mut Vec<(SequenceNumber, T)>) {
+    if let Some((&first, _)) = self.held.first_key_value() {
+      self.next = Some(first);
+      self.release_in_sequence(deliverable);
+    }
+  }
+
+  fn release_in_sequence(&mut self, deliverable: &mut Vec<(SequenceNumber, T)>) {
+    while let Some(next) = self.next {
+      match self.held.remove(&next) {
+        Some(held) => {
+          deliverable.push((next, held.sample));
+          self.next = Some(next + SequenceNumber::new(1));
+        }
+        None => break,
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn buffer(max_samples: usize) -> ReorderBuffer<i64> {
+    ReorderBuffer::new(BestEffortReordering {
+      max_delay: Duration::from_millis(10),
+      max_samples,
+    })
+  }
+
+  fn insert(b: &mut ReorderBuffer<i64>, sn: i64, now: Instant) -> Vec<i64> {
+    sns(b.insert(SequenceNumber::new(sn), sn, now))
+  }
+
+  fn sns(delivered: Vec<(SequenceNumber, i64)>) -> Vec<i64> {
+    delivered
+      .into_iter()
+      .map(|(sn, sample)| {
+        assert_eq!(SequenceNumber::new(sample), sn);
+        sample
+      })
+      .collect()
+  }
+
+  #[test]
+  fn in_sequence_samples_are_delivered_at_once() {
+    let mut b = buffer(8);
+    let now = Instant::now();
+    assert_eq!(insert(&mut b, 5, now), vec![5]);
+    assert_eq!(insert(&mut b, 6, now), vec![6]);
+    assert_eq!(b.flush_deadline(), None);
+  }
+
+  #[test]
+  fn filled_gap_releases_held_samples_in_order() {
+    let mut b = buffer(8);
+    let now = Instant::now();
+    assert_eq!(insert(&mut b, 1, now), vec![1]);
+    assert_eq!(insert(&mut b, 4, now), Vec::<i64>::new());
+    assert_eq!(insert(&mut b, 3, now), Vec::<i64>::new());
+    assert_eq!(b.flush_deadline(), Some(now + Duration::from_millis(10)));
+    assert_eq!(insert(&mut b, 2, now), vec![2, 3, 4]);
+    assert_eq!(b.flush_deadline(), None);
+  }
+
+  #[test]
+  fn gap_is_given_up_after_max_delay() {
+    let mut b = buffer(8);
+    let t0 = Instant::now();
+    insert(&mut b, 1, t0);
+    insert(&mut b, 3, t0);
+    insert(&mut b, 6, t0 + Duration::from_millis(5));
+
+    assert!(sns(b.flush_expired(t0 + Duration::from_millis(9))).is_empty());
+    // 2 is lost; 3 was held long enough, and 6 waits for 4 and 5.
+    assert_eq!(
+      sns(b.flush_expired(t0 + Duration::from_millis(10))),
+      vec![3]
+    );
+    assert_eq!(b.flush_deadline(), Some(t0 + Duration::from_millis(15)));
+    assert_eq!(insert(&mut b, 4, t0), vec![4]);
+    // Too late to be delivered in order.
+    assert_eq!(insert(&mut b, 2, t0), Vec::<i64>::new());
+    assert_eq!(
+      sns(b.flush_expired(t0 + Duration::from_millis(15))),
+      vec![6]
+    );
+    assert_eq!(insert(&mut b, 5, t0), Vec::<i64>::new());
+  }
+
+  #[test]
+  fn gap_is_given_up_when_too_many_samples_are_held() {
+    let mut b = buffer(2);
+    let now = Instant::now();
+    insert(&mut b, 1, now);
+    assert!(insert(&mut b, 3, now).is_empty());
+    assert!(insert(&mut b, 5, now).is_empty());
+    assert_eq!(insert(&mut b, 6, now), vec![3]);
+    assert_eq!(sns(b.flush_all()), vec![5, 6]);
+  }
+}
@@ -1,7 +1,7 @@
 use std::{
-  cmp::max,
+  cmp::{max, min},
   collections::{BTreeMap, BTreeSet},
-  net::SocketAddr,
+  time::{Duration, Instant},
 };
 
 use bit_vec::BitVec;
@@ -9,10 +9,13 @@ use bit_vec::BitVec;
 use log::{debug, error, info, trace, warn};
 
 use crate::{
-  dds::{participant::DomainParticipant, qos::QosPolicies},
+  dds::{
+    participant::{DomainParticipant, HeartbeatBackoff},
+    qos::QosPolicies,
+  },
   discovery::sedp_messages::DiscoveredReaderData,
   messages::submessages::submessage::AckSubmessage,
-  network::util::{path_mtu_payload_for_peer, IfAddr},
+  network::{path_mtu::PathMtu, util::IfAddr},
   rtps::{
     constant::*,
     transmit::{InterfaceObservations, InterfaceSelector, RouteSelector, SendRoute},
@@ -87,6 +90,20 @@ pub(crate) struct RtpsReaderProxy {
   // multi-fragment DATAFRAG datagrams. An overestimate only causes IP
   // fragmentation, never data loss.
   max_datagram_payload: usize,
+  // The path MTU `max_datagram_payload` was derived from.
+  path_mtu: u32,
+
+  // HEARTBEAT backoff state, used only if the Writer has a HeartbeatBackoff
+  // policy. heartbeat_interval is None until the first HEARTBEAT after an
+  // ACKNACK, and next_heartbeat is None when a HEARTBEAT is due immediately.
+  heartbeat_interval: Option<Duration>,
+  next_heartbeat: Option<Instant>,
+  // Statistics: periodic HEARTBEATs sent to this Reader under backoff
+  heartbeats_sent: u64,
+
+  // Samples repaired by unicast since the last periodic HEARTBEAT. Limited
+  // by the large fan-out policy of the Writer.
+  unicast_repairs: usize,
 }
 
 impl RtpsReaderProxy {
@@ -107,6 +124,11 @@ impl RtpsReaderProxy {
       frags_requested: BTreeMap::new(),
       send_route: SendRoute::default(),
       max_datagram_payload: FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE,
+      path_mtu: PathMtu::DEFAULT_MTU,
+      heartbeat_interval: None,
+      next_heartbeat: None,
+      heartbeats_sent: 0,
+      unicast_repairs: 0,
     }
   }
 
@@ -232,6 +254,11 @@ impl RtpsReaderProxy {
       frags_requested: BTreeMap::new(),
       send_route: SendRoute::default(),
       max_datagram_payload: FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE,
+      path_mtu: PathMtu::DEFAULT_MTU,
+      heartbeat_interval: None,
+      next_heartbeat: None,
+      heartbeats_sent: 0,
+      unicast_repairs: 0,
     }
   }
 
@@ -306,6 +333,11 @@ impl RtpsReaderProxy {
       frags_requested: BTreeMap::new(),
       send_route: SendRoute::default(),
       max_datagram_payload: FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE,
+      path_mtu: PathMtu::DEFAULT_MTU,
+      heartbeat_interval: None,
+      next_heartbeat: None,
+      heartbeats_sent: 0,
+      unicast_repairs: 0,
     }
   }
 
@@ -314,6 +346,20 @@ impl RtpsReaderProxy {
     self.send_route
   }
 
+  /// The multicast locator that multicast messages to this reader are sent
+  /// to, if any. Readers with the same group share multicast datagrams.
+  pub fn multicast_group(&self) -> Option<Locator> {
+    if self.send_route.fallback {
+      self
+        .multicast_locator_list
+        .iter()
+        .copied()
+        .find(Locator::is_udp)
+    } else {
+      self.send_route.multicast.map(|(group, _interface)| group)
+    }
+  }
+
   /// Recompute this reader's [`SendRoute`] from its advertised locators and the
   /// per-participant [`InterfaceObservations`], using `selector`.
   pub fn resolve_send_route(
@@ -343,7 +389,7 @@ impl RtpsReaderProxy {
 
   /// Recompute this reader's
   /// [`max_datagram_payload`](Self::max_datagram_payload) from its advertised
-  /// unicast locators and the local interface table.
+  /// unicast locators, the configured `path_mtu` and the local interface table.
   ///
   /// We take the minimum budget over all of the reader's unicast locators
   /// (including the loopback bucket) so that whichever path the [`SendRoute`]
@@ -351,8 +397,8 @@ impl RtpsReaderProxy {
   /// (typically large) loopback-interface MTU, so it never lowers the minimum;
   /// its only effect is to give a same-host-only peer (which advertises *only*
   /// loopback) the large loopback budget instead of the conservative default.
-  /// When the reader advertises no unicast locators at all, we keep the
-  /// default.
+  /// When the reader advertises no unicast locators at all, we use the
+  /// default path MTU.
   ///
   /// # Design note: current vs. ideal (future work)
   ///
@@ -382,15 +428,23 @@ impl RtpsReaderProxy {
   /// 3. A guarantee that a datagram built with the larger budget is sent to
   ///    *only* that one locator (today the fallback and multicast-to-all paths
   ///    reuse one datagram across many locators).
-  pub fn resolve_path_mtu(&mut self, local_interfaces: &[IfAddr]) {
-    let budget = self
+  pub fn resolve_path_mtu(&mut self, local_interfaces: &[IfAddr], path_mtu: &PathMtu) {
+    self.path_mtu = self
       .unicast_locator_list
       .iter()
       .chain(self.loopback_unicast_locators.iter())
       .filter(|l| l.is_udp())
-      .map(|l| path_mtu_payload_for_peer(local_interfaces, SocketAddr::from(*l).ip()))
-      .min();
-    self.max_datagram_payload = budget.unwrap_or(FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE);
+      .filter_map(Locator::ip_addr)
+      .map(|ip| path_mtu.mtu_for(local_interfaces, ip))
+      .min()
+      .unwrap_or_else(|| path_mtu.default_mtu());
+    self.max_datagram_payload = payload_budget_for_mtu(self.path_mtu);
+  }
+
+  /// The path MTU to this reader, resolved by
+  /// [`resolve_path_mtu`](Self::resolve_path_mtu).
+  pub fn path_mtu(&self) -> u32 {
+    self.path_mtu
   }
 
   pub fn handle_ack_nack(
@@ -398,6 +452,9 @@ impl RtpsReaderProxy {
     ack_submessage: &AckSubmessage,
     last_available: SequenceNumber,
   ) {
+    // The Reader is responsive, so HEARTBEATs to it go at full rate again.
+    self.reset_heartbeat_backoff();
+
     match ack_submessage {
       AckSubmessage::AckNack(acknack) => {
         // Eliminate case that base = 0
@@ -470,6 +527,48 @@ impl RtpsReaderProxy {
     }
   }
 
+  pub fn heartbeat_due(&self, now: Instant) -> bool {
+    self.next_heartbeat.is_none_or(|next| next <= now)
+  }
+
+  pub fn next_heartbeat(&self) -> Option<Instant> {
+    self.next_heartbeat
+  }
+
+  pub fn heartbeats_sent(&self) -> u64 {
+    self.heartbeats_sent
+  }
+
+  pub fn unicast_repair_count(&self) -> usize {
+    self.unicast_repairs
+  }
+
+  pub fn count_unicast_repair(&mut self) {
+    self.unicast_repairs += 1;
+  }
+
+  pub fn reset_unicast_repair_count(&mut self) {
+    self.unicast_repairs = 0;
+  }
+
+  // A periodic HEARTBEAT was sent to this Reader at `now`. Lengthen the
+  // interval to the next one, as the Reader has not responded since the
+  // previous HEARTBEAT.
+  pub fn heartbeat_sent(&mut self, now: Instant, backoff: &HeartbeatBackoff) {
+    let interval = match self.heartbeat_interval {
+      None => backoff.min_interval,
+      Some(previous) => min(previous.mul_f64(backoff.factor), backoff.max_interval),
+    };
+    self.heartbeat_interval = Some(interval);
+    self.next_heartbeat = Some(now + interval);
+    self.heartbeats_sent += 1;
+  }
+
+  pub fn reset_heartbeat_backoff(&mut self) {
+    self.heartbeat_interval = None;
+    self.next_heartbeat = None;
+  }
+
   pub fn insert_pending_gap(&mut self, seq_num: SequenceNumber) {
     self.pending_gap.insert(seq_num);
   }
@@ -742,6 +841,66 @@ mod acknack_tests {
   }
 }
 
+#[cfg(test)]
+mod heartbeat_backoff_tests {
+  use super::*;
+  use crate::{
+    messages::submessages::{ack_nack::AckNack, submessage::AckSubmessage},
+    structure::{guid::GuidPrefix, sequence_number::SequenceNumberSet},
+  };
+
+  // Simulate a periodic heartbeat tick every 10 ms towards a silent reader and
+  // count the HEARTBEATs it gets in each second.
+  fn heartbeats_per_second(
+    rp: &mut RtpsReaderProxy,
+    backoff: &HeartbeatBackoff,
+    start: Instant,
+    seconds: u64,
+  ) -> Vec<u64> {
+    let mut per_second = vec![];
+    for s in 0..seconds {
+      let sent_before = rp.heartbeats_sent();
+      for tick in 0..100 {
+        let now = start + Duration::from_secs(s) + Duration::from_millis(tick * 10);
+        if rp.heartbeat_due(now) {
+          rp.heartbeat_sent(now, backoff);
+        }
+      }
+      per_second.push(rp.heartbeats_sent() - sent_before);
+    }
+    per_second
+  }
+
+  #[test]
+  fn heartbeat_rate_to_silent_reader_decays_and_recovers() {
+    let guid = GUID::new(GuidPrefix::UNKNOWN, EntityId::UNKNOWN);
+    let mut rp = RtpsReaderProxy::new(guid, QosPolicies::default(), false);
+    let backoff = HeartbeatBackoff {
+      min_interval: Duration::from_millis(100),
+      max_interval: Duration::from_secs(2),
+      factor: 2.0,
+    };
+    let start = Instant::now();
+
+    let rates = heartbeats_per_second(&mut rp, &backoff, start, 10);
+    // Sent at 0, 100, 300, 700 ms in the first second, then at 1.5 s, 3.1 s,
+    // and every 2 s (max_interval) after that.
+    assert_eq!(rates, vec![4, 1, 0, 1, 0, 1, 0, 1, 0, 1]);
+
+    // An ACKNACK restores the full rate
+    let ack = AckNack {
+      reader_id: EntityId::UNKNOWN,
+      writer_id: EntityId::UNKNOWN,
+      reader_sn_state: SequenceNumberSet::new_empty(SequenceNumber::from(1)),
+      count: 1,
+    };
+    rp.handle_ack_nack(&AckSubmessage::AckNack(ack), SequenceNumber::from(10));
+    let later = start + Duration::from_secs(10);
+    assert!(rp.heartbeat_due(later));
+    assert_eq!(heartbeats_per_second(&mut rp, &backoff, later, 1), vec![4]);
+  }
+}
+
 #[cfg(test)]
 mod route_tests {
   use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
@@ -1,13 +1,15 @@
 use core::ops::Bound::{Included, Unbounded};
-use std::{cmp::max, collections::BTreeMap};
+use std::{cmp::max, collections::BTreeMap, time::Instant};
 
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 
 use crate::{
+  dds::qos::policy,
   discovery::sedp_messages::DiscoveredWriterData,
   rtps::constant::MAX_TRACKED_CHANGES_PER_WRITER,
   structure::{
+    duration::Duration,
     guid::{EntityId, GUID},
     locator::Locator,
     sequence_number::SequenceNumber,
@@ -64,8 +66,17 @@ pub(crate) struct RtpsWriterProxy {
 
   // The changes map is cleaned on heartbeat messages. The changes no longer available are dropped.
   pub received_heartbeat_count: i32,
+  // Count of the last HEARTBEAT_FRAG, which has a counter of its own.
+  pub received_heartbeatfrag_count: i32,
+
+  // Count of the last HEARTBEAT that announced SequenceNumbers below the ones
+  // we have already received, if no normal HEARTBEAT has arrived after it.
+  regressed_heartbeat_count: Option<i32>,
 
   pub sent_ack_nack_count: i32,
+  // When the Reader last answered a HEARTBEAT of the Writer. HEARTBEATs are
+  // answered again only after the heartbeat suppression duration.
+  pub last_heartbeat_response: Option<Instant>,
 
   ack_base: SequenceNumber, // We can ACK everything before this number.
   // ack_base can be increased from N-1 to N, if we receive DATA with SequenceNumber N-1
@@ -75,6 +86,31 @@ pub(crate) struct RtpsWriterProxy {
   // These are used for quick tracking of
   last_received_sequence_number: SequenceNumber,
   last_received_timestamp: Timestamp,
+  // Source timestamp of the last received change, if it had one
+  last_received_source_timestamp: Option<Timestamp>,
+  // When the Reader matched the Writer. The requested DEADLINE is measured
+  // from this until the first change is received.
+  pub matched_at: Timestamp,
+
+  // Highest last SequenceNumber announced by HEARTBEATs of the Writer. None
+  // until the first HEARTBEAT.
+  announced_last_sn: Option<SequenceNumber>,
+  // true, if no change is missing below announced_last_sn or
+  // last_received_sequence_number. See `update_synced`.
+  synced: bool,
+
+  // Liveliness offered by the remote Writer. None, if not known, which is the
+  // case for built-in Writers that are not matched via Discovery.
+  pub offered_liveliness: Option<policy::Liveliness>,
+  // When the liveliness of the remote Writer was last asserted. See DDS Spec
+  // v1.4 Section 2.2.3.11 LIVELINESS.
+  last_liveliness_assertion: Instant,
+  // false, if the liveliness lease has expired since the last assertion
+  pub is_alive: bool,
+
+  // Strength of the EXCLUSIVE ownership offered by the remote Writer, or 0.
+  // See DDS Spec v1.4 Section 2.2.3.10 OWNERSHIP_STRENGTH.
+  pub ownership_strength: i32,
 }
 
 impl RtpsWriterProxy {
@@ -91,12 +127,46 @@ impl RtpsWriterProxy {
       remote_group_entity_id,
       changes: BTreeMap::new(),
       received_heartbeat_count: 0,
+      received_heartbeatfrag_count: 0,
+      regressed_heartbeat_count: None,
       sent_ack_nack_count: 0,
+      last_heartbeat_response: None,
       // Sequence numbering must start at 1.
       // Therefore, we can ACK all sequence numbers below 1 even before receiving anything.
       ack_base: SequenceNumber::new(1),
       last_received_sequence_number: SequenceNumber::new(0),
       last_received_timestamp: Timestamp::INVALID,
+      last_received_source_timestamp: None,
+      matched_at: Timestamp::INVALID,
+      announced_last_sn: None,
+      synced: false,
+      offered_liveliness: None,
+      last_liveliness_assertion: Instant::now(),
+      is_alive: true,
+      ownership_strength: 0,
+    }
+  }
+
+  // Records a liveliness assertion from the remote Writer.
+  // Returns true, if the Writer was previously considered not alive.
+  pub fn assert_liveliness(&mut self) -> bool {
+    self.last_liveliness_assertion = Instant::now();
+    let became_alive = !self.is_alive;
+    self.is_alive = true;
+    became_alive
+  }
+
+  // The instant when the liveliness lease of the Writer will expire, unless
+  // liveliness is asserted again before that. None if the lease is infinite
+  // or unknown.
+  pub fn liveliness_expiry(&self) -> Option<Instant> {
+    let lease_duration = self.offered_liveliness?.duration();
+    if lease_duration == Duration::INFINITE {
+      None
+    } else {
+      self
+        .last_liveliness_assertion
+        .checked_add(lease_duration.to_std())
     }
   }
 
@@ -112,6 +182,64 @@ impl RtpsWriterProxy {
     self.ack_base
   }
 
+  // Check if a HEARTBEAT shows that the remote Writer has restarted with the
+  // same GUID, i.e. its last SequenceNumber is below the ones we have already
+  // received. A single such HEARTBEAT may just be an old one that arrived late,
+  // so the restart is confirmed only by a newer (higher count) one, without a
+  // normal HEARTBEAT in between.
+  pub fn heartbeat_shows_restart(&mut self, hb_last_sn: SequenceNumber, hb_count: i32) -> bool {
+    if hb_last_sn + SequenceNumber::new(1) >= self.ack_base {
+      self.regressed_heartbeat_count = None;
+      return false;
+    }
+    match self.regressed_heartbeat_count {
+      Some(previous) if hb_count > previous => true,
+      _ => {
+        self.regressed_heartbeat_count = Some(hb_count);
+        false
+      }
+    }
+  }
+
+  // The remote Writer has restarted, and its SequenceNumbers start again from
+  // 1. Forget everything known about the earlier ones, as if the Writer had
+  // just been matched.
+  pub fn reset_sequence_numbers(&mut self) {
+    self.changes.clear();
+    self.ack_base = SequenceNumber::new(1);
+    self.last_received_sequence_number = SequenceNumber::new(0);
+    self.last_received_timestamp = Timestamp::INVALID;
+    self.last_received_source_timestamp = None;
+    self.received_heartbeat_count = 0;
+    self.received_heartbeatfrag_count = 0;
+    self.regressed_heartbeat_count = None;
+    self.announced_last_sn = None;
+    self.synced = false;
+  }
+
+  // Record the last SequenceNumber announced by a HEARTBEAT.
+  pub fn heartbeat_announced(&mut self, hb_last_sn: SequenceNumber) {
+    self.announced_last_sn = Some(
+      self
+        .announced_last_sn
+        .map_or(hb_last_sn, |announced| max(announced, hb_last_sn)),
+    );
+  }
+
+  // Re-evaluate if the received changes are contiguous from the start up to
+  // both the last announced and the last received SequenceNumber, i.e. nothing
+  // is missing. Returns true, if that has just become the case.
+  //
+  // Nothing is considered synced before the first HEARTBEAT, because only that
+  // tells how far the Writer is.
+  pub fn update_synced(&mut self) -> bool {
+    let was_synced = self.synced;
+    self.synced = self
+      .announced_last_sn
+      .is_some_and(|announced| self.ack_base > max(announced, self.last_received_sequence_number));
+    self.synced && !was_synced
+  }
+
   pub fn update_contents(&mut self, other: Self) {
     self.unicast_locator_list = other.unicast_locator_list;
     self.multicast_locator_list = other.multicast_locator_list;
@@ -127,6 +255,15 @@ impl RtpsWriterProxy {
     }
   }
 
+  // Like last_change_timestamp, but the source timestamp of the change, if it
+  // had one. Used for DEADLINE with the participant-builder
+  // `deadline_timestamp` knob.
+  pub fn last_change_source_timestamp(&self) -> Option<Timestamp> {
+    self
+      .last_change_timestamp()
+      .map(|received| self.last_received_source_timestamp.unwrap_or(received))
+  }
+
   // Check if we no samples in the received state.
   pub fn no_changes_received(&self) -> bool {
     self.ack_base == SequenceNumber::new(0) && self.changes.is_empty()
@@ -195,6 +332,18 @@ impl RtpsWriterProxy {
     missing_seqnums
   }
 
+  // The smallest SequenceNumber received so far. Received or irrelevant
+  // SequenceNumbers below ack_base are no longer tracked one by one.
+  pub fn first_received_sn(&self) -> Option<SequenceNumber> {
+    if self.ack_base > SequenceNumber::new(1) {
+      return Some(SequenceNumber::new(1));
+    }
+    self
+      .changes
+      .iter()
+      .find_map(|(sn, received)| received.map(|_| *sn))
+  }
+
   // Check if we have already received this sequence number
   // or it has been marked as not_available
   pub fn should_ignore_change(&self, seqnum: SequenceNumber) -> bool {
@@ -202,13 +351,19 @@ impl RtpsWriterProxy {
   }
 
   // This is used to mark DATA as received.
-  pub fn received_changes_add(&mut self, seq_num: SequenceNumber, receive_timestamp: Timestamp) {
+  pub fn received_changes_add(
+    &mut self,
+    seq_num: SequenceNumber,
+    receive_timestamp: Timestamp,
+    source_timestamp: Option<Timestamp>,
+  ) {
     self.changes.insert(seq_num, Some(receive_timestamp));
 
     // Update deadline tracker
     if seq_num > self.last_received_sequence_number {
       self.last_received_sequence_number = seq_num;
       self.last_received_timestamp = receive_timestamp;
+      self.last_received_source_timestamp = source_timestamp;
     }
 
     // We get to advance ack_base if it was equal to seq_num
@@ -327,10 +482,24 @@ impl RtpsWriterProxy {
       multicast_locator_list,
       changes: BTreeMap::new(),
       received_heartbeat_count: 0,
+      received_heartbeatfrag_count: 0,
+      regressed_heartbeat_count: None,
       sent_ack_nack_count: 0,
+      last_heartbeat_response: None,
       ack_base: SequenceNumber::default(),
       last_received_sequence_number: SequenceNumber::new(0),
       last_received_timestamp: Timestamp::INVALID,
+      last_received_source_timestamp: None,
+      matched_at: Timestamp::INVALID,
+      announced_last_sn: None,
+      synced: false,
+      offered_liveliness: discovered_writer_data.publication_topic_data.liveliness,
+      last_liveliness_assertion: Instant::now(),
+      is_alive: true,
+      ownership_strength: match discovered_writer_data.publication_topic_data.ownership {
+        Some(policy::Ownership::Exclusive { strength }) => strength,
+        Some(policy::Ownership::Shared) | None => 0,
+      },
     }
   } // fn
 
@@ -424,7 +593,7 @@ mod bounded_map_tests {
     let mut wp = test_proxy();
     let n: i64 = 50_000;
     for i in 1..=n {
-      wp.received_changes_add(SequenceNumber::new(i), Timestamp::INVALID);
+      wp.received_changes_add(SequenceNumber::new(i), Timestamp::INVALID, None);
     }
     // Everything received in order: ack_base advanced past the last sample.
     assert_eq!(wp.all_ackable_before(), SequenceNumber::new(n + 1));
@@ -444,7 +613,7 @@ mod bounded_map_tests {
     // Sample 1 is never delivered; deliver a long run above it.
     let n: i64 = MAX_TRACKED_CHANGES_PER_WRITER as i64 * 4;
     for i in 2..=n {
-      wp.received_changes_add(SequenceNumber::new(i), Timestamp::INVALID);
+      wp.received_changes_add(SequenceNumber::new(i), Timestamp::INVALID, None);
     }
     assert!(
       wp.tracked_changes_count() <= MAX_TRACKED_CHANGES_PER_WRITER,
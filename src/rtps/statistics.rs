@@ -0,0 +1,76 @@
+// Participant-wide traffic counters, updated by the event loop, the UDP sender,
+// Readers and Writers, and read by the DomainParticipant. Relaxed atomics, so
+// that counting costs next to nothing on the send and receive paths.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::dds::statusevents::ParticipantStatistics;
+
+#[derive(Debug, Default)]
+pub(crate) struct StatisticsCounters {
+  bytes_sent: AtomicU64,
+  messages_sent: AtomicU64,
+  bytes_received: AtomicU64,
+  messages_received: AtomicU64,
+  acknacks_sent: AtomicU64,
+  nackfrags_sent: AtomicU64,
+  heartbeats_sent: AtomicU64,
+  samples_resent: AtomicU64,
+  fragment_assembly_timeouts: AtomicU64,
+  samples_rejected: AtomicU64,
+}
+
+fn add(counter: &AtomicU64, n: u64) {
+  counter.fetch_add(n, Ordering::Relaxed);
+}
+
+impl StatisticsCounters {
+  pub fn message_sent(&self, bytes: usize) {
+    add(&self.messages_sent, 1);
+    add(&self.bytes_sent, bytes as u64);
+  }
+
+  pub fn message_received(&self, bytes: usize) {
+    add(&self.messages_received, 1);
+    add(&self.bytes_received, bytes as u64);
+  }
+
+  pub fn acknack_sent(&self) {
+    add(&self.acknacks_sent, 1);
+  }
+
+  pub fn nackfrags_sent(&self, count: usize) {
+    add(&self.nackfrags_sent, count as u64);
+  }
+
+  pub fn heartbeat_sent(&self) {
+    add(&self.heartbeats_sent, 1);
+  }
+
+  pub fn sample_resent(&self) {
+    add(&self.samples_resent, 1);
+  }
+
+  pub fn fragment_assemblies_timed_out(&self, count: usize) {
+    add(&self.fragment_assembly_timeouts, count as u64);
+  }
+
+  pub fn sample_rejected(&self) {
+    add(&self.samples_rejected, 1);
+  }
+
+  pub fn snapshot(&self) -> ParticipantStatistics {
+    let get = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+    ParticipantStatistics {
+      bytes_sent: get(&self.bytes_sent),
+      messages_sent: get(&self.messages_sent),
+      bytes_received: get(&self.bytes_received),
+      messages_received: get(&self.messages_received),
+      acknacks_sent: get(&self.acknacks_sent),
+      nackfrags_sent: get(&self.nackfrags_sent),
+      heartbeats_sent: get(&self.heartbeats_sent),
+      samples_resent: get(&self.samples_resent),
+      fragment_assembly_timeouts: get(&self.fragment_assembly_timeouts),
+      samples_rejected: get(&self.samples_rejected),
+    }
+  }
+}
@@ -19,6 +19,10 @@ pub(crate) enum DpTimerEvent {
     entity_id: EntityId,
     event: writer::TimedEvent,
   },
+  // The DataReader of this Reader is gone, so the Reader should be removed.
+  DisposeReader {
+    entity_id: EntityId,
+  },
   PreemptiveAcknack,
   CacheGc,
 }
@@ -375,12 +375,7 @@ fn select_unicast(advertised_unicast: &[Locator], obs: &ObservedRoutes) -> Optio
       // Multiple advertised addresses: only pick one if the observed source
       // address disambiguates it. Otherwise stay ambiguous (caller falls back).
       let source_ip = obs.last_source().map(|sa| sa.ip());
-      source_ip.and_then(|ip| {
-        candidates
-          .iter()
-          .copied()
-          .find(|l| SocketAddr::from(*l).ip() == ip)
-      })
+      source_ip.and_then(|ip| candidates.iter().copied().find(|l| l.ip_addr() == Some(ip)))
     }
   }
 }
@@ -0,0 +1,116 @@
+//! Workarounds for known deviations of other RTPS implementations.
+//!
+//! Each workaround ("quirk") has a [`QuirkMode`] in [`VendorQuirks`], which is
+//! configured with
+//! [`DomainParticipantBuilder::vendor_quirks`](crate::DomainParticipantBuilder::vendor_quirks).
+//! In the default `Detect` mode a quirk is applied only to messages whose RTPS
+//! header identifies an affected implementation, so that workarounds do not
+//! hide genuine protocol errors of conformant peers.
+
+use crate::messages::{protocol_version::ProtocolVersion, vendor_id::VendorId};
+
+/// When to apply a workaround.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuirkMode {
+  /// Never apply the workaround.
+  Never,
+  /// Apply the workaround to peers detected to need it.
+  #[default]
+  Detect,
+  /// Apply the workaround to all peers.
+  Always,
+}
+
+/// Workarounds for other RTPS implementations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VendorQuirks {
+  /// Accept SPDP participant DATA that repeats an already received sequence
+  /// number. Older eProsima Fast RTPS / Fast DDS versions (e.g. the 2.1.0
+  /// shapes demo) re-announce changed participant data without incrementing
+  /// the sequence number. `Detect` applies this to eProsima peers announcing
+  /// RTPS protocol version 2.3 or older.
+  pub duplicate_spdp_sequence_numbers: QuirkMode,
+}
+
+impl VendorQuirks {
+  /// The quirks to apply to messages from a source with the given RTPS header
+  /// fields.
+  pub(crate) fn for_source(&self, vendor_id: VendorId, version: ProtocolVersion) -> ActiveQuirks {
+    let legacy_eprosima =
+      vendor_id == VendorId::EPROSIMA && version <= ProtocolVersion::PROTOCOLVERSION_2_3;
+
+    ActiveQuirks {
+      duplicate_spdp_sequence_numbers: self
+        .duplicate_spdp_sequence_numbers
+        .applies(legacy_eprosima),
+    }
+  }
+}
+
+impl QuirkMode {
+  fn applies(self, detected: bool) -> bool {
+    match self {
+      Self::Never => false,
+      Self::Detect => detected,
+      Self::Always => true,
+    }
+  }
+}
+
+/// Quirks in effect for the message currently being received.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ActiveQuirks {
+  pub duplicate_spdp_sequence_numbers: bool,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn duplicate_spdp_quirk_detection() {
+    let quirks = VendorQuirks::default();
+    let detect = |vendor, version| {
+      quirks
+        .for_source(vendor, version)
+        .duplicate_spdp_sequence_numbers
+    };
+    assert!(detect(
+      VendorId::EPROSIMA,
+      ProtocolVersion::PROTOCOLVERSION_2_2
+    ));
+    assert!(detect(
+      VendorId::EPROSIMA,
+      ProtocolVersion::PROTOCOLVERSION_2_3
+    ));
+    assert!(!detect(
+      VendorId::EPROSIMA,
+      ProtocolVersion::PROTOCOLVERSION_2_4
+    ));
+    assert!(!detect(
+      VendorId::ATOSTEK,
+      ProtocolVersion::PROTOCOLVERSION_2_2
+    ));
+    assert!(!detect(
+      VendorId::VENDOR_UNKNOWN,
+      ProtocolVersion::PROTOCOLVERSION_2_3
+    ));
+
+    let never = VendorQuirks {
+      duplicate_spdp_sequence_numbers: QuirkMode::Never,
+    };
+    assert!(
+      !never
+        .for_source(VendorId::EPROSIMA, ProtocolVersion::PROTOCOLVERSION_2_2)
+        .duplicate_spdp_sequence_numbers
+    );
+    let always = VendorQuirks {
+      duplicate_spdp_sequence_numbers: QuirkMode::Always,
+    };
+    assert!(
+      always
+        .for_source(VendorId::ATOSTEK, ProtocolVersion::PROTOCOLVERSION_2_4)
+        .duplicate_spdp_sequence_numbers
+    );
+  }
+}
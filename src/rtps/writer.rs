@@ -4,7 +4,8 @@ use std::{
   collections::{BTreeMap, BTreeSet},
   ops::Bound::Included,
   rc::Rc,
-  sync::atomic,
+  sync::{atomic, Arc},
+  time::Instant,
 };
 
 #[allow(unused_imports)]
@@ -15,6 +16,7 @@ use mio_06::{Ready, Registration, SetReadiness, Token};
 
 use crate::{
   dds::{
+    participant::{HeartbeatBackoff, HeartbeatDelivery, LargeFanOut},
     qos::{
       policy,
       policy::{History, Reliability},
@@ -25,7 +27,7 @@ use crate::{
     },
   },
   messages::submessages::submessages::AckSubmessage,
-  network::{udp_sender::UDPSender, util::IfAddr},
+  network::{path_mtu::PathMtu, transport::Transport, util::IfAddr},
   polling::SharedTimer,
   rtps::{
     constant::{
@@ -33,8 +35,10 @@ use crate::{
       HEARTBEAT_PERIOD_FAST, HEARTBEAT_PERIOD_SLOW, HEARTBEAT_SUBMESSAGE_SERIALIZED_SIZE,
       NACK_RESPONSE_DELAY, NACK_SUPPRESSION_DURATION,
     },
+    message::RTPS_MESSAGE_HEADER_SIZE,
     outbound::{SocketId, TrafficClass},
     rtps_reader_proxy::RtpsReaderProxy,
+    statistics::StatisticsCounters,
     timed_event::DpTimerEvent,
     transmit::{DefaultRouteSelector, InterfaceObservations, RouteKey},
     writer_send_buffer::WriterSendBuffer,
@@ -47,7 +51,7 @@ use crate::{
     guid::{EntityId, GuidPrefix, GUID},
     locator::Locator,
     sequence_number::{FragmentNumber, SequenceNumber},
-    time::Timestamp,
+    time::{Clock, SystemClock},
   },
 };
 #[cfg(feature = "security")]
@@ -116,6 +120,7 @@ pub(crate) enum TimedEvent {
   CacheCleaning,
   SendRepairData { to_reader: GUID },
   SendRepairFrags { to_reader: GUID },
+  CoalesceFlush,
 }
 
 // This is used to construct an actual Writer.
@@ -161,6 +166,8 @@ pub(crate) struct Writer {
   /// Faster Heartbeat period used while some matched reader still has
   /// unacknowledged samples. `None` for BestEffort (no periodic Heartbeat).
   pub heartbeat_period_fast: Option<Duration>,
+  // The pending periodic Heartbeat in the timer
+  heartbeat_timeout: Option<mio_extras::timer::Timeout>,
   /// duration to launch cache change remove from DDSCache
   pub cache_cleaning_period: Duration,
   /// Protocol tuning parameter that
@@ -202,13 +209,14 @@ pub(crate) struct Writer {
   requested_incompatible_qos_count: i32, // how many times some Reader requested incompatible QoS
 
   // Sending mechanism
-  udp_sender: Rc<UDPSender>,
+  transport: Rc<dyn Transport>,
 
   // Extra fixed unicast destinations that every outgoing message from this
   // writer is *also* sent to, bypassing route selection. Empty for all writers
   // except the built-in SPDP participant writer, which uses it for the
-  // "localhost SPDP peers" (127.0.0.1:<well-known SPDP ports>) so same-host
-  // participants discover each other with no external network. Unlike loopback
+  // configured initial peers and for the "localhost SPDP peers"
+  // (127.0.0.1:<well-known SPDP ports>) so participants discover each other
+  // without multicast. Unlike loopback
   // locators discovered from peers, these are unconditional (they are how we
   // bootstrap same-host discovery in the first place). See
   // `src/rtps/loopback_same_host_design.md`.
@@ -219,6 +227,38 @@ pub(crate) struct Writer {
   // default. Passed into `DefaultRouteSelector` on every route resolution.
   prefer_loopback_same_host: bool,
 
+  // Backoff of periodic HEARTBEATs towards readers that do not ACKNACK.
+  // Mirrors the participant-builder `heartbeat_backoff` knob; None = disabled,
+  // i.e. every reader that is behind gets every periodic HEARTBEAT.
+  heartbeat_backoff: Option<HeartbeatBackoff>,
+
+  // Whether periodic HEARTBEATs go to multicast groups shared by the readers.
+  // Mirrors the participant-builder `heartbeat_delivery` knob.
+  heartbeat_delivery: HeartbeatDelivery,
+
+  // Repair policy when there are many matched readers. Mirrors the
+  // participant-builder `large_fan_out` knob; None = repair every reader by
+  // unicast without a limit.
+  large_fan_out: Option<LargeFanOut>,
+
+  // Longest time a sample may be held back, waiting for more samples to
+  // coalesce into the same datagram. Mirrors the participant-builder
+  // `max_coalesce_delay` knob; None = never hold, i.e. coalesce only samples
+  // that are already pending when the Writer gets to send.
+  max_coalesce_delay: Option<std::time::Duration>,
+  // When the Writer started holding the oldest pending sample. Cleared once
+  // everything pending has been sent.
+  coalesce_hold_since: Option<Instant>,
+  // Timer that bounds the current hold, and whether it has expired.
+  coalesce_flush_timeout: Option<mio_extras::timer::Timeout>,
+  coalesce_flush_due: bool,
+
+  // Source of the INFO_TS timestamps sent along with HEARTBEATs.
+  clock: Arc<dyn Clock>,
+
+  // Participant-wide counters of HEARTBEATs and repairs sent.
+  statistics: Arc<StatisticsCounters>,
+
   // Interface-aware transmit: per-remote observed receive interfaces/addresses,
   // shared (intra-thread) with the MessageReceiver that records them. Consulted
   // when (re)resolving each reader proxy's SendRoute.
@@ -227,6 +267,9 @@ pub(crate) struct Writer {
   // Snapshot of the local interface table (shared, read-only), used to resolve
   // each matched reader's per-peer path-MTU budget when its locators change.
   local_interfaces: Rc<[IfAddr]>,
+  // Configured path MTU to destinations, from the participant-builder
+  // `path_mtu` knob. Consulted before `local_interfaces`.
+  path_mtu: Arc<PathMtu>,
 
   // Minimum per-peer datagram-payload budget over all matched readers. The
   // aggregated / packed datagram is a single packet shared by every reader
@@ -284,7 +327,7 @@ pub(crate) struct Writer {
 impl Writer {
   pub fn new(
     i: WriterIngredients,
-    udp_sender: Rc<UDPSender>,
+    transport: Rc<dyn Transport>,
     timed_event_timer: SharedTimer<DpTimerEvent>,
     participant_status_sender: StatusChannelSender<DomainParticipantStatusEvent>,
     interface_observations: Rc<RefCell<InterfaceObservations>>,
@@ -296,27 +339,7 @@ impl Writer {
       panic!("RustDDS internal bug: attempted to create a stateless-like Writer with Reliable QoS");
     }
 
-    let heartbeat_period = i
-      .qos_policies
-      .reliability
-      .and_then(|reliability| {
-        if matches!(reliability, Reliability::Reliable { .. }) {
-          Some(HEARTBEAT_PERIOD_SLOW)
-        } else {
-          None
-        }
-      })
-      .map(|hbp| {
-        // What is the logic here? Which spec section?
-        if let Some(policy::Liveliness::ManualByTopic { lease_duration }) =
-          i.qos_policies.liveliness
-        {
-          let std_dur = lease_duration;
-          std_dur / 3
-        } else {
-          hbp
-        }
-      });
+    let heartbeat_period = Self::slow_heartbeat_period(&i.qos_policies, HEARTBEAT_PERIOD_SLOW);
 
     // Faster Heartbeat period used while some reader is still behind. Never
     // slower than the (possibly liveliness-shortened) slow period.
@@ -326,15 +349,15 @@ impl Writer {
     let cache_cleaning_period = Duration::from_secs(6);
 
     // Start periodic Heartbeat
-    if let Some(period) = heartbeat_period {
+    let heartbeat_timeout = heartbeat_period.map(|period| {
       timed_event_timer.borrow_mut().set_timeout(
         std::time::Duration::from(period),
         DpTimerEvent::Writer {
           entity_id: i.guid.entity_id,
           event: TimedEvent::Heartbeat,
         },
-      );
-    }
+      )
+    });
     // start periodic cache cleaning
     timed_event_timer.borrow_mut().set_timeout(
       std::time::Duration::from(cache_cleaning_period),
@@ -350,6 +373,7 @@ impl Writer {
       push_mode: true,
       heartbeat_period,
       heartbeat_period_fast,
+      heartbeat_timeout,
       cache_cleaning_period,
       nack_response_delay: NACK_RESPONSE_DELAY, // default value from dp_event_loop
       nackfrag_response_delay: NACK_RESPONSE_DELAY, // default value from dp_event_loop
@@ -363,11 +387,21 @@ impl Writer {
       readers: BTreeMap::new(),
       matched_readers_count_total: 0,
       requested_incompatible_qos_count: 0,
-      udp_sender,
+      transport,
       extra_unicast_destinations: Vec::new(),
       prefer_loopback_same_host: true,
+      heartbeat_backoff: None,
+      heartbeat_delivery: HeartbeatDelivery::default(),
+      large_fan_out: None,
+      max_coalesce_delay: None,
+      clock: Arc::new(SystemClock),
+      statistics: Arc::new(StatisticsCounters::default()),
+      coalesce_hold_since: None,
+      coalesce_flush_timeout: None,
+      coalesce_flush_due: false,
       interface_observations,
       local_interfaces,
+      path_mtu: Arc::new(PathMtu::default()),
       min_datagram_payload: FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE,
       my_topic_name: i.topic_name,
       send_buffer: i.send_buffer,
@@ -430,11 +464,40 @@ impl Writer {
     );
   }
 
+  // Replaces the pending periodic Heartbeat, if any.
+  fn schedule_heartbeat(&mut self, after: std::time::Duration) {
+    let timeout = self.timed_event_timer.borrow_mut().set_timeout(
+      after,
+      DpTimerEvent::Writer {
+        entity_id: self.my_guid.entity_id,
+        event: TimedEvent::Heartbeat,
+      },
+    );
+    if let Some(old) = self.heartbeat_timeout.replace(timeout) {
+      self.timed_event_timer.borrow_mut().cancel_timeout(&old);
+    }
+  }
+
+  // Period of Heartbeats when all matched readers are up to date. None for
+  // BestEffort Writers, which send no periodic Heartbeats.
+  fn slow_heartbeat_period(qos: &QosPolicies, period: Duration) -> Option<Duration> {
+    if !matches!(qos.reliability, Some(Reliability::Reliable { .. })) {
+      return None;
+    }
+    // What is the logic here? Which spec section?
+    if let Some(policy::Liveliness::ManualByTopic { lease_duration }) = qos.liveliness {
+      Some(lease_duration / 3)
+    } else {
+      Some(period)
+    }
+  }
+
   // Handle a single timed event. The shared timer is drained by the event loop,
   // which dispatches each expired event to the addressed Writer.
   pub fn handle_timed_event(&mut self, event: TimedEvent) {
     match event {
       TimedEvent::Heartbeat => {
+        self.heartbeat_timeout = None; // fired
         let readers_behind = self.handle_heartbeat_tick(false);
         // ^^ false = This is automatic heartbeat by timer, not manual by application
         // call.
@@ -447,7 +510,26 @@ impl Writer {
           self.heartbeat_period
         };
         if let Some(period) = next_period {
-          self.schedule_timed_event(std::time::Duration::from(period), TimedEvent::Heartbeat);
+          let mut delay = std::time::Duration::from(period);
+          // With backoff, the fast period no longer applies to lagging readers:
+          // wake up exactly when the next one is due, but still at least once
+          // per slow period.
+          if let Some(backoff_delay) = self
+            .next_backoff_heartbeat_delay()
+            .filter(|_| readers_behind)
+          {
+            delay = self
+              .heartbeat_period
+              .map_or(backoff_delay, |slow| min(backoff_delay, slow.into()));
+          }
+          self.schedule_heartbeat(delay);
+        }
+      }
+      TimedEvent::CoalesceFlush => {
+        self.coalesce_flush_timeout = None;
+        if self.coalesce_hold_since.is_some() {
+          self.coalesce_flush_due = true;
+          self.process_pending();
         }
       }
       TimedEvent::CacheCleaning => {
@@ -524,6 +606,28 @@ impl Writer {
         self.remove_all_acked_changes_but_keep_depth(Some(d as usize), resource_limit);
       }
     }
+
+    // Expired samples are removed regardless of acknowledgements, so that they
+    // are not replayed to late-joining readers.
+    if let Some(lifespan) = self.qos_policies.lifespan {
+      self
+        .send_buffer
+        .remove_changes_written_before(self.clock.now() - lifespan.duration);
+    }
+  }
+
+  // Has the sample outlived its LIFESPAN? Samples are stamped with a source
+  // timestamp by the DataWriter when LIFESPAN is set.
+  fn is_expired(&self, cc: &CacheChange) -> bool {
+    match (
+      self.qos_policies.lifespan,
+      cc.write_options.source_timestamp(),
+    ) {
+      (Some(lifespan), Some(written)) => {
+        self.clock.now().duration_since(written) > lifespan.duration
+      }
+      _ => false,
+    }
   }
 
   // --------------------------------------------------------------
@@ -599,6 +703,89 @@ impl Writer {
       && cc.write_options.to_single_reader().is_none()
   }
 
+  // Should the samples pending from `first_seq` be held back to wait for more
+  // samples to coalesce with? Only if a hold delay is configured, the samples
+  // would be coalesced, and the datagram still has room for one more sample of
+  // the same size as the latest one. The first hold arms a timer that forces
+  // a flush after `max_coalesce_delay`. A delay set on the DataWriter
+  // overrides the participant-wide one.
+  fn hold_for_coalescing(
+    &mut self,
+    first_seq: SequenceNumber,
+    last_available: SequenceNumber,
+  ) -> bool {
+    let max_delay = match self.send_buffer.max_coalesce_delay() {
+      Some(own_delay) => Some(own_delay).filter(|d| !d.is_zero()),
+      None => self.max_coalesce_delay,
+    };
+    let Some(max_delay) = max_delay else {
+      return false;
+    };
+    if self.coalesce_flush_due || !self.coalesced_datagram_has_room(first_seq, last_available) {
+      return false;
+    }
+    match self.coalesce_hold_since {
+      Some(since) => since.elapsed() < max_delay,
+      None => {
+        self.coalesce_hold_since = Some(Instant::now());
+        self.coalesce_flush_timeout = Some(self.timed_event_timer.borrow_mut().set_timeout(
+          max_delay,
+          DpTimerEvent::Writer {
+            entity_id: self.my_guid.entity_id,
+            event: TimedEvent::CoalesceFlush,
+          },
+        ));
+        true
+      }
+    }
+  }
+
+  // Estimate of the datagram `try_send_aggregated_batch` would build from the
+  // pending samples. It need not be exact: it only decides whether to wait for
+  // more samples.
+  fn coalesced_datagram_has_room(
+    &self,
+    first_seq: SequenceNumber,
+    last_available: SequenceNumber,
+  ) -> bool {
+    const INFO_TS_SUBMESSAGE_SIZE: usize = 12;
+    let hb_reserve = if self.is_reliable() && !self.like_stateless {
+      HEARTBEAT_SUBMESSAGE_SERIALIZED_SIZE
+    } else {
+      0
+    };
+    let mut len = RTPS_MESSAGE_HEADER_SIZE + hb_reserve;
+    let mut sample_len = 0;
+    let mut seq = first_seq;
+    while seq <= last_available {
+      let Some(cc) = self.send_buffer.get_by_sn(seq) else {
+        return false;
+      };
+      if !self.is_aggregatable(&cc) {
+        return false;
+      }
+      sample_len = DATA_SUBMESSAGE_OVERHEAD
+        + cc.data_value.payload_size()
+        + if cc.write_options.source_timestamp().is_some() {
+          INFO_TS_SUBMESSAGE_SIZE
+        } else {
+          0
+        };
+      len += sample_len;
+      seq = seq.plus_1();
+    }
+    len + sample_len <= self.min_datagram_payload
+  }
+
+  // All pending samples are sent, so a new hold may begin.
+  fn end_coalescing_hold(&mut self) {
+    if let Some(timeout) = self.coalesce_flush_timeout.take() {
+      self.timed_event_timer.borrow_mut().cancel_timeout(&timeout);
+    }
+    self.coalesce_hold_since = None;
+    self.coalesce_flush_due = false;
+  }
+
   // Item 1: greedily coalesce consecutive unfragmented multicast-to-all samples
   // starting at `first_seq` (up to `last_available`) into a single RTPS
   // datagram, then send it once. Returns None if the first sample is not
@@ -714,6 +901,12 @@ impl Writer {
     );
 
     if blocked.is_empty() {
+      let hold_delay = self
+        .coalesce_hold_since
+        .map_or(std::time::Duration::ZERO, |since| since.elapsed());
+      self
+        .send_buffer
+        .record_coalesced_datagram(count as u64, hold_delay);
       Some(BatchOutcome::Sent { last_seq })
     } else if may_block {
       Some(BatchOutcome::Blocked { blocked })
@@ -727,10 +920,15 @@ impl Writer {
     // sample admitted concurrently re-arms the (edge-triggered) doorbell and we
     // are woken again. The shared buffer's `last_seq` is the source of truth.
     let _ = self.doorbell.set_readiness(Ready::empty());
+    if self.send_buffer.take_flush_request() {
+      // Send whatever was written before `DataWriter::flush` without holding.
+      self.coalesce_flush_due = true;
+    }
 
     loop {
       let last_available = self.send_buffer.last_change_sequence_number();
       if self.last_sent >= last_available {
+        self.end_coalescing_hold();
         break;
       }
       let sequence_number = self.last_sent.plus_1();
@@ -746,6 +944,11 @@ impl Writer {
         && self.sample_cursor == SampleCursor::Fresh
         && self.security_plugins.is_none()
       {
+        if self.hold_for_coalescing(sequence_number, last_available) {
+          // Wait for more samples. The doorbell wakes us when they come, and the
+          // CoalesceFlush timer when the hold delay is over.
+          return;
+        }
         match self.try_send_aggregated_batch(sequence_number, last_available) {
           Some(BatchOutcome::Sent { last_seq }) | Some(BatchOutcome::Dropped { last_seq }) => {
             // Coalesced samples are multicast-to-all, so per-reader `unsent`
@@ -879,12 +1082,7 @@ impl Writer {
             liveliness_flag,
           )
           .add_header_and_build(self.my_guid.prefix);
-        self.send_message_to_readers(
-          DeliveryMode::Multicast,
-          hb_message,
-          &mut self.readers.values(),
-          TrafficClass::Control,
-        );
+        self.send_heartbeat_to_readers(hb_message, &mut self.readers.values());
         self.last_sent = sequence_number;
         self.sample_cursor = SampleCursor::Fresh;
         self.send_buffer.set_sent_frontier(sequence_number);
@@ -1011,11 +1209,17 @@ impl Writer {
       );
       return false;
     }
-    // Reliable Stateful Writer (that tracks Readers by ReaderProxy) will not set
-    // the final flag.
-    let final_flag = false;
     let liveliness_flag = is_manual_assertion; // RTPS spec "8.3.7.5 Heartbeat"
 
+    // The unicast repair limit of a large fan-out counts repairs between
+    // periodic HEARTBEATs.
+    if !is_manual_assertion {
+      self
+        .readers
+        .values_mut()
+        .for_each(RtpsReaderProxy::reset_unicast_repair_count);
+    }
+
     trace!(
       "heartbeat tick in topic {:?} have {} readers",
       self.topic_name(),
@@ -1025,18 +1229,46 @@ impl Writer {
     let first_change = self.send_buffer.first_change_sequence_number();
     let last_change = self.send_buffer.last_change_sequence_number();
 
-    if self
+    let all_acked = self
       .readers
       .values()
-      .all(|rp| last_change < rp.all_acked_before)
-    {
+      .all(|rp| last_change < rp.all_acked_before);
+    // Reliable Stateful Writer (that tracks Readers by ReaderProxy) will not set
+    // the final flag, except for a manual liveliness assertion that has nothing
+    // to repair. That needs no response from the Readers.
+    let final_flag = all_acked;
+
+    if all_acked && !is_manual_assertion {
       trace!("heartbeat tick: all readers have all available data.");
       false
     } else {
+      // With HEARTBEAT backoff, only those readers that are behind and whose
+      // backoff interval has elapsed get this HEARTBEAT. Manual liveliness
+      // assertions are not delayed by backoff.
+      let backoff = self.heartbeat_backoff.filter(|_| !is_manual_assertion);
+      let now = Instant::now();
+      let backoff_targets: Option<BTreeSet<GUID>> = backoff.map(|_| {
+        self
+          .readers
+          .values()
+          .filter(|rp| last_change >= rp.all_acked_before && rp.heartbeat_due(now))
+          .map(|rp| rp.remote_reader_guid)
+          .collect()
+      });
+      if backoff_targets.as_ref().is_some_and(BTreeSet::is_empty) {
+        trace!("heartbeat tick: all lagging readers are backing off.");
+        return true;
+      }
+      let is_target = |rp: &&RtpsReaderProxy| {
+        backoff_targets
+          .as_ref()
+          .is_none_or(|targets| targets.contains(&rp.remote_reader_guid))
+      };
+
       // the interface to .heartbeat_msg is silly: we give ref to ourself
       // and that function then queries us.
       let hb_message = MessageBuilder::new()
-        .ts_msg(self.endianness, Some(Timestamp::now()))
+        .ts_msg(self.endianness, Some(self.clock.now()))
         .heartbeat_msg(
           self.entity_id(), // from Writer
           self.send_buffer.first_change_sequence_number(),
@@ -1060,7 +1292,7 @@ impl Writer {
       // In the volatile key exchange topic we cannot send to multiple readers by any
       // means, so we handle that separately.
       if self.entity_id() == EntityId::P2P_BUILTIN_PARTICIPANT_VOLATILE_SECURE_WRITER {
-        for rp in self.readers.values() {
+        for rp in self.readers.values().filter(is_target) {
           if last_change < rp.all_acked_before {
             // Everything we have has been acknowledged already. Do nothing.
           } else {
@@ -1073,16 +1305,46 @@ impl Writer {
         }
       } else {
         // Normal case
-        self.send_control_to_readers(
-          DeliveryMode::Multicast,
-          hb_message,
-          &mut self.readers.values(),
-        );
+        self.send_heartbeat_to_readers(hb_message, &mut self.readers.values().filter(is_target));
+      }
+
+      if let (Some(backoff), Some(targets)) = (backoff, backoff_targets) {
+        for reader_guid in targets {
+          if let Some(rp) = self.readers.get_mut(&reader_guid) {
+            rp.heartbeat_sent(now, &backoff);
+            trace!(
+              "HEARTBEAT backoff: reader={:?} heartbeats_sent={} next in {:?}",
+              reader_guid,
+              rp.heartbeats_sent(),
+              rp.next_heartbeat()
+                .map(|t| t.saturating_duration_since(now)),
+            );
+          }
+        }
       }
-      true
+      !all_acked
     }
   }
 
+  // With HEARTBEAT backoff, the delay until the next reader that is behind
+  // becomes due for a HEARTBEAT. None if backoff is disabled or no reader is
+  // behind.
+  fn next_backoff_heartbeat_delay(&self) -> Option<std::time::Duration> {
+    self.heartbeat_backoff?;
+    let last_change = self.send_buffer.last_change_sequence_number();
+    let now = Instant::now();
+    self
+      .readers
+      .values()
+      .filter(|rp| last_change >= rp.all_acked_before)
+      .map(|rp| {
+        rp.next_heartbeat().map_or(std::time::Duration::ZERO, |t| {
+          t.saturating_duration_since(now)
+        })
+      })
+      .min()
+  }
+
   /// When receiving an ACKNACK Message indicating a Reader is missing some data
   /// samples, the Writer must respond by either sending the missing data
   /// samples, sending a GAP message when the sample is not relevant, or
@@ -1096,7 +1358,7 @@ impl Writer {
     if !self.is_reliable() || self.like_stateless {
       // Stateless-like Writer currently supports only BestEffort QoS, so ignore
       // acknack also for it
-      warn!(
+      warn_throttled!(
         "Writer {:x?} is best effort or stateless-like! It should not handle acknack messages!",
         self.entity_id()
       );
@@ -1273,12 +1535,18 @@ impl Writer {
       self.send_buffer.set_acked_frontier(None);
       return;
     }
-    let frontier = self
+    let reader_acked_before: BTreeMap<GUID, SequenceNumber> = self
       .readers
-      .values()
-      .filter(|rp| rp.qos().is_reliable())
-      .map(RtpsReaderProxy::acked_up_to_before)
-      .min();
+      .iter()
+      .filter(|(_, rp)| rp.qos().is_reliable())
+      .map(|(guid, rp)| (*guid, rp.acked_up_to_before()))
+      .collect();
+    let frontier = reader_acked_before.values().min().copied();
+    // Per-reader counts first: setting the frontier wakes the ack waiters,
+    // which must then see every reader as caught up.
+    self
+      .send_buffer
+      .set_reader_acked_before(reader_acked_before);
     self.send_buffer.set_acked_frontier(frontier);
   }
 
@@ -1366,11 +1634,54 @@ impl Writer {
       // SN.
       if pending_gaps.contains(&unsent_sn) || all_irrelevant_before.is_some() {
         no_longer_relevant.extend(pending_gaps);
+      } else if self
+        .send_buffer
+        .get_by_sn(unsent_sn)
+        .is_some_and(|cc| self.is_expired(&cc))
+      {
+        // The sample has outlived its LIFESPAN. Tell the reader it is gone.
+        debug!(
+          "Not repairing expired {unsent_sn:?} to {reader_guid:?}. topic={:?}",
+          self.my_topic_name
+        );
+        no_longer_relevant.insert(unsent_sn);
       } else {
         // Reader not pending gap on unsent_sn. Get the cache change from the send
         // buffer
         if let Some(cc) = self.send_buffer.get_by_sn(unsent_sn) {
-          // The cache change was found. Send it to the reader
+          // The cache change was found. Send it to the reader.
+          // Note: reader_proxy is not in self.readers now.
+          let fan_out = self.large_fan_out_policy(self.readers.len() + 1);
+          let repair_group = fan_out
+            .filter(|policy| policy.multicast_repair)
+            .and_then(|_| reader_proxy.multicast_group())
+            .filter(|_| {
+              cc.write_options.to_single_reader().is_none()
+                && cc.data_value.payload_size() <= self.max_unfragmented_serialized_payload(None)
+            });
+          if let Some(group) = repair_group {
+            self.send_repair_to_group(&cc, unsent_sn, group, reader_proxy);
+            self.send_buffer.record_repair(reader_guid, true);
+            self.statistics.sample_resent();
+            return;
+          }
+          if fan_out
+            .and_then(|policy| policy.max_unicast_repairs_per_reader)
+            .is_some_and(|max| reader_proxy.unicast_repair_count() >= max)
+          {
+            // Stop repairing until the reader asks again after the next
+            // HEARTBEAT.
+            debug!(
+              "Unicast repair limit reached for {reader_guid:?}. topic={:?}",
+              self.my_topic_name
+            );
+            self.send_buffer.record_capped_repair(reader_guid);
+            reader_proxy.repair_mode = false;
+            return;
+          }
+          reader_proxy.count_unicast_repair();
+          self.send_buffer.record_repair(reader_guid, false);
+          self.statistics.sample_resent();
           let data_was_fragmented = self.send_cache_change(&cc, false, Some(reader_proxy));
 
           if data_was_fragmented {
@@ -1394,9 +1705,12 @@ impl Writer {
           no_longer_relevant.insert(unsent_sn);
           // Try to find a reason why and log about it
           if unsent_sn < first_available {
-            info!(
+            info_throttled!(
               "Reader {:?} requested too old data {:?}. I have only from {:?}. Topic {:?}",
-              reader_proxy, unsent_sn, first_available, self.my_topic_name
+              reader_proxy,
+              unsent_sn,
+              first_available,
+              self.my_topic_name
             );
           } else {
             // we are running out of excuses
@@ -1445,6 +1759,43 @@ impl Writer {
     }
   } // fn
 
+  // Repair an unfragmented sample by sending it once to a multicast group. The
+  // other readers in the group that are missing the same sample get it from
+  // the same datagram, so they are marked as repaired, too.
+  fn send_repair_to_group(
+    &mut self,
+    cc: &CacheChange,
+    sequence_number: SequenceNumber,
+    group: Locator,
+    reader_proxy: &mut RtpsReaderProxy,
+  ) {
+    let group_readers: Vec<GUID> = self
+      .readers
+      .values()
+      .filter(|rp| {
+        rp.multicast_group() == Some(group)
+          && rp.unsent_changes_iter().any(|sn| sn == sequence_number)
+      })
+      .map(|rp| rp.remote_reader_guid)
+      .collect();
+
+    // Broadcast form of the DATA, i.e. not addressed to the requesting reader.
+    for (_cursor, msg) in FragmentationIter::new_resume(self, cc, None, false, SampleCursor::Fresh)
+    {
+      self.send_control_to_readers(
+        DeliveryMode::Multicast,
+        msg,
+        &mut std::iter::once(&*reader_proxy),
+      );
+    }
+    reader_proxy.mark_change_sent(sequence_number);
+    for guid in group_readers {
+      if let Some(rp) = self.readers.get_mut(&guid) {
+        rp.mark_change_sent(sequence_number);
+      }
+    }
+  }
+
   fn handle_repair_frags_send_worker(
     &mut self,
     reader_proxy: &mut RtpsReaderProxy, /* This is mutable proxy temporarily detached from the
@@ -1581,7 +1932,17 @@ impl Writer {
     self.send_buffer.remove_changes_before(first_keeper);
   }
 
+  // Count the HEARTBEATs from `count` on. Test-only.
+  #[cfg(any(test, feature = "test-util"))]
+  pub(crate) fn set_next_heartbeat_count(&self, count: i32) {
+    self
+      .heartbeat_message_counter
+      .store(count, atomic::Ordering::SeqCst);
+  }
+
+  // Called for each HEARTBEAT sent
   pub(crate) fn next_heartbeat_count(&self) -> i32 {
+    self.statistics.heartbeat_sent();
     self
       .heartbeat_message_counter
       .fetch_add(1, atomic::Ordering::SeqCst)
@@ -1693,11 +2054,11 @@ impl Writer {
             if sent_routes.insert(RouteKey::Multicast($mc, $iface)) {
               match class {
                 TrafficClass::Control => self
-                  .udp_sender
+                  .transport
                   .send_to_multicast_locator_via(&buffer, &$mc, &$iface),
                 TrafficClass::Bulk => blocked.extend(
                   self
-                    .udp_sender
+                    .transport
                     .try_send_to_multicast_locator_via(&buffer, &$mc, &$iface),
                 ),
               }
@@ -1710,9 +2071,9 @@ impl Writer {
           ($uc:expr) => {
             if sent_routes.insert(RouteKey::Unicast($uc)) {
               match class {
-                TrafficClass::Control => self.udp_sender.send_to_locator(&buffer, &$uc),
+                TrafficClass::Control => self.transport.send_to_locator(&buffer, &$uc),
                 TrafficClass::Bulk => {
-                  blocked.extend(self.udp_sender.try_send_to_locator(&buffer, &$uc));
+                  blocked.extend(self.transport.try_send_to_locator(&buffer, &$uc));
                 }
               }
             } else {
@@ -1725,9 +2086,9 @@ impl Writer {
             for loc in $locs.iter() {
               if sent_legacy.insert(*loc) {
                 match class {
-                  TrafficClass::Control => self.udp_sender.send_to_locator(&buffer, loc),
+                  TrafficClass::Control => self.transport.send_to_locator(&buffer, loc),
                   TrafficClass::Bulk => {
-                    blocked.extend(self.udp_sender.try_send_to_locator(&buffer, loc));
+                    blocked.extend(self.transport.try_send_to_locator(&buffer, loc));
                   }
                 }
               } else {
@@ -1748,17 +2109,19 @@ impl Writer {
               reader
                 .unicast_locator_list
                 .iter()
-                .find(|l| Locator::is_udp(l)),
+                .find(|l| self.transport.supports_locator(l)),
               reader
                 .multicast_locator_list
                 .iter()
-                .find(|l| Locator::is_udp(l)),
+                .find(|l| self.transport.supports_locator(l)),
             ) {
               (DeliveryMode::Multicast, _, Some(_)) => send_legacy!(reader.multicast_locator_list),
               (DeliveryMode::Unicast, Some(_), _) => send_legacy!(reader.unicast_locator_list),
               (_, _, Some(_)) => send_legacy!(reader.multicast_locator_list),
               (_, Some(_), _) => send_legacy!(reader.unicast_locator_list),
-              (_, None, None) => warn!("send_message_to_readers: No locators for {reader:?}"),
+              (_, None, None) => {
+                warn_throttled!("send_message_to_readers: No locators for {reader:?}");
+              }
             }
             continue;
           }
@@ -1770,12 +2133,14 @@ impl Writer {
             (_, _, Some(uc)) => emit_unicast!(uc),
             (_, Some((mc, iface)), _) => emit_multicast!(mc, iface),
             (_, None, None) => {
-              warn!("send_message_to_readers: resolved route has no destination for {reader:?}");
+              warn_throttled!(
+                "send_message_to_readers: resolved route has no destination for {reader:?}"
+              );
             }
           }
         }
 
-        // Fixed extra unicast destinations (SPDP localhost peers): send the same
+        // Fixed extra unicast destinations (SPDP initial and localhost peers): send the same
         // datagram unconditionally, deduplicated against everything already sent.
         send_legacy!(self.extra_unicast_destinations);
       }
@@ -1784,6 +2149,40 @@ impl Writer {
     blocked
   }
 
+  // Send the same HEARTBEAT (same count) to all `readers`: one datagram to each
+  // multicast group shared by enough readers, as configured by
+  // `heartbeat_delivery`, and unicast to the other readers.
+  fn send_heartbeat_to_readers(
+    &self,
+    hb_message: Message,
+    readers: &mut dyn Iterator<Item = &RtpsReaderProxy>,
+  ) {
+    let min_readers = match self.heartbeat_delivery {
+      HeartbeatDelivery::Multicast { min_readers } => min_readers,
+      HeartbeatDelivery::Unicast => usize::MAX,
+    };
+    let readers: Vec<&RtpsReaderProxy> = readers.collect();
+    let mut group_sizes: BTreeMap<Locator, usize> = BTreeMap::new();
+    for group in readers.iter().filter_map(|rp| rp.multicast_group()) {
+      *group_sizes.entry(group).or_default() += 1;
+    }
+    let (multicast, unicast): (Vec<_>, Vec<_>) = readers.into_iter().partition(|rp| {
+      rp.multicast_group()
+        .is_some_and(|group| group_sizes[&group] >= min_readers)
+    });
+
+    if !multicast.is_empty() {
+      self.send_control_to_readers(
+        DeliveryMode::Multicast,
+        hb_message.clone(),
+        &mut multicast.into_iter(),
+      );
+    }
+    if !unicast.is_empty() {
+      self.send_control_to_readers(DeliveryMode::Unicast, hb_message, &mut unicast.into_iter());
+    }
+  }
+
   // Kept for readability at call sites that fire a single control message and
   // do not care about back-pressure (heartbeats, GAPs, repair control).
   fn send_control_to_readers(
@@ -1823,7 +2222,7 @@ impl Writer {
 
   /// Set the fixed unicast destinations every outgoing message is also sent to
   /// (in addition to matched readers), bypassing route selection. Used only for
-  /// the built-in SPDP writer's "localhost SPDP peers". See
+  /// the built-in SPDP writer's initial peers and "localhost SPDP peers". See
   /// [`Self::extra_unicast_destinations`].
   pub fn set_extra_unicast_destinations(&mut self, locators: Vec<Locator>) {
     self.extra_unicast_destinations = locators;
@@ -1835,6 +2234,68 @@ impl Writer {
     self.prefer_loopback_same_host = enabled;
   }
 
+  /// Set the period of HEARTBEATs when all matched readers are up to date.
+  /// See the participant-builder `heartbeat_period` knob.
+  pub fn set_heartbeat_period(&mut self, period: std::time::Duration) {
+    self.heartbeat_period = Self::slow_heartbeat_period(&self.qos_policies, period.into());
+    self.heartbeat_period_fast = self
+      .heartbeat_period
+      .map(|slow| min(HEARTBEAT_PERIOD_FAST, slow));
+    if let Some(period) = self.heartbeat_period {
+      self.schedule_heartbeat(period.into());
+    }
+  }
+
+  /// Set the backoff policy of periodic HEARTBEATs towards unresponsive
+  /// readers. See the participant-builder `heartbeat_backoff` knob.
+  pub fn set_heartbeat_backoff(&mut self, backoff: Option<HeartbeatBackoff>) {
+    self.heartbeat_backoff = backoff;
+  }
+
+  /// How periodic HEARTBEATs are sent. See the participant-builder
+  /// `heartbeat_delivery` knob.
+  pub fn set_heartbeat_delivery(&mut self, delivery: HeartbeatDelivery) {
+    self.heartbeat_delivery = delivery;
+  }
+
+  /// See the participant-builder `clock` knob.
+  pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+    self.clock = clock;
+  }
+
+  pub fn set_statistics(&mut self, statistics: Arc<StatisticsCounters>) {
+    self.statistics = statistics;
+  }
+
+  /// Repair policy for many matched readers. See the participant-builder
+  /// `large_fan_out` knob.
+  pub fn set_large_fan_out(&mut self, policy: Option<LargeFanOut>) {
+    self.large_fan_out = policy;
+    self.update_fan_out_statistics();
+  }
+
+  // The large fan-out policy, if it is configured and applies to
+  // `matched_readers` readers.
+  fn large_fan_out_policy(&self, matched_readers: usize) -> Option<LargeFanOut> {
+    self
+      .large_fan_out
+      .filter(|policy| matched_readers >= policy.min_readers)
+  }
+
+  fn update_fan_out_statistics(&self) {
+    let matched_readers = self.readers.len();
+    self.send_buffer.set_fan_out(
+      matched_readers,
+      self.large_fan_out_policy(matched_readers).is_some(),
+    );
+  }
+
+  /// Hold samples up to `max_delay` to coalesce them into fuller datagrams.
+  /// See the participant-builder `max_coalesce_delay` knob.
+  pub fn set_max_coalesce_delay(&mut self, max_delay: Option<std::time::Duration>) {
+    self.max_coalesce_delay = max_delay.filter(|d| !d.is_zero());
+  }
+
   pub fn update_reader_proxy(
     &mut self,
     reader_proxy: &RtpsReaderProxy,
@@ -1848,6 +2309,9 @@ impl Writer {
       // matched QoS
       None => {
         let new_reader = self.matched_reader_update(reader_proxy);
+        self
+          .send_buffer
+          .set_matched_reader_qos(reader_proxy.remote_reader_guid, requested_qos.clone());
         // A (possibly new) reliable reader changes the acknowledgement frontier
         // and thus the back-pressure window.
         self.refresh_acked_frontier();
@@ -1878,7 +2342,7 @@ impl Writer {
             let first = self.send_buffer.first_change_sequence_number();
             let last = self.send_buffer.last_change_sequence_number();
             let hb_message = MessageBuilder::new()
-              .ts_msg(self.endianness, Some(Timestamp::now()))
+              .ts_msg(self.endianness, Some(self.clock.now()))
               .heartbeat_msg(
                 self.entity_id(),
                 first,
@@ -1942,7 +2406,7 @@ impl Writer {
     let mut is_new = false;
     let is_volatile = self.qos().is_volatile(); // Get this in advance to work with the borrow checker
                                                 // Capture the interface set once; resolution consults current observations.
-    let multicast_ifaces = self.udp_sender.multicast_interfaces();
+    let multicast_ifaces = self.transport.multicast_interfaces();
     let selector = DefaultRouteSelector::new(self.prefer_loopback_same_host);
     self
       .readers
@@ -1956,7 +2420,7 @@ impl Writer {
           &multicast_ifaces,
           &selector,
         );
-        rp.resolve_path_mtu(&self.local_interfaces);
+        rp.resolve_path_mtu(&self.local_interfaces, &self.path_mtu);
       })
       .or_insert_with(|| {
         is_new = true;
@@ -1975,12 +2439,15 @@ impl Writer {
           &multicast_ifaces,
           &selector,
         );
-        new_proxy.resolve_path_mtu(&self.local_interfaces);
+        new_proxy.resolve_path_mtu(&self.local_interfaces, &self.path_mtu);
         new_proxy
       });
     // A reader was added or its locators changed: refresh the writer-wide
     // minimum datagram budget used for packing.
     self.recompute_min_datagram_payload();
+    if is_new {
+      self.update_fan_out_statistics();
+    }
     is_new
   }
 
@@ -1988,6 +2455,7 @@ impl Writer {
   /// minimum per-peer budget over all matched readers. The aggregated/packed
   /// datagram is one packet multicast to every reader, so it must fit the
   /// smallest path MTU. With no matched readers, fall back to the default.
+  /// Also publishes the path MTU of each reader to the DataWriter.
   fn recompute_min_datagram_payload(&mut self) {
     self.min_datagram_payload = self
       .readers
@@ -1995,26 +2463,54 @@ impl Writer {
       .map(RtpsReaderProxy::max_datagram_payload)
       .min()
       .unwrap_or(FALLBACK_MAX_AGGREGATED_DATAGRAM_SIZE);
+    self.send_buffer.set_reader_path_mtus(
+      self
+        .readers
+        .iter()
+        .map(|(guid, rp)| (*guid, rp.path_mtu()))
+        .collect(),
+    );
   }
 
   /// Refresh the [`SendRoute`](crate::rtps::transmit::SendRoute) of every
   /// matched reader belonging to `prefix`. Called when fresh interface
   /// observations for that participant may have arrived (e.g. periodic SPDP).
   pub fn recompute_routes_for(&mut self, prefix: GuidPrefix) {
-    let multicast_ifaces = self.udp_sender.multicast_interfaces();
+    let multicast_ifaces = self.transport.multicast_interfaces();
     let selector = DefaultRouteSelector::new(self.prefer_loopback_same_host);
     {
       let observations = self.interface_observations.borrow();
       for rp in self.readers.values_mut() {
         if rp.remote_reader_guid.prefix == prefix {
           rp.resolve_send_route(&observations, &multicast_ifaces, &selector);
-          rp.resolve_path_mtu(&self.local_interfaces);
+          rp.resolve_path_mtu(&self.local_interfaces, &self.path_mtu);
         }
       }
     }
     self.recompute_min_datagram_payload();
   }
 
+  /// Replace the local interface table after a network configuration change,
+  /// and re-resolve the path-MTU budget of every matched reader against it.
+  pub fn set_local_interfaces(&mut self, local_interfaces: Rc<[IfAddr]>) {
+    self.local_interfaces = local_interfaces;
+    for rp in self.readers.values_mut() {
+      rp.resolve_path_mtu(&self.local_interfaces, &self.path_mtu);
+    }
+    self.recompute_min_datagram_payload();
+  }
+
+  /// Set the configured path MTU to destinations, from the participant-builder
+  /// `path_mtu` knob, and re-resolve the path-MTU budget of every matched
+  /// reader.
+  pub fn set_path_mtu(&mut self, path_mtu: Arc<PathMtu>) {
+    self.path_mtu = path_mtu;
+    for rp in self.readers.values_mut() {
+      rp.resolve_path_mtu(&self.local_interfaces, &self.path_mtu);
+    }
+    self.recompute_min_datagram_payload();
+  }
+
   fn matched_reader_remove(&mut self, guid: GUID) -> Option<RtpsReaderProxy> {
     let removed = self.readers.remove(&guid);
     if let Some(ref removed_reader) = removed {
@@ -2024,6 +2520,9 @@ impl Writer {
         removed_reader.remote_reader_guid,
       );
       debug!("Removed reader proxy details: {removed_reader:?}");
+      self.send_buffer.forget_reader_repairs(guid);
+      self.send_buffer.remove_matched_readers(&[guid]);
+      self.update_fan_out_statistics();
     }
     #[cfg(feature = "security")]
     if let Some(security_plugins_handle) = &self.security_plugins {
@@ -2065,6 +2564,7 @@ impl Writer {
       .range(guid_prefix.range())
       .map(|(g, _)| *g)
       .collect();
+    self.send_buffer.remove_matched_readers(&lost_readers);
     for reader in lost_readers {
       self.reader_lost(reader);
     }
@@ -2446,7 +2946,12 @@ impl<'a> Iterator for FragmentationIter<'a> {
 
 #[cfg(test)]
 mod tests {
-  use std::thread;
+  use std::{
+    collections::BTreeSet,
+    net::{IpAddr, SocketAddr},
+    thread,
+    time::{Duration, Instant},
+  };
 
   use byteorder::LittleEndian;
   use log::info;
@@ -2501,11 +3006,21 @@ mod tests {
 
   use crate::{
     dds::{
-      participant::DomainParticipant, qos::QosPolicies, topic::TopicKind,
+      participant::{DomainParticipant, HeartbeatDelivery, LargeFanOut},
+      qos::{policy, QosPolicies, QosPolicyBuilder},
+      topic::TopicKind,
       with_key::datawriter::DataWriter,
     },
+    network::path_mtu::PathMtu,
+    rtps::constant::NACK_RESPONSE_DELAY,
     serialization::CDRSerializerAdapter,
+    structure::{
+      guid::{EntityId, EntityKind, GuidPrefix, GUID},
+      locator::Locator,
+      sequence_number::SequenceNumber,
+    },
     test::random_data::*,
+    test_util::{CapturedDatagram, CapturedSubmessage, WriterHarness},
   };
 
   #[test]
@@ -2558,4 +3073,534 @@ mod tests {
     thread::sleep(std::time::Duration::from_millis(100));
     info!("writerResult:  {write_result:?}");
   }
+
+  // The tests below drive a Writer through a `WriterHarness`, which captures
+  // what the Writer sends.
+
+  fn harness_reader(n: u8) -> GUID {
+    GUID::new(
+      GuidPrefix::new(&[n; 12]),
+      EntityId::new([0, 0, 1], EntityKind::READER_NO_KEY_USER_DEFINED),
+    )
+  }
+
+  fn unicast(n: u8) -> SocketAddr {
+    SocketAddr::from(([192, 0, 2, n], 7411))
+  }
+
+  fn reliable_qos() -> QosPolicies {
+    QosPolicyBuilder::new()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: crate::Duration::ZERO,
+      })
+      .history(policy::History::KeepAll)
+      .build()
+  }
+
+  // A harnessed Writer with `qos`, matched with `harness_reader(1)` at
+  // `unicast(1)`. The Writer is configured before the Reader is matched, and
+  // the messages sent at matching are discarded.
+  fn writer_harness(
+    qos: &QosPolicies,
+    configure: impl FnOnce(&mut WriterHarness),
+  ) -> WriterHarness {
+    let mut h = WriterHarness::new("test_topic", qos).unwrap();
+    configure(&mut h);
+    h.add_matched_reader(harness_reader(1), vec![Locator::from(unicast(1))], qos);
+    h.take_sent();
+    h
+  }
+
+  // Submessages sent since the previous call
+  fn sent_submessages(h: &mut WriterHarness) -> Vec<CapturedSubmessage> {
+    h.take_sent()
+      .iter()
+      .flat_map(|d| d.submessages().unwrap())
+      .collect()
+  }
+
+  const MAX_COALESCE_DELAY: Duration = Duration::from_millis(50);
+
+  fn coalescing_harness() -> WriterHarness {
+    writer_harness(&QosPolicies::qos_none(), |h| {
+      h.set_max_coalesce_delay(Some(MAX_COALESCE_DELAY));
+    })
+  }
+
+  fn data_count(datagram: &CapturedDatagram) -> usize {
+    datagram
+      .submessages()
+      .unwrap()
+      .iter()
+      .filter(|sm| matches!(sm, CapturedSubmessage::Data { .. }))
+      .count()
+  }
+
+  #[test]
+  fn small_samples_are_held_until_max_delay() {
+    let mut h = coalescing_harness();
+    let written_at = Instant::now();
+    h.write_bytes(&[1; 8]).unwrap();
+    h.write_bytes(&[2; 8]).unwrap();
+    assert!(h.take_sent().is_empty());
+
+    h.run_timers_for(MAX_COALESCE_DELAY * 2);
+    let sent = h.take_sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(data_count(&sent[0]), 2);
+
+    let stats = h.coalescing_statistics();
+    assert_eq!((stats.datagrams, stats.samples), (1, 2));
+    assert!(stats.last_hold_delay >= MAX_COALESCE_DELAY);
+    // The timers may run late on a loaded machine.
+    assert!(stats.last_hold_delay <= written_at.elapsed());
+  }
+
+  #[test]
+  fn full_datagram_is_sent_without_waiting() {
+    let mut h = coalescing_harness();
+    // Two of these fit in one datagram, but a third would not.
+    h.write_bytes(&[1; 600]).unwrap();
+    assert!(h.take_sent().is_empty());
+    h.write_bytes(&[2; 600]).unwrap();
+
+    let sent = h.take_sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(data_count(&sent[0]), 2);
+    assert!(h.coalescing_statistics().last_hold_delay < MAX_COALESCE_DELAY);
+
+    // The flush timer of the finished hold does not fire any more.
+    h.run_timers_for(MAX_COALESCE_DELAY * 2);
+    assert!(h.take_sent().is_empty());
+  }
+
+  #[test]
+  fn samples_are_sent_at_once_without_max_delay() {
+    let mut h = coalescing_harness();
+    h.set_max_coalesce_delay(None);
+    h.write_bytes(&[1; 8]).unwrap();
+    assert_eq!(h.take_sent().len(), 1);
+    assert_eq!(h.coalescing_statistics().last_hold_delay, Duration::ZERO);
+  }
+
+  #[test]
+  fn flushed_samples_share_one_datagram_and_heartbeat() {
+    // Only this writer holds samples; the participant-wide delay is not set.
+    let mut h = writer_harness(&reliable_qos(), |_| {});
+    h.set_own_max_coalesce_delay(Duration::from_secs(10));
+    h.take_sent();
+
+    let written: Vec<SequenceNumber> = (0..10).map(|n| h.write_bytes(&[n; 8]).unwrap()).collect();
+    assert!(h.take_sent().is_empty());
+
+    h.flush();
+    let sent = h.take_sent();
+    assert_eq!(sent.len(), 1);
+    let submessages = sent[0].submessages().unwrap();
+    let data_sns: Vec<SequenceNumber> = submessages
+      .iter()
+      .filter_map(|sm| match sm {
+        CapturedSubmessage::Data { writer_sn, .. } => Some(*writer_sn),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(data_sns, written);
+    let heartbeat_range: Vec<(SequenceNumber, SequenceNumber)> = submessages
+      .iter()
+      .filter_map(|sm| match sm {
+        CapturedSubmessage::Heartbeat {
+          first_sn, last_sn, ..
+        } => Some((*first_sn, *last_sn)),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(heartbeat_range, vec![(written[0], written[9])]);
+  }
+
+  #[test]
+  fn zero_own_delay_overrides_participant_delay() {
+    let mut h = coalescing_harness();
+    h.set_own_max_coalesce_delay(Duration::ZERO);
+    h.write_bytes(&[1; 8]).unwrap();
+    assert_eq!(h.take_sent().len(), 1);
+  }
+
+  // Number of HEARTBEATs sent in 200 ms to a Reader that does not acknowledge
+  // the written sample.
+  fn heartbeats_in_200_ms(period: Option<Duration>) -> usize {
+    let mut h = writer_harness(&reliable_qos(), |h| {
+      if let Some(period) = period {
+        h.set_heartbeat_period(period);
+      }
+    });
+    h.write_bytes(&[1; 8]).unwrap();
+    h.take_sent();
+
+    h.run_timers_for(Duration::from_millis(200));
+    sent_submessages(&mut h)
+      .iter()
+      .filter(|sm| matches!(sm, CapturedSubmessage::Heartbeat { .. }))
+      .count()
+  }
+
+  #[test]
+  fn configured_heartbeat_period_applies_at_once() {
+    // The default period is 1 s, so no periodic HEARTBEAT is due yet.
+    assert_eq!(heartbeats_in_200_ms(None), 0);
+    let sent = heartbeats_in_200_ms(Some(Duration::from_millis(30)));
+    assert!((4..=7).contains(&sent), "{sent} HEARTBEATs");
+  }
+
+  fn multicast_group(n: u8) -> SocketAddr {
+    SocketAddr::from(([239, 255, 0, n], 7401))
+  }
+
+  // Readers 1..=3 share group 1, reader 4 has no multicast and reader 5 is
+  // alone in group 2. Returns the destinations and counts of the HEARTBEATs
+  // sent by a periodic tick.
+  fn heartbeat_destinations(delivery: HeartbeatDelivery) -> (BTreeSet<SocketAddr>, BTreeSet<i32>) {
+    let qos = reliable_qos();
+    let mut h = WriterHarness::new("test_topic", &qos).unwrap();
+    h.set_heartbeat_delivery(delivery);
+    for n in 1..=5 {
+      let multicast = match n {
+        1..=3 => vec![Locator::from(multicast_group(1))],
+        5 => vec![Locator::from(multicast_group(2))],
+        _ => vec![],
+      };
+      h.add_matched_multicast_reader(
+        harness_reader(n),
+        vec![Locator::from(unicast(n))],
+        multicast,
+        &qos,
+      );
+    }
+    h.write_bytes(&[1; 8]).unwrap();
+    h.take_sent();
+
+    h.heartbeat_tick();
+    let mut destinations = BTreeSet::new();
+    let mut counts = BTreeSet::new();
+    for datagram in h.take_sent() {
+      for sm in datagram.submessages().unwrap() {
+        if let CapturedSubmessage::Heartbeat { count, .. } = sm {
+          destinations.insert(datagram.destination);
+          counts.insert(count);
+        }
+      }
+    }
+    (destinations, counts)
+  }
+
+  fn unicast_destinations(destinations: &BTreeSet<SocketAddr>) -> BTreeSet<SocketAddr> {
+    destinations
+      .iter()
+      .filter(|d| !d.ip().is_multicast())
+      .copied()
+      .collect()
+  }
+
+  fn is_group(d: &SocketAddr, groups: &[SocketAddr]) -> bool {
+    d.ip().is_multicast() && groups.contains(d)
+  }
+
+  #[test]
+  fn readers_sharing_a_group_get_one_multicast_heartbeat() {
+    let (destinations, counts) =
+      heartbeat_destinations(HeartbeatDelivery::Multicast { min_readers: 2 });
+    // Readers of group 1 get no unicast HEARTBEAT. Multicast sockets depend on
+    // the interfaces of the host, so only check that nothing else was
+    // multicast.
+    assert_eq!(
+      unicast_destinations(&destinations),
+      BTreeSet::from([unicast(4), unicast(5)])
+    );
+    assert!(destinations
+      .iter()
+      .all(|d| !d.ip().is_multicast() || is_group(d, &[multicast_group(1)])));
+    // Everyone got the same HEARTBEAT.
+    assert_eq!(counts.len(), 1);
+
+    let (destinations, _) = heartbeat_destinations(HeartbeatDelivery::default());
+    assert_eq!(
+      unicast_destinations(&destinations),
+      BTreeSet::from([unicast(4)])
+    );
+    assert!(destinations
+      .iter()
+      .all(|d| !d.ip().is_multicast() || is_group(d, &[multicast_group(1), multicast_group(2)])));
+  }
+
+  #[test]
+  fn unicast_heartbeat_delivery() {
+    let (destinations, counts) = heartbeat_destinations(HeartbeatDelivery::Unicast);
+    assert_eq!(destinations, (1..=5).map(unicast).collect::<BTreeSet<_>>());
+    assert_eq!(counts.len(), 1);
+    assert!(destinations.iter().all(|d| !d.ip().is_multicast()));
+  }
+
+  // A writer with `readers` matched readers, all in the same multicast group
+  // if `multicast` is set, and two samples written and delivered.
+  fn writer_with_readers(readers: u8, multicast: bool, policy: LargeFanOut) -> WriterHarness {
+    let qos = reliable_qos();
+    let mut h = WriterHarness::new("test_topic", &qos).unwrap();
+    h.set_large_fan_out(Some(policy));
+    for n in 1..=readers {
+      let group = if multicast {
+        vec![Locator::from(multicast_group(1))]
+      } else {
+        vec![]
+      };
+      h.add_matched_multicast_reader(
+        harness_reader(n),
+        vec![Locator::from(unicast(n))],
+        group,
+        &qos,
+      );
+    }
+    h.write_bytes(&[1; 8]).unwrap();
+    h.write_bytes(&[2; 8]).unwrap();
+    h.take_sent();
+    h
+  }
+
+  fn repair_all(h: &mut WriterHarness, from: GUID, count: i32) {
+    let missing = [SequenceNumber::new(1), SequenceNumber::new(2)];
+    h.inject_acknack(from, SequenceNumber::new(1), &missing, count);
+    h.run_timers_for(NACK_RESPONSE_DELAY * 2);
+  }
+
+  fn unicast_data_to(h: &mut WriterHarness, n: u8) -> usize {
+    h.take_sent()
+      .iter()
+      .filter(|d| d.destination == unicast(n))
+      .map(data_count)
+      .sum()
+  }
+
+  #[test]
+  fn large_fan_out_repairs_by_multicast() {
+    let h = writer_with_readers(4, true, LargeFanOut::default());
+    let stats = h.fan_out_statistics();
+    assert_eq!(stats.matched_readers, 4);
+    assert!(!stats.large_fan_out); // default threshold is higher
+
+    let policy = LargeFanOut {
+      min_readers: 3,
+      ..LargeFanOut::default()
+    };
+    let mut h = writer_with_readers(4, true, policy);
+    assert!(h.fan_out_statistics().large_fan_out);
+
+    repair_all(&mut h, harness_reader(1), 1);
+    // Multicast sockets depend on the host, so check only that no unicast
+    // repair was sent.
+    assert_eq!(unicast_data_to(&mut h, 1), 0);
+    let stats = h.fan_out_statistics();
+    assert_eq!(stats.multicast_repairs, 2);
+    assert_eq!(stats.unicast_repairs, 0);
+    assert_eq!(stats.readers.len(), 1);
+    assert_eq!(stats.readers[0].reader, harness_reader(1));
+    assert_eq!(stats.readers[0].repairs, 2);
+  }
+
+  #[test]
+  fn unicast_repairs_per_reader_are_capped() {
+    let policy = LargeFanOut {
+      min_readers: 3,
+      multicast_repair: true,
+      max_unicast_repairs_per_reader: Some(1),
+    };
+    let mut h = writer_with_readers(3, false, policy);
+
+    repair_all(&mut h, harness_reader(2), 1);
+    assert_eq!(unicast_data_to(&mut h, 2), 1);
+    let stats = h.fan_out_statistics();
+    assert_eq!(stats.unicast_repairs, 1);
+    assert_eq!(stats.capped_repairs, 1);
+
+    // The limit is per HEARTBEAT period.
+    h.heartbeat_tick();
+    h.take_sent();
+    repair_all(&mut h, harness_reader(2), 2);
+    assert_eq!(unicast_data_to(&mut h, 2), 1);
+    let stats = h.fan_out_statistics();
+    assert_eq!(stats.unicast_repairs, 2);
+    assert_eq!(stats.readers[0].repairs, 2);
+    assert_eq!(stats.readers[0].capped_repairs, 2);
+  }
+
+  #[test]
+  fn small_fan_out_is_not_limited() {
+    let policy = LargeFanOut {
+      min_readers: 3,
+      multicast_repair: true,
+      max_unicast_repairs_per_reader: Some(1),
+    };
+    let mut h = writer_with_readers(2, true, policy);
+    assert!(!h.fan_out_statistics().large_fan_out);
+
+    repair_all(&mut h, harness_reader(1), 1);
+    assert_eq!(unicast_data_to(&mut h, 1), 2);
+    let stats = h.fan_out_statistics();
+    assert_eq!(stats.unicast_repairs, 2);
+    assert_eq!(stats.capped_repairs, 0);
+  }
+
+  fn locator(ip: [u8; 4]) -> Locator {
+    Locator::from(SocketAddr::from((ip, 7411)))
+  }
+
+  // Does the Writer send a sample of `size` bytes to a reader at 10.8.0.5 in
+  // DATAFRAGs?
+  fn fragments(path_mtu: Option<PathMtu>, size: usize) -> bool {
+    let qos = QosPolicies::qos_none();
+    let mut h = WriterHarness::new("test_topic", &qos).unwrap();
+    if let Some(path_mtu) = path_mtu {
+      h.set_path_mtu(path_mtu);
+    }
+    h.add_matched_reader(harness_reader(1), vec![locator([10, 8, 0, 5])], &qos);
+    h.write_bytes(&vec![7; size]).unwrap();
+    let submessages = sent_submessages(&mut h);
+    assert!(!submessages.is_empty());
+    submessages
+      .iter()
+      .any(|sm| matches!(sm, CapturedSubmessage::DataFrag { .. }))
+  }
+
+  #[test]
+  fn configured_path_mtu_lowers_fragmentation_threshold() {
+    let tunnel = PathMtu::new().with_destination(IpAddr::from([10, 8, 0, 0]), 16, 1280);
+
+    assert!(!fragments(None, 1300));
+    assert!(fragments(Some(tunnel.clone()), 1300));
+    assert!(!fragments(Some(tunnel), 1000));
+  }
+
+  #[test]
+  fn path_mtus_of_matched_readers_are_reported() {
+    let qos = QosPolicies::qos_none();
+    let mut h = WriterHarness::new("test_topic", &qos).unwrap();
+    h.add_matched_reader(harness_reader(1), vec![locator([10, 8, 0, 5])], &qos);
+    h.add_matched_reader(harness_reader(2), vec![locator([192, 0, 2, 1])], &qos);
+    assert_eq!(
+      h.reader_path_mtus(),
+      vec![(harness_reader(1), 1500), (harness_reader(2), 1500)]
+    );
+
+    // Changing the configuration re-resolves already matched readers.
+    h.set_path_mtu(
+      PathMtu::new()
+        .with_destination(IpAddr::from([10, 8, 0, 0]), 16, 1280)
+        .with_default(1400),
+    );
+    assert_eq!(
+      h.reader_path_mtus(),
+      vec![(harness_reader(1), 1280), (harness_reader(2), 1400)]
+    );
+  }
+
+  fn best_effort_qos() -> QosPolicies {
+    QosPolicyBuilder::new()
+      .reliability(policy::Reliability::BestEffort)
+      .build()
+  }
+
+  #[test]
+  fn unacked_samples_are_counted_per_reliable_reader() {
+    let mut h = writer_harness(&reliable_qos(), |_| {});
+    h.add_matched_reader(
+      harness_reader(2),
+      vec![Locator::from(unicast(1))],
+      &reliable_qos(),
+    );
+    h.add_matched_reader(
+      harness_reader(3),
+      vec![Locator::from(unicast(1))],
+      &best_effort_qos(),
+    );
+    for n in 0..5 {
+      h.write_bytes(&[n]).unwrap();
+    }
+    assert_eq!(h.unacked_sample_count(harness_reader(1)), 5);
+    assert_eq!(h.max_unacked_sample_count(), 5);
+
+    // Reader 1 acknowledges samples 1 to 3, reader 2 everything.
+    h.inject_acknack(harness_reader(1), SequenceNumber::new(4), &[], 1);
+    h.inject_acknack(harness_reader(2), SequenceNumber::new(6), &[], 1);
+    assert_eq!(h.unacked_sample_count(harness_reader(1)), 2);
+    assert_eq!(h.unacked_sample_count(harness_reader(2)), 0);
+    assert_eq!(h.max_unacked_sample_count(), 2);
+
+    // Best-effort and unknown readers do not acknowledge anything.
+    assert_eq!(h.unacked_sample_count(harness_reader(3)), 0);
+    assert_eq!(h.unacked_sample_count(harness_reader(4)), 0);
+  }
+
+  #[test]
+  fn matched_readers_are_reported_with_their_qos() {
+    // Another Reader in the participant of reader 1
+    let reader_1b = GUID::new(
+      harness_reader(1).prefix,
+      EntityId::new([0, 0, 2], EntityKind::READER_NO_KEY_USER_DEFINED),
+    );
+    let mut h = writer_harness(&reliable_qos(), |_| {});
+    h.add_matched_reader(
+      reader_1b,
+      vec![Locator::from(unicast(1))],
+      &best_effort_qos(),
+    );
+    h.add_matched_reader(
+      harness_reader(2),
+      vec![Locator::from(unicast(2))],
+      &best_effort_qos(),
+    );
+
+    let matched = h.matched_reader_qos();
+    assert_eq!(
+      matched.keys().copied().collect::<Vec<_>>(),
+      vec![harness_reader(1), reader_1b, harness_reader(2)]
+    );
+    assert!(matches!(
+      matched[&harness_reader(1)].reliability,
+      Some(policy::Reliability::Reliable { .. })
+    ));
+    assert_eq!(
+      matched[&harness_reader(2)].reliability,
+      Some(policy::Reliability::BestEffort)
+    );
+
+    // Losing a participant removes all of its readers.
+    h.participant_lost(harness_reader(1).prefix);
+    assert_eq!(
+      h.matched_reader_qos().keys().copied().collect::<Vec<_>>(),
+      vec![harness_reader(2)]
+    );
+  }
+
+  #[test]
+  fn sent_messages_heartbeats_and_resends_are_counted() {
+    let mut h = writer_harness(&reliable_qos(), |_| {});
+    let before = h.statistics();
+
+    const N: u64 = 5;
+    for n in 0..N {
+      h.write_bytes(&[n as u8; 16]).unwrap();
+    }
+    let after = h.statistics();
+    assert!(after.messages_sent >= before.messages_sent + N);
+    assert!(after.bytes_sent >= before.bytes_sent + N * 16);
+    assert!(after.heartbeats_sent > before.heartbeats_sent);
+    assert_eq!(after.samples_resent, 0);
+
+    // The reader reports sample 2 missing.
+    h.inject_acknack(
+      harness_reader(1),
+      SequenceNumber::new(2),
+      &[SequenceNumber::new(2)],
+      1,
+    );
+    h.run_timers_for(Duration::from_millis(500));
+    assert_eq!(h.statistics().samples_resent, 1);
+  }
 }
@@ -1,4 +1,5 @@
 use std::{
+  cmp::max,
   collections::BTreeMap,
   sync::{Arc, Condvar, Mutex},
   time::{Duration as StdDuration, Instant},
@@ -9,8 +10,16 @@ use core::task::Waker;
 use log::{debug, error, trace, warn};
 
 use crate::{
-  dds::{ddsdata::DDSData, with_key::datawriter::WriteOptions},
-  structure::{cache_change::CacheChange, guid::GUID, sequence_number::SequenceNumber},
+  dds::{
+    ddsdata::DDSData,
+    durability_store::{DurabilityStore, StoredSample},
+    qos::{policy::DurabilityService, QosPolicies},
+    statusevents::{CoalescingStatistics, FanOutStatistics, ReaderRepairLoad},
+    with_key::datawriter::WriteOptions,
+  },
+  structure::{
+    cache_change::CacheChange, guid::GUID, sequence_number::SequenceNumber, time::Timestamp,
+  },
 };
 
 /// Result of an admission attempt into the [`WriterSendBuffer`].
@@ -72,6 +81,42 @@ struct Inner {
   // Wakers of async producers / ack-waiters parked because the window was full
   // or acknowledgements were still pending. Drained (woken) on any advance.
   wakers: Vec<Waker>,
+
+  // Updated by the Writer for every coalesced datagram it sends, read by the
+  // DataWriter.
+  coalescing: CoalescingStatistics,
+  // Coalescing hold delay chosen for this DataWriter, overriding the
+  // participant-wide one. Zero = never hold. Set by the DataWriter, read by the
+  // Writer.
+  max_coalesce_delay: Option<StdDuration>,
+  // The DataWriter asked to send held samples now. Cleared by the Writer.
+  flush_requested: bool,
+
+  // Updated by the Writer as readers are matched and repaired, read by the
+  // DataWriter. The per-reader loads are kept here, not in `fan_out.readers`.
+  fan_out: FanOutStatistics,
+  reader_repairs: BTreeMap<GUID, ReaderRepairLoad>,
+
+  // Path MTU to each matched reader, updated by the Writer whenever it
+  // resolves them, read by the DataWriter.
+  reader_path_mtus: Vec<(GUID, u32)>,
+
+  // Requested QoS of each matched reader, updated by the Writer as readers are
+  // matched and lost, read by the DataWriter.
+  matched_reader_qos: BTreeMap<GUID, QosPolicies>,
+
+  // `all_acked_before` of each matched reliable reader, updated by the Writer
+  // along with the acknowledgement frontier, read by the DataWriter.
+  reader_acked_before: BTreeMap<GUID, SequenceNumber>,
+
+  // Between Publisher::begin_coherent_changes and end_coherent_changes, samples
+  // are tagged with the coherent set that starts at the first of them.
+  coherent_changes: bool,
+  coherent_set_start: Option<SequenceNumber>,
+
+  // Transient and Persistent writers also hand every sample to the
+  // DurabilityStore of the participant, with their DURABILITY_SERVICE QoS.
+  durability_store: Option<(Arc<dyn DurabilityStore>, DurabilityService)>,
 }
 
 struct Shared {
@@ -130,6 +175,17 @@ impl WriterSendBuffer {
           sent_frontier: SequenceNumber::new(0),
           max_retain: max_retain.max(1),
           wakers: Vec::new(),
+          coalescing: CoalescingStatistics::default(),
+          max_coalesce_delay: None,
+          flush_requested: false,
+          fan_out: FanOutStatistics::default(),
+          reader_path_mtus: Vec::new(),
+          matched_reader_qos: BTreeMap::new(),
+          reader_acked_before: BTreeMap::new(),
+          reader_repairs: BTreeMap::new(),
+          coherent_changes: false,
+          coherent_set_start: None,
+          durability_store: None,
         }),
         progress: Condvar::new(),
         writer_guid,
@@ -236,6 +292,8 @@ impl WriterSendBuffer {
   /// sequence number. On a full window returns the (write_options, data) back
   /// so the caller can retry later, and registers `waker` to be woken when
   /// room becomes available.
+  // The sample is handed back as is, to be retried without copying.
+  #[allow(clippy::result_large_err)]
   pub fn try_admit(
     &self,
     write_options: WriteOptions,
@@ -267,7 +325,20 @@ impl WriterSendBuffer {
     may_block: bool,
   ) -> SequenceNumber {
     let seq = inner.last_seq.plus_1();
+    let write_options = if inner.coherent_changes {
+      write_options.with_coherent_set(*inner.coherent_set_start.get_or_insert(seq))
+    } else {
+      write_options
+    };
     let cc = CacheChange::new(shared.writer_guid, seq, write_options, data);
+    // Stored under the lock, so the store gets the samples in SequenceNumber
+    // order. A coherent set end marker is not a sample to replay.
+    if let (Some((store, service)), false) = (
+      &inner.durability_store,
+      matches!(cc.data_value, DDSData::CoherentSetEnd),
+    ) {
+      store.store(&shared.topic_name, service, StoredSample::new(cc.clone()));
+    }
     inner.changes.insert(seq, cc);
     inner.last_seq = seq;
 
@@ -345,6 +416,25 @@ impl WriterSendBuffer {
     }
   }
 
+  /// Number the samples from `first` on, instead of from 1. Test-only, for
+  /// checking how peers cope with unusual starting sequence numbers.
+  ///
+  /// # Panics
+  ///
+  /// If samples have already been written.
+  #[cfg(any(test, feature = "test-util"))]
+  pub fn set_initial_sequence_number(&self, first: SequenceNumber) {
+    let mut inner = self.shared.inner.lock().unwrap();
+    assert!(
+      inner.last_seq == SequenceNumber::new(0),
+      "Initial sequence number must be set before writing"
+    );
+    inner.first_seq = first;
+    inner.last_seq = first - SequenceNumber::new(1);
+    inner.acked_before = first;
+    inner.sent_frontier = first - SequenceNumber::new(1);
+  }
+
   /// The sequence number of the latest allocated sample (0 if none yet).
   pub fn last_change_sequence_number(&self) -> SequenceNumber {
     self.shared.inner.lock().unwrap().last_seq
@@ -373,6 +463,32 @@ impl WriterSendBuffer {
   pub fn remove_changes_before(&self, remove_before: SequenceNumber) {
     let shared = &*self.shared;
     let mut inner = shared.inner.lock().unwrap();
+    Self::remove_changes_before_locked(shared, &mut inner, remove_before);
+  }
+
+  /// Evict the oldest samples up to the first one whose source timestamp is
+  /// not before `cutoff`. Samples without a source timestamp are never
+  /// evicted by this.
+  pub fn remove_changes_written_before(&self, cutoff: Timestamp) {
+    let shared = &*self.shared;
+    let mut inner = shared.inner.lock().unwrap();
+    let remove_before = inner
+      .changes
+      .iter()
+      .find(|(_, cc)| {
+        cc.write_options
+          .source_timestamp()
+          .is_none_or(|written| written >= cutoff)
+      })
+      .map_or(inner.last_seq + SequenceNumber::new(1), |(sn, _)| *sn);
+    Self::remove_changes_before_locked(shared, &mut inner, remove_before);
+  }
+
+  fn remove_changes_before_locked(
+    shared: &Shared,
+    inner: &mut Inner,
+    remove_before: SequenceNumber,
+  ) {
     let count_before = inner.changes.len();
     inner.changes = inner.changes.split_off(&remove_before);
     if remove_before > inner.first_seq {
@@ -386,6 +502,8 @@ impl WriterSendBuffer {
         remove_before,
         shared.topic_name
       );
+      // Someone may be waiting for the history to drain.
+      Self::wake_all(inner, &shared.progress);
     }
   }
 
@@ -417,6 +535,240 @@ impl WriterSendBuffer {
     }
   }
 
+  // --- wait_for_history_drain support ---
+
+  /// Synchronously wait until the buffer retains no samples at all, or
+  /// `max_wait` elapses. Returns `true` if drained.
+  ///
+  /// A VOLATILE writer has no late joiners to serve, so once every retained
+  /// sample has been transmitted and acknowledged by all matched reliable
+  /// readers, nobody can need them any more. Such samples are pruned right
+  /// here instead of waiting for the periodic cache cleaning, which would also
+  /// keep the History depth around. Durable writers retain their history for
+  /// late joiners and are only drained by eviction elsewhere.
+  pub fn wait_for_drain(&self, max_wait: StdDuration) -> bool {
+    let shared = &*self.shared;
+    let mut inner = shared.inner.lock().unwrap();
+    let deadline = Instant::now() + max_wait;
+    loop {
+      if shared.volatile
+        && inner.sent_frontier >= inner.last_seq
+        && (!inner.reliable_readers_present || inner.acked_before > inner.last_seq)
+      {
+        let remove_before = inner.last_seq.plus_1();
+        Self::remove_changes_before_locked(shared, &mut inner, remove_before);
+      }
+      if inner.changes.is_empty() {
+        return true;
+      }
+      let now = Instant::now();
+      if now >= deadline {
+        return false;
+      }
+      let (guard, _to) = shared.progress.wait_timeout(inner, deadline - now).unwrap();
+      inner = guard;
+    }
+  }
+
+  // --- coherent sets ---
+
+  /// Tag the samples written from now on as one coherent set.
+  pub fn begin_coherent_set(&self) {
+    let mut inner = self.shared.inner.lock().unwrap();
+    inner.coherent_changes = true;
+    inner.coherent_set_start = None;
+  }
+
+  /// End the coherent set. If samples were written in it, stores the end
+  /// marker, which the Writer sends like a sample, and returns `true`.
+  pub fn end_coherent_set(&self) -> bool {
+    let shared = &*self.shared;
+    let mut inner = shared.inner.lock().unwrap();
+    inner.coherent_changes = false;
+    if inner.coherent_set_start.take().is_none() {
+      return false;
+    }
+    // The marker is tiny, so it is stored even if the send window is full.
+    let write_options = WriteOptions::default().with_coherent_set(SequenceNumber::UNKNOWN);
+    Self::insert_locked(
+      shared,
+      &mut inner,
+      write_options,
+      DDSData::CoherentSetEnd,
+      shared.reliable_writer,
+    );
+    true
+  }
+
+  // --- coalescing statistics ---
+
+  /// Account one coalesced datagram of `samples` samples, which the Writer
+  /// held back for `hold_delay` before sending.
+  pub fn record_coalesced_datagram(&self, samples: u64, hold_delay: StdDuration) {
+    let stats = &mut self.shared.inner.lock().unwrap().coalescing;
+    stats.datagrams += 1;
+    stats.samples += samples;
+    stats.last_hold_delay = hold_delay;
+    stats.max_hold_delay = stats.max_hold_delay.max(hold_delay);
+  }
+
+  pub fn coalescing_statistics(&self) -> CoalescingStatistics {
+    self.shared.inner.lock().unwrap().coalescing
+  }
+
+  pub fn set_durability_store(&self, store: Arc<dyn DurabilityStore>, service: DurabilityService) {
+    self.shared.inner.lock().unwrap().durability_store = Some((store, service));
+  }
+
+  pub fn set_max_coalesce_delay(&self, max_delay: Option<StdDuration>) {
+    self.shared.inner.lock().unwrap().max_coalesce_delay = max_delay;
+  }
+
+  pub fn max_coalesce_delay(&self) -> Option<StdDuration> {
+    self.shared.inner.lock().unwrap().max_coalesce_delay
+  }
+
+  /// Ask the Writer to stop holding samples back. The caller rings the
+  /// doorbell.
+  pub fn request_flush(&self) {
+    self.shared.inner.lock().unwrap().flush_requested = true;
+  }
+
+  pub fn take_flush_request(&self) -> bool {
+    std::mem::take(&mut self.shared.inner.lock().unwrap().flush_requested)
+  }
+
+  // --- fan-out statistics ---
+
+  pub fn set_fan_out(&self, matched_readers: usize, large_fan_out: bool) {
+    let stats = &mut self.shared.inner.lock().unwrap().fan_out;
+    stats.matched_readers = matched_readers;
+    stats.large_fan_out = large_fan_out;
+  }
+
+  /// Number of readers currently matched by the Writer.
+  pub fn matched_readers(&self) -> usize {
+    self.shared.inner.lock().unwrap().fan_out.matched_readers
+  }
+
+  /// Account one sample repaired on request of `reader`.
+  pub fn record_repair(&self, reader: GUID, multicast: bool) {
+    let inner = &mut *self.shared.inner.lock().unwrap();
+    if multicast {
+      inner.fan_out.multicast_repairs += 1;
+    } else {
+      inner.fan_out.unicast_repairs += 1;
+    }
+    Self::reader_repair_load(inner, reader).repairs += 1;
+  }
+
+  /// Account one repair to `reader` postponed by the repair limit.
+  pub fn record_capped_repair(&self, reader: GUID) {
+    let inner = &mut *self.shared.inner.lock().unwrap();
+    inner.fan_out.capped_repairs += 1;
+    Self::reader_repair_load(inner, reader).capped_repairs += 1;
+  }
+
+  pub fn forget_reader_repairs(&self, reader: GUID) {
+    self
+      .shared
+      .inner
+      .lock()
+      .unwrap()
+      .reader_repairs
+      .remove(&reader);
+  }
+
+  fn reader_repair_load(inner: &mut Inner, reader: GUID) -> &mut ReaderRepairLoad {
+    inner
+      .reader_repairs
+      .entry(reader)
+      .or_insert(ReaderRepairLoad {
+        reader,
+        repairs: 0,
+        capped_repairs: 0,
+      })
+  }
+
+  pub fn fan_out_statistics(&self) -> FanOutStatistics {
+    let inner = self.shared.inner.lock().unwrap();
+    let mut readers: Vec<ReaderRepairLoad> = inner.reader_repairs.values().copied().collect();
+    readers.sort_by_key(|load| std::cmp::Reverse(load.repairs + load.capped_repairs));
+    FanOutStatistics {
+      readers,
+      ..inner.fan_out.clone()
+    }
+  }
+
+  // --- path MTU ---
+
+  pub fn set_reader_path_mtus(&self, reader_path_mtus: Vec<(GUID, u32)>) {
+    self.shared.inner.lock().unwrap().reader_path_mtus = reader_path_mtus;
+  }
+
+  pub fn reader_path_mtus(&self) -> Vec<(GUID, u32)> {
+    self.shared.inner.lock().unwrap().reader_path_mtus.clone()
+  }
+
+  // --- matched readers ---
+
+  pub fn set_matched_reader_qos(&self, reader: GUID, qos: QosPolicies) {
+    self
+      .shared
+      .inner
+      .lock()
+      .unwrap()
+      .matched_reader_qos
+      .insert(reader, qos);
+  }
+
+  // All at once, so that the DataWriter never sees only some of them removed.
+  pub fn remove_matched_readers(&self, readers: &[GUID]) {
+    let mut inner = self.shared.inner.lock().unwrap();
+    for reader in readers {
+      inner.matched_reader_qos.remove(reader);
+    }
+  }
+
+  pub fn matched_reader_qos(&self) -> BTreeMap<GUID, QosPolicies> {
+    self.shared.inner.lock().unwrap().matched_reader_qos.clone()
+  }
+
+  // --- acknowledgements per reader ---
+
+  pub fn set_reader_acked_before(&self, reader_acked_before: BTreeMap<GUID, SequenceNumber>) {
+    self.shared.inner.lock().unwrap().reader_acked_before = reader_acked_before;
+  }
+
+  /// Number of retained samples that `reader` has not acknowledged yet. Zero
+  /// if `reader` is not a matched reliable reader.
+  pub fn unacked_sample_count(&self, reader: GUID) -> usize {
+    let inner = self.shared.inner.lock().unwrap();
+    inner
+      .reader_acked_before
+      .get(&reader)
+      .map_or(0, |acked_before| Self::unacked_from(&inner, *acked_before))
+  }
+
+  /// The largest [`unacked_sample_count`](Self::unacked_sample_count) over
+  /// all matched reliable readers.
+  pub fn max_unacked_sample_count(&self) -> usize {
+    let inner = self.shared.inner.lock().unwrap();
+    inner
+      .reader_acked_before
+      .values()
+      .map(|acked_before| Self::unacked_from(&inner, *acked_before))
+      .max()
+      .unwrap_or(0)
+  }
+
+  // Samples that are no longer retained cannot be acknowledged any more, so
+  // they are not counted.
+  fn unacked_from(inner: &Inner, acked_before: SequenceNumber) -> usize {
+    let first_unacked = max(acked_before, inner.first_seq);
+    usize::try_from(i64::from(inner.last_seq - first_unacked) + 1).unwrap_or(0)
+  }
+
   /// Register `waker` to be notified when the acknowledgement frontier advances
   /// (used by the async `wait_for_acknowledgments` future).
   pub fn register_ack_waker(&self, waker: &Waker) {
@@ -631,4 +983,90 @@ mod tests {
     }
     assert_eq!(buf.retained_len(), max_retain + 10);
   }
+
+  // A volatile writer drains once everything is sent and acknowledged; until
+  // then the samples are kept for repair.
+  #[test]
+  fn volatile_writer_drains_when_sent_and_acked() {
+    let buf = WriterSendBuffer::new(
+      GUID::GUID_UNKNOWN,
+      "t".to_string(),
+      /* reliable_writer */ true,
+      /* is_builtin */ false,
+      /* volatile */ true,
+      /* window_limit */ 1000,
+      /* backlog_limit */ 1000,
+      /* max_retain */ 1000,
+    );
+    assert!(buf.wait_for_drain(StdDuration::ZERO)); // nothing written yet
+
+    buf.set_acked_frontier(Some(SequenceNumber::new(1)));
+    for _ in 0..3 {
+      assert!(admit_now(&buf, WriteOptions::default()));
+    }
+    // Not yet sent.
+    assert!(!buf.wait_for_drain(StdDuration::ZERO));
+    // Sent, but acknowledged only up to seq 2.
+    buf.set_sent_frontier(SequenceNumber::new(3));
+    buf.set_acked_frontier(Some(SequenceNumber::new(3)));
+    assert!(!buf.wait_for_drain(StdDuration::ZERO));
+    assert_eq!(buf.retained_len(), 3);
+    // Everything acknowledged: pruned and drained.
+    buf.set_acked_frontier(Some(SequenceNumber::new(4)));
+    assert!(buf.wait_for_drain(StdDuration::ZERO));
+    assert_eq!(buf.retained_len(), 0);
+    assert_eq!(buf.first_change_sequence_number(), SequenceNumber::new(4));
+  }
+
+  // A durable writer keeps its history for late joiners, so acknowledgement
+  // alone does not drain it. Eviction by the Writer does.
+  #[test]
+  fn durable_writer_drains_only_by_eviction() {
+    let buf = WriterSendBuffer::new(
+      GUID::GUID_UNKNOWN,
+      "t".to_string(),
+      /* reliable_writer */ true,
+      /* is_builtin */ false,
+      /* volatile */ false,
+      /* window_limit */ 1000,
+      /* backlog_limit */ 1000,
+      /* max_retain */ 1000,
+    );
+    assert!(admit_now(&buf, WriteOptions::default()));
+    buf.set_sent_frontier(SequenceNumber::new(1));
+    assert!(!buf.wait_for_drain(StdDuration::ZERO));
+    assert_eq!(buf.retained_len(), 1);
+
+    buf.remove_changes_before(SequenceNumber::new(2));
+    assert!(buf.wait_for_drain(StdDuration::ZERO));
+  }
+  // Samples written before the cutoff are evicted from the front of the
+  // buffer. A sample without a source timestamp stops the eviction.
+  #[test]
+  fn changes_written_before_cutoff_are_evicted() {
+    let buf = WriterSendBuffer::new(
+      GUID::GUID_UNKNOWN,
+      "t".to_string(),
+      /* reliable_writer */ true,
+      /* is_builtin */ false,
+      /* volatile */ false,
+      /* window_limit */ 1000,
+      /* backlog_limit */ 1000,
+      /* max_retain */ 1000,
+    );
+    let start = Timestamp::now();
+    let written_at = |ms| {
+      WriteOptionsBuilder::new()
+        .source_timestamp(start + crate::Duration::from_millis(ms))
+        .build()
+    };
+    assert!(admit_now(&buf, written_at(0)));
+    assert!(admit_now(&buf, written_at(100)));
+    assert!(admit_now(&buf, WriteOptions::default()));
+    assert!(admit_now(&buf, written_at(50)));
+
+    buf.remove_changes_written_before(start + crate::Duration::from_millis(200));
+    assert_eq!(buf.first_change_sequence_number(), SequenceNumber::new(3));
+    assert_eq!(buf.retained_len(), 2);
+  }
 }
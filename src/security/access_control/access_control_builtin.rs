@@ -1,4 +1,4 @@
-use std::{collections::HashMap, ops::Not};
+use std::{collections::HashMap, ops::Not, time::Duration};
 
 use bytes::Bytes;
 use chrono::Utc;
@@ -8,7 +8,7 @@ use crate::{
   rtps::constant::builtin_topic_names,
   security::{
     authentication::IdentityHandle,
-    certificate::{Certificate, DistinguishedName},
+    certificate::{Certificate, DistinguishedName, DEFAULT_CLOCK_SKEW_TOLERANCE},
     SecurityError, SecurityResult,
   },
 };
@@ -40,6 +40,8 @@ pub struct AccessControlBuiltin {
   permissions_ca_certificates: HashMap<PermissionsHandle, Certificate>,
   identity_to_permissions: HashMap<IdentityHandle, PermissionsHandle>,
   permissions_handle_counter: u32,
+  // Allowed clock skew when checking certificate validity periods
+  clock_skew_tolerance: Duration,
 }
 
 impl AccessControl for AccessControlBuiltin {}
@@ -53,6 +55,7 @@ impl AccessControlBuiltin {
       permissions_ca_certificates: HashMap::new(),
       identity_to_permissions: HashMap::new(),
       permissions_handle_counter: 0,
+      clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE,
     }
   }
 
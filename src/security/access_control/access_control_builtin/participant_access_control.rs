@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use chrono::Utc;
 
 use crate::{
@@ -30,8 +32,9 @@ use super::{
   s_mime_config_parser::SignedDocument,
   types::{
     BuiltinPermissionsCredentialToken, BuiltinPermissionsToken,
-    BuiltinPluginParticipantSecurityAttributes, QOS_GOVERNANCE_DOCUMENT_PROPERTY_NAME,
-    QOS_PERMISSIONS_CERTIFICATE_PROPERTY_NAME, QOS_PERMISSIONS_DOCUMENT_PROPERTY_NAME,
+    BuiltinPluginParticipantSecurityAttributes, QOS_CLOCK_SKEW_TOLERANCE_PROPERTY_NAME,
+    QOS_GOVERNANCE_DOCUMENT_PROPERTY_NAME, QOS_PERMISSIONS_CERTIFICATE_PROPERTY_NAME,
+    QOS_PERMISSIONS_DOCUMENT_PROPERTY_NAME,
   },
 };
 
@@ -62,6 +65,14 @@ impl AccessControlBuiltin {
     let joinable_topics = unprotected_topics || grant.check_participant_join(domain_id);
     Ok(joinable_topics)
   }
+
+  // Reject a certificate that is expired or not yet valid. A node with a
+  // skewed clock must not accept e.g. an expired CA certificate.
+  fn check_certificate_validity(&self, certificate: &Certificate) -> SecurityResult<()> {
+    certificate
+      .check_validity_at(Utc::now(), self.clock_skew_tolerance)
+      .map_err(|e| create_security_error_and_log!("{e:?}"))
+  }
 }
 
 // 9.4.3
@@ -73,6 +84,19 @@ impl ParticipantAccessControl for AccessControlBuiltin {
     domain_id: u16,
     participant_qos: &QosPolicies,
   ) -> SecurityResult<PermissionsHandle> {
+    if let Some(tolerance) =
+      participant_qos.get_optional_property(QOS_CLOCK_SKEW_TOLERANCE_PROPERTY_NAME)
+    {
+      self.clock_skew_tolerance = tolerance.parse().map(Duration::from_secs).map_err(|e| {
+        create_security_error_and_log!(
+          "Invalid clock skew tolerance {:?} in property {}: {}",
+          tolerance,
+          QOS_CLOCK_SKEW_TOLERANCE_PROPERTY_NAME,
+          e
+        )
+      })?;
+    }
+
     let permissions_ca_certificate = participant_qos
       .get_property(QOS_PERMISSIONS_CERTIFICATE_PROPERTY_NAME)
       .and_then(|certificate_uri| {
@@ -88,6 +112,7 @@ impl ParticipantAccessControl for AccessControlBuiltin {
         Certificate::from_pem(certificate_contents_pem)
           .map_err(|e| create_security_error_and_log!("{e:?}"))
       })?;
+    self.check_certificate_validity(&permissions_ca_certificate)?;
 
     let domain_rule = participant_qos
       .get_property(QOS_GOVERNANCE_DOCUMENT_PROPERTY_NAME)
@@ -155,7 +180,10 @@ impl ParticipantAccessControl for AccessControlBuiltin {
         Certificate::from_pem(certificate_contents_pem)
           .map_err(|e| create_security_error_and_log!("{e:?}"))
       })
-      .map(|cert| cert.subject_name().clone())?;
+      .and_then(|cert| {
+        self.check_certificate_validity(&cert)?;
+        Ok(cert.subject_name().clone())
+      })?;
 
     // Then verify that we have permissions for this subject name
     if domain_participant_permissions
@@ -215,6 +243,8 @@ impl ParticipantAccessControl for AccessControlBuiltin {
 
     let permissions_ca_certificate =
       self.get_permissions_ca_certificate(local_permissions_handle)?;
+    // The CA certificate may have expired since it was loaded.
+    self.check_certificate_validity(permissions_ca_certificate)?;
 
     let bin_prop_map = remote_credential_token
       .data_holder
@@ -232,6 +262,7 @@ impl ParticipantAccessControl for AccessControlBuiltin {
       ))
     })?;
 
+    self.check_certificate_validity(&remote_identity_certificate)?;
     let remote_subject_name = remote_identity_certificate.subject_name();
 
     // Extract remote signed permissions document bytes
@@ -240,6 +240,9 @@ pub(in crate::security) const QOS_GOVERNANCE_DOCUMENT_PROPERTY_NAME: &str =
   "dds.sec.access.governance";
 pub(in crate::security) const QOS_PERMISSIONS_DOCUMENT_PROPERTY_NAME: &str =
   "dds.sec.access.permissions";
+// Not in the spec: clock skew tolerance for certificate validity, in seconds
+pub(in crate::security) const QOS_CLOCK_SKEW_TOLERANCE_PROPERTY_NAME: &str =
+  "rustdds.sec.access.clock_skew_tolerance";
 
 // 9.4.2.2
 pub(super) struct BuiltinPermissionsToken {
@@ -17,13 +17,18 @@
 // Permissions documents. The verification of the two can use the same or
 // different Certificate instances.
 
+use std::time::Duration;
+
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use x509_certificate::{
   certificate::CapturedX509Certificate, EcdsaCurve, KeyAlgorithm, SignatureAlgorithm,
 };
-use der::Decode;
+use der::{asn1::ObjectIdentifier, Decode, Encode, Tag, Tagged};
+use const_oid::db::{rfc3280, rfc4519};
+use x509_cert::attr::AttributeTypeAndValue;
 use bcder::{encode::Values, Mode};
 
 use crate::security::{
@@ -41,8 +46,15 @@ use crate::security::{
 pub struct Certificate {
   cert: CapturedX509Certificate,
   subject_name: DistinguishedName,
+  // Validity period, both ends inclusive (RFC 5280 Section 4.1.2.5)
+  not_before: DateTime<Utc>,
+  not_after: DateTime<Utc>,
 }
 
+/// How much the clocks of the certificate issuer and this node may differ,
+/// unless configured otherwise.
+pub const DEFAULT_CLOCK_SKEW_TOLERANCE: Duration = Duration::from_secs(60);
+
 impl Certificate {
   pub fn from_pem(pem_data: impl AsRef<[u8]>) -> Result<Self, ConfigError> {
     let cert = CapturedX509Certificate::from_pem(pem_data)
@@ -51,9 +63,17 @@ impl Certificate {
     let other_cert = x509_cert::certificate::Certificate::from_der(cert.constructed_data())
       .map_err(to_config_error_parse("Cannot read X.509 Certificate(2)"))?;
 
+    let validity = &other_cert.tbs_certificate.validity;
+    let not_before = DateTime::from(validity.not_before.to_system_time());
+    let not_after = DateTime::from(validity.not_after.to_system_time());
     let subject_name = other_cert.tbs_certificate.subject.into();
 
-    Ok(Certificate { cert, subject_name })
+    Ok(Certificate {
+      cert,
+      subject_name,
+      not_before,
+      not_after,
+    })
   }
 
   pub fn to_pem(&self) -> String {
@@ -85,6 +105,39 @@ impl Certificate {
     &self.subject_name
   }
 
+  // Is the current time within the validity period? The period is widened by
+  // `clock_skew_tolerance` at both ends.
+  #[allow(dead_code)] // Access control uses check_validity_at for its error message
+  pub fn is_currently_valid(&self, clock_skew_tolerance: Duration) -> bool {
+    self
+      .check_validity_at(Utc::now(), clock_skew_tolerance)
+      .is_ok()
+  }
+
+  // Check that `time` is within the validity period, widened by
+  // `clock_skew_tolerance` at both ends.
+  pub fn check_validity_at(
+    &self,
+    time: DateTime<Utc>,
+    clock_skew_tolerance: Duration,
+  ) -> Result<(), ConfigError> {
+    let tolerance = chrono::Duration::from_std(clock_skew_tolerance)
+      .map_err(to_config_error_other("Clock skew tolerance out of range"))?;
+    if time + tolerance < self.not_before {
+      Err(ConfigError::Security(format!(
+        "Certificate '{}' is not valid before {}. Now is {}.",
+        self.subject_name, self.not_before, time
+      )))
+    } else if self.not_after < time - tolerance {
+      Err(ConfigError::Security(format!(
+        "Certificate '{}' expired at {}. Now is {}.",
+        self.subject_name, self.not_after, time
+      )))
+    } else {
+      Ok(())
+    }
+  }
+
   pub fn subject_name_der(&self) -> Result<Vec<u8>, ConfigError> {
     let er = &self.cert.subject_name().encode_ref();
     let mut buf = Vec::with_capacity(er.encoded_len(Mode::Der));
@@ -147,40 +200,136 @@ impl Certificate {
 //
 // See https://datatracker.ietf.org/doc/html/rfc4514
 //
-// It is supposed to be a structured type of key-value-mappings,
-// but for simplicity, we treat it just as a string for time being.
-// It is needs to process "Subject Name" and "Issuer Name" in
-// X.509 Certificates.
+// It needs to process "Subject Name" and "Issuer Name" in X.509
+// Certificates, and subject names in Permissions documents.
 //
-// Structured representation would allow standards-compliant
-// equality comparison (`.matches()`) according to
+// The name is kept as it was given, and also as its sequence of relative
+// distinguished names (RDNs), each a set of attribute type-value pairs. These
+// are used for standards-compliant equality comparison (`.matches()`)
+// according to
 // https://datatracker.ietf.org/doc/html/rfc5280#section-7.1
-//
-// TODO: Implement the structured format and matching.
 #[derive(Debug, Clone)]
-pub struct DistinguishedName(x509_cert::name::DistinguishedName);
+pub struct DistinguishedName {
+  name: x509_cert::name::DistinguishedName,
+  rdns: Vec<Vec<NameAttribute>>,
+}
+
+// An attribute type and value of a relative distinguished name, e.g.
+// "CN=srosCA", prepared for comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NameAttribute {
+  oid: ObjectIdentifier,
+  value: NameValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NameValue {
+  // A string, with insignificant whitespace removed, and case folded if the
+  // attribute type is matched case-insensitively.
+  Text(String),
+  // Any other value, compared by its DER encoding
+  Der(Vec<u8>),
+}
+
+// Attribute types, whose string values are compared case-insensitively
+// (caseIgnoreMatch or caseIgnoreIA5Match in RFC 4519 and RFC 5280).
+const CASE_INSENSITIVE_ATTRIBUTES: &[ObjectIdentifier] = &[
+  rfc4519::CN,
+  rfc4519::C,
+  rfc4519::DC,
+  rfc4519::DN_QUALIFIER,
+  rfc4519::GENERATION_QUALIFIER,
+  rfc4519::GIVEN_NAME,
+  rfc4519::INITIALS,
+  rfc4519::L,
+  rfc4519::O,
+  rfc4519::OU,
+  rfc4519::SERIAL_NUMBER,
+  rfc4519::SN,
+  rfc4519::ST,
+  rfc4519::STREET,
+  rfc4519::TITLE,
+  rfc4519::UID,
+  rfc3280::EMAIL_ADDRESS,
+  rfc3280::PSEUDONYM,
+];
+
+impl NameAttribute {
+  fn new(atv: &AttributeTypeAndValue) -> Self {
+    let value = match Self::decode_string(&atv.value) {
+      Some(text) => {
+        // RFC 4518 insignificant space handling: no leading or trailing
+        // spaces, and a single space between words.
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if CASE_INSENSITIVE_ATTRIBUTES.contains(&atv.oid) {
+          NameValue::Text(text.to_lowercase())
+        } else {
+          NameValue::Text(text)
+        }
+      }
+      None => NameValue::Der(atv.value.to_der().unwrap_or_default()),
+    };
+    NameAttribute {
+      oid: atv.oid,
+      value,
+    }
+  }
+
+  fn decode_string(value: &der::Any) -> Option<String> {
+    let bytes = value.value();
+    match value.tag() {
+      Tag::Utf8String | Tag::PrintableString | Tag::Ia5String => {
+        String::from_utf8(bytes.to_vec()).ok()
+      }
+      // Treated as Latin-1, like most implementations do
+      Tag::TeletexString => Some(bytes.iter().map(|b| char::from(*b)).collect()),
+      Tag::BmpString => char::decode_utf16(
+        bytes
+          .chunks_exact(2)
+          .map(|pair| u16::from_be_bytes([pair[0], pair[1]])),
+      )
+      .collect::<Result<String, _>>()
+      .ok(),
+      _ => None,
+    }
+  }
+}
+
 impl DistinguishedName {
   pub fn parse(s: &str) -> Result<DistinguishedName, ConfigError> {
     x509_cert::name::DistinguishedName::from_str(s)
-      .map(DistinguishedName)
+      .map(DistinguishedName::from)
       .map_err(|e| ConfigError::Parse(format!("Error parsing DistinguishedName: {e:?}")))
   }
 
   pub fn serialize(&self) -> String {
     // This returns the RFC 4514 Distinguished Name string representation
-    self.0.to_string()
+    self.name.to_string()
   }
 
-  // TODO is this a too strict equivalence?
+  // RFC 5280 Section 7.1: The names must have the same RDNs in the same
+  // order. RDNs are the same, if they have the same set of attributes. The
+  // attribute types are compared by OID, and string values as prepared by
+  // LDAP StringPrep (RFC 4518). Case folding approximates the Unicode case
+  // folding of StringPrep.
   pub fn matches(&self, other: &Self) -> bool {
-    self.0 == other.0
+    self.rdns.len() == other.rdns.len()
+      && self
+        .rdns
+        .iter()
+        .zip(&other.rdns)
+        .all(|(a, b)| a.len() == b.len() && a.iter().all(|attribute| b.contains(attribute)))
   }
 }
 
-// This conversion should be non-fallible?
 impl From<x509_cert::name::Name> for DistinguishedName {
   fn from(name: x509_cert::name::Name) -> DistinguishedName {
-    DistinguishedName(name)
+    let rdns = name
+      .0
+      .iter()
+      .map(|rdn| rdn.0.iter().map(NameAttribute::new).collect())
+      .collect();
+    DistinguishedName { name, rdns }
   }
 }
 
@@ -212,5 +361,75 @@ iHhbVPRB9Uxts9CwglxYgZoUdGUAxreYIIaLO4yLqw==
     let cert = Certificate::from_pem(cert_pem).unwrap();
 
     println!("{cert:?}");
+
+    // Subject name from the certificate matches one parsed from a string
+    let parsed = DistinguishedName::parse("cn=SROS2CA").unwrap();
+    assert!(cert.subject_name().matches(&parsed));
+  }
+
+  #[test]
+  pub fn validity_period() {
+    // Self-signed, valid from 2020-01-01 to 2021-01-01
+    let expired_pem = r#"-----BEGIN CERTIFICATE-----
+MIIBfTCCASOgAwIBAgIUJb+dssbBMeDOWVLs9dbU/9uDbCkwCgYIKoZIzj0EAwIw
+FDESMBAGA1UEAwwJZXhwaXJlZENBMB4XDTIwMDEwMTAwMDAwMFoXDTIxMDEwMTAw
+MDAwMFowFDESMBAGA1UEAwwJZXhwaXJlZENBMFkwEwYHKoZIzj0CAQYIKoZIzj0D
+AQcDQgAElqMEy4IU08BDIn2WKTNaXmqYF8g9kPSjMBiBb4O1uQFt/CDHx1lrVDjc
+3z9cRd2M2Uo/uWruAPKSFZaR7uT/t6NTMFEwHQYDVR0OBBYEFCg9LpVsbej0NOvT
+NDkCyv54xRhuMB8GA1UdIwQYMBaAFCg9LpVsbej0NOvTNDkCyv54xRhuMA8GA1Ud
+EwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSAAwRQIgDTnvL2Vfni7N6tnlrPCOrdg6
+YJVWPjmygLNZkfwxuawCIQCFWMjJ9OUAP1dy+6F6H3n6cv+9SANEAeQcVxyFIGwR
+1A==
+-----END CERTIFICATE-----
+"#;
+    let cert = Certificate::from_pem(expired_pem).unwrap();
+    let time = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+    assert_eq!(cert.not_before, time("2020-01-01T00:00:00Z"));
+    assert_eq!(cert.not_after, time("2021-01-01T00:00:00Z"));
+
+    assert!(!cert.is_currently_valid(DEFAULT_CLOCK_SKEW_TOLERANCE));
+    match cert.check_validity_at(Utc::now(), DEFAULT_CLOCK_SKEW_TOLERANCE) {
+      Err(ConfigError::Security(msg)) => assert!(msg.contains("expired"), "{msg}"),
+      other => panic!("expected expiry error, got {other:?}"),
+    }
+    let early = time("2019-12-31T23:59:00Z");
+    match cert.check_validity_at(early, Duration::ZERO) {
+      Err(ConfigError::Security(msg)) => assert!(msg.contains("not valid before"), "{msg}"),
+      other => panic!("expected not-yet-valid error, got {other:?}"),
+    }
+
+    // Clock skew tolerance widens the period at both ends.
+    let tolerance = Duration::from_secs(120);
+    assert!(cert.check_validity_at(early, tolerance).is_ok());
+    let late = time("2021-01-01T00:01:00Z");
+    assert!(cert.check_validity_at(late, Duration::ZERO).is_err());
+    assert!(cert.check_validity_at(late, tolerance).is_ok());
+  }
+
+  #[test]
+  pub fn distinguished_name_matching() {
+    let matches = |a: &str, b: &str| {
+      DistinguishedName::parse(a)
+        .unwrap()
+        .matches(&DistinguishedName::parse(b).unwrap())
+    };
+
+    // Attribute types by OID, values case-insensitively
+    assert!(matches("CN=srosCA", "cn=srosca"));
+    assert!(matches("CN=srosCA", "2.5.4.3=SROSCA"));
+    assert!(matches("CN=a,O=b", "cn=A,o=B"));
+    assert!(!matches("CN=srosCA", "CN=srosCB"));
+    assert!(!matches("CN=srosCA", "O=srosCA"));
+    // Insignificant whitespace
+    assert!(matches("CN=some   subject", "CN=Some subject"));
+    assert!(!matches("CN=some subject", "CN=somesubject"));
+    // RDNs in order, but the attributes of an RDN in any order
+    assert!(!matches("CN=a,O=b", "O=b,CN=a"));
+    assert!(!matches("CN=a,O=b", "CN=a"));
+    assert!(matches("CN=a+O=b,C=FI", "O=b+CN=a,C=fi"));
+    assert!(!matches("CN=a+O=b", "CN=a"));
+    // Unknown attribute types are case-sensitive
+    assert!(matches("1.2.3.4=Abc", "1.2.3.4=Abc"));
+    assert!(!matches("1.2.3.4=Abc", "1.2.3.4=abc"));
   }
 }
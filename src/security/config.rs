@@ -1,18 +1,20 @@
 use std::{
   borrow::Borrow,
   path::{Path, PathBuf},
+  time::Duration,
 };
 
 use crate::{
   qos, security,
   security::{
-    authentication::authentication_builtin::types::CertificateAlgorithm, private_key::PrivateKey,
+    authentication::authentication_builtin::types::CertificateAlgorithm,
+    certificate::DEFAULT_CLOCK_SKEW_TOLERANCE, private_key::PrivateKey,
   },
 };
 use super::{
   access_control::access_control_builtin::types::{
-    QOS_GOVERNANCE_DOCUMENT_PROPERTY_NAME, QOS_PERMISSIONS_CERTIFICATE_PROPERTY_NAME,
-    QOS_PERMISSIONS_DOCUMENT_PROPERTY_NAME,
+    QOS_CLOCK_SKEW_TOLERANCE_PROPERTY_NAME, QOS_GOVERNANCE_DOCUMENT_PROPERTY_NAME,
+    QOS_PERMISSIONS_CERTIFICATE_PROPERTY_NAME, QOS_PERMISSIONS_DOCUMENT_PROPERTY_NAME,
   },
   authentication::authentication_builtin::types::{
     QOS_IDENTITY_CA_PROPERTY_NAME, QOS_IDENTITY_CERTIFICATE_PROPERTY_NAME,
@@ -75,6 +77,10 @@ pub struct DomainParticipantSecurityConfigFiles {
   pub participant_permissions_document: PathBuf,
   /// CRLs are not yet implemented.
   pub certificate_revocation_list: Option<PathBuf>,
+  /// How far the current time may be outside the validity period of the
+  /// permissions CA and identity certificates, to allow for clock skew
+  /// between nodes. Expired or not yet valid certificates are rejected.
+  pub certificate_clock_skew_tolerance: Duration,
 }
 
 impl DomainParticipantSecurityConfigFiles {
@@ -98,6 +104,7 @@ impl DomainParticipantSecurityConfigFiles {
       domain_governance_document: own_and_append(&d, "governance.p7s"),
       participant_permissions_document: own_and_append(&d, "permissions.p7s"),
       certificate_revocation_list: None, // "crl.pem"
+      certificate_clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE,
     }
   }
 
@@ -123,6 +130,7 @@ impl DomainParticipantSecurityConfigFiles {
       domain_governance_document: own_and_append(&d, "governance.p7s"),
       participant_permissions_document: own_and_append(&d, "permissions.p7s"),
       certificate_revocation_list: None, // "crl.pem"
+      certificate_clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE,
     }
   }
 
@@ -169,6 +177,10 @@ impl DomainParticipantSecurityConfigFiles {
         &self.participant_permissions_document,
       ),
     ];
+    value.push(mk_string_prop(
+      QOS_CLOCK_SKEW_TOLERANCE_PROPERTY_NAME,
+      self.certificate_clock_skew_tolerance.as_secs().to_string(),
+    ));
     if let PrivateSigningKey::Files { file_password, .. } = self.participant_identity_private_key {
       value.push(mk_string_prop(QOS_PASSWORD_PROPERTY_NAME, file_password));
     }
@@ -1,9 +1,12 @@
 mod cdr_adapters;
+#[cfg(feature = "json")]
+mod json_adapter;
 
 pub(crate) mod pl_cdr_adapters;
 pub(crate) mod speedy_pl_cdr_helpers;
 
 mod representation_identifier;
+pub mod xcdr2;
 
 // Most of the CDR encoding/decoding comes from this external crate
 pub use cdr_encoding::{
@@ -12,8 +15,11 @@ pub use cdr_encoding::{
 // Export some parts of inner modules
 pub use cdr_adapters::{
   deserialize_from_cdr_with_decoder_and_rep_id, deserialize_from_cdr_with_rep_id,
-  to_writer_with_rep_id, CDRDeserializerAdapter, CDRSerializerAdapter, CdrDeserializeSeedDecoder,
+  to_writer_with_rep_id, CDR2SerializerAdapter, CDRDeserializerAdapter, CDRSerializerAdapter,
+  CdrDeserializeSeedDecoder,
 };
+#[cfg(feature = "json")]
+pub use json_adapter::{JsonAdapter, JsonDecoder};
 pub use representation_identifier::RepresentationIdentifier;
 
 // Compute how much padding bytes are needed to
@@ -15,6 +15,7 @@ use crate::{
   RepresentationIdentifier,
 };
 pub use super::*;
+use super::xcdr2::{self, ExtensibilityKind, Xcdr2Deserializer};
 
 /// This type adapts [`CdrSerializer`] (which implements
 /// [`serde::Serializer`]) to work as a [`no_key::SerializerAdapter`] and
@@ -74,6 +75,50 @@ where
   }
 }
 
+/// Like [`CDRSerializerAdapter`], but serializes in XCDR2, i.e. encoding
+/// version 2 of DDS-XTypes.
+///
+/// The type parameter X tells the extensibility of the data type, one of
+/// [`xcdr2::Final`], [`xcdr2::Appendable`] and [`xcdr2::Mutable`]. It also
+/// selects the representation identifier.
+pub struct CDR2SerializerAdapter<D, X = xcdr2::Final, BO = LittleEndian>
+where
+  BO: ByteOrder,
+{
+  phantom: PhantomData<D>,
+  extensibility: PhantomData<X>,
+  ghost: PhantomData<BO>,
+}
+
+impl<D, X, BO> no_key::SerializerAdapter<D> for CDR2SerializerAdapter<D, X, BO>
+where
+  D: Serialize,
+  X: ExtensibilityKind,
+  BO: ByteOrder + 'static,
+{
+  type Error = Error;
+
+  fn output_encoding() -> RepresentationIdentifier {
+    X::EXTENSIBILITY.representation_identifier(TypeId::of::<BO>() == TypeId::of::<LittleEndian>())
+  }
+
+  fn to_bytes(value: &D) -> Result<Bytes> {
+    xcdr2::to_vec::<D, BO>(value, X::EXTENSIBILITY).map(Bytes::from)
+  }
+}
+
+impl<D, X, BO> with_key::SerializerAdapter<D> for CDR2SerializerAdapter<D, X, BO>
+where
+  D: Keyed + Serialize,
+  <D as Keyed>::K: Serialize,
+  X: ExtensibilityKind,
+  BO: ByteOrder + 'static,
+{
+  fn key_to_bytes(value: &D::K) -> Result<Bytes> {
+    xcdr2::to_vec::<D::K, BO>(value, X::EXTENSIBILITY).map(Bytes::from)
+  }
+}
+
 /// Serialize
 pub fn to_writer_with_rep_id<T, W>(
   writer: W,
@@ -96,6 +141,9 @@ where
 /// work as a [`with_key::DeserializerAdapter`] and
 /// [`no_key::DeserializerAdapter`].
 ///
+/// Both classic CDR (XCDR1) and XCDR2 data are accepted. XCDR2 data is decoded
+/// with [`Xcdr2Deserializer`].
+///
 /// CdrDeserializer cannot directly implement
 /// the trait itself, because CdrDeserializer has the type parameter BO open,
 /// and the adapter needs to be bi-endian.
@@ -103,10 +151,16 @@ pub struct CDRDeserializerAdapter<D> {
   phantom: PhantomData<D>,
 }
 
-const REPR_IDS: [RepresentationIdentifier; 3] = [
+const REPR_IDS: [RepresentationIdentifier; 9] = [
   RepresentationIdentifier::CDR_BE,
   RepresentationIdentifier::CDR_LE,
   RepresentationIdentifier::PL_CDR_LE,
+  RepresentationIdentifier::XCDR2_BE,
+  RepresentationIdentifier::XCDR2_LE,
+  RepresentationIdentifier::D_CDR2_BE,
+  RepresentationIdentifier::D_CDR2_LE,
+  RepresentationIdentifier::PL_XCDR2_BE,
+  RepresentationIdentifier::PL_XCDR2_LE,
 ];
 
 impl<D> no_key::DeserializerAdapter<D> for CDRDeserializerAdapter<D> {
@@ -121,6 +175,19 @@ impl<D> no_key::DeserializerAdapter<D> for CDRDeserializerAdapter<D> {
   fn transform_decoded(decoded: Self::Decoded) -> D {
     decoded
   }
+
+  // Decoded is D, so the decoder can work in place.
+  fn from_bytes_into_with<'de, S>(
+    input_bytes: &'de [u8],
+    encoding: RepresentationIdentifier,
+    decoder: S,
+    target: &mut D,
+  ) -> std::result::Result<(), S::Error>
+  where
+    S: no_key::Decode<'de, Self::Decoded>,
+  {
+    decoder.decode_bytes_into(input_bytes, encoding, target)
+  }
 }
 
 impl<D> with_key::DeserializerAdapter<D> for CDRDeserializerAdapter<D>
@@ -167,6 +234,15 @@ where
   fn decode_bytes(self, input_bytes: &'de [u8], encoding: RepresentationIdentifier) -> Result<D> {
     deserialize_from_cdr_with_decoder_and_rep_id(input_bytes, encoding, PhantomData).map(|r| r.0)
   }
+
+  fn decode_bytes_into(
+    self,
+    input_bytes: &'de [u8],
+    encoding: RepresentationIdentifier,
+    target: &mut D,
+  ) -> Result<()> {
+    deserialize_from_cdr_in_place_with_rep_id(input_bytes, encoding, target)
+  }
 }
 
 impl<'de, Dec, DecKey> with_key::Decode<'de, Dec, DecKey> for CdrDeserializeDecoder<Dec>
@@ -252,6 +328,42 @@ where
   deserialize_from_cdr_with_decoder_and_rep_id::<PhantomData<T>>(input_bytes, encoding, PhantomData)
 }
 
+/// Decode into an existing value with [`Deserialize::deserialize_in_place`],
+/// which reuses the allocations of e.g. `Vec` and `String` fields.
+pub fn deserialize_from_cdr_in_place_with_rep_id<'de, T>(
+  input_bytes: &'de [u8],
+  encoding: RepresentationIdentifier,
+  target: &mut T,
+) -> Result<()>
+where
+  T: Deserialize<'de>,
+{
+  match encoding {
+    RepresentationIdentifier::CDR_LE | RepresentationIdentifier::PL_CDR_LE => {
+      T::deserialize_in_place(
+        &mut CdrDeserializer::<LittleEndian>::new(input_bytes),
+        target,
+      )
+    }
+    RepresentationIdentifier::CDR_BE | RepresentationIdentifier::PL_CDR_BE => {
+      T::deserialize_in_place(&mut CdrDeserializer::<BigEndian>::new(input_bytes), target)
+    }
+    repr_id => match xcdr2::Extensibility::from_representation_identifier(repr_id) {
+      Some(extensibility) if xcdr2::is_little_endian(repr_id) => T::deserialize_in_place(
+        &mut Xcdr2Deserializer::<LittleEndian>::new(input_bytes, extensibility),
+        target,
+      ),
+      Some(extensibility) => T::deserialize_in_place(
+        &mut Xcdr2Deserializer::<BigEndian>::new(input_bytes, extensibility),
+        target,
+      ),
+      None => Err(Error::Message(format!(
+        "Unknown serialization format. requested={repr_id:?}."
+      ))),
+    },
+  }
+}
+
 /// Decode type using the given [`DeserializeSeed`]-based decoder.
 ///
 /// Returns deserialized object and byte count of stream consumed.
@@ -280,8 +392,104 @@ where
       ))
     }
 
-    repr_id => Err(Error::Message(format!(
-      "Unknown serialization format. requested={repr_id:?}."
-    ))),
+    repr_id => match xcdr2::Extensibility::from_representation_identifier(repr_id) {
+      Some(extensibility) if xcdr2::is_little_endian(repr_id) => {
+        xcdr2::from_bytes_with::<S, LittleEndian>(input_bytes, extensibility, decoder)
+      }
+      Some(extensibility) => {
+        xcdr2::from_bytes_with::<S, BigEndian>(input_bytes, extensibility, decoder)
+      }
+      None => Err(Error::Message(format!(
+        "Unknown serialization format. requested={repr_id:?}."
+      ))),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dds::adapters::no_key::{Decode, DeserializerAdapter};
+
+  #[test]
+  fn in_place_decoding_reuses_allocation() {
+    let bytes = to_vec::<Vec<u32>, LittleEndian>(&vec![1, 2, 3]).unwrap();
+    let mut target: Vec<u32> = Vec::with_capacity(16);
+    let buffer = target.as_ptr();
+    CdrDeserializeDecoder(PhantomData)
+      .decode_bytes_into(&bytes, RepresentationIdentifier::CDR_LE, &mut target)
+      .unwrap();
+    assert_eq!(target, vec![1, 2, 3]);
+    assert_eq!(target.as_ptr(), buffer);
+
+    // The adapter forwards to the decoder.
+    let mut target = vec![9];
+    CDRDeserializerAdapter::from_bytes_into_with(
+      &bytes,
+      RepresentationIdentifier::CDR_LE,
+      CdrDeserializeDecoder(PhantomData),
+      &mut target,
+    )
+    .unwrap();
+    assert_eq!(target, vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn xcdr2_adapters() {
+    use crate::dds::adapters::no_key::SerializerAdapter;
+
+    #[derive(Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Sample {
+      id: u32,
+      names: Vec<String>,
+      extra: Option<u64>,
+    }
+    let sample = Sample {
+      id: 3,
+      names: vec!["a".to_string(), "bc".to_string()],
+      extra: None,
+    };
+
+    type Mutable<BO> = CDR2SerializerAdapter<Sample, xcdr2::Mutable, BO>;
+    for (encoding, bytes) in [
+      (
+        Mutable::<LittleEndian>::output_encoding(),
+        Mutable::<LittleEndian>::to_bytes(&sample).unwrap(),
+      ),
+      (
+        Mutable::<BigEndian>::output_encoding(),
+        Mutable::<BigEndian>::to_bytes(&sample).unwrap(),
+      ),
+      (
+        CDR2SerializerAdapter::<Sample>::output_encoding(),
+        CDR2SerializerAdapter::<Sample>::to_bytes(&sample).unwrap(),
+      ),
+      // XCDR1 is still decoded.
+      (
+        CDRSerializerAdapter::<Sample>::output_encoding(),
+        CDRSerializerAdapter::<Sample>::to_bytes(&sample).unwrap(),
+      ),
+    ] {
+      assert!(CDRDeserializerAdapter::<Sample>::supported_encodings().contains(&encoding));
+      let decoded: Sample = CdrDeserializeDecoder(PhantomData)
+        .decode_bytes(&bytes, encoding)
+        .unwrap();
+      assert_eq!(decoded, sample, "{encoding:?}");
+      let mut target = Sample {
+        id: 0,
+        names: vec![],
+        extra: Some(1),
+      };
+      deserialize_from_cdr_in_place_with_rep_id(&bytes, encoding, &mut target).unwrap();
+      assert_eq!(target, sample, "{encoding:?}");
+    }
+    assert_eq!(
+      Mutable::<BigEndian>::output_encoding(),
+      RepresentationIdentifier::PL_XCDR2_BE
+    );
+    assert_eq!(
+      CDR2SerializerAdapter::<Sample>::output_encoding(),
+      RepresentationIdentifier::XCDR2_LE
+    );
   }
 }
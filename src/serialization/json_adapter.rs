@@ -0,0 +1,185 @@
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use bytes::Bytes;
+
+use crate::{
+  dds::{
+    adapters::{no_key, with_key},
+    key::Keyed,
+  },
+  RepresentationIdentifier,
+};
+
+/// Serializes and deserializes data as JSON text with `serde_json`. This is a
+/// [`no_key`] and [`with_key`] `SerializerAdapter` and `DeserializerAdapter`.
+///
+/// JSON is not a standard DDS data representation, so this interoperates only
+/// with other participants using this adapter. The data is marked with
+/// [`RepresentationIdentifier::JSON`], so that DataReaders using other adapters
+/// reject it instead of misinterpreting it. This is meant for bridging into web
+/// tooling and for debugging, where CDR bytes are opaque.
+///
+/// Requires the feature "json".
+pub struct JsonAdapter<D> {
+  phantom: PhantomData<D>,
+}
+
+const REPR_IDS: [RepresentationIdentifier; 1] = [RepresentationIdentifier::JSON];
+
+fn to_json_bytes<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<Bytes> {
+  serde_json::to_vec(value).map(Bytes::from)
+}
+
+impl<D> no_key::SerializerAdapter<D> for JsonAdapter<D>
+where
+  D: Serialize,
+{
+  type Error = serde_json::Error;
+
+  fn output_encoding() -> RepresentationIdentifier {
+    RepresentationIdentifier::JSON
+  }
+
+  fn to_bytes(value: &D) -> serde_json::Result<Bytes> {
+    to_json_bytes(value)
+  }
+}
+
+impl<D> with_key::SerializerAdapter<D> for JsonAdapter<D>
+where
+  D: Keyed + Serialize,
+  <D as Keyed>::K: Serialize,
+{
+  fn key_to_bytes(value: &D::K) -> serde_json::Result<Bytes> {
+    to_json_bytes(value)
+  }
+}
+
+impl<D> no_key::DeserializerAdapter<D> for JsonAdapter<D> {
+  type Error = serde_json::Error;
+  type Decoded = D;
+
+  fn supported_encodings() -> &'static [RepresentationIdentifier] {
+    &REPR_IDS
+  }
+
+  fn transform_decoded(decoded: Self::Decoded) -> D {
+    decoded
+  }
+}
+
+impl<D> with_key::DeserializerAdapter<D> for JsonAdapter<D>
+where
+  D: Keyed + DeserializeOwned,
+  <D as Keyed>::K: DeserializeOwned,
+{
+  type DecodedKey = D::K;
+
+  fn transform_decoded_key(decoded_key: Self::DecodedKey) -> D::K {
+    decoded_key
+  }
+}
+
+impl<D> no_key::DefaultDecoder<D> for JsonAdapter<D>
+where
+  D: DeserializeOwned,
+{
+  type Decoder = JsonDecoder<D>;
+  const DECODER: Self::Decoder = JsonDecoder(PhantomData);
+}
+
+impl<D> with_key::DefaultDecoder<D> for JsonAdapter<D>
+where
+  D: Keyed + DeserializeOwned,
+  D::K: DeserializeOwned,
+{
+  type Decoder = JsonDecoder<D>;
+  const DECODER: Self::Decoder = JsonDecoder(PhantomData);
+}
+
+/// Decode type based on a `serde::Deserialize` implementation from JSON.
+pub struct JsonDecoder<D>(PhantomData<D>);
+
+impl<D> Clone for JsonDecoder<D> {
+  fn clone(&self) -> Self {
+    Self(self.0)
+  }
+}
+
+impl<'de, D> no_key::Decode<'de, D> for JsonDecoder<D>
+where
+  D: Deserialize<'de>,
+{
+  type Error = serde_json::Error;
+
+  fn decode_bytes(
+    self,
+    input_bytes: &'de [u8],
+    _encoding: RepresentationIdentifier,
+  ) -> serde_json::Result<D> {
+    serde_json::from_slice(input_bytes)
+  }
+}
+
+impl<'de, Dec, DecKey> with_key::Decode<'de, Dec, DecKey> for JsonDecoder<Dec>
+where
+  Dec: Deserialize<'de>,
+  DecKey: Deserialize<'de>,
+{
+  fn decode_key_bytes(
+    self,
+    input_key_bytes: &'de [u8],
+    _encoding: RepresentationIdentifier,
+  ) -> serde_json::Result<DecKey> {
+    serde_json::from_slice(input_key_bytes)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dds::adapters::{
+    no_key::{DeserializerAdapter, SerializerAdapter},
+    with_key::{DeserializerAdapter as _, SerializerAdapter as _},
+  };
+
+  #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+  struct Reading {
+    sensor: String,
+    value: f64,
+  }
+
+  impl Keyed for Reading {
+    type K = String;
+    fn key(&self) -> String {
+      self.sensor.clone()
+    }
+  }
+
+  #[test]
+  fn json_round_trip() {
+    let reading = Reading {
+      sensor: "temp".to_string(),
+      value: 21.5,
+    };
+    assert_eq!(
+      JsonAdapter::<Reading>::output_encoding(),
+      RepresentationIdentifier::JSON
+    );
+    let bytes = JsonAdapter::to_bytes(&reading).unwrap();
+    assert_eq!(&bytes[..], br#"{"sensor":"temp","value":21.5}"#);
+    let decoded: Reading = JsonAdapter::from_bytes(&bytes, RepresentationIdentifier::JSON).unwrap();
+    assert_eq!(decoded, reading);
+
+    let key_bytes = JsonAdapter::<Reading>::key_to_bytes(&reading.key()).unwrap();
+    assert_eq!(&key_bytes[..], br#""temp""#);
+    let key =
+      JsonAdapter::<Reading>::key_from_bytes(&key_bytes, RepresentationIdentifier::JSON).unwrap();
+    assert_eq!(key, "temp");
+
+    assert!(
+      JsonAdapter::<Reading>::from_bytes(b"\x00\x01", RepresentationIdentifier::JSON).is_err()
+    );
+  }
+}
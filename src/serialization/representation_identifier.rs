@@ -3,8 +3,13 @@ use std::io;
 use speedy::{Readable, Writable};
 use byteorder::ReadBytesExt;
 
+use crate::dds::qos::policy::{
+  DataRepresentationId, XCDR2_DATA_REPRESENTATION, XCDR_DATA_REPRESENTATION,
+  XML_DATA_REPRESENTATION,
+};
+
 /// Used to identify serialization format of payload data over RTPS.
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Readable, Writable)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Readable, Writable)]
 pub struct RepresentationIdentifier {
   pub(crate) bytes: [u8; 2], // semi-public for serialization elsewhere
 }
@@ -84,6 +89,13 @@ impl RepresentationIdentifier {
     bytes: [0x00, 0x0b],
   };
 
+  /// Non-standard: JSON text, as written by `JsonAdapter` (feature "json").
+  /// Standard identifiers have a zero first byte, so this does not collide
+  /// with them.
+  pub const JSON: Self = Self {
+    bytes: [0x80, 0x4a], // 'J'
+  };
+
   // Reads two bytes to form a `RepresentationIdentifier`
   pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
     let mut reader = io::Cursor::new(bytes);
@@ -95,4 +107,22 @@ impl RepresentationIdentifier {
   pub fn to_bytes(self) -> [u8; 2] {
     self.bytes
   }
+
+  // The DATA_REPRESENTATION QoS policy value of data in this encapsulation,
+  // DDS-XTypes v1.3 Table 60
+  pub(crate) fn data_representation_id(self) -> Option<DataRepresentationId> {
+    match self {
+      Self::CDR_BE | Self::CDR_LE | Self::PL_CDR_BE | Self::PL_CDR_LE => {
+        Some(XCDR_DATA_REPRESENTATION)
+      }
+      Self::XCDR2_BE
+      | Self::XCDR2_LE
+      | Self::D_CDR2_BE
+      | Self::D_CDR2_LE
+      | Self::PL_XCDR2_BE
+      | Self::PL_XCDR2_LE => Some(XCDR2_DATA_REPRESENTATION),
+      Self::XML => Some(XML_DATA_REPRESENTATION),
+      _ => None,
+    }
+  }
 }
@@ -0,0 +1,1690 @@
+//! Extended CDR encoding version 2 (XCDR2) with Serde, as specified in
+//! DDS-XTypes v1.3 Section 7.4.3.
+//!
+//! XCDR2 differs from classic CDR (XCDR1) as follows:
+//! * 8-byte values are aligned to 4 bytes only.
+//! * Sequences and arrays of non-primitive elements are preceded by a DHEADER,
+//!   the byte length of the serialized collection.
+//! * Optional values (`Option`) are preceded by a boolean presence flag.
+//! * Structs of APPENDABLE types are preceded by a DHEADER, so that a
+//!   receiver can skip members that were appended to the type later.
+//! * Structs of MUTABLE types are preceded by a DHEADER, and each member by an
+//!   EMHEADER that carries the member id. Absent optional members are left
+//!   out.
+//!
+//! Serde does not describe the extensibility of types, so all structs in the
+//! data are encoded with the same [`Extensibility`], which is chosen by the
+//! encapsulation (representation identifier) of the data. Member ids of
+//! MUTABLE structs are the positions of the fields in the struct declaration,
+//! which is the IDL default. Enums are encoded as 32-bit enumerations, enums
+//! with data as FINAL unions with a 32-bit discriminator, and tuples as
+//! arrays.
+//!
+//! Serde does not tell the element type of an empty sequence, so an empty
+//! sequence is always written as if its elements were primitive, i.e. without
+//! DHEADER. Empty sequences with a DHEADER are decoded correctly. Elements that
+//! are enums are treated as primitive, also if they carry data.
+
+use std::{marker::PhantomData, mem};
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use serde::{
+  de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+  },
+  ser::{self, Serialize},
+};
+
+use super::{Error, RepresentationIdentifier, Result};
+
+/// Extensibility of the types in XCDR2 data. See DDS-XTypes v1.3 Section
+/// 7.2.2.4.4.4.8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Extensibility {
+  /// The type cannot change. Encapsulation CDR2, i.e.
+  /// [`XCDR2_LE`](RepresentationIdentifier::XCDR2_LE) or
+  /// [`XCDR2_BE`](RepresentationIdentifier::XCDR2_BE).
+  Final,
+  /// Members can be added to the end of the type. Encapsulation D_CDR2, i.e.
+  /// [`D_CDR2_LE`](RepresentationIdentifier::D_CDR2_LE) or
+  /// [`D_CDR2_BE`](RepresentationIdentifier::D_CDR2_BE).
+  Appendable,
+  /// Members are identified by their id, so they can be added and removed.
+  /// Encapsulation PL_CDR2, i.e.
+  /// [`PL_XCDR2_LE`](RepresentationIdentifier::PL_XCDR2_LE) or
+  /// [`PL_XCDR2_BE`](RepresentationIdentifier::PL_XCDR2_BE).
+  Mutable,
+}
+
+impl Extensibility {
+  /// Extensibility of data with encapsulation `encoding`, if it is XCDR2.
+  pub fn from_representation_identifier(encoding: RepresentationIdentifier) -> Option<Self> {
+    match encoding {
+      RepresentationIdentifier::XCDR2_LE | RepresentationIdentifier::XCDR2_BE => Some(Self::Final),
+      RepresentationIdentifier::D_CDR2_LE | RepresentationIdentifier::D_CDR2_BE => {
+        Some(Self::Appendable)
+      }
+      RepresentationIdentifier::PL_XCDR2_LE | RepresentationIdentifier::PL_XCDR2_BE => {
+        Some(Self::Mutable)
+      }
+      _ => None,
+    }
+  }
+
+  /// Encapsulation of XCDR2 data of this extensibility.
+  pub fn representation_identifier(self, little_endian: bool) -> RepresentationIdentifier {
+    match (self, little_endian) {
+      (Self::Final, true) => RepresentationIdentifier::XCDR2_LE,
+      (Self::Final, false) => RepresentationIdentifier::XCDR2_BE,
+      (Self::Appendable, true) => RepresentationIdentifier::D_CDR2_LE,
+      (Self::Appendable, false) => RepresentationIdentifier::D_CDR2_BE,
+      (Self::Mutable, true) => RepresentationIdentifier::PL_XCDR2_LE,
+      (Self::Mutable, false) => RepresentationIdentifier::PL_XCDR2_BE,
+    }
+  }
+}
+
+/// Selects the [`Extensibility`] in type parameters, e.g. of
+/// [`CDR2SerializerAdapter`](crate::serialization::CDR2SerializerAdapter).
+pub trait ExtensibilityKind {
+  const EXTENSIBILITY: Extensibility;
+}
+
+/// [`Extensibility::Final`] as a type
+pub struct Final;
+/// [`Extensibility::Appendable`] as a type
+pub struct Appendable;
+/// [`Extensibility::Mutable`] as a type
+pub struct Mutable;
+
+impl ExtensibilityKind for Final {
+  const EXTENSIBILITY: Extensibility = Extensibility::Final;
+}
+impl ExtensibilityKind for Appendable {
+  const EXTENSIBILITY: Extensibility = Extensibility::Appendable;
+}
+impl ExtensibilityKind for Mutable {
+  const EXTENSIBILITY: Extensibility = Extensibility::Mutable;
+}
+
+// XCDR2 aligns nothing to more than 4 bytes.
+const MAX_ALIGNMENT: usize = 4;
+
+// EMHEADER: must understand flag (bit 31), length code (bits 28..30) and member
+// id (bits 0..27)
+const EMHEADER_MEMBER_ID_MASK: u32 = 0x0fff_ffff;
+const EMHEADER_LC_SHIFT: u32 = 28;
+// Length code: the member is preceded by NEXTINT, which is its length
+const LC_NEXTINT: u32 = 4;
+
+/// Serialize `value` in XCDR2 with byte order `BO`.
+pub fn to_vec<T, BO>(value: &T, extensibility: Extensibility) -> Result<Vec<u8>>
+where
+  T: ?Sized + Serialize,
+  BO: ByteOrder,
+{
+  let mut serializer = Xcdr2Serializer::<BO>::new(extensibility);
+  value.serialize(&mut serializer)?;
+  Ok(serializer.into_inner())
+}
+
+/// Serialize `value` in XCDR2 with the byte order and extensibility of
+/// `encoding`, which must be an XCDR2 representation identifier.
+pub fn to_vec_with_rep_id<T>(value: &T, encoding: RepresentationIdentifier) -> Result<Vec<u8>>
+where
+  T: ?Sized + Serialize,
+{
+  match (
+    Extensibility::from_representation_identifier(encoding),
+    is_little_endian(encoding),
+  ) {
+    (Some(extensibility), true) => to_vec::<T, LittleEndian>(value, extensibility),
+    (Some(extensibility), false) => to_vec::<T, BigEndian>(value, extensibility),
+    (None, _) => Err(Error::Message(format!(
+      "Not an XCDR2 representation: {encoding:?}"
+    ))),
+  }
+}
+
+// All the standard little-endian representation identifiers are odd.
+pub(crate) fn is_little_endian(encoding: RepresentationIdentifier) -> bool {
+  encoding.bytes[1] & 0x01 != 0
+}
+
+/// Deserialize an object in XCDR2 with byte order `BO`.
+///
+/// Returns the deserialized object and the count of bytes consumed.
+pub fn from_bytes<'de, T, BO>(input: &'de [u8], extensibility: Extensibility) -> Result<(T, usize)>
+where
+  T: de::Deserialize<'de>,
+  BO: ByteOrder,
+{
+  from_bytes_with::<PhantomData<T>, BO>(input, extensibility, PhantomData)
+}
+
+/// Deserialize with `decoder` an object in XCDR2 with byte order `BO`.
+///
+/// Returns the deserialized object and the count of bytes consumed.
+pub fn from_bytes_with<'de, S, BO>(
+  input: &'de [u8],
+  extensibility: Extensibility,
+  decoder: S,
+) -> Result<(S::Value, usize)>
+where
+  S: DeserializeSeed<'de>,
+  BO: ByteOrder,
+{
+  let mut deserializer = Xcdr2Deserializer::<BO>::new(input, extensibility);
+  let value = decoder.deserialize(&mut deserializer)?;
+  Ok((value, deserializer.bytes_consumed()))
+}
+
+// ----------------------------------------------------------
+// Serialization
+
+// Is the value being serialized primitive? This decides if a DHEADER is needed
+// before a sequence or array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Probe {
+  Off,
+  Pending,
+  Primitive,
+  Constructed,
+}
+
+/// Serde serializer for XCDR2 data.
+pub struct Xcdr2Serializer<BO> {
+  output: Vec<u8>,
+  extensibility: Extensibility,
+  // The next value is the value of a MUTABLE member, which is left out if it is
+  // an absent optional member.
+  member_start: bool,
+  member_omitted: bool,
+  probe: Probe,
+  phantom: PhantomData<BO>,
+}
+
+impl<BO: ByteOrder> Xcdr2Serializer<BO> {
+  pub fn new(extensibility: Extensibility) -> Self {
+    Self {
+      output: Vec::new(),
+      extensibility,
+      member_start: false,
+      member_omitted: false,
+      probe: Probe::Off,
+      phantom: PhantomData,
+    }
+  }
+
+  /// The serialized bytes
+  pub fn into_inner(self) -> Vec<u8> {
+    self.output
+  }
+
+  fn begin_value(&mut self, primitive: bool) {
+    self.member_start = false;
+    if self.probe == Probe::Pending {
+      self.probe = if primitive {
+        Probe::Primitive
+      } else {
+        Probe::Constructed
+      };
+    }
+  }
+
+  fn align(&mut self, alignment: usize) {
+    let alignment = alignment.min(MAX_ALIGNMENT);
+    let padding = (alignment - self.output.len() % alignment) % alignment;
+    self.output.resize(self.output.len() + padding, 0);
+  }
+
+  fn write_u32(&mut self, v: u32) {
+    self.align(4);
+    let mut bytes = [0; 4];
+    BO::write_u32(&mut bytes, v);
+    self.output.extend_from_slice(&bytes);
+  }
+
+  fn write_length(&mut self, length: usize) -> Result<()> {
+    let length = u32::try_from(length)
+      .map_err(|_| Error::Message(format!("Length {length} does not fit in 32 bits")))?;
+    self.write_u32(length);
+    Ok(())
+  }
+
+  // Reserve a DHEADER, returning its position for end_dheader.
+  fn begin_dheader(&mut self) -> usize {
+    self.write_u32(0);
+    self.output.len() - 4
+  }
+
+  fn end_dheader(&mut self, position: usize) -> Result<()> {
+    let length = self.output.len() - position - 4;
+    let length = u32::try_from(length)
+      .map_err(|_| Error::Message(format!("Length {length} does not fit in 32 bits")))?;
+    BO::write_u32(&mut self.output[position..position + 4], length);
+    Ok(())
+  }
+
+  // Serialize the first element of a collection, finding out if it is primitive.
+  fn serialize_first_element<T>(&mut self, value: &T) -> Result<Probe>
+  where
+    T: ?Sized + Serialize,
+  {
+    let outer_probe = mem::replace(&mut self.probe, Probe::Pending);
+    let result = value.serialize(&mut *self);
+    let probe = mem::replace(&mut self.probe, outer_probe);
+    result.map(|()| probe)
+  }
+}
+
+macro_rules! serialize_number {
+  ($fn_name:ident, $t:ty, $write:ident) => {
+    fn $fn_name(self, v: $t) -> Result<()> {
+      self.begin_value(true);
+      self.align(mem::size_of::<$t>());
+      let mut bytes = [0; mem::size_of::<$t>()];
+      BO::$write(&mut bytes, v);
+      self.output.extend_from_slice(&bytes);
+      Ok(())
+    }
+  };
+}
+
+impl<'a, BO: ByteOrder> ser::Serializer for &'a mut Xcdr2Serializer<BO> {
+  type Ok = ();
+  type Error = Error;
+
+  type SerializeSeq = Compound<'a, BO>;
+  type SerializeTuple = Compound<'a, BO>;
+  type SerializeTupleStruct = Compound<'a, BO>;
+  type SerializeTupleVariant = Compound<'a, BO>;
+  type SerializeMap = Compound<'a, BO>;
+  type SerializeStruct = Compound<'a, BO>;
+  type SerializeStructVariant = Compound<'a, BO>;
+
+  fn serialize_bool(self, v: bool) -> Result<()> {
+    self.begin_value(true);
+    self.output.push(u8::from(v));
+    Ok(())
+  }
+
+  fn serialize_i8(self, v: i8) -> Result<()> {
+    self.begin_value(true);
+    self.output.extend_from_slice(&v.to_ne_bytes());
+    Ok(())
+  }
+
+  fn serialize_u8(self, v: u8) -> Result<()> {
+    self.begin_value(true);
+    self.output.push(v);
+    Ok(())
+  }
+
+  serialize_number!(serialize_i16, i16, write_i16);
+  serialize_number!(serialize_i32, i32, write_i32);
+  serialize_number!(serialize_i64, i64, write_i64);
+  serialize_number!(serialize_u16, u16, write_u16);
+  serialize_number!(serialize_u32, u32, write_u32);
+  serialize_number!(serialize_u64, u64, write_u64);
+  serialize_number!(serialize_f32, f32, write_f32);
+  serialize_number!(serialize_f64, f64, write_f64);
+
+  // A Rust char is a 32-bit Unicode code point, as in classic CDR.
+  fn serialize_char(self, v: char) -> Result<()> {
+    self.serialize_u32(u32::from(v))
+  }
+
+  fn serialize_str(self, v: &str) -> Result<()> {
+    self.begin_value(false);
+    self.write_length(v.len() + 1)?; // +1 for the NUL terminator
+    self.output.extend_from_slice(v.as_bytes());
+    self.output.push(0);
+    Ok(())
+  }
+
+  fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+    self.begin_value(false);
+    self.write_length(v.len())?;
+    self.output.extend_from_slice(v);
+    Ok(())
+  }
+
+  fn serialize_none(self) -> Result<()> {
+    if self.member_start {
+      // An absent optional member of a MUTABLE struct is left out.
+      self.member_start = false;
+      self.member_omitted = true;
+      return Ok(());
+    }
+    self.begin_value(false);
+    self.output.push(0); // not present
+    Ok(())
+  }
+
+  fn serialize_some<T>(self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    if self.member_start {
+      // The EMHEADER tells that a member of a MUTABLE struct is present.
+      return value.serialize(self);
+    }
+    self.begin_value(false);
+    self.output.push(1); // present
+    value.serialize(self)
+  }
+
+  fn serialize_unit(self) -> Result<()> {
+    self.begin_value(true);
+    Ok(())
+  }
+
+  fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+    self.serialize_unit()
+  }
+
+  fn serialize_unit_variant(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+  ) -> Result<()> {
+    self.serialize_u32(variant_index)
+  }
+
+  fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    value.serialize(self)
+  }
+
+  fn serialize_newtype_variant<T>(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+    value: &T,
+  ) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    self.serialize_u32(variant_index)?;
+    value.serialize(self)
+  }
+
+  fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+    let len = len.ok_or(Error::SequenceLengthUnknown)?;
+    self.begin_value(false);
+    self.align(4);
+    let start = self.output.len();
+    self.write_length(len)?;
+    Ok(Compound::new(self, CompoundKind::Sequence { start }))
+  }
+
+  fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+    // A tuple is an array: no length on the wire.
+    self.begin_value(false);
+    let start = self.output.len();
+    Ok(Compound::new(self, CompoundKind::Array { start }))
+  }
+
+  fn serialize_tuple_struct(
+    self,
+    _name: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeTupleStruct> {
+    self.begin_value(false);
+    Ok(Compound::new(self, CompoundKind::Plain))
+  }
+
+  fn serialize_tuple_variant(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeTupleVariant> {
+    self.serialize_u32(variant_index)?;
+    Ok(Compound::new(self, CompoundKind::Plain))
+  }
+
+  fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+    let len = len.ok_or(Error::SequenceLengthUnknown)?;
+    self.begin_value(false);
+    self.write_length(len)?;
+    Ok(Compound::new(self, CompoundKind::Plain))
+  }
+
+  fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+    self.begin_value(false);
+    let dheader = match self.extensibility {
+      Extensibility::Final => None,
+      Extensibility::Appendable | Extensibility::Mutable => Some(self.begin_dheader()),
+    };
+    Ok(Compound::new(
+      self,
+      CompoundKind::Struct {
+        dheader,
+        member_id: 0,
+      },
+    ))
+  }
+
+  fn serialize_struct_variant(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+    _len: usize,
+  ) -> Result<Self::SerializeStructVariant> {
+    self.serialize_u32(variant_index)?;
+    Ok(Compound::new(self, CompoundKind::Plain))
+  }
+
+  fn is_human_readable(&self) -> bool {
+    false
+  }
+}
+
+enum CompoundKind {
+  // Fields or elements one after another
+  Plain,
+  // `start` is the position of the sequence length.
+  Sequence {
+    start: usize,
+  },
+  // `start` is the position of the first element.
+  Array {
+    start: usize,
+  },
+  Struct {
+    dheader: Option<usize>,
+    member_id: u32,
+  },
+}
+
+/// Serializer of the members of a collection or struct
+pub struct Compound<'a, BO> {
+  ser: &'a mut Xcdr2Serializer<BO>,
+  kind: CompoundKind,
+  first_element: bool,
+  // DHEADER of a collection of non-primitive elements
+  dheader: Option<usize>,
+}
+
+impl<'a, BO: ByteOrder> Compound<'a, BO> {
+  fn new(ser: &'a mut Xcdr2Serializer<BO>, kind: CompoundKind) -> Self {
+    Self {
+      ser,
+      kind,
+      first_element: true,
+      dheader: None,
+    }
+  }
+
+  fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    let start = match self.kind {
+      CompoundKind::Sequence { start } | CompoundKind::Array { start } if self.first_element => {
+        start
+      }
+      _ => return value.serialize(&mut *self.ser),
+    };
+    self.first_element = false;
+    if self.ser.serialize_first_element(value)? == Probe::Primitive {
+      return Ok(());
+    }
+    // The elements are not primitive, so the collection needs a DHEADER in
+    // front. Serialize the first element again after it.
+    let length = match self.kind {
+      CompoundKind::Sequence { .. } => Some(self.ser.output[start..start + 4].to_vec()),
+      _ => None,
+    };
+    self.ser.output.truncate(start);
+    self.dheader = Some(self.ser.begin_dheader());
+    if let Some(length) = length {
+      self.ser.output.extend_from_slice(&length);
+    }
+    value.serialize(&mut *self.ser)
+  }
+
+  fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    let CompoundKind::Struct { member_id, .. } = &mut self.kind else {
+      return value.serialize(&mut *self.ser);
+    };
+    let id = *member_id;
+    *member_id += 1;
+    if self.ser.extensibility != Extensibility::Mutable {
+      return value.serialize(&mut *self.ser);
+    }
+
+    // EMHEADER and NEXTINT, filled in after the member is serialized
+    self.ser.align(4);
+    let start = self.ser.output.len();
+    self.ser.output.extend_from_slice(&[0; 8]);
+    self.ser.member_start = true;
+    self.ser.member_omitted = false;
+    value.serialize(&mut *self.ser)?;
+    self.ser.member_start = false;
+    if mem::take(&mut self.ser.member_omitted) {
+      self.ser.output.truncate(start);
+      return Ok(());
+    }
+    if id > EMHEADER_MEMBER_ID_MASK {
+      return Err(Error::Message(format!("Member id {id} is too large")));
+    }
+    let length = self.ser.output.len() - start - 8;
+    let length = u32::try_from(length)
+      .map_err(|_| Error::Message(format!("Length {length} does not fit in 32 bits")))?;
+    BO::write_u32(
+      &mut self.ser.output[start..start + 4],
+      (LC_NEXTINT << EMHEADER_LC_SHIFT) | id,
+    );
+    BO::write_u32(&mut self.ser.output[start + 4..start + 8], length);
+    Ok(())
+  }
+
+  fn end(self) -> Result<()> {
+    if let Some(dheader) = self.dheader {
+      self.ser.end_dheader(dheader)?;
+    }
+    if let CompoundKind::Struct {
+      dheader: Some(dheader),
+      ..
+    } = self.kind
+    {
+      self.ser.end_dheader(dheader)?;
+    }
+    Ok(())
+  }
+}
+
+impl<BO: ByteOrder> ser::SerializeSeq for Compound<'_, BO> {
+  type Ok = ();
+  type Error = Error;
+
+  fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    Compound::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<()> {
+    Compound::end(self)
+  }
+}
+
+impl<BO: ByteOrder> ser::SerializeTuple for Compound<'_, BO> {
+  type Ok = ();
+  type Error = Error;
+
+  fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    Compound::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<()> {
+    Compound::end(self)
+  }
+}
+
+impl<BO: ByteOrder> ser::SerializeTupleStruct for Compound<'_, BO> {
+  type Ok = ();
+  type Error = Error;
+
+  fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    value.serialize(&mut *self.ser)
+  }
+
+  fn end(self) -> Result<()> {
+    Compound::end(self)
+  }
+}
+
+impl<BO: ByteOrder> ser::SerializeTupleVariant for Compound<'_, BO> {
+  type Ok = ();
+  type Error = Error;
+
+  fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    value.serialize(&mut *self.ser)
+  }
+
+  fn end(self) -> Result<()> {
+    Compound::end(self)
+  }
+}
+
+impl<BO: ByteOrder> ser::SerializeMap for Compound<'_, BO> {
+  type Ok = ();
+  type Error = Error;
+
+  fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    key.serialize(&mut *self.ser)
+  }
+
+  fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    value.serialize(&mut *self.ser)
+  }
+
+  fn end(self) -> Result<()> {
+    Compound::end(self)
+  }
+}
+
+impl<BO: ByteOrder> ser::SerializeStruct for Compound<'_, BO> {
+  type Ok = ();
+  type Error = Error;
+
+  fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    Compound::serialize_field(self, value)
+  }
+
+  // Member ids are positions in the declaration, also of skipped fields.
+  fn skip_field(&mut self, _key: &'static str) -> Result<()> {
+    if let CompoundKind::Struct { member_id, .. } = &mut self.kind {
+      *member_id += 1;
+    }
+    Ok(())
+  }
+
+  fn end(self) -> Result<()> {
+    Compound::end(self)
+  }
+}
+
+impl<BO: ByteOrder> ser::SerializeStructVariant for Compound<'_, BO> {
+  type Ok = ();
+  type Error = Error;
+
+  fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<()>
+  where
+    T: ?Sized + Serialize,
+  {
+    value.serialize(&mut *self.ser)
+  }
+
+  fn end(self) -> Result<()> {
+    Compound::end(self)
+  }
+}
+
+// ----------------------------------------------------------
+// Deserialization
+
+// The header of a collection, which can be read only when we know if the
+// elements are primitive.
+#[derive(Clone, Copy, Debug)]
+enum PendingHeader {
+  // The first word of a sequence is its DHEADER or length. The length goes to
+  // `sequence_lengths[slot]`.
+  Sequence { slot: usize, first_word: u32 },
+  // An array has a DHEADER if its elements are not primitive.
+  Array,
+}
+
+/// Serde deserializer for XCDR2 data.
+pub struct Xcdr2Deserializer<'de, BO> {
+  input: &'de [u8],
+  position: usize,
+  extensibility: Extensibility,
+  // The next value is the value of a MUTABLE member.
+  member_start: bool,
+  pending_header: Option<PendingHeader>,
+  // Lengths of the sequences whose first element is being deserialized
+  sequence_lengths: Vec<Option<usize>>,
+  phantom: PhantomData<BO>,
+}
+
+impl<'de, BO: ByteOrder> Xcdr2Deserializer<'de, BO> {
+  pub fn new(input: &'de [u8], extensibility: Extensibility) -> Self {
+    Self {
+      input,
+      position: 0,
+      extensibility,
+      member_start: false,
+      pending_header: None,
+      sequence_lengths: Vec::new(),
+      phantom: PhantomData,
+    }
+  }
+
+  /// How many bytes of input have been consumed
+  pub fn bytes_consumed(&self) -> usize {
+    self.position
+  }
+
+  fn next_bytes(&mut self, count: usize) -> Result<&'de [u8]> {
+    let end = self.position.checked_add(count).ok_or(Error::Eof)?;
+    let bytes = self.input.get(self.position..end).ok_or(Error::Eof)?;
+    self.position = end;
+    Ok(bytes)
+  }
+
+  fn align(&mut self, alignment: usize) -> Result<()> {
+    let alignment = alignment.min(MAX_ALIGNMENT);
+    let padding = (alignment - self.position % alignment) % alignment;
+    self.next_bytes(padding).map(|_| ())
+  }
+
+  fn read_u32(&mut self) -> Result<u32> {
+    self.align(4)?;
+    Ok(BO::read_u32(self.next_bytes(4)?))
+  }
+
+  fn peek_u32(&self) -> Result<u32> {
+    let bytes = self
+      .input
+      .get(self.position..self.position + 4)
+      .ok_or(Error::Eof)?;
+    Ok(BO::read_u32(bytes))
+  }
+
+  // Read a DHEADER, returning the end position of the data it covers.
+  fn read_dheader(&mut self) -> Result<usize> {
+    let length = self.read_u32()? as usize;
+    let end = self.position.checked_add(length).ok_or(Error::Eof)?;
+    if end > self.input.len() {
+      return Err(Error::Eof);
+    }
+    Ok(end)
+  }
+
+  // Skip to `end`, which must not be behind us.
+  fn skip_to(&mut self, end: usize) -> Result<()> {
+    if self.position > end {
+      return Err(Error::Message(format!(
+        "Data overruns its length: position {} > end {end}",
+        self.position
+      )));
+    }
+    self.position = end;
+    Ok(())
+  }
+
+  fn begin_value(&mut self, primitive: bool) -> Result<()> {
+    self.member_start = false;
+    match self.pending_header.take() {
+      None => Ok(()),
+      Some(PendingHeader::Array) => {
+        if !primitive {
+          self.read_dheader()?;
+        }
+        Ok(())
+      }
+      Some(PendingHeader::Sequence { slot, first_word }) => {
+        // The first word was the length, unless it was the DHEADER.
+        let length = if primitive {
+          first_word
+        } else {
+          self.read_u32()?
+        };
+        self.sequence_lengths[slot] = Some(length as usize);
+        if length == 0 {
+          // We are asked for the first element, but there is none.
+          // SequenceAccess recognizes this error from the length.
+          Err(Error::Message("Empty sequence".to_string()))
+        } else {
+          Ok(())
+        }
+      }
+    }
+  }
+}
+
+macro_rules! deserialize_number {
+  ($fn_name:ident, $t:ty, $visit:ident, $read:ident) => {
+    fn $fn_name<V>(self, visitor: V) -> Result<V::Value>
+    where
+      V: Visitor<'de>,
+    {
+      self.begin_value(true)?;
+      self.align(mem::size_of::<$t>())?;
+      let value = BO::$read(self.next_bytes(mem::size_of::<$t>())?);
+      visitor.$visit(value)
+    }
+  };
+}
+
+impl<'de, BO: ByteOrder> de::Deserializer<'de> for &mut Xcdr2Deserializer<'de, BO> {
+  type Error = Error;
+
+  fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    Err(Error::NotSelfDescribingFormat(
+      "XCDR2 cannot deserialize \"any\" type.".to_string(),
+    ))
+  }
+
+  fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(true)?;
+    match self.next_bytes(1)?[0] {
+      0 => visitor.visit_bool(false),
+      1 => visitor.visit_bool(true),
+      x => Err(Error::BadBoolean(x)),
+    }
+  }
+
+  fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(true)?;
+    visitor.visit_i8(i8::from_ne_bytes([self.next_bytes(1)?[0]]))
+  }
+
+  fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(true)?;
+    visitor.visit_u8(self.next_bytes(1)?[0])
+  }
+
+  deserialize_number!(deserialize_i16, i16, visit_i16, read_i16);
+  deserialize_number!(deserialize_i32, i32, visit_i32, read_i32);
+  deserialize_number!(deserialize_i64, i64, visit_i64, read_i64);
+  deserialize_number!(deserialize_u16, u16, visit_u16, read_u16);
+  deserialize_number!(deserialize_u32, u32, visit_u32, read_u32);
+  deserialize_number!(deserialize_u64, u64, visit_u64, read_u64);
+  deserialize_number!(deserialize_f32, f32, visit_f32, read_f32);
+  deserialize_number!(deserialize_f64, f64, visit_f64, read_f64);
+
+  fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(true)?;
+    let code_point = self.read_u32()?;
+    char::from_u32(code_point)
+      .ok_or(Error::BadChar(code_point))
+      .and_then(|c| visitor.visit_char(c))
+  }
+
+  fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(false)?;
+    let length = self.read_u32()? as usize;
+    let bytes = self.next_bytes(length)?;
+    // The length includes the NUL terminator.
+    let contents = match bytes.split_last() {
+      Some((&0, contents)) => contents,
+      _ => bytes,
+    };
+    std::str::from_utf8(contents)
+      .map_err(Error::BadUTF8)
+      .and_then(|s| visitor.visit_borrowed_str(s))
+  }
+
+  fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_str(visitor)
+  }
+
+  fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(false)?;
+    let length = self.read_u32()? as usize;
+    visitor.visit_borrowed_bytes(self.next_bytes(length)?)
+  }
+
+  fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_bytes(visitor)
+  }
+
+  fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    if self.member_start {
+      // A member of a MUTABLE struct is present, if it is on the wire.
+      self.member_start = false;
+      return visitor.visit_some(self);
+    }
+    self.begin_value(false)?;
+    match self.next_bytes(1)?[0] {
+      0 => visitor.visit_none(),
+      1 => visitor.visit_some(self),
+      x => Err(Error::BadOption(u32::from(x))),
+    }
+  }
+
+  fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(true)?;
+    visitor.visit_unit()
+  }
+
+  fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_unit(visitor)
+  }
+
+  fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(false)?;
+    let first_word = self.read_u32()?;
+    if first_word == 0 {
+      // A DHEADER is never zero, as it covers the length.
+      return visitor.visit_seq(CountedAccess::new(self, 0, None));
+    }
+    let slot = self.sequence_lengths.len();
+    self.sequence_lengths.push(None);
+    self.pending_header = Some(PendingHeader::Sequence { slot, first_word });
+    let result = visitor.visit_seq(SequenceAccess {
+      de: &mut *self,
+      remaining: None,
+      slot,
+    });
+    self.sequence_lengths.truncate(slot);
+    self.pending_header = None;
+    result
+  }
+
+  fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(false)?;
+    if len > 0 {
+      self.pending_header = Some(PendingHeader::Array);
+    }
+    let result = visitor.visit_seq(CountedAccess::new(&mut *self, len, None));
+    self.pending_header = None;
+    result
+  }
+
+  fn deserialize_tuple_struct<V>(
+    self,
+    _name: &'static str,
+    len: usize,
+    visitor: V,
+  ) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(false)?;
+    visitor.visit_seq(CountedAccess::new(self, len, None))
+  }
+
+  fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(false)?;
+    let count = self.read_u32()? as usize;
+    visitor.visit_map(CountedAccess::new(self, count, None))
+  }
+
+  fn deserialize_struct<V>(
+    self,
+    _name: &'static str,
+    fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(false)?;
+    match self.extensibility {
+      Extensibility::Final => visitor.visit_seq(CountedAccess::new(self, fields.len(), None)),
+      Extensibility::Appendable => {
+        // Members appended to the type after ours are skipped.
+        let end = self.read_dheader()?;
+        let value = visitor.visit_seq(CountedAccess::new(&mut *self, fields.len(), Some(end)))?;
+        self.skip_to(end)?;
+        Ok(value)
+      }
+      Extensibility::Mutable => {
+        let end = self.read_dheader()?;
+        let value = visitor.visit_map(MemberAccess {
+          de: &mut *self,
+          end,
+          member_end: end,
+        })?;
+        self.skip_to(end)?;
+        Ok(value)
+      }
+    }
+  }
+
+  fn deserialize_enum<V>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.begin_value(true)?;
+    visitor.visit_enum(EnumerationAccess { de: self })
+  }
+
+  fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_u32(visitor)
+  }
+
+  fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    if self.member_start {
+      // An unknown member of a MUTABLE struct. MemberAccess skips it.
+      self.member_start = false;
+      visitor.visit_unit()
+    } else {
+      self.deserialize_any(visitor)
+    }
+  }
+
+  fn is_human_readable(&self) -> bool {
+    false
+  }
+}
+
+// Elements, fields or map entries, `remaining` of them, or until `end`
+struct CountedAccess<'a, 'de, BO> {
+  de: &'a mut Xcdr2Deserializer<'de, BO>,
+  remaining: usize,
+  end: Option<usize>,
+}
+
+impl<'a, 'de, BO> CountedAccess<'a, 'de, BO> {
+  fn new(de: &'a mut Xcdr2Deserializer<'de, BO>, remaining: usize, end: Option<usize>) -> Self {
+    Self { de, remaining, end }
+  }
+
+  fn next(&mut self) -> bool {
+    if self.remaining == 0 || self.end.is_some_and(|end| self.de.position >= end) {
+      false
+    } else {
+      self.remaining -= 1;
+      true
+    }
+  }
+}
+
+impl<'de, BO: ByteOrder> SeqAccess<'de> for CountedAccess<'_, 'de, BO> {
+  type Error = Error;
+
+  fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    if self.next() {
+      seed.deserialize(&mut *self.de).map(Some)
+    } else {
+      Ok(None)
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.remaining)
+  }
+}
+
+impl<'de, BO: ByteOrder> MapAccess<'de> for CountedAccess<'_, 'de, BO> {
+  type Error = Error;
+
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+  where
+    K: DeserializeSeed<'de>,
+  {
+    if self.next() {
+      seed.deserialize(&mut *self.de).map(Some)
+    } else {
+      Ok(None)
+    }
+  }
+
+  fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    seed.deserialize(&mut *self.de)
+  }
+}
+
+// Elements of a sequence, whose length is known after the first element has
+// told if the sequence has a DHEADER.
+struct SequenceAccess<'a, 'de, BO> {
+  de: &'a mut Xcdr2Deserializer<'de, BO>,
+  remaining: Option<usize>,
+  slot: usize,
+}
+
+impl<'de, BO: ByteOrder> SeqAccess<'de> for SequenceAccess<'_, 'de, BO> {
+  type Error = Error;
+
+  fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    match self.remaining {
+      Some(0) => Ok(None),
+      Some(n) => {
+        self.remaining = Some(n - 1);
+        seed.deserialize(&mut *self.de).map(Some)
+      }
+      None => {
+        let result = seed.deserialize(&mut *self.de);
+        self.de.pending_header = None;
+        let length = self.de.sequence_lengths.get(self.slot).copied().flatten();
+        self.de.sequence_lengths.truncate(self.slot);
+        match length {
+          Some(0) => {
+            self.remaining = Some(0);
+            Ok(None)
+          }
+          Some(n) => {
+            self.remaining = Some(n - 1);
+            result.map(Some)
+          }
+          None => result.and(Err(Error::Message(
+            "Sequence element did not read anything".to_string(),
+          ))),
+        }
+      }
+    }
+  }
+}
+
+// Members of a MUTABLE struct, each preceded by an EMHEADER
+struct MemberAccess<'a, 'de, BO> {
+  de: &'a mut Xcdr2Deserializer<'de, BO>,
+  end: usize,
+  member_end: usize,
+}
+
+impl<'de, BO: ByteOrder> MapAccess<'de> for MemberAccess<'_, 'de, BO> {
+  type Error = Error;
+
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+  where
+    K: DeserializeSeed<'de>,
+  {
+    // There may be padding after the last member.
+    if self.de.position.next_multiple_of(4) >= self.end {
+      self.de.position = self.end;
+      return Ok(None);
+    }
+    let emheader = self.de.read_u32()?;
+    let member_id = emheader & EMHEADER_MEMBER_ID_MASK;
+    // DDS-XTypes v1.3 Table 34: length of the member by the length code
+    let length = match (emheader >> EMHEADER_LC_SHIFT) & 0x7 {
+      0 => 1,
+      1 => 2,
+      2 => 4,
+      3 => 8,
+      4 => self.de.read_u32()? as usize,
+      // NEXTINT is a part of the member.
+      lc => {
+        let nextint = self.de.peek_u32()? as usize;
+        let element_size = match lc {
+          5 => 1,
+          6 => 4,
+          _ => 8,
+        };
+        nextint
+          .checked_mul(element_size)
+          .and_then(|l| l.checked_add(4))
+          .ok_or(Error::Eof)?
+      }
+    };
+    self.member_end = self
+      .de
+      .position
+      .checked_add(length)
+      .filter(|member_end| *member_end <= self.end)
+      .ok_or_else(|| Error::Message(format!("Member {member_id} overruns its struct")))?;
+    seed
+      .deserialize(IntoDeserializer::<Error>::into_deserializer(u64::from(
+        member_id,
+      )))
+      .map(Some)
+  }
+
+  fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    self.de.member_start = true;
+    let value = seed.deserialize(&mut *self.de)?;
+    self.de.member_start = false;
+    self.de.skip_to(self.member_end)?;
+    Ok(value)
+  }
+}
+
+struct EnumerationAccess<'a, 'de, BO> {
+  de: &'a mut Xcdr2Deserializer<'de, BO>,
+}
+
+impl<'de, BO: ByteOrder> EnumAccess<'de> for EnumerationAccess<'_, 'de, BO> {
+  type Error = Error;
+  type Variant = Self;
+
+  fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    let variant_index = self.de.read_u32()?;
+    let variant = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(variant_index))?;
+    Ok((variant, self))
+  }
+}
+
+impl<'de, BO: ByteOrder> VariantAccess<'de> for EnumerationAccess<'_, 'de, BO> {
+  type Error = Error;
+
+  fn unit_variant(self) -> Result<()> {
+    Ok(())
+  }
+
+  fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    seed.deserialize(self.de)
+  }
+
+  fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_seq(CountedAccess::new(self.de, len, None))
+  }
+
+  fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_seq(CountedAccess::new(self.de, fields.len(), None))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::{Deserialize, Serialize};
+
+  use super::*;
+
+  #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+  struct Inner {
+    a: u8,
+    b: f64,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+  struct Outer {
+    x: i16,
+    inner: Inner,
+    names: Vec<String>,
+    values: Vec<u32>,
+    opt: Option<i32>,
+  }
+
+  fn outer() -> Outer {
+    Outer {
+      x: 1,
+      inner: Inner { a: 2, b: 1.0 },
+      names: vec!["ab".to_string()],
+      values: vec![7],
+      opt: Some(5),
+    }
+  }
+
+  const ONE_F64_LE: [u8; 8] = [0, 0, 0, 0, 0, 0, 0xf0, 0x3f];
+
+  fn le(words: &[&[u8]]) -> Vec<u8> {
+    words.concat()
+  }
+
+  #[test]
+  fn final_struct_layout() {
+    let expected = le(&[
+      &[0x01, 0x00],    // x
+      &[0x02],          // inner.a
+      &[0x00],          // padding: f64 is aligned to 4
+      &ONE_F64_LE,      // inner.b
+      &[0x0b, 0, 0, 0], // names: DHEADER
+      &[0x01, 0, 0, 0], // names: length
+      &[0x03, 0, 0, 0, b'a', b'b', 0x00],
+      &[0x00],          // padding
+      &[0x01, 0, 0, 0], // values: length, no DHEADER for primitives
+      &[0x07, 0, 0, 0],
+      &[0x01, 0, 0, 0], // opt: present, padding
+      &[0x05, 0, 0, 0],
+    ]);
+    let bytes = to_vec::<_, LittleEndian>(&outer(), Extensibility::Final).unwrap();
+    assert_eq!(bytes, expected);
+    let (decoded, consumed) =
+      from_bytes::<Outer, LittleEndian>(&bytes, Extensibility::Final).unwrap();
+    assert_eq!(decoded, outer());
+    assert_eq!(consumed, 44);
+  }
+
+  #[test]
+  fn appendable_struct_layout() {
+    let expected = le(&[
+      &[0x34, 0, 0, 0], // DHEADER
+      &[0x01, 0x00],
+      &[0x00, 0x00],
+      &[0x0c, 0, 0, 0], // inner: DHEADER
+      &[0x02, 0, 0, 0],
+      &ONE_F64_LE,
+      &[0x0b, 0, 0, 0],
+      &[0x01, 0, 0, 0],
+      &[0x03, 0, 0, 0, b'a', b'b', 0x00, 0x00],
+      &[0x01, 0, 0, 0],
+      &[0x07, 0, 0, 0],
+      &[0x01, 0, 0, 0],
+      &[0x05, 0, 0, 0],
+    ]);
+    let bytes = to_vec::<_, LittleEndian>(&outer(), Extensibility::Appendable).unwrap();
+    assert_eq!(bytes, expected);
+    let (decoded, _) =
+      from_bytes::<Outer, LittleEndian>(&bytes, Extensibility::Appendable).unwrap();
+    assert_eq!(decoded, outer());
+  }
+
+  #[test]
+  fn appendable_struct_skips_appended_members() {
+    #[derive(Serialize)]
+    struct InnerV2 {
+      a: u8,
+      b: f64,
+      c: String,
+    }
+    #[derive(Serialize)]
+    struct Pair {
+      first: InnerV2,
+      second: InnerV2,
+    }
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct OldPair {
+      first: Inner,
+      second: Inner,
+    }
+
+    let v2 = |a| InnerV2 {
+      a,
+      b: 0.5,
+      c: "appended".to_string(),
+    };
+    let bytes = to_vec::<_, BigEndian>(
+      &Pair {
+        first: v2(1),
+        second: v2(2),
+      },
+      Extensibility::Appendable,
+    )
+    .unwrap();
+    let (decoded, consumed) =
+      from_bytes::<OldPair, BigEndian>(&bytes, Extensibility::Appendable).unwrap();
+    let v1 = |a| Inner { a, b: 0.5 };
+    assert_eq!(
+      decoded,
+      OldPair {
+        first: v1(1),
+        second: v1(2),
+      }
+    );
+    assert_eq!(consumed, bytes.len());
+  }
+
+  #[test]
+  fn mutable_struct_layout() {
+    let expected = le(&[
+      &[0x68, 0, 0, 0],          // DHEADER
+      &[0x00, 0, 0, 0x40],       // EMHEADER: LC 4, id 0
+      &[0x02, 0, 0, 0],          // NEXTINT
+      &[0x01, 0x00, 0x00, 0x00], // x, padding
+      &[0x01, 0, 0, 0x40],       // inner
+      &[0x20, 0, 0, 0],
+      &[0x1c, 0, 0, 0],    // inner: DHEADER
+      &[0x00, 0, 0, 0x40], // inner.a
+      &[0x01, 0, 0, 0],
+      &[0x02, 0, 0, 0],
+      &[0x01, 0, 0, 0x40], // inner.b
+      &[0x08, 0, 0, 0],
+      &ONE_F64_LE,
+      &[0x02, 0, 0, 0x40], // names
+      &[0x0f, 0, 0, 0],
+      &[0x0b, 0, 0, 0],
+      &[0x01, 0, 0, 0],
+      &[0x03, 0, 0, 0, b'a', b'b', 0x00, 0x00],
+      &[0x03, 0, 0, 0x40], // values
+      &[0x08, 0, 0, 0],
+      &[0x01, 0, 0, 0],
+      &[0x07, 0, 0, 0],
+      &[0x04, 0, 0, 0x40], // opt: no presence flag
+      &[0x04, 0, 0, 0],
+      &[0x05, 0, 0, 0],
+    ]);
+    let bytes = to_vec::<_, LittleEndian>(&outer(), Extensibility::Mutable).unwrap();
+    assert_eq!(bytes, expected);
+    let (decoded, _) = from_bytes::<Outer, LittleEndian>(&bytes, Extensibility::Mutable).unwrap();
+    assert_eq!(decoded, outer());
+
+    // An absent optional member is left out.
+    let without_opt = Outer {
+      opt: None,
+      ..outer()
+    };
+    let bytes = to_vec::<_, LittleEndian>(&without_opt, Extensibility::Mutable).unwrap();
+    assert_eq!(bytes[..4], [92, 0, 0, 0]);
+    assert_eq!(bytes[4..], expected[4..96]);
+    let (decoded, _) = from_bytes::<Outer, LittleEndian>(&bytes, Extensibility::Mutable).unwrap();
+    assert_eq!(decoded, without_opt);
+  }
+
+  #[test]
+  fn mutable_struct_with_compact_length_codes() {
+    // Members out of order, with the length codes of other implementations, a
+    // must-understand flag and an unknown member.
+    let bytes = le(&[
+      &[0x19, 0, 0, 0],
+      &[0x01, 0, 0, 0xb0], // M flag, LC 3, id 1
+      &2.5f64.to_le_bytes(),
+      &[0x07, 0, 0, 0x20], // LC 2, id 7
+      &[0xde, 0xad, 0xbe, 0xef],
+      &[0x00, 0, 0, 0x00], // LC 0, id 0
+      &[0x09],
+    ]);
+    let (decoded, consumed) =
+      from_bytes::<Inner, LittleEndian>(&bytes, Extensibility::Mutable).unwrap();
+    assert_eq!(decoded, Inner { a: 9, b: 2.5 });
+    assert_eq!(consumed, bytes.len());
+
+    // LC 5: NEXTINT is the length of the string.
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Named {
+      name: String,
+    }
+    let bytes = le(&[
+      &[0x0b, 0, 0, 0],
+      &[0x00, 0, 0, 0x50],
+      &[0x03, 0, 0, 0, b'a', b'b', 0x00],
+    ]);
+    let (decoded, _) = from_bytes::<Named, LittleEndian>(&bytes, Extensibility::Mutable).unwrap();
+    assert_eq!(decoded.name, "ab");
+
+    // A member must not overrun its struct.
+    let bytes = le(&[&[0x08, 0, 0, 0], &[0x00, 0, 0, 0x30], &[0; 8]]);
+    assert!(from_bytes::<Inner, LittleEndian>(&bytes, Extensibility::Mutable).is_err());
+  }
+
+  #[test]
+  fn empty_sequence_of_structs() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Tail {
+      items: Vec<Inner>,
+      after: u32,
+    }
+    let tail = Tail {
+      items: vec![],
+      after: 0x1122_3344,
+    };
+    // Written without DHEADER, as the element type is not known.
+    let bytes = to_vec::<_, LittleEndian>(&tail, Extensibility::Final).unwrap();
+    assert_eq!(bytes, [0, 0, 0, 0, 0x44, 0x33, 0x22, 0x11]);
+    let (decoded, _) = from_bytes::<Tail, LittleEndian>(&bytes, Extensibility::Final).unwrap();
+    assert_eq!(decoded, tail);
+
+    // With DHEADER, as other implementations write it.
+    let bytes = [4, 0, 0, 0, 0, 0, 0, 0, 0x44, 0x33, 0x22, 0x11];
+    let (decoded, consumed) =
+      from_bytes::<Tail, LittleEndian>(&bytes, Extensibility::Final).unwrap();
+    assert_eq!(decoded, tail);
+    assert_eq!(consumed, bytes.len());
+  }
+
+  #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+  enum Shape {
+    Empty,
+    Circle(f32),
+    Rectangle { width: u16, height: u16 },
+  }
+
+  #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+  struct Everything {
+    flag: bool,
+    letter: char,
+    big: i64,
+    text: String,
+    shapes: Vec<Shape>,
+    inners: Vec<Inner>,
+    nested: Vec<Vec<Inner>>,
+    matrix: [[u16; 2]; 2],
+    maybe_inner: Option<Inner>,
+    nothing: Option<String>,
+    pair: (u8, Inner),
+  }
+
+  fn everything() -> Everything {
+    Everything {
+      flag: true,
+      letter: 'ä',
+      big: -1 << 40,
+      text: "Hello".to_string(),
+      shapes: vec![
+        Shape::Empty,
+        Shape::Circle(1.5),
+        Shape::Rectangle {
+          width: 3,
+          height: 4,
+        },
+      ],
+      inners: vec![Inner { a: 1, b: 0.25 }, Inner { a: 2, b: -8.0 }],
+      nested: vec![vec![], vec![Inner { a: 3, b: 3.0 }]],
+      matrix: [[1, 2], [3, 4]],
+      maybe_inner: Some(Inner { a: 4, b: 1e10 }),
+      nothing: None,
+      pair: (5, Inner { a: 6, b: 6.5 }),
+    }
+  }
+
+  #[test]
+  fn round_trips() {
+    for extensibility in [
+      Extensibility::Final,
+      Extensibility::Appendable,
+      Extensibility::Mutable,
+    ] {
+      let le_bytes = to_vec::<_, LittleEndian>(&everything(), extensibility).unwrap();
+      let (decoded, consumed) =
+        from_bytes::<Everything, LittleEndian>(&le_bytes, extensibility).unwrap();
+      assert_eq!(decoded, everything(), "{extensibility:?}");
+      assert_eq!(consumed, le_bytes.len(), "{extensibility:?}");
+
+      let be_bytes = to_vec::<_, BigEndian>(&everything(), extensibility).unwrap();
+      assert_ne!(be_bytes, le_bytes);
+      let (decoded, _) = from_bytes::<Everything, BigEndian>(&be_bytes, extensibility).unwrap();
+      assert_eq!(decoded, everything(), "{extensibility:?}");
+    }
+  }
+
+  #[test]
+  fn representation_identifiers() {
+    for extensibility in [
+      Extensibility::Final,
+      Extensibility::Appendable,
+      Extensibility::Mutable,
+    ] {
+      for little_endian in [true, false] {
+        let rep_id = extensibility.representation_identifier(little_endian);
+        assert_eq!(
+          Extensibility::from_representation_identifier(rep_id),
+          Some(extensibility)
+        );
+        assert_eq!(is_little_endian(rep_id), little_endian);
+      }
+    }
+    assert_eq!(
+      Extensibility::from_representation_identifier(RepresentationIdentifier::CDR_LE),
+      None
+    );
+    assert!(to_vec_with_rep_id(&outer(), RepresentationIdentifier::PL_CDR_LE).is_err());
+    assert_eq!(
+      to_vec_with_rep_id(&outer(), RepresentationIdentifier::D_CDR2_LE).unwrap(),
+      to_vec::<_, LittleEndian>(&outer(), Extensibility::Appendable).unwrap()
+    );
+  }
+}
@@ -4,6 +4,7 @@ pub mod duration;
 pub mod endpoint;
 pub mod entity;
 pub mod guid;
+pub(crate) mod history_storage;
 pub mod locator;
 pub mod parameter_id;
 pub mod sequence_number;
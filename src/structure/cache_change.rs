@@ -11,6 +11,9 @@ pub enum ChangeKind {
   Alive,
   NotAliveDisposed,
   NotAliveUnregistered,
+  // Disposed and unregistered by the same change, e.g. when the DataWriter
+  // disposes instances it unregisters.
+  NotAliveDisposedUnregistered,
 }
 
 #[derive(Debug, Clone)]
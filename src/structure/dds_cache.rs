@@ -1,8 +1,8 @@
 use std::{
-  cmp::max,
-  collections::{btree_map, BTreeMap, HashMap},
+  cmp::{max, min},
+  collections::{btree_map, BTreeMap, BTreeSet, HashMap},
   iter,
-  ops::Bound::{Excluded, Included},
+  ops::Bound::Excluded,
   sync::{Arc, Mutex},
 };
 
@@ -12,17 +12,22 @@ use log::{debug, error, info, trace, warn};
 use crate::{
   create_error_internal,
   dds::{
+    key::KeyHash,
     qos::{
       policy::{History, ResourceLimits},
       QosPolicies,
     },
+    statusevents::{CacheOccupancy, SampleRejectedStatusKind},
     typedesc::TypeDesc,
     CreateError, CreateResult,
   },
   structure::{sequence_number::SequenceNumber, time::Timestamp},
   GUID,
 };
-use super::cache_change::CacheChange;
+use super::{
+  cache_change::CacheChange,
+  history_storage::{history_storage_for, HistoryStorage},
+};
 
 /// DDSCache contains all cacheChanges that are
 /// received by this participant. It is for serving local Readers. Local
@@ -101,8 +106,8 @@ impl DDSCache {
   pub fn garbage_collect(&mut self) {
     for tc in self.topic_caches.values_mut() {
       let mut tc = tc.lock().unwrap();
-      if let Some((last_timestamp, _)) = tc.changes.iter().next_back() {
-        if *last_timestamp > tc.changes_reallocated_up_to {
+      if let Some(last_timestamp) = tc.changes.newest_key() {
+        if last_timestamp > tc.changes_reallocated_up_to {
           tc.remove_changes_before(Timestamp::ZERO);
         }
       }
@@ -122,12 +127,18 @@ pub(crate) struct TopicCache {
   max_keep_samples: i32, // from QoS, for quick, repeated access
   // TODO: Change this to Option<u32>, where None means "no limit".
 
-  // The main content of the cache is in this map.
+  // Per-instance ResourceLimits. These are observed only if some QoS of the
+  // Topic specifies them, so None means "no limit".
+  max_instances: Option<usize>,
+  max_samples_per_instance: Option<usize>,
+
+  // The main content of the cache is in this storage, ordered by Timestamp.
   // Timestamp is assumed to be unique id over all the CacheChanges. Uniqueness is
-  // enforced on insert (see `add_change_internal`) by never issuing a key that is
+  // enforced on insert (see `add_change`) by never issuing a key that is
   // not strictly greater than the previous one, so a coarse system clock that
   // returns the same instant for two back-to-back receives cannot collide.
-  changes: BTreeMap<Timestamp, CacheChange>,
+  // The storage backend is chosen by History QoS (see `select_storage`).
+  changes: Box<dyn HistoryStorage>,
 
   // The underlying Bytes buffers are reallocated after some time (once for each) in
   // order to release the original receive buffer. The idea behind this is that if a CacheChange
@@ -136,8 +147,8 @@ pub(crate) struct TopicCache {
   changes_reallocated_up_to: Timestamp,
 
   // The largest key ever inserted into `changes`. Used to keep the keys strictly
-  // monotonic: `receive_timestamp` is `Timestamp::now()` sampled per incoming
-  // message, but the system clock granularity can be coarser than the time it
+  // monotonic: `receive_timestamp` is the participant clock sampled per
+  // incoming message, but the system clock granularity can be coarser than the time it
   // takes to process several aggregated DATA submessages, so two distinct samples
   // may carry the same instant. We then bump the key by one tick (~0.233 ns) so it
   // stays unique. This cannot outrun real time: regaining a whole second of skew
@@ -147,12 +158,21 @@ pub(crate) struct TopicCache {
   // sequence_numbers is an index to "changes" by GUID and SN
   sequence_numbers: BTreeMap<GUID, BTreeMap<SequenceNumber, Timestamp>>,
 
+  // instances is an index to "changes" by instance. Only changes that carry a
+  // key hash are known to belong to an instance. Others, e.g. all samples of a
+  // NO_KEY topic, are limited only by the sample counts.
+  instances: BTreeMap<KeyHash, BTreeSet<Timestamp>>,
+
   // Keep track of how far we have "reliably" received samples from each Writer
   // This means that all data up to this point has either been received, or
   // we have been notified (GAP or HEARTBEAT) that is not available and never will.
   // Therefore, data before the marker SN can be handed off to a Reliable DataReader.
   // Initially, we consider the marker for each Writer (GUID) to be SequenceNumber::new(1)
   received_reliably_before: BTreeMap<GUID, SequenceNumber>,
+
+  // How many times each Writer has restarted its SequenceNumbers. DataReaders
+  // reset their read pointers when this changes.
+  writer_restarts: BTreeMap<GUID, u32>,
 }
 
 impl TopicCache {
@@ -164,11 +184,15 @@ impl TopicCache {
       min_keep_samples: History::KeepLast { depth: 1 }, /* dummy value, next call will overwrite
                                                          * this */
       max_keep_samples: 1, // dummy value, next call will overwrite this
-      changes: BTreeMap::new(),
+      max_instances: None,
+      max_samples_per_instance: None,
+      changes: history_storage_for(History::KeepLast { depth: 1 }, 1), // likewise
       changes_reallocated_up_to: Timestamp::ZERO,
       last_added_instant: Timestamp::ZERO,
       sequence_numbers: BTreeMap::new(),
+      instances: BTreeMap::new(),
       received_reliably_before: BTreeMap::new(),
+      writer_restarts: BTreeMap::new(),
     };
 
     new_self.update_keep_limits(topic_qos);
@@ -196,8 +220,6 @@ impl TopicCache {
         max_samples_per_instance: 64,
       })
       .max_samples;
-    // TODO: We cannot currently keep track of instance counts, because TopicCache
-    // or DDSCache below do not know about instances.
 
     // If a definite minimum is specified, increase resource limit to at least that.
     let max_keep_samples = match min_keep_samples {
@@ -208,6 +230,52 @@ impl TopicCache {
     // actual update. This is will only ever increase cache size.
     self.min_keep_samples = max(min_keep_samples, self.min_keep_samples);
     self.max_keep_samples = max(max_keep_samples, self.max_keep_samples);
+
+    // Instance limits are not made up like the above, but observed only if
+    // specified. Then the loosest one applies, as with the sample counts.
+    if let Some(limits) = qos.resource_limits() {
+      self.max_instances = loosest_limit(self.max_instances, limits.max_instances);
+      self.max_samples_per_instance = loosest_limit(
+        self.max_samples_per_instance,
+        limits.max_samples_per_instance,
+      );
+    }
+
+    self.select_storage();
+  }
+
+  // Switch to the storage backend that suits the current History and limits:
+  // a ring buffer of `max_keep_samples` slots for KeepLast, and an unbounded
+  // ordered map for KeepAll. Already stored changes are moved over.
+  fn select_storage(&mut self) {
+    let max_samples = self.max_keep_samples as usize;
+    if self.changes.suits(self.min_keep_samples, max_samples) {
+      return;
+    }
+    debug!(
+      "TopicCache {}: using storage for {:?}, max {} samples",
+      self.topic_name, self.min_keep_samples, max_samples
+    );
+    let mut storage = history_storage_for(self.min_keep_samples, max_samples);
+    let mut overwritten = Vec::new();
+    for (key, cc) in self.changes.drain() {
+      overwritten.extend(storage.push_newest(key, cc));
+    }
+    self.changes = storage;
+    overwritten.iter().for_each(|cc| self.unindex(cc));
+  }
+
+  // A Writer has restarted with the same GUID, and its SequenceNumbers start
+  // again from 1. Forget the SequenceNumbers of its earlier samples, so that
+  // new samples are not taken for duplicates of them.
+  pub fn writer_restarted(&mut self, writer: GUID) {
+    self.sequence_numbers.remove(&writer);
+    self.received_reliably_before.remove(&writer);
+    *self.writer_restarts.entry(writer).or_default() += 1;
+  }
+
+  pub fn writer_restarts(&self) -> &BTreeMap<GUID, u32> {
+    &self.writer_restarts
   }
 
   // Returns true if the "reliably_received_before"-marker was actually moved
@@ -217,26 +285,31 @@ impl TopicCache {
     prev_sn.unwrap_or(SequenceNumber::new(1)) < sn
   }
 
-  pub fn get_change(&self, instant: &Timestamp) -> Option<&CacheChange> {
-    self.changes.get(instant)
+  // The reception timestamp of the newest cached change.
+  pub fn last_added_instant(&self) -> Timestamp {
+    self.last_added_instant
   }
 
-  pub fn add_change(&mut self, instant: &Timestamp, cache_change: CacheChange) {
+  // The newest cached SequenceNumber of each Writer.
+  pub fn last_sequence_numbers(&self) -> BTreeMap<GUID, SequenceNumber> {
     self
-      .add_change_internal(instant, cache_change)
-      .map(|cc_back| {
-        warn!(
-          "DDSCache insert failed topic={:?} cache_change={:?}",
-          self.topic_name, cc_back
-        );
-      });
+      .sequence_numbers
+      .iter()
+      .filter_map(|(writer, sns)| sns.keys().next_back().map(|sn| (*writer, *sn)))
+      .collect()
   }
 
-  fn add_change_internal(
+  pub fn get_change(&self, instant: &Timestamp) -> Option<&CacheChange> {
+    self.changes.get(instant)
+  }
+
+  /// Store a received change. If other samples had to be dropped to stay
+  /// within the ResourceLimits, returns the limit that caused it.
+  pub fn add_change(
     &mut self,
     instant: &Timestamp,
     cache_change: CacheChange,
-  ) -> Option<CacheChange> {
+  ) -> Option<SampleRejectedStatusKind> {
     // First, do garbage collection.
     // But not at every insert, just to save time and effort.
     // Some heuristic to decide if we should collect now.
@@ -278,24 +351,112 @@ impl TopicCache {
       None
     } else {
       // This is a new (to us) SequenceNumber, this is the default processing path.
-      // Ensure a strictly monotonic (hence unique) key: if the clock did not
-      // advance since the previous insert (coarse clock vs. back-to-back receives),
-      // bump by one tick so distinct samples never share a key. Keys stay ordered,
-      // so time-range reads and GC are unaffected.
-      let key = if *instant > self.last_added_instant {
-        *instant
-      } else {
-        Timestamp::from_ticks(self.last_added_instant.to_ticks().wrapping_add(1))
-      };
-      self.last_added_instant = key;
-
-      self.insert_sn(key, &cache_change);
-      self.changes.insert(key, cache_change).inspect(|old_cc| {
-        // Should be unreachable now that keys are strictly monotonic, but keep the
-        // guard rather than silently overwriting if the invariant is ever broken.
-        error!("DDSHistoryCache already contained element with key {key:?} !!!");
-        self.remove_sn(old_cc);
-      })
+      self.insert_change(instant, cache_change)
+    }
+  }
+
+  /// Store a change whose SequenceNumber is already cached, as a new sample.
+  /// The SequenceNumber then refers to the new change. See
+  /// DataReader::set_duplicate_acceptance.
+  pub fn add_repeated_change(
+    &mut self,
+    instant: &Timestamp,
+    cache_change: CacheChange,
+  ) -> Option<SampleRejectedStatusKind> {
+    self.insert_change(instant, cache_change)
+  }
+
+  fn insert_change(
+    &mut self,
+    instant: &Timestamp,
+    cache_change: CacheChange,
+  ) -> Option<SampleRejectedStatusKind> {
+    // Ensure a strictly monotonic (hence unique) key: if the clock did not
+    // advance since the previous insert (coarse clock vs. back-to-back receives),
+    // bump by one tick so distinct samples never share a key. Keys stay ordered,
+    // so time-range reads and GC are unaffected.
+    let key = if *instant > self.last_added_instant {
+      *instant
+    } else {
+      Timestamp::from_ticks(self.last_added_instant.to_ticks().wrapping_add(1))
+    };
+    self.last_added_instant = key;
+
+    let instance = cache_change.write_options.key_hash();
+    self.insert_sn(key, &cache_change);
+    // A full KeepLast ring buffer overwrites its oldest change.
+    if let Some(overwritten) = self.changes.push_newest(key, cache_change) {
+      self.unindex(&overwritten);
+    }
+    instance.and_then(|key_hash| self.insert_to_instance(key_hash, key))
+  }
+
+  // Index the change at `instant` to its instance. Then drop samples to keep
+  // within the instance limits: the least recently updated instance, if there
+  // are too many, and the oldest samples of this instance, if it has too many.
+  fn insert_to_instance(
+    &mut self,
+    key_hash: KeyHash,
+    instant: Timestamp,
+  ) -> Option<SampleRejectedStatusKind> {
+    let mut limit_hit = None;
+
+    if !self.instances.contains_key(&key_hash)
+      && self
+        .max_instances
+        .is_some_and(|max_instances| self.instances.len() >= max_instances)
+    {
+      let stalest = self
+        .instances
+        .iter()
+        .min_by_key(|(_, instants)| instants.last())
+        .map(|(key_hash, _)| *key_hash);
+      if let Some(instants) = stalest.and_then(|kh| self.instances.remove(&kh)) {
+        self.remove_changes(instants);
+      }
+      limit_hit = Some(SampleRejectedStatusKind::ByInstancesLimit);
+    }
+
+    let (max_samples, limit) = self.samples_per_instance();
+    let instants = self.instances.entry(key_hash).or_default();
+    instants.insert(instant);
+    let mut dropped = Vec::new();
+    while instants.len() > max_samples {
+      dropped.extend(instants.pop_first());
+    }
+    if !dropped.is_empty() {
+      self.remove_changes(dropped);
+      limit_hit = limit_hit.or(limit);
+    }
+    limit_hit
+  }
+
+  // How many samples are kept of each instance, and the limit to report when
+  // samples are dropped because of it. KeepLast History replaces old samples by
+  // design, so that is not reported.
+  fn samples_per_instance(&self) -> (usize, Option<SampleRejectedStatusKind>) {
+    let max_samples_per_instance = self.max_samples_per_instance.unwrap_or(usize::MAX);
+    match self.min_keep_samples {
+      History::KeepLast { depth } => {
+        let depth = usize::try_from(depth).unwrap_or(0).max(1);
+        if depth <= max_samples_per_instance {
+          return (depth, None);
+        }
+      }
+      History::KeepAll => (),
+    }
+    (
+      max_samples_per_instance,
+      Some(SampleRejectedStatusKind::BySamplesPerInstanceLimit),
+    )
+  }
+
+  // Remove the changes at `instants`, which are no longer in `instances`.
+  fn remove_changes(&mut self, instants: impl IntoIterator<Item = Timestamp>) {
+    for instant in instants {
+      if let Some(cc) = self.changes.remove(&instant) {
+        self.unindex(&cc);
+      }
     }
   }
 
@@ -333,10 +494,9 @@ impl TopicCache {
       end_instant
     };
 
-    // Get result as tree range
     self
       .changes
-      .range((Excluded(start_instant), Included(end_instant)))
+      .range(start_instant, end_instant)
       .map(|(i, c)| (*i, c))
   }
 
@@ -374,7 +534,9 @@ impl TopicCache {
       ChangesInRange::Reliable(self.get_changes_in_range_reliable(last_read_sn))
     } else {
       ChangesInRange::BestEffort(
-        self.get_changes_in_range_best_effort(latest_instant, Timestamp::now()),
+        // Keys follow the participant clock, which need not be the system
+        // clock, so bound by the newest key instead of the current time.
+        self.get_changes_in_range_best_effort(latest_instant, self.last_added_instant),
       )
     }
   }
@@ -388,11 +550,38 @@ impl TopicCache {
     // received reliably, since no such samples exist.
   }
 
-  fn remove_sn(&mut self, cc: &CacheChange) {
+  // Called after `cc` has been removed from `changes`.
+  fn unindex(&mut self, cc: &CacheChange) {
+    let changes = &self.changes;
+
+    // Changes of an instance are removed oldest first.
+    if let Some(btree_map::Entry::Occupied(mut instance)) = cc
+      .write_options
+      .key_hash()
+      .map(|kh| self.instances.entry(kh))
+    {
+      let instants = instance.get_mut();
+      while instants
+        .first()
+        .is_some_and(|instant| changes.get(instant).is_none())
+      {
+        instants.pop_first();
+      }
+      if instants.is_empty() {
+        instance.remove();
+      }
+    }
+
     let mut emptied = false;
 
     self.sequence_numbers.entry(cc.writer_guid).and_modify(|s| {
-      s.remove(&cc.sequence_number);
+      // If the Writer has restarted, the SN may already index a newer change.
+      if s
+        .get(&cc.sequence_number)
+        .is_some_and(|instant| changes.get(instant).is_none())
+      {
+        s.remove(&cc.sequence_number);
+      }
       emptied = s.is_empty();
     });
     if emptied {
@@ -408,10 +597,8 @@ impl TopicCache {
   /// If we are over `self.max_keep_samples`, then remove the oldest samples
   /// until `max_keep_samples` is reached, regardless of `remove_before`.
   pub fn remove_changes_before(&mut self, remove_before: Timestamp) {
-    // TODO: Currently, TopicCache does not know about Keys is Samples/CacheChanges,
-    // because they are opaque binary blobs at this point. Therefore, we cannot
-    // distinguish between instances, so cannot observe max instance counts.
-    // We have to do just with min/max sample counts.
+    // Instance limits are observed already on insert (see `insert_to_instance`),
+    // so here we have to do just with min/max sample counts.
 
     let sample_count = self.changes.len();
 
@@ -431,51 +618,72 @@ impl TopicCache {
       ),
     };
 
-    let oldest_timestamp_to_retain_opt: Option<Timestamp> = self
+    // Remove the oldest samples. Force "must" count, and then remove until
+    // either limit timestamp or "may" count stops us.
+    let remove_count = max(
+      must_remove_count,
+      min(may_remove_count, self.changes.count_before(remove_before)),
+    );
+    let mut to_remove = Vec::with_capacity(min(remove_count, sample_count));
+    self
       .changes
-      .keys()
-      .enumerate()
-      .skip_while(|(i, ts)|
-        // Skip samples to be removed. Force "must" count, and then remove until
-        // either limit timestamp or "may" count stops us
-        *i < must_remove_count
-        || (**ts < remove_before && *i < may_remove_count))
-      .map(|(_, ts)| ts) // un-enumerate
-      .next() // the next element would be the first to retain
-      .copied();
-
-    // In case `.next()` is `None`, then we just decided to to discard everything,
-    // or, as a special case, the cache was empty to begin with, but the end
-    // result is the same.
-
-    let to_retain = if let Some(split_key) = oldest_timestamp_to_retain_opt {
-      // split_off: Returns everything after the given key, including the key.
-      self.changes.split_off(&split_key)
-    } else {
-      BTreeMap::new()
-    };
+      .remove_oldest(remove_count, &mut |cc| to_remove.push(cc));
 
-    let to_remove = std::mem::replace(&mut self.changes, to_retain);
-
-    // update also SequenceNumber map
-    to_remove.values().for_each(|r| self.remove_sn(r));
+    // update also SequenceNumber and instance maps
+    to_remove.iter().for_each(|r| self.unindex(r));
 
     // Now, reallocate old cache changes
     let reallocate_timeout = crate::Duration::from_secs(5);
-    let now = Timestamp::now();
+    // Measured against the newest key rather than the system clock, since keys
+    // follow the participant clock. Saturate, so that an empty cache does not
+    // wrap around.
+    let now = self.last_added_instant;
+    let reallocate_before = Timestamp::from_ticks(
+      now
+        .to_ticks()
+        .saturating_sub(reallocate_timeout.to_ticks() as u64),
+    );
 
     // Take max to avoid crash if clock jumps backward.
-    let reallocate_limit = max(now - reallocate_timeout, self.changes_reallocated_up_to);
+    let reallocate_limit = max(reallocate_before, self.changes_reallocated_up_to);
+
+    self.changes.for_each_in_range_mut(
+      self.changes_reallocated_up_to,
+      reallocate_limit,
+      &mut |cc| cc.reallocate(),
+    );
 
+    self.changes_reallocated_up_to = reallocate_limit;
+  }
+
+  // Manual purge by a DataReader: remove changes received before
+  // `remove_before`, oldest first, regardless of History. If
+  // `keep_unacknowledged`, stop at the first change that is not yet before the
+  // reliably-received marker of its Writer, as a Reliable DataReader has not
+  // been able to read it yet. Returns the number of removed changes.
+  pub fn purge_before(&mut self, remove_before: Timestamp, keep_unacknowledged: bool) -> usize {
+    let remove_count = self
+      .changes
+      .range(Timestamp::ZERO, remove_before)
+      .take_while(|(instant, cc)| {
+        **instant < remove_before
+          && (!keep_unacknowledged || cc.sequence_number < self.reliable_before(cc.writer_guid))
+      })
+      .count();
+    let mut to_remove = Vec::with_capacity(remove_count);
     self
       .changes
-      .range_mut((
-        Excluded(self.changes_reallocated_up_to),
-        Included(reallocate_limit),
-      ))
-      .for_each(|(_, cc)| cc.reallocate());
+      .remove_oldest(remove_count, &mut |cc| to_remove.push(cc));
+    to_remove.iter().for_each(|r| self.unindex(r));
+    remove_count
+  }
 
-    self.changes_reallocated_up_to = reallocate_limit;
+  pub fn occupancy(&self) -> CacheOccupancy {
+    CacheOccupancy {
+      sample_count: self.changes.len(),
+      oldest: self.changes.oldest_key(),
+      newest: self.changes.newest_key(),
+    }
   }
 
   pub fn topic_name(&self) -> String {
@@ -483,7 +691,18 @@ impl TopicCache {
   }
 }
 
-type ChangesInRangeBestEffort<'a, F> = iter::Map<btree_map::Range<'a, Timestamp, CacheChange>, F>;
+// Combine a ResourceLimits value with the limit so far. Zero or negative
+// values mean LENGTH_UNLIMITED.
+fn loosest_limit(limit_so_far: Option<usize>, limit: i32) -> Option<usize> {
+  let limit = usize::try_from(limit)
+    .ok()
+    .filter(|limit| *limit > 0)
+    .unwrap_or(usize::MAX);
+  Some(limit_so_far.map_or(limit, |so_far| max(so_far, limit)))
+}
+
+type ChangesInRangeBestEffort<'a, F> =
+  iter::Map<Box<dyn Iterator<Item = BestEffortMap<'a>> + 'a>, F>;
 type ChangesInRangeReliable<'a, F1, F2> = iter::FilterMap<
   iter::FlatMap<
     btree_map::Iter<'a, GUID, BTreeMap<SequenceNumber, Timestamp>>,
@@ -611,4 +830,196 @@ mod tests {
       3
     );
   }
+
+  #[test]
+  fn keep_last_storage_overwrites_oldest_and_switches_to_keep_all() {
+    use crate::dds::qos::policy::{History, ResourceLimits};
+
+    let keep_last = QosPolicies::builder()
+      .history(History::KeepLast { depth: 2 })
+      .resource_limits(ResourceLimits {
+        max_samples: 3,
+        max_instances: 1,
+        max_samples_per_instance: 3,
+      })
+      .build();
+    let mut dds_cache = DDSCache::new();
+    let topic_cache_handle = dds_cache.add_new_topic(
+      "KeepLastTopic".to_string(),
+      TypeDesc::new("KeepLastType".to_string()),
+      &keep_last,
+    );
+    let change = |sn| {
+      CacheChange::new(
+        GUID::GUID_UNKNOWN,
+        SequenceNumber::new(sn),
+        WriteOptions::default(),
+        DDSData::new(SerializedPayload::default()),
+      )
+    };
+    let stored_sns = || -> Vec<i64> {
+      topic_cache_handle
+        .lock()
+        .unwrap()
+        .get_changes_in_range_best_effort(crate::Timestamp::ZERO, crate::Timestamp::now())
+        .map(|(_, cc)| i64::from(cc.sequence_number))
+        .collect()
+    };
+
+    for sn in 1..=5 {
+      topic_cache_handle
+        .lock()
+        .unwrap()
+        .add_change(&crate::Timestamp::now(), change(sn));
+    }
+    // Only max_samples are retained, and overwritten ones are forgotten
+    // completely, so they are not treated as duplicates when received again.
+    assert_eq!(stored_sns(), vec![3, 4, 5]);
+    topic_cache_handle
+      .lock()
+      .unwrap()
+      .add_change(&crate::Timestamp::now(), change(1));
+    assert_eq!(stored_sns(), vec![4, 5, 1]);
+
+    // A KeepAll reader on the same topic switches storage, keeping the changes.
+    let keep_all = QosPolicies::builder().history(History::KeepAll).build();
+    dds_cache.add_new_topic(
+      "KeepLastTopic".to_string(),
+      TypeDesc::new("KeepLastType".to_string()),
+      &keep_all,
+    );
+    topic_cache_handle
+      .lock()
+      .unwrap()
+      .add_change(&crate::Timestamp::now(), change(6));
+    assert_eq!(stored_sns(), vec![4, 5, 1, 6]);
+  }
+
+  #[test]
+  fn instance_limits_drop_oldest_samples_of_the_instance() {
+    use crate::dds::{
+      key::KeyHash,
+      qos::policy::{History, ResourceLimits},
+      statusevents::SampleRejectedStatusKind,
+      with_key::datawriter::WriteOptionsBuilder,
+    };
+
+    let change = |sn, instance| {
+      CacheChange::new(
+        GUID::GUID_UNKNOWN,
+        SequenceNumber::new(sn),
+        WriteOptionsBuilder::new()
+          .key_hash(KeyHash::from_pl_cdr_bytes(vec![instance; 16]).unwrap())
+          .build(),
+        DDSData::new(SerializedPayload::default()),
+      )
+    };
+    let topic_cache = |qos: &QosPolicies| {
+      DDSCache::new().add_new_topic(
+        "InstanceTopic".to_string(),
+        TypeDesc::new("InstanceType".to_string()),
+        qos,
+      )
+    };
+    let stored_sns = |tc: &super::TopicCache| -> Vec<i64> {
+      tc.get_changes_in_range_best_effort(crate::Timestamp::ZERO, crate::Timestamp::now())
+        .map(|(_, cc)| i64::from(cc.sequence_number))
+        .collect()
+    };
+
+    // KeepLast keeps `depth` samples of each instance, and that is no loss.
+    let handle = topic_cache(
+      &QosPolicies::builder()
+        .history(History::KeepLast { depth: 2 })
+        .build(),
+    );
+    let mut tc = handle.lock().unwrap();
+    for (sn, instance) in [(1, 1), (2, 2), (3, 1), (4, 1), (5, 2)] {
+      assert_eq!(
+        tc.add_change(&crate::Timestamp::now(), change(sn, instance)),
+        None
+      );
+    }
+    assert_eq!(stored_sns(&tc), vec![2, 3, 4, 5]);
+    drop(tc);
+
+    // KeepAll drops samples only because of ResourceLimits, and tells so.
+    let handle = topic_cache(
+      &QosPolicies::builder()
+        .history(History::KeepAll)
+        .resource_limits(ResourceLimits {
+          max_samples: 64,
+          max_instances: 2,
+          max_samples_per_instance: 2,
+        })
+        .build(),
+    );
+    let mut tc = handle.lock().unwrap();
+    for (sn, instance) in [(1, 1), (2, 2), (3, 1)] {
+      assert_eq!(
+        tc.add_change(&crate::Timestamp::now(), change(sn, instance)),
+        None
+      );
+    }
+    assert_eq!(
+      tc.add_change(&crate::Timestamp::now(), change(4, 1)),
+      Some(SampleRejectedStatusKind::BySamplesPerInstanceLimit)
+    );
+    assert_eq!(stored_sns(&tc), vec![2, 3, 4]);
+    // Instance 2 was updated least recently, so it gives way to instance 3.
+    assert_eq!(
+      tc.add_change(&crate::Timestamp::now(), change(5, 3)),
+      Some(SampleRejectedStatusKind::ByInstancesLimit)
+    );
+    assert_eq!(stored_sns(&tc), vec![3, 4, 5]);
+    assert_eq!(tc.add_change(&crate::Timestamp::now(), change(6, 3)), None);
+    assert_eq!(stored_sns(&tc), vec![3, 4, 5, 6]);
+  }
+
+  #[test]
+  fn purge_keeps_samples_not_reliably_received() {
+    use crate::dds::qos::policy::History;
+
+    let keep_all = QosPolicies::builder().history(History::KeepAll).build();
+    let mut dds_cache = DDSCache::new();
+    let topic_cache_handle = dds_cache.add_new_topic(
+      "PurgeTopic".to_string(),
+      TypeDesc::new("PurgeType".to_string()),
+      &keep_all,
+    );
+    let mut tc = topic_cache_handle.lock().unwrap();
+    let writer =
+      GUID::dummy_test_guid(crate::structure::guid::EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    let at = |secs| crate::Timestamp::ZERO + crate::Duration::from_secs(secs);
+    for sn in 1..=5 {
+      let change = CacheChange::new(
+        writer,
+        SequenceNumber::new(sn.into()),
+        WriteOptions::default(),
+        DDSData::new(SerializedPayload::default()),
+      );
+      tc.add_change(&at(10 * sn), change);
+    }
+    assert_eq!(tc.occupancy().sample_count, 5);
+    assert_eq!(tc.occupancy().oldest, Some(at(10)));
+    assert_eq!(tc.occupancy().newest, Some(at(50)));
+
+    // A Reliable DataReader can read #1 and #2, but not yet #3.
+    tc.mark_reliably_received_before(writer, SequenceNumber::new(3));
+    assert_eq!(tc.purge_before(at(45), true), 2);
+    assert_eq!(tc.occupancy().sample_count, 3);
+    assert_eq!(tc.occupancy().oldest, Some(at(30)));
+
+    // Without the Reliable restriction, the purge goes up to the timestamp.
+    assert_eq!(tc.purge_before(at(45), false), 2);
+    let occupancy = tc.occupancy();
+    assert_eq!(occupancy.sample_count, 1);
+    assert_eq!(occupancy.oldest, occupancy.newest);
+    assert_eq!(
+      tc.get_changes_in_range_best_effort(crate::Timestamp::ZERO, at(100))
+        .map(|(_, cc)| i64::from(cc.sequence_number))
+        .collect::<Vec<_>>(),
+      vec![5]
+    );
+  }
 }
@@ -48,6 +48,32 @@ impl GuidPrefix {
     Self { bytes }
   }
 
+  /// Like [`Self::random_for_this_participant`], but places `tenant_id`
+  /// (big-endian) in bytes 2 and 3, right after our vendor id.
+  ///
+  /// RustDDS does not encode host or process ids into the prefix. All bytes
+  /// after the vendor id are random, so reserving two of them for the tenant
+  /// leaves 64 random bits to keep prefixes unique within a tenant.
+  pub fn random_for_tenant(tenant_id: u16) -> Self {
+    let mut prefix = Self::random_for_this_participant();
+    prefix.bytes[2..4].copy_from_slice(&tenant_id.to_be_bytes());
+    prefix
+  }
+
+  /// The tenant id embedded by [`Self::random_for_tenant`], if this prefix
+  /// was generated by RustDDS. Other vendors do not use this layout.
+  ///
+  /// Note that a RustDDS participant created without a tenant id has random
+  /// bytes in the tenant position.
+  pub fn tenant_id(&self) -> Option<u16> {
+    let vendor_id = crate::messages::vendor_id::VendorId::THIS_IMPLEMENTATION.as_bytes();
+    if self.bytes[0..2] == vendor_id {
+      Some(u16::from_be_bytes([self.bytes[2], self.bytes[3]]))
+    } else {
+      None
+    }
+  }
+
   pub fn range(&self) -> impl RangeBounds<GUID> {
     GUID::new(*self, EntityId::MIN)..=GUID::new(*self, EntityId::MAX)
   }
@@ -161,6 +187,11 @@ impl EntityKind {
     e == 0x02 || e == 0x03 || e == 0x08
   }
 
+  pub fn is_with_key(&self) -> bool {
+    let e = self.0 & 0x0F;
+    e == 0x02 || e == 0x07
+  }
+
   pub fn is_built_in(&self) -> bool {
     (self.0 & 0xF0) == 0xC0
   }
@@ -582,6 +613,15 @@ impl GUID {
     }
   }
 
+  /// Generates new GUID for Participant with a random `guid_prefix` that
+  /// carries `tenant_id`. See [`GuidPrefix::random_for_tenant`].
+  pub fn new_participant_guid_for_tenant(tenant_id: u16) -> Self {
+    Self {
+      prefix: GuidPrefix::random_for_tenant(tenant_id),
+      entity_id: EntityId::PARTICIPANT,
+    }
+  }
+
   pub fn dummy_test_guid(entity_kind: EntityKind) -> Self {
     Self {
       prefix: GuidPrefix::new(b"FakeTestGUID"),
@@ -666,6 +706,18 @@ mod tests {
   //   assert_eq!(test_guid, and_back);
   // }
 
+  #[test]
+  fn tenant_id_in_prefix() {
+    let a = GuidPrefix::random_for_tenant(0xBEEF);
+    let b = GuidPrefix::random_for_tenant(0xBEEF);
+    assert_eq!(a.tenant_id(), Some(0xBEEF));
+    assert_eq!(b.tenant_id(), Some(0xBEEF));
+    assert_ne!(a, b);
+
+    // Not a RustDDS prefix: no tenant layout.
+    assert_eq!(GuidPrefix::new(b"FakeTestGUID").tenant_id(), None);
+  }
+
   #[test]
   fn keyhash_test() {
     let test_bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
@@ -0,0 +1,351 @@
+use std::{
+  collections::{BTreeMap, VecDeque},
+  fmt, iter,
+  ops::Bound::{Excluded, Included},
+};
+
+use crate::{
+  dds::qos::policy::History,
+  structure::{cache_change::CacheChange, time::Timestamp},
+};
+
+// Initial slot allocation limit of a ring buffer. Capacities derived from
+// ResourceLimits may be very large, so slots beyond this are allocated only
+// when actually needed.
+const MAX_PREALLOCATED_SLOTS: usize = 1024;
+
+/// Storage backend of the CacheChanges in a TopicCache, ordered by their
+/// (receive) Timestamp.
+///
+/// TopicCache only ever inserts keys that are strictly greater than any key
+/// already stored, and removes changes oldest first, except when it drops a
+/// single change with `remove`. Implementations may rely on this.
+pub(crate) trait HistoryStorage: fmt::Debug + Send {
+  fn len(&self) -> usize;
+
+  fn get(&self, key: &Timestamp) -> Option<&CacheChange>;
+
+  fn oldest_key(&self) -> Option<Timestamp>;
+
+  fn newest_key(&self) -> Option<Timestamp>;
+
+  /// Number of stored changes with a key less than `key`.
+  fn count_before(&self, key: Timestamp) -> usize;
+
+  /// Store `cache_change` as the newest change. If the storage is full, the
+  /// oldest change is removed and returned to make room.
+  fn push_newest(&mut self, key: Timestamp, cache_change: CacheChange) -> Option<CacheChange>;
+
+  /// Remove the `count` oldest changes, passing each to `removed`.
+  fn remove_oldest(&mut self, count: usize, removed: &mut dyn FnMut(CacheChange));
+
+  /// Remove the change with the given key, wherever it is in the history.
+  fn remove(&mut self, key: &Timestamp) -> Option<CacheChange>;
+
+  /// Changes with key in `(after, up_to]`, oldest first.
+  fn range(
+    &self,
+    after: Timestamp,
+    up_to: Timestamp,
+  ) -> Box<dyn Iterator<Item = (&Timestamp, &CacheChange)> + '_>;
+
+  /// Apply `f` to the changes with key in `(after, up_to]`.
+  fn for_each_in_range_mut(
+    &mut self,
+    after: Timestamp,
+    up_to: Timestamp,
+    f: &mut dyn FnMut(&mut CacheChange),
+  );
+
+  /// Remove all changes, oldest first.
+  fn drain(&mut self) -> Vec<(Timestamp, CacheChange)>;
+
+  /// Does this backend suit the given history settings as is?
+  fn suits(&self, history: History, max_samples: usize) -> bool;
+}
+
+/// Backend for the History QoS. KeepLast topics get a ring buffer holding at
+/// most `max_samples` changes, KeepAll topics an ordered map without a fixed
+/// size.
+pub(crate) fn history_storage_for(history: History, max_samples: usize) -> Box<dyn HistoryStorage> {
+  match history {
+    History::KeepLast { .. } => Box::new(RingHistory::with_capacity(max_samples)),
+    History::KeepAll => Box::<OrderedHistory>::default(),
+  }
+}
+
+/// History storage in a BTreeMap. The size is unlimited, so this suits
+/// KeepAll caches, which are trimmed only by garbage collection.
+#[derive(Debug, Default)]
+pub(crate) struct OrderedHistory {
+  changes: BTreeMap<Timestamp, CacheChange>,
+}
+
+impl HistoryStorage for OrderedHistory {
+  fn len(&self) -> usize {
+    self.changes.len()
+  }
+
+  fn get(&self, key: &Timestamp) -> Option<&CacheChange> {
+    self.changes.get(key)
+  }
+
+  fn oldest_key(&self) -> Option<Timestamp> {
+    self.changes.keys().next().copied()
+  }
+
+  fn newest_key(&self) -> Option<Timestamp> {
+    self.changes.keys().next_back().copied()
+  }
+
+  fn count_before(&self, key: Timestamp) -> usize {
+    self.changes.range(..key).count()
+  }
+
+  fn push_newest(&mut self, key: Timestamp, cache_change: CacheChange) -> Option<CacheChange> {
+    debug_assert!(self.newest_key() < Some(key));
+    self.changes.insert(key, cache_change)
+  }
+
+  fn remove_oldest(&mut self, count: usize, removed: &mut dyn FnMut(CacheChange)) {
+    let retained = match self.changes.keys().nth(count) {
+      Some(split_key) => self.changes.split_off(&split_key.clone()),
+      None => BTreeMap::new(),
+    };
+    std::mem::replace(&mut self.changes, retained)
+      .into_values()
+      .for_each(removed);
+  }
+
+  fn remove(&mut self, key: &Timestamp) -> Option<CacheChange> {
+    self.changes.remove(key)
+  }
+
+  fn range(
+    &self,
+    after: Timestamp,
+    up_to: Timestamp,
+  ) -> Box<dyn Iterator<Item = (&Timestamp, &CacheChange)> + '_> {
+    if after > up_to {
+      // BTreeMap::range would panic
+      return Box::new(iter::empty());
+    }
+    Box::new(self.changes.range((Excluded(after), Included(up_to))))
+  }
+
+  fn for_each_in_range_mut(
+    &mut self,
+    after: Timestamp,
+    up_to: Timestamp,
+    f: &mut dyn FnMut(&mut CacheChange),
+  ) {
+    if after > up_to {
+      return;
+    }
+    self
+      .changes
+      .range_mut((Excluded(after), Included(up_to)))
+      .for_each(|(_, cc)| f(cc));
+  }
+
+  fn drain(&mut self) -> Vec<(Timestamp, CacheChange)> {
+    std::mem::take(&mut self.changes).into_iter().collect()
+  }
+
+  fn suits(&self, history: History, _max_samples: usize) -> bool {
+    history == History::KeepAll
+  }
+}
+
+/// Fixed-capacity ring buffer of history. Inserting into a full buffer
+/// overwrites the oldest change, and freed slots are reused, so a KeepLast
+/// cache in steady state does not allocate. Keys are found by binary search,
+/// since they are stored in increasing order.
+#[derive(Debug)]
+pub(crate) struct RingHistory {
+  slots: VecDeque<(Timestamp, CacheChange)>,
+  capacity: usize,
+}
+
+impl RingHistory {
+  pub fn with_capacity(capacity: usize) -> Self {
+    let capacity = capacity.max(1);
+    Self {
+      slots: VecDeque::with_capacity(capacity.min(MAX_PREALLOCATED_SLOTS)),
+      capacity,
+    }
+  }
+
+  // Index of the first slot with key greater than `key`.
+  fn index_after(&self, key: Timestamp) -> usize {
+    self.slots.partition_point(|(k, _)| *k <= key)
+  }
+}
+
+impl HistoryStorage for RingHistory {
+  fn len(&self) -> usize {
+    self.slots.len()
+  }
+
+  fn get(&self, key: &Timestamp) -> Option<&CacheChange> {
+    self
+      .slots
+      .binary_search_by_key(key, |(k, _)| *k)
+      .ok()
+      .map(|i| &self.slots[i].1)
+  }
+
+  fn oldest_key(&self) -> Option<Timestamp> {
+    self.slots.front().map(|(k, _)| *k)
+  }
+
+  fn newest_key(&self) -> Option<Timestamp> {
+    self.slots.back().map(|(k, _)| *k)
+  }
+
+  fn count_before(&self, key: Timestamp) -> usize {
+    self.slots.partition_point(|(k, _)| *k < key)
+  }
+
+  fn push_newest(&mut self, key: Timestamp, cache_change: CacheChange) -> Option<CacheChange> {
+    debug_assert!(self.newest_key() < Some(key));
+    let overwritten = if self.slots.len() >= self.capacity {
+      self.slots.pop_front().map(|(_, cc)| cc)
+    } else {
+      None
+    };
+    self.slots.push_back((key, cache_change));
+    overwritten
+  }
+
+  fn remove_oldest(&mut self, count: usize, removed: &mut dyn FnMut(CacheChange)) {
+    let count = count.min(self.slots.len());
+    self.slots.drain(..count).for_each(|(_, cc)| removed(cc));
+  }
+
+  fn remove(&mut self, key: &Timestamp) -> Option<CacheChange> {
+    let index = self.slots.binary_search_by_key(key, |(k, _)| *k).ok()?;
+    self.slots.remove(index).map(|(_, cc)| cc)
+  }
+
+  fn range(
+    &self,
+    after: Timestamp,
+    up_to: Timestamp,
+  ) -> Box<dyn Iterator<Item = (&Timestamp, &CacheChange)> + '_> {
+    let start = self.index_after(after);
+    let end = self.index_after(up_to).max(start);
+    Box::new(self.slots.range(start..end).map(|(k, cc)| (k, cc)))
+  }
+
+  fn for_each_in_range_mut(
+    &mut self,
+    after: Timestamp,
+    up_to: Timestamp,
+    f: &mut dyn FnMut(&mut CacheChange),
+  ) {
+    let start = self.index_after(after);
+    let end = self.index_after(up_to).max(start);
+    self.slots.range_mut(start..end).for_each(|(_, cc)| f(cc));
+  }
+
+  fn drain(&mut self) -> Vec<(Timestamp, CacheChange)> {
+    self.slots.drain(..).collect()
+  }
+
+  fn suits(&self, history: History, max_samples: usize) -> bool {
+    matches!(history, History::KeepLast { .. }) && self.capacity == max_samples.max(1)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    dds::{ddsdata::DDSData, with_key::datawriter::WriteOptions},
+    messages::submessages::elements::serialized_payload::SerializedPayload,
+    structure::{guid::GUID, sequence_number::SequenceNumber},
+  };
+
+  fn change(sn: i64) -> CacheChange {
+    CacheChange::new(
+      GUID::GUID_UNKNOWN,
+      SequenceNumber::new(sn),
+      WriteOptions::default(),
+      DDSData::new(SerializedPayload::default()),
+    )
+  }
+
+  fn ts(ticks: u64) -> Timestamp {
+    Timestamp::from_ticks(ticks)
+  }
+
+  fn sns<'a>(iter: impl Iterator<Item = (&'a Timestamp, &'a CacheChange)>) -> Vec<i64> {
+    iter.map(|(_, cc)| i64::from(cc.sequence_number)).collect()
+  }
+
+  // Both backends must behave the same, as long as the ring is not full.
+  #[test]
+  fn backends_agree() {
+    let backends: Vec<Box<dyn HistoryStorage>> = vec![
+      Box::<OrderedHistory>::default(),
+      Box::new(RingHistory::with_capacity(10)),
+    ];
+    for mut storage in backends {
+      for sn in 1..=5 {
+        assert!(storage
+          .push_newest(ts(sn as u64 * 10), change(sn))
+          .is_none());
+      }
+      assert_eq!(storage.len(), 5);
+      assert_eq!(storage.newest_key(), Some(ts(50)));
+      assert_eq!(
+        storage.get(&ts(30)).map(|cc| cc.sequence_number),
+        Some(SequenceNumber::new(3))
+      );
+      assert!(storage.get(&ts(35)).is_none());
+      assert_eq!(storage.count_before(ts(30)), 2);
+      assert_eq!(sns(storage.range(ts(20), ts(40))), vec![3, 4]);
+      assert_eq!(sns(storage.range(ts(0), ts(100))), vec![1, 2, 3, 4, 5]);
+      assert_eq!(sns(storage.range(ts(40), ts(20))), Vec::<i64>::new());
+
+      let mut removed = Vec::new();
+      storage.remove_oldest(2, &mut |cc| removed.push(i64::from(cc.sequence_number)));
+      assert_eq!(removed, vec![1, 2]);
+      assert_eq!(sns(storage.range(ts(0), ts(100))), vec![3, 4, 5]);
+
+      assert_eq!(
+        storage.remove(&ts(40)).map(|cc| cc.sequence_number),
+        Some(SequenceNumber::new(4))
+      );
+      assert!(storage.remove(&ts(40)).is_none());
+      assert_eq!(sns(storage.range(ts(0), ts(100))), vec![3, 5]);
+
+      assert_eq!(storage.drain().len(), 2);
+      assert_eq!(storage.len(), 0);
+    }
+  }
+
+  #[test]
+  fn full_ring_overwrites_oldest() {
+    let mut ring = RingHistory::with_capacity(3);
+    for sn in 1..=3 {
+      assert!(ring.push_newest(ts(sn as u64), change(sn)).is_none());
+    }
+    let overwritten = ring.push_newest(ts(4), change(4)).unwrap();
+    assert_eq!(overwritten.sequence_number, SequenceNumber::new(1));
+    assert_eq!(sns(ring.range(ts(0), ts(10))), vec![2, 3, 4]);
+  }
+
+  #[test]
+  fn storage_is_chosen_by_history() {
+    let keep_last = history_storage_for(History::KeepLast { depth: 4 }, 64);
+    assert!(keep_last.suits(History::KeepLast { depth: 4 }, 64));
+    assert!(!keep_last.suits(History::KeepLast { depth: 4 }, 128));
+    assert!(!keep_last.suits(History::KeepAll, 64));
+
+    let keep_all = history_storage_for(History::KeepAll, 64);
+    assert!(keep_all.suits(History::KeepAll, 64));
+    assert!(!keep_all.suits(History::KeepLast { depth: 4 }, 64));
+  }
+}
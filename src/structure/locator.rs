@@ -1,18 +1,32 @@
-use std::net::{SocketAddrV4, SocketAddrV6};
+use std::net::{IpAddr, SocketAddrV4, SocketAddrV6};
 pub use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 
 use speedy::{Context, Readable, Reader, Writable, Writer};
 
-mod kind {
+/// Locator kind values, as they appear on the wire.
+pub mod kind {
   pub const INVALID: i32 = -1;
   pub const RESERVED: i32 = 0;
   pub const UDP_V4: i32 = 1;
   pub const UDP_V6: i32 = 2;
+  /// From the DDS Interoperability Wire Protocol TCP/IP PSM.
+  pub const TCP_V4: i32 = 4;
+  /// From the DDS Interoperability Wire Protocol TCP/IP PSM.
+  pub const TCP_V6: i32 = 8;
+  /// Shared memory transport. This is not standardized. The value is the one
+  /// used by eProsima Fast DDS.
+  pub const SHM: i32 = 16;
 }
 
 const INVALID_PORT: u16 = 0;
 const INVALID_ADDRESS: [u8; 16] = [0; 16];
 
+/// Address of an RTPS endpoint, i.e. where to send RTPS messages.
+///
+/// See RTPS spec v2.5 Section 8.3.2 "Type Definitions". UDP locators are
+/// represented as socket addresses. Locators of other kinds are carried as
+/// `Other`, so that they can be passed through Discovery, even though RustDDS
+/// does not currently send to them.
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub enum Locator {
   Invalid,
@@ -26,11 +40,130 @@ pub enum Locator {
   },
 }
 
+/// Error from converting a [`Locator`] that does not contain an IP address
+/// and port, e.g. [`Locator::Invalid`] or a shared memory locator, into a
+/// [`SocketAddr`]. Also returned for TCP locators whose port does not fit in
+/// 16 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Locator {0:?} is not an IP locator")]
+pub struct NotIpLocatorError(pub Locator);
+
 impl Locator {
+  /// UDP over IPv4 locator
+  pub fn udp_v4(ip: Ipv4Addr, port: u16) -> Self {
+    Self::UdpV4(SocketAddrV4::new(ip, port))
+  }
+
+  /// UDP over IPv6 locator
+  pub fn udp_v6(ip: Ipv6Addr, port: u16) -> Self {
+    Self::UdpV6(SocketAddrV6::new(ip, port, 0, 0))
+  }
+
+  /// TCP over IPv4 locator. As in the DDS Interoperability Wire Protocol
+  /// TCP/IP PSM, the port field carries both the `physical_port`, i.e. the TCP
+  /// listen port, in its low 16 bits and the `logical_port`, which selects the
+  /// RTPS endpoint behind it, in its high 16 bits.
+  pub fn tcp_v4(ip: Ipv4Addr, physical_port: u16, logical_port: u16) -> Self {
+    Self::Other {
+      kind: kind::TCP_V4,
+      port: tcp_port(physical_port, logical_port),
+      address: ip.to_ipv6_compatible().octets(),
+    }
+  }
+
+  /// TCP over IPv6 locator. See [`tcp_v4`](Self::tcp_v4) for the ports.
+  pub fn tcp_v6(ip: Ipv6Addr, physical_port: u16, logical_port: u16) -> Self {
+    Self::Other {
+      kind: kind::TCP_V6,
+      port: tcp_port(physical_port, logical_port),
+      address: ip.octets(),
+    }
+  }
+
+  /// Shared memory locator. The meaning of `port` and `address` is specific
+  /// to the shared memory transport implementation.
+  pub fn shm(port: u32, address: [u8; 16]) -> Self {
+    Self::Other {
+      kind: kind::SHM,
+      port,
+      address,
+    }
+  }
+
+  /// Locator kind, as defined in module [`kind`].
+  pub fn kind(&self) -> i32 {
+    repr::Locator::from(*self).kind
+  }
+
+  /// Port number, as it appears on the wire
+  pub fn port(&self) -> u32 {
+    repr::Locator::from(*self).port
+  }
+
+  /// The 16-byte address field, as it appears on the wire. IPv4 addresses
+  /// are in the last four bytes.
+  pub fn address(&self) -> [u8; 16] {
+    repr::Locator::from(*self).address
+  }
+
+  /// IP address of a UDP or TCP locator. `None` for other kinds.
+  pub fn ip_addr(&self) -> Option<IpAddr> {
+    match *self {
+      Self::UdpV4(socket_address) => Some((*socket_address.ip()).into()),
+      Self::UdpV6(socket_address) => Some((*socket_address.ip()).into()),
+      Self::Other {
+        kind: kind::TCP_V4,
+        address,
+        ..
+      } => Some(Ipv4Addr::new(address[12], address[13], address[14], address[15]).into()),
+      Self::Other {
+        kind: kind::TCP_V6,
+        address,
+        ..
+      } => Some(Ipv6Addr::from(address).into()),
+      Self::Invalid | Self::Reserved | Self::Other { .. } => None,
+    }
+  }
+
+  /// Address of the TCP listen port (the physical port) of a TCP locator.
+  /// `None` for other kinds.
+  pub fn tcp_physical_address(&self) -> Option<SocketAddr> {
+    match *self {
+      Self::Other {
+        kind: kind::TCP_V4 | kind::TCP_V6,
+        port,
+        ..
+      } => Some(SocketAddr::new(self.ip_addr()?, port as u16)),
+      _ => None,
+    }
+  }
+
+  /// Logical port of a TCP locator. `None` for other kinds.
+  pub fn tcp_logical_port(&self) -> Option<u16> {
+    match *self {
+      Self::Other {
+        kind: kind::TCP_V4 | kind::TCP_V6,
+        port,
+        ..
+      } => Some((port >> 16) as u16),
+      _ => None,
+    }
+  }
+
   pub fn is_udp(&self) -> bool {
     matches!(self, Self::UdpV4(_) | Self::UdpV6(_))
   }
 
+  pub fn is_tcp(&self) -> bool {
+    matches!(
+      self,
+      Self::Other {
+        kind: kind::TCP_V4 | kind::TCP_V6,
+        ..
+      }
+    )
+  }
+
   pub fn is_loopback(&self) -> bool {
     match self {
       Locator::UdpV4(socket_address) => socket_address.ip().is_loopback(),
@@ -40,14 +173,35 @@ impl Locator {
   }
 }
 
-impl From<Locator> for SocketAddr {
-  fn from(locator: Locator) -> Self {
+/// Succeeds for UDP and TCP locators, unless the port of a TCP locator does
+/// not fit in 16 bits, i.e. it has a logical port. Use
+/// [`Locator::tcp_physical_address`] for those.
+impl TryFrom<Locator> for SocketAddr {
+  type Error = NotIpLocatorError;
+
+  fn try_from(locator: Locator) -> Result<Self, Self::Error> {
     match locator {
-      Locator::UdpV4(socket_address) => socket_address.into(),
-      Locator::UdpV6(socket_address) => socket_address.into(),
+      Locator::UdpV4(socket_address) => Ok(socket_address.into()),
+      Locator::UdpV6(socket_address) => Ok(socket_address.into()),
+      Locator::Other {
+        kind: kind::TCP_V4,
+        port,
+        address,
+      } => {
+        let ip = Ipv4Addr::new(address[12], address[13], address[14], address[15]);
+        let port = u16::try_from(port).map_err(|_| NotIpLocatorError(locator))?;
+        Ok(SocketAddrV4::new(ip, port).into())
+      }
+      Locator::Other {
+        kind: kind::TCP_V6,
+        port,
+        address,
+      } => {
+        let port = u16::try_from(port).map_err(|_| NotIpLocatorError(locator))?;
+        Ok(SocketAddrV6::new(Ipv6Addr::from(address), port, 0, 0).into())
+      }
       Locator::Invalid | Locator::Reserved | Locator::Other { .. } => {
-        let ip = Ipv6Addr::from(INVALID_ADDRESS).into();
-        Self::new(ip, INVALID_PORT)
+        Err(NotIpLocatorError(locator))
       }
     }
   }
@@ -65,6 +219,10 @@ impl From<SocketAddr> for Locator {
   }
 }
 
+fn tcp_port(physical_port: u16, logical_port: u16) -> u32 {
+  u32::from(logical_port) << 16 | u32::from(physical_port)
+}
+
 // use repr::Locator to serialize
 
 impl<'a, C: Context> Readable<'a, C> for Locator {
@@ -171,7 +329,7 @@ mod tests {
   use speedy::{Endianness, Readable, Writable};
   use test_case::test_case;
 
-  use super::{repr, Locator};
+  use super::{kind, repr, Locator, NotIpLocatorError};
 
   #[test_case(
     &[
@@ -304,6 +462,73 @@ mod tests {
     );
   }
 
+  #[test]
+  fn constructors_and_accessors() {
+    let udp = Locator::udp_v4(Ipv4Addr::new(10, 0, 0, 1), 7400);
+    assert_eq!(udp.kind(), kind::UDP_V4);
+    assert_eq!(udp.port(), 7400);
+    assert_eq!(udp.address()[12..], [10, 0, 0, 1]);
+    assert_eq!(udp.ip_addr(), Some(Ipv4Addr::new(10, 0, 0, 1).into()));
+
+    let udp6 = Locator::udp_v6(Ipv6Addr::LOCALHOST, 7401);
+    assert_eq!(udp6.kind(), kind::UDP_V6);
+    assert_eq!(
+      SocketAddr::try_from(udp6),
+      Ok(SocketAddr::new(Ipv6Addr::LOCALHOST.into(), 7401))
+    );
+
+    let tcp = Locator::tcp_v4(Ipv4Addr::new(192, 168, 1, 2), 7410, 0);
+    assert!(tcp.is_tcp() && !tcp.is_udp());
+    assert_eq!(tcp.kind(), kind::TCP_V4);
+    assert_eq!(
+      SocketAddr::try_from(tcp),
+      Ok(SocketAddr::new(Ipv4Addr::new(192, 168, 1, 2).into(), 7410))
+    );
+
+    let tcp = Locator::tcp_v4(Ipv4Addr::new(192, 168, 1, 2), 7410, 7412);
+    assert_eq!(tcp.port(), 7412 << 16 | 7410);
+    assert_eq!(
+      tcp.tcp_physical_address(),
+      Some(SocketAddr::new(Ipv4Addr::new(192, 168, 1, 2).into(), 7410))
+    );
+    assert_eq!(tcp.tcp_logical_port(), Some(7412));
+    assert_eq!(udp.tcp_physical_address(), None);
+    assert_eq!(udp.tcp_logical_port(), None);
+
+    let shm = Locator::shm(5, [1; 16]);
+    assert_eq!(shm.kind(), kind::SHM);
+    assert_eq!(shm.port(), 5);
+    assert_eq!(shm.ip_addr(), None);
+    assert_eq!(SocketAddr::try_from(shm), Err(NotIpLocatorError(shm)));
+    assert!(SocketAddr::try_from(Locator::Invalid).is_err());
+  }
+
+  #[test]
+  fn tcp_port_beyond_16_bits_is_not_truncated() {
+    let tcp = Locator::Other {
+      kind: kind::TCP_V4,
+      port: 70000,
+      address: Ipv4Addr::new(192, 168, 1, 2).to_ipv6_compatible().octets(),
+    };
+    assert_eq!(SocketAddr::try_from(tcp), Err(NotIpLocatorError(tcp)));
+    assert_eq!(tcp.ip_addr(), Some(Ipv4Addr::new(192, 168, 1, 2).into()));
+
+    let tcp6 = Locator::Other {
+      kind: kind::TCP_V6,
+      port: 70000,
+      address: Ipv6Addr::LOCALHOST.octets(),
+    };
+    assert_eq!(SocketAddr::try_from(tcp6), Err(NotIpLocatorError(tcp6)));
+  }
+
+  #[test]
+  fn tcp_locator_round_trip() {
+    let tcp = Locator::tcp_v6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 2), 7411, 7410);
+    let bytes = tcp.write_to_vec_with_ctx(Endianness::BigEndian).unwrap();
+    let back = Locator::read_from_buffer_with_ctx(Endianness::BigEndian, &bytes).unwrap();
+    assert_eq!(back, tcp);
+  }
+
   /// Regression: `Vec<Locator>` must not call `with_capacity` from a forged
   /// length alone (issue #404).
   #[test]
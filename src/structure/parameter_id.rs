@@ -63,6 +63,9 @@ impl ParameterId {
   pub const PID_ENTITY_NAME: Self = Self { value: 0x0062 };
   pub const PID_KEY_HASH: Self = Self { value: 0x0070 };
   pub const PID_STATUS_INFO: Self = Self { value: 0x0071 };
+  // Inline QoS: the first SequenceNumber of the coherent set that a sample
+  // belongs to. RTPS spec v2.5 Section 8.7.5.
+  pub const PID_COHERENT_SET: Self = Self { value: 0x0056 };
   // DDS-XTypes v1.3, Section 7.6.3.1: DataRepresentationQosPolicy.
   pub const PID_DATA_REPRESENTATION: Self = Self { value: 0x0073 };
 
@@ -438,5 +441,11 @@ mod tests {
       ParameterId::PID_STATUS_INFO,
       le = [0x71, 0x00],
       be = [0x00, 0x71]
+  },
+  {
+      pid_coherent_set,
+      ParameterId::PID_COHERENT_SET,
+      le = [0x56, 0x00],
+      be = [0x00, 0x56]
   });
 }
@@ -1,4 +1,7 @@
-use std::ops::{Add, Sub};
+use std::{
+  fmt,
+  ops::{Add, Sub},
+};
 
 use speedy::{Readable, Writable};
 use serde::{Deserialize, Serialize};
@@ -148,6 +151,30 @@ impl Add<Duration> for Timestamp {
   }
 }
 
+/// Source of the current time of a
+/// [`DomainParticipant`](crate::DomainParticipant),
+/// set with [`DomainParticipantBuilder::clock`](crate::DomainParticipantBuilder::clock).
+///
+/// DataReaders take reception timestamps from the clock, and compare it
+/// against them in requested deadline checks and against source timestamps in
+/// lifespan checks. DataWriters use it to timestamp HEARTBEATs. A simulated or
+/// replayed clock thus makes these follow simulated time. Timers still fire
+/// in real time, and Discovery uses the system clock.
+pub trait Clock: fmt::Debug + Send + Sync {
+  fn now(&self) -> Timestamp;
+}
+
+/// The system (wall) clock, i.e. [`Timestamp::now`]. This is the default
+/// [`Clock`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+  fn now(&self) -> Timestamp {
+    Timestamp::now()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
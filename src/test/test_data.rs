@@ -85,9 +85,9 @@ use crate::{
     adapters::no_key::DeserializerAdapter,
     qos::{
       policy::{
-        Deadline, DestinationOrder, Durability, History, LatencyBudget, Lifespan, Liveliness,
-        Ownership, Presentation, PresentationAccessScope, Reliability, ResourceLimits,
-        TimeBasedFilter,
+        Deadline, DestinationOrder, Durability, DurabilityService, History, LatencyBudget,
+        Lifespan, Liveliness, Ownership, Presentation, PresentationAccessScope, Reliability,
+        ResourceLimits, TimeBasedFilter,
       },
       QosPolicyBuilder,
     },
@@ -291,6 +291,7 @@ pub(crate) fn publication_builtin_topic_data() -> Option<PublicationBuiltinTopic
     time_based_filter: Some(TimeBasedFilter {
       minimum_separation: Duration::from(StdDuration::from_secs(5 * 30)),
     }),
+    durability_service: None,
     ownership: Some(Ownership::Shared),
     destination_order: Some(DestinationOrder::ByReceptionTimestamp),
     presentation: Some(Presentation {
@@ -298,6 +299,7 @@ pub(crate) fn publication_builtin_topic_data() -> Option<PublicationBuiltinTopic
       coherent_access: true,
       ordered_access: false,
     }),
+    partition: None,
     data_representation: None,
     related_datareader_key: None,
     service_instance_name: None,
@@ -339,6 +341,15 @@ pub(crate) fn topic_data() -> Option<TopicBuiltinTopicData> {
       max_samples_per_instance: 15,
     }),
     ownership: Some(Ownership::Exclusive { strength: 432 }),
+    durability_service: Some(DurabilityService {
+      service_cleanup_delay: Duration::from(StdDuration::from_secs(7 * 45)),
+      history: History::KeepAll,
+      resource_limits: ResourceLimits {
+        max_samples: 20,
+        max_instances: 2,
+        max_samples_per_instance: 10,
+      },
+    }),
   };
 
   Some(topic_data)
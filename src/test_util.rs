@@ -0,0 +1,1176 @@
+//! Harness for testing a single RTPS Reader or Writer without a
+//! DomainParticipant or a network.
+//!
+//! [`ReaderHarness`] and [`WriterHarness`] each own one endpoint, built with
+//! the given QoS. Crafted RTPS messages are injected into the endpoint as if
+//! they had been received from the network, and everything the endpoint sends
+//! in response is captured as [`CapturedDatagram`]s instead of going out over
+//! UDP. This makes it possible to write deterministic protocol conformance
+//! tests for a QoS configuration, or to reproduce an interop problem from a
+//! packet capture.
+//!
+//! Timers are not driven automatically. Timed behaviour (e.g. repair DATA
+//! after an ACKNACK, periodic HEARTBEATs) happens only when the harness is
+//! asked to run its timers.
+//!
+//! Available with the `test-util` feature. This API is meant for tests only
+//! and may change between releases.
+
+use std::{
+  cell::RefCell,
+  collections::{BTreeMap, BTreeSet},
+  io,
+  net::SocketAddr,
+  rc::Rc,
+  sync::{Arc, Mutex, RwLock},
+  time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use mio_extras::channel as mio_channel;
+use speedy::Endianness;
+
+use crate::{
+  dds::{
+    ddsdata::DDSData,
+    participant::{
+      BestEffortReordering, DeadlineTimestamp, FragmentAssembly, HeartbeatDelivery, LargeFanOut,
+    },
+    qos::{policy, QosPolicies},
+    statusevents::{
+      sync_status_channel, CoalescingStatistics, DataReaderStatus, DataWriterStatus,
+      DomainParticipantStatusEvent, FanOutStatistics, ParticipantStatistics, StatusChannelReceiver,
+    },
+    typedesc::TypeDesc,
+    with_key::{simpledatareader::ReaderCommand, DuplicateAcceptance, WriteOptions},
+  },
+  messages::submessages::submessages::*,
+  mio_source,
+  network::{path_mtu::PathMtu, udp_listener::PacketOrigin, udp_sender::UDPSender, util::IfAddr},
+  polling::{new_shared_timer, SharedTimer},
+  rtps::{
+    constant::DEFAULT_WRITER_MAX_SAMPLES,
+    message_receiver::MessageReceiver,
+    reader::{Reader, ReaderIngredients},
+    rtps_reader_proxy::RtpsReaderProxy,
+    statistics::StatisticsCounters,
+    timed_event::DpTimerEvent,
+    transmit::InterfaceObservations,
+    writer::{Writer, WriterIngredients},
+    writer_send_buffer::{Admission, WriterSendBuffer},
+    Message, MessageBuilder, SubmessageBody,
+  },
+  structure::{
+    cache_change::CacheChange,
+    dds_cache::DDSCache,
+    entity::RTPSEntity,
+    guid::{EntityId, EntityKind, GuidPrefix, GUID},
+    locator::Locator,
+    sequence_number::{FragmentNumber, SequenceNumber, SequenceNumberSet},
+    time::{Clock, Timestamp},
+  },
+  RepresentationIdentifier, SerializedPayload,
+};
+
+/// Endianness of all messages built by the harness.
+const ENDIANNESS: Endianness = Endianness::LittleEndian;
+
+/// One datagram sent by the endpoint under test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedDatagram {
+  /// Where the datagram would have been sent to.
+  pub destination: SocketAddr,
+  /// The complete RTPS message, as it would have been sent.
+  pub bytes: Vec<u8>,
+}
+
+impl CapturedDatagram {
+  /// Parse the datagram and summarize the submessages it contains, in order.
+  /// INFO_* submessages are omitted.
+  pub fn submessages(&self) -> io::Result<Vec<CapturedSubmessage>> {
+    let message = Message::read_from_buffer(&Bytes::copy_from_slice(&self.bytes))?;
+    let source = message.header.guid_prefix;
+    Ok(
+      message
+        .submessages
+        .into_iter()
+        .filter_map(|sm| CapturedSubmessage::from_body(source, sm.body))
+        .collect(),
+    )
+  }
+}
+
+/// Summary of a submessage found in a [`CapturedDatagram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CapturedSubmessage {
+  Data {
+    writer: GUID,
+    writer_sn: SequenceNumber,
+    /// Serialized payload, including the 4-byte encapsulation header.
+    payload: Option<Bytes>,
+  },
+  DataFrag {
+    writer: GUID,
+    writer_sn: SequenceNumber,
+    fragment_starting_num: u32,
+    fragments_in_submessage: u16,
+  },
+  Gap {
+    writer: GUID,
+    /// Every sequence number declared irrelevant, in order.
+    irrelevant: Vec<SequenceNumber>,
+  },
+  Heartbeat {
+    writer: GUID,
+    first_sn: SequenceNumber,
+    last_sn: SequenceNumber,
+    count: i32,
+    final_flag: bool,
+    liveliness_flag: bool,
+  },
+  AckNack {
+    reader: GUID,
+    /// All sequence numbers before `base` are acknowledged.
+    base: SequenceNumber,
+    /// Sequence numbers requested for (re)sending.
+    missing: Vec<SequenceNumber>,
+    count: i32,
+    final_flag: bool,
+  },
+  NackFrag {
+    reader: GUID,
+    writer_sn: SequenceNumber,
+    /// Fragment numbers requested for (re)sending.
+    missing: Vec<u32>,
+  },
+  /// Anything else, e.g. a security submessage.
+  Other,
+}
+
+impl CapturedSubmessage {
+  fn from_body(source: GuidPrefix, body: SubmessageBody) -> Option<Self> {
+    let summary = match body {
+      SubmessageBody::Interpreter(_) => return None,
+      SubmessageBody::Writer(WriterSubmessage::Data(data, _)) => Self::Data {
+        writer: GUID::new(source, data.writer_id),
+        writer_sn: data.writer_sn,
+        payload: data.serialized_payload,
+      },
+      SubmessageBody::Writer(WriterSubmessage::DataFrag(df, _)) => Self::DataFrag {
+        writer: GUID::new(source, df.writer_id),
+        writer_sn: df.writer_sn,
+        fragment_starting_num: df.fragment_starting_num.into(),
+        fragments_in_submessage: df.fragments_in_submessage,
+      },
+      SubmessageBody::Writer(WriterSubmessage::Gap(gap, _)) => Self::Gap {
+        writer: GUID::new(source, gap.writer_id),
+        irrelevant: (i64::from(gap.gap_start)..i64::from(gap.gap_list.base()))
+          .map(SequenceNumber::new)
+          .chain(gap.gap_list.iter())
+          .collect(),
+      },
+      SubmessageBody::Writer(WriterSubmessage::Heartbeat(hb, flags)) => Self::Heartbeat {
+        writer: GUID::new(source, hb.writer_id),
+        first_sn: hb.first_sn,
+        last_sn: hb.last_sn,
+        count: hb.count,
+        final_flag: flags.contains(HEARTBEAT_Flags::Final),
+        liveliness_flag: flags.contains(HEARTBEAT_Flags::Liveliness),
+      },
+      SubmessageBody::Reader(ReaderSubmessage::AckNack(an, flags)) => Self::AckNack {
+        reader: GUID::new(source, an.reader_id),
+        base: an.reader_sn_state.base(),
+        missing: an.reader_sn_state.iter().collect(),
+        count: an.count,
+        final_flag: flags.contains(ACKNACK_Flags::Final),
+      },
+      SubmessageBody::Reader(ReaderSubmessage::NackFrag(nf, _)) => Self::NackFrag {
+        reader: GUID::new(source, nf.reader_id),
+        writer_sn: nf.writer_sn,
+        missing: nf.fragment_number_state.iter().map(u32::from).collect(),
+      },
+      #[allow(unreachable_patterns)] // security submessages exist only with feature "security"
+      _ => Self::Other,
+    };
+    Some(summary)
+  }
+}
+
+fn user_entity_id(kind: EntityKind) -> EntityId {
+  EntityId::new([0, 0, 1], kind)
+}
+
+fn serialize(message: &Message) -> Bytes {
+  // Messages built by the harness itself are always serializable.
+  Bytes::from(message.write_to_vec_fast(ENDIANNESS).unwrap())
+}
+
+fn take_captured(udp_sender: &UDPSender) -> Vec<CapturedDatagram> {
+  udp_sender
+    .take_captured()
+    .into_iter()
+    .map(|(destination, bytes)| CapturedDatagram { destination, bytes })
+    .collect()
+}
+
+/// Poll `timer` for `duration`, passing every expired event to `dispatch`.
+fn run_timers(
+  timer: &SharedTimer<DpTimerEvent>,
+  duration: Duration,
+  mut dispatch: impl FnMut(DpTimerEvent),
+) {
+  let deadline = Instant::now() + duration;
+  loop {
+    // Do not hold the timer borrow while dispatching: handlers set new timeouts.
+    let event = timer.borrow_mut().poll();
+    match event {
+      Some(event) => dispatch(event),
+      None if Instant::now() >= deadline => return,
+      None => std::thread::sleep(Duration::from_millis(1)),
+    }
+  }
+}
+
+/// A single no-key RTPS Reader fed with injected messages.
+pub struct ReaderHarness {
+  message_receiver: MessageReceiver,
+  reader_guid: GUID,
+  udp_sender: Rc<UDPSender>,
+  timer: SharedTimer<DpTimerEvent>,
+  status_receiver: StatusChannelReceiver<DataReaderStatus>,
+  // Kept so that the Reader's channels stay connected.
+  _dds_cache: Arc<RwLock<DDSCache>>,
+  _notification_receiver: mio_channel::Receiver<()>,
+  _acknack_receiver: mio_channel::Receiver<(GuidPrefix, AckSubmessage)>,
+  _spdp_liveness_receiver: mio_channel::Receiver<GuidPrefix>,
+  _reader_command_sender: mio_channel::SyncSender<ReaderCommand>,
+  _participant_status_receiver: StatusChannelReceiver<DomainParticipantStatusEvent>,
+  _poll_event_source: mio_source::PollEventSource,
+}
+
+impl ReaderHarness {
+  /// Create a Reader for topic `topic_name` with QoS `qos`.
+  pub fn new(topic_name: &str, qos: &QosPolicies) -> io::Result<Self> {
+    let reader_guid = GUID::new(
+      GuidPrefix::random_for_this_participant(),
+      user_entity_id(EntityKind::READER_NO_KEY_USER_DEFINED),
+    );
+
+    let (acknack_sender, _acknack_receiver) = mio_channel::sync_channel(16);
+    let (spdp_liveness_sender, _spdp_liveness_receiver) = mio_channel::sync_channel(16);
+    let mut message_receiver = MessageReceiver::new(
+      reader_guid.prefix,
+      acknack_sender,
+      spdp_liveness_sender,
+      None,
+      Rc::new(RefCell::new(InterfaceObservations::new())),
+    );
+
+    let dds_cache = Arc::new(RwLock::new(DDSCache::new()));
+    let topic_cache_handle = dds_cache.write().unwrap().add_new_topic(
+      topic_name.to_string(),
+      TypeDesc::new(topic_name.to_string()),
+      qos,
+    );
+
+    let (notification_sender, _notification_receiver) = mio_channel::sync_channel(100);
+    let (_poll_event_source, poll_event_sender) = mio_source::make_poll_channel()?;
+    let (status_sender, status_receiver) = sync_status_channel(64)?;
+    let (participant_status_sender, _participant_status_receiver) = sync_status_channel(64)?;
+    let (_reader_command_sender, data_reader_command_receiver) = mio_channel::sync_channel(10);
+
+    let udp_sender = Rc::new(UDPSender::new_capturing()?);
+    let timer = new_shared_timer();
+
+    let mut reader = Reader::new(
+      ReaderIngredients {
+        guid: reader_guid,
+        notification_sender,
+        status_sender,
+        topic_name: topic_name.to_string(),
+        topic_cache_handle,
+        like_stateless: false,
+        qos_policy: qos.clone(),
+        data_reader_command_receiver,
+        data_reader_waker: Arc::new(Mutex::new(None)),
+        poll_event_sender,
+        liveliness_status: Default::default(),
+        requested_deadline_missed_status: Default::default(),
+        detect_content_duplicates: Default::default(),
+        ignore_ambiguous_status_info: Default::default(),
+        duplicate_acceptance: Default::default(),
+        paused: Default::default(),
+        history_boundaries: None,
+        security_plugins: None,
+      },
+      udp_sender.clone(),
+      Rc::clone(&timer),
+      participant_status_sender,
+    )
+    .map_err(io::Error::other)?;
+    // Prime the deadline check, as the event loop does.
+    reader.set_requested_deadline_check_timer();
+    message_receiver.add_reader(reader);
+
+    Ok(Self {
+      message_receiver,
+      reader_guid,
+      udp_sender,
+      timer,
+      status_receiver,
+      _dds_cache: dds_cache,
+      _notification_receiver,
+      _acknack_receiver,
+      _spdp_liveness_receiver,
+      _reader_command_sender,
+      _participant_status_receiver,
+      _poll_event_source,
+    })
+  }
+
+  /// GUID of the Reader under test.
+  pub fn guid(&self) -> GUID {
+    self.reader_guid
+  }
+
+  fn reader(&mut self) -> &mut Reader {
+    // The Reader is added in the constructor and never removed.
+    self
+      .message_receiver
+      .reader_mut(self.reader_guid.entity_id)
+      .unwrap()
+  }
+
+  /// Use `clock` instead of the system clock, as the participant-builder
+  /// `clock` knob does.
+  pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+    self.reader().set_clock(clock);
+  }
+
+  /// Hold samples from BestEffort Writers to deliver them in sequence, as the
+  /// participant-builder `best_effort_reordering` knob does.
+  pub fn set_best_effort_reordering(&mut self, policy: Option<BestEffortReordering>) {
+    self.reader().set_best_effort_reordering(policy);
+  }
+
+  /// Request fragment gaps after `delay` without waiting for a HEARTBEAT, as
+  /// the participant-builder `proactive_nackfrag_delay` knob does.
+  pub fn set_proactive_nackfrag_delay(&mut self, delay: Option<Duration>) {
+    self.reader().set_proactive_nackfrag_delay(delay);
+  }
+
+  /// Delay the responses to HEARTBEATs by up to `delay`, as the
+  /// participant-builder `heartbeat_response_delay` knob does.
+  pub fn set_heartbeat_response_delay(&mut self, delay: Duration) {
+    self.reader().set_heartbeat_response_delay(delay);
+  }
+
+  /// Answer the HEARTBEATs of a Writer at most once per `duration`, as
+  /// `DataReader::set_heartbeat_suppression` does.
+  pub fn set_heartbeat_suppression_duration(&mut self, duration: Duration) {
+    self.reader().set_heartbeat_suppression_duration(duration);
+  }
+
+  /// Drop partially received samples as the participant-builder
+  /// `fragment_assembly` knob does.
+  pub fn set_fragment_assembly(&mut self, timing: FragmentAssembly) {
+    self.reader().set_fragment_assembly(timing);
+  }
+
+  /// Measure requested deadlines against `timestamp`, as the
+  /// participant-builder `deadline_timestamp` knob does.
+  pub fn set_deadline_timestamp(&mut self, timestamp: DeadlineTimestamp) {
+    self.reader().set_deadline_timestamp(timestamp);
+  }
+
+  /// Flag samples whose payload repeats the previous one of their instance, as
+  /// `DataReader::set_detect_content_duplicates` does.
+  pub fn set_detect_content_duplicates(&mut self, enabled: bool) {
+    self.reader().set_detect_content_duplicates(enabled);
+  }
+
+  /// Deliver repeated SequenceNumbers or not, as
+  /// `DataReader::set_duplicate_acceptance` does.
+  pub fn set_duplicate_acceptance(&mut self, acceptance: DuplicateAcceptance) {
+    self.reader().set_duplicate_acceptance(acceptance);
+  }
+
+  /// Pause or resume the Reader, as `DataReader::pause` and
+  /// `DataReader::resume` do.
+  pub fn set_paused(&mut self, paused: bool) {
+    self.reader().set_paused(paused);
+  }
+
+  /// Match a remote Writer, as Discovery would. `unicast_locators` are where
+  /// the Reader sends its ACKNACKs to.
+  pub fn add_matched_writer(
+    &mut self,
+    writer_guid: GUID,
+    unicast_locators: Vec<Locator>,
+    offered_qos: &QosPolicies,
+  ) {
+    self.reader().matched_writer_add(
+      writer_guid,
+      EntityId::UNKNOWN,
+      unicast_locators,
+      vec![],
+      offered_qos,
+    );
+  }
+
+  /// Count the ACKNACKs and NACK_FRAGs sent to the matched `writer` from
+  /// `count` on, instead of from the usual starting value.
+  pub fn set_next_acknack_count(&mut self, writer: GUID, count: i32) {
+    self.reader().set_next_acknack_count(writer, count);
+  }
+
+  /// Unmatch a remote Writer, as Discovery would when the Writer is lost.
+  pub fn remove_matched_writer(&mut self, writer_guid: GUID) {
+    self.reader().remove_writer_proxy(writer_guid);
+  }
+
+  /// Feed a complete serialized RTPS message to the Reader, as if it had been
+  /// received from the network.
+  pub fn inject_message(&mut self, bytes: &[u8]) {
+    self
+      .message_receiver
+      .handle_received_packet(&Bytes::copy_from_slice(bytes), PacketOrigin::UNKNOWN);
+  }
+
+  fn inject(&mut self, message: &Message) {
+    self
+      .message_receiver
+      .handle_received_packet(&serialize(message), PacketOrigin::UNKNOWN);
+  }
+
+  /// Inject a DATA submessage from `writer`.
+  pub fn inject_data(
+    &mut self,
+    writer: GUID,
+    writer_sn: SequenceNumber,
+    payload: SerializedPayload,
+  ) {
+    self.inject_data_with_options(writer, writer_sn, payload, WriteOptions::default());
+  }
+
+  /// Inject a DATA submessage from `writer`, with the inline QoS given by
+  /// `write_options`, e.g. a key hash. The source timestamp of
+  /// `write_options`, if any, is sent in an INFO_TS.
+  pub fn inject_data_with_options(
+    &mut self,
+    writer: GUID,
+    writer_sn: SequenceNumber,
+    payload: SerializedPayload,
+    write_options: WriteOptions,
+  ) {
+    let source_timestamp = write_options.source_timestamp();
+    let change = CacheChange::new(writer, writer_sn, write_options, DDSData::new(payload));
+    let message = MessageBuilder::new()
+      .ts_msg(ENDIANNESS, source_timestamp)
+      .data_msg(
+        &change,
+        self.reader_guid.entity_id,
+        writer,
+        ENDIANNESS,
+        None,
+      )
+      .add_header_and_build(writer.prefix);
+    self.inject(&message);
+  }
+
+  /// Inject a HEARTBEAT submessage from `writer`.
+  pub fn inject_heartbeat(
+    &mut self,
+    writer: GUID,
+    first_sn: SequenceNumber,
+    last_sn: SequenceNumber,
+    count: i32,
+    final_flag: bool,
+  ) {
+    let message = MessageBuilder::new()
+      .heartbeat_msg(
+        writer.entity_id,
+        first_sn,
+        last_sn,
+        count,
+        ENDIANNESS,
+        self.reader_guid.entity_id,
+        final_flag,
+        false,
+      )
+      .add_header_and_build(writer.prefix);
+    self.inject(&message);
+  }
+
+  /// Inject a DATA_FRAG submessage from `writer`, carrying
+  /// `fragments_in_submessage` fragments of `payload` starting from
+  /// `fragment_starting_num` (1-based).
+  pub fn inject_data_frag(
+    &mut self,
+    writer: GUID,
+    writer_sn: SequenceNumber,
+    payload: SerializedPayload,
+    fragment_starting_num: u32,
+    fragments_in_submessage: u16,
+    fragment_size: u16,
+  ) {
+    let sample_size = payload.len_serialized() as u32;
+    let change = CacheChange::new(
+      writer,
+      writer_sn,
+      WriteOptions::default(),
+      DDSData::new(payload),
+    );
+    let message = MessageBuilder::new()
+      .ts_msg(ENDIANNESS, None)
+      .data_frag_msg(
+        &change,
+        self.reader_guid.entity_id,
+        writer,
+        FragmentNumber::new(fragment_starting_num),
+        fragments_in_submessage,
+        fragment_size,
+        sample_size,
+        ENDIANNESS,
+        None,
+      )
+      .add_header_and_build(writer.prefix);
+    self.inject(&message);
+  }
+
+  /// Inject a HEARTBEAT_FRAG submessage from `writer`, announcing fragments up
+  /// to `last_fragment_num` of `writer_sn`.
+  pub fn inject_heartbeat_frag(
+    &mut self,
+    writer: GUID,
+    writer_sn: SequenceNumber,
+    last_fragment_num: u32,
+    count: i32,
+  ) {
+    let message = MessageBuilder::new()
+      .heartbeat_frag_msg(
+        writer.entity_id,
+        writer_sn,
+        FragmentNumber::new(last_fragment_num),
+        count,
+        ENDIANNESS,
+        self.reader_guid.entity_id,
+      )
+      .add_header_and_build(writer.prefix);
+    self.inject(&message);
+  }
+
+  /// Inject a GAP submessage from `writer`, declaring `irrelevant` sequence
+  /// numbers irrelevant. Does nothing if `irrelevant` is empty.
+  pub fn inject_gap(&mut self, writer: GUID, irrelevant: &[SequenceNumber]) {
+    let irrelevant: BTreeSet<SequenceNumber> = irrelevant.iter().copied().collect();
+    let message = MessageBuilder::new()
+      .gap_msg(&irrelevant, writer.entity_id, ENDIANNESS, self.reader_guid)
+      .add_header_and_build(writer.prefix);
+    if !message.submessages.is_empty() {
+      self.inject(&message);
+    }
+  }
+
+  /// Run the Reader's timed events (deadline and liveliness checks) that
+  /// expire within `duration`. Blocks for `duration`.
+  pub fn run_timers_for(&mut self, duration: Duration) {
+    let timer = Rc::clone(&self.timer);
+    run_timers(&timer, duration, |event| {
+      if let DpTimerEvent::Reader { entity_id, event } = event {
+        if let Some(reader) = self.message_receiver.reader_mut(entity_id) {
+          reader.handle_timed_event(event);
+        }
+      }
+    });
+  }
+
+  /// Sequence numbers of the samples the Reader has stored, in order.
+  pub fn received_sequence_numbers(&mut self) -> Vec<SequenceNumber> {
+    self.reader().received_sequence_numbers()
+  }
+
+  /// Sequence numbers of the samples the Reader has stored, in the order the
+  /// DataReader reads them.
+  pub fn delivered_sequence_numbers(&mut self) -> Vec<SequenceNumber> {
+    self.reader().cached_sequence_numbers()
+  }
+
+  /// Sequence numbers of the samples the Reader has stored, in the order a
+  /// Reliable DataReader reads them in `DeliveryOrder::InOrder`.
+  pub fn reliably_delivered_sequence_numbers(&mut self) -> Vec<SequenceNumber> {
+    self.reader().reliably_cached_sequence_numbers()
+  }
+
+  /// Sequence numbers of the stored samples that are flagged as content
+  /// duplicates, in the order the DataReader reads them.
+  pub fn content_duplicate_sequence_numbers(&mut self) -> Vec<SequenceNumber> {
+    self.reader().content_duplicate_sequence_numbers()
+  }
+
+  /// Payload of the stored sample `sequence_number`, if it is a data sample.
+  pub fn received_payload(&mut self, sequence_number: SequenceNumber) -> Option<SerializedPayload> {
+    match self.reader().history_cache_change_data(sequence_number)? {
+      DDSData::Data { serialized_payload } => Some(serialized_payload),
+      _ => None,
+    }
+  }
+
+  /// Reception timestamp of the stored sample `sequence_number`.
+  pub fn reception_timestamp(&mut self, sequence_number: SequenceNumber) -> Option<Timestamp> {
+    self.reader().reception_timestamp(sequence_number)
+  }
+
+  /// Take the datagrams sent by the Reader since the previous call.
+  pub fn take_sent(&mut self) -> Vec<CapturedDatagram> {
+    take_captured(&self.udp_sender)
+  }
+
+  /// Next pending status event of the Reader, if any.
+  pub fn try_recv_status(&self) -> Option<DataReaderStatus> {
+    self.status_receiver.try_recv().ok()
+  }
+}
+
+/// A single no-key RTPS Writer driven by the test.
+pub struct WriterHarness {
+  writer: Writer,
+  send_buffer: WriterSendBuffer,
+  udp_sender: Rc<UDPSender>,
+  timer: SharedTimer<DpTimerEvent>,
+  status_receiver: StatusChannelReceiver<DataWriterStatus>,
+  _participant_status_receiver: StatusChannelReceiver<DomainParticipantStatusEvent>,
+  statistics: Arc<StatisticsCounters>,
+}
+
+impl WriterHarness {
+  /// Create a Writer for topic `topic_name` with QoS `qos`.
+  pub fn new(topic_name: &str, qos: &QosPolicies) -> io::Result<Self> {
+    let writer_guid = GUID::new(
+      GuidPrefix::random_for_this_participant(),
+      user_entity_id(EntityKind::WRITER_NO_KEY_USER_DEFINED),
+    );
+
+    // Same sizing as for a DataWriter: History depth, or the default capacity.
+    let window_limit = match qos.history() {
+      Some(policy::History::KeepLast { depth }) => depth as usize,
+      _ => DEFAULT_WRITER_MAX_SAMPLES,
+    };
+    let volatile = matches!(
+      qos.durability().unwrap_or(policy::Durability::Volatile),
+      policy::Durability::Volatile
+    );
+    let send_buffer = WriterSendBuffer::new(
+      writer_guid,
+      topic_name.to_string(),
+      qos.is_reliable(),
+      false,
+      volatile,
+      window_limit,
+      window_limit,
+      window_limit,
+    );
+
+    let (doorbell_registration, doorbell) = mio_06::Registration::new2();
+    let (status_sender, status_receiver) = sync_status_channel(64)?;
+    let (participant_status_sender, _participant_status_receiver) = sync_status_channel(64)?;
+
+    let statistics = Arc::new(StatisticsCounters::default());
+    let mut udp_sender = UDPSender::new_capturing()?;
+    udp_sender.set_statistics(Arc::clone(&statistics));
+    let udp_sender = Rc::new(udp_sender);
+    let timer = new_shared_timer();
+    let local_interfaces: Rc<[IfAddr]> = Rc::from(Vec::new());
+
+    let mut writer = Writer::new(
+      WriterIngredients {
+        guid: writer_guid,
+        send_buffer: send_buffer.clone(),
+        doorbell_registration,
+        doorbell,
+        topic_name: topic_name.to_string(),
+        like_stateless: false,
+        qos_policies: qos.clone(),
+        status_sender,
+        security_plugins: None,
+      },
+      udp_sender.clone(),
+      Rc::clone(&timer),
+      participant_status_sender,
+      Rc::new(RefCell::new(InterfaceObservations::new())),
+      local_interfaces,
+    );
+    writer.set_statistics(Arc::clone(&statistics));
+
+    Ok(Self {
+      writer,
+      send_buffer,
+      udp_sender,
+      timer,
+      status_receiver,
+      _participant_status_receiver,
+      statistics,
+    })
+  }
+
+  /// GUID of the Writer under test.
+  pub fn guid(&self) -> GUID {
+    self.writer.guid()
+  }
+
+  /// Number the written samples from `first` on, instead of from 1.
+  ///
+  /// # Panics
+  ///
+  /// If samples have already been written.
+  pub fn set_initial_sequence_number(&mut self, first: SequenceNumber) {
+    self.send_buffer.set_initial_sequence_number(first);
+  }
+
+  /// Count the HEARTBEATs from `count` on, instead of from 1.
+  pub fn set_next_heartbeat_count(&mut self, count: i32) {
+    self.writer.set_next_heartbeat_count(count);
+  }
+
+  /// Match a remote Reader, as Discovery would. Messages to the Reader are
+  /// addressed to `unicast_locators`.
+  pub fn add_matched_reader(
+    &mut self,
+    reader_guid: GUID,
+    unicast_locators: Vec<Locator>,
+    requested_qos: &QosPolicies,
+  ) {
+    self.add_matched_multicast_reader(reader_guid, unicast_locators, vec![], requested_qos);
+  }
+
+  /// Like [`add_matched_reader`](Self::add_matched_reader), but the Reader
+  /// also listens to `multicast_locators`.
+  pub fn add_matched_multicast_reader(
+    &mut self,
+    reader_guid: GUID,
+    unicast_locators: Vec<Locator>,
+    multicast_locators: Vec<Locator>,
+    requested_qos: &QosPolicies,
+  ) {
+    let mut proxy = RtpsReaderProxy::new(reader_guid, requested_qos.clone(), false);
+    proxy.unicast_locator_list = unicast_locators;
+    proxy.multicast_locator_list = multicast_locators;
+    self.writer.update_reader_proxy(&proxy, requested_qos);
+  }
+
+  /// Lose all Readers of the participant `prefix`, as Discovery would when
+  /// the participant expires.
+  pub fn participant_lost(&mut self, prefix: GuidPrefix) {
+    self.writer.participant_lost(prefix);
+  }
+
+  /// Traffic counters, as the DomainParticipant would report them.
+  pub fn statistics(&self) -> ParticipantStatistics {
+    self.statistics.snapshot()
+  }
+
+  /// Requested QoS of each matched Reader, as the DataWriter would report it.
+  pub fn matched_reader_qos(&self) -> BTreeMap<GUID, QosPolicies> {
+    self.send_buffer.matched_reader_qos()
+  }
+
+  /// Write a sample and let the Writer transmit it. Returns the sequence
+  /// number assigned to the sample, or `None` if the send window stayed full
+  /// for `timeout`.
+  pub fn write(
+    &mut self,
+    payload: SerializedPayload,
+    timeout: Option<Duration>,
+  ) -> Option<SequenceNumber> {
+    let admission =
+      self
+        .send_buffer
+        .admit_blocking(WriteOptions::default(), DDSData::new(payload), timeout);
+    self.writer.process_pending();
+    match admission {
+      Admission::Admitted(sn) => Some(sn),
+      Admission::WouldBlock => None,
+    }
+  }
+
+  /// Convenience for [`write`](Self::write): CDR-encapsulated `bytes`, without
+  /// a timeout.
+  pub fn write_bytes(&mut self, bytes: &[u8]) -> Option<SequenceNumber> {
+    self.write(
+      SerializedPayload::new(RepresentationIdentifier::CDR_LE, bytes.to_vec()),
+      None,
+    )
+  }
+
+  /// Inject an ACKNACK from `reader`: everything before `base` is
+  /// acknowledged, and `missing` sequence numbers are requested.
+  pub fn inject_acknack(
+    &mut self,
+    reader: GUID,
+    base: SequenceNumber,
+    missing: &[SequenceNumber],
+    count: i32,
+  ) {
+    let missing: BTreeSet<SequenceNumber> = missing.iter().copied().collect();
+    let acknack = AckNack {
+      reader_id: reader.entity_id,
+      writer_id: self.guid().entity_id,
+      reader_sn_state: SequenceNumberSet::from_base_and_set(base, &missing),
+      count,
+    };
+    self
+      .writer
+      .handle_ack_nack(reader.prefix, &AckSubmessage::AckNack(acknack));
+  }
+
+  /// Let the Writer hold samples up to `max_delay` to coalesce them, as the
+  /// participant-builder `max_coalesce_delay` knob does.
+  pub fn set_max_coalesce_delay(&mut self, max_delay: Option<Duration>) {
+    self.writer.set_max_coalesce_delay(max_delay);
+  }
+
+  /// Override the coalescing delay for this Writer only, as
+  /// `DataWriter::set_max_coalesce_delay` does.
+  pub fn set_own_max_coalesce_delay(&mut self, max_delay: Duration) {
+    self.send_buffer.set_max_coalesce_delay(Some(max_delay));
+    self.writer.process_pending();
+  }
+
+  /// Send held samples now, as `DataWriter::flush` does.
+  pub fn flush(&mut self) {
+    self.send_buffer.request_flush();
+    self.writer.process_pending();
+  }
+
+  /// Send HEARTBEATs every `period` when the Readers are up to date, as the
+  /// participant-builder `heartbeat_period` knob does.
+  pub fn set_heartbeat_period(&mut self, period: Duration) {
+    self.writer.set_heartbeat_period(period);
+  }
+
+  /// Choose how periodic HEARTBEATs are sent, as the participant-builder
+  /// `heartbeat_delivery` knob does.
+  pub fn set_heartbeat_delivery(&mut self, delivery: HeartbeatDelivery) {
+    self.writer.set_heartbeat_delivery(delivery);
+  }
+
+  /// Coalescing statistics, as the DataWriter would report them.
+  pub fn coalescing_statistics(&self) -> CoalescingStatistics {
+    self.send_buffer.coalescing_statistics()
+  }
+
+  pub fn set_large_fan_out(&mut self, policy: Option<LargeFanOut>) {
+    self.writer.set_large_fan_out(policy);
+  }
+
+  /// Fan-out statistics, as the DataWriter would report them.
+  pub fn fan_out_statistics(&self) -> FanOutStatistics {
+    self.send_buffer.fan_out_statistics()
+  }
+
+  /// Configure the path MTU to destinations, as the participant-builder
+  /// `path_mtu` knob does.
+  pub fn set_path_mtu(&mut self, path_mtu: PathMtu) {
+    self.writer.set_path_mtu(Arc::new(path_mtu));
+  }
+
+  /// Path MTU to each matched Reader, as the DataWriter would report it.
+  pub fn reader_path_mtus(&self) -> Vec<(GUID, u32)> {
+    self.send_buffer.reader_path_mtus()
+  }
+
+  /// Samples not yet acknowledged by `reader`, as the DataWriter would report
+  /// them.
+  pub fn unacked_sample_count(&self, reader: GUID) -> usize {
+    self.send_buffer.unacked_sample_count(reader)
+  }
+
+  /// Samples not yet acknowledged by the slowest reader, as the DataWriter
+  /// would report them.
+  pub fn max_unacked_sample_count(&self) -> usize {
+    self.send_buffer.max_unacked_sample_count()
+  }
+
+  /// Send a periodic HEARTBEAT now, as the heartbeat timer would.
+  pub fn heartbeat_tick(&mut self) {
+    self.writer.handle_heartbeat_tick(false);
+  }
+
+  /// Run the Writer's timed events (repairs, periodic HEARTBEATs) that expire
+  /// within `duration`. Blocks for `duration`.
+  pub fn run_timers_for(&mut self, duration: Duration) {
+    let timer = Rc::clone(&self.timer);
+    run_timers(&timer, duration, |event| {
+      if let DpTimerEvent::Writer { event, .. } = event {
+        self.writer.handle_timed_event(event);
+      }
+    });
+  }
+
+  /// Take the datagrams sent by the Writer since the previous call.
+  pub fn take_sent(&mut self) -> Vec<CapturedDatagram> {
+    take_captured(&self.udp_sender)
+  }
+
+  /// Next pending status event of the Writer, if any.
+  pub fn try_recv_status(&self) -> Option<DataWriterStatus> {
+    self.status_receiver.try_recv().ok()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::rtps::constant::NACK_RESPONSE_DELAY;
+
+  fn peer_guid(kind: EntityKind) -> GUID {
+    GUID::new(GuidPrefix::new(&[7; 12]), user_entity_id(kind))
+  }
+
+  fn peer_locator() -> Locator {
+    Locator::from(SocketAddr::from(([192, 0, 2, 1], 7411)))
+  }
+
+  fn acknacks(sent: &[CapturedDatagram]) -> Vec<(SequenceNumber, Vec<SequenceNumber>)> {
+    sent
+      .iter()
+      .flat_map(|d| d.submessages().unwrap())
+      .filter_map(|sm| match sm {
+        CapturedSubmessage::AckNack { base, missing, .. } => Some((base, missing)),
+        _ => None,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn reader_harness_nacks_missing_data() {
+    let qos = QosPolicies::builder()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: crate::Duration::from_millis(100),
+      })
+      .build();
+    let mut h = ReaderHarness::new("harness_reader", &qos).unwrap();
+    let writer = peer_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    h.add_matched_writer(writer, vec![peer_locator()], &qos);
+
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![1, 2, 3, 4]);
+    h.inject_data(writer, SequenceNumber::new(1), payload.clone());
+    h.inject_data(writer, SequenceNumber::new(3), payload.clone());
+    assert_eq!(
+      h.received_sequence_numbers(),
+      vec![SequenceNumber::new(1), SequenceNumber::new(3)]
+    );
+    assert_eq!(
+      h.received_payload(SequenceNumber::new(3)).unwrap().value,
+      payload.value
+    );
+
+    h.take_sent();
+    h.inject_heartbeat(
+      writer,
+      SequenceNumber::new(1),
+      SequenceNumber::new(3),
+      1,
+      false,
+    );
+    let sent = h.take_sent();
+    assert!(sent
+      .iter()
+      .all(|d| d.destination == SocketAddr::from(([192, 0, 2, 1], 7411))));
+    assert_eq!(
+      acknacks(&sent),
+      vec![(SequenceNumber::new(2), vec![SequenceNumber::new(2)])]
+    );
+
+    // GAP fills the hole, so the next HEARTBEAT is acknowledged in full.
+    h.inject_gap(writer, &[SequenceNumber::new(2)]);
+    h.inject_heartbeat(
+      writer,
+      SequenceNumber::new(1),
+      SequenceNumber::new(3),
+      2,
+      false,
+    );
+    assert_eq!(
+      acknacks(&h.take_sent()),
+      vec![(SequenceNumber::new(4), vec![])]
+    );
+  }
+
+  #[test]
+  fn writer_harness_repairs_nacked_data() {
+    let qos = QosPolicies::builder()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: crate::Duration::from_millis(100),
+      })
+      .build();
+    let mut h = WriterHarness::new("harness_writer", &qos).unwrap();
+    let reader = peer_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    h.add_matched_reader(reader, vec![peer_locator()], &qos);
+    h.take_sent();
+
+    let sn = h.write_bytes(&[1, 2, 3, 4]).unwrap();
+    let data_sns = |sent: &[CapturedDatagram]| -> Vec<SequenceNumber> {
+      sent
+        .iter()
+        .flat_map(|d| d.submessages().unwrap())
+        .filter_map(|sm| match sm {
+          CapturedSubmessage::Data { writer_sn, .. } => Some(writer_sn),
+          _ => None,
+        })
+        .collect()
+    };
+    assert_eq!(data_sns(&h.take_sent()), vec![sn]);
+
+    h.heartbeat_tick();
+    let heartbeats: Vec<_> = h
+      .take_sent()
+      .iter()
+      .flat_map(|d| d.submessages().unwrap())
+      .filter(|sm| matches!(sm, CapturedSubmessage::Heartbeat { .. }))
+      .collect();
+    assert!(!heartbeats.is_empty());
+
+    // Reader lost the sample: the repair goes out after the NACK response delay.
+    h.inject_acknack(reader, sn, &[sn], 1);
+    assert_eq!(data_sns(&h.take_sent()), vec![]);
+    h.run_timers_for(NACK_RESPONSE_DELAY + Duration::from_millis(100));
+    assert_eq!(data_sns(&h.take_sent()), vec![sn]);
+  }
+
+  #[test]
+  fn writer_harness_starts_from_configured_numbers() {
+    let qos = QosPolicies::builder()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: crate::Duration::from_millis(100),
+      })
+      .build();
+    let mut h = WriterHarness::new("harness_initial_writer", &qos).unwrap();
+    h.set_initial_sequence_number(SequenceNumber::new(1000));
+    let reader = peer_guid(EntityKind::READER_NO_KEY_USER_DEFINED);
+    h.add_matched_reader(reader, vec![peer_locator()], &qos);
+    h.take_sent();
+
+    assert_eq!(
+      h.write_bytes(&[1, 2, 3, 4]),
+      Some(SequenceNumber::new(1000))
+    );
+    assert_eq!(
+      h.write_bytes(&[5, 6, 7, 8]),
+      Some(SequenceNumber::new(1001))
+    );
+    h.take_sent();
+    h.set_next_heartbeat_count(50);
+    h.heartbeat_tick();
+    let heartbeats: Vec<_> = h
+      .take_sent()
+      .iter()
+      .flat_map(|d| d.submessages().unwrap())
+      .filter_map(|sm| match sm {
+        CapturedSubmessage::Heartbeat {
+          first_sn,
+          last_sn,
+          count,
+          ..
+        } => Some((first_sn, last_sn, count)),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(
+      heartbeats,
+      vec![(SequenceNumber::new(1000), SequenceNumber::new(1001), 50)]
+    );
+  }
+
+  #[test]
+  fn reader_harness_starts_from_configured_count() {
+    let qos = QosPolicies::builder()
+      .reliability(policy::Reliability::Reliable {
+        max_blocking_time: crate::Duration::from_millis(100),
+      })
+      .build();
+    let mut h = ReaderHarness::new("harness_initial_reader", &qos).unwrap();
+    let writer = peer_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    h.add_matched_writer(writer, vec![peer_locator()], &qos);
+    h.set_next_acknack_count(writer, 100);
+
+    // A Writer whose samples start from 1000
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![1, 2, 3, 4]);
+    h.inject_data(writer, SequenceNumber::new(1000), payload);
+    h.take_sent();
+    h.inject_heartbeat(
+      writer,
+      SequenceNumber::new(1000),
+      SequenceNumber::new(1001),
+      1,
+      false,
+    );
+    let sent: Vec<_> = h
+      .take_sent()
+      .iter()
+      .flat_map(|d| d.submessages().unwrap())
+      .filter_map(|sm| match sm {
+        CapturedSubmessage::AckNack {
+          base,
+          missing,
+          count,
+          ..
+        } => Some((base, missing, count)),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(
+      sent,
+      vec![(
+        SequenceNumber::new(1001),
+        vec![SequenceNumber::new(1001)],
+        100
+      )]
+    );
+    assert_eq!(
+      h.received_sequence_numbers(),
+      vec![SequenceNumber::new(1000)]
+    );
+  }
+
+  // Simulated time, advanced by the test only.
+  #[derive(Debug)]
+  struct ManualClock(Mutex<Timestamp>);
+
+  impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+      *self.0.lock().unwrap()
+    }
+  }
+
+  #[test]
+  fn reader_follows_the_configured_clock() {
+    let qos = QosPolicies::builder()
+      .deadline(policy::Deadline(crate::Duration::from_millis(50)))
+      .build();
+    let mut h = ReaderHarness::new("harness_clock", &qos).unwrap();
+    let start = Timestamp::now() - crate::Duration::from_secs(3600);
+    let clock = Arc::new(ManualClock(Mutex::new(start)));
+    h.set_clock(clock.clone());
+    let writer = peer_guid(EntityKind::WRITER_NO_KEY_USER_DEFINED);
+    h.add_matched_writer(writer, vec![peer_locator()], &qos);
+    while h.try_recv_status().is_some() {} // match events
+
+    let payload = SerializedPayload::new(RepresentationIdentifier::CDR_LE, vec![1, 2, 3, 4]);
+    h.inject_data(writer, SequenceNumber::new(1), payload);
+    assert_eq!(h.reception_timestamp(SequenceNumber::new(1)), Some(start));
+
+    // Simulated time stands still, so the deadline is not missed however
+    // long the test waits.
+    h.run_timers_for(Duration::from_millis(200));
+    assert!(h.try_recv_status().is_none());
+
+    *clock.0.lock().unwrap() = start + crate::Duration::from_secs(1);
+    h.run_timers_for(Duration::from_millis(100));
+    assert!(matches!(
+      h.try_recv_status(),
+      Some(DataReaderStatus::RequestedDeadlineMissed { .. })
+    ));
+  }
+}
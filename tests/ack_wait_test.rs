@@ -0,0 +1,182 @@
+//! wait_for_acknowledgments returns once the matched reliable DataReader has
+//! acknowledged the written sample, and does not wait for a BestEffort
+//! DataReader, which never acknowledges. The async variant can be raced
+//! against the status stream, and dropped unfinished.
+use std::{
+  task::Poll,
+  time::{Duration, Instant},
+};
+
+use futures::{
+  future::{self, Either},
+  StreamExt,
+};
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, StatusEvented, TopicKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn wait_for_acknowledgments_waits_for_reliable_readers() {
+  let participant_a = DomainParticipantBuilder::new(67)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(67)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let reliable_qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+  let best_effort_qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::BestEffort)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "ack_wait_test_topic".to_string(),
+      "Ping".to_string(),
+      &reliable_qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&reliable_qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "ack_wait_test_topic".to_string(),
+      "Ping".to_string(),
+      &reliable_qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let subscriber = participant_b.create_subscriber(&reliable_qos).unwrap();
+  let mut reliable_reader = subscriber
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+  let _best_effort_reader = subscriber
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, Some(best_effort_qos))
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while writer.get_matched_subscriptions().len() < 2 {
+    assert!(
+      Instant::now() < deadline,
+      "readers not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+
+  writer.write(Ping { seq: 1 }, None).unwrap();
+  // The sample has not even been sent yet, let alone acknowledged.
+  assert_eq!(writer.max_unacked_across_readers(), 1);
+  assert!(!writer.wait_for_acknowledgments(Duration::ZERO).unwrap());
+
+  assert!(writer
+    .wait_for_acknowledgments(Duration::from_secs(5))
+    .unwrap());
+  assert_eq!(writer.max_unacked_across_readers(), 0);
+  let sample = reliable_reader.take_next_sample().unwrap().unwrap();
+  assert_eq!(sample.into_value(), Ping { seq: 1 });
+
+  // The async variant completes as well.
+  writer.write(Ping { seq: 2 }, None).unwrap();
+  assert!(smol::block_on(writer.async_wait_for_acknowledgments()).unwrap());
+  assert_eq!(writer.max_unacked_across_readers(), 0);
+}
+
+#[test]
+fn async_wait_for_acknowledgments_alongside_status_stream() {
+  let participant_a = DomainParticipantBuilder::new(69)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(69)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "async_ack_wait_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "async_ack_wait_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while writer.get_matched_subscriptions().is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "reader not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+
+  let acked = smol::block_on(async {
+    writer.async_write(Ping { seq: 1 }, None).await.unwrap();
+
+    // A wait that is dropped unfinished leaves the DataWriter intact.
+    let mut abandoned = Box::pin(writer.async_wait_for_acknowledgments());
+    assert!(matches!(futures::poll!(abandoned.as_mut()), Poll::Pending));
+    drop(abandoned);
+
+    // Wait for the acknowledgment, while handling status events.
+    let mut status_stream = writer.as_async_status_stream();
+    let mut ack_wait = Box::pin(writer.async_wait_for_acknowledgments());
+    let timeout = smol::Timer::after(Duration::from_secs(5));
+    let mut timeout = Box::pin(timeout);
+    loop {
+      let next_event = status_stream.select_next_some();
+      match future::select(ack_wait, future::select(next_event, timeout)).await {
+        Either::Left((result, _)) => break result.unwrap(),
+        Either::Right((Either::Left((_status, pending_timeout)), pending_ack_wait)) => {
+          ack_wait = pending_ack_wait;
+          timeout = pending_timeout;
+        }
+        Either::Right((Either::Right(_), _)) => panic!("not acknowledged within 5 seconds"),
+      }
+    }
+  });
+  assert!(acked);
+  assert_eq!(writer.max_unacked_across_readers(), 0);
+  let sample = reader.take_next_sample().unwrap().unwrap();
+  assert_eq!(sample.into_value(), Ping { seq: 1 });
+}
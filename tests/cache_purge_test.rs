@@ -0,0 +1,97 @@
+//! A KeepAll DataReader reports how many samples its topic cache holds, and
+//! purging the samples it has already consumed shrinks the cache.
+use std::time::{Duration, Instant};
+
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, TopicKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn consumed_samples_are_purged_from_the_cache() {
+  let participant_a = DomainParticipantBuilder::new(70)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(70)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "cache_purge_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "cache_purge_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+  assert_eq!(reader.cache_occupancy().sample_count, 0);
+  assert_eq!(reader.cache_occupancy().oldest, None);
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while writer.get_matched_subscriptions().is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "reader not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  for seq in 0..5 {
+    writer.write(Ping { seq }, None).unwrap();
+  }
+
+  let mut reception_timestamps = Vec::new();
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while reception_timestamps.len() < 5 {
+    assert!(
+      Instant::now() < deadline,
+      "samples not received within 5 seconds"
+    );
+    while let Ok(Some(sample)) = reader.take_next_sample() {
+      reception_timestamps.push(sample.sample_info().reception_timestamp());
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+
+  // Taking does not remove samples from the KeepAll cache.
+  let occupancy = reader.cache_occupancy();
+  assert_eq!(occupancy.sample_count, 5);
+  assert_eq!(occupancy.oldest, Some(reception_timestamps[0]));
+  assert_eq!(occupancy.newest, Some(reception_timestamps[4]));
+
+  assert_eq!(reader.purge_before(reception_timestamps[4]), 4);
+  let occupancy = reader.cache_occupancy();
+  assert_eq!(occupancy.sample_count, 1);
+  assert_eq!(occupancy.oldest, Some(reception_timestamps[4]));
+  // Nothing older is left.
+  assert_eq!(reader.purge_before(reception_timestamps[4]), 0);
+}
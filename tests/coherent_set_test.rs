@@ -0,0 +1,108 @@
+//! Samples written between begin_coherent_changes and end_coherent_changes
+//! reach the DataReader together, and only after the set has ended.
+use std::time::{Duration, Instant};
+
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, TopicKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn coherent_set_is_delivered_at_once() {
+  let participant_a = DomainParticipantBuilder::new(68)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(68)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "coherent_set_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let publisher = participant_a.create_publisher(&qos).unwrap();
+  let writer = publisher
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "coherent_set_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while writer.get_matched_subscriptions().is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "reader not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  writer.write(Ping { seq: 0 }, None).unwrap();
+
+  publisher.begin_coherent_changes();
+  writer.write(Ping { seq: 1 }, None).unwrap();
+
+  // The sample before the set is delivered, the first member is held.
+  let deadline = Instant::now() + Duration::from_secs(5);
+  loop {
+    assert!(
+      Instant::now() < deadline,
+      "sample not received within 5 seconds"
+    );
+    if let Ok(Some(sample)) = reader.take_next_sample() {
+      assert_eq!(sample.into_value(), Ping { seq: 0 });
+      break;
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+  std::thread::sleep(Duration::from_millis(300));
+  assert!(
+    reader.take_next_sample().unwrap().is_none(),
+    "member of an unfinished coherent set was delivered"
+  );
+
+  writer.write(Ping { seq: 2 }, None).unwrap();
+  publisher.end_coherent_changes();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  let set = loop {
+    assert!(
+      Instant::now() < deadline,
+      "coherent set not received within 5 seconds"
+    );
+    let samples = reader.take(100, rustdds::ReadCondition::any()).unwrap();
+    if !samples.is_empty() {
+      break samples
+        .into_iter()
+        .map(|sample| sample.value().seq)
+        .collect::<Vec<_>>();
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  };
+  assert_eq!(set, vec![1, 2]);
+}
@@ -0,0 +1,163 @@
+//! A content-filtered DataReader delivers only the matching samples, but all
+//! disposals. A blocking take waits past the samples it filters out.
+use std::time::{Duration, Instant};
+
+use rustdds::{policy, with_key::Sample, DomainParticipant, Keyed, QosPolicyBuilder, TopicKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Shape {
+  color: String,
+  x: i32,
+  y: i32,
+  shapesize: i32,
+}
+
+impl Keyed for Shape {
+  type K = String;
+
+  fn key(&self) -> Self::K {
+    self.color.clone()
+  }
+}
+
+fn shape(color: &str, x: i32) -> Shape {
+  Shape {
+    color: color.to_string(),
+    x,
+    y: 0,
+    shapesize: 30,
+  }
+}
+
+#[test]
+fn only_matching_samples_and_disposals_are_read() {
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .durability(policy::Durability::TransientLocal)
+    .history(policy::History::KeepAll)
+    .build();
+
+  let participant = DomainParticipant::new(56).unwrap();
+  let topic = participant
+    .create_topic(
+      "content_filter_test_topic".to_string(),
+      "ShapeType".to_string(),
+      &qos,
+      TopicKind::WithKey,
+    )
+    .unwrap();
+  let mut reader = participant
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_content_filtered_datareader::<Shape, rustdds::CDRDeserializerAdapter<Shape>, _>(
+      &topic,
+      None,
+      |shape| shape.x > 100,
+    )
+    .unwrap();
+  let writer = participant
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_cdr::<Shape>(&topic, None)
+    .unwrap();
+
+  writer.write(shape("RED", 50), None).unwrap();
+  writer.write(shape("BLUE", 150), None).unwrap();
+  writer.write(shape("RED", 60), None).unwrap();
+  writer.dispose(&"RED".to_string(), None).unwrap();
+  writer.write(shape("GREEN", 200), None).unwrap();
+
+  let mut received = Vec::new();
+  let deadline = Instant::now() + Duration::from_secs(10);
+  while received.len() < 3 {
+    assert!(
+      Instant::now() < deadline,
+      "expected samples not received within 10 seconds, got {received:?}"
+    );
+    received.extend(reader.into_iterator().unwrap());
+    std::thread::sleep(Duration::from_millis(50));
+  }
+
+  assert_eq!(
+    received,
+    vec![
+      Sample::Value(shape("BLUE", 150)),
+      Sample::Dispose("RED".to_string()),
+      Sample::Value(shape("GREEN", 200)),
+    ]
+  );
+}
+
+#[test]
+fn blocking_take_waits_past_filtered_samples() {
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let participant = DomainParticipant::new(57).unwrap();
+  let topic = participant
+    .create_topic(
+      "content_filter_blocking_test_topic".to_string(),
+      "ShapeType".to_string(),
+      &qos,
+      TopicKind::WithKey,
+    )
+    .unwrap();
+  let mut reader = participant
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_content_filtered_datareader::<Shape, rustdds::CDRDeserializerAdapter<Shape>, _>(
+      &topic,
+      None,
+      |shape| shape.x > 100,
+    )
+    .unwrap();
+  let writer = participant
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_cdr::<Shape>(&topic, None)
+    .unwrap();
+
+  // Nothing to take
+  let start = Instant::now();
+  assert!(reader
+    .take_next_sample_timeout(Duration::from_millis(200))
+    .unwrap()
+    .is_none());
+  assert!(start.elapsed() >= Duration::from_millis(200));
+
+  // The filtered samples wake the reader up, but it keeps waiting.
+  let start = Instant::now();
+  let writing = std::thread::spawn(move || {
+    writer.write(shape("RED", 50), None).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    writer.write(shape("RED", 60), None).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    writer.write(shape("BLUE", 150), None).unwrap();
+    writer
+  });
+  let sample = reader
+    .take_next_sample_timeout(Duration::from_secs(10))
+    .unwrap()
+    .expect("matching sample not received within 10 seconds");
+  assert!(start.elapsed() >= Duration::from_millis(600));
+  assert_eq!(sample.into_value(), Sample::Value(shape("BLUE", 150)));
+  drop(writing.join().unwrap());
+
+  // The blocking takes left the reader free to be registered to a Poll.
+  let poll = mio_06::Poll::new().unwrap();
+  poll
+    .register(
+      &reader,
+      mio_06::Token(0),
+      mio_06::Ready::readable(),
+      mio_06::PollOpt::edge(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,109 @@
+//! Participants that list each other as initial peers discover each other
+//! with no multicast, and then exchange samples.
+use std::{
+  net::{IpAddr, Ipv4Addr, SocketAddr},
+  time::{Duration, Instant},
+};
+
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, TopicKind};
+use serde::{Deserialize, Serialize};
+
+const DOMAIN_ID: u16 = 64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+// Well-known SPDP unicast port, RTPS spec v2.5 Section 9.6.2.3.
+fn spdp_peer(participant_id: u16) -> SocketAddr {
+  let port = 7400 + 250 * DOMAIN_ID + 10 + 2 * participant_id;
+  SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)
+}
+
+#[test]
+fn participants_listing_each_other_discover_by_unicast() {
+  let participant_a = DomainParticipantBuilder::new(DOMAIN_ID)
+    .participant_id(3)
+    .same_host_loopback(false)
+    .unicast_discovery_only(true)
+    .initial_peers([spdp_peer(7)])
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(DOMAIN_ID)
+    .participant_id(7)
+    .same_host_loopback(false)
+    .unicast_discovery_only(true)
+    .initial_peers([spdp_peer(3)])
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "initial_peers_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "initial_peers_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(10);
+  while writer.get_matched_subscriptions().is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "reader not matched within 10 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  writer.write(Ping { seq: 1 }, None).unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  loop {
+    assert!(
+      Instant::now() < deadline,
+      "sample not received within 5 seconds"
+    );
+    if let Ok(Some(sample)) = reader.take_next_sample() {
+      assert_eq!(sample.into_value(), Ping { seq: 1 });
+      break;
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+}
+
+#[test]
+fn invalid_initial_peers_are_rejected() {
+  let unspecified = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 7410);
+  let no_port = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+  for peer in [unspecified, no_port] {
+    assert!(DomainParticipantBuilder::new(DOMAIN_ID)
+      .initial_peers([peer])
+      .build()
+      .is_err());
+  }
+}
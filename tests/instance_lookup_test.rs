@@ -0,0 +1,136 @@
+//! Instances of a WITH_KEY topic are looked up and read by key, and a
+//! disposed instance stays readable with its instance state.
+use std::time::{Duration, Instant};
+
+use rustdds::{
+  policy, with_key::Sample, DomainParticipantBuilder, InstanceState, Key, Keyed, QosPolicyBuilder,
+  ReadCondition, SelectByKey, TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Shape {
+  color: String,
+  x: i32,
+}
+
+impl Keyed for Shape {
+  type K = String;
+
+  fn key(&self) -> String {
+    self.color.clone()
+  }
+}
+
+#[test]
+fn instances_are_looked_up_and_read_by_key() {
+  let participant_a = DomainParticipantBuilder::new(63)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(63)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "instance_lookup_test_topic".to_string(),
+      "Shape".to_string(),
+      &qos,
+      TopicKind::WithKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_cdr::<Shape>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "instance_lookup_test_topic".to_string(),
+      "Shape".to_string(),
+      &qos,
+      TopicKind::WithKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_cdr::<Shape>(&topic_b, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while writer.get_matched_subscriptions().is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "reader not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  for (color, x) in [("RED", 1), ("BLUE", 2), ("RED", 3)] {
+    let color = color.to_string();
+    writer.write(Shape { color, x }, None).unwrap();
+  }
+  writer.dispose(&"BLUE".to_string(), None).unwrap();
+
+  let blue = "BLUE".to_string();
+  let deadline = Instant::now() + Duration::from_secs(5);
+  let blue_samples = loop {
+    assert!(
+      Instant::now() < deadline,
+      "dispose not received within 5 seconds"
+    );
+    let samples = reader
+      .read_instance(
+        100,
+        ReadCondition::any(),
+        Some(blue.clone()),
+        SelectByKey::This,
+      )
+      .unwrap()
+      .into_iter()
+      .map(|s| (s.sample_info().instance_state(), s.into_value()))
+      .collect::<Vec<_>>();
+    if samples.len() == 2 {
+      break samples;
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  };
+  assert!(blue_samples
+    .iter()
+    .all(|(state, _)| *state == InstanceState::NotAliveDisposed));
+  assert!(matches!(blue_samples[1].1, Sample::Dispose(ref k) if *k == blue));
+
+  let red = "RED".to_string();
+  let red_values: Vec<i32> = reader
+    .take_instance(
+      100,
+      ReadCondition::any(),
+      Some(red.clone()),
+      SelectByKey::This,
+    )
+    .unwrap()
+    .into_iter()
+    .map(|s| s.into_value().value().unwrap().x)
+    .collect();
+  assert_eq!(red_values, vec![1, 3]);
+
+  // Instances stay known when disposed or when their samples are taken.
+  assert_eq!(
+    reader.lookup_instance(&blue).unwrap(),
+    Some(blue.hash_key(false))
+  );
+  assert_eq!(
+    reader.lookup_instance(&red).unwrap(),
+    Some(red.hash_key(false))
+  );
+  assert_eq!(reader.lookup_instance(&"GREEN".to_string()).unwrap(), None);
+}
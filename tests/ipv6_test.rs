@@ -0,0 +1,77 @@
+//! Two participants using IPv6 discovery, reachable only through IPv6
+//! loopback, exchange a sample.
+use std::time::{Duration, Instant};
+
+use rustdds::{
+  policy, DiscoveryMulticast, DomainParticipant, DomainParticipantBuilder, IpVersion,
+  MulticastScope, QosPolicyBuilder, TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+fn ipv6_participant() -> DomainParticipant {
+  DomainParticipantBuilder::new(53)
+    .discovery_multicast(DiscoveryMulticast::new(
+      MulticastScope::LinkLocal,
+      IpVersion::V6,
+    ))
+    .advertised_addresses(["::1".parse::<std::net::IpAddr>().unwrap()])
+    .build()
+    .unwrap()
+}
+
+#[test]
+fn sample_is_exchanged_over_ipv6_loopback() {
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let participant_a = ipv6_participant();
+  let participant_b = ipv6_participant();
+
+  let topic_a = participant_a
+    .create_topic(
+      "ipv6_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_a
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "ipv6_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_b
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(10);
+  while Instant::now() < deadline {
+    writer.write(Ping { seq: 7 }, None).unwrap();
+    if let Ok(Some(sample)) = reader.take_next_sample() {
+      assert_eq!(sample.into_value().seq, 7);
+      return;
+    }
+    std::thread::sleep(Duration::from_millis(200));
+  }
+  panic!("no sample arrived over IPv6 loopback within 10 seconds");
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "json")]
+use std::time::{Duration, Instant};
+
+use rustdds::{
+  dds::ReadError, policy, DataWriterStatus, DomainParticipantBuilder, QosPolicyBuilder,
+  StatusEvented, TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+  text: String,
+}
+
+#[test]
+fn json_writer_reaches_json_reader_but_not_cdr_reader() {
+  let participant_a = DomainParticipantBuilder::new(57)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(57)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .durability(policy::Durability::Volatile)
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "json_adapter_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_json::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "json_adapter_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let subscriber = participant_b.create_subscriber(&qos).unwrap();
+  let mut json_reader = subscriber
+    .create_datareader_no_key_json::<Ping>(&topic_b, None)
+    .unwrap();
+  let mut cdr_reader = subscriber
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  // Wait until the writer has matched both readers, so that the volatile
+  // sample reaches them.
+  let deadline = Instant::now() + Duration::from_secs(5);
+  let mut matched_readers = 0;
+  while matched_readers < 2 {
+    assert!(
+      Instant::now() < deadline,
+      "writer did not match the readers within 5 seconds"
+    );
+    match writer.try_recv_status() {
+      Some(DataWriterStatus::PublicationMatched { current, .. }) => {
+        matched_readers = current.count();
+      }
+      Some(_) => {}
+      None => std::thread::sleep(Duration::from_millis(10)),
+    }
+  }
+
+  let ping = Ping {
+    seq: 7,
+    text: "hello".to_string(),
+  };
+  writer.write(ping.clone(), None).unwrap();
+
+  let mut json_received = None;
+  let mut cdr_rejected = false;
+  while Instant::now() < deadline && !(json_received.is_some() && cdr_rejected) {
+    if let Ok(Some(sample)) = json_reader.take_next_sample() {
+      json_received = Some(sample.into_value());
+    }
+    match cdr_reader.take_next_sample() {
+      Ok(None) => {}
+      Ok(Some(sample)) => panic!("CDR reader decoded JSON data: {sample:?}"),
+      Err(ReadError::Deserialization { .. }) => cdr_rejected = true,
+      Err(e) => panic!("Unexpected read error: {e}"),
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+  assert_eq!(json_received, Some(ping));
+  assert!(
+    cdr_rejected,
+    "CDR reader did not report the encoding mismatch"
+  );
+}
@@ -5,7 +5,10 @@
 /// drop all data.
 use std::time::{Duration, Instant};
 
-use rustdds::{policy, DomainParticipant, QosPolicyBuilder, TopicKind};
+use rustdds::{
+  discovery::MatchStatus, policy, DataWriterStatus, DomainParticipant, DomainParticipantBuilder,
+  QosPolicyBuilder, StatusEvented, TopicKind,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -63,15 +66,11 @@ fn late_writer_can_reach_early_reader() {
   writer.write(Ping { seq: 42 }, None).unwrap();
 
   // Read from A.
-  let deadline = Instant::now() + Duration::from_secs(5);
-  while Instant::now() < deadline {
-    if let Ok(Some(sample)) = reader.take_next_sample() {
-      assert_eq!(sample.into_value().seq, 42);
-      return; // success
-    }
-    std::thread::sleep(Duration::from_millis(50));
-  }
-  panic!("late writer's data never arrived at the early reader within 5 seconds");
+  let sample = reader
+    .take_next_sample_timeout(Duration::from_secs(5))
+    .unwrap()
+    .expect("late writer's data never arrived at the early reader within 5 seconds");
+  assert_eq!(sample.into_value().seq, 42);
 }
 
 #[test]
@@ -124,13 +123,89 @@ fn late_reader_can_receive_from_early_writer() {
   writer.write(Ping { seq: 99 }, None).unwrap();
 
   // Read from B's late reader.
+  let sample = reader
+    .take_next_sample_timeout(Duration::from_secs(5))
+    .unwrap()
+    .expect("early writer's data never arrived at the late reader within 5 seconds");
+  assert_eq!(sample.into_value().seq, 99);
+}
+
+#[test]
+fn in_process_discovery_matches_without_sleeping() {
+  let participant_a = DomainParticipantBuilder::new(53)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(53)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .durability(policy::Durability::Volatile)
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "in_process_discovery_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "in_process_discovery_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
   let deadline = Instant::now() + Duration::from_secs(5);
-  while Instant::now() < deadline {
-    if let Ok(Some(sample)) = reader.take_next_sample() {
-      assert_eq!(sample.into_value().seq, 99);
-      return; // success
-    }
-    std::thread::sleep(Duration::from_millis(50));
+  let matched = || {
+    participant_a.discovery_dump().matches.iter().any(|m| {
+      m.topic_name == "in_process_discovery_test_topic" && m.status == MatchStatus::Matched
+    })
+  };
+  while !matched() {
+    assert!(
+      Instant::now() < deadline,
+      "in-process participants did not match within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(10));
   }
-  panic!("early writer's data never arrived at the late reader within 5 seconds");
+  // Discovery knows of the match before the writer has added the reader, and
+  // a volatile sample written before that would not reach the reader.
+  while !matches!(
+    writer.try_recv_status(),
+    Some(DataWriterStatus::PublicationMatched { .. })
+  ) {
+    assert!(
+      Instant::now() < deadline,
+      "writer did not match the reader within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(10));
+  }
+
+  writer.write(Ping { seq: 7 }, None).unwrap();
+  let sample = reader
+    .take_next_sample_timeout(deadline.saturating_duration_since(Instant::now()))
+    .unwrap()
+    .expect("sample never arrived at the in-process reader within 5 seconds");
+  assert_eq!(sample.into_value().seq, 7);
 }
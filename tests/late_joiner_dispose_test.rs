@@ -0,0 +1,112 @@
+//! Disposals and unregistrations are written by key, and a TransientLocal
+//! DataWriter keeps them in its history, so a DataReader that joins later
+//! learns that the instances are gone.
+use std::time::{Duration, Instant};
+
+use rustdds::{
+  policy, with_key::Sample, DomainParticipantBuilder, InstanceState, Keyed, QosPolicyBuilder,
+  TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Shape {
+  color: String,
+  x: i32,
+}
+
+impl Keyed for Shape {
+  type K = String;
+
+  fn key(&self) -> String {
+    self.color.clone()
+  }
+}
+
+fn shape(color: &str, x: i32) -> Shape {
+  Shape {
+    color: color.to_string(),
+    x,
+  }
+}
+
+#[test]
+fn late_reader_takes_disposals_by_key() {
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .durability(policy::Durability::TransientLocal)
+    .history(policy::History::KeepAll)
+    .build();
+
+  let participant_a = DomainParticipantBuilder::new(73)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let topic_a = participant_a
+    .create_topic(
+      "late_joiner_dispose_test_topic".to_string(),
+      "Shape".to_string(),
+      &qos,
+      TopicKind::WithKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_cdr::<Shape>(&topic_a, None)
+    .unwrap();
+
+  writer.write(shape("RED", 1), None).unwrap();
+  writer.write(shape("BLUE", 2), None).unwrap();
+  writer.dispose(&"RED".to_string(), None).unwrap();
+  writer
+    .unregister_instance(&"BLUE".to_string(), None)
+    .unwrap();
+
+  // The DataReader joins after everything was written.
+  let participant_b = DomainParticipantBuilder::new(73)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let topic_b = participant_b
+    .create_topic(
+      "late_joiner_dispose_test_topic".to_string(),
+      "Shape".to_string(),
+      &qos,
+      TopicKind::WithKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_cdr::<Shape>(&topic_b, None)
+    .unwrap();
+
+  let mut received = Vec::new();
+  let deadline = Instant::now() + Duration::from_secs(10);
+  while received.len() < 4 {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let sample = reader
+      .take_next_sample_timeout(remaining)
+      .unwrap()
+      .unwrap_or_else(|| panic!("history not received within 10 seconds, got {received:?}"));
+    received.push((sample.sample_info().instance_state(), sample.into_value()));
+  }
+
+  let values: Vec<Sample<Shape, String>> = received.iter().map(|(_, v)| v.clone()).collect();
+  assert_eq!(
+    values,
+    vec![
+      Sample::Value(shape("RED", 1)),
+      Sample::Value(shape("BLUE", 2)),
+      Sample::Dispose("RED".to_string()),
+      // An unregistration is delivered by key, too.
+      Sample::Dispose("BLUE".to_string()),
+    ]
+  );
+  assert_eq!(received[2].0, InstanceState::NotAliveDisposed);
+  // Unlike a disposal, an unregistration leaves the instance without writers.
+  assert_eq!(received[3].0, InstanceState::NotAliveNoWriters);
+}
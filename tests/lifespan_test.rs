@@ -0,0 +1,100 @@
+//! A TransientLocal DataWriter does not replay samples that have outlived
+//! their Lifespan to late-joining DataReaders.
+use std::time::{Duration, Instant};
+
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, TopicKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  text: String,
+}
+
+#[test]
+fn expired_samples_are_not_replayed_to_late_joiners() {
+  let participant_a = DomainParticipantBuilder::new(59)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(59)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_millis(100),
+    })
+    .durability(policy::Durability::TransientLocal)
+    .history(policy::History::KeepAll)
+    .build();
+  let short_lived_qos = qos.modify_by(
+    &QosPolicyBuilder::new()
+      .lifespan(policy::Lifespan {
+        duration: rustdds::Duration::from_secs(1),
+      })
+      .build(),
+  );
+
+  let topic_a = participant_a
+    .create_topic(
+      "lifespan_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let publisher = participant_a.create_publisher(&qos).unwrap();
+  let short_lived_writer = publisher
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, Some(short_lived_qos))
+    .unwrap();
+  // Without a Lifespan, for comparison
+  let durable_writer = publisher
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  short_lived_writer
+    .write(
+      Ping {
+        text: "expired".to_string(),
+      },
+      None,
+    )
+    .unwrap();
+  durable_writer
+    .write(
+      Ping {
+        text: "kept".to_string(),
+      },
+      None,
+    )
+    .unwrap();
+  std::thread::sleep(Duration::from_secs(2));
+
+  let topic_b = participant_b
+    .create_topic(
+      "lifespan_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  // The sample without a Lifespan is replayed. Keep reading for a while after
+  // it, so that the expired one would have arrived, too.
+  let mut received = vec![];
+  let deadline = Instant::now() + Duration::from_secs(5);
+  let mut read_until = deadline;
+  while Instant::now() < read_until {
+    while let Ok(Some(sample)) = reader.take_next_sample() {
+      received.push(sample.into_value().text);
+      read_until = Instant::now() + Duration::from_secs(1);
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+  assert_eq!(received, vec!["kept".to_string()]);
+}
@@ -0,0 +1,87 @@
+//! A DataReader reports a matched DataWriter as not alive once the writer
+//! stops asserting its MANUAL_BY_TOPIC liveliness for longer than the lease.
+use std::time::{Duration, Instant};
+
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, TopicKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn reader_detects_lost_manual_by_topic_liveliness() {
+  let participant_a = DomainParticipantBuilder::new(58)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(58)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_millis(100),
+    })
+    .liveliness(policy::Liveliness::ManualByTopic {
+      lease_duration: rustdds::Duration::from_millis(500),
+    })
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "liveliness_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "liveliness_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  // While the writer keeps asserting its liveliness, it is alive.
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while reader.liveliness_changed_status().alive_count() != 1 {
+    assert!(
+      Instant::now() < deadline,
+      "writer was not seen alive within 5 seconds"
+    );
+    writer.assert_liveliness().unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+  }
+  let assert_until = Instant::now() + Duration::from_secs(1);
+  while Instant::now() < assert_until {
+    writer.assert_liveliness().unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(reader.liveliness_changed_status().alive_count(), 1);
+  }
+
+  // Once the assertions stop, the lease expires.
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while reader.liveliness_changed_status().not_alive_count() != 1 {
+    assert!(
+      Instant::now() < deadline,
+      "writer liveliness was not lost within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  assert_eq!(reader.liveliness_changed_status().alive_count(), 0);
+}
@@ -0,0 +1,102 @@
+//! Participants restricted to one network interface advertise only its
+//! locators and still communicate, and a filter that selects no interface is
+//! rejected.
+use std::{
+  net::Ipv4Addr,
+  time::{Duration, Instant},
+};
+
+use rustdds::{policy, DomainParticipantBuilder, InterfaceFilter, QosPolicyBuilder, TopicKind};
+use serde::{Deserialize, Serialize};
+
+const DOMAIN_ID: u16 = 71;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn participants_on_loopback_interface_communicate() {
+  let loopback_only = || InterfaceFilter::new().allow_address(Ipv4Addr::LOCALHOST);
+  let participant_a = DomainParticipantBuilder::new(DOMAIN_ID)
+    .network_interfaces(loopback_only())
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(DOMAIN_ID)
+    .network_interfaces(loopback_only())
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "network_interfaces_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "network_interfaces_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while writer.get_matched_subscriptions().is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "reader not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  writer.write(Ping { seq: 1 }, None).unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  loop {
+    assert!(
+      Instant::now() < deadline,
+      "sample not received within 5 seconds"
+    );
+    if let Ok(Some(sample)) = reader.take_next_sample() {
+      assert_eq!(sample.into_value(), Ping { seq: 1 });
+      break;
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+}
+
+#[test]
+fn filter_selecting_no_interface_is_rejected() {
+  for filter in [
+    InterfaceFilter::new().allow_name("no-such-interface"),
+    InterfaceFilter::new().allow_name(""),
+  ] {
+    assert!(DomainParticipantBuilder::new(DOMAIN_ID)
+      .network_interfaces(filter)
+      .build()
+      .is_err());
+  }
+}
@@ -0,0 +1,114 @@
+//! A DataReader created to start from now skips the history that a
+//! TransientLocal DataWriter sends to late joiners, but not the samples
+//! written after it was created.
+use std::time::{Duration, Instant};
+
+use rustdds::{
+  policy, DataReaderStatus, DomainParticipantBuilder, QosPolicyBuilder, ReadStart, StatusEvented,
+  TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn reader_from_now_skips_writer_history() {
+  let participant_a = DomainParticipantBuilder::new(66)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(66)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .durability(policy::Durability::TransientLocal)
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "read_start_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+  for seq in 0..5 {
+    writer.write(Ping { seq }, None).unwrap();
+  }
+
+  let topic_b = participant_b
+    .create_topic(
+      "read_start_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let subscriber = participant_b.create_subscriber(&qos).unwrap();
+  let mut from_now = subscriber
+    .create_datareader_no_key_with_start::<Ping, rustdds::CDRDeserializerAdapter<Ping>>(
+      &topic_b,
+      None,
+      ReadStart::FromNow,
+    )
+    .unwrap();
+  let mut all_available = subscriber
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  // Samples that a reader receives before it has matched the writer are
+  // history too, so wait for the reader side.
+  let deadline = Instant::now() + Duration::from_secs(5);
+  let mut matched = false;
+  while !matched || writer.get_matched_subscriptions().len() < 2 {
+    assert!(
+      Instant::now() < deadline,
+      "readers not matched within 5 seconds"
+    );
+    while let Some(status) = from_now.try_recv_status() {
+      matched |= matches!(status, DataReaderStatus::SubscriptionMatched { .. });
+    }
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  for seq in 5..8 {
+    writer.write(Ping { seq }, None).unwrap();
+  }
+
+  let mut new_samples = Vec::new();
+  let mut all_samples = Vec::new();
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while new_samples.len() < 3 || all_samples.len() < 8 {
+    assert!(
+      Instant::now() < deadline,
+      "samples not received within 5 seconds: {new_samples:?}, {all_samples:?}"
+    );
+    while let Ok(Some(sample)) = from_now.take_next_sample() {
+      new_samples.push(sample.into_value().seq);
+    }
+    while let Ok(Some(sample)) = all_available.take_next_sample() {
+      all_samples.push(sample.into_value().seq);
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+
+  // The history may still be arriving to the other DataReader.
+  std::thread::sleep(Duration::from_millis(500));
+  while let Ok(Some(sample)) = from_now.take_next_sample() {
+    new_samples.push(sample.into_value().seq);
+  }
+  assert_eq!(new_samples, vec![5, 6, 7]);
+  assert_eq!(all_samples, (0..8).collect::<Vec<u32>>());
+}
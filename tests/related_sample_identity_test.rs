@@ -0,0 +1,141 @@
+//! A reply carries the sample identity of its request as related sample
+//! identity, so the requester can match replies to its requests.
+use std::time::{Duration, Instant};
+
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, TopicKind, WriteOptionsBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Request {
+  x: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Reply {
+  x_squared: i32,
+}
+
+#[test]
+fn reply_is_matched_to_request() {
+  let requester = DomainParticipantBuilder::new(72)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let replier = DomainParticipantBuilder::new(72)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+  let topics = |participant: &rustdds::DomainParticipant| {
+    let request_topic = participant
+      .create_topic(
+        "related_identity_test_request".to_string(),
+        "Request".to_string(),
+        &qos,
+        TopicKind::NoKey,
+      )
+      .unwrap();
+    let reply_topic = participant
+      .create_topic(
+        "related_identity_test_reply".to_string(),
+        "Reply".to_string(),
+        &qos,
+        TopicKind::NoKey,
+      )
+      .unwrap();
+    (request_topic, reply_topic)
+  };
+
+  let (request_topic, reply_topic) = topics(&requester);
+  let request_writer = requester
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Request>(&request_topic, None)
+    .unwrap();
+  let mut reply_reader = requester
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Reply>(&reply_topic, None)
+    .unwrap();
+
+  let (request_topic, reply_topic) = topics(&replier);
+  let mut request_reader = replier
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Request>(&request_topic, None)
+    .unwrap();
+  let reply_writer = replier
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Reply>(&reply_topic, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while request_writer.get_matched_subscriptions().is_empty()
+    || reply_writer.get_matched_subscriptions().is_empty()
+  {
+    assert!(
+      Instant::now() < deadline,
+      "endpoints not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+
+  let request_ids: Vec<_> = [3, 4]
+    .into_iter()
+    .map(|x| {
+      request_writer
+        .write_with_options(Request { x }, WriteOptionsBuilder::new().build())
+        .unwrap()
+    })
+    .collect();
+
+  // Replier: answer each request, relating the reply to it.
+  let mut answered = 0;
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while answered < request_ids.len() {
+    assert!(
+      Instant::now() < deadline,
+      "requests not received within 5 seconds"
+    );
+    while let Ok(Some(sample)) = request_reader.take_next_sample() {
+      let request_id = sample.sample_info().sample_identity();
+      assert_eq!(sample.sample_info().related_sample_identity(), None);
+      let x = sample.value().x;
+      reply_writer
+        .write_with_options(
+          Reply { x_squared: x * x },
+          WriteOptionsBuilder::new()
+            .related_sample_identity(request_id)
+            .build(),
+        )
+        .unwrap();
+      answered += 1;
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+
+  // Requester: match the replies back to the requests.
+  let mut replies = Vec::new();
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while replies.len() < request_ids.len() {
+    assert!(
+      Instant::now() < deadline,
+      "replies not received within 5 seconds"
+    );
+    while let Ok(Some(sample)) = reply_reader.take_next_sample() {
+      let related = sample.sample_info().related_sample_identity();
+      replies.push((related, sample.into_value().x_squared));
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+  assert_eq!(
+    replies,
+    vec![(Some(request_ids[0]), 9), (Some(request_ids[1]), 16)]
+  );
+}
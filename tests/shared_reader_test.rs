@@ -0,0 +1,116 @@
+//! DataReaders sharing one RTPS Reader each see every sample, and take from
+//! one of them does not remove samples from the other.
+use std::time::{Duration, Instant};
+
+use rustdds::{policy, DomainParticipantBuilder, QosPolicyBuilder, RTPSEntity, TopicKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn readers_sharing_a_reader_see_the_full_stream() {
+  let participant_a = DomainParticipantBuilder::new(62)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let participant_b = DomainParticipantBuilder::new(62)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let topic_a = participant_a
+    .create_topic(
+      "shared_reader_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "shared_reader_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+  let mut shared = reader.share().unwrap();
+  assert_eq!(reader.guid(), shared.guid());
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while writer.get_matched_subscriptions().is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "reader not matched within 5 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(50));
+  }
+  for seq in 0..10 {
+    writer.write(Ping { seq }, None).unwrap();
+  }
+
+  let mut taken = Vec::new();
+  let mut read = Vec::new();
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while taken.len() < 10 || read.len() < 10 {
+    assert!(
+      Instant::now() < deadline,
+      "samples not received within 5 seconds: taken {taken:?}, read {read:?}"
+    );
+    while let Ok(Some(sample)) = reader.take_next_sample() {
+      taken.push(sample.into_value().seq);
+    }
+    read = shared
+      .read(100, rustdds::ReadCondition::any())
+      .unwrap()
+      .into_iter()
+      .map(|sample| sample.value().seq)
+      .collect();
+    std::thread::sleep(Duration::from_millis(10));
+  }
+  let all: Vec<u32> = (0..10).collect();
+  assert_eq!(taken, all);
+  assert_eq!(read, all);
+
+  // Taking from the shared DataReader does not depend on the other one.
+  let taken_from_shared: Vec<u32> = shared
+    .take(100, rustdds::ReadCondition::any())
+    .unwrap()
+    .into_iter()
+    .map(|sample| sample.value().seq)
+    .collect();
+  assert_eq!(taken_from_shared, all);
+
+  // The Reader keeps working after the original DataReader is dropped.
+  drop(reader);
+  writer.write(Ping { seq: 10 }, None).unwrap();
+  let deadline = Instant::now() + Duration::from_secs(5);
+  loop {
+    assert!(Instant::now() < deadline, "sample not received after drop");
+    if let Ok(Some(sample)) = shared.take_next_sample() {
+      assert_eq!(sample.into_value().seq, 10);
+      break;
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+}
@@ -0,0 +1,50 @@
+//! A dropped DomainParticipant disposes itself over discovery, so remote
+//! participants lose it at once instead of waiting for its lease to expire.
+use std::time::{Duration, Instant};
+
+use rustdds::{DomainParticipant, DomainParticipantStatusEvent, LostReason, RTPSEntity, StatusEvented};
+
+#[test]
+fn dropped_participant_is_lost_at_once() {
+  let participant_a = DomainParticipant::new(61).unwrap();
+  let participant_b = DomainParticipant::new(61).unwrap();
+  let a_prefix = participant_a.guid().prefix;
+  let b_status = participant_b.status_listener();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  loop {
+    assert!(
+      Instant::now() < deadline,
+      "participant A was not discovered within 5 seconds"
+    );
+    match b_status.try_recv_status() {
+      Some(DomainParticipantStatusEvent::ParticipantDiscovered { dpd })
+        if dpd.guid.prefix == a_prefix =>
+      {
+        break
+      }
+      Some(_) => {}
+      None => std::thread::sleep(Duration::from_millis(10)),
+    }
+  }
+
+  drop(participant_a);
+  let dropped_at = Instant::now();
+
+  // The lease of A lasts tens of seconds, so only its disposal can make B
+  // lose it this fast.
+  loop {
+    assert!(
+      dropped_at.elapsed() < Duration::from_millis(100),
+      "participant A was not lost within 100 ms of being dropped"
+    );
+    match b_status.try_recv_status() {
+      Some(DomainParticipantStatusEvent::ParticipantLost { id, reason }) if id == a_prefix => {
+        assert!(matches!(reason, LostReason::Disposed), "{reason:?}");
+        break;
+      }
+      Some(_) => {}
+      None => std::thread::sleep(Duration::from_millis(1)),
+    }
+  }
+}
@@ -0,0 +1,84 @@
+//! Two participants using the TCP transport, one of them knowing the other
+//! only as its initial peer, exchange a sample.
+#![cfg(feature = "tcp")]
+use std::{
+  net::{IpAddr, SocketAddr},
+  time::{Duration, Instant},
+};
+
+use rustdds::{
+  policy, DomainParticipant, DomainParticipantBuilder, QosPolicyBuilder, TcpTransport, TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+const DOMAIN_ID: u16 = 54;
+const LISTEN_PORT: u16 = 17454;
+
+fn tcp_participant(tcp_transport: TcpTransport) -> DomainParticipant {
+  DomainParticipantBuilder::new(DOMAIN_ID)
+    .tcp_transport(tcp_transport)
+    .advertised_addresses(["127.0.0.1".parse::<IpAddr>().unwrap()])
+    .build()
+    .unwrap()
+}
+
+#[test]
+fn sample_is_exchanged_over_tcp() {
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_secs(1),
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  // A has no initial peers, but is found by B.
+  let participant_a = tcp_participant(TcpTransport::new([]).with_listen_port(LISTEN_PORT));
+  let participant_b = tcp_participant(TcpTransport::new([SocketAddr::from((
+    [127, 0, 0, 1],
+    LISTEN_PORT,
+  ))]));
+
+  let topic_a = participant_a
+    .create_topic(
+      "tcp_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_a
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+
+  let topic_b = participant_b
+    .create_topic(
+      "tcp_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_b
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(10);
+  while Instant::now() < deadline {
+    writer.write(Ping { seq: 7 }, None).unwrap();
+    if let Ok(Some(sample)) = reader.take_next_sample() {
+      assert_eq!(sample.into_value().seq, 7);
+      return;
+    }
+    std::thread::sleep(Duration::from_millis(200));
+  }
+  panic!("no sample arrived over TCP within 10 seconds");
+}
@@ -0,0 +1,76 @@
+//! A remote publisher makes its topic show up once in the participant status
+//! stream, with name, type name and QoS.
+use std::time::{Duration, Instant};
+
+use rustdds::{
+  policy, DomainParticipantBuilder, DomainParticipantStatusEvent, QosPolicyBuilder, StatusEvented,
+  TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct ShapeType {
+  x: i32,
+}
+
+#[test]
+fn remote_topic_is_detected_once() {
+  let participant_a = DomainParticipantBuilder::new(65)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let a_status = participant_a.status_listener();
+  let participant_b = DomainParticipantBuilder::new(65)
+    .in_process_discovery(true)
+    .build()
+    .unwrap();
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_millis(100),
+    })
+    .build();
+  let topic = participant_b
+    .create_topic(
+      "Square".to_string(),
+      "ShapeType".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let _writer = participant_b
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<ShapeType>(&topic, None)
+    .unwrap();
+
+  let mut detected = Vec::new();
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while detected.is_empty() {
+    assert!(
+      Instant::now() < deadline,
+      "topic not detected within 5 seconds"
+    );
+    while let Some(event) = a_status.try_recv_status() {
+      if let DomainParticipantStatusEvent::TopicDetected {
+        name,
+        type_name,
+        qos,
+      } = event
+      {
+        if name == "Square" {
+          detected.push((type_name, qos.reliability()));
+        }
+      }
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+
+  // Later announcements of the same topic are not reported again.
+  std::thread::sleep(Duration::from_millis(1500));
+  while let Some(event) = a_status.try_recv_status() {
+    if let DomainParticipantStatusEvent::TopicDetected { name, .. } = event {
+      assert_ne!(name, "Square", "topic detected twice");
+    }
+  }
+  assert_eq!(detected, vec![("ShapeType".to_string(), qos.reliability())]);
+}
@@ -0,0 +1,71 @@
+//! A participant learns the QoS another participant has declared for a topic,
+//! even though the only DataWriter on the topic uses other QoS.
+use std::time::{Duration, Instant};
+
+use rustdds::{
+  policy, qos::HasQoSPolicy, DomainParticipant, QosPolicyBuilder, TopicDescription, TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  seq: u32,
+}
+
+#[test]
+fn remote_topic_qos_is_discovered() {
+  let topic_qos = QosPolicyBuilder::new()
+    .durability(policy::Durability::TransientLocal)
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_millis(100),
+    })
+    .build();
+  let writer_qos = QosPolicyBuilder::new()
+    .durability(policy::Durability::Volatile)
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_millis(100),
+    })
+    .build();
+
+  let participant_a = DomainParticipant::new(55).unwrap();
+  let participant_b = DomainParticipant::new(55).unwrap();
+
+  let topic = participant_a
+    .create_topic(
+      "topic_qos_test_topic".to_string(),
+      "Ping".to_string(),
+      &topic_qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let _writer = participant_a
+    .create_publisher(&writer_qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic, Some(writer_qos.clone()))
+    .unwrap();
+
+  // The DataWriter may be discovered before the topic, so wait for the topic
+  // QoS.
+  let deadline = Instant::now() + Duration::from_secs(10);
+  while participant_b
+    .topic_qos("topic_qos_test_topic")
+    .and_then(|qos| qos.durability())
+    != Some(policy::Durability::TransientLocal)
+  {
+    assert!(
+      Instant::now() < deadline,
+      "topic QoS was not discovered within 10 seconds"
+    );
+    std::thread::sleep(Duration::from_millis(100));
+  }
+
+  let found = participant_b
+    .find_topic("topic_qos_test_topic", Duration::from_secs(1))
+    .unwrap()
+    .expect("topic not found");
+  assert_eq!(found.get_type().name(), "Ping");
+  assert_eq!(
+    found.qos().durability(),
+    Some(policy::Durability::TransientLocal)
+  );
+}
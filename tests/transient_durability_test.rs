@@ -0,0 +1,121 @@
+//! A Transient DataReader receives the samples of a Transient DataWriter from
+//! the durability store, after the DataWriter and its DomainParticipant are
+//! gone.
+use std::{
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+use rustdds::{
+  policy, DomainParticipantBuilder, InMemoryDurabilityStore, QosPolicyBuilder, RTPSEntity,
+  Timestamp, TopicKind,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Ping {
+  text: String,
+}
+
+#[test]
+fn transient_reader_receives_samples_of_writer_that_is_gone() {
+  let store = Arc::new(InMemoryDurabilityStore::new());
+  let qos = QosPolicyBuilder::new()
+    .reliability(policy::Reliability::Reliable {
+      max_blocking_time: rustdds::Duration::from_millis(100),
+    })
+    .durability(policy::Durability::Transient)
+    .durability_service(policy::DurabilityService {
+      history: policy::History::KeepLast { depth: 2 },
+      ..policy::DurabilityService::default()
+    })
+    .history(policy::History::KeepAll)
+    .build();
+
+  let participant_a = DomainParticipantBuilder::new(61)
+    .durability_store(store.clone())
+    .build()
+    .unwrap();
+  let topic_a = participant_a
+    .create_topic(
+      "transient_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let writer = participant_a
+    .create_publisher(&qos)
+    .unwrap()
+    .create_datawriter_no_key_cdr::<Ping>(&topic_a, None)
+    .unwrap();
+  let writer_guid = writer.guid();
+
+  // Written with source timestamps in the past, so that replaying them could
+  // not restamp them unnoticed.
+  let written_at = Timestamp::now();
+  let source_timestamps: Vec<Timestamp> = (0..3)
+    .map(|i| written_at - rustdds::Duration::from_secs(10 - i))
+    .collect();
+  for (i, ts) in source_timestamps.iter().enumerate() {
+    writer
+      .write(
+        Ping {
+          text: format!("ping {i}"),
+        },
+        Some(*ts),
+      )
+      .unwrap();
+  }
+  drop(writer);
+  drop(participant_a);
+
+  let participant_b = DomainParticipantBuilder::new(61)
+    .durability_store(store)
+    .build()
+    .unwrap();
+  let topic_b = participant_b
+    .create_topic(
+      "transient_test_topic".to_string(),
+      "Ping".to_string(),
+      &qos,
+      TopicKind::NoKey,
+    )
+    .unwrap();
+  let mut reader = participant_b
+    .create_subscriber(&qos)
+    .unwrap()
+    .create_datareader_no_key_cdr::<Ping>(&topic_b, None)
+    .unwrap();
+
+  let mut received = vec![];
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while received.len() < 2 && Instant::now() < deadline {
+    while let Ok(Some(sample)) = reader.take_next_sample() {
+      let info = sample.sample_info().clone();
+      received.push((
+        sample.into_value().text,
+        info.source_timestamp(),
+        info.writer_guid(),
+      ));
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+
+  // The store keeps the last two, with their original timestamps and writer.
+  assert_eq!(
+    received,
+    vec![
+      (
+        "ping 1".to_string(),
+        Some(source_timestamps[1]),
+        writer_guid
+      ),
+      (
+        "ping 2".to_string(),
+        Some(source_timestamps[2]),
+        writer_guid
+      ),
+    ]
+  );
+}